@@ -0,0 +1,14 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use starts_fetcher::config::Config;
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(contents) = std::str::from_utf8(data) {
+        if let Ok(config) = Config::from_toml_str(contents) {
+            // Exported settings must stay importable
+            let exported = config.export().expect("export should succeed");
+            Config::import(&exported, Some(&config)).expect("exported config should import");
+        }
+    }
+});
@@ -0,0 +1,26 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use serde_json::json;
+use starts_fetcher::api::stars::StarredRepo;
+use starts_fetcher::utils::filter::parse_filter;
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(input) = std::str::from_utf8(data) {
+        if let Ok(filter) = parse_filter(input) {
+            // Any accepted filter must evaluate without panicking, even on sparse repositories
+            let sparse: StarredRepo = serde_json::from_value(json!({
+                "id": 1, "name": "", "full_name": "", "owner": { "login": "" }, "html_url": ""
+            }))
+            .unwrap();
+            let full: StarredRepo = serde_json::from_value(json!({
+                "id": 724712, "name": "rust", "full_name": "rust-lang/rust", "owner": { "login": "rust-lang" },
+                "description": "Empowering everyone to build reliable and efficient software.", "language": "Rust",
+                "html_url": "https://github.com/rust-lang/rust", "stargazers_count": 97412, "topics": ["rust"]
+            }))
+            .unwrap();
+            filter.matches(&sparse);
+            filter.matches(&full);
+        }
+    }
+});
@@ -0,0 +1,15 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use starts_fetcher::utils::parse::{parse_repo_spec, RepoRef};
+
+fuzz_target!(|data: &[u8]| {
+    if let Ok(input) = std::str::from_utf8(data) {
+        if let Ok(repo) = parse_repo_spec(input) {
+            // Anything accepted must round-trip through its owner/repo form
+            let reparsed = parse_repo_spec(&repo.full_name()).expect("full_name should parse");
+            assert_eq!(repo, reparsed);
+            assert!(RepoRef::new(&repo.owner, &repo.repo).is_ok());
+        }
+    }
+});
@@ -0,0 +1,348 @@
+//!
+//! Conditional request cache
+//! JSON responses that came with an `ETag` are kept on disk by URL. The next request for the same
+//! URL sends `If-None-Match`, and a 304 is answered from the kept copy; GitHub doesn't count such
+//! requests against the rate limit. Entries are keyed by a hash of the token, the media type, the
+//! URL and any `Accept-Language`, so a different token never sees another account's responses and
+//! a description kept in one language isn't shown for another.
+//!
+
+use crate::store::{EntryInfo, Store};
+use reqwest::header::{HeaderMap, HeaderValue, ACCEPT, ACCEPT_LANGUAGE, CONTENT_TYPE, ETAG, LINK};
+use reqwest::{Method, Request, Response, StatusCode};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::error::Error;
+use std::path::Path;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+
+/// A response as kept on disk: what a caller reads from it, but not the rate limit headers
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct CachedResponse {
+    /// URL the response was fetched from, for `cache stats`
+    #[serde(default)]
+    pub url: String,
+    pub etag: String,
+    pub content_type: Option<String>,
+    /// Pagination links, so a cached page still leads to the next one
+    pub link: Option<String>,
+    pub body: String,
+}
+
+impl CachedResponse {
+    // Keep a 200 response with an ETag, None for anything else
+    fn from_parts(url: &str, status: StatusCode, headers: &HeaderMap, body: &[u8]) -> Option<Self> {
+        if status != StatusCode::OK {
+            return None;
+        }
+        let header = |name| headers.get(name).and_then(|value: &HeaderValue| value.to_str().ok()).map(String::from);
+
+        Some(CachedResponse {
+            url: url.to_string(),
+            etag: header(ETAG)?,
+            content_type: header(CONTENT_TYPE),
+            link: header(LINK),
+            body: String::from_utf8(body.to_vec()).ok()?,
+        })
+    }
+
+    // A 200 response with the kept body, carrying the headers of the 304 that confirmed it
+    fn into_response(self, mut headers: HeaderMap) -> Response {
+        for (name, value) in [(CONTENT_TYPE, self.content_type), (LINK, self.link)] {
+            match value.and_then(|value| HeaderValue::from_str(&value).ok()) {
+                Some(value) => headers.insert(name, value),
+                None => headers.remove(name),
+            };
+        }
+        headers.insert(ETAG, HeaderValue::from_str(&self.etag).unwrap_or(HeaderValue::from_static("")));
+
+        rebuild(StatusCode::OK, headers, self.body)
+    }
+}
+
+// A response with the given parts, for bodies already read
+fn rebuild(status: StatusCode, headers: HeaderMap, body: impl Into<reqwest::Body>) -> Response {
+    let mut response = http::Response::new(body.into());
+    *response.status_mut() = status;
+    *response.headers_mut() = headers;
+    Response::from(response)
+}
+
+/// Responses kept for conditional requests. Cloning it is cheap and shares the store.
+#[derive(Clone)]
+pub struct ResponseCache {
+    store: Arc<Store>,
+}
+
+impl ResponseCache {
+    /// A cache in `store`, which should hold nothing else
+    pub fn new(store: Store) -> Self {
+        Self { store: Arc::new(store) }
+    }
+
+    /// The cache in the user's data directory, e.g. `~/.local/share/stars_fetcher/responses`
+    pub fn open_default() -> Result<Self, Box<dyn Error>> {
+        Ok(Self::new(Store::open(Store::open_default()?.root().join("responses"))))
+    }
+
+    /// Directory the responses are kept in
+    pub fn root(&self) -> &Path {
+        self.store.root()
+    }
+
+    /// The kept responses, oldest first
+    pub fn entries(&self) -> Result<Vec<EntryInfo>, Box<dyn Error>> {
+        let mut entries = self.store.entries()?;
+        entries.sort_by_key(|entry| entry.modified);
+        Ok(entries)
+    }
+
+    /// URL of a kept response, None if it can't be read
+    pub fn url(&self, entry: &EntryInfo) -> Option<String> {
+        self.get(&entry.name).map(|cached| cached.url).filter(|url| !url.is_empty())
+    }
+
+    /// Put back responses backed up before a store migration rewrote them, returning their names
+    pub fn rollback(&self) -> Result<Vec<String>, Box<dyn Error>> {
+        self.store.rollback()
+    }
+
+    /// Forget every kept response, returning how many there were
+    pub fn clear(&self) -> Result<usize, Box<dyn Error>> {
+        self.prune(Duration::ZERO, SystemTime::now() + Duration::from_secs(1))
+    }
+
+    /// Forget responses fetched more than `max_age` before `now`, returning how many there were.
+    /// Revalidating a response doesn't refetch it, so this is the age of its last full fetch.
+    pub fn prune(&self, max_age: Duration, now: SystemTime) -> Result<usize, Box<dyn Error>> {
+        let mut pruned = 0;
+        for entry in self.store.entries()? {
+            if now.duration_since(entry.modified).unwrap_or_default() > max_age {
+                self.store.remove(&entry.name)?;
+                pruned += 1;
+            }
+        }
+        Ok(pruned)
+    }
+
+    // Key of a request whose response may be kept: a GET asking for JSON, not raw files or binaries
+    pub(crate) fn key(token: &str, request: &Request) -> Option<String> {
+        let accept = request.headers().get(ACCEPT)?.to_str().ok()?;
+        if request.method() != Method::GET || !accept.ends_with("json") {
+            return None;
+        }
+
+        let mut key = format!("{}\n{}\n{}", token, accept, request.url());
+        // Only added when set, so entries kept before languages could be asked for still match
+        if let Some(language) = request.headers().get(ACCEPT_LANGUAGE).and_then(|language| language.to_str().ok()) {
+            key = format!("{}\n{}", key, language);
+        }
+
+        let hash = Sha256::digest(key.as_bytes());
+        Some(format!("{:x}", hash))
+    }
+
+    // The kept response for `key`. A broken entry is treated like a missing one.
+    pub(crate) fn get(&self, key: &str) -> Option<CachedResponse> {
+        self.store.load(key).ok().flatten()
+    }
+
+    // Answer a response to a request sent with `cached`'s ETag: a 304 from the kept copy, a new
+    // 200 with an ETag kept for next time and anything else as it is
+    pub(crate) async fn resolve(
+        &self,
+        key: &str,
+        cached: Option<CachedResponse>,
+        response: Response,
+    ) -> Result<Response, reqwest::Error> {
+        let status = response.status();
+        if let (StatusCode::NOT_MODIFIED, Some(cached)) = (status, cached) {
+            return Ok(cached.into_response(response.headers().clone()));
+        }
+        if status != StatusCode::OK || !response.headers().contains_key(ETAG) {
+            return Ok(response);
+        }
+
+        let (url, headers) = (response.url().to_string(), response.headers().clone());
+        let body = response.bytes().await?;
+        if let Some(entry) = CachedResponse::from_parts(&url, status, &headers, &body) {
+            // A response that couldn't be kept is still returned
+            let _ = self.store.save(key, &entry);
+        }
+
+        Ok(rebuild(status, headers, body))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::client::{GitHubClient, MediaType};
+    use crate::api::metrics;
+    use crate::api::repos::Repo;
+    use mockito::{Matcher, Server};
+    use tempfile::tempdir;
+
+    fn request(method: Method, accept: MediaType) -> Request {
+        let client = reqwest::Client::new();
+        client
+            .request(method, "https://api.github.com/user/starred")
+            .header(ACCEPT, accept.as_str())
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_key() {
+        let json = ResponseCache::key("token", &request(Method::GET, MediaType::Json)).unwrap();
+
+        assert_ne!(Some(json.clone()), ResponseCache::key("other", &request(Method::GET, MediaType::Json)));
+        assert_ne!(Some(json.clone()), ResponseCache::key("token", &request(Method::GET, MediaType::StarJson)));
+        assert_eq!(ResponseCache::key("token", &request(Method::GET, MediaType::Raw)), None);
+        assert_eq!(ResponseCache::key("token", &request(Method::PUT, MediaType::Json)), None);
+
+        let mut localized = request(Method::GET, MediaType::Json);
+        localized.headers_mut().insert(ACCEPT_LANGUAGE, HeaderValue::from_static("zh-CN"));
+        assert_ne!(Some(json), ResponseCache::key("token", &localized));
+    }
+
+    #[tokio::test]
+    async fn test_not_modified_served_from_cache() {
+        let mut server = Server::new_async().await;
+        let dir = tempdir().unwrap();
+        let body = r#"{"id": 1, "name": "repo", "full_name": "owner/repo", "owner": {"login": "owner"}, "stargazers_count": 5}"#;
+
+        let first = server
+            .mock("GET", "/repos/owner/repo")
+            .match_header("if-none-match", Matcher::Missing)
+            .with_status(200)
+            .with_header("etag", r#""abc""#)
+            .with_header("content-type", "application/json")
+            .with_body(body)
+            .expect(1)
+            .create_async()
+            .await;
+        let revalidated = server
+            .mock("GET", "/repos/owner/repo")
+            .match_header("if-none-match", r#""abc""#)
+            .with_status(304)
+            .expect(2)
+            .create_async()
+            .await;
+
+        let client = GitHubClient::new(server.url().to_string(), "test_token".to_string())
+            .await
+            .with_cache(ResponseCache::new(Store::open(dir.path())));
+
+        assert_eq!(client.get_repo("owner", "repo").await.unwrap().stars, 5);
+        let before = metrics::snapshot();
+        assert_eq!(client.get_repo("owner", "repo").await.unwrap().stars, 5);
+        assert_eq!(client.get_repo("owner", "repo").await.unwrap().stars, 5);
+
+        // Other tests may count requests at the same time, so only growth is checked
+        assert!(metrics::snapshot().cache_hits >= before.cache_hits + 2);
+        first.assert_async().await;
+        revalidated.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_cached_pages_keep_their_links() {
+        let mut server = Server::new_async().await;
+        let dir = tempdir().unwrap();
+        let link = format!(r#"<{}/user/starred?page=2>; rel="next""#, server.url());
+
+        server
+            .mock("GET", "/user/starred?per_page=100")
+            .match_header("if-none-match", Matcher::Missing)
+            .with_status(200)
+            .with_header("etag", r#"W/"page1""#)
+            .with_header("link", &link)
+            .with_body(r#"[{"id": 1, "name": "one", "full_name": "owner/one", "owner": {"login": "owner"}, "description": null, "html_url": "https://github.com/owner/one", "stargazers_count": 1}]"#)
+            .create_async()
+            .await;
+        server
+            .mock("GET", "/user/starred?per_page=100")
+            .match_header("if-none-match", r#"W/"page1""#)
+            .with_status(304)
+            .create_async()
+            .await;
+        server
+            .mock("GET", "/user/starred?page=2")
+            .with_status(200)
+            .with_body(r#"[{"id": 2, "name": "two", "full_name": "owner/two", "owner": {"login": "owner"}, "description": null, "html_url": "https://github.com/owner/two", "stargazers_count": 2}]"#)
+            .expect(2)
+            .create_async()
+            .await;
+
+        let client = GitHubClient::new(server.url().to_string(), "test_token".to_string())
+            .await
+            .with_cache(ResponseCache::new(Store::open(dir.path())));
+
+        for _ in 0..2 {
+            let repos = client.list_repos().await.unwrap();
+            assert_eq!(repos.iter().map(|repo| repo.name.as_str()).collect::<Vec<_>>(), ["one", "two"]);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_changed_response_replaces_entry() {
+        let mut server = Server::new_async().await;
+        let dir = tempdir().unwrap();
+        let cache = ResponseCache::new(Store::open(dir.path()));
+
+        server
+            .mock("GET", "/repos/owner/repo")
+            .with_status(200)
+            .with_header("etag", r#""v2""#)
+            .with_body(r#"{"id": 1, "name": "repo", "full_name": "owner/repo", "owner": {"login": "owner"}, "stargazers_count": 6}"#)
+            .create_async()
+            .await;
+
+        let client = GitHubClient::new(server.url().to_string(), "test_token".to_string())
+            .await
+            .with_cache(cache.clone());
+        let key = ResponseCache::key(&client.token, &client.request(Method::GET, &format!("{}/repos/owner/repo", client.api_url)).build().unwrap()).unwrap();
+        cache.store.save(&key, &CachedResponse {
+            url: String::new(),
+            etag: r#""v1""#.to_string(),
+            content_type: None,
+            link: None,
+            body: String::from("{}"),
+        }).unwrap();
+
+        assert_eq!(client.get_repo("owner", "repo").await.unwrap().stars, 6);
+        let entry = cache.get(&key).unwrap();
+        assert_eq!(entry.etag, r#""v2""#);
+        assert_eq!(entry.url, format!("{}/repos/owner/repo", server.url()));
+    }
+
+    #[test]
+    fn test_prune_and_clear() {
+        let dir = tempdir().unwrap();
+        let cache = ResponseCache::new(Store::open(dir.path()));
+        let entry = |url: &str| CachedResponse {
+            url: url.to_string(),
+            etag: r#""v1""#.to_string(),
+            content_type: None,
+            link: None,
+            body: String::from("[]"),
+        };
+        cache.store.save("first", &entry("https://api.github.com/user/starred")).unwrap();
+        cache.store.save("second", &entry("")).unwrap();
+
+        let entries = cache.entries().unwrap();
+        assert_eq!(entries.len(), 2);
+        let url = |name: &str| cache.url(entries.iter().find(|entry| entry.name == name).unwrap());
+        assert_eq!(url("first").as_deref(), Some("https://api.github.com/user/starred"));
+        assert_eq!(url("second"), None);
+
+        let now = SystemTime::now();
+        assert_eq!(cache.prune(Duration::from_secs(3600), now).unwrap(), 0);
+        assert_eq!(cache.prune(Duration::from_secs(3600), now + Duration::from_secs(7200)).unwrap(), 2);
+
+        cache.store.save("third", &entry("")).unwrap();
+        assert_eq!(cache.clear().unwrap(), 1);
+        assert!(cache.entries().unwrap().is_empty());
+    }
+}
@@ -0,0 +1,212 @@
+//!
+//! Detection of what the current token is allowed to do
+//! This module probes `/user` once and derives which operations are available, so commands
+//! can be flagged up front instead of failing mid-operation.
+//!
+
+use crate::error::StarsError;
+use crate::api::client::GitHubClient;
+use reqwest::header::HeaderMap;
+use reqwest::{Method, StatusCode};
+
+/// The kind of token in use, which decides how its permissions can be inspected
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TokenKind {
+    /// No token at all
+    Anonymous,
+    /// Classic personal access or OAuth token, which reports its scopes
+    Classic,
+    /// Fine-grained personal access token, whose permissions can't be listed
+    FineGrained,
+    /// GitHub App installation token, which acts as the app rather than a user
+    App,
+}
+
+impl TokenKind {
+    /// Guess the kind of a token from its prefix
+    pub fn from_token(token: &str) -> Self {
+        if token.is_empty() {
+            TokenKind::Anonymous
+        } else if token.starts_with("github_pat_") {
+            TokenKind::FineGrained
+        } else if token.starts_with("ghs_") {
+            TokenKind::App
+        } else {
+            TokenKind::Classic
+        }
+    }
+}
+
+/// Whether an operation can be performed with the current token
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Availability {
+    Available,
+    /// The operation will fail, with a message explaining how to fix the token
+    Unavailable(String),
+    /// The token's permissions can't be inspected, with a hint in case the operation fails
+    Unknown(String),
+}
+
+/// What the current token was found to be allowed to do
+#[derive(Debug, Clone)]
+pub struct Capabilities {
+    pub kind: TokenKind,
+    /// Scopes reported by the server, only known for classic tokens
+    pub scopes: Option<Vec<String>>,
+    /// Set when the server rejected the token
+    pub rejected: bool,
+}
+
+impl Capabilities {
+    /// Whether the starred repositories of the authenticated user can be listed
+    pub fn read_stars(&self) -> Availability {
+        self.common().unwrap_or(Availability::Available)
+    }
+
+    /// Whether repositories can be starred and unstarred
+    pub fn write_stars(&self) -> Availability {
+        if let Some(availability) = self.common() {
+            return availability;
+        }
+
+        match (&self.kind, &self.scopes) {
+            (TokenKind::FineGrained, _) => Availability::Unknown(
+                "Fine-grained tokens need the \"Starring\" user permission set to read and write".to_string()
+            ),
+            (_, Some(scopes)) if !scopes.iter().any(|s| s == "repo" || s == "public_repo") => Availability::Unavailable(
+                "Starring needs the public_repo scope (or repo for private repositories), \
+                 regenerate the token at https://github.com/settings/tokens".to_string()
+            ),
+            _ => Availability::Available,
+        }
+    }
+
+    // Conditions that rule out every operation needing a user
+    fn common(&self) -> Option<Availability> {
+        if self.rejected {
+            return Some(Availability::Unavailable(
+                "The token was rejected by GitHub, check GITHUB_TOKEN or the token in the config file".to_string()
+            ));
+        }
+
+        match self.kind {
+            TokenKind::Anonymous => Some(Availability::Unavailable(
+                "This command needs a token, set GITHUB_TOKEN or add one to the config file".to_string()
+            )),
+            TokenKind::App => Some(Availability::Unavailable(
+                "GitHub App installation tokens act as the app and have no stars, use a personal access token".to_string()
+            )),
+            _ => None,
+        }
+    }
+}
+
+/// Scopes a response says the token was granted, only sent for classic tokens
+pub(crate) fn granted_scopes(headers: &HeaderMap) -> Option<Vec<String>> {
+    headers
+        .get("x-oauth-scopes")
+        .and_then(|value| value.to_str().ok())
+        .map(|value| {
+            value
+                .split(',')
+                .map(|scope| scope.trim().to_string())
+                .filter(|scope| !scope.is_empty())
+                .collect()
+        })
+}
+
+#[allow(async_fn_in_trait)]
+pub trait TokenCapabilities {
+    async fn capabilities(&self) -> Result<Capabilities, StarsError>;
+}
+
+impl TokenCapabilities for GitHubClient {
+    async fn capabilities(&self) -> Result<Capabilities, StarsError> {
+        let kind = TokenKind::from_token(&self.token);
+        if kind == TokenKind::Anonymous {
+            return Ok(Capabilities { kind, scopes: None, rejected: false });
+        }
+
+        let url = format!("{}/user", self.api_url);
+        let response = self.send(self.request(Method::GET, &url)).await?;
+
+        match response.status() {
+            StatusCode::UNAUTHORIZED => Ok(Capabilities { kind, scopes: None, rejected: true }),
+            status if status.is_success() => {
+                let scopes = granted_scopes(response.headers());
+                Ok(Capabilities { kind, scopes, rejected: false })
+            }
+            _ => Err(StarsError::from_response(response).await),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mockito::Server;
+
+    async fn probe(status: usize, scopes: Option<&str>, token: &str) -> Capabilities {
+        let mut server = Server::new_async().await;
+
+        let mut mock = server.mock("GET", "/user").with_status(status);
+        if let Some(scopes) = scopes {
+            mock = mock.with_header("x-oauth-scopes", scopes);
+        }
+        let _mock = mock.create_async().await;
+
+        let client = GitHubClient::new(server.url().to_string(), token.to_string()).await;
+        client.capabilities().await.unwrap()
+    }
+
+    #[test]
+    fn test_token_kind_from_prefix() {
+        assert_eq!(TokenKind::from_token(""), TokenKind::Anonymous);
+        assert_eq!(TokenKind::from_token("ghp_abc"), TokenKind::Classic);
+        assert_eq!(TokenKind::from_token("github_pat_abc"), TokenKind::FineGrained);
+        assert_eq!(TokenKind::from_token("ghs_abc"), TokenKind::App);
+    }
+
+    #[tokio::test]
+    async fn test_classic_token_with_public_repo() {
+        let capabilities = probe(200, Some("public_repo, read:user"), "ghp_test").await;
+
+        assert_eq!(capabilities.scopes.as_deref(), Some(&["public_repo".to_string(), "read:user".to_string()][..]));
+        assert_eq!(capabilities.read_stars(), Availability::Available);
+        assert_eq!(capabilities.write_stars(), Availability::Available);
+    }
+
+    #[tokio::test]
+    async fn test_classic_token_without_scopes() {
+        let capabilities = probe(200, Some(""), "ghp_test").await;
+
+        assert_eq!(capabilities.read_stars(), Availability::Available);
+        assert!(matches!(capabilities.write_stars(), Availability::Unavailable(message) if message.contains("public_repo")));
+    }
+
+    #[tokio::test]
+    async fn test_fine_grained_token() {
+        let capabilities = probe(200, None, "github_pat_test").await;
+
+        assert!(capabilities.scopes.is_none());
+        assert!(matches!(capabilities.write_stars(), Availability::Unknown(_)));
+    }
+
+    #[tokio::test]
+    async fn test_rejected_token() {
+        let capabilities = probe(401, None, "ghp_bad").await;
+
+        assert!(capabilities.rejected);
+        assert!(matches!(capabilities.read_stars(), Availability::Unavailable(_)));
+        assert!(matches!(capabilities.write_stars(), Availability::Unavailable(_)));
+    }
+
+    #[tokio::test]
+    async fn test_anonymous_needs_no_request() {
+        let client = GitHubClient::new("http://127.0.0.1:1".to_string(), String::new()).await;
+        let capabilities = client.capabilities().await.unwrap();
+
+        assert_eq!(capabilities.kind, TokenKind::Anonymous);
+        assert!(matches!(capabilities.write_stars(), Availability::Unavailable(_)));
+    }
+}
@@ -0,0 +1,214 @@
+//!
+//! CI results for a commit
+//! GitHub reports CI in two places: check runs (GitHub Actions and apps) and the older commit
+//! statuses. This module reads both and folds them into one summary.
+//!
+
+use crate::error::StarsError;
+use std::fmt;
+use crate::api::client::GitHubClient;
+use reqwest::{Method, StatusCode};
+use serde::{Deserialize, Serialize};
+
+/// Conclusions that mean a check run failed
+const FAILED_CONCLUSIONS: &[&str] = &["failure", "timed_out", "action_required", "startup_failure"];
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct CheckRun {
+    pub name: String,
+    pub status: String,
+    pub conclusion: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct CheckRunsResponse {
+    check_runs: Vec<CheckRun>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct CommitStatus {
+    pub context: String,
+    pub state: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct CombinedStatusResponse {
+    statuses: Vec<CommitStatus>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CiState {
+    Passing,
+    Failing,
+    Pending,
+    /// No CI reported anything for the commit
+    Unknown,
+}
+
+impl fmt::Display for CiState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let state = match self {
+            CiState::Passing => "passing",
+            CiState::Failing => "failing",
+            CiState::Pending => "pending",
+            CiState::Unknown => "no CI",
+        };
+        write!(f, "{}", state)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CiSummary {
+    pub state: CiState,
+    /// Names of the failing check runs and status contexts
+    pub failing: Vec<String>,
+}
+
+/// Fold check runs and commit statuses into one result: any failure wins, then anything pending
+pub fn summarize(check_runs: &[CheckRun], statuses: &[CommitStatus]) -> CiSummary {
+    let mut failing = Vec::new();
+    let mut pending = false;
+
+    for run in check_runs {
+        match run.conclusion.as_deref() {
+            Some(conclusion) if FAILED_CONCLUSIONS.contains(&conclusion) => failing.push(run.name.clone()),
+            Some(_) => {}
+            None => pending = true,
+        }
+    }
+    for status in statuses {
+        match status.state.as_str() {
+            "failure" | "error" => failing.push(status.context.clone()),
+            "pending" => pending = true,
+            _ => {}
+        }
+    }
+
+    let state = if !failing.is_empty() {
+        CiState::Failing
+    } else if pending {
+        CiState::Pending
+    } else if check_runs.is_empty() && statuses.is_empty() {
+        CiState::Unknown
+    } else {
+        CiState::Passing
+    };
+
+    CiSummary { state, failing }
+}
+
+#[allow(async_fn_in_trait)]
+pub trait Checks {
+    /// Check runs for a commit, branch or tag; `HEAD` means the default branch
+    async fn check_runs(&self, owner: &str, repo: &str, git_ref: &str) -> Result<Vec<CheckRun>, StarsError>;
+    /// Commit statuses for a commit, branch or tag, latest per context
+    async fn commit_statuses(&self, owner: &str, repo: &str, git_ref: &str) -> Result<Vec<CommitStatus>, StarsError>;
+    /// CI summary for the latest commit on the default branch
+    async fn ci_summary(&self, owner: &str, repo: &str) -> Result<CiSummary, StarsError> {
+        let check_runs = self.check_runs(owner, repo, "HEAD").await?;
+        let statuses = self.commit_statuses(owner, repo, "HEAD").await?;
+        Ok(summarize(&check_runs, &statuses))
+    }
+}
+
+impl Checks for GitHubClient {
+    async fn check_runs(&self, owner: &str, repo: &str, git_ref: &str) -> Result<Vec<CheckRun>, StarsError> {
+        let url = format!("{}/repos/{}/{}/commits/{}/check-runs?per_page=100", self.api_url, owner, repo, git_ref);
+        let response = self.send(self.request(Method::GET, &url)).await?;
+
+        match response.status() {
+            StatusCode::OK => Ok(response.json::<CheckRunsResponse>().await?.check_runs),
+            StatusCode::NOT_FOUND => Err(StarsError::NotFound(format!("Repository {}/{} not found", owner, repo))),
+            _ => Err(StarsError::from_response(response).await),
+        }
+    }
+
+    async fn commit_statuses(&self, owner: &str, repo: &str, git_ref: &str) -> Result<Vec<CommitStatus>, StarsError> {
+        let url = format!("{}/repos/{}/{}/commits/{}/status", self.api_url, owner, repo, git_ref);
+        let response = self.send(self.request(Method::GET, &url)).await?;
+
+        match response.status() {
+            StatusCode::OK => Ok(response.json::<CombinedStatusResponse>().await?.statuses),
+            StatusCode::NOT_FOUND => Err(StarsError::NotFound(format!("Repository {}/{} not found", owner, repo))),
+            _ => Err(StarsError::from_response(response).await),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mockito::Server;
+    use serde_json::json;
+
+    fn run(name: &str, conclusion: Option<&str>) -> CheckRun {
+        CheckRun {
+            name: name.to_string(),
+            status: if conclusion.is_some() { "completed" } else { "in_progress" }.to_string(),
+            conclusion: conclusion.map(str::to_string),
+        }
+    }
+
+    fn status(context: &str, state: &str) -> CommitStatus {
+        CommitStatus { context: context.to_string(), state: state.to_string() }
+    }
+
+    #[test]
+    fn test_summarize() {
+        let passing = summarize(&[run("test", Some("success")), run("docs", Some("skipped"))], &[status("ci/netlify", "success")]);
+        assert_eq!(passing, CiSummary { state: CiState::Passing, failing: vec![] });
+
+        let failing = summarize(&[run("test", Some("failure")), run("lint", None)], &[status("ci/legacy", "error")]);
+        assert_eq!(failing.state, CiState::Failing);
+        assert_eq!(failing.failing, vec!["test", "ci/legacy"]);
+
+        assert_eq!(summarize(&[run("test", None)], &[]).state, CiState::Pending);
+        assert_eq!(summarize(&[], &[status("ci/legacy", "pending")]).state, CiState::Pending);
+        assert_eq!(summarize(&[], &[]).state, CiState::Unknown);
+    }
+
+    #[tokio::test]
+    async fn test_ci_summary() {
+        let mut server = Server::new_async().await;
+
+        server
+            .mock("GET", "/repos/rust-lang/rust/commits/HEAD/check-runs?per_page=100")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(json!({
+                "total_count": 2,
+                "check_runs": [
+                    { "name": "build", "status": "completed", "conclusion": "success" },
+                    { "name": "test", "status": "completed", "conclusion": "timed_out" }
+                ]
+            }).to_string())
+            .create_async()
+            .await;
+        server
+            .mock("GET", "/repos/rust-lang/rust/commits/HEAD/status")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(json!({ "state": "pending", "statuses": [] }).to_string())
+            .create_async()
+            .await;
+
+        let client = GitHubClient::new(server.url().to_string(), "test_token".to_string()).await;
+        let summary = client.ci_summary("rust-lang", "rust").await.unwrap();
+
+        assert_eq!(summary, CiSummary { state: CiState::Failing, failing: vec!["test".to_string()] });
+    }
+
+    #[tokio::test]
+    async fn test_check_runs_repo_not_found() {
+        let mut server = Server::new_async().await;
+
+        server
+            .mock("GET", "/repos/octocat/missing/commits/HEAD/check-runs?per_page=100")
+            .with_status(404)
+            .create_async()
+            .await;
+
+        let client = GitHubClient::new(server.url().to_string(), "test_token".to_string()).await;
+        assert!(client.check_runs("octocat", "missing", "HEAD").await.is_err());
+    }
+}
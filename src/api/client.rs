@@ -1,16 +1,202 @@
-///
-/// This module contains the client for the GitHub API.
-///
+//!
+//! This module contains the client for the GitHub API.
+//!
 
-use crate::config::Config;
-use reqwest::{Client, ClientBuilder};
-use std::error::Error;
-use std::time::Duration;
+use crate::api::cache::ResponseCache;
+use crate::api::{metrics, rate_limit};
+use crate::config::{Config, ListBackend, RetryConfig};
+use reqwest::header::{HeaderMap, HeaderValue, ACCEPT, ACCEPT_LANGUAGE, IF_NONE_MATCH};
+use reqwest::{Client, ClientBuilder, Method, Request, RequestBuilder, Response, StatusCode};
+use crate::error::StarsError;
+use std::sync::Mutex;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 
+/// REST API version every request is pinned to
+pub const API_VERSION: &str = "2022-11-28";
+
+/// Header used to pin the REST API version
+pub const API_VERSION_HEADER: &str = "X-GitHub-Api-Version";
+
+/// Media types understood by the GitHub REST API
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MediaType {
+    /// The default JSON representation
+    Json,
+    /// Starred repositories wrapped with their `starred_at` timestamp
+    StarJson,
+    /// Repositories including their topics
+    Topics,
+    /// Raw file contents, e.g. a README
+    Raw,
+    /// The binary contents of a release asset
+    OctetStream,
+}
+
+impl MediaType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            MediaType::Json => "application/vnd.github+json",
+            MediaType::StarJson => "application/vnd.github.star+json",
+            MediaType::Topics => "application/vnd.github.mercy-preview+json",
+            MediaType::Raw => "application/vnd.github.raw",
+            MediaType::OctetStream => "application/octet-stream",
+        }
+    }
+}
+
+/// Headers sent with every request: the pinned API version and the default media type
+pub fn default_headers() -> HeaderMap {
+    let mut headers = HeaderMap::new();
+    headers.insert(ACCEPT, HeaderValue::from_static(MediaType::Json.as_str()));
+    headers.insert(API_VERSION_HEADER, HeaderValue::from_static(API_VERSION));
+    headers
+}
+
+/// Explain a 400 response caused by the server not supporting the pinned API version
+pub fn unsupported_version_error(message: &str) -> Option<String> {
+    if message.contains(API_VERSION_HEADER) || message.to_lowercase().contains("api version") {
+        Some(format!(
+            "The server does not support GitHub API version {} ({}); check api_url or upgrade stars_fetcher",
+            API_VERSION, message
+        ))
+    } else {
+        None
+    }
+}
+
+/// Header GitHub tags every response with, which its support can look up
+pub const REQUEST_ID_HEADER: &str = "x-github-request-id";
+
+/// The latest request answered with an error status, kept for bug reports
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FailedRequest {
+    pub method: Option<String>,
+    pub url: String,
+    pub status: u16,
+    pub request_id: Option<String>,
+}
+
+static LAST_FAILED_REQUEST: Mutex<Option<FailedRequest>> = Mutex::new(None);
+
+/// Remember `response` if it carries an error status
+pub fn record_failed_request(method: Option<&str>, response: &Response) {
+    if response.status().is_success() || response.status() == StatusCode::NOT_MODIFIED {
+        return;
+    }
+
+    let failed = FailedRequest {
+        method: method.map(String::from),
+        url: response.url().to_string(),
+        status: response.status().as_u16(),
+        request_id: response
+            .headers()
+            .get(REQUEST_ID_HEADER)
+            .and_then(|value| value.to_str().ok())
+            .map(String::from),
+    };
+    if let Ok(mut last) = LAST_FAILED_REQUEST.lock() {
+        *last = Some(failed);
+    }
+}
+
+/// The latest request answered with an error status, if any
+pub fn last_failed_request() -> Option<FailedRequest> {
+    LAST_FAILED_REQUEST.lock().ok()?.clone()
+}
+
+/// Times one request waits for a rate limit to reset before giving up, in case the clock is off
+const MAX_RATE_LIMIT_WAITS: u32 = 3;
+
+/// Longest wait between two attempts, however many retries came before
+const MAX_BACKOFF: Duration = Duration::from_secs(30);
+
+/// How requests failing for a passing reason are retried: a 500, 502, 503 or 504 status, a
+/// timeout or a connection that couldn't be made or was reset
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RetryPolicy {
+    /// Attempts per request including the first one, 1 to never retry
+    pub max_attempts: u32,
+    /// Delay before the first retry, doubled for every retry after it
+    pub initial_backoff: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        RetryPolicy::from(&RetryConfig::default())
+    }
+}
+
+impl From<&RetryConfig> for RetryPolicy {
+    fn from(config: &RetryConfig) -> Self {
+        RetryPolicy {
+            max_attempts: config.max_attempts.max(1),
+            initial_backoff: Duration::from_millis(config.initial_backoff_ms),
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// A policy sending every request once
+    pub fn none() -> Self {
+        RetryPolicy { max_attempts: 1, initial_backoff: Duration::ZERO }
+    }
+
+    /// Wait before retry number `retry`, counted from 1. The exponential delay is jittered to
+    /// between half and all of it, so clients failing together don't retry together.
+    pub fn backoff(&self, retry: u32) -> Duration {
+        let delay = self
+            .initial_backoff
+            .saturating_mul(2u32.saturating_pow(retry.saturating_sub(1)))
+            .min(MAX_BACKOFF);
+        let millis = delay.as_millis() as u64;
+        Duration::from_millis(fastrand::u64(millis / 2..=millis))
+    }
+}
+
+// Seconds since the Unix epoch
+fn unix_now() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or_default()
+}
+
+// Statuses GitHub answers with while a server is overloaded or being deployed
+fn is_transient_status(status: StatusCode) -> bool {
+    matches!(
+        status,
+        StatusCode::INTERNAL_SERVER_ERROR
+            | StatusCode::BAD_GATEWAY
+            | StatusCode::SERVICE_UNAVAILABLE
+            | StatusCode::GATEWAY_TIMEOUT
+    )
+}
+
+// Timeouts, refused connections and connections reset before a response arrived
+fn is_transient_error(error: &reqwest::Error) -> bool {
+    error.is_timeout() || error.is_connect() || error.is_request()
+}
+
+// Only requests that may be sent twice without changing the outcome are retried, so a
+// comment or a fork is never created twice. Starring is a PUT and unstarring a DELETE.
+fn is_idempotent(method: &Method) -> bool {
+    matches!(*method, Method::GET | Method::HEAD | Method::PUT | Method::DELETE | Method::OPTIONS)
+}
+
+/// Client for the GitHub REST and GraphQL APIs. Cloning it is cheap and shares the connection pool.
+#[derive(Clone)]
 pub struct GitHubClient {
     pub(crate) client: Client,
     pub api_url: String,
     pub token: String,
+    /// API used to list starred repositories
+    pub backend: ListBackend,
+    /// How transient failures are retried
+    pub retry: RetryPolicy,
+    /// Whether a request rejected by a rate limit waits for the reset and is sent again, rather
+    /// than failing with `StarsError::RateLimited`
+    pub wait_on_rate_limit: bool,
+    /// Responses kept for conditional requests, none to always fetch in full
+    pub cache: Option<ResponseCache>,
+    /// Sent as `Accept-Language`, for servers that localize descriptions, e.g. `zh-CN, en;q=0.8`
+    pub accept_language: Option<String>,
 }
 
 impl GitHubClient {
@@ -18,31 +204,175 @@ impl GitHubClient {
         ClientBuilder::new()
             .timeout(Duration::from_secs(30))
             .user_agent("stars-fetcher")
+            .default_headers(default_headers())
             .build()
             .expect("Failed to create HTTP client")
     }
 
+    // Build an authenticated request asking for the default JSON media type
+    pub(crate) fn request(&self, method: Method, url: &str) -> RequestBuilder {
+        self.request_as(method, url, MediaType::Json)
+    }
+
+    // Build a request asking for a specific media type, authenticated unless the client is anonymous
+    pub(crate) fn request_as(&self, method: Method, url: &str, media_type: MediaType) -> RequestBuilder {
+        let request = self.client
+            .request(method, url)
+            .header(ACCEPT, media_type.as_str());
+        let request = match &self.accept_language {
+            Some(language) => request.header(ACCEPT_LANGUAGE, language.as_str()),
+            None => request,
+        };
+
+        if self.is_anonymous() {
+            request
+        } else {
+            request.bearer_auth(&self.token)
+        }
+    }
+
+    /// Whether requests are sent without a token, with GitHub's lower anonymous rate limit
+    pub fn is_anonymous(&self) -> bool {
+        self.token.is_empty()
+    }
+
+    // Send a request, retrying transient failures, waiting out rate limits if asked to and turning
+    // an unsupported API version into a clear error
+    pub(crate) async fn send(&self, request: RequestBuilder) -> Result<Response, StarsError> {
+        let (client, request) = request.build_split();
+        let mut request = request?;
+        let mut waits = 0;
+
+        let cache = self
+            .cache
+            .as_ref()
+            .and_then(|cache| Some((cache, ResponseCache::key(&self.token, &request)?)));
+        let cached = cache.as_ref().and_then(|(cache, key)| cache.get(key));
+        if let Some(etag) = cached.as_ref().and_then(|cached| HeaderValue::from_str(&cached.etag).ok()) {
+            request.headers_mut().insert(IF_NONE_MATCH, etag);
+        }
+
+        loop {
+            // A request rejected by a rate limit wasn't processed, so any method may be resent
+            let resend = request.try_clone();
+            let (method, url) = (request.method().to_string(), request.url().clone());
+            let started = Instant::now();
+            let response = self.execute(&client, request).await?;
+            let cache_hit = response.status() == StatusCode::NOT_MODIFIED && cached.is_some();
+            metrics::record_response(cache_hit);
+            metrics::record_call(&method, &url, started.elapsed(), response.content_length().unwrap_or_default(), cache_hit);
+
+            if let Some(delay) = rate_limit::retry_delay(response.status(), response.headers(), unix_now()) {
+                match resend {
+                    Some(resend) if self.wait_on_rate_limit && waits < MAX_RATE_LIMIT_WAITS => {
+                        rate_limit::wait_for_reset(delay).await;
+                        request = resend;
+                        waits += 1;
+                        continue;
+                    }
+                    _ => return Err(StarsError::from_response(response).await),
+                }
+            }
+
+            if response.status() == StatusCode::BAD_REQUEST {
+                return Err(StarsError::from_response(response).await);
+            }
+
+            return match cache {
+                Some((cache, key)) => Ok(cache.resolve(&key, cached, response).await?),
+                None => Ok(response),
+            };
+        }
+    }
+
+    // Execute a request, retrying transient failures following the retry policy
+    async fn execute(&self, client: &Client, request: Request) -> Result<Response, StarsError> {
+        let method = request.method().to_string();
+        // The client sends GraphQL queries only, which are POSTs that change nothing
+        let retryable = is_idempotent(request.method()) || request.url().path().ends_with("/graphql");
+        let mut attempt = 1;
+
+        let response = loop {
+            // Streamed bodies can't be cloned, and are sent once
+            let retry = match request.try_clone() {
+                Some(retry) if retryable && attempt < self.retry.max_attempts => retry,
+                _ => break client.execute(request).await?,
+            };
+
+            match client.execute(retry).await {
+                Ok(response) if is_transient_status(response.status()) => {
+                    record_failed_request(Some(&method), &response);
+                    rate_limit::record(response.headers());
+                    metrics::record_response(false);
+                }
+                Ok(response) => break response,
+                Err(error) if is_transient_error(&error) => {}
+                Err(error) => return Err(error.into()),
+            }
+
+            tokio::time::sleep(self.retry.backoff(attempt)).await;
+            attempt += 1;
+        };
+        record_failed_request(Some(&method), &response);
+        rate_limit::record(response.headers());
+
+        Ok(response)
+    }
+
+    /// A client for the API at `api_url`, e.g. `https://api.github.com`, anonymous if `token` is empty
     pub async fn new(api_url: String, token: String) -> Self {
         let client = Self::create_http_client();
         Self {
             client,
             api_url,
-            token
+            token,
+            backend: ListBackend::default(),
+            retry: RetryPolicy::default(),
+            wait_on_rate_limit: false,
+            cache: None,
+            accept_language: None,
         }
     }
 
-    async fn validate_auth(&self) -> Result<bool, Box<dyn Error>> {
+    /// List starred repositories through `backend`
+    pub fn with_backend(mut self, backend: ListBackend) -> Self {
+        self.backend = backend;
+        self
+    }
+
+    /// Retry transient failures following `retry`
+    pub fn with_retry(mut self, retry: RetryPolicy) -> Self {
+        self.retry = retry;
+        self
+    }
+
+    /// Revalidate JSON responses kept in `cache` instead of fetching them in full
+    pub fn with_cache(mut self, cache: ResponseCache) -> Self {
+        self.cache = Some(cache);
+        self
+    }
+
+    /// Wait out rate limits instead of failing, counting down on stderr
+    pub fn with_wait_on_rate_limit(mut self, wait: bool) -> Self {
+        self.wait_on_rate_limit = wait;
+        self
+    }
+
+    /// Ask for descriptions in `language`, an `Accept-Language` value, or the server's default if none
+    pub fn with_accept_language(mut self, language: Option<String>) -> Self {
+        self.accept_language = language.filter(|language| !language.trim().is_empty());
+        self
+    }
+
+    async fn validate_auth(&self) -> Result<bool, StarsError> {
         let url = format!("{}/user", self.api_url);
-        let response = self.client
-            .get(&url)
-            .bearer_auth(&self.token)
-            .send()
-            .await?;
+        let response = self.send(self.request(Method::GET, &url)).await?;
 
         Ok(response.status().is_success())
     }
 
-    pub async fn from_config(config: &Config) -> Result<Self, Box<dyn Error>> {
+    /// A client with the API URL, token, list backend and retry policy of the config file
+    pub async fn from_config(config: &Config) -> Result<Self, StarsError> {
         let api_url = config.github.api_url.clone();
         let token = config.github.token.clone();
 
@@ -50,24 +380,41 @@ impl GitHubClient {
             return Err("API URL is empty".into());
         }
         if token.is_empty() {
-            return Err("GitHub API token is empty".into());
+            return Err(StarsError::Auth(String::from("GitHub API token is empty")));
         }
 
-        Ok(Self::new(api_url, token).await)
+        Ok(Self::new(api_url, token)
+            .await
+            .with_backend(config.github.backend)
+            .with_retry(RetryPolicy::from(&config.retry)))
     }
 
-    pub async fn new_validated(config: &Config) -> Result<Self, Box<dyn Error>> {
+    /// Create a client for public data only, without a token
+    pub async fn anonymous(config: &Config) -> Result<Self, StarsError> {
+        let api_url = config.github.api_url.clone();
+        if api_url.is_empty() {
+            return Err("API URL is empty".into());
+        }
+
+        Ok(Self::new(api_url, String::new())
+            .await
+            .with_backend(config.github.backend)
+            .with_retry(RetryPolicy::from(&config.retry)))
+    }
+
+    /// Like `from_config`, checking with GitHub that the token works
+    pub async fn new_validated(config: &Config) -> Result<Self, StarsError> {
         let client = Self::from_config(config).await?;
 
         if !client.validate_auth().await? {
-            return Err("Invalid GitHub API token".into());
+            return Err(StarsError::Auth(String::from("Invalid GitHub API token")));
         }
 
         Ok(client)
     }
 }
 
-pub async fn validate_github_config() -> Result<(), Box<dyn Error>> {
+pub async fn validate_github_config() -> Result<(), StarsError> {
     let config = Config::new()?;
     // Create client without validation first
     let api_url = config.github.api_url.clone();
@@ -86,11 +433,403 @@ pub async fn validate_github_config() -> Result<(), Box<dyn Error>> {
         }
         Ok(false) => {
             println!("GitHub API authentication failed");
-            Err("Invalid GitHub API token".into())
+            Err(StarsError::Auth(String::from("Invalid GitHub API token")))
         }
         Err(e) => {
             println!("Error validating GitHub API token: {}", e);
             Err(e)
         }
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::repos::Repo;
+    use crate::api::stars::Star;
+    use mockito::Server;
+    use proptest::prelude::*;
+
+    #[tokio::test]
+    async fn test_requests_pin_api_version() {
+        let mut server = Server::new_async().await;
+
+        let mock = server
+            .mock("GET", "/user")
+            .match_header(API_VERSION_HEADER, API_VERSION)
+            .match_header("accept", MediaType::Json.as_str())
+            .match_header("authorization", "Bearer test_token")
+            .with_status(200)
+            .create_async()
+            .await;
+
+        let client = GitHubClient::new(
+            server.url().to_string(),
+            "test_token".to_string()
+        ).await;
+
+        assert!(client.validate_auth().await.unwrap());
+        mock.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_request_as_overrides_media_type() {
+        let mut server = Server::new_async().await;
+
+        let mock = server
+            .mock("GET", "/user/starred")
+            .match_header("accept", MediaType::StarJson.as_str())
+            .match_header(API_VERSION_HEADER, API_VERSION)
+            .with_status(200)
+            .create_async()
+            .await;
+
+        let client = GitHubClient::new(
+            server.url().to_string(),
+            "test_token".to_string()
+        ).await;
+
+        let url = format!("{}/user/starred", client.api_url);
+        let request = client.request_as(Method::GET, &url, MediaType::StarJson);
+        let response = client.send(request).await.unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+        mock.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_accept_language() {
+        let mut server = Server::new_async().await;
+
+        let localized = server
+            .mock("GET", "/repos/octocat/hello")
+            .match_header("accept-language", "zh-CN, en;q=0.8")
+            .with_status(200)
+            .with_body(r#"{"id": 1, "name": "hello", "full_name": "octocat/hello", "owner": {"login": "octocat"}, "description": "你好，世界", "html_url": "https://github.com/octocat/hello", "stargazers_count": 1}"#)
+            .create_async()
+            .await;
+        let default = server
+            .mock("GET", "/user")
+            .match_header("accept-language", mockito::Matcher::Missing)
+            .with_status(200)
+            .create_async()
+            .await;
+
+        let client = GitHubClient::new(server.url().to_string(), "test_token".to_string()).await;
+        assert!(client.validate_auth().await.unwrap());
+        assert!(client.clone().with_accept_language(Some(" ".to_string())).accept_language.is_none());
+
+        let client = client.with_accept_language(Some("zh-CN, en;q=0.8".to_string()));
+        let repo = client.get_repo_details("octocat", "hello").await.unwrap();
+
+        assert_eq!(repo.description.as_deref(), Some("你好，世界"));
+        localized.assert_async().await;
+        default.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_unsupported_api_version() {
+        let mut server = Server::new_async().await;
+
+        server
+            .mock("GET", "/user")
+            .with_status(400)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"message": "Unsupported 'X-GitHub-Api-Version' header"}"#)
+            .create_async()
+            .await;
+
+        let client = GitHubClient::new(
+            server.url().to_string(),
+            "test_token".to_string()
+        ).await;
+
+        let error = client.validate_auth().await.unwrap_err().to_string();
+        assert!(error.contains("does not support GitHub API version 2022-11-28"), "{}", error);
+    }
+
+    #[tokio::test]
+    async fn test_anonymous_requests_have_no_authorization() {
+        let mut server = Server::new_async().await;
+
+        let mock = server
+            .mock("GET", "/user")
+            .match_header("authorization", mockito::Matcher::Missing)
+            .with_status(200)
+            .create_async()
+            .await;
+
+        let client = GitHubClient::new(server.url().to_string(), String::new()).await;
+
+        assert!(client.is_anonymous());
+        assert!(client.validate_auth().await.unwrap());
+        mock.assert_async().await;
+    }
+
+    // A client retrying without noticeable waits
+    async fn retrying_client(server: &Server, max_attempts: u32) -> GitHubClient {
+        GitHubClient::new(server.url().to_string(), "test_token".to_string())
+            .await
+            .with_retry(RetryPolicy { max_attempts, initial_backoff: Duration::from_millis(1) })
+    }
+
+    #[tokio::test]
+    async fn test_get_repo_gives_up_after_max_attempts() {
+        let mut server = Server::new_async().await;
+
+        let mock = server
+            .mock("GET", "/repos/owner/repo")
+            .with_status(503)
+            .expect(3)
+            .create_async()
+            .await;
+
+        let client = retrying_client(&server, 3).await;
+        let error = client.get_repo("owner", "repo").await.unwrap_err();
+
+        assert!(matches!(error, StarsError::Api { status: StatusCode::SERVICE_UNAVAILABLE, .. }), "{}", error);
+        mock.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_list_repos_retries_server_errors() {
+        let mut server = Server::new_async().await;
+
+        let failing = server
+            .mock("GET", "/user/starred?per_page=100")
+            .with_status(502)
+            .expect(2)
+            .create_async()
+            .await;
+        let ok = server
+            .mock("GET", "/user/starred?per_page=100")
+            .with_status(200)
+            .with_body(r#"[{"id": 1, "name": "repo", "full_name": "owner/repo", "owner": {"login": "owner"}, "description": null, "html_url": "https://github.com/owner/repo", "stargazers_count": 5}]"#)
+            .expect(1)
+            .create_async()
+            .await;
+
+        let client = retrying_client(&server, 3).await;
+        let repos = client.list_repos().await.unwrap();
+
+        assert_eq!(repos.len(), 1);
+        failing.assert_async().await;
+        ok.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_star_and_unstar_are_retried() {
+        let mut server = Server::new_async().await;
+
+        let star_failing = server.mock("PUT", "/user/starred/owner/repo").with_status(500).expect(1).create_async().await;
+        let star_ok = server.mock("PUT", "/user/starred/owner/repo").with_status(204).expect(1).create_async().await;
+        let unstar_failing = server.mock("DELETE", "/user/starred/owner/repo").with_status(504).expect(1).create_async().await;
+        let unstar_ok = server.mock("DELETE", "/user/starred/owner/repo").with_status(204).expect(1).create_async().await;
+
+        let client = retrying_client(&server, 2).await;
+        client.star_repo("owner", "repo").await.unwrap();
+        client.unstar_repo("owner", "repo").await.unwrap();
+
+        for mock in [star_failing, star_ok, unstar_failing, unstar_ok] {
+            mock.assert_async().await;
+        }
+    }
+
+    #[tokio::test]
+    async fn test_client_errors_and_posts_are_not_retried() {
+        let mut server = Server::new_async().await;
+
+        let not_found = server.mock("GET", "/repos/owner/gone").with_status(404).expect(1).create_async().await;
+        let post = server.mock("POST", "/repos/owner/repo/forks").with_status(503).expect(1).create_async().await;
+
+        let client = retrying_client(&server, 3).await;
+        assert!(matches!(client.get_repo("owner", "gone").await, Err(StarsError::NotFound(_))));
+
+        let url = format!("{}/repos/owner/repo/forks", client.api_url);
+        let response = client.send(client.request(Method::POST, &url)).await.unwrap();
+        assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+
+        not_found.assert_async().await;
+        post.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_refused_connections_are_retried() {
+        // Bind and drop a listener to get a port nothing listens on
+        let port = std::net::TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap().port();
+        let client = GitHubClient::new(format!("http://127.0.0.1:{}", port), "test_token".to_string())
+            .await
+            .with_retry(RetryPolicy { max_attempts: 3, initial_backoff: Duration::from_millis(20) });
+
+        let started = std::time::Instant::now();
+        let error = client.get_repo("owner", "repo").await.unwrap_err();
+
+        assert!(matches!(error, StarsError::Network(_)), "{}", error);
+        // Two waits of at least 10ms and 20ms between the three attempts
+        assert!(started.elapsed() >= Duration::from_millis(30));
+    }
+
+    #[tokio::test]
+    async fn test_rate_limited_requests_fail_without_waiting() {
+        let mut server = Server::new_async().await;
+
+        let mock = server
+            .mock("GET", "/repos/owner/repo")
+            .with_status(403)
+            .with_header("x-ratelimit-limit", "5000")
+            .with_header("x-ratelimit-remaining", "0")
+            .with_header("x-ratelimit-reset", "4102444800")
+            .with_body(r#"{"message": "API rate limit exceeded for user ID 1."}"#)
+            .expect(1)
+            .create_async()
+            .await;
+
+        let client = retrying_client(&server, 3).await;
+        let error = client.get_repo("owner", "repo").await.unwrap_err();
+
+        assert!(matches!(error, StarsError::RateLimited { reset: Some(4102444800), .. }), "{}", error);
+        mock.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_rate_limited_requests_wait_and_resend() {
+        let mut server = Server::new_async().await;
+
+        let limited = server
+            .mock("PUT", "/user/starred/owner/repo")
+            .with_status(429)
+            .with_header("retry-after", "1")
+            .expect(1)
+            .create_async()
+            .await;
+        let ok = server.mock("PUT", "/user/starred/owner/repo").with_status(204).expect(1).create_async().await;
+
+        let client = retrying_client(&server, 1).await.with_wait_on_rate_limit(true);
+        let started = std::time::Instant::now();
+        client.star_repo("owner", "repo").await.unwrap();
+
+        assert!(started.elapsed() >= Duration::from_secs(1));
+        limited.assert_async().await;
+        ok.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_listing_resumes_after_rate_limit_reset() {
+        let mut server = Server::new_async().await;
+        let reset = unix_now().to_string();
+
+        let first = server
+            .mock("GET", "/user/starred?per_page=100")
+            .with_status(200)
+            .with_header("link", &format!(r#"<{}/user/starred?page=2>; rel="next""#, server.url()))
+            .with_body(r#"[{"id": 1, "name": "one", "full_name": "owner/one", "owner": {"login": "owner"}, "description": null, "html_url": "https://github.com/owner/one", "stargazers_count": 1}]"#)
+            .expect(1)
+            .create_async()
+            .await;
+        let limited = server
+            .mock("GET", "/user/starred?page=2")
+            .with_status(403)
+            .with_header("x-ratelimit-limit", "5000")
+            .with_header("x-ratelimit-remaining", "0")
+            .with_header("x-ratelimit-reset", &reset)
+            .expect(1)
+            .create_async()
+            .await;
+        let second = server
+            .mock("GET", "/user/starred?page=2")
+            .with_status(200)
+            .with_body(r#"[{"id": 2, "name": "two", "full_name": "owner/two", "owner": {"login": "owner"}, "description": null, "html_url": "https://github.com/owner/two", "stargazers_count": 2}]"#)
+            .expect(1)
+            .create_async()
+            .await;
+
+        let client = retrying_client(&server, 1).await.with_wait_on_rate_limit(true);
+        let repos = client.list_repos().await.unwrap();
+
+        assert_eq!(repos.iter().map(|repo| repo.name.as_str()).collect::<Vec<_>>(), ["one", "two"]);
+        for mock in [first, limited, second] {
+            mock.assert_async().await;
+        }
+    }
+
+    #[test]
+    fn test_backoff_is_jittered_exponential() {
+        let policy = RetryPolicy { max_attempts: 5, initial_backoff: Duration::from_millis(100) };
+
+        for _ in 0..20 {
+            let first = policy.backoff(1);
+            let third = policy.backoff(3);
+            assert!(first >= Duration::from_millis(50) && first <= Duration::from_millis(100), "{:?}", first);
+            assert!(third >= Duration::from_millis(200) && third <= Duration::from_millis(400), "{:?}", third);
+        }
+        assert!(policy.backoff(30) <= MAX_BACKOFF);
+        assert_eq!(RetryPolicy::none().backoff(1), Duration::ZERO);
+    }
+
+    // The longest `policy` lets a request wait in all between `attempts` attempts
+    fn backoff_budget(policy: &RetryPolicy, attempts: u32) -> Duration {
+        (1..attempts)
+            .map(|retry| policy.initial_backoff.saturating_mul(2u32.saturating_pow(retry - 1)).min(MAX_BACKOFF))
+            .sum()
+    }
+
+    proptest! {
+        #[test]
+        fn test_backoff_stays_within_budget(initial_ms in 0u64..60_000, retry in 1u32..100) {
+            let policy = RetryPolicy { max_attempts: retry + 1, initial_backoff: Duration::from_millis(initial_ms) };
+            let ceiling = backoff_budget(&policy, retry + 1) - backoff_budget(&policy, retry);
+
+            let backoff = policy.backoff(retry);
+            prop_assert!(backoff <= ceiling && backoff <= MAX_BACKOFF, "{:?} over {:?}", backoff, ceiling);
+            prop_assert!(backoff >= (ceiling / 2).saturating_sub(Duration::from_millis(1)), "{:?} under half of {:?}", backoff, ceiling);
+        }
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(24))]
+
+        #[test]
+        fn test_retries_stay_within_attempts_and_time(
+            max_attempts in 1u32..5,
+            failures in 0u32..7,
+            status in prop::sample::select(vec![500usize, 502, 503, 504]),
+            initial_ms in 1u64..15,
+        ) {
+            let policy = RetryPolicy { max_attempts, initial_backoff: Duration::from_millis(initial_ms) };
+            let attempts = (failures + 1).min(max_attempts);
+
+            let runtime = tokio::runtime::Runtime::new().unwrap();
+            let (answered, elapsed, mocks) = runtime.block_on(async {
+                let mut server = Server::new_async().await;
+                // More requests than expected would still hit the last mock and fail `matched`
+                let mut mocks = Vec::new();
+                if failures > 0 {
+                    let failing = server.mock("GET", "/repos/owner/repo").with_status(status).expect(failures.min(max_attempts) as usize);
+                    mocks.push(failing.create_async().await);
+                }
+                if failures < max_attempts {
+                    mocks.push(server.mock("GET", "/repos/owner/repo").with_status(200).expect(1).create_async().await);
+                }
+
+                let client = GitHubClient::new(server.url(), "test_token".to_string()).await.with_retry(policy);
+                let url = format!("{}/repos/owner/repo", client.api_url);
+                let started = std::time::Instant::now();
+                let response = client.send(client.request(Method::GET, &url)).await.unwrap();
+                let elapsed = started.elapsed();
+                (response.status().as_u16(), elapsed, mocks.iter().map(|mock| mock.matched()).collect::<Vec<_>>())
+            });
+
+            prop_assert_eq!(answered, if failures < max_attempts { 200 } else { status as u16 });
+            prop_assert!(mocks.into_iter().all(|matched| matched), "{} attempts expected", attempts);
+            // Waits are capped by the budget; the rest is time spent on the local requests themselves
+            let budget = backoff_budget(&policy, attempts) + Duration::from_millis(500);
+            prop_assert!(elapsed <= budget, "{:?} over {:?}", elapsed, budget);
+        }
+    }
+
+    #[test]
+    fn test_unsupported_version_error() {
+        assert!(unsupported_version_error("Unsupported 'X-GitHub-Api-Version' header").is_some());
+        assert!(unsupported_version_error("Problems parsing JSON").is_none());
+    }
+}
@@ -0,0 +1,97 @@
+//!
+//! Comparing two refs of a repository
+//! The compare API reports how far apart two commits are and the commits in between.
+//!
+
+use crate::error::StarsError;
+use crate::api::client::GitHubClient;
+use reqwest::{Method, StatusCode};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct Comparison {
+    /// `ahead`, `behind`, `diverged` or `identical`, describing head relative to base
+    pub status: String,
+    pub ahead_by: u64,
+    pub behind_by: u64,
+    /// Commits reachable from head but not base, oldest first; GitHub includes at most 250
+    #[serde(default)]
+    pub commits: Vec<CompareCommit>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct CompareCommit {
+    pub sha: String,
+    pub commit: CommitDetail,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct CommitDetail {
+    pub message: String,
+    pub author: Option<CommitAuthor>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct CommitAuthor {
+    pub name: String,
+    pub date: String,
+}
+
+#[allow(async_fn_in_trait)]
+pub trait Compare {
+    /// Compare `head` against `base`; either may be `owner:branch` to compare across forks
+    async fn compare(&self, owner: &str, repo: &str, base: &str, head: &str) -> Result<Comparison, StarsError>;
+}
+
+impl Compare for GitHubClient {
+    async fn compare(&self, owner: &str, repo: &str, base: &str, head: &str) -> Result<Comparison, StarsError> {
+        let url = format!("{}/repos/{}/{}/compare/{}...{}", self.api_url, owner, repo, base, head);
+        let response = self.send(self.request(Method::GET, &url)).await?;
+
+        match response.status() {
+            StatusCode::OK => Ok(response.json::<Comparison>().await?),
+            StatusCode::NOT_FOUND => Err(StarsError::NotFound(format!("Can't compare {}...{} in {}/{}: unknown ref", base, head, owner, repo))),
+            _ => Err(StarsError::from_response(response).await)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mockito::Server;
+    use serde_json::json;
+
+    #[tokio::test]
+    async fn test_compare() {
+        let mut server = Server::new_async().await;
+
+        server
+            .mock("GET", "/repos/tokio-rs/tokio/compare/tokio-1.36.0...tokio-1.37.0")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(json!({
+                "status": "ahead",
+                "ahead_by": 1,
+                "behind_by": 0,
+                "commits": [{
+                    "sha": "0123456789abcdef",
+                    "commit": { "message": "chore: prepare 1.37.0\n\nDetails", "author": { "name": "Alice", "date": "2024-03-28T00:00:00Z" } }
+                }]
+            }).to_string())
+            .create_async()
+            .await;
+        server
+            .mock("GET", "/repos/tokio-rs/tokio/compare/nope...tokio-1.37.0")
+            .with_status(404)
+            .create_async()
+            .await;
+
+        let client = GitHubClient::new(server.url().to_string(), "test_token".to_string()).await;
+        let comparison = client.compare("tokio-rs", "tokio", "tokio-1.36.0", "tokio-1.37.0").await.unwrap();
+
+        assert_eq!(comparison.ahead_by, 1);
+        assert_eq!(comparison.commits[0].commit.author.as_ref().unwrap().name, "Alice");
+        assert!(client.compare("tokio-rs", "tokio", "nope", "tokio-1.37.0").await.unwrap_err().to_string().contains("unknown ref"));
+    }
+}
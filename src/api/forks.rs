@@ -0,0 +1,164 @@
+//!
+//! How far forks have drifted from their upstream
+//! A fork's default branch is compared with its parent's default branch across repositories,
+//! so `behind_by` counts upstream commits the fork is missing.
+//!
+
+use crate::error::StarsError;
+use crate::api::client::GitHubClient;
+use crate::api::compare::Compare;
+use reqwest::{Method, StatusCode};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct ForkInfo {
+    pub full_name: String,
+    pub default_branch: String,
+    #[serde(default)]
+    pub fork: bool,
+    /// The repository this one was forked from, only present when `fork` is true
+    pub parent: Option<UpstreamRepo>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct UpstreamRepo {
+    pub full_name: String,
+    pub default_branch: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ForkStatus {
+    pub fork: String,
+    pub upstream: String,
+    /// Commits on the fork's default branch that upstream doesn't have
+    pub ahead_by: u64,
+    /// Upstream commits the fork's default branch is missing
+    pub behind_by: u64,
+}
+
+impl ForkStatus {
+    pub fn summary(&self) -> &'static str {
+        match (self.ahead_by, self.behind_by) {
+            (0, 0) => "up to date",
+            (_, 0) => "ahead",
+            (0, _) => "behind",
+            _ => "diverged",
+        }
+    }
+}
+
+#[allow(async_fn_in_trait)]
+pub trait Forks: Compare {
+    /// The repository's default branch and, for forks, its parent
+    async fn fork_info(&self, owner: &str, repo: &str) -> Result<ForkInfo, StarsError>;
+    /// Compare a fork's default branch with its upstream's default branch
+    async fn fork_status(&self, owner: &str, repo: &str) -> Result<ForkStatus, StarsError> {
+        let info = self.fork_info(owner, repo).await?;
+        let parent = match info.parent {
+            Some(parent) if info.fork => parent,
+            _ => return Err(format!("{} is not a fork", info.full_name).into()),
+        };
+        let (parent_owner, parent_repo) = parent
+            .full_name
+            .split_once('/')
+            .ok_or_else(|| format!("Unexpected upstream name {:?}", parent.full_name))?;
+
+        let head = format!("{}:{}", owner, info.default_branch);
+        let comparison = self.compare(parent_owner, parent_repo, &parent.default_branch, &head).await?;
+
+        Ok(ForkStatus {
+            fork: info.full_name,
+            upstream: parent.full_name,
+            ahead_by: comparison.ahead_by,
+            behind_by: comparison.behind_by,
+        })
+    }
+}
+
+impl Forks for GitHubClient {
+    async fn fork_info(&self, owner: &str, repo: &str) -> Result<ForkInfo, StarsError> {
+        let url = format!("{}/repos/{}/{}", self.api_url, owner, repo);
+        let response = self.send(self.request(Method::GET, &url)).await?;
+
+        match response.status() {
+            StatusCode::OK => Ok(response.json::<ForkInfo>().await?),
+            StatusCode::NOT_FOUND => Err(StarsError::NotFound(format!("Repository {}/{} not found", owner, repo))),
+            _ => Err(StarsError::from_response(response).await)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mockito::Server;
+    use serde_json::json;
+
+    #[tokio::test]
+    async fn test_fork_status() {
+        let mut server = Server::new_async().await;
+
+        server
+            .mock("GET", "/repos/octocat/tokio")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(json!({
+                "full_name": "octocat/tokio",
+                "default_branch": "master",
+                "fork": true,
+                "parent": { "full_name": "tokio-rs/tokio", "default_branch": "main" }
+            }).to_string())
+            .create_async()
+            .await;
+        server
+            .mock("GET", "/repos/tokio-rs/tokio/compare/main...octocat:master")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(json!({ "status": "diverged", "ahead_by": 2, "behind_by": 40, "commits": [] }).to_string())
+            .create_async()
+            .await;
+
+        let client = GitHubClient::new(server.url().to_string(), "test_token".to_string()).await;
+        let status = client.fork_status("octocat", "tokio").await.unwrap();
+
+        assert_eq!(status, ForkStatus {
+            fork: "octocat/tokio".to_string(),
+            upstream: "tokio-rs/tokio".to_string(),
+            ahead_by: 2,
+            behind_by: 40,
+        });
+        assert_eq!(status.summary(), "diverged");
+    }
+
+    #[tokio::test]
+    async fn test_fork_status_not_a_fork() {
+        let mut server = Server::new_async().await;
+
+        server
+            .mock("GET", "/repos/tokio-rs/tokio")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(json!({ "full_name": "tokio-rs/tokio", "default_branch": "main", "fork": false }).to_string())
+            .create_async()
+            .await;
+
+        let client = GitHubClient::new(server.url().to_string(), "test_token".to_string()).await;
+        let error = client.fork_status("tokio-rs", "tokio").await.unwrap_err();
+
+        assert_eq!(error.to_string(), "tokio-rs/tokio is not a fork");
+    }
+
+    #[test]
+    fn test_summary() {
+        let status = |ahead_by, behind_by| ForkStatus {
+            fork: "a/b".to_string(),
+            upstream: "c/b".to_string(),
+            ahead_by,
+            behind_by,
+        };
+
+        assert_eq!(status(0, 0).summary(), "up to date");
+        assert_eq!(status(3, 0).summary(), "ahead");
+        assert_eq!(status(0, 3).summary(), "behind");
+    }
+}
@@ -0,0 +1,217 @@
+//!
+//! Functions for working with gists
+//! This module contains functions to read, create, update and comment on gists.
+//!
+
+use std::collections::BTreeMap;
+use crate::error::StarsError;
+use crate::api::client::{GitHubClient, MediaType};
+use reqwest::{Method, StatusCode};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct GistResponse {
+    pub id: String,
+    pub html_url: String,
+    #[serde(default)]
+    pub files: BTreeMap<String, GistFile>,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct GistFile {
+    pub raw_url: String,
+    /// Missing or cut short for large files, which must be read from `raw_url`
+    pub content: Option<String>,
+    #[serde(default)]
+    pub truncated: bool,
+}
+
+#[allow(async_fn_in_trait)]
+pub trait Gist {
+    async fn get_gist(&self, gist_id: &str) -> Result<GistResponse, StarsError>;
+    async fn gist_file_content(&self, file: &GistFile) -> Result<String, StarsError>;
+    async fn create_gist_comment(&self, gist_id: &str, body: &str) -> Result<(), StarsError>;
+    async fn create_gist(&self, description: &str, public: bool, filename: &str, content: &str) -> Result<GistResponse, StarsError>;
+    async fn update_gist(&self, gist_id: &str, filename: &str, content: &str) -> Result<GistResponse, StarsError>;
+}
+
+impl Gist for GitHubClient {
+    async fn get_gist(&self, gist_id: &str) -> Result<GistResponse, StarsError> {
+        let url = format!("{}/gists/{}", self.api_url, gist_id);
+        let response = self.send(self.request(Method::GET, &url)).await?;
+
+        match response.status() {
+            StatusCode::OK => Ok(response.json::<GistResponse>().await?),
+            StatusCode::NOT_FOUND => Err(StarsError::NotFound(format!("Gist {} not found", gist_id))),
+            _ => Err(StarsError::from_response(response).await)
+        }
+    }
+
+    async fn gist_file_content(&self, file: &GistFile) -> Result<String, StarsError> {
+        if let (Some(content), false) = (&file.content, file.truncated) {
+            return Ok(content.clone());
+        }
+
+        let response = self.send(self.request_as(Method::GET, &file.raw_url, MediaType::Raw)).await?;
+        match response.status() {
+            StatusCode::OK => Ok(response.text().await?),
+            _ => Err(StarsError::from_response(response).await)
+        }
+    }
+
+    async fn create_gist_comment(&self, gist_id: &str, body: &str) -> Result<(), StarsError> {
+        let url = format!("{}/gists/{}/comments", self.api_url, gist_id);
+        let response = self.send(self.request(Method::POST, &url).json(&json!({ "body": body }))).await?;
+
+        match response.status() {
+            StatusCode::CREATED => Ok(()),
+            _ => Err(StarsError::from_response(response).await)
+        }
+    }
+
+    async fn create_gist(&self, description: &str, public: bool, filename: &str, content: &str) -> Result<GistResponse, StarsError> {
+        let url = format!("{}/gists", self.api_url);
+        let body = json!({
+            "description": description,
+            "public": public,
+            "files": { filename: { "content": content } },
+        });
+        let response = self.send(self.request(Method::POST, &url).json(&body)).await?;
+
+        match response.status() {
+            StatusCode::CREATED => Ok(response.json::<GistResponse>().await?),
+            _ => Err(StarsError::from_response(response).await)
+        }
+    }
+
+    async fn update_gist(&self, gist_id: &str, filename: &str, content: &str) -> Result<GistResponse, StarsError> {
+        let url = format!("{}/gists/{}", self.api_url, gist_id);
+        let body = json!({
+            "files": { filename: { "content": content } },
+        });
+        let response = self.send(self.request(Method::PATCH, &url).json(&body)).await?;
+
+        match response.status() {
+            StatusCode::OK => Ok(response.json::<GistResponse>().await?),
+            _ => Err(StarsError::from_response(response).await)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mockito::{Matcher, Server};
+
+    #[tokio::test]
+    async fn test_create_gist() {
+        let mut server = Server::new_async().await;
+
+        let mock = server
+            .mock("POST", "/gists")
+            .match_body(Matcher::PartialJson(json!({
+                "public": true,
+                "files": { "badge.svg": { "content": "<svg/>" } }
+            })))
+            .with_status(201)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"id": "abc123", "html_url": "https://gist.github.com/abc123"}"#)
+            .create_async()
+            .await;
+
+        let client = GitHubClient::new(server.url().to_string(), "test_token".to_string()).await;
+        let gist = client.create_gist("Stars badge", true, "badge.svg", "<svg/>").await.unwrap();
+
+        assert_eq!(gist.id, "abc123");
+        assert_eq!(gist.html_url, "https://gist.github.com/abc123");
+        mock.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_update_gist() {
+        let mut server = Server::new_async().await;
+
+        let mock = server
+            .mock("PATCH", "/gists/abc123")
+            .match_body(Matcher::PartialJson(json!({
+                "files": { "badge.svg": { "content": "<svg/>" } }
+            })))
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"id": "abc123", "html_url": "https://gist.github.com/abc123"}"#)
+            .create_async()
+            .await;
+
+        let client = GitHubClient::new(server.url().to_string(), "test_token".to_string()).await;
+        let gist = client.update_gist("abc123", "badge.svg", "<svg/>").await.unwrap();
+
+        assert_eq!(gist.id, "abc123");
+        mock.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_get_gist_with_truncated_file() {
+        let mut server = Server::new_async().await;
+
+        let gist = json!({
+            "id": "abc123",
+            "html_url": "https://gist.github.com/abc123",
+            "files": {
+                "small.txt": { "raw_url": format!("{}/raw/small.txt", server.url()), "content": "rust-lang/rust\n", "truncated": false },
+                "large.txt": { "raw_url": format!("{}/raw/large.txt", server.url()), "content": "tokio", "truncated": true }
+            }
+        });
+        server
+            .mock("GET", "/gists/abc123")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(gist.to_string())
+            .create_async()
+            .await;
+        let raw = server
+            .mock("GET", "/raw/large.txt")
+            .with_status(200)
+            .with_body("tokio-rs/tokio\n")
+            .create_async()
+            .await;
+
+        let client = GitHubClient::new(server.url().to_string(), "test_token".to_string()).await;
+        let gist = client.get_gist("abc123").await.unwrap();
+
+        assert_eq!(client.gist_file_content(&gist.files["small.txt"]).await.unwrap(), "rust-lang/rust\n");
+        assert_eq!(client.gist_file_content(&gist.files["large.txt"]).await.unwrap(), "tokio-rs/tokio\n");
+        raw.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_create_gist_comment() {
+        let mut server = Server::new_async().await;
+
+        let mock = server
+            .mock("POST", "/gists/abc123/comments")
+            .match_body(Matcher::PartialJson(json!({ "body": "hello" })))
+            .with_status(201)
+            .create_async()
+            .await;
+
+        let client = GitHubClient::new(server.url().to_string(), "test_token".to_string()).await;
+        client.create_gist_comment("abc123", "hello").await.unwrap();
+
+        mock.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_update_gist_not_found() {
+        let mut server = Server::new_async().await;
+
+        server
+            .mock("PATCH", "/gists/missing")
+            .with_status(404)
+            .create_async()
+            .await;
+
+        let client = GitHubClient::new(server.url().to_string(), "test_token".to_string()).await;
+        assert!(client.update_gist("missing", "badge.svg", "<svg/>").await.is_err());
+    }
+}
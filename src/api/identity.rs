@@ -0,0 +1,226 @@
+//!
+//! The authenticated user's identity
+//! This module fetches `/user` and caches the login and id in the local store, keyed by a hash
+//! of the token so a different token never sees another account's identity.
+//!
+
+use crate::error::StarsError;
+use crate::api::capabilities::granted_scopes;
+use crate::api::client::GitHubClient;
+use crate::store::Store;
+use reqwest::{Method, StatusCode};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+const IDENTITY_ENTRY: &str = "identity";
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct Identity {
+    pub login: String,
+    pub id: u64,
+}
+
+/// The account behind the token in more detail, with what the token was granted
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct Account {
+    pub login: String,
+    pub id: u64,
+    pub name: Option<String>,
+    /// Only sent to the account's own tokens with access to its private profile
+    pub plan: Option<Plan>,
+    /// Scopes of a classic token, None for tokens whose permissions can't be listed
+    #[serde(default)]
+    pub scopes: Option<Vec<String>>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct Plan {
+    pub name: String,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+struct CachedIdentity {
+    token_hash: String,
+    identity: Identity,
+}
+
+fn token_hash(token: &str) -> String {
+    format!("{:x}", Sha256::digest(token.as_bytes()))
+}
+
+#[allow(async_fn_in_trait)]
+pub trait AuthenticatedUser {
+    /// Fetch the identity behind the current token
+    async fn authenticated_user(&self) -> Result<Identity, StarsError>;
+    /// The identity behind the current token, from the store when it was fetched with the same token
+    async fn cached_authenticated_user(&self, store: &Store) -> Result<Identity, StarsError>;
+    /// The account behind the current token and the token's scopes, always fetched
+    async fn account(&self) -> Result<Account, StarsError>;
+}
+
+impl AuthenticatedUser for GitHubClient {
+    async fn authenticated_user(&self) -> Result<Identity, StarsError> {
+        if self.is_anonymous() {
+            return Err(StarsError::Auth(String::from("A token is needed to look up the authenticated user")));
+        }
+
+        let url = format!("{}/user", self.api_url);
+        let response = self.send(self.request(Method::GET, &url)).await?;
+
+        match response.status() {
+            StatusCode::OK => Ok(response.json::<Identity>().await?),
+            StatusCode::UNAUTHORIZED => Err(StarsError::Auth(String::from("GitHub rejected the token"))),
+            _ => Err(StarsError::from_response(response).await),
+        }
+    }
+
+    async fn cached_authenticated_user(&self, store: &Store) -> Result<Identity, StarsError> {
+        let hash = token_hash(&self.token);
+
+        // A broken cache entry is treated like a missing one
+        if let Ok(Some(cached)) = store.load::<CachedIdentity>(IDENTITY_ENTRY) {
+            if cached.token_hash == hash {
+                return Ok(cached.identity);
+            }
+        }
+
+        let identity = self.authenticated_user().await?;
+        store.save(IDENTITY_ENTRY, &CachedIdentity { token_hash: hash, identity: identity.clone() })?;
+
+        Ok(identity)
+    }
+
+    async fn account(&self) -> Result<Account, StarsError> {
+        if self.is_anonymous() {
+            return Err(StarsError::Auth(String::from("No token is set, so requests are anonymous")));
+        }
+
+        let url = format!("{}/user", self.api_url);
+        let response = self.send(self.request(Method::GET, &url)).await?;
+
+        match response.status() {
+            StatusCode::OK => {
+                let scopes = granted_scopes(response.headers());
+                Ok(Account { scopes, ..response.json::<Account>().await? })
+            }
+            StatusCode::UNAUTHORIZED => Err(StarsError::Auth(String::from("GitHub rejected the token"))),
+            _ => Err(StarsError::from_response(response).await),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mockito::Server;
+    use tempfile::tempdir;
+
+    #[tokio::test]
+    async fn test_cached_authenticated_user() {
+        let mut server = Server::new_async().await;
+        let dir = tempdir().unwrap();
+        let store = Store::open(dir.path());
+
+        let mock = server
+            .mock("GET", "/user")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"login": "octocat", "id": 583231, "name": "The Octocat"}"#)
+            .expect(1)
+            .create_async()
+            .await;
+
+        let client = GitHubClient::new(server.url().to_string(), "test_token".to_string()).await;
+
+        let first = client.cached_authenticated_user(&store).await.unwrap();
+        let second = client.cached_authenticated_user(&store).await.unwrap();
+
+        assert_eq!(first, Identity { login: "octocat".to_string(), id: 583231 });
+        assert_eq!(first, second);
+        mock.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_cache_invalidated_when_token_changes() {
+        let mut server = Server::new_async().await;
+        let dir = tempdir().unwrap();
+        let store = Store::open(dir.path());
+
+        let mock = server
+            .mock("GET", "/user")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"login": "octocat", "id": 583231}"#)
+            .expect(2)
+            .create_async()
+            .await;
+
+        let client = GitHubClient::new(server.url().to_string(), "first_token".to_string()).await;
+        client.cached_authenticated_user(&store).await.unwrap();
+
+        let client = GitHubClient::new(server.url().to_string(), "second_token".to_string()).await;
+        client.cached_authenticated_user(&store).await.unwrap();
+
+        mock.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_cache_never_contains_the_token() {
+        let mut server = Server::new_async().await;
+        let dir = tempdir().unwrap();
+        let store = Store::open(dir.path());
+
+        server
+            .mock("GET", "/user")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"login": "octocat", "id": 583231}"#)
+            .create_async()
+            .await;
+
+        let client = GitHubClient::new(server.url().to_string(), "secret_token".to_string()).await;
+        client.cached_authenticated_user(&store).await.unwrap();
+
+        let contents = std::fs::read_to_string(dir.path().join("identity.json")).unwrap();
+        assert!(!contents.contains("secret_token"));
+    }
+
+    #[tokio::test]
+    async fn test_account() {
+        let mut server = Server::new_async().await;
+
+        server
+            .mock("GET", "/user")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_header("x-oauth-scopes", "public_repo, read:user")
+            .with_body(include_str!("../../fixtures/user.json"))
+            .create_async()
+            .await;
+
+        let client = GitHubClient::new(server.url().to_string(), "test_token".to_string()).await;
+        let account = client.account().await.unwrap();
+
+        assert_eq!(account.login, "octocat");
+        assert_eq!(account.name.as_deref(), Some("The Octocat"));
+        assert_eq!(account.plan, Some(Plan { name: "free".to_string() }));
+        assert_eq!(account.scopes, Some(vec!["public_repo".to_string(), "read:user".to_string()]));
+
+        let anonymous = GitHubClient::new(server.url().to_string(), String::new()).await;
+        assert!(matches!(anonymous.account().await, Err(StarsError::Auth(_))));
+    }
+
+    #[tokio::test]
+    async fn test_authenticated_user_rejected() {
+        let mut server = Server::new_async().await;
+
+        server
+            .mock("GET", "/user")
+            .with_status(401)
+            .create_async()
+            .await;
+
+        let client = GitHubClient::new(server.url().to_string(), "bad_token".to_string()).await;
+        assert!(client.authenticated_user().await.is_err());
+    }
+}
@@ -0,0 +1,187 @@
+//!
+//! Request metrics for the current run
+//! Counts the API requests a command makes, how many were served from a cache and how many list
+//! pages were fetched, so results can report what producing them cost. Each endpoint's calls,
+//! time and bytes are kept too, for the summary `-vv` prints.
+//!
+
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
+use reqwest::Url;
+
+static STARTED: OnceLock<Instant> = OnceLock::new();
+static CACHE_HITS: AtomicU64 = AtomicU64::new(0);
+static CACHE_MISSES: AtomicU64 = AtomicU64::new(0);
+static PAGES: AtomicU64 = AtomicU64::new(0);
+static ENDPOINTS: Mutex<BTreeMap<(String, String), EndpointMetrics>> = Mutex::new(BTreeMap::new());
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RequestMetrics {
+    pub cache_hits: u64,
+    /// Requests answered by GitHub rather than a cache
+    pub cache_misses: u64,
+    /// Pages of paginated lists fetched
+    pub pages: u64,
+}
+
+/// Calls to one endpoint of one host
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct EndpointMetrics {
+    /// Such as `api.github.com`, with the port when it isn't the default
+    pub host: String,
+    /// Method and path with names and ids left out, such as `GET /repos/{owner}/{repo}/releases`
+    pub endpoint: String,
+    pub calls: u64,
+    /// Waiting for responses, retries included
+    pub time: Duration,
+    /// Response bodies as their Content-Length gives them
+    pub bytes: u64,
+    pub cache_hits: u64,
+}
+
+/// Mark the start of the run, which `elapsed` measures from
+pub fn start() {
+    STARTED.get_or_init(Instant::now);
+}
+
+/// Time since `start`, zero if it was never called
+pub fn elapsed() -> Duration {
+    STARTED.get().map(Instant::elapsed).unwrap_or_default()
+}
+
+/// Count a response, served from a cache or by GitHub
+pub fn record_response(cache_hit: bool) {
+    let counter = if cache_hit { &CACHE_HITS } else { &CACHE_MISSES };
+    counter.fetch_add(1, Ordering::Relaxed);
+}
+
+/// Count a page of a paginated list
+pub fn record_page() {
+    PAGES.fetch_add(1, Ordering::Relaxed);
+}
+
+/// Count a call to `url`, which took `time` and returned `bytes`
+pub fn record_call(method: &str, url: &Url, time: Duration, bytes: u64, cache_hit: bool) {
+    let host = match (url.host_str(), url.port()) {
+        (Some(host), Some(port)) => format!("{}:{}", host, port),
+        (host, None) => host.unwrap_or_default().to_string(),
+        (None, Some(_)) => String::new(),
+    };
+    let endpoint = format!("{} {}", method, endpoint_of(url.path()));
+
+    let mut endpoints = ENDPOINTS.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+    let entry = endpoints.entry((host.clone(), endpoint.clone())).or_insert_with(|| EndpointMetrics {
+        host,
+        endpoint,
+        ..EndpointMetrics::default()
+    });
+    entry.calls += 1;
+    entry.time += time;
+    entry.bytes += bytes;
+    entry.cache_hits += u64::from(cache_hit);
+}
+
+/// Every endpoint called so far, the most called first
+pub fn endpoints() -> Vec<EndpointMetrics> {
+    let endpoints = ENDPOINTS.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+    let mut endpoints: Vec<EndpointMetrics> = endpoints.values().cloned().collect();
+    endpoints.sort_by(|a, b| b.calls.cmp(&a.calls).then_with(|| b.time.cmp(&a.time)));
+    endpoints
+}
+
+/// Average time GitHub took to answer the calls so far, None before the first one
+pub fn average_latency() -> Option<Duration> {
+    let endpoints = ENDPOINTS.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+    let calls: u64 = endpoints.values().map(|endpoint| endpoint.calls).sum();
+    let time: Duration = endpoints.values().map(|endpoint| endpoint.time).sum();
+    (calls > 0).then(|| time / u32::try_from(calls).unwrap_or(u32::MAX))
+}
+
+// A path with the parts naming a user, repository or object replaced by placeholders, so calls
+// for different repositories count as one endpoint
+fn endpoint_of(path: &str) -> String {
+    let segments: Vec<&str> = path.trim_matches('/').split('/').collect();
+    let mut endpoint = Vec::with_capacity(segments.len());
+    for (index, segment) in segments.iter().enumerate() {
+        let previous = index.checked_sub(1).map(|index| segments[index]);
+        let before_previous = index.checked_sub(2).map(|index| segments[index]);
+        let placeholder = match (before_previous, previous) {
+            (_, Some("repos" | "starred")) => Some("{owner}"),
+            (Some("repos" | "starred"), _) => Some("{repo}"),
+            (_, Some("users")) => Some("{user}"),
+            (_, Some("orgs")) => Some("{org}"),
+            (_, Some("gists")) => Some("{gist}"),
+            (_, Some("tags" | "branches" | "compare")) => Some("{ref}"),
+            _ if !segment.is_empty() && segment.chars().all(|c| c.is_ascii_digit()) => Some("{id}"),
+            _ => None,
+        };
+        endpoint.push(placeholder.unwrap_or(segment));
+    }
+    format!("/{}", endpoint.join("/"))
+}
+
+/// The counts so far
+pub fn snapshot() -> RequestMetrics {
+    RequestMetrics {
+        cache_hits: CACHE_HITS.load(Ordering::Relaxed),
+        cache_misses: CACHE_MISSES.load(Ordering::Relaxed),
+        pages: PAGES.load(Ordering::Relaxed),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_counts_grow() {
+        let before = snapshot();
+        record_response(true);
+        record_response(false);
+        record_page();
+        let after = snapshot();
+
+        // Other tests may count requests at the same time, so only growth is checked
+        assert!(after.cache_hits > before.cache_hits);
+        assert!(after.cache_misses > before.cache_misses);
+        assert!(after.pages > before.pages);
+    }
+
+    #[test]
+    fn test_record_call() {
+        let url = Url::parse("http://127.0.0.1:8080/repos/octocat/metrics-test/releases/42?per_page=100").unwrap();
+        record_call("GET", &url, Duration::from_millis(30), 512, false);
+        record_call("GET", &url, Duration::from_millis(10), 0, true);
+
+        let endpoints = endpoints();
+        let endpoint = endpoints
+            .iter()
+            .find(|endpoint| endpoint.endpoint == "GET /repos/{owner}/{repo}/releases/{id}" && endpoint.host == "127.0.0.1:8080")
+            .unwrap();
+        // Other tests may call the same endpoint, so only growth is checked
+        assert!(endpoint.calls >= 2);
+        assert!(endpoint.time >= Duration::from_millis(40));
+        assert!(endpoint.bytes >= 512);
+        assert!(endpoint.cache_hits >= 1);
+    }
+
+    #[test]
+    fn test_average_latency() {
+        let url = Url::parse("https://api.github.com/rate_limit").unwrap();
+        record_call("GET", &url, Duration::from_millis(20), 0, false);
+
+        assert!(average_latency().is_some());
+    }
+
+    #[test]
+    fn test_endpoint_of() {
+        assert_eq!(endpoint_of("/user/starred"), "/user/starred");
+        assert_eq!(endpoint_of("/user/starred/rust-lang/rust"), "/user/starred/{owner}/{repo}");
+        assert_eq!(endpoint_of("/repos/rust-lang/rust/tags"), "/repos/{owner}/{repo}/tags");
+        assert_eq!(endpoint_of("/users/octocat/repos"), "/users/{user}/repos");
+        assert_eq!(endpoint_of("/api/v3/repos/o/r/compare/v1...v2"), "/api/v3/repos/{owner}/{repo}/compare/{ref}");
+        assert_eq!(endpoint_of("/graphql"), "/graphql");
+    }
+}
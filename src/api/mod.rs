@@ -1,3 +1,23 @@
-mod repos;
-mod stars;
-mod client;
\ No newline at end of file
+pub mod repos;
+pub mod stars;
+pub mod client;
+pub mod capabilities;
+pub mod identity;
+pub mod gists;
+pub mod search;
+pub mod users;
+pub mod stats;
+pub mod checks;
+pub mod traffic;
+pub mod stargazers;
+pub mod releases;
+pub mod compare;
+pub mod refs;
+pub mod forks;
+pub mod pagination;
+pub mod rate_limit;
+pub mod metrics;
+pub mod cache;
+pub mod graphql;
+pub mod tuning;
+pub mod service;
\ No newline at end of file
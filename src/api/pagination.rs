@@ -0,0 +1,119 @@
+//!
+//! Following paginated list endpoints
+//! GitHub returns list results a page at a time and links to the next page in the `Link` header.
+//!
+
+/// Most items GitHub returns in one page
+pub const MAX_PER_PAGE: u32 = 100;
+
+/// How to walk a paginated list
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Pagination {
+    /// Items per page, GitHub's default of 30 when unset
+    pub per_page: Option<u32>,
+    /// Stop after this many pages, following every page when unset
+    pub max_pages: Option<u32>,
+}
+
+impl Pagination {
+    /// URL of the first page of `url`, which must not have a query string yet
+    pub fn first_page(&self, url: &str) -> String {
+        match self.per_page {
+            Some(per_page) => format!("{}?per_page={}", url, per_page),
+            None => url.to_string(),
+        }
+    }
+
+    /// Whether another page may be fetched after `fetched` pages
+    pub fn allows_more(&self, fetched: u32) -> bool {
+        self.max_pages.is_none_or(|max_pages| fetched < max_pages)
+    }
+}
+
+/// URL of the rel="next" link in a Link header, absent on the last page
+pub fn next_page(link: &str) -> Option<&str> {
+    link.split(',')
+        .find(|part| part.split(';').skip(1).any(|param| param.trim() == r#"rel="next""#))?
+        .split(';')
+        .next()?
+        .trim()
+        .strip_prefix('<')?
+        .strip_suffix('>')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    #[test]
+    fn test_next_page() {
+        let link = r#"<https://api.github.com/user/starred?page=1>; rel="prev", <https://api.github.com/user/starred?page=3>; rel="next", <https://api.github.com/user/starred?page=9>; rel="last""#;
+
+        assert_eq!(next_page(link), Some("https://api.github.com/user/starred?page=3"));
+        assert_eq!(next_page(r#"<https://api.github.com/user/starred?page=1>; rel="first""#), None);
+        assert_eq!(next_page(""), None);
+    }
+
+    #[test]
+    fn test_pagination() {
+        let pagination = Pagination { per_page: Some(50), max_pages: Some(2) };
+
+        assert_eq!(pagination.first_page("https://api.github.com/user/starred"), "https://api.github.com/user/starred?per_page=50");
+        assert!(pagination.allows_more(1));
+        assert!(!pagination.allows_more(2));
+        assert_eq!(Pagination::default().first_page("https://api.github.com/user/starred"), "https://api.github.com/user/starred");
+        assert!(Pagination::default().allows_more(1000));
+    }
+
+    fn page_url(page: u32) -> String {
+        format!("https://api.github.com/user/starred?per_page=100&page={}", page)
+    }
+
+    // The links GitHub sends with page `page` of `last`
+    fn links(page: u32, last: u32) -> Vec<String> {
+        let mut links = Vec::new();
+        if page > 1 {
+            links.push(format!(r#"<{}>; rel="prev""#, page_url(page - 1)));
+            links.push(format!(r#"<{}>; rel="first""#, page_url(1)));
+        }
+        if page < last {
+            links.push(format!(r#"<{}>; rel="next""#, page_url(page + 1)));
+            links.push(format!(r#"<{}>; rel="last""#, page_url(last)));
+        }
+        links
+    }
+
+    proptest! {
+        #[test]
+        fn test_following_next_links_visits_every_page_once(
+            last in 1u32..60,
+            max_pages in proptest::option::of(1u32..80),
+            rotation in any::<usize>(),
+            reversed in any::<bool>(),
+        ) {
+            let pagination = Pagination { per_page: Some(100), max_pages };
+            let mut visited = vec![1];
+
+            loop {
+                // Nothing promises the order of the links in the header
+                let mut links = links(*visited.last().unwrap(), last);
+                let count = links.len().max(1);
+                links.rotate_left(rotation % count);
+                if reversed {
+                    links.reverse();
+                }
+
+                match next_page(&links.join(", ")) {
+                    Some(next) if pagination.allows_more(visited.len() as u32) => {
+                        visited.push(next.rsplit("page=").next().unwrap().parse().unwrap());
+                    }
+                    _ => break,
+                }
+            }
+
+            let expected: Vec<u32> = (1..=max_pages.map_or(last, |max_pages| max_pages.min(last))).collect();
+            prop_assert_eq!(visited, expected);
+        }
+    }
+}
@@ -0,0 +1,234 @@
+//!
+//! Rate limit tracking
+//! GitHub reports the caller's remaining requests in the `x-ratelimit-*` headers of every
+//! response. The latest report is kept so long-running views can show it without asking again.
+//! A request rejected by a limit can wait for the limit to reset and be sent again.
+//! `/rate_limit` reports every limit at once, without counting against any of them.
+//!
+
+use crate::api::client::GitHubClient;
+use crate::error::StarsError;
+use console::Term;
+use reqwest::header::{HeaderMap, RETRY_AFTER};
+use reqwest::{Method, StatusCode};
+use serde::Deserialize;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Wait on a rejection that says neither when the limit resets nor when to retry, as GitHub advises
+const DEFAULT_RETRY_AFTER: Duration = Duration::from_secs(60);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+pub struct RateLimit {
+    pub limit: u64,
+    pub remaining: u64,
+    /// When the limit resets, in seconds since the Unix epoch
+    pub reset: u64,
+}
+
+impl RateLimit {
+    /// Read the rate limit from a response's headers, if GitHub sent one
+    pub fn from_headers(headers: &HeaderMap) -> Option<Self> {
+        let value = |name: &str| headers.get(name)?.to_str().ok()?.parse::<u64>().ok();
+        Some(RateLimit {
+            limit: value("x-ratelimit-limit")?,
+            remaining: value("x-ratelimit-remaining")?,
+            reset: value("x-ratelimit-reset")?,
+        })
+    }
+
+    /// Whole minutes until the limit resets, rounded up
+    pub fn minutes_until_reset(&self, now: u64) -> u64 {
+        self.reset.saturating_sub(now).div_ceil(60)
+    }
+
+    /// Short description such as `4999/5000 requests left, resets in 42m`, as of `now` in seconds
+    /// since the Unix epoch
+    pub fn summary(&self, now: u64) -> String {
+        format!("{}/{} requests left, resets in {}m", self.remaining, self.limit, self.minutes_until_reset(now))
+    }
+}
+
+/// The limits a token's requests count against, as `/rate_limit` reports them
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+pub struct RateLimits {
+    /// REST requests other than searches
+    pub core: RateLimit,
+    pub search: RateLimit,
+    /// Not reported by GitHub Enterprise Server versions without the GraphQL limit
+    pub graphql: Option<RateLimit>,
+}
+
+#[derive(Debug, Deserialize)]
+struct RateLimitResponse {
+    resources: RateLimits,
+}
+
+#[allow(async_fn_in_trait)]
+pub trait RateLimitStatus {
+    /// The core, search and GraphQL limits of the current token, or of the caller's IP address
+    /// without one
+    async fn rate_limits(&self) -> Result<RateLimits, StarsError>;
+}
+
+impl RateLimitStatus for GitHubClient {
+    async fn rate_limits(&self) -> Result<RateLimits, StarsError> {
+        let url = format!("{}/rate_limit", self.api_url);
+        let response = self.send(self.request(Method::GET, &url)).await?;
+
+        match response.status() {
+            StatusCode::OK => Ok(response.json::<RateLimitResponse>().await?.resources),
+            // GitHub Enterprise Server answers this way when rate limiting is turned off
+            StatusCode::NOT_FOUND => Err(StarsError::NotFound(String::from("Rate limiting is not enabled on this server"))),
+            _ => Err(StarsError::from_response(response).await),
+        }
+    }
+}
+
+static LATEST: Mutex<Option<RateLimit>> = Mutex::new(None);
+
+/// Remember the rate limit reported in `headers`
+pub fn record(headers: &HeaderMap) {
+    if let Some(rate_limit) = RateLimit::from_headers(headers) {
+        if let Ok(mut latest) = LATEST.lock() {
+            *latest = Some(rate_limit);
+        }
+    }
+}
+
+/// The most recently reported rate limit, if any response carried one
+pub fn latest() -> Option<RateLimit> {
+    *LATEST.lock().ok()?
+}
+
+/// How long a response rejected by a rate limit asks to wait before the request is sent again,
+/// `None` if it wasn't rejected by one. Secondary limits say so in `retry-after`, the primary
+/// limit by running out of remaining requests until its reset, `now` in seconds since the Unix epoch.
+pub fn retry_delay(status: StatusCode, headers: &HeaderMap, now: u64) -> Option<Duration> {
+    if status != StatusCode::FORBIDDEN && status != StatusCode::TOO_MANY_REQUESTS {
+        return None;
+    }
+
+    let value = |name: &str| headers.get(name)?.to_str().ok()?.parse::<u64>().ok();
+    match (value(RETRY_AFTER.as_str()), value("x-ratelimit-remaining"), value("x-ratelimit-reset")) {
+        (Some(seconds), _, _) => Some(Duration::from_secs(seconds)),
+        // A second past the reset, so a clock slightly behind GitHub's doesn't resend too early
+        (None, Some(0), Some(reset)) => Some(Duration::from_secs(reset.saturating_sub(now) + 1)),
+        (None, Some(0), None) => Some(DEFAULT_RETRY_AFTER),
+        _ if status == StatusCode::TOO_MANY_REQUESTS => Some(DEFAULT_RETRY_AFTER),
+        _ => None,
+    }
+}
+
+/// Sleep for `delay`, counting down on stderr
+pub async fn wait_for_reset(delay: Duration) {
+    let term = Term::stderr();
+    if !term.is_term() {
+        eprintln!("Rate limit exceeded, waiting {}s for it to reset", delay.as_secs());
+        tokio::time::sleep(delay).await;
+        return;
+    }
+
+    let deadline = Instant::now() + delay;
+    loop {
+        let left = deadline.saturating_duration_since(Instant::now());
+        if left.is_zero() {
+            break;
+        }
+        let _ = term.clear_line();
+        let _ = term.write_str(&format!("Rate limit exceeded, resuming in {}", format_countdown(left)));
+        tokio::time::sleep(left.min(Duration::from_secs(1))).await;
+    }
+    let _ = term.clear_line();
+}
+
+// Time left as minutes and seconds, e.g. 4:05
+fn format_countdown(left: Duration) -> String {
+    let secs = left.as_secs_f64().ceil() as u64;
+    format!("{}:{:02}", secs / 60, secs % 60)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mockito::Server;
+    use reqwest::header::HeaderValue;
+
+    #[test]
+    fn test_from_headers() {
+        let mut headers = HeaderMap::new();
+        headers.insert("x-ratelimit-limit", HeaderValue::from_static("5000"));
+        headers.insert("x-ratelimit-remaining", HeaderValue::from_static("4999"));
+        assert_eq!(RateLimit::from_headers(&headers), None);
+
+        headers.insert("x-ratelimit-reset", HeaderValue::from_static("4102444800"));
+        let rate_limit = RateLimit::from_headers(&headers).unwrap();
+        assert_eq!(rate_limit, RateLimit { limit: 5000, remaining: 4999, reset: 4102444800 });
+        assert_eq!(rate_limit.minutes_until_reset(4102444800 - 61), 2);
+        assert_eq!(rate_limit.minutes_until_reset(4102444800 + 10), 0);
+        assert_eq!(rate_limit.summary(4102444800 - 600), "4999/5000 requests left, resets in 10m");
+    }
+
+    #[test]
+    fn test_retry_delay() {
+        let mut headers = HeaderMap::new();
+        headers.insert("x-ratelimit-limit", HeaderValue::from_static("5000"));
+        headers.insert("x-ratelimit-remaining", HeaderValue::from_static("0"));
+        headers.insert("x-ratelimit-reset", HeaderValue::from_static("4102444800"));
+
+        let now = 4102444800 - 90;
+        assert_eq!(retry_delay(StatusCode::FORBIDDEN, &headers, now), Some(Duration::from_secs(91)));
+        assert_eq!(retry_delay(StatusCode::OK, &headers, now), None);
+        assert_eq!(retry_delay(StatusCode::FORBIDDEN, &headers, 4102444800 + 5), Some(Duration::from_secs(1)));
+
+        headers.insert(RETRY_AFTER, HeaderValue::from_static("30"));
+        assert_eq!(retry_delay(StatusCode::FORBIDDEN, &headers, now), Some(Duration::from_secs(30)));
+
+        // A 403 for a missing permission, with requests left
+        let mut headers = HeaderMap::new();
+        headers.insert("x-ratelimit-limit", HeaderValue::from_static("5000"));
+        headers.insert("x-ratelimit-remaining", HeaderValue::from_static("4000"));
+        headers.insert("x-ratelimit-reset", HeaderValue::from_static("4102444800"));
+        assert_eq!(retry_delay(StatusCode::FORBIDDEN, &headers, now), None);
+        assert_eq!(retry_delay(StatusCode::TOO_MANY_REQUESTS, &headers, now), Some(DEFAULT_RETRY_AFTER));
+    }
+
+    #[test]
+    fn test_format_countdown() {
+        assert_eq!(format_countdown(Duration::from_secs(245)), "4:05");
+        assert_eq!(format_countdown(Duration::from_millis(500)), "0:01");
+    }
+
+    #[tokio::test]
+    async fn test_rate_limits() {
+        let mut server = Server::new_async().await;
+        let mock = server
+            .mock("GET", "/rate_limit")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{
+                "resources": {
+                    "core": { "limit": 5000, "used": 1, "remaining": 4999, "reset": 1700000000 },
+                    "search": { "limit": 30, "used": 12, "remaining": 18, "reset": 1700000060 },
+                    "graphql": { "limit": 5000, "used": 0, "remaining": 5000, "reset": 1700003600 },
+                    "code_search": { "limit": 10, "used": 0, "remaining": 10, "reset": 1700000060 }
+                },
+                "rate": { "limit": 5000, "used": 1, "remaining": 4999, "reset": 1700000000 }
+            }"#)
+            .create_async()
+            .await;
+        server.mock("GET", "/api/v3/rate_limit").with_status(404).create_async().await;
+
+        let client = GitHubClient::new(server.url().to_string(), "test_token".to_string()).await;
+        let limits = client.rate_limits().await.unwrap();
+
+        assert_eq!(limits.core, RateLimit { limit: 5000, remaining: 4999, reset: 1700000000 });
+        assert_eq!(limits.search.remaining, 18);
+        assert_eq!(limits.graphql.unwrap().reset, 1700003600);
+        mock.assert_async().await;
+
+        let enterprise = GitHubClient::new(format!("{}/api/v3", server.url()), "test_token".to_string()).await;
+        let error = enterprise.rate_limits().await.unwrap_err();
+        assert_eq!(error.to_string(), "Rate limiting is not enabled on this server");
+    }
+}
@@ -0,0 +1,138 @@
+//!
+//! Tags and branches of a repository
+//! The list endpoints only return the commit SHA of each ref, so its date is fetched from the commit.
+//!
+
+use crate::error::StarsError;
+use std::fmt::{self, Display};
+use crate::api::client::GitHubClient;
+use reqwest::{Method, StatusCode};
+use serde::{Deserialize, Serialize};
+
+/// Refs fetched per page, GitHub's maximum
+pub const MAX_REFS_PER_PAGE: u32 = 100;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RefKind {
+    Tag,
+    Branch,
+}
+
+impl RefKind {
+    /// The plural, as used in API paths
+    pub fn plural(&self) -> &'static str {
+        match self {
+            RefKind::Tag => "tags",
+            RefKind::Branch => "branches",
+        }
+    }
+}
+
+impl Display for RefKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", match self {
+            RefKind::Tag => "tag",
+            RefKind::Branch => "branch",
+        })
+    }
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct GitRef {
+    pub name: String,
+    pub commit: RefCommit,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct RefCommit {
+    pub sha: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct CommitResponse {
+    commit: CommitDates,
+}
+
+#[derive(Debug, Deserialize)]
+struct CommitDates {
+    committer: Option<Signature>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Signature {
+    date: String,
+}
+
+#[allow(async_fn_in_trait)]
+pub trait Refs {
+    /// One page of tags or branches, in the order GitHub returns them
+    async fn list_refs(&self, owner: &str, repo: &str, kind: RefKind, page: u32, per_page: u32) -> Result<Vec<GitRef>, StarsError>;
+    /// When a commit was committed, e.g. `2024-03-01T10:00:00Z`
+    async fn commit_date(&self, owner: &str, repo: &str, sha: &str) -> Result<Option<String>, StarsError>;
+}
+
+impl Refs for GitHubClient {
+    async fn list_refs(&self, owner: &str, repo: &str, kind: RefKind, page: u32, per_page: u32) -> Result<Vec<GitRef>, StarsError> {
+        let url = format!(
+            "{}/repos/{}/{}/{}?per_page={}&page={}",
+            self.api_url, owner, repo, kind.plural(), per_page.min(MAX_REFS_PER_PAGE), page
+        );
+        let response = self.send(self.request(Method::GET, &url)).await?;
+
+        match response.status() {
+            StatusCode::OK => Ok(response.json::<Vec<GitRef>>().await?),
+            StatusCode::NOT_FOUND => Err(StarsError::NotFound(format!("Repository {}/{} not found", owner, repo))),
+            _ => Err(StarsError::from_response(response).await)
+        }
+    }
+
+    async fn commit_date(&self, owner: &str, repo: &str, sha: &str) -> Result<Option<String>, StarsError> {
+        let url = format!("{}/repos/{}/{}/commits/{}", self.api_url, owner, repo, sha);
+        let response = self.send(self.request(Method::GET, &url)).await?;
+
+        match response.status() {
+            StatusCode::OK => Ok(response.json::<CommitResponse>().await?.commit.committer.map(|committer| committer.date)),
+            _ => Err(StarsError::from_response(response).await)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mockito::{Matcher, Server};
+    use serde_json::json;
+
+    #[tokio::test]
+    async fn test_list_tags_with_dates() {
+        let mut server = Server::new_async().await;
+
+        server
+            .mock("GET", "/repos/tokio-rs/tokio/tags")
+            .match_query(Matcher::AllOf(vec![
+                Matcher::UrlEncoded("page".into(), "2".into()),
+                Matcher::UrlEncoded("per_page".into(), "100".into()),
+            ]))
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(json!([{ "name": "tokio-1.37.0", "commit": { "sha": "abc123" } }]).to_string())
+            .create_async()
+            .await;
+        server
+            .mock("GET", "/repos/tokio-rs/tokio/commits/abc123")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(json!({ "commit": { "committer": { "date": "2024-03-28T10:00:00Z" } } }).to_string())
+            .create_async()
+            .await;
+
+        let client = GitHubClient::new(server.url().to_string(), "test_token".to_string()).await;
+        let tags = client.list_refs("tokio-rs", "tokio", RefKind::Tag, 2, 500).await.unwrap();
+
+        assert_eq!(tags[0].name, "tokio-1.37.0");
+        assert_eq!(
+            client.commit_date("tokio-rs", "tokio", &tags[0].commit.sha).await.unwrap().as_deref(),
+            Some("2024-03-28T10:00:00Z")
+        );
+    }
+}
@@ -0,0 +1,296 @@
+//!
+//! Functions for working with releases
+//! This module contains functions to find releases and download their assets.
+//!
+
+use crate::error::StarsError;
+use std::time::Duration;
+use crate::api::client::{GitHubClient, MediaType};
+use crate::utils::checksum::{checksum_files_for, expected_checksum, verify, Verification};
+use reqwest::{Method, StatusCode};
+use serde::{Deserialize, Serialize};
+
+// Releases fetched per page when listing
+const RELEASES_PER_PAGE: u32 = 100;
+
+// Assets can be far larger than API responses, so downloads get longer than the client's default timeout
+const ASSET_DOWNLOAD_TIMEOUT: Duration = Duration::from_secs(600);
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct Release {
+    pub tag_name: String,
+    pub name: Option<String>,
+    pub body: Option<String>,
+    pub published_at: Option<String>,
+    #[serde(default)]
+    pub assets: Vec<ReleaseAsset>,
+}
+
+impl Release {
+    pub fn asset(&self, name: &str) -> Option<&ReleaseAsset> {
+        self.assets.iter().find(|asset| asset.name == name)
+    }
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct ReleaseAsset {
+    pub name: String,
+    pub size: u64,
+    /// API URL of the asset, which serves its contents when asked for `application/octet-stream`
+    pub url: String,
+    pub browser_download_url: String,
+}
+
+#[allow(async_fn_in_trait)]
+pub trait Releases {
+    async fn latest_release(&self, owner: &str, repo: &str) -> Result<Release, StarsError>;
+    async fn release_by_tag(&self, owner: &str, repo: &str, tag: &str) -> Result<Release, StarsError>;
+    async fn download_asset(&self, asset: &ReleaseAsset) -> Result<Vec<u8>, StarsError>;
+    /// One page of releases, newest first
+    async fn list_releases(&self, owner: &str, repo: &str, page: u32) -> Result<Vec<Release>, StarsError>;
+}
+
+impl GitHubClient {
+    async fn release(&self, owner: &str, repo: &str, path: &str) -> Result<Option<Release>, StarsError> {
+        let url = format!("{}/repos/{}/{}/releases/{}", self.api_url, owner, repo, path);
+        let response = self.send(self.request(Method::GET, &url)).await?;
+
+        match response.status() {
+            StatusCode::OK => Ok(Some(response.json::<Release>().await?)),
+            StatusCode::NOT_FOUND => Ok(None),
+            _ => Err(StarsError::from_response(response).await)
+        }
+    }
+}
+
+impl Releases for GitHubClient {
+    async fn latest_release(&self, owner: &str, repo: &str) -> Result<Release, StarsError> {
+        self.release(owner, repo, "latest")
+            .await?
+            .ok_or_else(|| StarsError::NotFound(format!("{}/{} has no published releases", owner, repo)))
+    }
+
+    async fn release_by_tag(&self, owner: &str, repo: &str, tag: &str) -> Result<Release, StarsError> {
+        self.release(owner, repo, &format!("tags/{}", tag))
+            .await?
+            .ok_or_else(|| StarsError::NotFound(format!("{}/{} has no release tagged {}", owner, repo, tag)))
+    }
+
+    async fn download_asset(&self, asset: &ReleaseAsset) -> Result<Vec<u8>, StarsError> {
+        let request = self
+            .request_as(Method::GET, &asset.url, MediaType::OctetStream)
+            .timeout(ASSET_DOWNLOAD_TIMEOUT);
+        let response = self.send(request).await?;
+
+        match response.status() {
+            StatusCode::OK => Ok(response.bytes().await?.to_vec()),
+            _ => Err(StarsError::from_response(response).await)
+        }
+    }
+
+    async fn list_releases(&self, owner: &str, repo: &str, page: u32) -> Result<Vec<Release>, StarsError> {
+        let url = format!("{}/repos/{}/{}/releases?per_page={}&page={}", self.api_url, owner, repo, RELEASES_PER_PAGE, page);
+        let response = self.send(self.request(Method::GET, &url)).await?;
+
+        match response.status() {
+            StatusCode::OK => Ok(response.json::<Vec<Release>>().await?),
+            _ => Err(StarsError::from_response(response).await)
+        }
+    }
+}
+
+/// Releases from the newest back to the one tagged `tag`, or all of them if no release has that tag
+pub async fn releases_back_to(client: &impl Releases, owner: &str, repo: &str, tag: &str) -> Result<Vec<Release>, StarsError> {
+    let mut releases = Vec::new();
+
+    for page in 1.. {
+        let batch = client.list_releases(owner, repo, page).await?;
+        let last_page = batch.len() < RELEASES_PER_PAGE as usize;
+        let found = batch.iter().any(|release| release.tag_name == tag);
+        releases.extend(batch);
+        if found || last_page {
+            break;
+        }
+    }
+
+    Ok(releases)
+}
+
+/// The releases after `from` up to and including `to`, newest first, or None unless both tags have
+/// releases with `to` the newer of the two
+pub fn releases_between<'a>(releases: &'a [Release], from: &str, to: &str) -> Option<&'a [Release]> {
+    let to_index = releases.iter().position(|release| release.tag_name == to)?;
+    let from_index = releases.iter().position(|release| release.tag_name == from)?;
+
+    (to_index < from_index).then(|| &releases[to_index..from_index])
+}
+
+/// Check a downloaded asset against the checksums published alongside it in the release
+pub async fn verify_download(
+    client: &impl Releases,
+    release: &Release,
+    asset: &ReleaseAsset,
+    bytes: &[u8],
+) -> Result<Verification, StarsError> {
+    let names: Vec<&str> = release.assets.iter().map(|asset| asset.name.as_str()).collect();
+
+    for name in checksum_files_for(&asset.name, &names) {
+        let Some(checksum_file) = release.asset(name) else { continue };
+        let contents = client.download_asset(checksum_file).await?;
+        if let Some(expected) = expected_checksum(&String::from_utf8_lossy(&contents), &asset.name) {
+            return Ok(verify(bytes, &expected, name, &contents));
+        }
+    }
+
+    Ok(Verification::Unavailable)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mockito::{Matcher, Server};
+    use serde_json::json;
+
+    #[tokio::test]
+    async fn test_latest_release_and_download() {
+        let mut server = Server::new_async().await;
+
+        server
+            .mock("GET", "/repos/BurntSushi/ripgrep/releases/latest")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(json!({
+                "tag_name": "14.1.0",
+                "name": "14.1.0",
+                "body": "Bug fixes",
+                "published_at": "2024-01-06T14:24:05Z",
+                "assets": [{
+                    "name": "ripgrep-14.1.0-x86_64-unknown-linux-musl.tar.gz",
+                    "size": 6,
+                    "url": format!("{}/repos/BurntSushi/ripgrep/releases/assets/1", server.url()),
+                    "browser_download_url": "https://github.com/BurntSushi/ripgrep/releases/download/14.1.0/ripgrep.tar.gz"
+                }]
+            }).to_string())
+            .create_async()
+            .await;
+        let download = server
+            .mock("GET", "/repos/BurntSushi/ripgrep/releases/assets/1")
+            .match_header("accept", Matcher::Exact("application/octet-stream".to_string()))
+            .with_status(200)
+            .with_body("binary")
+            .create_async()
+            .await;
+
+        let client = GitHubClient::new(server.url().to_string(), "test_token".to_string()).await;
+        let release = client.latest_release("BurntSushi", "ripgrep").await.unwrap();
+
+        assert_eq!(release.tag_name, "14.1.0");
+        assert_eq!(client.download_asset(&release.assets[0]).await.unwrap(), b"binary");
+        download.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_verify_download_with_shared_checksums() {
+        let mut server = Server::new_async().await;
+
+        let asset = |name: &str, id: u32| json!({
+            "name": name,
+            "size": 5,
+            "url": format!("{}/assets/{}", server.url(), id),
+            "browser_download_url": format!("https://example.com/{}", name)
+        });
+        let release: Release = serde_json::from_value(json!({
+            "tag_name": "v1.0.0",
+            "assets": [asset("tool-linux", 1), asset("tool-macos", 2), asset("SHA256SUMS", 3)]
+        })).unwrap();
+        server
+            .mock("GET", "/assets/3")
+            .with_status(200)
+            .with_body("2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824  tool-linux\n")
+            .create_async()
+            .await;
+
+        let client = GitHubClient::new(server.url().to_string(), "test_token".to_string()).await;
+
+        assert_eq!(
+            verify_download(&client, &release, &release.assets[0], b"hello").await.unwrap(),
+            Verification::Verified {
+                checksum_file: "SHA256SUMS".to_string(),
+                contents: b"2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824  tool-linux\n".to_vec(),
+            }
+        );
+        assert!(matches!(
+            verify_download(&client, &release, &release.assets[0], b"evil!").await.unwrap(),
+            Verification::Mismatch { .. }
+        ));
+        assert_eq!(
+            verify_download(&client, &release, &release.assets[1], b"hello").await.unwrap(),
+            Verification::Unavailable
+        );
+    }
+
+    fn release(tag: &str) -> Release {
+        serde_json::from_value(json!({ "tag_name": tag })).unwrap()
+    }
+
+    #[test]
+    fn test_releases_between() {
+        let releases = vec![release("v1.4"), release("v1.3"), release("v1.2"), release("v1.1")];
+        let tags = |releases: &[Release]| releases.iter().map(|release| release.tag_name.clone()).collect::<Vec<_>>();
+
+        assert_eq!(tags(releases_between(&releases, "v1.2", "v1.4").unwrap()), vec!["v1.4", "v1.3"]);
+        assert!(releases_between(&releases, "v1.4", "v1.2").is_none());
+        assert!(releases_between(&releases, "v1.0", "v1.4").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_releases_back_to_stops_at_tag() {
+        let mut server = Server::new_async().await;
+
+        let page: Vec<serde_json::Value> = (0..100).map(|n| json!({ "tag_name": format!("v2.{}", 99 - n) })).collect();
+        server
+            .mock("GET", "/repos/tokio-rs/tokio/releases")
+            .match_query(Matcher::UrlEncoded("page".into(), "1".into()))
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(json!(page).to_string())
+            .create_async()
+            .await;
+        server
+            .mock("GET", "/repos/tokio-rs/tokio/releases")
+            .match_query(Matcher::UrlEncoded("page".into(), "2".into()))
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(json!([{ "tag_name": "v1.1" }, { "tag_name": "v1.0" }]).to_string())
+            .create_async()
+            .await;
+        let page_3 = server
+            .mock("GET", "/repos/tokio-rs/tokio/releases")
+            .match_query(Matcher::UrlEncoded("page".into(), "3".into()))
+            .expect(0)
+            .create_async()
+            .await;
+
+        let client = GitHubClient::new(server.url().to_string(), "test_token".to_string()).await;
+        let releases = releases_back_to(&client, "tokio-rs", "tokio", "v1.1").await.unwrap();
+
+        assert_eq!(releases.len(), 102);
+        page_3.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_release_by_missing_tag() {
+        let mut server = Server::new_async().await;
+
+        server
+            .mock("GET", "/repos/BurntSushi/ripgrep/releases/tags/v0.0.0")
+            .with_status(404)
+            .create_async()
+            .await;
+
+        let client = GitHubClient::new(server.url().to_string(), "test_token".to_string()).await;
+        let error = client.release_by_tag("BurntSushi", "ripgrep", "v0.0.0").await.unwrap_err();
+
+        assert!(error.to_string().contains("no release tagged v0.0.0"));
+    }
+}
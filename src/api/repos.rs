@@ -1,95 +1,231 @@
-///
-/// Functions to interact with the GitHub API for repositories
-/// This module contains functions to get, list, get details of repositories, star, and unstar repositories.
-///
-
-use std::{error::Error, path::Path, fs, io::Write, process::Command};
-use crate::api::client::GitHubClient;
+//!
+//! Functions to interact with the GitHub API for repositories
+//! This module contains functions to get, list, get details of repositories, star, and unstar repositories.
+//!
+
+use std::{path::Path, fs, process::Command};
+use std::fmt::{self, Display};
+use crate::error::StarsError;
+use crate::api::client::{GitHubClient, MediaType};
+use crate::api::metrics;
+use crate::api::pagination::{next_page, Pagination, MAX_PER_PAGE};
+use crate::api::stars::Star;
+use crate::models::{RepoDetails, RepoSummary};
 use serde::{Deserialize, Serialize};
-use reqwest::StatusCode;
+use async_trait::async_trait;
+use reqwest::{Method, StatusCode};
 
+/// Reading, forking and cloning repositories
+///
+/// ```no_run
+/// use starts_fetcher::{GitHubClient, Repo, StarsError};
+///
+/// # async fn run() -> Result<(), StarsError> {
+/// let github = GitHubClient::new(String::from("https://api.github.com"), String::new()).await;
+/// let details = github.get_repo_details("rust-lang", "rust").await?;
+/// println!("{} by {} has {} stars", details.full_name, details.owner.login, details.stars);
+/// # Ok(())
+/// # }
+/// ```
+#[async_trait]
 pub trait Repo {
-    async fn get_repo(&self, owner: &str, repo: &str) -> Result<RepoResponse, Box<dyn Error>>;
-    async fn list_repos(&self) -> Result<Vec<RepoResponse>, Box<dyn Error>>;
-    async fn get_repo_details(&self, owner: &str, repo: &str) -> Result<RepoDetailsResponse, Box<dyn Error>>;
-    async fn download_repo(&self, owner: &str, repo: &str, path: Option<&Path>) -> Result<String, Box<dyn Error>>;
+    /// A repository's name, owner and star count
+    async fn get_repo(&self, owner: &str, repo: &str) -> Result<RepoSummary, StarsError>;
+    /// Every starred repository, following all pages of the largest size GitHub allows
+    async fn list_repos(&self) -> Result<Vec<RepoSummary>, StarsError> {
+        self.list_repos_paged(&Pagination { per_page: Some(MAX_PER_PAGE), max_pages: None }).await
+    }
+    /// Starred repositories, page by page as `pagination` allows
+    async fn list_repos_paged(&self, pagination: &Pagination) -> Result<Vec<RepoSummary>, StarsError>;
+    /// A repository with its full name, description, URL and topics
+    async fn get_repo_details(&self, owner: &str, repo: &str) -> Result<RepoDetails, StarsError>;
+    /// Clone a repository with git into `path`, or `owner-repo` in the current directory, returning
+    /// where it went
+    async fn download_repo(&self, owner: &str, repo: &str, path: Option<&Path>) -> Result<String, StarsError>;
+    async fn get_topics(&self, owner: &str, repo: &str) -> Result<Vec<String>, StarsError>;
+    /// Repositories owned by the authenticated user, across every page
+    async fn list_owned_repos(&self) -> Result<Vec<RepoSummary>, StarsError>;
+    /// Replace a repository's topics, returning the topics GitHub kept
+    async fn set_topics(&self, owner: &str, repo: &str, topics: &[String]) -> Result<Vec<String>, StarsError>;
+    /// Fork a repository to my account or `organization`; GitHub finishes creating it in the background
+    async fn fork_repo(&self, owner: &str, repo: &str, organization: Option<&str>) -> Result<ForkResponse, StarsError>;
 }
 
-#[derive(Debug, Deserialize, Serialize)]
-struct RepoResponse {
+/// Former name of [`RepoSummary`]
+pub type RepoResponse = RepoSummary;
+
+/// Former name of [`RepoDetails`]
+pub type RepoDetailsResponse = RepoDetails;
+
+/// The user or organization a repository belongs to, as GitHub embeds it in repositories
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[non_exhaustive]
+pub struct Owner {
+    pub login: String,
+    #[serde(default)]
     pub id: u64,
-    pub name: String,
-    pub owner: OwnerResponse,
-    #[serde(rename = "stargazers_count")]
-    pub stars: u64,
+    #[serde(rename = "type", default)]
+    pub kind: OwnerKind,
+    #[serde(default)]
+    pub avatar_url: Option<String>,
+    /// Profile page, e.g. `https://github.com/octocat`
+    #[serde(default)]
+    pub html_url: Option<String>,
 }
 
-#[derive(Debug, Deserialize, Serialize)]
-struct OwnerResponse {
-    pub login: String,
+impl Owner {
+    /// An owner known only by its login, as forges and older listings describe it
+    pub fn from_login(login: &str) -> Self {
+        Owner { login: login.to_string(), id: 0, kind: OwnerKind::default(), avatar_url: None, html_url: None }
+    }
+}
+
+/// What kind of account owns a repository
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[non_exhaustive]
+pub enum OwnerKind {
+    #[default]
+    User,
+    Organization,
+    Bot,
+    /// Accounts GitHub has added since, such as enterprise-managed users
+    #[serde(other)]
+    Other,
 }
 
+impl Display for OwnerKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", match self {
+            OwnerKind::User => "user",
+            OwnerKind::Organization => "organization",
+            OwnerKind::Bot => "bot",
+            OwnerKind::Other => "other",
+        })
+    }
+}
+
+/// Former name of [`Owner`], which only had the login
+pub type OwnerResponse = Owner;
+
+/// A fork as GitHub starts creating it
 #[derive(Debug, Deserialize, Serialize)]
-struct RepoDetailsResponse {
-    pub id: u64,
-    pub name: String,
-    pub owner: OwnerResponse,  // Changed from String to OwnerResponse
-    #[serde(rename = "stargazers_count")]
-    pub stars: u64,
-    pub description: Option<String>,
+#[non_exhaustive]
+pub struct ForkResponse {
+    pub full_name: String,
     pub html_url: String,
+    pub clone_url: String,
+    pub ssh_url: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct TopicsResponse {
+    names: Vec<String>,
 }
 
+#[async_trait]
 impl Repo for GitHubClient {
-    async fn get_repo(&self, owner: &str, repo: &str) -> Result<RepoResponse, Box<dyn Error>> {
+    async fn get_repo(&self, owner: &str, repo: &str) -> Result<RepoSummary, StarsError> {
         let url = format!("{}/repos/{}/{}", self.api_url, owner, repo);
-        let response = self.client
-            .get(&url)
-            .bearer_auth(&self.token)
-            .send()
-            .await?;
+        let response = self.send(self.request(Method::GET, &url)).await?;
 
         if response.status() == StatusCode::OK {
-            let repo_response = response.json::<RepoResponse>().await?;
+            let repo_response = response.json::<RepoSummary>().await?;
             Ok(repo_response)
         } else {
-            Err("Failed to fetch repository".into())
+            Err(StarsError::from_response(response).await)
         }
     }
 
-    async fn list_repos(&self) -> Result<Vec<RepoResponse>, Box<dyn Error>> {
-        let url = format!("{}/user/starred", self.api_url);
-        let response = self.client
-            .get(&url)
-            .bearer_auth(&self.token)
-            .send()
-            .await?;
+    async fn list_repos_paged(&self, pagination: &Pagination) -> Result<Vec<RepoSummary>, StarsError> {
+        Ok(self.list_starred(pagination)
+            .await?
+            .repos
+            .into_iter()
+            .map(RepoSummary::from)
+            .collect())
+    }
+
+    async fn get_repo_details(&self, owner: &str, repo: &str) -> Result<RepoDetails, StarsError> {
+        let url = format!("{}/repos/{}/{}", self.api_url, owner, repo);
+        let response = self.send(self.request_as(Method::GET, &url, MediaType::Topics)).await?;
 
         if response.status() == StatusCode::OK {
-            let repos = response.json::<Vec<RepoResponse>>().await?;
-            Ok(repos)
+            let repo_details = response.json::<RepoDetails>().await?;
+            Ok(repo_details)
         } else {
-            Err("Failed to list repositories".into())
+            Err(StarsError::from_response(response).await)
         }
     }
 
-    async fn get_repo_details(&self, owner: &str, repo: &str) -> Result<RepoDetailsResponse, Box<dyn Error>> {
-        let url = format!("{}/repos/{}/{}", self.api_url, owner, repo);
-        let response = self.client
-            .get(&url)
-            .bearer_auth(&self.token)
-            .send()
-            .await?;
+    async fn set_topics(&self, owner: &str, repo: &str, topics: &[String]) -> Result<Vec<String>, StarsError> {
+        let url = format!("{}/repos/{}/{}/topics", self.api_url, owner, repo);
+        let request = self
+            .request_as(Method::PUT, &url, MediaType::Topics)
+            .json(&serde_json::json!({ "names": topics }));
+        let response = self.send(request).await?;
+
+        match response.status() {
+            StatusCode::OK => Ok(response.json::<TopicsResponse>().await?.names),
+            StatusCode::FORBIDDEN | StatusCode::NOT_FOUND => {
+                Err(StarsError::Auth(format!("Cannot edit topics of {}/{}, it needs admin or push access", owner, repo)))
+            }
+            _ => Err(StarsError::from_response(response).await)
+        }
+    }
+
+    async fn fork_repo(&self, owner: &str, repo: &str, organization: Option<&str>) -> Result<ForkResponse, StarsError> {
+        let url = format!("{}/repos/{}/{}/forks", self.api_url, owner, repo);
+        let body = match organization {
+            Some(organization) => serde_json::json!({ "organization": organization }),
+            None => serde_json::json!({}),
+        };
+        let response = self.send(self.request(Method::POST, &url).json(&body)).await?;
+
+        // GitHub creates forks asynchronously and answers 202 with the fork it is creating
+        match response.status() {
+            StatusCode::ACCEPTED | StatusCode::OK => Ok(response.json::<ForkResponse>().await?),
+            StatusCode::NOT_FOUND => Err(StarsError::NotFound(format!("Repository {}/{} not found", owner, repo))),
+            _ => Err(StarsError::from_response(response).await)
+        }
+    }
+
+    async fn list_owned_repos(&self) -> Result<Vec<RepoSummary>, StarsError> {
+        let mut url = format!("{}/user/repos?type=owner&per_page={}", self.api_url, MAX_PER_PAGE);
+        let mut repos = Vec::new();
+        loop {
+            let response = self.send(self.request(Method::GET, &url)).await?;
+            if response.status() != StatusCode::OK {
+                return Err(StarsError::from_response(response).await);
+            }
+
+            let next = response
+                .headers()
+                .get("link")
+                .and_then(|value| value.to_str().ok())
+                .and_then(next_page)
+                .map(String::from);
+            repos.extend(response.json::<Vec<RepoSummary>>().await?);
+            metrics::record_page();
+
+            match next {
+                Some(next) => url = next,
+                None => return Ok(repos),
+            }
+        }
+    }
+
+    async fn get_topics(&self, owner: &str, repo: &str) -> Result<Vec<String>, StarsError> {
+        let url = format!("{}/repos/{}/{}/topics", self.api_url, owner, repo);
+        let response = self.send(self.request_as(Method::GET, &url, MediaType::Topics)).await?;
 
         if response.status() == StatusCode::OK {
-            let repo_details = response.json::<RepoDetailsResponse>().await?;
-            Ok(repo_details)
+            Ok(response.json::<TopicsResponse>().await?.names)
         } else {
-            Err("Failed to fetch repository details".into())
+            Err(StarsError::from_response(response).await)
         }
     }
 
-    async fn download_repo(&self, owner: &str, repo: &str, path: Option<&Path>) -> Result<String, Box<dyn Error>> {
+    async fn download_repo(&self, owner: &str, repo: &str, path: Option<&Path>) -> Result<String, StarsError> {
         // Use the default download path if none is specified
         let download_path = match path {
             Some(p) => p.to_path_buf(),
@@ -142,10 +278,27 @@ impl Repo for GitHubClient {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::config::ListBackend;
     use serde_json::json;
     use mockito::Server;
     use tempfile::tempdir;
 
+    #[test]
+    fn test_owner() {
+        let owner: Owner = serde_json::from_value(json!({
+            "login": "tokio-rs", "id": 20248544, "type": "Organization",
+            "avatar_url": "https://avatars.githubusercontent.com/u/20248544", "html_url": "https://github.com/tokio-rs"
+        }))
+        .unwrap();
+        assert_eq!(owner.kind, OwnerKind::Organization);
+        assert_eq!(owner.kind.to_string(), "organization");
+
+        let managed: Owner = serde_json::from_value(json!({ "login": "jdoe_acme", "type": "EnterpriseUserAccount" })).unwrap();
+        assert_eq!(managed.kind, OwnerKind::Other);
+        assert_eq!(managed.html_url, None);
+        assert_eq!(Owner::from_login("octocat").kind, OwnerKind::User);
+    }
+
     #[tokio::test]
     async fn test_get_repo() {
         // Create a new mock server
@@ -159,6 +312,7 @@ mod tests {
             .with_body(json!({
                 "id": 1296269,
                 "name": "hello-world",
+                "full_name": "octocat/hello-world",
                 "owner": {
                     "login": "octocat"
                 },
@@ -212,7 +366,7 @@ mod tests {
         let mut server = Server::new_async().await;
 
         let mock = server
-            .mock("GET", "/user/starred")
+            .mock("GET", "/user/starred?per_page=100")
             .with_status(200)
             .with_header("content-type", "application/json")
             .with_body(json!([
@@ -222,6 +376,9 @@ mod tests {
                     "owner": {
                         "login": "user1"
                     },
+                    "full_name": "user1/repo1",
+                    "description": null,
+                    "html_url": "https://github.com/user1/repo1",
                     "stargazers_count": 10
                 },
                 {
@@ -230,6 +387,9 @@ mod tests {
                     "owner": {
                         "login": "user2"
                     },
+                    "full_name": "user2/repo2",
+                    "description": null,
+                    "html_url": "https://github.com/user2/repo2",
                     "stargazers_count": 20
                 }
             ]).to_string())
@@ -257,12 +417,93 @@ mod tests {
         mock.assert_async().await;
     }
 
+    #[tokio::test]
+    async fn test_list_repos_follows_pages() {
+        let mut server = Server::new_async().await;
+
+        let repo = |id: u64| json!({
+            "id": id, "name": format!("repo{}", id), "full_name": format!("user/repo{}", id), "owner": { "login": "user" },
+            "description": null, "html_url": format!("https://github.com/user/repo{}", id), "stargazers_count": id
+        });
+        let first = server
+            .mock("GET", "/user/starred?per_page=2")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_header("link", &format!(r#"<{}/user/starred?per_page=2&page=2>; rel="next", <{}/user/starred?per_page=2&page=3>; rel="last""#, server.url(), server.url()))
+            .with_body(json!([repo(1), repo(2)]).to_string())
+            .expect(2)
+            .create_async()
+            .await;
+        let second = server
+            .mock("GET", "/user/starred?per_page=2&page=2")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_header("link", &format!(r#"<{}/user/starred?per_page=2&page=3>; rel="next""#, server.url()))
+            .with_body(json!([repo(3), repo(4)]).to_string())
+            .expect(2)
+            .create_async()
+            .await;
+        let third = server
+            .mock("GET", "/user/starred?per_page=2&page=3")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(json!([repo(5)]).to_string())
+            .create_async()
+            .await;
+
+        let client = GitHubClient::new(server.url().to_string(), "test_token".to_string()).await;
+
+        let all = client.list_repos_paged(&Pagination { per_page: Some(2), max_pages: None }).await.unwrap();
+        assert_eq!(all.iter().map(|repo| repo.id).collect::<Vec<_>>(), vec![1, 2, 3, 4, 5]);
+
+        let limited = client.list_repos_paged(&Pagination { per_page: Some(2), max_pages: Some(2) }).await.unwrap();
+        assert_eq!(limited.len(), 4);
+
+        first.assert_async().await;
+        second.assert_async().await;
+        third.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_list_repos_through_graphql() {
+        let mut server = Server::new_async().await;
+        let mock = server
+            .mock("POST", "/graphql")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(json!({ "data": { "viewer": { "starredRepositories": {
+                "pageInfo": { "hasNextPage": false, "endCursor": null },
+                "edges": [{
+                    "starredAt": "2024-05-01T12:00:00Z",
+                    "node": {
+                        "databaseId": 1296269, "name": "Hello-World", "description": null,
+                        "url": "https://github.com/octocat/Hello-World", "stargazerCount": 80, "forkCount": 9,
+                        "isArchived": false, "isFork": false, "owner": { "login": "octocat" },
+                        "primaryLanguage": null, "repositoryTopics": { "nodes": [] }
+                    }
+                }]
+            } } } }).to_string())
+            .create_async()
+            .await;
+
+        let client = GitHubClient::new(server.url().to_string(), "test_token".to_string())
+            .await
+            .with_backend(ListBackend::Graphql);
+        let repos = client.list_repos().await.unwrap();
+
+        assert_eq!(repos.len(), 1);
+        assert_eq!(repos[0].id, 1296269);
+        assert_eq!(repos[0].owner.login, "octocat");
+        assert_eq!(repos[0].stars, 80);
+        mock.assert_async().await;
+    }
+
     #[tokio::test]
     async fn test_list_repos_error() {
         let mut server = Server::new_async().await;
 
         let mock = server
-            .mock("GET", "/user/starred")
+            .mock("GET", "/user/starred?per_page=100")
             .with_status(401)
             .create_async()
             .await;
@@ -284,17 +525,20 @@ mod tests {
 
         let mock = server
             .mock("GET", "/repos/octocat/hello-world")
+            .match_header("accept", MediaType::Topics.as_str())
             .with_status(200)
             .with_header("content-type", "application/json")
             .with_body(json!({
                 "id": 1296269,
                 "name": "hello-world",
+                "full_name": "octocat/hello-world",
                 "owner": {
                     "login": "octocat"
                 },
                 "stargazers_count": 80,
                 "description": "My first repository",
-                "html_url": "https://github.com/octocat/hello-world"
+                "html_url": "https://github.com/octocat/hello-world",
+                "topics": ["octocat", "api"]
             }).to_string())
             .create_async()
             .await;
@@ -310,10 +554,12 @@ mod tests {
         let details = result.unwrap();
         assert_eq!(details.id, 1296269);
         assert_eq!(details.name, "hello-world");
+        assert_eq!(details.full_name, "octocat/hello-world");
         assert_eq!(details.owner.login, "octocat");
         assert_eq!(details.stars, 80);
         assert_eq!(details.description, Some("My first repository".to_string()));
         assert_eq!(details.html_url, "https://github.com/octocat/hello-world");
+        assert_eq!(details.topics, ["octocat", "api"]);
 
         mock.assert_async().await;
     }
@@ -339,6 +585,131 @@ mod tests {
         mock.assert_async().await;
     }
 
+    #[tokio::test]
+    async fn test_set_topics() {
+        let mut server = Server::new_async().await;
+
+        let mock = server
+            .mock("PUT", "/repos/octocat/Hello-World/topics")
+            .match_body(mockito::Matcher::Json(json!({ "names": ["cli", "rust"] })))
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"names": ["cli", "rust"]}"#)
+            .create_async()
+            .await;
+
+        let client = GitHubClient::new(server.url().to_string(), "test_token".to_string()).await;
+        let topics = client
+            .set_topics("octocat", "Hello-World", &["cli".to_string(), "rust".to_string()])
+            .await
+            .unwrap();
+
+        assert_eq!(topics, vec!["cli", "rust"]);
+        mock.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_fork_repo_into_org() {
+        let mut server = Server::new_async().await;
+
+        let mock = server
+            .mock("POST", "/repos/octocat/Hello-World/forks")
+            .match_body(mockito::Matcher::Json(json!({ "organization": "octo-org" })))
+            .with_status(202)
+            .with_header("content-type", "application/json")
+            .with_body(json!({
+                "full_name": "octo-org/Hello-World",
+                "html_url": "https://github.com/octo-org/Hello-World",
+                "clone_url": "https://github.com/octo-org/Hello-World.git",
+                "ssh_url": "git@github.com:octo-org/Hello-World.git"
+            }).to_string())
+            .create_async()
+            .await;
+
+        let client = GitHubClient::new(server.url().to_string(), "test_token".to_string()).await;
+        let fork = client.fork_repo("octocat", "Hello-World", Some("octo-org")).await.unwrap();
+
+        assert_eq!(fork.full_name, "octo-org/Hello-World");
+        assert_eq!(fork.clone_url, "https://github.com/octo-org/Hello-World.git");
+        mock.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_list_owned_repos() {
+        let mut server = Server::new_async().await;
+
+        let mock = server
+            .mock("GET", "/user/repos?type=owner&per_page=100")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(json!([{
+                "id": 1296269,
+                "name": "Hello-World",
+                "full_name": "octocat/Hello-World",
+                "owner": { "login": "octocat" },
+                "stargazers_count": 2789
+            }]).to_string())
+            .create_async()
+            .await;
+
+        let client = GitHubClient::new(server.url().to_string(), "test_token".to_string()).await;
+        let repos = client.list_owned_repos().await.unwrap();
+
+        assert_eq!(repos.len(), 1);
+        assert_eq!(repos[0].name, "Hello-World");
+        mock.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_list_owned_repos_follows_pages() {
+        let mut server = Server::new_async().await;
+        let repo = |name: &str| json!({ "id": 1, "name": name, "full_name": format!("octocat/{}", name), "owner": { "login": "octocat" }, "stargazers_count": 0 });
+
+        let first = server
+            .mock("GET", "/user/repos?type=owner&per_page=100")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_header("link", &format!(r#"<{}/user/repos?type=owner&per_page=100&page=2>; rel="next""#, server.url()))
+            .with_body(json!([repo("Hello-World")]).to_string())
+            .create_async()
+            .await;
+        let second = server
+            .mock("GET", "/user/repos?type=owner&per_page=100&page=2")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_header("link", &format!(r#"<{}/user/repos?type=owner&per_page=100&page=1>; rel="prev""#, server.url()))
+            .with_body(json!([repo("Spoon-Knife")]).to_string())
+            .create_async()
+            .await;
+
+        let client = GitHubClient::new(server.url().to_string(), "test_token".to_string()).await;
+        let repos = client.list_owned_repos().await.unwrap();
+
+        assert_eq!(repos.iter().map(|repo| repo.name.as_str()).collect::<Vec<_>>(), ["Hello-World", "Spoon-Knife"]);
+        first.assert_async().await;
+        second.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_get_topics() {
+        let mut server = Server::new_async().await;
+
+        let mock = server
+            .mock("GET", "/repos/tokio-rs/tokio/topics")
+            .match_header("accept", "application/vnd.github.mercy-preview+json")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"names": ["async", "rust"]}"#)
+            .create_async()
+            .await;
+
+        let client = GitHubClient::new(server.url().to_string(), "test_token".to_string()).await;
+        let topics = client.get_topics("tokio-rs", "tokio").await.unwrap();
+
+        assert_eq!(topics, vec!["async", "rust"]);
+        mock.assert_async().await;
+    }
+
     #[tokio::test]
     async fn test_download_repo() {
         // Skip this test if git is not installed
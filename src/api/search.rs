@@ -0,0 +1,218 @@
+//!
+//! Repository search
+//! This module wraps `/search/repositories`, which is how repositories outside my stars are discovered.
+//!
+
+use crate::error::StarsError;
+use crate::api::client::{GitHubClient, MediaType};
+use crate::api::repos::Owner;
+use reqwest::{Method, StatusCode};
+use serde::{Deserialize, Serialize};
+use std::str::FromStr;
+
+/// Names accepted by `search --sort`
+pub const SEARCH_SORTS: &[&str] = &["best-match", "stars", "forks", "updated"];
+
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct SearchRepo {
+    pub id: u64,
+    pub name: String,
+    pub full_name: String,
+    pub owner: Owner,
+    pub description: Option<String>,
+    pub html_url: String,
+    #[serde(rename = "stargazers_count")]
+    pub stars: u64,
+    #[serde(default)]
+    pub language: Option<String>,
+    #[serde(default)]
+    pub topics: Vec<String>,
+}
+
+/// Order of search results, always descending
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SearchSort {
+    /// GitHub's relevance ranking
+    #[default]
+    BestMatch,
+    Stars,
+    Forks,
+    /// Most recently pushed to
+    Updated,
+}
+
+impl SearchSort {
+    // Value of the sort parameter, None for GitHub's default ranking
+    fn param(&self) -> Option<&'static str> {
+        match self {
+            SearchSort::BestMatch => None,
+            SearchSort::Stars => Some("stars"),
+            SearchSort::Forks => Some("forks"),
+            SearchSort::Updated => Some("updated"),
+        }
+    }
+}
+
+impl FromStr for SearchSort {
+    type Err = String;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        match name {
+            "best-match" => Ok(SearchSort::BestMatch),
+            "stars" => Ok(SearchSort::Stars),
+            "forks" => Ok(SearchSort::Forks),
+            "updated" => Ok(SearchSort::Updated),
+            _ => Err(format!("Unknown sort {:?}, expected one of {}", name, SEARCH_SORTS.join(", "))),
+        }
+    }
+}
+
+/// A search query for `terms` with the `language:` and `stars:` qualifiers added when given, e.g.
+/// `stars` as `>1000` or `100..500`
+pub fn search_query(terms: &str, language: Option<&str>, stars: Option<&str>) -> String {
+    let mut query = terms.trim().to_string();
+    let qualifiers = [language.map(|language| ("language", language)), stars.map(|stars| ("stars", stars))];
+    for (name, value) in qualifiers.into_iter().flatten() {
+        if !query.is_empty() {
+            query.push(' ');
+        }
+        // Quoted so languages such as "Jupyter Notebook" stay one qualifier
+        if value.contains(' ') {
+            query.push_str(&format!("{}:\"{}\"", name, value));
+        } else {
+            query.push_str(&format!("{}:{}", name, value));
+        }
+    }
+    query
+}
+
+#[derive(Debug, Deserialize)]
+struct SearchResponse {
+    items: Vec<SearchRepo>,
+}
+
+#[allow(async_fn_in_trait)]
+pub trait Search {
+    /// Search repositories with GitHub's search syntax, most starred first
+    async fn search_repos(&self, query: &str, per_page: u32) -> Result<Vec<SearchRepo>, StarsError> {
+        self.search_repos_sorted(query, SearchSort::Stars, per_page).await
+    }
+    /// Search repositories with GitHub's search syntax, the first `per_page` results in `sort` order
+    async fn search_repos_sorted(&self, query: &str, sort: SearchSort, per_page: u32) -> Result<Vec<SearchRepo>, StarsError>;
+}
+
+impl Search for GitHubClient {
+    async fn search_repos_sorted(&self, query: &str, sort: SearchSort, per_page: u32) -> Result<Vec<SearchRepo>, StarsError> {
+        let url = format!("{}/search/repositories", self.api_url);
+        let mut request = self
+            .request_as(Method::GET, &url, MediaType::Topics)
+            .query(&[("q", query), ("per_page", &per_page.to_string())]);
+        if let Some(sort) = sort.param() {
+            request = request.query(&[("sort", sort)]);
+        }
+        let response = self.send(request).await?;
+
+        match response.status() {
+            StatusCode::OK => Ok(response.json::<SearchResponse>().await?.items),
+            StatusCode::UNPROCESSABLE_ENTITY => Err(format!("Invalid search query: {}", query).into()),
+            _ => Err(StarsError::from_response(response).await),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mockito::{Matcher, Server};
+    use serde_json::json;
+
+    #[tokio::test]
+    async fn test_search_repos() {
+        let mut server = Server::new_async().await;
+
+        let mock = server
+            .mock("GET", "/search/repositories")
+            .match_query(Matcher::AllOf(vec![
+                Matcher::UrlEncoded("q".into(), "topic:async".into()),
+                Matcher::UrlEncoded("sort".into(), "stars".into()),
+                Matcher::UrlEncoded("per_page".into(), "5".into()),
+            ]))
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(json!({
+                "total_count": 1,
+                "items": [{
+                    "id": 1,
+                    "name": "async-std",
+                    "full_name": "async-rs/async-std",
+                    "owner": { "login": "async-rs" },
+                    "description": "Async version of the Rust standard library",
+                    "html_url": "https://github.com/async-rs/async-std",
+                    "stargazers_count": 3900,
+                    "topics": ["async", "rust"]
+                }]
+            }).to_string())
+            .create_async()
+            .await;
+
+        let client = GitHubClient::new(server.url().to_string(), "test_token".to_string()).await;
+        let repos = client.search_repos("topic:async", 5).await.unwrap();
+
+        assert_eq!(repos.len(), 1);
+        assert_eq!(repos[0].full_name, "async-rs/async-std");
+        assert_eq!(repos[0].topics, vec!["async", "rust"]);
+        mock.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_search_repos_invalid_query() {
+        let mut server = Server::new_async().await;
+
+        server
+            .mock("GET", "/search/repositories")
+            .match_query(Matcher::Any)
+            .with_status(422)
+            .create_async()
+            .await;
+
+        let client = GitHubClient::new(server.url().to_string(), "test_token".to_string()).await;
+        assert!(client.search_repos("topic:", 5).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_search_repos_sorted() {
+        let mut server = Server::new_async().await;
+        let best_match = server
+            .mock("GET", "/search/repositories")
+            .match_query(Matcher::AllOf(vec![
+                Matcher::UrlEncoded("q".into(), "http client language:rust".into()),
+                Matcher::Regex("^q=[^&]*&per_page=10$".into()),
+            ]))
+            .with_status(200)
+            .with_body(json!({ "total_count": 0, "items": [] }).to_string())
+            .create_async()
+            .await;
+        let updated = server
+            .mock("GET", "/search/repositories")
+            .match_query(Matcher::UrlEncoded("sort".into(), "updated".into()))
+            .with_status(200)
+            .with_body(json!({ "total_count": 0, "items": [] }).to_string())
+            .create_async()
+            .await;
+
+        let client = GitHubClient::new(server.url().to_string(), "test_token".to_string()).await;
+        client.search_repos_sorted("http client language:rust", SearchSort::BestMatch, 10).await.unwrap();
+        client.search_repos_sorted("http client", "updated".parse().unwrap(), 10).await.unwrap();
+
+        best_match.assert_async().await;
+        updated.assert_async().await;
+        assert!("newest".parse::<SearchSort>().is_err());
+    }
+
+    #[test]
+    fn test_search_query() {
+        assert_eq!(search_query("http client", Some("rust"), Some(">1000")), "http client language:rust stars:>1000");
+        assert_eq!(search_query(" cli ", None, Some("100..500")), "cli stars:100..500");
+        assert_eq!(search_query("", Some("Jupyter Notebook"), None), r#"language:"Jupyter Notebook""#);
+    }
+}
@@ -0,0 +1,217 @@
+//!
+//! One entry point to GitHub
+//! `StarsService` puts the client together with the layers around it, the response cache, retries
+//! and waiting out rate limits, and groups what it does by area: `service.stars().list(..)`,
+//! `service.repos().details(..)`. Callers get what they need to show, such as the API URL or the
+//! kind of token, without reaching into the client.
+//!
+
+use std::path::Path;
+use crate::api::cache::ResponseCache;
+use crate::api::capabilities::TokenKind;
+use crate::api::client::{GitHubClient, RetryPolicy};
+use crate::api::pagination::Pagination;
+use crate::api::repos::{ForkResponse, Repo};
+use crate::api::stars::{Star, StarDate, StarredRepos};
+use crate::config::ListBackend;
+use crate::error::StarsError;
+use crate::models::{RepoDetails, RepoSummary};
+
+/// The GitHub client with its cache, retry and rate limit layers. Cloning it is cheap.
+#[derive(Clone)]
+pub struct StarsService {
+    github: GitHubClient,
+}
+
+impl StarsService {
+    /// A service for the API at `api_url`, anonymous if `token` is empty
+    pub async fn new(api_url: String, token: String) -> Self {
+        StarsService { github: GitHubClient::new(api_url, token).await }
+    }
+
+    /// List starred repositories through `backend`
+    pub fn with_backend(self, backend: ListBackend) -> Self {
+        StarsService { github: self.github.with_backend(backend) }
+    }
+
+    /// Retry transient failures following `retry`
+    pub fn with_retry(self, retry: RetryPolicy) -> Self {
+        StarsService { github: self.github.with_retry(retry) }
+    }
+
+    /// Revalidate JSON responses kept in `cache` instead of fetching them in full
+    pub fn with_cache(self, cache: ResponseCache) -> Self {
+        StarsService { github: self.github.with_cache(cache) }
+    }
+
+    /// Wait out rate limits instead of failing
+    pub fn with_wait_on_rate_limit(self, wait: bool) -> Self {
+        StarsService { github: self.github.with_wait_on_rate_limit(wait) }
+    }
+
+    /// Ask for descriptions in `language`, an `Accept-Language` value, or the server's default if none
+    pub fn with_accept_language(self, language: Option<String>) -> Self {
+        StarsService { github: self.github.with_accept_language(language) }
+    }
+
+    /// Listing, starring and unstarring my stars
+    pub fn stars(&self) -> Stars<'_> {
+        Stars { github: &self.github }
+    }
+
+    /// Reading, forking and cloning repositories
+    pub fn repos(&self) -> Repos<'_> {
+        Repos { github: &self.github }
+    }
+
+    /// The API the service talks to, e.g. `https://api.github.com`
+    pub fn api_url(&self) -> &str {
+        &self.github.api_url
+    }
+
+    /// What kind of token requests are sent with
+    pub fn token_kind(&self) -> TokenKind {
+        TokenKind::from_token(&self.github.token)
+    }
+
+    /// The client underneath, for the API areas the service doesn't group
+    pub fn client(&self) -> &GitHubClient {
+        &self.github
+    }
+}
+
+impl From<GitHubClient> for StarsService {
+    fn from(github: GitHubClient) -> Self {
+        StarsService { github }
+    }
+}
+
+/// My stars, from [`StarsService::stars`]
+pub struct Stars<'a> {
+    github: &'a GitHubClient,
+}
+
+impl Stars<'_> {
+    /// My starred repositories, most recently starred first, page by page as `pagination` allows
+    pub async fn list(&self, pagination: &Pagination) -> Result<StarredRepos, StarsError> {
+        self.github.list_starred(pagination).await
+    }
+
+    /// The URL [`star`](Self::star) sends a PUT to and [`unstar`](Self::unstar) a DELETE
+    pub fn url(&self, owner: &str, repo: &str) -> String {
+        self.github.starred_repo_url(owner, repo)
+    }
+
+    pub async fn star(&self, owner: &str, repo: &str) -> Result<(), StarsError> {
+        self.github.star_repo(owner, repo).await
+    }
+
+    pub async fn unstar(&self, owner: &str, repo: &str) -> Result<(), StarsError> {
+        self.github.unstar_repo(owner, repo).await
+    }
+
+    pub async fn is_starred(&self, owner: &str, repo: &str) -> Result<bool, StarsError> {
+        self.github.is_starred(owner, repo).await
+    }
+
+    /// How many repositories I starred
+    pub async fn count(&self) -> Result<u64, StarsError> {
+        self.github.starred_count().await
+    }
+
+    /// When each of my starred repositories was starred, most recent first
+    pub async fn dates(&self) -> Result<Vec<StarDate>, StarsError> {
+        self.github.starred_dates().await
+    }
+}
+
+/// Repositories, from [`StarsService::repos`]
+pub struct Repos<'a> {
+    github: &'a GitHubClient,
+}
+
+impl Repos<'_> {
+    /// A repository's name, owner and star count
+    pub async fn get(&self, owner: &str, repo: &str) -> Result<RepoSummary, StarsError> {
+        self.github.get_repo(owner, repo).await
+    }
+
+    /// A repository with its description, URL and topics
+    pub async fn details(&self, owner: &str, repo: &str) -> Result<RepoDetails, StarsError> {
+        self.github.get_repo_details(owner, repo).await
+    }
+
+    pub async fn topics(&self, owner: &str, repo: &str) -> Result<Vec<String>, StarsError> {
+        self.github.get_topics(owner, repo).await
+    }
+
+    /// Replace a repository's topics, returning the topics GitHub kept
+    pub async fn set_topics(&self, owner: &str, repo: &str, topics: &[String]) -> Result<Vec<String>, StarsError> {
+        self.github.set_topics(owner, repo, topics).await
+    }
+
+    /// Repositories I own
+    pub async fn owned(&self) -> Result<Vec<RepoSummary>, StarsError> {
+        self.github.list_owned_repos().await
+    }
+
+    /// Fork a repository to my account or `organization`
+    pub async fn fork(&self, owner: &str, repo: &str, organization: Option<&str>) -> Result<ForkResponse, StarsError> {
+        self.github.fork_repo(owner, repo, organization).await
+    }
+
+    /// Clone a repository with git into `path`, or `owner-repo` in the current directory
+    pub async fn download(&self, owner: &str, repo: &str, path: Option<&Path>) -> Result<String, StarsError> {
+        self.github.download_repo(owner, repo, path).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mockito::Server;
+
+    #[tokio::test]
+    async fn test_service() {
+        let mut server = Server::new_async().await;
+        let starred = server
+            .mock("GET", "/user/starred?per_page=100")
+            .with_status(200)
+            .with_body(r#"[{"id": 1, "name": "rust", "full_name": "rust-lang/rust", "owner": {"login": "rust-lang"}, "description": null, "html_url": "https://github.com/rust-lang/rust", "stargazers_count": 97412}]"#)
+            .create_async()
+            .await;
+        let details = server
+            .mock("GET", "/repos/rust-lang/rust")
+            .with_status(200)
+            .with_body(r#"{"id": 1, "name": "rust", "full_name": "rust-lang/rust", "owner": {"login": "rust-lang"}, "description": null, "html_url": "https://github.com/rust-lang/rust", "stargazers_count": 97412, "topics": ["compiler"]}"#)
+            .create_async()
+            .await;
+        let unstar = server
+            .mock("DELETE", "/user/starred/rust-lang/rust")
+            .with_status(204)
+            .create_async()
+            .await;
+
+        let service = StarsService::new(server.url(), "ghp_test".to_string()).await.with_retry(RetryPolicy::none());
+        let listed = service.stars().list(&Pagination { per_page: Some(100), max_pages: None }).await.unwrap();
+        let repo = service.repos().details("rust-lang", "rust").await.unwrap();
+        service.stars().unstar("rust-lang", "rust").await.unwrap();
+
+        assert_eq!(listed.repos[0].full_name, "rust-lang/rust");
+        assert_eq!(repo.topics, ["compiler"]);
+        assert_eq!(service.api_url(), server.url());
+        assert_eq!(service.stars().url("rust-lang", "rust"), format!("{}/user/starred/rust-lang/rust", server.url()));
+        assert_eq!(service.token_kind(), TokenKind::Classic);
+        starred.assert_async().await;
+        details.assert_async().await;
+        unstar.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_anonymous_service() {
+        let service = StarsService::new(String::from("https://api.github.com"), String::new()).await;
+
+        assert_eq!(service.token_kind(), TokenKind::Anonymous);
+        assert!(service.client().is_anonymous());
+    }
+}
@@ -0,0 +1,106 @@
+//!
+//! Who starred a repository, and when
+//! GitHub lists stargazers oldest first, so the most recent ones are on the last page.
+//!
+
+use crate::error::StarsError;
+use crate::api::client::{GitHubClient, MediaType};
+use crate::api::stars::last_page;
+use reqwest::{Method, StatusCode};
+use serde::{Deserialize, Serialize};
+
+const PAGE_SIZE: u32 = 100;
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct StargazerUser {
+    pub login: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct Stargazer {
+    pub starred_at: String,
+    pub user: StargazerUser,
+}
+
+#[allow(async_fn_in_trait)]
+pub trait Stargazers {
+    /// Up to the last 100 people who starred a repository, most recent first
+    async fn recent_stargazers(&self, owner: &str, repo: &str) -> Result<Vec<Stargazer>, StarsError>;
+}
+
+impl GitHubClient {
+    async fn stargazers_page(&self, owner: &str, repo: &str, page: u64) -> Result<(Vec<Stargazer>, Option<u64>), StarsError> {
+        let url = format!(
+            "{}/repos/{}/{}/stargazers?per_page={}&page={}",
+            self.api_url, owner, repo, PAGE_SIZE, page
+        );
+        let response = self.send(self.request_as(Method::GET, &url, MediaType::StarJson)).await?;
+
+        if response.status() != StatusCode::OK {
+            return Err(StarsError::from_response(response).await);
+        }
+
+        let last = response
+            .headers()
+            .get("link")
+            .and_then(|value| value.to_str().ok())
+            .and_then(last_page);
+
+        Ok((response.json::<Vec<Stargazer>>().await?, last))
+    }
+}
+
+impl Stargazers for GitHubClient {
+    async fn recent_stargazers(&self, owner: &str, repo: &str) -> Result<Vec<Stargazer>, StarsError> {
+        let (mut stargazers, last) = self.stargazers_page(owner, repo, 1).await?;
+        if let Some(last) = last.filter(|&last| last > 1) {
+            stargazers = self.stargazers_page(owner, repo, last).await?.0;
+        }
+
+        stargazers.reverse();
+        Ok(stargazers)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mockito::Server;
+    use serde_json::json;
+
+    #[tokio::test]
+    async fn test_recent_stargazers_reads_last_page() {
+        let mut server = Server::new_async().await;
+
+        let first = server
+            .mock("GET", "/repos/octocat/Hello-World/stargazers?per_page=100&page=1")
+            .match_header("accept", "application/vnd.github.star+json")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_header(
+                "link",
+                &format!(r#"<{}/repositories/1/stargazers?per_page=100&page=3>; rel="last""#, server.url()),
+            )
+            .with_body(json!([{ "starred_at": "2011-01-26T19:14:43Z", "user": { "login": "early" } }]).to_string())
+            .create_async()
+            .await;
+        let last = server
+            .mock("GET", "/repos/octocat/Hello-World/stargazers?per_page=100&page=3")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(json!([
+                { "starred_at": "2024-05-01T10:00:00Z", "user": { "login": "hubot" } },
+                { "starred_at": "2024-05-02T10:00:00Z", "user": { "login": "monalisa" } }
+            ]).to_string())
+            .create_async()
+            .await;
+
+        let client = GitHubClient::new(server.url().to_string(), "test_token".to_string()).await;
+        let stargazers = client.recent_stargazers("octocat", "Hello-World").await.unwrap();
+
+        let logins: Vec<&str> = stargazers.iter().map(|stargazer| stargazer.user.login.as_str()).collect();
+        assert_eq!(logins, vec!["monalisa", "hubot"]);
+        first.assert_async().await;
+        last.assert_async().await;
+    }
+}
@@ -1,65 +1,248 @@
-///
-/// Functions for handling stars
-/// This module contains functions to star and unstar repositories.
-///
+//!
+//! Functions for handling stars
+//! This module contains functions to list, star and unstar repositories.
+//!
 
-use std::error::Error;
-use crate::api::client::GitHubClient;
-use reqwest::StatusCode;
+use crate::error::StarsError;
+use crate::api::client::{GitHubClient, MediaType};
+use crate::api::graphql::StarredGraphQl;
+use crate::api::metrics;
+use crate::api::pagination::{next_page, Pagination, MAX_PER_PAGE};
+use crate::api::repos::{Owner, Repo};
+use crate::config::ListBackend;
+use async_trait::async_trait;
+use reqwest::{Method, StatusCode};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
 
+/// A starred repository with the fields the tool reads, parsed straight from the response body
+/// and shared by the commands and the terminal UI. Serializes to the REST shape.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[non_exhaustive]
+pub struct StarredRepo {
+    pub id: u64,
+    pub name: String,
+    pub full_name: String,
+    pub owner: Owner,
+    pub description: Option<String>,
+    pub html_url: String,
+    #[serde(rename = "stargazers_count", default)]
+    pub stars: u64,
+    #[serde(rename = "forks_count", default)]
+    pub forks: u64,
+    #[serde(default)]
+    pub language: Option<String>,
+    #[serde(default)]
+    pub topics: Vec<String>,
+    #[serde(default)]
+    pub archived: bool,
+    #[serde(default)]
+    pub fork: bool,
+    /// When the repository was last updated, such as `2024-05-14T06:19:56Z`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub updated_at: Option<String>,
+    /// When a commit was last pushed to any of its branches
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pushed_at: Option<String>,
+    /// When it was starred, only known when listed through GraphQL
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub starred_at: Option<String>,
+}
+
+/// Starred repositories as the API returns them, and how far the listing got
+#[derive(Debug, Default)]
+#[non_exhaustive]
+pub struct StarredRepos {
+    pub repos: Vec<StarredRepo>,
+    /// Pages fetched
+    pub pages: u32,
+    /// Whether `Pagination::max_pages` stopped the listing with pages left
+    pub truncated: bool,
+}
+
+/// When a repository was starred
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StarDate {
+    pub full_name: String,
+    /// Timestamp such as `2024-05-01T12:00:00Z`
+    pub starred_at: String,
+}
+
+// A starred repository as the star media type wraps it
+#[derive(Debug, Deserialize)]
+struct StarredEntry {
+    starred_at: String,
+    repo: StarredRepoName,
+}
+
+#[derive(Debug, Deserialize)]
+struct StarredRepoName {
+    full_name: String,
+}
+
+/// Listing, starring and unstarring repositories as the authenticated user
+#[async_trait]
 pub trait Star {
-    async fn star_repo(&self, owner: &str, repo: &str) -> Result<(), Box<dyn Error>>;
-    async fn unstar_repo(&self, owner: &str, repo: &str) -> Result<(), Box<dyn Error>>;
-    async fn is_starred(&self, owner: &str, repo: &str) -> Result<bool, Box<dyn Error>>;
+    /// My starred repositories, most recently starred first, page by page as `pagination`
+    /// allows and through the client's list backend
+    async fn list_starred(&self, pagination: &Pagination) -> Result<StarredRepos, StarsError>;
+    async fn star_repo(&self, owner: &str, repo: &str) -> Result<(), StarsError>;
+    async fn unstar_repo(&self, owner: &str, repo: &str) -> Result<(), StarsError>;
+    async fn is_starred(&self, owner: &str, repo: &str) -> Result<bool, StarsError>;
+    /// How many repositories I starred, in one request
+    async fn starred_count(&self) -> Result<u64, StarsError>;
+    /// When each of my starred repositories was starred, most recent first
+    async fn starred_dates(&self) -> Result<Vec<StarDate>, StarsError>;
+}
+
+// Page number of the rel="last" link in a Link header
+pub(crate) fn last_page(link: &str) -> Option<u64> {
+    link.split(',')
+        .find(|part| part.contains(r#"rel="last""#))?
+        .split(['?', '&', '>'])
+        .find_map(|param| param.strip_prefix("page="))?
+        .parse()
+        .ok()
 }
 
+impl GitHubClient {
+    // Where starring, unstarring and checking a star of a repository send their request
+    pub(crate) fn starred_repo_url(&self, owner: &str, repo: &str) -> String {
+        format!("{}/user/starred/{}/{}", self.api_url, owner, repo)
+    }
+}
+
+/// Everything the tool asks of GitHub, as one trait object so callers can hold a
+/// `Box<dyn StarsApi>` and swap in another provider or a mock
+pub trait StarsApi: Star + Repo + Send + Sync {}
+
+impl<T: Star + Repo + Send + Sync> StarsApi for T {}
+
+#[async_trait]
 impl Star for GitHubClient {
-    async fn star_repo(&self, owner: &str, repo: &str) -> Result<(), Box<dyn Error>> {
-        let url = format!("{}/user/starred/{}/{}", self.api_url, owner, repo);
-        let response = self.client
-            .put(&url)
-            .bearer_auth(&self.token)
-            .header("Content-Length", "0")
-            .send()
+    async fn list_starred(&self, pagination: &Pagination) -> Result<StarredRepos, StarsError> {
+        if self.backend == ListBackend::Graphql {
+            return self.list_starred_graphql(pagination).await;
+        }
+
+        let mut url = pagination.first_page(&format!("{}/user/starred", self.api_url));
+        let mut starred = StarredRepos::default();
+
+        loop {
+            let response = self.send(self.request(Method::GET, &url)).await?;
+            if response.status() != StatusCode::OK {
+                return Err(StarsError::from_response(response).await);
+            }
+
+            let next = response
+                .headers()
+                .get("link")
+                .and_then(|value| value.to_str().ok())
+                .and_then(next_page)
+                .map(String::from);
+            starred.repos.extend(response.json::<Vec<StarredRepo>>().await?);
+            starred.pages += 1;
+            metrics::record_page();
+
+            match next {
+                Some(next) if pagination.allows_more(starred.pages) => url = next,
+                Some(_) => return Ok(StarredRepos { truncated: true, ..starred }),
+                None => return Ok(starred),
+            }
+        }
+    }
+
+    async fn star_repo(&self, owner: &str, repo: &str) -> Result<(), StarsError> {
+        let url = self.starred_repo_url(owner, repo);
+        let response = self
+            .send(self.request(Method::PUT, &url).header("Content-Length", "0"))
             .await?;
 
         match response.status() {
             StatusCode::NO_CONTENT => Ok(()),
             StatusCode::OK => Ok(()),
-            _ => Err(format!("Failed to star repository: {}",
-                             response.text().await.unwrap_or_default()).into())
+            _ => Err(StarsError::from_response(response).await)
         }
     }
 
-    async fn unstar_repo(&self, owner: &str, repo: &str) -> Result<(), Box<dyn Error>> {
-        let url = format!("{}/user/starred/{}/{}", self.api_url, owner, repo);
-        let response = self.client
-            .delete(&url)
-            .bearer_auth(&self.token)
-            .send()
-            .await?;
+    async fn unstar_repo(&self, owner: &str, repo: &str) -> Result<(), StarsError> {
+        let url = self.starred_repo_url(owner, repo);
+        let response = self.send(self.request(Method::DELETE, &url)).await?;
 
         match response.status() {
             StatusCode::NO_CONTENT => Ok(()),
             StatusCode::OK => Ok(()),
-            _ => Err(format!("Failed to unstar repository: {}",
-                             response.text().await.unwrap_or_default()).into())
+            _ => Err(StarsError::from_response(response).await)
         }
     }
 
-    async fn is_starred(&self, owner: &str, repo: &str) -> Result<bool, Box<dyn Error>> {
-        let url = format!("{}/user/starred/{}/{}", self.api_url, owner, repo);
-        let response = self.client
-            .get(&url)
-            .bearer_auth(&self.token)
-            .send()
-            .await?;
+    async fn is_starred(&self, owner: &str, repo: &str) -> Result<bool, StarsError> {
+        let url = self.starred_repo_url(owner, repo);
+        let response = self.send(self.request(Method::GET, &url)).await?;
 
         match response.status() {
             StatusCode::NO_CONTENT => Ok(true),
             StatusCode::NOT_FOUND => Ok(false),
-            _ => Err(format!("Failed to check starred status: {}",
-                             response.text().await.unwrap_or_default()).into())
+            _ => Err(StarsError::from_response(response).await)
+        }
+    }
+
+    async fn starred_count(&self) -> Result<u64, StarsError> {
+        // With one repository per page, the last page number is the number of stars
+        let url = format!("{}/user/starred?per_page=1", self.api_url);
+        let response = self.send(self.request(Method::GET, &url)).await?;
+
+        if response.status() != StatusCode::OK {
+            return Err(StarsError::from_response(response).await);
+        }
+
+        let link = response
+            .headers()
+            .get("link")
+            .and_then(|value| value.to_str().ok())
+            .and_then(last_page);
+
+        match link {
+            Some(count) => Ok(count),
+            None => Ok(response.json::<Vec<Value>>().await?.len() as u64),
+        }
+    }
+
+    async fn starred_dates(&self) -> Result<Vec<StarDate>, StarsError> {
+        if self.backend == ListBackend::Graphql {
+            let starred = self.list_starred_graphql(&Pagination::default()).await?;
+            return Ok(starred
+                .repos
+                .into_iter()
+                .filter_map(|repo| Some(StarDate { starred_at: repo.starred_at?, full_name: repo.full_name }))
+                .collect());
+        }
+
+        let mut url = format!("{}/user/starred?per_page={}", self.api_url, MAX_PER_PAGE);
+        let mut dates = Vec::new();
+        loop {
+            let response = self.send(self.request_as(Method::GET, &url, MediaType::StarJson)).await?;
+            if response.status() != StatusCode::OK {
+                return Err(StarsError::from_response(response).await);
+            }
+
+            let next = response
+                .headers()
+                .get("link")
+                .and_then(|value| value.to_str().ok())
+                .and_then(next_page)
+                .map(String::from);
+            let entries: Vec<StarredEntry> = response.json().await?;
+            dates.extend(entries.into_iter().map(|entry| StarDate {
+                full_name: entry.repo.full_name,
+                starred_at: entry.starred_at,
+            }));
+            metrics::record_page();
+
+            match next {
+                Some(next) => url = next,
+                None => return Ok(dates),
+            }
         }
     }
 }
@@ -68,6 +251,7 @@ impl Star for GitHubClient {
 mod tests {
     use super::*;
     use mockito::Server;
+    use proptest::prelude::*;
 
     #[tokio::test]
     async fn test_star_repo() {
@@ -90,6 +274,58 @@ mod tests {
         mock.assert_async().await;
     }
 
+    #[tokio::test]
+    async fn test_list_starred_stops_at_max_pages() {
+        let mut server = Server::new_async().await;
+        let first = server
+            .mock("GET", "/user/starred?per_page=1")
+            .with_status(200)
+            .with_header("link", &format!(r#"<{}/user/starred?per_page=1&page=2>; rel="next""#, server.url()))
+            .with_body(r#"[{
+                "id": 724712, "name": "rust", "full_name": "rust-lang/rust", "owner": { "login": "rust-lang" },
+                "description": null, "html_url": "https://github.com/rust-lang/rust", "language": "Rust"
+            }]"#)
+            .create_async()
+            .await;
+        let second = server.mock("GET", "/user/starred?per_page=1&page=2").expect(0).create_async().await;
+
+        let client = GitHubClient::new(server.url().to_string(), "test_token".to_string()).await;
+        let starred = client.list_starred(&Pagination { per_page: Some(1), max_pages: Some(1) }).await.unwrap();
+
+        assert_eq!(starred.repos.len(), 1);
+        assert_eq!(starred.repos[0].language.as_deref(), Some("Rust"));
+        assert_eq!(starred.pages, 1);
+        assert!(starred.truncated);
+        first.assert_async().await;
+        second.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_star_repo_errors_are_typed() {
+        let mut server = Server::new_async().await;
+        server
+            .mock("PUT", "/user/starred/octocat/hello-world")
+            .with_status(403)
+            .with_header("x-ratelimit-remaining", "0")
+            .with_header("x-ratelimit-reset", "1700000000")
+            .with_body(r#"{"message": "API rate limit exceeded"}"#)
+            .create_async()
+            .await;
+        server
+            .mock("PUT", "/user/starred/octocat/missing")
+            .with_status(404)
+            .with_body(r#"{"message": "Not Found"}"#)
+            .create_async()
+            .await;
+
+        let client = GitHubClient::new(server.url().to_string(), "test_token".to_string()).await;
+
+        let error = client.star_repo("octocat", "hello-world").await.unwrap_err();
+        assert!(matches!(error, StarsError::RateLimited { reset: Some(1_700_000_000), .. }), "{:?}", error);
+        let error = client.star_repo("octocat", "missing").await.unwrap_err();
+        assert!(matches!(error, StarsError::NotFound(_)), "{:?}", error);
+    }
+
     #[tokio::test]
     async fn test_unstar_repo() {
         let mut server = Server::new_async().await;
@@ -133,6 +369,112 @@ mod tests {
         mock.assert_async().await;
     }
 
+    #[tokio::test]
+    async fn test_starred_dates() {
+        let mut server = Server::new_async().await;
+        let first = server
+            .mock("GET", "/user/starred?per_page=100")
+            .match_header("accept", MediaType::StarJson.as_str())
+            .with_status(200)
+            .with_header("link", &format!(r#"<{}/user/starred?per_page=100&page=2>; rel="next""#, server.url()))
+            .with_body(r#"[{ "starred_at": "2024-05-01T12:00:00Z", "repo": { "full_name": "rust-lang/rust" } }]"#)
+            .create_async()
+            .await;
+        let second = server
+            .mock("GET", "/user/starred?per_page=100&page=2")
+            .with_status(200)
+            .with_body(r#"[{ "starred_at": "2019-02-03T04:05:06Z", "repo": { "full_name": "vuejs/vue" } }]"#)
+            .create_async()
+            .await;
+
+        let client = GitHubClient::new(server.url().to_string(), "test_token".to_string()).await;
+        let dates = client.starred_dates().await.unwrap();
+
+        assert_eq!(dates, vec![
+            StarDate { full_name: "rust-lang/rust".to_string(), starred_at: "2024-05-01T12:00:00Z".to_string() },
+            StarDate { full_name: "vuejs/vue".to_string(), starred_at: "2019-02-03T04:05:06Z".to_string() },
+        ]);
+        first.assert_async().await;
+        second.assert_async().await;
+    }
+
+    #[test]
+    fn test_last_page() {
+        let link = r#"<https://api.github.com/user/starred?per_page=1&page=2>; rel="next", <https://api.github.com/user/starred?per_page=1&page=1234>; rel="last""#;
+        assert_eq!(last_page(link), Some(1234));
+        assert_eq!(last_page(r#"<https://api.github.com/user/starred?page=1>; rel="prev""#), None);
+    }
+
+    #[tokio::test]
+    async fn test_starred_count_from_link() {
+        let mut server = Server::new_async().await;
+
+        let mock = server
+            .mock("GET", "/user/starred?per_page=1")
+            .with_status(200)
+            .with_header("link", &format!(r#"<{}/user/starred?per_page=1&page=2>; rel="next", <{}/user/starred?per_page=1&page=57>; rel="last""#, server.url(), server.url()))
+            .with_body("[{}]")
+            .create_async()
+            .await;
+
+        let client = GitHubClient::new(
+            server.url().to_string(),
+            "test_token".to_string()
+        ).await;
+
+        assert_eq!(client.starred_count().await.unwrap(), 57);
+        mock.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_starred_count_single_page() {
+        let mut server = Server::new_async().await;
+
+        let mock = server
+            .mock("GET", "/user/starred?per_page=1")
+            .with_status(200)
+            .with_body("[{}]")
+            .create_async()
+            .await;
+
+        let client = GitHubClient::new(
+            server.url().to_string(),
+            "test_token".to_string()
+        ).await;
+
+        assert_eq!(client.starred_count().await.unwrap(), 1);
+        mock.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_stars_api_as_trait_object() {
+        let mut server = Server::new_async().await;
+        let starred = server
+            .mock("GET", "/user/starred/octocat/hello-world")
+            .with_status(204)
+            .create_async()
+            .await;
+        let repo = server
+            .mock("GET", "/repos/octocat/hello-world")
+            .with_status(200)
+            .with_body(r#"{"id": 1, "name": "hello-world", "full_name": "octocat/hello-world", "owner": {"login": "octocat"}, "stargazers_count": 80}"#)
+            .create_async()
+            .await;
+
+        let api: Box<dyn StarsApi> = Box::new(GitHubClient::new(server.url(), "test_token".to_string()).await);
+        // The futures are Send, so a boxed API can be used from spawned tasks
+        let (is_starred, stars) = tokio::spawn(async move {
+            (api.is_starred("octocat", "hello-world").await.unwrap(), api.get_repo("octocat", "hello-world").await.unwrap().stars)
+        })
+        .await
+        .unwrap();
+
+        assert!(is_starred);
+        assert_eq!(stars, 80);
+        starred.assert_async().await;
+        repo.assert_async().await;
+    }
+
     #[tokio::test]
     async fn test_is_starred_false() {
         let mut server = Server::new_async().await;
@@ -154,4 +496,60 @@ mod tests {
         assert!(!result.unwrap());
         mock.assert_async().await;
     }
-}
\ No newline at end of file
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(24))]
+
+        #[test]
+        fn test_list_starred_returns_every_page_once(page_sizes in prop::collection::vec(0usize..4, 1..8)) {
+            let runtime = tokio::runtime::Runtime::new().unwrap();
+            let (ids, pages, mocks) = runtime.block_on(async {
+                let mut server = Server::new_async().await;
+                let last = page_sizes.len();
+                let mut mocks = Vec::new();
+                let mut next_id = 0;
+
+                for (index, size) in page_sizes.iter().enumerate() {
+                    let page = index + 1;
+                    let path = match page {
+                        1 => String::from("/user/starred?per_page=3"),
+                        _ => format!("/user/starred?per_page=3&page={}", page),
+                    };
+                    let repos: Vec<Value> = (next_id..next_id + size)
+                        .map(|id| serde_json::json!({
+                            "id": id, "name": "repo", "full_name": format!("owner/repo-{}", id),
+                            "owner": { "login": "owner" }, "html_url": "https://github.com/owner/repo"
+                        }))
+                        .collect();
+                    next_id += size;
+
+                    let mut link = Vec::new();
+                    if page < last {
+                        link.push(format!(r#"<{}/user/starred?per_page=3&page={}>; rel="next""#, server.url(), page + 1));
+                        link.push(format!(r#"<{}/user/starred?per_page=3&page={}>; rel="last""#, server.url(), last));
+                    }
+                    if page > 1 {
+                        link.push(format!(r#"<{}/user/starred?per_page=3&page={}>; rel="prev""#, server.url(), page - 1));
+                    }
+
+                    let mut mock = server.mock("GET", path.as_str()).with_status(200).with_body(Value::from(repos).to_string());
+                    if !link.is_empty() {
+                        mock = mock.with_header("link", &link.join(", "));
+                    }
+                    mocks.push(mock.expect(1).create_async().await);
+                }
+
+                let client = GitHubClient::new(server.url(), "test_token".to_string()).await;
+                let starred = client.list_starred(&Pagination { per_page: Some(3), max_pages: None }).await.unwrap();
+                let ids: Vec<u64> = starred.repos.iter().map(|repo| repo.id).collect();
+                let mocks: Vec<bool> = mocks.iter().map(|mock| mock.matched()).collect();
+                (ids, starred.pages as usize, mocks)
+            });
+
+            let total = page_sizes.iter().sum::<usize>() as u64;
+            prop_assert_eq!(ids, (0..total).collect::<Vec<_>>());
+            prop_assert_eq!(pages, page_sizes.len());
+            prop_assert!(mocks.into_iter().all(|matched| matched), "every page is requested exactly once");
+        }
+    }
+}
@@ -0,0 +1,122 @@
+//!
+//! Repository statistics
+//! GitHub computes statistics in the background and answers 202 Accepted until they are ready,
+//! so callers retry with [`wait_for_commit_activity`].
+//!
+
+use crate::error::StarsError;
+use std::time::Duration;
+use crate::api::client::GitHubClient;
+use reqwest::{Method, StatusCode};
+use serde::{Deserialize, Serialize};
+
+/// Commits in one week, starting on Sunday
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct WeekActivity {
+    /// Unix timestamp of the start of the week
+    pub week: i64,
+    pub total: u64,
+    /// Commits per day, Sunday first
+    pub days: [u64; 7],
+}
+
+#[allow(async_fn_in_trait)]
+pub trait Stats {
+    /// Weekly commit counts for the last year, or None while GitHub is still computing them
+    async fn commit_activity(&self, owner: &str, repo: &str) -> Result<Option<Vec<WeekActivity>>, StarsError>;
+}
+
+impl Stats for GitHubClient {
+    async fn commit_activity(&self, owner: &str, repo: &str) -> Result<Option<Vec<WeekActivity>>, StarsError> {
+        let url = format!("{}/repos/{}/{}/stats/commit_activity", self.api_url, owner, repo);
+        let response = self.send(self.request(Method::GET, &url)).await?;
+
+        match response.status() {
+            StatusCode::OK => Ok(Some(response.json::<Vec<WeekActivity>>().await?)),
+            StatusCode::ACCEPTED => Ok(None),
+            // An empty repository has no statistics at all
+            StatusCode::NO_CONTENT => Ok(Some(Vec::new())),
+            StatusCode::NOT_FOUND => Err(StarsError::NotFound(format!("Repository {}/{} not found", owner, repo))),
+            _ => Err(StarsError::from_response(response).await),
+        }
+    }
+}
+
+/// Fetch commit activity, retrying while GitHub is still computing it
+pub async fn wait_for_commit_activity(
+    client: &impl Stats,
+    owner: &str,
+    repo: &str,
+    retries: u32,
+    delay: Duration,
+) -> Result<Vec<WeekActivity>, StarsError> {
+    for attempt in 0..=retries {
+        if let Some(activity) = client.commit_activity(owner, repo).await? {
+            return Ok(activity);
+        }
+        if attempt < retries {
+            tokio::time::sleep(delay).await;
+        }
+    }
+
+    Err("GitHub is still computing commit activity for this repository, try again in a minute".into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mockito::Server;
+    use serde_json::json;
+
+    #[tokio::test]
+    async fn test_commit_activity() {
+        let mut server = Server::new_async().await;
+
+        server
+            .mock("GET", "/repos/octocat/Hello-World/stats/commit_activity")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(json!([{ "days": [0, 3, 26, 20, 39, 1, 0], "total": 89, "week": 1336280400 }]).to_string())
+            .create_async()
+            .await;
+
+        let client = GitHubClient::new(server.url().to_string(), "test_token".to_string()).await;
+        let activity = client.commit_activity("octocat", "Hello-World").await.unwrap().unwrap();
+
+        assert_eq!(activity, vec![WeekActivity { week: 1336280400, total: 89, days: [0, 3, 26, 20, 39, 1, 0] }]);
+    }
+
+    #[tokio::test]
+    async fn test_wait_for_commit_activity_retries_while_computing() {
+        let mut server = Server::new_async().await;
+
+        let computing = server
+            .mock("GET", "/repos/octocat/Hello-World/stats/commit_activity")
+            .with_status(202)
+            .expect(2)
+            .create_async()
+            .await;
+
+        let client = GitHubClient::new(server.url().to_string(), "test_token".to_string()).await;
+        let result = wait_for_commit_activity(&client, "octocat", "Hello-World", 1, Duration::ZERO).await;
+
+        assert!(result.unwrap_err().to_string().contains("still computing"));
+        computing.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_commit_activity_empty_repo() {
+        let mut server = Server::new_async().await;
+
+        server
+            .mock("GET", "/repos/octocat/empty/stats/commit_activity")
+            .with_status(204)
+            .create_async()
+            .await;
+
+        let client = GitHubClient::new(server.url().to_string(), "test_token".to_string()).await;
+        let activity = wait_for_commit_activity(&client, "octocat", "empty", 3, Duration::ZERO).await.unwrap();
+
+        assert!(activity.is_empty());
+    }
+}
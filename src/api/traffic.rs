@@ -0,0 +1,130 @@
+//!
+//! Traffic for repositories I own
+//! GitHub only keeps the last 14 days of traffic and only shows it to people with push access.
+//!
+
+use crate::error::StarsError;
+use crate::api::client::GitHubClient;
+use reqwest::{Method, StatusCode};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+
+/// Views or clones on one day
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct DailyCount {
+    /// Start of the day, e.g. `2024-05-01T00:00:00Z`
+    pub timestamp: String,
+    pub count: u64,
+    pub uniques: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct Referrer {
+    pub referrer: String,
+    pub count: u64,
+    pub uniques: u64,
+}
+
+#[derive(Debug, Deserialize)]
+struct ViewsResponse {
+    views: Vec<DailyCount>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ClonesResponse {
+    clones: Vec<DailyCount>,
+}
+
+#[allow(async_fn_in_trait)]
+pub trait Traffic {
+    async fn views(&self, owner: &str, repo: &str) -> Result<Vec<DailyCount>, StarsError>;
+    async fn clones(&self, owner: &str, repo: &str) -> Result<Vec<DailyCount>, StarsError>;
+    async fn referrers(&self, owner: &str, repo: &str) -> Result<Vec<Referrer>, StarsError>;
+}
+
+impl GitHubClient {
+    async fn traffic<T: DeserializeOwned>(&self, owner: &str, repo: &str, path: &str) -> Result<T, StarsError> {
+        let url = format!("{}/repos/{}/{}/traffic/{}", self.api_url, owner, repo, path);
+        let response = self.send(self.request(Method::GET, &url)).await?;
+
+        match response.status() {
+            StatusCode::OK => Ok(response.json::<T>().await?),
+            StatusCode::FORBIDDEN | StatusCode::NOT_FOUND => {
+                Err(StarsError::Auth(format!("Traffic for {}/{} needs push access to the repository", owner, repo)))
+            }
+            _ => Err(StarsError::from_response(response).await),
+        }
+    }
+}
+
+impl Traffic for GitHubClient {
+    async fn views(&self, owner: &str, repo: &str) -> Result<Vec<DailyCount>, StarsError> {
+        Ok(self.traffic::<ViewsResponse>(owner, repo, "views").await?.views)
+    }
+
+    async fn clones(&self, owner: &str, repo: &str) -> Result<Vec<DailyCount>, StarsError> {
+        Ok(self.traffic::<ClonesResponse>(owner, repo, "clones").await?.clones)
+    }
+
+    async fn referrers(&self, owner: &str, repo: &str) -> Result<Vec<Referrer>, StarsError> {
+        self.traffic(owner, repo, "popular/referrers").await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mockito::Server;
+    use serde_json::json;
+
+    #[tokio::test]
+    async fn test_views_and_referrers() {
+        let mut server = Server::new_async().await;
+
+        server
+            .mock("GET", "/repos/octocat/Hello-World/traffic/views")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(json!({
+                "count": 14,
+                "uniques": 3,
+                "views": [{ "timestamp": "2024-05-01T00:00:00Z", "count": 14, "uniques": 3 }]
+            }).to_string())
+            .create_async()
+            .await;
+        server
+            .mock("GET", "/repos/octocat/Hello-World/traffic/popular/referrers")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(json!([{ "referrer": "Google", "count": 4, "uniques": 3 }]).to_string())
+            .create_async()
+            .await;
+
+        let client = GitHubClient::new(server.url().to_string(), "test_token".to_string()).await;
+
+        assert_eq!(
+            client.views("octocat", "Hello-World").await.unwrap(),
+            vec![DailyCount { timestamp: "2024-05-01T00:00:00Z".to_string(), count: 14, uniques: 3 }]
+        );
+        assert_eq!(
+            client.referrers("octocat", "Hello-World").await.unwrap(),
+            vec![Referrer { referrer: "Google".to_string(), count: 4, uniques: 3 }]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_clones_without_push_access() {
+        let mut server = Server::new_async().await;
+
+        server
+            .mock("GET", "/repos/rust-lang/rust/traffic/clones")
+            .with_status(403)
+            .create_async()
+            .await;
+
+        let client = GitHubClient::new(server.url().to_string(), "test_token".to_string()).await;
+        let error = client.clones("rust-lang", "rust").await.unwrap_err();
+
+        assert!(error.to_string().contains("push access"));
+    }
+}
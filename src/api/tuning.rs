@@ -0,0 +1,115 @@
+//!
+//! Tuning how many API reads run at once
+//! Rather than always running the configured number of reads at once, the limit follows what
+//! the run has measured: enough requests in flight to keep a steady rate given how long GitHub
+//! takes to answer, and one at a time when little of the rate limit is left.
+//!
+
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::Semaphore;
+use crate::api::metrics;
+use crate::api::rate_limit::{self, RateLimit};
+
+// Requests a second to aim for, well below GitHub's secondary limits
+const TARGET_RATE: f64 = 20.0;
+
+// Below this share of the rate limit, in percent, reads go one at a time
+const LOW_REMAINING_PERCENT: u64 = 10;
+
+/// Reads to run at once, at most `max`. Without a measured `latency` the first read runs alone.
+pub fn read_concurrency(max: usize, latency: Option<Duration>, rate_limit: Option<RateLimit>) -> usize {
+    let Some(latency) = latency else {
+        return 1;
+    };
+    // Little's law: requests in flight are the rate times the time each one takes
+    let mut limit = (latency.as_secs_f64() * TARGET_RATE).ceil() as usize;
+    if let Some(rate_limit) = rate_limit {
+        if rate_limit.remaining * 100 < rate_limit.limit * LOW_REMAINING_PERCENT {
+            limit = 1;
+        }
+        limit = limit.min(usize::try_from(rate_limit.remaining).unwrap_or(usize::MAX));
+    }
+    limit.clamp(1, max.max(1))
+}
+
+/// A semaphore for API reads whose permits follow `read_concurrency` as responses come in
+pub struct ReadLimit {
+    semaphore: Arc<Semaphore>,
+    max: usize,
+    adaptive: bool,
+    permits: usize,
+}
+
+impl ReadLimit {
+    /// Up to `max` reads at once, always `max` unless `adaptive`
+    pub fn new(max: usize, adaptive: bool) -> Self {
+        let permits = if adaptive { Self::target(max) } else { max.max(1) };
+        ReadLimit { semaphore: Arc::new(Semaphore::new(permits)), max, adaptive, permits }
+    }
+
+    pub fn semaphore(&self) -> Arc<Semaphore> {
+        self.semaphore.clone()
+    }
+
+    /// Reads allowed at once right now
+    pub fn permits(&self) -> usize {
+        self.permits
+    }
+
+    /// Adjust the permits to the latency and rate limit measured so far. Permits held by reads in
+    /// flight are only taken away once they're returned, at a later call.
+    pub fn retune(&mut self) {
+        if !self.adaptive {
+            return;
+        }
+        let target = Self::target(self.max);
+        if target > self.permits {
+            self.semaphore.add_permits(target - self.permits);
+            self.permits = target;
+        } else if target < self.permits {
+            self.permits -= self.semaphore.forget_permits(self.permits - target);
+        }
+    }
+
+    fn target(max: usize) -> usize {
+        read_concurrency(max, metrics::average_latency(), rate_limit::latest())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rate_limit(remaining: u64) -> Option<RateLimit> {
+        Some(RateLimit { limit: 5000, remaining, reset: 0 })
+    }
+
+    #[test]
+    fn test_read_concurrency_follows_latency() {
+        assert_eq!(read_concurrency(8, None, rate_limit(5000)), 1);
+        assert_eq!(read_concurrency(8, Some(Duration::from_millis(40)), rate_limit(5000)), 1);
+        assert_eq!(read_concurrency(8, Some(Duration::from_millis(200)), rate_limit(5000)), 4);
+        assert_eq!(read_concurrency(8, Some(Duration::from_secs(2)), None), 8);
+        assert_eq!(read_concurrency(0, Some(Duration::from_secs(2)), None), 1);
+    }
+
+    #[test]
+    fn test_read_concurrency_saves_the_rate_limit() {
+        let slow = Some(Duration::from_secs(1));
+
+        assert_eq!(read_concurrency(8, slow, rate_limit(600)), 8);
+        assert_eq!(read_concurrency(8, slow, rate_limit(499)), 1);
+        assert_eq!(read_concurrency(8, slow, Some(RateLimit { limit: 60, remaining: 3, reset: 0 })), 1);
+        assert_eq!(read_concurrency(8, slow, Some(RateLimit { limit: 20, remaining: 5, reset: 0 })), 5);
+    }
+
+    #[test]
+    fn test_fixed_read_limit() {
+        let mut limit = ReadLimit::new(3, false);
+        limit.retune();
+
+        assert_eq!(limit.permits(), 3);
+        assert_eq!(limit.semaphore().available_permits(), 3);
+    }
+}
@@ -0,0 +1,89 @@
+//!
+//! User and organization profiles
+//! This module fetches public profiles from `/users/{login}`.
+//!
+
+use crate::error::StarsError;
+use crate::api::client::GitHubClient;
+use crate::api::repos::Owner;
+use reqwest::{Method, StatusCode};
+use serde::{Deserialize, Serialize};
+
+/// An owner's public profile, the owner as repositories embed it plus what only the profile has
+#[derive(Debug, Deserialize, Serialize)]
+pub struct UserProfile {
+    #[serde(flatten)]
+    pub owner: Owner,
+    pub name: Option<String>,
+    pub bio: Option<String>,
+    pub company: Option<String>,
+    #[serde(default)]
+    pub followers: u64,
+    #[serde(default)]
+    pub public_repos: u64,
+}
+
+#[allow(async_fn_in_trait)]
+pub trait Users {
+    async fn get_user(&self, login: &str) -> Result<UserProfile, StarsError>;
+}
+
+impl Users for GitHubClient {
+    async fn get_user(&self, login: &str) -> Result<UserProfile, StarsError> {
+        let url = format!("{}/users/{}", self.api_url, login);
+        let response = self.send(self.request(Method::GET, &url)).await?;
+
+        match response.status() {
+            StatusCode::OK => Ok(response.json::<UserProfile>().await?),
+            StatusCode::NOT_FOUND => Err(StarsError::NotFound(format!("User {} not found", login))),
+            _ => Err(StarsError::from_response(response).await),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mockito::Server;
+
+    #[tokio::test]
+    async fn test_get_user() {
+        let mut server = Server::new_async().await;
+
+        let mock = server
+            .mock("GET", "/users/octocat")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(include_str!("../../fixtures/user.json"))
+            .create_async()
+            .await;
+
+        let client = GitHubClient::new(server.url().to_string(), "test_token".to_string()).await;
+        let user = client.get_user("octocat").await.unwrap();
+
+        assert_eq!(user.owner.login, "octocat");
+        assert_eq!(user.owner.id, 583231);
+        assert_eq!(user.owner.html_url.as_deref(), Some("https://github.com/octocat"));
+        assert_eq!(user.name.as_deref(), Some("The Octocat"));
+        assert_eq!(user.company.as_deref(), Some("@github"));
+        assert_eq!(user.bio, None);
+        assert_eq!(user.followers, 9999);
+        mock.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_get_user_not_found() {
+        let mut server = Server::new_async().await;
+
+        server
+            .mock("GET", "/users/ghost")
+            .with_status(404)
+            .create_async()
+            .await;
+
+        let client = GitHubClient::new(server.url().to_string(), "test_token".to_string()).await;
+        let error = client.get_user("ghost").await.unwrap_err();
+
+        assert_eq!(error.to_string(), "User ghost not found");
+    }
+}
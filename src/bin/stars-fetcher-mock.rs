@@ -0,0 +1,947 @@
+//!
+//! Fixture server serving canned GitHub API responses from `fixtures/`.
+//! It lets integration tests, plugin authors and demos run the CLI end-to-end offline:
+//!
+//!     stars-fetcher-mock --port 8787
+//!     GITHUB_API_URL=http://127.0.0.1:8787 GITHUB_TOKEN=fixture-token stars_fetcher list
+//!
+//! The token `fixture-token` is accepted, `rate-limited` gets a 403 with an exhausted
+//! rate limit, and any other token gets a 401. Public repository data is also served
+//! without a token.
+//!
+//! It answers every endpoint the client calls:
+//! - the user, their profile and owned repositories, and the rate limit
+//! - starred repositories, paginated with Link headers and with dates under the star media type,
+//!   and starring, unstarring and checking a star
+//! - the starred repositories query of the GraphQL API
+//! - repositories, their topics, forks, releases and release assets with SHA256SUMS, tags,
+//!   branches, commits, comparisons, CI checks, commit activity, stargazers and traffic
+//! - repository search by words and `language:`, `topic:`, `user:`/`org:` and `stars:`
+//! - creating, reading and updating gists, and commenting on them
+//!
+//! Data not in the fixtures, such as commit SHAs, activity or traffic, is made up from the
+//! repository so the same request always gets the same answer. Starring, unstarring, topics,
+//! forks and gists change what later requests see until the server stops.
+//!
+
+use clap::{App, Arg};
+use hyper::service::{make_service_fn, service_fn};
+use hyper::{header, Body, Method, Request, Response, Server, StatusCode};
+use reqwest::Url;
+use serde_json::{json, Value};
+use starts_fetcher::utils::checksum::{is_checksum_file, sha256_hex};
+use starts_fetcher::utils::recall::date_of;
+use std::cmp::Reverse;
+use std::collections::HashMap;
+use std::convert::Infallible;
+use std::error::Error;
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+const VALID_TOKEN: &str = "fixture-token";
+const RATE_LIMITED_TOKEN: &str = "rate-limited";
+
+const USER_FIXTURE: &str = include_str!("../../fixtures/user.json");
+const REPOS_FIXTURE: &str = include_str!("../../fixtures/repos.json");
+const STARRED_FIXTURE: &str = include_str!("../../fixtures/starred.json");
+const RELEASES_FIXTURE: &str = include_str!("../../fixtures/releases.json");
+
+const STAR_JSON: &str = "application/vnd.github.star+json";
+
+// The fixture stars were made a day apart from 2024-01-01T12:00:00Z
+const FIXTURE_STARRED_FROM: u64 = 1_704_110_400;
+
+// Weeks of commit activity start on Sundays, the first on 2023-05-07
+const ACTIVITY_FROM: i64 = 1_683_417_600;
+
+// Page size when the request doesn't ask for one, as on GitHub
+const DEFAULT_PER_PAGE: usize = 30;
+
+struct Fixtures {
+    user: Value,
+    // Repositories as GET /repos/{owner}/{repo} returns them, forks made through the server included
+    repos: Mutex<Vec<Value>>,
+    // Full names of the starred repositories with when they were starred, in starring order
+    starred: Mutex<Vec<(String, u64)>>,
+    // Releases by repository full name, newest first, with assets named but without contents
+    releases: HashMap<String, Vec<Value>>,
+    gists: Mutex<Vec<Value>>,
+}
+
+impl Fixtures {
+    fn load() -> Result<Self, Box<dyn Error>> {
+        let starred: Vec<String> = serde_json::from_str(STARRED_FIXTURE)?;
+        let releases: HashMap<String, Vec<Value>> = serde_json::from_str(RELEASES_FIXTURE)?;
+        Ok(Self {
+            user: serde_json::from_str(USER_FIXTURE)?,
+            repos: Mutex::new(serde_json::from_str(REPOS_FIXTURE)?),
+            starred: Mutex::new(
+                starred
+                    .into_iter()
+                    .zip(0..)
+                    .map(|(full_name, day)| (full_name, FIXTURE_STARRED_FROM + day * 86_400))
+                    .collect(),
+            ),
+            releases: releases.into_iter().map(|(full_name, releases)| (full_name.to_lowercase(), releases)).collect(),
+            gists: Mutex::new(Vec::new()),
+        })
+    }
+
+    fn login(&self) -> &str {
+        self.user["login"].as_str().unwrap_or_default()
+    }
+
+    fn find_repo(&self, owner: &str, repo: &str) -> Option<Value> {
+        let full_name = format!("{}/{}", owner, repo);
+        self.repos
+            .lock()
+            .unwrap()
+            .iter()
+            .find(|r| r["full_name"].as_str().is_some_and(|name| name.eq_ignore_ascii_case(&full_name)))
+            .cloned()
+    }
+
+    fn update_repo(&self, full_name: &str, update: impl FnOnce(&mut Value)) {
+        let mut repos = self.repos.lock().unwrap();
+        if let Some(repo) = repos.iter_mut().find(|repo| repo["full_name"] == full_name) {
+            update(repo);
+        }
+    }
+
+    // Whether the user may change or read the private data of a repository
+    fn owns(&self, repo: &Value) -> bool {
+        repo["owner"]["login"].as_str() == Some(self.login())
+    }
+
+    // Starred repositories with when they were starred, most recently starred first
+    fn starred_repos(&self) -> Vec<(u64, Value)> {
+        let starred = self.starred.lock().unwrap();
+        starred
+            .iter()
+            .rev()
+            .filter_map(|(full_name, starred_at)| {
+                let (owner, repo) = full_name.split_once('/')?;
+                Some((*starred_at, self.find_repo(owner, repo)?))
+            })
+            .collect()
+    }
+
+    fn releases_of(&self, repo: &Value) -> &[Value] {
+        let full_name = repo["full_name"].as_str().unwrap_or_default().to_lowercase();
+        self.releases.get(&full_name).map(Vec::as_slice).unwrap_or_default()
+    }
+}
+
+// Seconds since the Unix epoch
+fn unix_now() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or_default()
+}
+
+// `seconds` since the Unix epoch as GitHub writes times, such as `2024-01-01T12:00:00Z`
+fn timestamp(seconds: u64) -> String {
+    format!("{}T{:02}:{:02}:{:02}Z", date_of(seconds), seconds / 3600 % 24, seconds / 60 % 60, seconds % 60)
+}
+
+// A made-up but stable commit SHA for `git_ref` in `repo`
+fn commit_sha(repo: &Value, git_ref: &str) -> String {
+    sha256_hex(format!("{}@{}", repo["full_name"].as_str().unwrap_or_default(), git_ref).as_bytes())[..40].to_string()
+}
+
+fn json_response(status: StatusCode, body: &Value) -> Response<Body> {
+    Response::builder()
+        .status(status)
+        .header(header::CONTENT_TYPE, "application/json; charset=utf-8")
+        .header("x-ratelimit-limit", "5000")
+        .header("x-ratelimit-remaining", "4999")
+        .header("x-ratelimit-reset", "4102444800")
+        .header("x-oauth-scopes", "public_repo, read:user, gist")
+        .body(Body::from(body.to_string()))
+        .unwrap()
+}
+
+fn empty_response(status: StatusCode) -> Response<Body> {
+    Response::builder()
+        .status(status)
+        .body(Body::empty())
+        .unwrap()
+}
+
+fn bytes_response(body: Vec<u8>) -> Response<Body> {
+    Response::builder()
+        .status(StatusCode::OK)
+        .header(header::CONTENT_TYPE, "application/octet-stream")
+        .body(Body::from(body))
+        .unwrap()
+}
+
+fn not_found() -> Response<Body> {
+    json_response(StatusCode::NOT_FOUND, &json!({ "message": "Not Found" }))
+}
+
+fn forbidden(message: &str) -> Response<Body> {
+    json_response(StatusCode::FORBIDDEN, &json!({ "message": message }))
+}
+
+fn with_link(mut response: Response<Body>, link: Option<String>) -> Response<Body> {
+    if let Some(link) = link {
+        response.headers_mut().insert("link", link.parse().unwrap());
+    }
+    response
+}
+
+fn token_of(req: &Request<()>) -> Option<&str> {
+    let value = req.headers().get(header::AUTHORIZATION)?.to_str().ok()?;
+    value
+        .strip_prefix("Bearer ")
+        .or_else(|| value.strip_prefix("token "))
+        .map(str::trim)
+        .filter(|token| !token.is_empty())
+}
+
+fn accepts(req: &Request<()>, media_type: &str) -> bool {
+    req.headers()
+        .get(header::ACCEPT)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|accept| accept.contains(media_type))
+}
+
+// Where the client reached the server, to link back to it
+fn base_url(req: &Request<()>) -> String {
+    let host = req.headers().get(header::HOST).and_then(|value| value.to_str().ok());
+    format!("http://{}", host.unwrap_or("127.0.0.1"))
+}
+
+fn query(req: &Request<()>) -> HashMap<String, String> {
+    Url::parse(&format!("http://mock{}", req.uri()))
+        .map(|url| url.query_pairs().into_owned().collect())
+        .unwrap_or_default()
+}
+
+// The items on the page the query asks for, and the Link header pointing at the other pages
+fn paginate<T: Clone>(req: &Request<()>, items: &[T]) -> (Vec<T>, Option<String>) {
+    let query = query(req);
+    let number = |name: &str| query.get(name).and_then(|value| value.parse::<usize>().ok());
+    let per_page = number("per_page").unwrap_or(DEFAULT_PER_PAGE).clamp(1, 100);
+    let page = number("page").unwrap_or(1).max(1);
+    let last = items.len().div_ceil(per_page).max(1);
+
+    let url = |page: usize| format!("{}{}?per_page={}&page={}", base_url(req), req.uri().path(), per_page, page);
+    let mut links = Vec::new();
+    if page < last {
+        links.push(format!(r#"<{}>; rel="next""#, url(page + 1)));
+        links.push(format!(r#"<{}>; rel="last""#, url(last)));
+    }
+    if page > 1 {
+        links.push(format!(r#"<{}>; rel="first""#, url(1)));
+        links.push(format!(r#"<{}>; rel="prev""#, url(page - 1)));
+    }
+
+    let items = items.iter().skip((page - 1) * per_page).take(per_page).cloned().collect();
+    (items, (!links.is_empty()).then(|| links.join(", ")))
+}
+
+// What a release asset holds: a line naming it, or the checksums of the other assets
+fn asset_contents(release: &Value, name: &str) -> Vec<u8> {
+    let tag = release["tag_name"].as_str().unwrap_or_default();
+    if !is_checksum_file(name) {
+        return format!("{} {}\n", name, tag).into_bytes();
+    }
+    let assets = release["assets"].as_array().map(Vec::as_slice).unwrap_or_default();
+    assets
+        .iter()
+        .filter_map(|asset| asset["name"].as_str())
+        .filter(|other| !is_checksum_file(other))
+        .map(|other| format!("{}  {}\n", sha256_hex(&asset_contents(release, other)), other))
+        .collect::<String>()
+        .into_bytes()
+}
+
+// A release as the API returns it, with the download URLs of its assets on this server
+fn release_json(req: &Request<()>, repo: &Value, release: &Value) -> Value {
+    let full_name = repo["full_name"].as_str().unwrap_or_default();
+    let tag = release["tag_name"].as_str().unwrap_or_default();
+    let mut release = release.clone();
+    for asset in release["assets"].as_array_mut().into_iter().flatten() {
+        let name = asset["name"].as_str().unwrap_or_default().to_string();
+        asset["url"] = json!(format!("{}/repos/{}/releases/assets/{}", base_url(req), full_name, asset["id"]));
+        asset["browser_download_url"] = json!(format!("https://github.com/{}/releases/download/{}/{}", full_name, tag, name));
+    }
+    let sizes: Vec<usize> = release["assets"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .map(|asset| asset_contents(&release, asset["name"].as_str().unwrap_or_default()).len())
+        .collect();
+    for (asset, size) in release["assets"].as_array_mut().into_iter().flatten().zip(sizes) {
+        asset["size"] = json!(size);
+    }
+    release
+}
+
+// Tags are those of the releases, and the only branch is the default one
+fn refs_of(fixtures: &Fixtures, repo: &Value, kind: &str) -> Vec<Value> {
+    let names: Vec<String> = match kind {
+        "tags" => fixtures.releases_of(repo).iter().filter_map(|release| release["tag_name"].as_str()).map(String::from).collect(),
+        _ => vec![repo["default_branch"].as_str().unwrap_or("main").to_string()],
+    };
+    names
+        .into_iter()
+        .map(|name| json!({ "name": name, "commit": { "sha": commit_sha(repo, &name) } }))
+        .collect()
+}
+
+fn commit_json(repo: &Value, sha: &str, message: &str) -> Value {
+    let date = repo["pushed_at"].clone();
+    let author = json!({ "name": repo["owner"]["login"], "date": date });
+    json!({ "sha": sha, "commit": { "message": message, "author": author, "committer": author } })
+}
+
+// 52 weeks of made-up commit activity, the same for a repository every time
+fn commit_activity(repo: &Value) -> Value {
+    let seed = repo["id"].as_u64().unwrap_or_default();
+    let weeks: Vec<Value> = (0..52u64)
+        .map(|week| {
+            let days: Vec<u64> = (0..7u64).map(|day| (seed + week * 7 + day) % 5).collect();
+            json!({ "week": ACTIVITY_FROM + week as i64 * 604_800, "total": days.iter().sum::<u64>(), "days": days })
+        })
+        .collect();
+    json!(weeks)
+}
+
+// Who starred a repository: two regulars, and the user if they did
+fn stargazers_of(fixtures: &Fixtures, repo: &Value) -> Vec<(u64, String)> {
+    let mut stargazers = vec![(FIXTURE_STARRED_FROM - 86_400 * 30, String::from("hubot")), (FIXTURE_STARRED_FROM - 86_400 * 7, String::from("monalisa"))];
+    let starred = fixtures.starred.lock().unwrap();
+    if let Some((_, starred_at)) = starred.iter().find(|(full_name, _)| repo["full_name"] == full_name.as_str()) {
+        stargazers.push((*starred_at, fixtures.login().to_string()));
+    }
+    stargazers
+}
+
+fn traffic(kind: &str) -> Option<Value> {
+    let days = |counts: [(u64, u64); 3]| -> Vec<Value> {
+        counts
+            .iter()
+            .zip(0..)
+            .map(|((count, uniques), day)| json!({ "timestamp": timestamp(FIXTURE_STARRED_FROM - 43_200 + day * 86_400), "count": count, "uniques": uniques }))
+            .collect()
+    };
+    match kind {
+        "views" => Some(json!({ "count": 60, "uniques": 17, "views": days([(25, 8), (14, 5), (21, 7)]) })),
+        "clones" => Some(json!({ "count": 9, "uniques": 4, "clones": days([(4, 2), (0, 0), (5, 3)]) })),
+        "popular/referrers" => Some(json!([
+            { "referrer": "github.com", "count": 32, "uniques": 9 },
+            { "referrer": "news.ycombinator.com", "count": 11, "uniques": 6 },
+        ])),
+        _ => None,
+    }
+}
+
+// Whether a repository has `stars`, given as in `stars:>100`, `stars:10..50` or `stars:42`
+fn stars_match(range: &str, stars: u64) -> bool {
+    let number = |value: &str| value.parse::<u64>().ok();
+    if let Some(n) = range.strip_prefix(">=").and_then(number) {
+        stars >= n
+    } else if let Some(n) = range.strip_prefix('>').and_then(number) {
+        stars > n
+    } else if let Some(n) = range.strip_prefix("<=").and_then(number) {
+        stars <= n
+    } else if let Some(n) = range.strip_prefix('<').and_then(number) {
+        stars < n
+    } else if let Some((low, high)) = range.split_once("..") {
+        number(low).is_some_and(|low| stars >= low) && number(high).is_some_and(|high| stars <= high)
+    } else {
+        number(range) == Some(stars)
+    }
+}
+
+fn search(fixtures: &Fixtures, query: &HashMap<String, String>) -> Value {
+    let q = query.get("q").map(String::as_str).unwrap_or_default().to_lowercase();
+    let text = |value: &Value| value.as_str().unwrap_or_default().to_lowercase();
+
+    let mut items: Vec<Value> = fixtures
+        .repos
+        .lock()
+        .unwrap()
+        .iter()
+        .filter(|repo| {
+            q.split_whitespace().all(|term| match term.split_once(':') {
+                Some(("language", language)) => text(&repo["language"]) == language.trim_matches('"'),
+                Some(("topic", topic)) => repo["topics"].as_array().is_some_and(|topics| topics.iter().any(|t| text(t) == topic)),
+                Some(("user" | "org", owner)) => text(&repo["owner"]["login"]) == owner,
+                Some(("stars", range)) => stars_match(range, repo["stargazers_count"].as_u64().unwrap_or_default()),
+                _ => text(&repo["full_name"]).contains(term) || text(&repo["description"]).contains(term),
+            })
+        })
+        .cloned()
+        .collect();
+
+    // Best match has no meaning here, so it sorts by stars like the default does
+    match query.get("sort").map(String::as_str) {
+        Some("updated") => items.sort_by_key(|repo| Reverse(text(&repo["updated_at"]))),
+        Some("forks") => items.sort_by_key(|repo| Reverse(repo["forks_count"].as_u64())),
+        _ => items.sort_by_key(|repo| Reverse(repo["stargazers_count"].as_u64())),
+    }
+    let per_page = query.get("per_page").and_then(|value| value.parse().ok()).unwrap_or(DEFAULT_PER_PAGE);
+    let total = items.len();
+    items.truncate(per_page);
+
+    json!({ "total_count": total, "incomplete_results": false, "items": items })
+}
+
+// A repository as the starred repositories query of the GraphQL API describes it
+fn graphql_node(repo: &Value) -> Value {
+    let topics: Vec<Value> = repo["topics"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .map(|topic| json!({ "topic": { "name": topic } }))
+        .collect();
+    json!({
+        "databaseId": repo["id"],
+        "name": repo["name"],
+        "description": repo["description"],
+        "url": repo["html_url"],
+        "stargazerCount": repo["stargazers_count"],
+        "forkCount": repo["forks_count"],
+        "isArchived": repo["archived"],
+        "isFork": repo["fork"],
+        "updatedAt": repo["updated_at"],
+        "pushedAt": repo["pushed_at"],
+        "owner": {
+            "__typename": repo["owner"]["type"],
+            "login": repo["owner"]["login"],
+            "databaseId": repo["owner"]["id"],
+            "avatarUrl": repo["owner"]["avatar_url"],
+            "url": repo["owner"]["html_url"],
+        },
+        "primaryLanguage": repo["language"].as_str().map(|name| json!({ "name": name })),
+        "repositoryTopics": { "nodes": topics },
+    })
+}
+
+// Answers the starred repositories query, with the cursor being the number of stars already listed
+fn graphql(fixtures: &Fixtures, body: &[u8]) -> Response<Body> {
+    let Ok(request) = serde_json::from_slice::<Value>(body) else {
+        return json_response(StatusCode::BAD_REQUEST, &json!({ "message": "Problems parsing JSON" }));
+    };
+    if !request["query"].as_str().is_some_and(|query| query.contains("starredRepositories")) {
+        return json_response(StatusCode::OK, &json!({
+            "errors": [{ "message": "stars-fetcher-mock only answers the starred repositories query" }]
+        }));
+    }
+
+    let starred = fixtures.starred_repos();
+    let first = request["variables"]["first"].as_u64().unwrap_or(100) as usize;
+    let after = request["variables"]["after"].as_str().and_then(|cursor| cursor.parse::<usize>().ok()).unwrap_or(0);
+    let end = (after + first).min(starred.len());
+    let edges: Vec<Value> = starred[after.min(end)..end]
+        .iter()
+        .map(|(starred_at, repo)| json!({ "starredAt": timestamp(*starred_at), "node": graphql_node(repo) }))
+        .collect();
+
+    json_response(StatusCode::OK, &json!({
+        "data": { "viewer": { "starredRepositories": {
+            "pageInfo": { "hasNextPage": end < starred.len(), "endCursor": end.to_string() },
+            "edges": edges,
+        } } }
+    }))
+}
+
+// Files of a gist as the API returns them, from `{ name: { content } }`
+fn gist_files(req: &Request<()>, id: &str, files: &Value, into: &mut Value) {
+    for (name, file) in files.as_object().into_iter().flatten() {
+        if file.is_null() {
+            into.as_object_mut().map(|files| files.remove(name));
+            continue;
+        }
+        into[name] = json!({
+            "filename": name,
+            "raw_url": format!("{}/gists/{}/raw/{}", base_url(req), id, name),
+            "content": file["content"],
+            "truncated": false,
+        });
+    }
+}
+
+fn gists(req: &Request<()>, body: &[u8], fixtures: &Fixtures, segments: &[&str]) -> Response<Body> {
+    let request: Value = serde_json::from_slice(body).unwrap_or_default();
+    let mut gists = fixtures.gists.lock().unwrap();
+
+    match (req.method(), segments) {
+        (&Method::POST, []) => {
+            let id = format!("{:032x}", gists.len() + 1);
+            let mut gist = json!({
+                "id": id,
+                "html_url": format!("https://gist.github.com/{}", id),
+                "description": request["description"],
+                "public": request["public"],
+                "files": {},
+                "comments": [],
+            });
+            gist_files(req, &id, &request["files"], &mut gist["files"]);
+            gists.push(gist.clone());
+            json_response(StatusCode::CREATED, &gist)
+        }
+        (method, [id, rest @ ..]) => {
+            let Some(gist) = gists.iter_mut().find(|gist| gist["id"] == *id) else {
+                return not_found();
+            };
+            match (method, rest) {
+                (&Method::GET, []) => json_response(StatusCode::OK, gist),
+                (&Method::PATCH, []) => {
+                    gist_files(req, id, &request["files"], &mut gist["files"]);
+                    json_response(StatusCode::OK, gist)
+                }
+                (&Method::POST, ["comments"]) => {
+                    let comment = json!({ "id": gist["comments"].as_array().map_or(0, Vec::len) + 1, "body": request["body"] });
+                    gist["comments"].as_array_mut().unwrap().push(comment.clone());
+                    json_response(StatusCode::CREATED, &comment)
+                }
+                (&Method::GET, ["raw", name]) => match gist["files"][*name]["content"].as_str() {
+                    Some(content) => bytes_response(content.as_bytes().to_vec()),
+                    None => not_found(),
+                },
+                _ => not_found(),
+            }
+        }
+        _ => not_found(),
+    }
+}
+
+// Starring, unstarring and checking a star of a repository
+fn star(req: &Request<()>, fixtures: &Fixtures, owner: &str, repo: &str) -> Response<Body> {
+    let Some(found) = fixtures.find_repo(owner, repo) else {
+        return not_found();
+    };
+    let full_name = found["full_name"].as_str().unwrap_or_default().to_string();
+    let mut starred = fixtures.starred.lock().unwrap();
+    let is_starred = starred.iter().any(|(name, _)| *name == full_name);
+
+    match *req.method() {
+        Method::GET if is_starred => empty_response(StatusCode::NO_CONTENT),
+        Method::GET => not_found(),
+        Method::PUT => {
+            if !is_starred {
+                starred.push((full_name, unix_now()));
+            }
+            empty_response(StatusCode::NO_CONTENT)
+        }
+        Method::DELETE => {
+            starred.retain(|(name, _)| *name != full_name);
+            empty_response(StatusCode::NO_CONTENT)
+        }
+        _ => not_found(),
+    }
+}
+
+// Everything under /repos/{owner}/{repo}
+fn repo_route(req: &Request<()>, body: &[u8], fixtures: &Fixtures, owner: &str, name: &str, rest: &[&str]) -> Response<Body> {
+    let Some(repo) = fixtures.find_repo(owner, name) else {
+        return not_found();
+    };
+    let full_name = repo["full_name"].as_str().unwrap_or_default().to_string();
+
+    match (req.method(), rest) {
+        (&Method::GET, []) => json_response(StatusCode::OK, &repo),
+        (&Method::GET, ["topics"]) => json_response(StatusCode::OK, &json!({ "names": repo["topics"] })),
+        (&Method::PUT, ["topics"]) => {
+            if !fixtures.owns(&repo) {
+                return forbidden("Must have admin rights to Repository.");
+            }
+            let names = serde_json::from_slice::<Value>(body).unwrap_or_default()["names"].clone();
+            fixtures.update_repo(&full_name, |repo| repo["topics"] = names.clone());
+            json_response(StatusCode::OK, &json!({ "names": names }))
+        }
+        (&Method::POST, ["forks"]) => {
+            let owner = serde_json::from_slice::<Value>(body).unwrap_or_default()["organization"]
+                .as_str()
+                .unwrap_or(fixtures.login())
+                .to_string();
+            let mut fork = repo.clone();
+            fork["id"] = json!(repo["id"].as_u64().unwrap_or_default() + 1_000_000_000);
+            fork["full_name"] = json!(format!("{}/{}", owner, repo["name"].as_str().unwrap_or_default()));
+            let fork_name = fork["full_name"].as_str().unwrap_or_default().to_string();
+            fork["html_url"] = json!(format!("https://github.com/{}", fork_name));
+            fork["clone_url"] = json!(format!("https://github.com/{}.git", fork_name));
+            fork["ssh_url"] = json!(format!("git@github.com:{}.git", fork_name));
+            fork["owner"] = json!({ "login": owner, "id": fixtures.user["id"], "type": "User" });
+            fork["fork"] = json!(true);
+            fork["stargazers_count"] = json!(0);
+            fork["parent"] = json!({ "full_name": full_name, "default_branch": repo["default_branch"] });
+            if fixtures.find_repo(&owner, repo["name"].as_str().unwrap_or_default()).is_none() {
+                fixtures.repos.lock().unwrap().push(fork.clone());
+            }
+            json_response(StatusCode::ACCEPTED, &fork)
+        }
+        (&Method::GET, ["releases"]) => {
+            let releases: Vec<Value> = fixtures.releases_of(&repo).iter().map(|release| release_json(req, &repo, release)).collect();
+            let (page, link) = paginate(req, &releases);
+            with_link(json_response(StatusCode::OK, &json!(page)), link)
+        }
+        (&Method::GET, ["releases", "latest"]) => match fixtures.releases_of(&repo).first() {
+            Some(release) => json_response(StatusCode::OK, &release_json(req, &repo, release)),
+            None => not_found(),
+        },
+        (&Method::GET, ["releases", "tags", tag]) => {
+            match fixtures.releases_of(&repo).iter().find(|release| release["tag_name"] == *tag) {
+                Some(release) => json_response(StatusCode::OK, &release_json(req, &repo, release)),
+                None => not_found(),
+            }
+        }
+        (&Method::GET, ["releases", "assets", id]) => {
+            let id = id.parse::<u64>().ok();
+            let found = fixtures.releases_of(&repo).iter().find_map(|release| {
+                let asset = release["assets"].as_array()?.iter().find(|asset| asset["id"].as_u64() == id)?;
+                Some(asset_contents(release, asset["name"].as_str()?))
+            });
+            match found {
+                Some(contents) => bytes_response(contents),
+                None => not_found(),
+            }
+        }
+        (&Method::GET, [kind @ ("tags" | "branches")]) => {
+            let (page, link) = paginate(req, &refs_of(fixtures, &repo, kind));
+            with_link(json_response(StatusCode::OK, &json!(page)), link)
+        }
+        (&Method::GET, ["commits", git_ref]) => {
+            json_response(StatusCode::OK, &commit_json(&repo, &commit_sha(&repo, git_ref), &format!("Update {}", git_ref)))
+        }
+        (&Method::GET, ["commits", _, "check-runs"]) => json_response(StatusCode::OK, &json!({
+            "total_count": 1,
+            "check_runs": [{ "name": "build", "status": "completed", "conclusion": "success" }],
+        })),
+        (&Method::GET, ["commits", _, "status"]) => json_response(StatusCode::OK, &json!({
+            "state": "success",
+            "statuses": [{ "context": "ci/mock", "state": "success" }],
+        })),
+        (&Method::GET, ["compare", range]) => {
+            let Some((base, head)) = range.split_once("...") else {
+                return not_found();
+            };
+            if base == head {
+                return json_response(StatusCode::OK, &json!({ "status": "identical", "ahead_by": 0, "behind_by": 0, "commits": [] }));
+            }
+            let commit = commit_json(&repo, &commit_sha(&repo, head), &format!("Changes on {}", head));
+            json_response(StatusCode::OK, &json!({ "status": "ahead", "ahead_by": 1, "behind_by": 0, "commits": [commit] }))
+        }
+        (&Method::GET, ["stats", "commit_activity"]) => json_response(StatusCode::OK, &commit_activity(&repo)),
+        (&Method::GET, ["stargazers"]) => {
+            let stargazers: Vec<Value> = stargazers_of(fixtures, &repo)
+                .into_iter()
+                .map(|(starred_at, login)| if accepts(req, STAR_JSON) {
+                    json!({ "starred_at": timestamp(starred_at), "user": { "login": login } })
+                } else {
+                    json!({ "login": login })
+                })
+                .collect();
+            let (page, link) = paginate(req, &stargazers);
+            with_link(json_response(StatusCode::OK, &json!(page)), link)
+        }
+        (&Method::GET, ["traffic", kind @ ..]) => {
+            if !fixtures.owns(&repo) {
+                return forbidden("Must have push access to repository");
+            }
+            match traffic(&kind.join("/")) {
+                Some(traffic) => json_response(StatusCode::OK, &traffic),
+                None => not_found(),
+            }
+        }
+        _ => not_found(),
+    }
+}
+
+fn route(req: &Request<()>, body: &[u8], fixtures: &Fixtures) -> Response<Body> {
+    let token = token_of(req);
+    match token {
+        Some(VALID_TOKEN) | None => {}
+        Some(RATE_LIMITED_TOKEN) => {
+            let mut response = forbidden("API rate limit exceeded");
+            response.headers_mut().insert("x-ratelimit-remaining", "0".parse().unwrap());
+            return response;
+        }
+        Some(_) => {
+            return json_response(StatusCode::UNAUTHORIZED, &json!({ "message": "Bad credentials" }));
+        }
+    }
+
+    let segments: Vec<&str> = req.uri().path().trim_matches('/').split('/').collect();
+
+    // Everything below /user, GraphQL and changing gists need a token
+    let needs_token = match segments[0] {
+        "user" | "graphql" => true,
+        "gists" => req.method() != Method::GET,
+        _ => false,
+    };
+    if needs_token && token.is_none() {
+        return json_response(
+            StatusCode::UNAUTHORIZED,
+            &json!({ "message": "Requires authentication" }),
+        );
+    }
+
+    match (req.method(), segments.as_slice()) {
+        (&Method::GET, ["user"]) => json_response(StatusCode::OK, &fixtures.user),
+        (&Method::GET, ["user", "starred"]) => {
+            let starred: Vec<Value> = fixtures
+                .starred_repos()
+                .into_iter()
+                .map(|(starred_at, repo)| if accepts(req, STAR_JSON) {
+                    json!({ "starred_at": timestamp(starred_at), "repo": repo })
+                } else {
+                    repo
+                })
+                .collect();
+            let (page, link) = paginate(req, &starred);
+            with_link(json_response(StatusCode::OK, &json!(page)), link)
+        }
+        (_, ["user", "starred", owner, repo]) => star(req, fixtures, owner, repo),
+        (&Method::GET, ["user", "repos"]) => {
+            let owned: Vec<Value> = fixtures.repos.lock().unwrap().iter().filter(|repo| fixtures.owns(repo)).cloned().collect();
+            let (page, link) = paginate(req, &owned);
+            with_link(json_response(StatusCode::OK, &json!(page)), link)
+        }
+        (&Method::GET, ["users", login]) => {
+            if login.eq_ignore_ascii_case(fixtures.login()) {
+                return json_response(StatusCode::OK, &fixtures.user);
+            }
+            let repos = fixtures.repos.lock().unwrap();
+            let owned: Vec<&Value> = repos.iter().filter(|repo| repo["owner"]["login"].as_str().is_some_and(|owner| owner.eq_ignore_ascii_case(login))).collect();
+            match owned.first() {
+                Some(repo) => {
+                    let mut profile = repo["owner"].clone();
+                    profile["name"] = Value::Null;
+                    profile["bio"] = Value::Null;
+                    profile["company"] = Value::Null;
+                    profile["followers"] = json!(0);
+                    profile["public_repos"] = json!(owned.len());
+                    json_response(StatusCode::OK, &profile)
+                }
+                None => not_found(),
+            }
+        }
+        (&Method::GET, ["rate_limit"]) => {
+            let limit = |limit: u64| json!({ "limit": limit, "used": 1, "remaining": limit - 1, "reset": 4102444800u64 });
+            json_response(StatusCode::OK, &json!({
+                "resources": { "core": limit(5000), "search": limit(30), "graphql": limit(5000) },
+                "rate": limit(5000),
+            }))
+        }
+        (&Method::GET, ["search", "repositories"]) => json_response(StatusCode::OK, &search(fixtures, &query(req))),
+        (&Method::POST, ["graphql"]) => graphql(fixtures, body),
+        (_, ["gists", rest @ ..]) => gists(req, body, fixtures, rest),
+        (_, ["repos", owner, repo, rest @ ..]) => repo_route(req, body, fixtures, owner, repo, rest),
+        _ => not_found(),
+    }
+}
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn Error>> {
+    let matches = App::new("stars-fetcher-mock")
+        .about("Serve canned GitHub API fixtures for offline use of stars_fetcher")
+        .arg(Arg::with_name("host")
+            .long("host")
+            .takes_value(true)
+            .default_value("127.0.0.1")
+            .help("Address to listen on"))
+        .arg(Arg::with_name("port")
+            .long("port")
+            .short('p')
+            .takes_value(true)
+            .default_value("8787")
+            .help("Port to listen on, 0 picks a free port"))
+        .get_matches();
+
+    let host = matches.value_of("host").unwrap();
+    let port: u16 = matches.value_of("port").unwrap().parse()?;
+    let addr: SocketAddr = format!("{}:{}", host, port).parse()?;
+
+    let fixtures = Arc::new(Fixtures::load()?);
+    let make_service = make_service_fn(move |_conn| {
+        let fixtures = fixtures.clone();
+        async move {
+            Ok::<_, Infallible>(service_fn(move |req: Request<Body>| {
+                let fixtures = fixtures.clone();
+                async move {
+                    let (parts, body) = req.into_parts();
+                    let body = hyper::body::to_bytes(body).await.unwrap_or_default();
+                    Ok::<_, Infallible>(route(&Request::from_parts(parts, ()), &body, &fixtures))
+                }
+            }))
+        }
+    });
+
+    let server = Server::try_bind(&addr)?.serve(make_service);
+    let url = format!("http://{}", server.local_addr());
+    println!("Serving GitHub API fixtures on {}", url);
+    println!("Try: GITHUB_API_URL={} GITHUB_TOKEN={} stars_fetcher list", url, VALID_TOKEN);
+
+    server
+        .with_graceful_shutdown(async {
+            tokio::signal::ctrl_c().await.ok();
+        })
+        .await?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request(method: Method, path: &str, token: Option<&str>) -> Request<()> {
+        let mut builder = Request::builder().method(method).uri(path).header(header::HOST, "127.0.0.1:8787");
+        if let Some(token) = token {
+            builder = builder.header(header::AUTHORIZATION, format!("Bearer {}", token));
+        }
+        builder.body(()).unwrap()
+    }
+
+    fn get(fixtures: &Fixtures, path: &str) -> Response<Body> {
+        route(&request(Method::GET, path, Some(VALID_TOKEN)), b"", fixtures)
+    }
+
+    fn json_of(response: Response<Body>) -> Value {
+        serde_json::from_slice(&body_of(response)).unwrap()
+    }
+
+    fn body_of(response: Response<Body>) -> Vec<u8> {
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+        runtime.block_on(hyper::body::to_bytes(response.into_body())).unwrap().to_vec()
+    }
+
+    #[test]
+    fn test_fixtures_are_consistent() {
+        let fixtures = Fixtures::load().unwrap();
+        for (full_name, _) in fixtures.starred.lock().unwrap().iter() {
+            let (owner, repo) = full_name.split_once('/').unwrap();
+            assert!(fixtures.find_repo(owner, repo).is_some(), "{} missing from repos.json", full_name);
+        }
+        for full_name in fixtures.releases.keys() {
+            let (owner, repo) = full_name.split_once('/').unwrap();
+            assert!(fixtures.find_repo(owner, repo).is_some(), "{} missing from repos.json", full_name);
+        }
+    }
+
+    #[test]
+    fn test_star_then_check() {
+        let fixtures = Fixtures::load().unwrap();
+        let path = "/user/starred/tokio-rs/tokio";
+
+        let before = get(&fixtures, path);
+        assert_eq!(before.status(), StatusCode::NOT_FOUND);
+
+        let star = route(&request(Method::PUT, path, Some(VALID_TOKEN)), b"", &fixtures);
+        assert_eq!(star.status(), StatusCode::NO_CONTENT);
+
+        let after = get(&fixtures, path);
+        assert_eq!(after.status(), StatusCode::NO_CONTENT);
+        assert_eq!(json_of(get(&fixtures, "/user/starred"))[0]["full_name"], "tokio-rs/tokio");
+    }
+
+    #[test]
+    fn test_tokens() {
+        let fixtures = Fixtures::load().unwrap();
+
+        let anonymous = route(&request(Method::GET, "/repos/rust-lang/rust", None), b"", &fixtures);
+        assert_eq!(anonymous.status(), StatusCode::OK);
+
+        let needs_auth = route(&request(Method::GET, "/user/starred", None), b"", &fixtures);
+        assert_eq!(needs_auth.status(), StatusCode::UNAUTHORIZED);
+
+        let bad = route(&request(Method::GET, "/user", Some("bad-token")), b"", &fixtures);
+        assert_eq!(bad.status(), StatusCode::UNAUTHORIZED);
+
+        let limited = route(&request(Method::GET, "/user", Some(RATE_LIMITED_TOKEN)), b"", &fixtures);
+        assert_eq!(limited.status(), StatusCode::FORBIDDEN);
+        assert_eq!(limited.headers()["x-ratelimit-remaining"], "0");
+    }
+
+    #[test]
+    fn test_starred_pages() {
+        let fixtures = Fixtures::load().unwrap();
+
+        let first = get(&fixtures, "/user/starred?per_page=2");
+        assert!(first.headers()["link"].to_str().unwrap().contains(r#"<http://127.0.0.1:8787/user/starred?per_page=2&page=2>; rel="next""#));
+        let names: Vec<Value> = json_of(first).as_array().unwrap().iter().map(|repo| repo["full_name"].clone()).collect();
+        assert_eq!(names, vec!["vuejs/vue", "rust-lang/rust"]);
+
+        let last = get(&fixtures, "/user/starred?per_page=2&page=2");
+        assert!(!last.headers()["link"].to_str().unwrap().contains("next"));
+        assert_eq!(json_of(last)[0]["full_name"], "octocat/Hello-World");
+
+        let dated = route(
+            &Request::builder().uri("/user/starred").header(header::ACCEPT, STAR_JSON).header(header::AUTHORIZATION, "token fixture-token").body(()).unwrap(),
+            b"",
+            &fixtures,
+        );
+        assert_eq!(json_of(dated)[2]["starred_at"], "2024-01-01T12:00:00Z");
+    }
+
+    #[test]
+    fn test_release_assets_match_their_checksums() {
+        let fixtures = Fixtures::load().unwrap();
+
+        let release = json_of(get(&fixtures, "/repos/octocat/Hello-World/releases/latest"));
+        assert_eq!(release["tag_name"], "v1.1.0");
+        let asset = |name: &str| {
+            let asset = release["assets"].as_array().unwrap().iter().find(|asset| asset["name"] == name).unwrap();
+            let path = asset["url"].as_str().unwrap().strip_prefix("http://127.0.0.1:8787").unwrap().to_string();
+            body_of(get(&fixtures, &path))
+        };
+
+        let sums = String::from_utf8(asset("SHA256SUMS")).unwrap();
+        assert!(sums.contains(&format!("{}  hello-linux", sha256_hex(&asset("hello-linux")))), "{}", sums);
+        assert_eq!(get(&fixtures, "/repos/rust-lang/rust/releases/latest").status(), StatusCode::NOT_FOUND);
+    }
+
+    #[test]
+    fn test_graphql_pages() {
+        let fixtures = Fixtures::load().unwrap();
+        let query = |after: Value| {
+            let body = json!({ "query": "query { viewer { starredRepositories { edges { node { name } } } } }", "variables": { "first": 2, "after": after } });
+            json_of(route(&request(Method::POST, "/graphql", Some(VALID_TOKEN)), body.to_string().as_bytes(), &fixtures))
+        };
+
+        let first = query(Value::Null);
+        let connection = &first["data"]["viewer"]["starredRepositories"];
+        assert_eq!(connection["edges"][0]["node"]["name"], "vue");
+        assert_eq!(connection["pageInfo"]["hasNextPage"], true);
+
+        let second = query(connection["pageInfo"]["endCursor"].clone());
+        let connection = &second["data"]["viewer"]["starredRepositories"];
+        assert_eq!(connection["edges"][0]["node"]["name"], "Hello-World");
+        assert_eq!(connection["pageInfo"]["hasNextPage"], false);
+    }
+
+    #[test]
+    fn test_search() {
+        let fixtures = Fixtures::load().unwrap();
+
+        let results = json_of(get(&fixtures, "/search/repositories?q=language%3Arust&per_page=10"));
+        let names: Vec<&str> = results["items"].as_array().unwrap().iter().map(|repo| repo["full_name"].as_str().unwrap()).collect();
+        assert_eq!(names, vec!["rust-lang/rust", "tokio-rs/tokio"]);
+    }
+
+    #[test]
+    fn test_gists() {
+        let fixtures = Fixtures::load().unwrap();
+        let body = json!({ "description": "stars", "public": false, "files": { "stars.md": { "content": "- rust-lang/rust" } } });
+
+        let created = json_of(route(&request(Method::POST, "/gists", Some(VALID_TOKEN)), body.to_string().as_bytes(), &fixtures));
+        let path = format!("/gists/{}", created["id"].as_str().unwrap());
+        let update = json!({ "files": { "stars.md": { "content": "- tokio-rs/tokio" } } });
+        route(&request(Method::PATCH, &path, Some(VALID_TOKEN)), update.to_string().as_bytes(), &fixtures);
+
+        assert_eq!(json_of(get(&fixtures, &path))["files"]["stars.md"]["content"], "- tokio-rs/tokio");
+    }
+
+    #[test]
+    fn test_traffic_needs_ownership() {
+        let fixtures = Fixtures::load().unwrap();
+
+        assert_eq!(json_of(get(&fixtures, "/repos/octocat/Hello-World/traffic/views"))["views"].as_array().unwrap().len(), 3);
+        assert_eq!(get(&fixtures, "/repos/rust-lang/rust/traffic/views").status(), StatusCode::FORBIDDEN);
+    }
+}
@@ -1,86 +1,619 @@
+use std::collections::BTreeMap;
 use std::env;
 use std::fs;
 use std::error::Error;
+use std::path::PathBuf;
 use dirs;
 use serde::{Deserialize, Serialize};
 use toml;
+use crate::api::pagination::MAX_PER_PAGE;
+use crate::config::credentials;
+use crate::utils::parse::parse_period;
+
+// Keys holding secrets, as `table.key` paths, which are never exported
+const SECRET_KEYS: &[&str] = &["github.token"];
+
+// Settings left out of the config until they're set
+const OPTIONAL_KEYS: &[&str] = &["install.bin_dir", "archive.auto_archive_after", "pagination.per_page", "github.accept_language"];
+
+/// Environment variable naming the profile to use, set by `--profile`
+pub const PROFILE_ENV: &str = "STARS_FETCHER_PROFILE";
+
+// Settings a profile can replace, as keys under `[profiles.NAME]`
+const PROFILE_KEYS: &[&str] = &["token", "token_source", "email", "api_url", "backend", "forge", "accept_language"];
 
 // Config struct to hold the configuration
-#[derive(Deserialize, Serialize, Debug)]
+#[derive(Deserialize, Serialize, Debug, Clone)]
 pub struct Config {
     pub github: GithubConfig,
+    /// Saved filters by name, e.g. `rusty = "language:rust stars:>500"`
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub filters: BTreeMap<String, String>,
+    #[serde(default, skip_serializing_if = "InstallConfig::is_empty")]
+    pub install: InstallConfig,
+    #[serde(default, skip_serializing_if = "ConcurrencyConfig::is_default")]
+    pub concurrency: ConcurrencyConfig,
+    #[serde(default, skip_serializing_if = "PaginationConfig::is_empty")]
+    pub pagination: PaginationConfig,
+    #[serde(default, skip_serializing_if = "ArchiveConfig::is_empty")]
+    pub archive: ArchiveConfig,
+    #[serde(default, skip_serializing_if = "RetryConfig::is_default")]
+    pub retry: RetryConfig,
+    /// Named alternatives to `[github]`, e.g. `[profiles.work]` for GitHub Enterprise
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub profiles: BTreeMap<String, ProfileConfig>,
+    /// Profile applied by `new`, if any
+    #[serde(skip)]
+    pub profile: Option<String>,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, Default)]
+pub struct InstallConfig {
+    /// Directory `install` puts binaries in, defaulting to `~/.local/bin`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bin_dir: Option<PathBuf>,
+}
+
+impl InstallConfig {
+    fn is_empty(&self) -> bool {
+        self.bin_dir.is_none()
+    }
+}
+
+/// How many API reads may run at once, lowered on slow connections or strict proxies
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+#[serde(default, deny_unknown_fields)]
+pub struct ConcurrencyConfig {
+    /// API requests that only read, like `get --batch`
+    pub api_reads: usize,
+    /// Run fewer API reads at once than `api_reads` when responses come back quickly or little of
+    /// the rate limit is left; `false` always runs `api_reads` at once
+    pub adaptive: bool,
+}
+
+impl Default for ConcurrencyConfig {
+    fn default() -> Self {
+        ConcurrencyConfig {
+            api_reads: 8,
+            adaptive: true,
+        }
+    }
 }
 
-#[derive(Deserialize, Serialize, Debug)]
+impl ConcurrencyConfig {
+    fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+
+    // validate function to reject a limit that would stop a batch from ever starting
+    fn validate(&self) -> Result<(), Box<dyn Error>> {
+        if self.api_reads == 0 {
+            return Err("concurrency.api_reads must be at least 1".into());
+        }
+        Ok(())
+    }
+}
+
+/// How paginated lists are fetched
+#[derive(Deserialize, Serialize, Debug, Clone, Default, PartialEq, Eq)]
+pub struct PaginationConfig {
+    /// Items per page when a command isn't given `--per-page`, the most GitHub allows when unset
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub per_page: Option<u32>,
+}
+
+impl PaginationConfig {
+    fn is_empty(&self) -> bool {
+        self.per_page.is_none()
+    }
+
+    // validate function to reject page sizes GitHub would ignore
+    fn validate(&self) -> Result<(), Box<dyn Error>> {
+        match self.per_page {
+            Some(per_page) if !(1..=MAX_PER_PAGE).contains(&per_page) => {
+                Err(format!("pagination.per_page must be between 1 and {}", MAX_PER_PAGE).into())
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+/// How requests failing with a server error, a timeout or a dropped connection are retried
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+#[serde(default)]
+pub struct RetryConfig {
+    /// Attempts per request including the first one, 1 to never retry
+    pub max_attempts: u32,
+    /// Delay before the first retry in milliseconds, doubled for every retry after it
+    pub initial_backoff_ms: u64,
+}
+
+impl Default for RetryConfig {
+    fn default() -> Self {
+        RetryConfig {
+            max_attempts: 3,
+            initial_backoff_ms: 500,
+        }
+    }
+}
+
+impl RetryConfig {
+    fn is_default(&self) -> bool {
+        *self == Self::default()
+    }
+
+    // validate function to reject a policy that would never send a request
+    fn validate(&self) -> Result<(), Box<dyn Error>> {
+        if self.max_attempts == 0 {
+            return Err("retry.max_attempts must be at least 1".into());
+        }
+        Ok(())
+    }
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, Default, PartialEq, Eq)]
+pub struct ArchiveConfig {
+    /// Stars older than this period, e.g. `2y`, that were never used locally are archived by
+    /// `archive --auto`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub auto_archive_after: Option<String>,
+}
+
+impl ArchiveConfig {
+    fn is_empty(&self) -> bool {
+        self.auto_archive_after.is_none()
+    }
+
+    // validate function to reject periods `archive --auto` couldn't apply
+    fn validate(&self) -> Result<(), Box<dyn Error>> {
+        match &self.auto_archive_after {
+            Some(period) => parse_period(period)
+                .map(|_| ())
+                .map_err(|error| format!("archive.auto_archive_after: {}", error).into()),
+            None => Ok(()),
+        }
+    }
+}
+
+/// API used to list starred repositories
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ListBackend {
+    #[default]
+    Rest,
+    /// Fewer requests for thousands of stars, but always needs a token
+    Graphql,
+}
+
+impl ListBackend {
+    fn is_rest(&self) -> bool {
+        *self == ListBackend::Rest
+    }
+}
+
+/// Forge the stars live on; only listing, starring and unstarring work outside GitHub
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ForgeKind {
+    #[default]
+    Github,
+    Gitlab,
+    /// Gitea, and Forgejo instances such as Codeberg which keep its API
+    #[serde(alias = "forgejo", alias = "codeberg")]
+    Gitea,
+}
+
+impl ForgeKind {
+    fn is_github(&self) -> bool {
+        *self == ForgeKind::Github
+    }
+}
+
+/// Where the token is kept
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum TokenSource {
+    /// The `token` key of config.toml
+    #[default]
+    File,
+    /// The system credential store; config.toml holds no token
+    Keyring,
+}
+
+impl TokenSource {
+    fn is_file(&self) -> bool {
+        *self == TokenSource::File
+    }
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
 pub struct GithubConfig {
+    #[serde(default)]
     pub token: String,
+    #[serde(default, skip_serializing_if = "TokenSource::is_file")]
+    pub token_source: TokenSource,
     pub email: String,
     pub api_url: String,
+    #[serde(default, skip_serializing_if = "ListBackend::is_rest")]
+    pub backend: ListBackend,
+    #[serde(default, skip_serializing_if = "ForgeKind::is_github")]
+    pub forge: ForgeKind,
+    /// `Accept-Language` sent with every request, for servers that localize descriptions
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub accept_language: Option<String>,
+}
+
+/// Settings of a named profile, each replacing the one under `[github]` when the profile is used
+#[derive(Deserialize, Serialize, Debug, Clone, Default, PartialEq, Eq)]
+pub struct ProfileConfig {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub token: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub token_source: Option<TokenSource>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub email: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub api_url: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub backend: Option<ListBackend>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub forge: Option<ForgeKind>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub accept_language: Option<String>,
 }
 
 impl Config {
     // new function to create a new Config instance
     pub fn new() -> Result<Self, Box<dyn Error>> {
-        match Self::load_from_file() {
-            Ok(config) => {
-                if config.github.token.is_empty() {
-                    if let Ok(token) = env::var("GITHUB_TOKEN") {
-                        let mut config = config;
-                        config.github.token = token;
-
-                        Ok(config)
-                    } else {
-                        Ok(config)
-                    }
-                } else {
-                    Ok(config)
-                }
-            },
+        let config = match Self::load_from_file() {
+            Ok(config) => config,
             Err(_) => {
-                Self::create_default_config()
+                Self::create_default_config()?
+            }
+        };
+
+        // A profile's own token wins over GITHUB_TOKEN, which may be meant for another server
+        let mut config = match env::var(PROFILE_ENV) {
+            Ok(name) if !name.is_empty() => config.with_profile(&name)?,
+            _ => config,
+        };
+        if config.github.token.is_empty() {
+            if let Ok(token) = env::var("GITHUB_TOKEN") {
+                config.github.token = token;
+            }
+        }
+
+        // GITHUB_TOKEN wins over the keyring, which is only asked when it isn't set
+        if config.github.token_source == TokenSource::Keyring && config.github.token.is_empty() {
+            config.github.token = credentials::read_token(config.profile.as_deref())?.unwrap_or_default();
+        }
+
+        // GITHUB_API_URL points the tool at another server without touching the config file
+        if let Ok(api_url) = env::var("GITHUB_API_URL") {
+            if !api_url.is_empty() {
+                config.github.api_url = api_url;
             }
         }
+
+        Ok(config)
     }
 
-    // load_from_file function to read the configuration from a file
-    fn load_from_file() -> Result<Self, Box<dyn Error>> {
+    // path function to locate the configuration file
+    pub fn path() -> Result<PathBuf, Box<dyn Error>> {
         let config_path = dirs::config_dir()
             .ok_or("Unable to find config directory")?
             .join("stars_fetcher");
 
-        let config_file = config_path.join("config.toml");
+        Ok(config_path.join("config.toml"))
+    }
+
+    // load_from_file function to read the configuration from a file
+    fn load_from_file() -> Result<Self, Box<dyn Error>> {
+        let config_file = Self::path()?;
 
         if config_file.exists() {
-            let contents = fs::read_to_string(config_file).unwrap();
-            let config: Config = toml::de::from_str(&contents)?;
+            let contents = fs::read_to_string(config_file)?;
 
-            Ok(config)
+            Self::from_toml_str(&contents)
         } else {
             Err("Config file not found".into())
         }
     }
 
-    fn create_default_config() -> Result<Self, Box<dyn Error>> {
-        let token = env::var("GITHUB_TOKEN").unwrap_or_default();
-        let config = Config {
+    // with_profile function to replace the `[github]` settings by those of a named profile
+    pub fn with_profile(mut self, name: &str) -> Result<Self, Box<dyn Error>> {
+        let profile = self
+            .profiles
+            .get(name)
+            .cloned()
+            .ok_or_else(|| format!("Unknown profile {}, add a [profiles.{}] section to the config", name, name))?;
+
+        let github = &mut self.github;
+        github.token = profile.token.unwrap_or_default();
+        github.token_source = profile.token_source.unwrap_or_default();
+        github.email = profile.email.unwrap_or_else(|| github.email.clone());
+        github.api_url = profile.api_url.unwrap_or_else(|| github.api_url.clone());
+        github.backend = profile.backend.unwrap_or(github.backend);
+        github.forge = profile.forge.unwrap_or(github.forge);
+        github.accept_language = profile.accept_language.or_else(|| github.accept_language.clone());
+        self.profile = Some(name.to_string());
+
+        Ok(self)
+    }
+
+    // defaults function to build the config used before any file exists
+    fn defaults(token: String) -> Self {
+        Config {
             github: GithubConfig {
                 token,
+                token_source: TokenSource::default(),
                 email: String::new(),
                 api_url: String::from("https://api.github.com"),
+                backend: ListBackend::default(),
+                forge: ForgeKind::default(),
+                accept_language: None,
+            },
+            filters: BTreeMap::new(),
+            install: InstallConfig::default(),
+            concurrency: ConcurrencyConfig::default(),
+            pagination: PaginationConfig::default(),
+            archive: ArchiveConfig::default(),
+            retry: RetryConfig::default(),
+            profiles: BTreeMap::new(),
+            profile: None,
+        }
+    }
+
+    fn create_default_config() -> Result<Self, Box<dyn Error>> {
+        let config = Self::defaults(env::var("GITHUB_TOKEN").unwrap_or_default());
+
+        if dirs::config_dir().is_some() {
+            config.save()?;
+        }
+
+        Ok(config)
+    }
+
+    // from_toml_str function to parse the contents of a config file
+    pub fn from_toml_str(contents: &str) -> Result<Self, Box<dyn Error>> {
+        let config: Config = toml::de::from_str(contents)?;
+        config.concurrency.validate()?;
+        config.pagination.validate()?;
+        config.archive.validate()?;
+        config.retry.validate()?;
+
+        Ok(config)
+    }
+
+    // save function to write the configuration to the config file
+    pub fn save(&self) -> Result<(), Box<dyn Error>> {
+        let config_file = Self::path()?;
+        if let Some(parent) = config_file.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        fs::write(config_file, self.to_file_string()?)?;
+
+        Ok(())
+    }
+
+    // to_file_string function to serialize the config as saved, leaving out a token kept in the keyring
+    fn to_file_string(&self) -> Result<String, Box<dyn Error>> {
+        match self.github.token_source {
+            TokenSource::File => Ok(toml::to_string(self)?),
+            TokenSource::Keyring => {
+                let mut config = self.clone();
+                config.github.token.clear();
+                Ok(toml::to_string(&config)?)
             }
-        };
+        }
+    }
 
-        if let Some(config_dir) = dirs::config_dir() {
-            let app_config_path = config_dir.join("stars_fetcher");
-            fs::create_dir_all(&app_config_path)?;
+    // move_token_to_keyring function to store the token in the system keyring and drop it from the
+    // file, for the profile in use if there is one
+    pub fn move_token_to_keyring(&self) -> Result<(), Box<dyn Error>> {
+        if self.github.token.is_empty() {
+            return Err("No token to move, set GITHUB_TOKEN or add it to the config file first".into());
+        }
 
-            let config_file = app_config_path.join("config.toml");
-            let toml_string = toml::to_string(&config)?;
-            fs::write(config_file, toml_string)?;
+        credentials::write_token(self.profile.as_deref(), &self.github.token)?;
+        let mut stored = Self::stored()?;
+        match &self.profile {
+            Some(name) => {
+                let profile = stored.profiles.entry(name.clone()).or_default();
+                profile.token = None;
+                profile.token_source = Some(TokenSource::Keyring);
+            }
+            None => stored.github.token_source = TokenSource::Keyring,
+        }
+        stored.save()
+    }
+
+    // filter function to look up a saved filter, falling back to treating the name as a filter
+    pub fn filter<'a>(&'a self, name: &'a str) -> &'a str {
+        self.filters.get(name).map(String::as_str).unwrap_or(name)
+    }
+
+    // bin_dir function to find where binaries are installed
+    pub fn bin_dir(&self) -> Result<PathBuf, Box<dyn Error>> {
+        match &self.install.bin_dir {
+            Some(bin_dir) => Ok(bin_dir.clone()),
+            None => Ok(dirs::home_dir().ok_or("Unable to find home directory")?.join(".local").join("bin")),
+        }
+    }
+
+    // export function to serialize all settings except secrets for sharing
+    pub fn export(&self) -> Result<String, Box<dyn Error>> {
+        let mut value = toml::Value::try_from(self)?;
+
+        for key in SECRET_KEYS {
+            if let Some((table, field)) = key.split_once('.') {
+                if let Some(table) = value.get_mut(table).and_then(|t| t.as_table_mut()) {
+                    table.remove(field);
+                }
+            }
+        }
+        if let Some(profiles) = value.get_mut("profiles").and_then(|profiles| profiles.as_table_mut()) {
+            for profile in profiles.iter_mut().filter_map(|(_, profile)| profile.as_table_mut()) {
+                profile.remove("token");
+            }
+        }
+
+        Ok(toml::to_string(&value)?)
+    }
+
+    // import function to read exported settings. Tokens come from the imported file, or else from
+    // the config file already on this machine (`current`, as stored); GITHUB_TOKEN and the keyring
+    // are left to be read at runtime and never written out.
+    pub fn import(contents: &str, current: Option<&Config>) -> Result<Self, Box<dyn Error>> {
+        let mut config = Self::from_toml_str(contents)?;
+
+        if config.github.token.is_empty() {
+            config.github.token = current.map(|current| current.github.token.clone()).unwrap_or_default();
+        }
+        for (name, profile) in config.profiles.iter_mut().filter(|(_, profile)| profile.token.is_none()) {
+            profile.token = current
+                .and_then(|current| current.profiles.get(name))
+                .and_then(|profile| profile.token.clone());
         }
 
         Ok(config)
     }
+
+    // stored function to read the config as saved, without GITHUB_TOKEN or the keyring filled in
+    pub fn stored() -> Result<Self, Box<dyn Error>> {
+        if Self::path()?.exists() {
+            Self::load_from_file()
+        } else {
+            Self::create_default_config()
+        }
+    }
+
+    // to_value function to build every setting as TOML, including the ones saving leaves out at
+    // their defaults
+    fn to_value(&self) -> Result<toml::Value, Box<dyn Error>> {
+        let mut github = toml::Value::try_from(&self.github)?;
+        if let Some(github) = github.as_table_mut() {
+            github.insert("token_source".to_string(), toml::Value::try_from(self.github.token_source)?);
+            github.insert("backend".to_string(), toml::Value::try_from(self.github.backend)?);
+            github.insert("forge".to_string(), toml::Value::try_from(self.github.forge)?);
+        }
+
+        let mut value = toml::Table::new();
+        value.insert("github".to_string(), github);
+        value.insert("filters".to_string(), toml::Value::try_from(&self.filters)?);
+        value.insert("install".to_string(), toml::Value::try_from(&self.install)?);
+        value.insert("concurrency".to_string(), toml::Value::try_from(&self.concurrency)?);
+        value.insert("pagination".to_string(), toml::Value::try_from(&self.pagination)?);
+        value.insert("archive".to_string(), toml::Value::try_from(&self.archive)?);
+        value.insert("retry".to_string(), toml::Value::try_from(&self.retry)?);
+        value.insert("profiles".to_string(), toml::Value::try_from(&self.profiles)?);
+
+        Ok(toml::Value::Table(value))
+    }
+
+    // get function to look up a setting by its dotted key, e.g. `github.api_url`, giving None for
+    // optional settings that aren't set
+    pub fn get(&self, key: &str) -> Result<Option<String>, Box<dyn Error>> {
+        let value = self.to_value()?;
+        match key.split('.').try_fold(&value, |value, part| value.get(part)) {
+            Some(toml::Value::String(value)) => Ok(Some(value.clone())),
+            Some(toml::Value::Table(table)) => Ok(Some(toml::to_string(table)?.trim_end().to_string())),
+            Some(value) => Ok(Some(value.to_string())),
+            None if is_known_key(key) => Ok(None),
+            None => Err(format!("Unknown setting {}", key).into()),
+        }
+    }
+
+    // settings function to list every setting that has a value as dotted keys, masking secrets
+    pub fn settings(&self) -> Result<Vec<(String, String)>, Box<dyn Error>> {
+        let mut settings = Vec::new();
+        flatten(&self.to_value()?, "", &mut settings);
+
+        for (key, value) in settings.iter_mut() {
+            let secret = SECRET_KEYS.contains(&key.as_str())
+                || (key.starts_with("profiles.") && key.ends_with(".token"));
+            if secret && !value.is_empty() {
+                *value = String::from("********");
+            }
+        }
+        Ok(settings)
+    }
+
+    // set function to change a setting by its dotted key. The value is read as TOML when the
+    // setting accepts it, so `5` sets a number, and as a plain string otherwise.
+    pub fn set(&self, key: &str, value: &str) -> Result<Self, Box<dyn Error>> {
+        let parts: Vec<&str> = key.split('.').collect();
+        let current = self.to_value()?;
+        let is_table = matches!(parts.iter().try_fold(&current, |value, part| value.get(part)), Some(toml::Value::Table(_)));
+        if !is_known_key(key) || is_table {
+            return Err(format!("Unknown setting {}", key).into());
+        }
+
+        let literal = toml::from_str::<toml::Table>(&format!("value = {}", value))
+            .ok()
+            .and_then(|mut table| table.remove("value"));
+        let candidates = literal.into_iter().chain([toml::Value::String(value.to_string())]);
+
+        // The error for the value as TOML explains more than the one for it as a string
+        let mut error = None;
+        for candidate in candidates {
+            let mut updated = current.clone();
+            let (field, tables) = parts.split_last().ok_or("Empty setting name")?;
+            // Tables such as a new profile's are created on the way
+            let mut table = updated.as_table_mut().ok_or("The config is not a table")?;
+            for part in tables {
+                table = table
+                    .entry(part.to_string())
+                    .or_insert_with(|| toml::Value::Table(toml::Table::new()))
+                    .as_table_mut()
+                    .ok_or_else(|| format!("Unknown setting {}", key))?;
+            }
+            table.insert(field.to_string(), candidate);
+
+            match Self::from_toml_str(&toml::to_string(&updated)?) {
+                Ok(config) => return Ok(config),
+                Err(invalid) => {
+                    error.get_or_insert(invalid);
+                }
+            }
+        }
+
+        Err(format!("Invalid value for {}: {}", key, error.map(|error| error.to_string()).unwrap_or_default()).into())
+    }
+}
+
+// Whether a dotted key names a setting: one that has a value, an optional one, a saved filter
+// or a profile's setting
+fn is_known_key(key: &str) -> bool {
+    if let Some(name) = key.strip_prefix("filters.") {
+        return !name.is_empty();
+    }
+    if let Some((name, field)) = key.strip_prefix("profiles.").and_then(|key| key.split_once('.')) {
+        return !name.is_empty() && PROFILE_KEYS.contains(&field);
+    }
+    if OPTIONAL_KEYS.contains(&key) {
+        return true;
+    }
+
+    let Ok(value) = Config::defaults(String::new()).to_value() else {
+        return false;
+    };
+    key.split('.').try_fold(&value, |value, part| value.get(part)).is_some()
+}
+
+// Collect the leaves of a TOML value as dotted keys, strings without quotes
+fn flatten(value: &toml::Value, prefix: &str, settings: &mut Vec<(String, String)>) {
+    match value {
+        toml::Value::Table(table) => {
+            for (key, value) in table {
+                let key = if prefix.is_empty() { key.clone() } else { format!("{}.{}", prefix, key) };
+                flatten(value, &key, settings);
+            }
+        }
+        toml::Value::String(value) => settings.push((prefix.to_string(), value.clone())),
+        value => settings.push((prefix.to_string(), value.to_string())),
+    }
 }
 
 // tests
@@ -175,4 +708,323 @@ api_url = "https://test-api.github.com"
         clean_test_config();
         env::remove_var("GITHUB_TOKEN");
     }
+
+    fn test_config(token: &str) -> Config {
+        Config {
+            github: GithubConfig {
+                token: token.to_string(),
+                token_source: TokenSource::default(),
+                email: "test@example.com".to_string(),
+                api_url: "https://test-api.github.com".to_string(),
+                backend: ListBackend::Graphql,
+                forge: ForgeKind::default(),
+                accept_language: None,
+            },
+            filters: BTreeMap::from([("rusty".to_string(), "language:rust stars:>500".to_string())]),
+            install: InstallConfig { bin_dir: Some(PathBuf::from("/opt/stars/bin")) },
+            concurrency: ConcurrencyConfig { api_reads: 2, ..ConcurrencyConfig::default() },
+            pagination: PaginationConfig { per_page: Some(50) },
+            archive: ArchiveConfig { auto_archive_after: Some("2y".to_string()) },
+            retry: RetryConfig { max_attempts: 5, ..RetryConfig::default() },
+            profiles: BTreeMap::from([(
+                "work".to_string(),
+                ProfileConfig {
+                    token: Some("work_token".to_string()),
+                    api_url: Some("https://github.example.com/api/v3".to_string()),
+                    accept_language: Some("ja".to_string()),
+                    ..ProfileConfig::default()
+                },
+            )]),
+            profile: None,
+        }
+    }
+
+    #[test]
+    fn test_export_strips_secrets() {
+        let exported = test_config("secret_token").export().unwrap();
+
+        assert!(!exported.contains("secret_token"));
+        assert!(!exported.contains("token"));
+        assert!(exported.contains("test@example.com"));
+        assert!(exported.contains("https://test-api.github.com"));
+    }
+
+    #[test]
+    fn test_with_profile() {
+        let config = test_config("secret_token").with_profile("work").unwrap();
+
+        assert_eq!(config.profile.as_deref(), Some("work"));
+        assert_eq!(config.github.token, "work_token");
+        assert_eq!(config.github.api_url, "https://github.example.com/api/v3");
+        // Settings the profile leaves out come from [github]
+        assert_eq!(config.github.email, "test@example.com");
+        assert_eq!(config.github.backend, ListBackend::Graphql);
+        assert_eq!(config.github.accept_language.as_deref(), Some("ja"));
+
+        let error = test_config("secret_token").with_profile("home").unwrap_err();
+        assert!(error.to_string().starts_with("Unknown profile home"));
+    }
+
+    #[test]
+    fn test_profiles_round_trip() {
+        let exported = test_config("secret_token").export().unwrap();
+        assert!(exported.contains("[profiles.work]"));
+        assert!(!exported.contains("work_token"));
+
+        let config = Config::import(&exported, Some(&test_config("local_token"))).unwrap();
+        assert_eq!(config.profiles["work"].token.as_deref(), Some("work_token"));
+
+        let config = config.set("profiles.home.api_url", "https://api.github.com").unwrap();
+        assert_eq!(config.profiles["home"].api_url.as_deref(), Some("https://api.github.com"));
+        assert!(config.set("profiles.home.colour", "blue").is_err());
+        let config = config.set("github.accept_language", "zh-CN, en;q=0.8").unwrap();
+        assert_eq!(config.github.accept_language.as_deref(), Some("zh-CN, en;q=0.8"));
+        assert_eq!(config.get("profiles.work.token").unwrap().as_deref(), Some("work_token"));
+        assert!(config.settings().unwrap().contains(&("profiles.work.token".to_string(), "********".to_string())));
+    }
+
+    #[test]
+    fn test_import_keeps_local_token() {
+        let exported = test_config("remote_token").export().unwrap();
+        let current = test_config("local_token");
+
+        let config = Config::import(&exported, Some(&current)).unwrap();
+        assert_eq!(config.github.token, "local_token");
+        assert_eq!(config.github.email, "test@example.com");
+        assert_eq!(config.github.api_url, "https://test-api.github.com");
+        assert_eq!(config.filters["rusty"], "language:rust stars:>500");
+        assert_eq!(config.bin_dir().unwrap(), PathBuf::from("/opt/stars/bin"));
+        assert_eq!(config.concurrency.api_reads, 2);
+        assert_eq!(config.pagination.per_page, Some(50));
+        assert_eq!(config.github.backend, ListBackend::Graphql);
+        assert_eq!(config.archive.auto_archive_after.as_deref(), Some("2y"));
+        assert_eq!(config.retry.max_attempts, 5);
+    }
+
+    #[test]
+    fn test_import_leaves_token_to_runtime() {
+        let exported = test_config("remote_token").export().unwrap();
+
+        let config = Config::import(&exported, None).unwrap();
+        assert_eq!(config.github.token, "");
+        assert!(!toml::to_string(&config).unwrap().contains("remote_token"));
+    }
+
+    #[test]
+    fn test_import_keeps_token_of_imported_file() {
+        let contents = toml::to_string(&test_config("imported_token")).unwrap();
+
+        let config = Config::import(&contents, Some(&test_config("local_token"))).unwrap();
+        assert_eq!(config.github.token, "imported_token");
+    }
+
+    #[test]
+    fn test_archive_rejects_invalid_period() {
+        let error = Config::from_toml_str(r#"
+[github]
+email = "test@example.com"
+api_url = "https://test-api.github.com"
+
+[archive]
+auto_archive_after = "two years"
+"#).unwrap_err();
+
+        assert!(error.to_string().starts_with("archive.auto_archive_after: Expected a period"));
+    }
+
+    #[test]
+    fn test_backend_defaults_to_rest() {
+        let config = Config::from_toml_str(r#"
+[github]
+email = "test@example.com"
+api_url = "https://test-api.github.com"
+"#).unwrap();
+
+        assert_eq!(config.github.backend, ListBackend::Rest);
+        assert!(!toml::to_string(&config).unwrap().contains("backend"));
+    }
+
+    #[test]
+    fn test_get() {
+        let config = test_config("secret_token");
+
+        assert_eq!(config.get("github.api_url").unwrap().as_deref(), Some("https://test-api.github.com"));
+        assert_eq!(config.get("retry.max_attempts").unwrap().as_deref(), Some("5"));
+        assert_eq!(config.get("github.forge").unwrap().as_deref(), Some("github"));
+        assert_eq!(config.get("filters.rusty").unwrap().as_deref(), Some("language:rust stars:>500"));
+        assert_eq!(config.get("filters.missing").unwrap(), None);
+        assert!(config.get("github.nope").unwrap_err().to_string().contains("Unknown setting github.nope"));
+    }
+
+    #[test]
+    fn test_set() {
+        let config = test_config("secret_token");
+
+        let config = config.set("retry.initial_backoff_ms", "250").unwrap();
+        assert_eq!(config.retry.initial_backoff_ms, 250);
+        let config = config.set("github.email", "me@example.com").unwrap();
+        assert_eq!(config.github.email, "me@example.com");
+        let config = config.set("github.backend", "rest").unwrap();
+        assert_eq!(config.github.backend, ListBackend::Rest);
+        let config = config.set("archive.auto_archive_after", "1y").unwrap();
+        assert_eq!(config.archive.auto_archive_after.as_deref(), Some("1y"));
+        let config = config.set("filters.new", "stars:>10").unwrap();
+        assert_eq!(config.filter("new"), "stars:>10");
+        assert_eq!(config.github.token, "secret_token");
+
+        assert!(config.set("retry.max_attempts", "0").unwrap_err().to_string().contains("must be at least 1"));
+        assert!(config.set("github.backend", "soap").is_err());
+        assert!(config.set("github.nope", "1").unwrap_err().to_string().contains("Unknown setting"));
+        assert!(config.set("retry", "1").unwrap_err().to_string().contains("Unknown setting"));
+    }
+
+    #[test]
+    fn test_settings_mask_secrets() {
+        let settings = test_config("secret_token").settings().unwrap();
+        let setting = |key: &str| settings.iter().find(|(name, _)| name == key).map(|(_, value)| value.as_str());
+
+        assert_eq!(setting("github.token"), Some("********"));
+        assert_eq!(setting("concurrency.api_reads"), Some("2"));
+        assert_eq!(setting("install.bin_dir"), Some("/opt/stars/bin"));
+        assert_eq!(setting("archive.auto_archive_after"), Some("2y"));
+    }
+
+    #[test]
+    fn test_keyring_token_left_out_of_file() {
+        let mut config = test_config("secret_token");
+        assert!(config.to_file_string().unwrap().contains("secret_token"));
+
+        config.github.token_source = TokenSource::Keyring;
+        let contents = config.to_file_string().unwrap();
+        assert!(!contents.contains("secret_token"));
+        assert!(contents.contains(r#"token_source = "keyring""#));
+
+        let loaded = Config::from_toml_str(&contents).unwrap();
+        assert_eq!(loaded.github.token_source, TokenSource::Keyring);
+        assert_eq!(loaded.github.token, "");
+    }
+
+    #[test]
+    fn test_forge_defaults_to_github() {
+        let config = Config::from_toml_str(r#"
+[github]
+email = "test@example.com"
+api_url = "https://test-api.github.com"
+"#).unwrap();
+
+        assert_eq!(config.github.forge, ForgeKind::Github);
+        assert!(!toml::to_string(&config).unwrap().contains("forge"));
+
+        let config = Config::from_toml_str(r#"
+[github]
+email = "test@example.com"
+api_url = "https://codeberg.org/api/v1"
+forge = "codeberg"
+"#).unwrap();
+
+        assert_eq!(config.github.forge, ForgeKind::Gitea);
+        assert!(toml::to_string(&config).unwrap().contains(r#"forge = "gitea""#));
+    }
+
+    #[test]
+    fn test_concurrency_defaults() {
+        let config = Config::from_toml_str(r#"
+[github]
+email = "test@example.com"
+api_url = "https://test-api.github.com"
+
+[concurrency]
+api_reads = 2
+"#).unwrap();
+
+        assert_eq!(config.concurrency, ConcurrencyConfig { api_reads: 2, ..ConcurrencyConfig::default() });
+        assert!(!toml::to_string(&Config { concurrency: ConcurrencyConfig::default(), ..config }).unwrap().contains("concurrency"));
+    }
+
+    #[test]
+    fn test_concurrency_rejects_zero() {
+        let error = Config::from_toml_str(r#"
+[github]
+email = "test@example.com"
+api_url = "https://test-api.github.com"
+
+[concurrency]
+api_reads = 0
+"#).unwrap_err();
+
+        assert_eq!(error.to_string(), "concurrency.api_reads must be at least 1");
+    }
+
+    #[test]
+    fn test_concurrency_rejects_unknown_limits() {
+        let error = Config::from_toml_str(r#"
+[github]
+email = "test@example.com"
+api_url = "https://test-api.github.com"
+
+[concurrency]
+downloads = 1
+"#).unwrap_err();
+
+        assert!(error.to_string().contains("unknown field `downloads`, expected `api_reads` or `adaptive`"));
+    }
+
+    #[test]
+    fn test_pagination_per_page() {
+        let config = Config::from_toml_str(r#"
+[github]
+email = "test@example.com"
+api_url = "https://test-api.github.com"
+
+[pagination]
+per_page = 30
+"#).unwrap();
+
+        assert_eq!(config.pagination.per_page, Some(30));
+        assert!(!toml::to_string(&Config { pagination: PaginationConfig::default(), ..config.clone() }).unwrap().contains("pagination"));
+        let error = config.set("pagination.per_page", "500").unwrap_err();
+        assert!(error.to_string().contains("pagination.per_page must be between 1 and 100"));
+    }
+
+    #[test]
+    fn test_retry_defaults() {
+        let config = Config::from_toml_str(r#"
+[github]
+email = "test@example.com"
+api_url = "https://test-api.github.com"
+
+[retry]
+initial_backoff_ms = 100
+"#).unwrap();
+
+        assert_eq!(config.retry, RetryConfig { initial_backoff_ms: 100, ..RetryConfig::default() });
+        assert!(!toml::to_string(&Config { retry: RetryConfig::default(), ..config }).unwrap().contains("retry"));
+    }
+
+    #[test]
+    fn test_retry_rejects_zero_attempts() {
+        let error = Config::from_toml_str(r#"
+[github]
+email = "test@example.com"
+api_url = "https://test-api.github.com"
+
+[retry]
+max_attempts = 0
+"#).unwrap_err();
+
+        assert_eq!(error.to_string(), "retry.max_attempts must be at least 1");
+    }
+
+    #[test]
+    fn test_filter_lookup() {
+        let config = test_config("token");
+
+        assert_eq!(config.filter("rusty"), "language:rust stars:>500");
+        assert_eq!(config.filter("topic:cli"), "topic:cli");
+    }
+
+    #[test]
+    fn test_import_rejects_invalid_toml() {
+        assert!(Config::import("not = [valid", None).is_err());
+    }
 }
\ No newline at end of file
@@ -0,0 +1,35 @@
+//!
+//! The token in the system credential store
+//! With `token_source = "keyring"` the token lives in the macOS Keychain, Windows Credential
+//! Manager or the Secret Service on Linux instead of config.toml. Each profile has its own entry.
+//!
+
+use std::error::Error;
+use keyring::Entry;
+
+const SERVICE: &str = "stars_fetcher";
+const USER: &str = "github-token";
+
+fn entry(profile: Option<&str>) -> Result<Entry, Box<dyn Error>> {
+    let user = match profile {
+        Some(profile) => format!("{}@{}", USER, profile),
+        None => USER.to_string(),
+    };
+    Entry::new(SERVICE, &user).map_err(|error| format!("Unable to open the system keyring: {}", error).into())
+}
+
+/// The stored token of `profile`, None when none was ever stored
+pub fn read_token(profile: Option<&str>) -> Result<Option<String>, Box<dyn Error>> {
+    match entry(profile)?.get_password() {
+        Ok(token) => Ok(Some(token)),
+        Err(keyring::Error::NoEntry) => Ok(None),
+        Err(error) => Err(format!("Unable to read the token from the system keyring: {}", error).into()),
+    }
+}
+
+/// Store `token` for `profile`, replacing any stored before
+pub fn write_token(profile: Option<&str>, token: &str) -> Result<(), Box<dyn Error>> {
+    entry(profile)?
+        .set_password(token)
+        .map_err(|error| format!("Unable to store the token in the system keyring: {}", error).into())
+}
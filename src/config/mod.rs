@@ -1,3 +1,5 @@
+#[allow(clippy::module_inception)]
 mod config;
+mod credentials;
 
-pub use config::Config;
\ No newline at end of file
+pub use config::{Config, ForgeKind, ListBackend, ProfileConfig, RetryConfig, TokenSource, PROFILE_ENV};
\ No newline at end of file
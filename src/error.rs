@@ -0,0 +1,182 @@
+//!
+//! Errors returned by the GitHub API layer
+//! Failures are sorted into the kinds a caller reacts to differently: a token that doesn't work,
+//! an exhausted rate limit, something that doesn't exist, a connection that failed and a
+//! response that couldn't be read. Each kind has its own exit code in the CLI.
+//!
+
+use crate::api::client::unsupported_version_error;
+use reqwest::{Response, StatusCode};
+use serde_json::Value;
+use std::error::Error;
+use thiserror::Error;
+
+/// Exit code for errors without a more specific one
+pub const EXIT_FAILURE: i32 = 1;
+
+#[derive(Debug, Error)]
+pub enum StarsError {
+    /// The token is missing, invalid or lacks a scope or permission
+    #[error("{0}")]
+    Auth(String),
+    /// GitHub's primary or secondary rate limit was hit
+    #[error("GitHub API rate limit exceeded: {message}")]
+    RateLimited {
+        message: String,
+        /// When the limit resets, in seconds since the Unix epoch
+        reset: Option<u64>,
+    },
+    #[error("{0}")]
+    NotFound(String),
+    /// The request never got a response
+    #[error("Network error: {0}")]
+    Network(#[source] reqwest::Error),
+    /// The response wasn't what GitHub documents
+    #[error("Could not read the response: {0}")]
+    Parse(String),
+    /// Any other error status
+    #[error("GitHub API error ({status}): {message}")]
+    Api { status: StatusCode, message: String },
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+    #[error("{0}")]
+    Other(String),
+}
+
+impl StarsError {
+    /// Classify an error response by its status, rate limit headers and GitHub's message
+    pub async fn from_response(response: Response) -> Self {
+        let status = response.status();
+        let header = |name: &str| {
+            response
+                .headers()
+                .get(name)
+                .and_then(|value| value.to_str().ok())
+                .and_then(|value| value.parse::<u64>().ok())
+        };
+        let (remaining, reset) = (header("x-ratelimit-remaining"), header("x-ratelimit-reset"));
+
+        let body = response.text().await.unwrap_or_default();
+        let message = serde_json::from_str::<Value>(&body)
+            .ok()
+            .and_then(|body| body["message"].as_str().map(String::from))
+            .or_else(|| Some(body).filter(|body| !body.trim().is_empty()))
+            .unwrap_or_else(|| String::from("no details"));
+
+        Self::from_status(status, message, remaining, reset)
+    }
+
+    // The classification behind from_response, apart from reading the response
+    fn from_status(status: StatusCode, message: String, remaining: Option<u64>, reset: Option<u64>) -> Self {
+        let rate_limited = status == StatusCode::TOO_MANY_REQUESTS
+            || (status == StatusCode::FORBIDDEN
+                && (remaining == Some(0) || message.to_lowercase().contains("rate limit")));
+
+        match status {
+            _ if rate_limited => StarsError::RateLimited { message, reset },
+            StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN => {
+                StarsError::Auth(format!("GitHub API error ({}): {}", status, message))
+            }
+            StatusCode::NOT_FOUND => StarsError::NotFound(format!("GitHub API error ({}): {}", status, message)),
+            StatusCode::BAD_REQUEST => match unsupported_version_error(&message) {
+                Some(error) => StarsError::Other(error),
+                None => StarsError::Api { status, message },
+            },
+            _ => StarsError::Api { status, message },
+        }
+    }
+
+    /// Process exit code for the CLI. 2 is left to argument errors.
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            StarsError::Auth(_) => 3,
+            StarsError::NotFound(_) => 4,
+            StarsError::RateLimited { .. } => 5,
+            StarsError::Network(_) => 6,
+            StarsError::Parse(_) => 7,
+            StarsError::Api { .. } | StarsError::Io(_) | StarsError::Other(_) => EXIT_FAILURE,
+        }
+    }
+}
+
+// Decoding a body is reported by reqwest too, but isn't a network problem
+impl From<reqwest::Error> for StarsError {
+    fn from(error: reqwest::Error) -> Self {
+        if error.is_decode() {
+            StarsError::Parse(error.to_string())
+        } else {
+            StarsError::Network(error)
+        }
+    }
+}
+
+impl From<serde_json::Error> for StarsError {
+    fn from(error: serde_json::Error) -> Self {
+        StarsError::Parse(error.to_string())
+    }
+}
+
+impl From<String> for StarsError {
+    fn from(message: String) -> Self {
+        StarsError::Other(message)
+    }
+}
+
+impl From<&str> for StarsError {
+    fn from(message: &str) -> Self {
+        StarsError::Other(message.to_string())
+    }
+}
+
+// Helpers outside the API layer, like config and local files, still return boxed errors
+impl From<Box<dyn Error>> for StarsError {
+    fn from(error: Box<dyn Error>) -> Self {
+        match error.downcast::<StarsError>() {
+            Ok(error) => *error,
+            Err(error) => StarsError::Other(error.to_string()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn classify(status: u16, message: &str, remaining: Option<u64>) -> StarsError {
+        StarsError::from_status(StatusCode::from_u16(status).unwrap(), message.to_string(), remaining, Some(1_700_000_000))
+    }
+
+    #[test]
+    fn test_classify_status() {
+        assert!(matches!(classify(401, "Bad credentials", Some(59)), StarsError::Auth(_)));
+        assert!(matches!(classify(403, "Resource not accessible by integration", Some(4000)), StarsError::Auth(_)));
+        assert!(matches!(classify(404, "Not Found", None), StarsError::NotFound(_)));
+        assert!(matches!(classify(422, "Validation Failed", None), StarsError::Api { .. }));
+        assert!(matches!(classify(400, "Unsupported 'X-GitHub-Api-Version' header", None), StarsError::Other(_)));
+    }
+
+    #[test]
+    fn test_classify_rate_limits() {
+        let primary = classify(403, "API rate limit exceeded", Some(0));
+        assert!(matches!(primary, StarsError::RateLimited { reset: Some(1_700_000_000), .. }));
+        assert!(matches!(classify(403, "You have exceeded a secondary rate limit", Some(10)), StarsError::RateLimited { .. }));
+        assert!(matches!(classify(429, "Too many requests", None), StarsError::RateLimited { .. }));
+        assert_eq!(primary.exit_code(), 5);
+    }
+
+    #[test]
+    fn test_messages() {
+        assert_eq!(classify(401, "Bad credentials", None).to_string(), "GitHub API error (401 Unauthorized): Bad credentials");
+        assert_eq!(classify(500, "Server Error", None).to_string(), "GitHub API error (500 Internal Server Error): Server Error");
+        assert_eq!(StarsError::from(serde_json::from_str::<Value>("{").unwrap_err()).exit_code(), 7);
+    }
+
+    #[test]
+    fn test_boxed_errors_keep_their_kind() {
+        let boxed: Box<dyn Error> = Box::new(StarsError::NotFound("Gist 1 not found".to_string()));
+        assert!(matches!(StarsError::from(boxed), StarsError::NotFound(_)));
+
+        let boxed: Box<dyn Error> = "Invalid topic".into();
+        assert!(matches!(StarsError::from(boxed), StarsError::Other(message) if message == "Invalid topic"));
+    }
+}
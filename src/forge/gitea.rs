@@ -0,0 +1,177 @@
+//!
+//! Gitea and Forgejo
+//! Forgejo, and Codeberg which runs it, keep Gitea's API, including GitHub-like star endpoints
+//! under `/user/starred`. Lists are paged with `page` and `limit`.
+//!
+
+use crate::error::StarsError;
+use crate::forge::{Forge, ForgeRepo};
+use crate::registry::http_client;
+use reqwest::{Client, Method, RequestBuilder, StatusCode};
+use serde::Deserialize;
+
+// Most repositories Gitea returns in one page by default; instances may lower the limit
+const PAGE_SIZE: usize = 50;
+
+#[derive(Debug, Deserialize)]
+struct GiteaRepo {
+    id: u64,
+    name: String,
+    full_name: String,
+    #[serde(default)]
+    description: Option<String>,
+    html_url: String,
+    #[serde(default)]
+    stars_count: u64,
+}
+
+impl From<GiteaRepo> for ForgeRepo {
+    fn from(repo: GiteaRepo) -> Self {
+        ForgeRepo {
+            id: repo.id,
+            name: repo.name,
+            full_name: repo.full_name,
+            // Gitea reports a missing description as an empty one
+            description: repo.description.filter(|description| !description.is_empty()),
+            html_url: repo.html_url,
+            stars: repo.stars_count,
+        }
+    }
+}
+
+pub struct Gitea {
+    client: Client,
+    /// e.g. `https://codeberg.org/api/v1`
+    api_url: String,
+    token: String,
+}
+
+impl Gitea {
+    pub fn new(api_url: String, token: String) -> Self {
+        Self { client: http_client(), api_url, token }
+    }
+
+    fn request(&self, method: Method, path: &str) -> RequestBuilder {
+        self.client
+            .request(method, format!("{}{}", self.api_url, path))
+            .header("Authorization", format!("token {}", self.token))
+    }
+
+    // Star, unstar or check a repository, which all answer 204 on success
+    async fn starred_request(&self, method: Method, owner: &str, repo: &str) -> Result<StatusCode, StarsError> {
+        let response = self.request(method, &format!("/user/starred/{}/{}", owner, repo)).send().await?;
+
+        match response.status() {
+            StatusCode::NO_CONTENT | StatusCode::NOT_FOUND => Ok(response.status()),
+            _ => Err(StarsError::from_response(response).await),
+        }
+    }
+}
+
+impl Forge for Gitea {
+    fn name(&self) -> &'static str {
+        "gitea"
+    }
+
+    async fn list_stars(&self) -> Result<Vec<ForgeRepo>, StarsError> {
+        let mut starred = Vec::new();
+
+        for page in 1.. {
+            let path = format!("/user/starred?page={}&limit={}", page, PAGE_SIZE);
+            let response = self.request(Method::GET, &path).send().await?;
+            if response.status() != StatusCode::OK {
+                return Err(StarsError::from_response(response).await);
+            }
+
+            let repos = response.json::<Vec<GiteaRepo>>().await?;
+            let last = repos.is_empty() || repos.len() < PAGE_SIZE;
+            starred.extend(repos.into_iter().map(ForgeRepo::from));
+            if last {
+                break;
+            }
+        }
+
+        Ok(starred)
+    }
+
+    async fn star(&self, owner: &str, repo: &str) -> Result<(), StarsError> {
+        match self.starred_request(Method::PUT, owner, repo).await? {
+            StatusCode::NOT_FOUND => Err(StarsError::NotFound(format!("Repository {}/{} not found", owner, repo))),
+            _ => Ok(()),
+        }
+    }
+
+    async fn unstar(&self, owner: &str, repo: &str) -> Result<(), StarsError> {
+        match self.starred_request(Method::DELETE, owner, repo).await? {
+            StatusCode::NOT_FOUND => Err(StarsError::NotFound(format!("Repository {}/{} not found", owner, repo))),
+            _ => Ok(()),
+        }
+    }
+
+    async fn has_starred(&self, owner: &str, repo: &str) -> Result<bool, StarsError> {
+        Ok(self.starred_request(Method::GET, owner, repo).await? == StatusCode::NO_CONTENT)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mockito::Server;
+
+    fn repos(count: usize, offset: usize) -> String {
+        let repos: Vec<_> = (offset..offset + count)
+            .map(|id| serde_json::json!({
+                "id": id,
+                "name": format!("repo{}", id),
+                "full_name": format!("owner/repo{}", id),
+                "description": "",
+                "html_url": format!("https://codeberg.org/owner/repo{}", id),
+                "stars_count": 3
+            }))
+            .collect();
+        serde_json::to_string(&repos).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_list_starred_pages() {
+        let mut server = Server::new_async().await;
+        server
+            .mock("GET", "/user/starred?page=1&limit=50")
+            .match_header("authorization", "token secret")
+            .with_status(200)
+            .with_body(repos(PAGE_SIZE, 0))
+            .create_async()
+            .await;
+        server
+            .mock("GET", "/user/starred?page=2&limit=50")
+            .with_status(200)
+            .with_body(repos(2, PAGE_SIZE))
+            .create_async()
+            .await;
+
+        let gitea = Gitea::new(server.url(), "secret".to_string());
+        let starred = gitea.list_stars().await.unwrap();
+
+        assert_eq!(starred.len(), PAGE_SIZE + 2);
+        assert_eq!(starred[51].full_name, "owner/repo51");
+        assert_eq!(starred[0].description, None);
+    }
+
+    #[tokio::test]
+    async fn test_star_unstar_and_check() {
+        let mut server = Server::new_async().await;
+        let star = server.mock("PUT", "/user/starred/owner/repo").with_status(204).create_async().await;
+        let unstar = server.mock("DELETE", "/user/starred/owner/repo").with_status(204).create_async().await;
+        server.mock("GET", "/user/starred/owner/repo").with_status(404).create_async().await;
+        server.mock("PUT", "/user/starred/owner/gone").with_status(404).create_async().await;
+
+        let gitea = Gitea::new(server.url(), "secret".to_string());
+        gitea.star("owner", "repo").await.unwrap();
+        gitea.unstar("owner", "repo").await.unwrap();
+
+        assert!(!gitea.has_starred("owner", "repo").await.unwrap());
+        assert!(matches!(gitea.star("owner", "gone").await, Err(StarsError::NotFound(_))));
+        star.assert_async().await;
+        unstar.assert_async().await;
+    }
+}
@@ -0,0 +1,183 @@
+//!
+//! GitLab
+//! GitLab calls repositories projects and addresses them by their URL-encoded path, e.g.
+//! `group%2Fsubgroup%2Fproject`. Starred projects are listed with `starred=true`, paged through
+//! the `x-next-page` header, and starring is a POST that answers 304 when nothing changed.
+//!
+
+use crate::error::StarsError;
+use crate::forge::{Forge, ForgeRepo};
+use crate::registry::http_client;
+use reqwest::{Client, Method, RequestBuilder, StatusCode};
+use serde::Deserialize;
+
+#[derive(Debug, Deserialize)]
+struct GitLabProject {
+    id: u64,
+    path: String,
+    path_with_namespace: String,
+    #[serde(default)]
+    description: Option<String>,
+    web_url: String,
+    #[serde(default)]
+    star_count: u64,
+}
+
+impl From<GitLabProject> for ForgeRepo {
+    fn from(project: GitLabProject) -> Self {
+        ForgeRepo {
+            id: project.id,
+            name: project.path,
+            full_name: project.path_with_namespace,
+            description: project.description.filter(|description| !description.is_empty()),
+            html_url: project.web_url,
+            stars: project.star_count,
+        }
+    }
+}
+
+// A project path as GitLab expects it in place of the project id
+fn project_id(owner: &str, repo: &str) -> String {
+    format!("{}/{}", owner, repo).replace('/', "%2F")
+}
+
+pub struct GitLab {
+    client: Client,
+    /// e.g. `https://gitlab.com/api/v4`
+    api_url: String,
+    token: String,
+}
+
+impl GitLab {
+    pub fn new(api_url: String, token: String) -> Self {
+        Self { client: http_client(), api_url, token }
+    }
+
+    fn request(&self, method: Method, path: &str) -> RequestBuilder {
+        self.client
+            .request(method, format!("{}{}", self.api_url, path))
+            .header("PRIVATE-TOKEN", &self.token)
+    }
+
+    // Star or unstar a project; 304 means it already was or wasn't starred
+    async fn set_starred(&self, action: &str, owner: &str, repo: &str) -> Result<(), StarsError> {
+        let path = format!("/projects/{}/{}", project_id(owner, repo), action);
+        let response = self.request(Method::POST, &path).send().await?;
+
+        match response.status() {
+            StatusCode::OK | StatusCode::CREATED | StatusCode::NOT_MODIFIED => Ok(()),
+            StatusCode::NOT_FOUND => Err(StarsError::NotFound(format!("Project {}/{} not found", owner, repo))),
+            _ => Err(StarsError::from_response(response).await),
+        }
+    }
+}
+
+impl Forge for GitLab {
+    fn name(&self) -> &'static str {
+        "gitlab"
+    }
+
+    async fn list_stars(&self) -> Result<Vec<ForgeRepo>, StarsError> {
+        let mut starred = Vec::new();
+        let mut page = String::from("1");
+
+        loop {
+            let path = format!("/projects?starred=true&simple=true&per_page=100&page={}", page);
+            let response = self.request(Method::GET, &path).send().await?;
+            if response.status() != StatusCode::OK {
+                return Err(StarsError::from_response(response).await);
+            }
+
+            let next = response
+                .headers()
+                .get("x-next-page")
+                .and_then(|value| value.to_str().ok())
+                .filter(|next| !next.is_empty())
+                .map(String::from);
+            starred.extend(response.json::<Vec<GitLabProject>>().await?.into_iter().map(ForgeRepo::from));
+
+            match next {
+                Some(next) => page = next,
+                None => return Ok(starred),
+            }
+        }
+    }
+
+    async fn star(&self, owner: &str, repo: &str) -> Result<(), StarsError> {
+        self.set_starred("star", owner, repo).await
+    }
+
+    async fn unstar(&self, owner: &str, repo: &str) -> Result<(), StarsError> {
+        self.set_starred("unstar", owner, repo).await
+    }
+
+    // GitLab has no endpoint for a single project, so this looks through the starred list
+    async fn has_starred(&self, owner: &str, repo: &str) -> Result<bool, StarsError> {
+        let full_name = format!("{}/{}", owner, repo);
+        Ok(self
+            .list_stars()
+            .await?
+            .iter()
+            .any(|project| project.full_name.eq_ignore_ascii_case(&full_name)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mockito::Server;
+
+    fn project(id: u64, path: &str) -> serde_json::Value {
+        serde_json::json!({
+            "id": id,
+            "path": path.rsplit('/').next().unwrap(),
+            "path_with_namespace": path,
+            "description": null,
+            "web_url": format!("https://gitlab.com/{}", path),
+            "star_count": 7
+        })
+    }
+
+    #[tokio::test]
+    async fn test_list_starred_follows_next_page() {
+        let mut server = Server::new_async().await;
+        server
+            .mock("GET", "/projects?starred=true&simple=true&per_page=100&page=1")
+            .match_header("private-token", "secret")
+            .with_status(200)
+            .with_header("x-next-page", "2")
+            .with_body(serde_json::json!([project(1, "gitlab-org/gitlab")]).to_string())
+            .create_async()
+            .await;
+        server
+            .mock("GET", "/projects?starred=true&simple=true&per_page=100&page=2")
+            .with_status(200)
+            .with_header("x-next-page", "")
+            .with_body(serde_json::json!([project(2, "group/sub/project")]).to_string())
+            .create_async()
+            .await;
+
+        let gitlab = GitLab::new(server.url(), "secret".to_string());
+        let starred = gitlab.list_stars().await.unwrap();
+
+        assert_eq!(starred.iter().map(|repo| repo.full_name.as_str()).collect::<Vec<_>>(), ["gitlab-org/gitlab", "group/sub/project"]);
+        assert_eq!(starred[1].name, "project");
+        assert!(gitlab.has_starred("GitLab-org", "gitlab").await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_star_and_unstar() {
+        let mut server = Server::new_async().await;
+        let star = server.mock("POST", "/projects/gitlab-org%2Fgitlab/star").with_status(201).create_async().await;
+        let unstar = server.mock("POST", "/projects/gitlab-org%2Fgitlab/unstar").with_status(304).create_async().await;
+        server.mock("POST", "/projects/nobody%2Fnothing/star").with_status(404).create_async().await;
+
+        let gitlab = GitLab::new(server.url(), "secret".to_string());
+        gitlab.star("gitlab-org", "gitlab").await.unwrap();
+        gitlab.unstar("gitlab-org", "gitlab").await.unwrap();
+
+        assert!(matches!(gitlab.star("nobody", "nothing").await, Err(StarsError::NotFound(_))));
+        star.assert_async().await;
+        unstar.assert_async().await;
+    }
+}
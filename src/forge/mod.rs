@@ -0,0 +1,165 @@
+//!
+//! Forges other than GitHub
+//! Stars live on GitLab, Gitea and Forgejo instances such as Codeberg too. Each forge implements
+//! [`Forge`] for listing, starring and unstarring, the part of the tool every forge supports;
+//! everything else stays GitHub only. The forge is chosen by `forge` under `[github]` or a profile.
+//!
+
+pub mod gitea;
+pub mod gitlab;
+
+use crate::api::client::GitHubClient;
+use crate::api::pagination::{Pagination, MAX_PER_PAGE};
+use crate::api::stars::{Star, StarredRepo};
+use crate::config::ForgeKind;
+use crate::error::StarsError;
+use crate::forge::gitea::Gitea;
+use crate::forge::gitlab::GitLab;
+
+/// A starred repository, or project on GitLab, as any forge describes it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ForgeRepo {
+    pub id: u64,
+    pub name: String,
+    /// `owner/repo`, with GitLab subgroups as further path segments
+    pub full_name: String,
+    pub description: Option<String>,
+    pub html_url: String,
+    pub stars: u64,
+}
+
+#[allow(async_fn_in_trait)]
+pub trait Forge {
+    /// Name used in the config file
+    fn name(&self) -> &'static str;
+    /// All my starred repositories
+    async fn list_stars(&self) -> Result<Vec<ForgeRepo>, StarsError>;
+    async fn star(&self, owner: &str, repo: &str) -> Result<(), StarsError>;
+    async fn unstar(&self, owner: &str, repo: &str) -> Result<(), StarsError>;
+    async fn has_starred(&self, owner: &str, repo: &str) -> Result<bool, StarsError>;
+}
+
+impl Forge for GitHubClient {
+    fn name(&self) -> &'static str {
+        "github"
+    }
+
+    async fn list_stars(&self) -> Result<Vec<ForgeRepo>, StarsError> {
+        let pagination = Pagination { per_page: Some(MAX_PER_PAGE), max_pages: None };
+        let starred = Star::list_starred(self, &pagination).await?;
+
+        Ok(starred.repos.into_iter().map(github_repo).collect())
+    }
+
+    async fn star(&self, owner: &str, repo: &str) -> Result<(), StarsError> {
+        self.star_repo(owner, repo).await
+    }
+
+    async fn unstar(&self, owner: &str, repo: &str) -> Result<(), StarsError> {
+        self.unstar_repo(owner, repo).await
+    }
+
+    async fn has_starred(&self, owner: &str, repo: &str) -> Result<bool, StarsError> {
+        Star::is_starred(self, owner, repo).await
+    }
+}
+
+// A starred repository as every forge describes it
+fn github_repo(repo: StarredRepo) -> ForgeRepo {
+    ForgeRepo {
+        id: repo.id,
+        name: repo.name,
+        full_name: repo.full_name,
+        description: repo.description,
+        html_url: repo.html_url,
+        stars: repo.stars,
+    }
+}
+
+/// Any of the supported forges, chosen by the config at runtime
+pub enum AnyForge {
+    GitHub(GitHubClient),
+    GitLab(GitLab),
+    Gitea(Gitea),
+}
+
+impl AnyForge {
+    /// The forge of `kind` at `api_url`, e.g. `https://gitlab.com/api/v4` or
+    /// `https://codeberg.org/api/v1`, authenticated with `token`
+    pub async fn new(kind: ForgeKind, api_url: String, token: String) -> Self {
+        match kind {
+            ForgeKind::Github => Self::GitHub(GitHubClient::new(api_url, token).await),
+            ForgeKind::Gitlab => Self::GitLab(GitLab::new(api_url, token)),
+            ForgeKind::Gitea => Self::Gitea(Gitea::new(api_url, token)),
+        }
+    }
+}
+
+impl Forge for AnyForge {
+    fn name(&self) -> &'static str {
+        match self {
+            Self::GitHub(forge) => forge.name(),
+            Self::GitLab(forge) => forge.name(),
+            Self::Gitea(forge) => forge.name(),
+        }
+    }
+
+    async fn list_stars(&self) -> Result<Vec<ForgeRepo>, StarsError> {
+        match self {
+            Self::GitHub(forge) => forge.list_stars().await,
+            Self::GitLab(forge) => forge.list_stars().await,
+            Self::Gitea(forge) => forge.list_stars().await,
+        }
+    }
+
+    async fn star(&self, owner: &str, repo: &str) -> Result<(), StarsError> {
+        match self {
+            Self::GitHub(forge) => forge.star(owner, repo).await,
+            Self::GitLab(forge) => forge.star(owner, repo).await,
+            Self::Gitea(forge) => forge.star(owner, repo).await,
+        }
+    }
+
+    async fn unstar(&self, owner: &str, repo: &str) -> Result<(), StarsError> {
+        match self {
+            Self::GitHub(forge) => forge.unstar(owner, repo).await,
+            Self::GitLab(forge) => forge.unstar(owner, repo).await,
+            Self::Gitea(forge) => forge.unstar(owner, repo).await,
+        }
+    }
+
+    async fn has_starred(&self, owner: &str, repo: &str) -> Result<bool, StarsError> {
+        match self {
+            Self::GitHub(forge) => forge.has_starred(owner, repo).await,
+            Self::GitLab(forge) => forge.has_starred(owner, repo).await,
+            Self::Gitea(forge) => forge.has_starred(owner, repo).await,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mockito::Server;
+
+    #[tokio::test]
+    async fn test_github_forge() {
+        let mut server = Server::new_async().await;
+        server
+            .mock("GET", "/user/starred?per_page=100")
+            .with_status(200)
+            .with_body(r#"[{"id": 1, "name": "rust", "full_name": "rust-lang/rust", "owner": {"login": "rust-lang"}, "description": null,
+                "html_url": "https://github.com/rust-lang/rust", "stargazers_count": 90000}]"#)
+            .create_async()
+            .await;
+        server.mock("PUT", "/user/starred/rust-lang/rust").with_status(204).create_async().await;
+
+        let forge = AnyForge::new(ForgeKind::Github, server.url(), "token".to_string()).await;
+        let repos = forge.list_stars().await.unwrap();
+
+        assert_eq!(forge.name(), "github");
+        assert_eq!(repos[0].full_name, "rust-lang/rust");
+        assert_eq!(repos[0].stars, 90000);
+        forge.star("rust-lang", "rust").await.unwrap();
+    }
+}
@@ -1,4 +1,44 @@
+//!
+//! GitHub stars as a library
+//! [`GitHubClient`] talks to the GitHub REST and GraphQL APIs. The [`Star`] and [`Repo`] traits it
+//! implements list, star and fetch repositories, and every API call fails with a [`StarsError`].
+//! Both are object safe, and [`StarsApi`] combines them so a `Box<dyn StarsApi>` can stand in for
+//! the client. [`StarsService`] wraps the client with its cache, retry and rate limit layers and
+//! groups its calls, as in `service.stars().list(..)` and `service.repos().details(..)`.
+//! The `starts_fetcher` command line tool is built on the same API.
+//!
+//! ```no_run
+//! use starts_fetcher::{GitHubClient, Pagination, Star, StarsError};
+//!
+//! # async fn run() -> Result<(), StarsError> {
+//! let token = std::env::var("GITHUB_TOKEN").unwrap_or_default();
+//! let github = GitHubClient::new(String::from("https://api.github.com"), token).await;
+//!
+//! let starred = github.list_starred(&Pagination::default()).await?;
+//! for repo in &starred.repos {
+//!     println!("{}", repo.full_name);
+//! }
+//! github.star_repo("rust-lang", "rust").await?;
+//! # Ok(())
+//! # }
+//! ```
+//!
+
 pub mod api;
+pub mod error;
 pub mod ui;
 pub mod utils;
 pub mod config;
+pub mod store;
+pub mod registry;
+pub mod forge;
+pub mod models;
+
+pub use api::client::{GitHubClient, RetryPolicy};
+pub use api::pagination::Pagination;
+pub use api::service::StarsService;
+pub use api::repos::{ForkResponse, Owner, OwnerKind, OwnerResponse, Repo, RepoDetailsResponse, RepoResponse};
+pub use api::stars::{Star, StarDate, StarredRepo, StarredRepos, StarsApi};
+pub use models::{RepoDetails, RepoSummary};
+pub use config::{Config, ForgeKind, ListBackend, ProfileConfig, RetryConfig, TokenSource};
+pub use error::StarsError;
\ No newline at end of file
@@ -1,307 +1,3723 @@
-use clap::{App, Arg, SubCommand};
-use dialoguer::{theme::ColorfulTheme, Select};
-use prettytable::{Table, row, cell};
-use reqwest::{Client, header};
-use serde::{Deserialize, Serialize};
+use clap::{App, Arg, ArgMatches, SubCommand};
+use clap_complete::Shell;
+use console::Term;
+use dialoguer::{theme::ColorfulTheme, MultiSelect, Select};
+use std::cmp::Reverse;
+use std::collections::{HashMap, HashSet};
 use std::error::Error;
 use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use starts_fetcher::api::capabilities::{Availability, TokenCapabilities, TokenKind};
+use starts_fetcher::api::checks::Checks;
+use starts_fetcher::api::cache::ResponseCache;
+use starts_fetcher::api::client::{last_failed_request, GitHubClient, RetryPolicy};
+use starts_fetcher::api::gists::Gist;
+use starts_fetcher::api::{metrics, rate_limit};
+use starts_fetcher::api::pagination::{Pagination, MAX_PER_PAGE};
+use starts_fetcher::api::compare::Compare;
+use starts_fetcher::api::forks::{ForkStatus, Forks};
+use starts_fetcher::api::refs::{RefKind, Refs, MAX_REFS_PER_PAGE};
+use starts_fetcher::api::releases::{releases_back_to, releases_between, verify_download, Release, ReleaseAsset, Releases};
+use starts_fetcher::api::repos::{Owner, Repo as _};
+use starts_fetcher::api::service::StarsService;
+use starts_fetcher::models::RepoDetails;
+use starts_fetcher::api::stars::StarredRepo;
+use starts_fetcher::api::search::{search_query, Search, SearchRepo, SearchSort, SEARCH_SORTS};
+use starts_fetcher::api::stargazers::Stargazers;
+use starts_fetcher::api::stars::Star;
+use starts_fetcher::api::stats::wait_for_commit_activity;
+use starts_fetcher::api::identity::AuthenticatedUser;
+use starts_fetcher::api::rate_limit::RateLimitStatus;
+use starts_fetcher::api::traffic::Traffic;
+use starts_fetcher::api::users::Users;
+use starts_fetcher::config::{Config, ForgeKind, ListBackend, PROFILE_ENV};
+use starts_fetcher::forge::{AnyForge, Forge};
+use starts_fetcher::error::{StarsError, EXIT_FAILURE};
+use starts_fetcher::registry::{AnyRegistry, Registry, REGISTRY_NAMES};
+use starts_fetcher::store::archive::{due_for_archive, LocalArchive};
+use starts_fetcher::store::dump;
+use starts_fetcher::store::history::{CommandHistory, HistoryCommand};
+use starts_fetcher::store::pins::Pins;
+use starts_fetcher::store::receipts::{Receipt, Receipts};
+use starts_fetcher::store::snoozes::Snoozes;
+use starts_fetcher::store::stargazers::StargazerSnapshot;
+use starts_fetcher::store::traffic::TrafficHistory;
+use starts_fetcher::store::usage::Usage;
+use starts_fetcher::store::verify::{find_discrepancies, fix_discrepancies};
+use starts_fetcher::store::Store;
+use starts_fetcher::ui::badge::{format_count, render_shields_json, render_svg};
+use starts_fetcher::ui::browser::{Browser, BrowserAction, BrowserRepo};
+use starts_fetcher::ui::changelog::{render_commits, render_release_notes};
+use starts_fetcher::ui::heatmap::render_heatmap;
+use starts_fetcher::ui::graph::{build_graph, render_dot, render_gexf, GraphLink, GRAPH_LINKS};
+use starts_fetcher::ui::html::render_html;
+use starts_fetcher::ui::treemap::{clusters, MapAction, MapGroup, StarMap, MAP_GROUPS};
+use starts_fetcher::ui::output::{render_output, render_rows, OutputFormat, OUTPUT_FORMATS};
 use starts_fetcher::ui::selector::RepoSelector;
-use serde_json::Value;
+use starts_fetcher::ui::table::{print_table, render_table};
+use starts_fetcher::ui::terminal::{enter_alternate_screen, install_panic_hook, leave_alternate_screen, TerminalGuard};
+use starts_fetcher::utils::bug_report::BugReport;
+use starts_fetcher::utils::checksum::{checksum_files_for, is_checksum_file, sha256_hex, Verification};
+use starts_fetcher::utils::signature::{is_signature_file, signature_file_for, verify_signature, SignatureStatus, Trust};
+use starts_fetcher::utils::collection::{gist_id, parse_collection, proposal_comment};
+use starts_fetcher::utils::install::{find_binary, pick_asset, unpack, ArchiveKind};
+use starts_fetcher::utils::filter::{language_filter, parse_filter, topic_filter, Filter};
+use starts_fetcher::utils::sort::{sort_repos, LIST_SORTS};
+use starts_fetcher::utils::parse::{parse_period, parse_repo_spec, parse_timestamp, parse_topics, RepoRef};
+use starts_fetcher::utils::shutdown::{Graceful, Shutdown};
+use starts_fetcher::utils::recall::{date_of, on_this_day};
+use starts_fetcher::utils::clock::local_time;
+use starts_fetcher::utils::similar::{rank_similar, similar_queries};
+use starts_fetcher::api::tuning::ReadLimit;
+use tokio::task::JoinSet;
 
-#[derive(Serialize, Deserialize, Debug)]
-struct Repo {
-    id: u64,
-    name: String,
-    full_name: String,
-    description: Option<String>,
-    html_url: String,
+// Error returned by long operations stopped early with Ctrl-C
+const INTERRUPTED: &str = "Interrupted";
+
+// Commands that only read public data and work without a token
+const ANONYMOUS_COMMANDS: &[&str] = &["get", "detail", "ci", "releases", "tags", "branches", "changelog", "install", "installed", "uninstall", "rate-limit"];
+
+async fn get_repo(service: &StarsService, owner: &str, repo: &str) -> Result<RepoDetails, Box<dyn Error>> {
+    Ok(service.repos().details(owner, repo).await?)
+}
+
+// Starred repositories with the fields RepoDetails leaves out, with a note when --max-pages stopped the
+// listing early
+async fn list_starred(service: &StarsService, pagination: &Pagination) -> Result<Vec<StarredRepo>, Box<dyn Error>> {
+    let starred = service.stars().list(pagination).await?;
+    if starred.truncated {
+        eprintln!("Note: stopped after {} pages of starred repositories, raise --max-pages to see more", starred.pages);
+    }
+    Ok(starred.repos)
+}
+
+const REPO_HEADERS: &[&str] = &["ID", "Name", "Full Name", "Description", "URL"];
+
+// GitHub usually finishes computing statistics within a few seconds of the first request
+const STATS_RETRIES: u32 = 5;
+const STATS_RETRY_DELAY: Duration = Duration::from_secs(2);
+
+const USAGE_HEADERS: &[&str] = &["Command", "Runs", "Failed", "Average", "Longest", "Total"];
+const STAR_RESULT_HEADERS: &[&str] = &["Repository", "Starred"];
+const REF_HEADERS: &[&str] = &["Name", "Commit", "Date"];
+const INSTALLED_HEADERS: &[&str] = &["Name", "Version", "Repository", "Path"];
+const STARGAZER_HEADERS: &[&str] = &["Repository", "Stargazer", "Starred At", "New"];
+const WHOAMI_HEADERS: &[&str] = &["Login", "Name", "Plan", "Token", "Scopes", "API"];
+
+const RATE_LIMIT_HEADERS: &[&str] = &["Resource", "Remaining", "Limit", "Resets"];
+const ON_THIS_DAY_HEADERS: &[&str] = &["Repository", "Starred At", "Years Ago"];
+const TRAFFIC_HEADERS: &[&str] = &["Date", "Views", "Unique Visitors", "Clones", "Unique Cloners"];
+const REFERRER_HEADERS: &[&str] = &["Referrer", "Views", "Unique Visitors"];
+const CI_HEADERS: &[&str] = &["Repository", "CI", "Failing"];
+const FORK_STATUS_HEADERS: &[&str] = &["Fork", "Upstream", "Behind", "Ahead", "Status"];
+const OWNER_HEADERS: &[&str] = &["Repository", "Stars", "Starred", "Description"];
+const SIMILAR_HEADERS: &[&str] = &["Repository", "Stars", "Shared Topics", "Description"];
+const SEARCH_HEADERS: &[&str] = &["Repository", "Stars", "Language", "Description"];
+const PRUNE_HEADERS: &[&str] = &["Repository", "Stars", "Last Push", "Description"];
+const PACKAGE_HEADERS: &[&str] = &["Repository", "Package", "Version", "Downloads", "Link", "Registry"];
+
+// Results fetched per search query when looking for similar repositories
+const SIMILAR_SEARCH_SIZE: u32 = 30;
+
+// The --from-file option of star and unstar
+fn from_file_arg() -> Arg<'static> {
+    Arg::with_name("from-file")
+        .long("from-file")
+        .takes_value(true)
+        .value_name("FILE")
+        .conflicts_with("owner")
+        .help("Repositories listed one owner/repo per line, - to read them from stdin")
+}
+
+// The --dry-run option of commands that change my stars or write files
+fn dry_run_arg() -> Arg<'static> {
+    Arg::with_name("dry-run")
+        .long("dry-run")
+        .help("Print the API calls and file writes it would make, without making them")
+}
+
+// Print an action a dry run leaves out, e.g. `send DELETE <url>`. Tables print it with the results,
+// other formats on stderr so their output can still be parsed.
+fn print_dry_run(format: OutputFormat, action: &str) {
+    if format.is_table() {
+        println!("Would {}", action);
+    } else {
+        eprintln!("Would {}", action);
+    }
+}
+
+// Print the request starring or unstarring a repository would send
+fn print_star_request(service: &StarsService, format: OutputFormat, owner: &str, repo: &str, star: bool) {
+    let method = if star { "PUT" } else { "DELETE" };
+    print_dry_run(format, &format!("send {} {}", method, service.stars().url(owner, repo)));
+}
+
+// The --format option of commands that print results
+fn format_arg() -> Arg<'static> {
+    Arg::with_name("format")
+        .long("format")
+        .takes_value(true)
+        .possible_values(OUTPUT_FORMATS)
+        .default_value("table")
+        .help("Print a table, JSON, CSV, YAML or a Markdown table")
+}
+
+fn output_format(matches: &ArgMatches) -> Result<OutputFormat, Box<dyn Error>> {
+    let format: OutputFormat = matches.value_of("format").unwrap_or("table").parse()?;
+    Ok(if matches.is_present("no-meta") { format.without_meta() } else { format })
+}
+
+// Print rows in the chosen format, keying JSON and YAML fields by the headers
+fn print_rows(format: OutputFormat, headers: &[&str], rows: &[Vec<String>]) -> Result<(), Box<dyn Error>> {
+    print!("{}", render_rows(format, headers, rows)?);
+    Ok(())
+}
+
+// Print the result of starring or unstarring: a message per repository, or records for scripts
+fn print_star_result(format: OutputFormat, repo_refs: &[RepoRef], starred: bool) -> Result<(), Box<dyn Error>> {
+    if format.is_table() {
+        for repo_ref in repo_refs {
+            println!("{} repository {}", if starred { "Starred" } else { "Unstarred" }, repo_ref);
+        }
+        return Ok(());
+    }
+
+    let rows: Vec<Vec<String>> = repo_refs.iter().map(|repo_ref| vec![repo_ref.full_name(), starred.to_string()]).collect();
+    let records: Vec<serde_json::Value> = repo_refs
+        .iter()
+        .map(|repo_ref| serde_json::json!({ "repository": repo_ref.full_name(), "starred": starred }))
+        .collect();
+    print!("{}", render_output(format, STAR_RESULT_HEADERS, &rows, &records)?);
+    Ok(())
+}
+
+// Star or unstar every repository listed in a file, or on stdin for `-`, in the format team
+// collections use. A line that fails is reported and skipped, and a summary follows the last one.
+async fn star_batch_command(service: &StarsService, shutdown: &Shutdown, matches: &ArgMatches, star: bool) -> Result<(), Box<dyn Error>> {
+    let path = matches.value_of("from-file").unwrap();
+    let text = if path == "-" {
+        std::io::read_to_string(std::io::stdin())?
+    } else {
+        fs::read_to_string(path).map_err(|e| format!("Could not read {}: {}", path, e))?
+    };
+    let collection = parse_collection(&text);
+    for line in &collection.invalid_lines {
+        eprintln!("Skipping invalid line: {}", line);
+    }
+
+    let format = output_format(matches)?;
+    let (verb, done_verb) = if star { ("star", "Starred") } else { ("unstar", "Unstarred") };
+    if matches.is_present("dry-run") {
+        for repo_ref in &collection.repos {
+            print_star_request(service, format, &repo_ref.owner, &repo_ref.repo, star);
+        }
+        print_dry_run(format, &format!(
+            "{} {} repositories, {} invalid lines skipped",
+            verb,
+            collection.repos.len(),
+            collection.invalid_lines.len()
+        ));
+        return Ok(());
+    }
+    let graceful = shutdown.graceful();
+    let mut done = Vec::new();
+    let mut failed = 0;
+    for repo_ref in collection.repos {
+        if graceful.is_cancelled() {
+            break;
+        }
+        let result = if star {
+            service.stars().star(&repo_ref.owner, &repo_ref.repo).await
+        } else {
+            service.stars().unstar(&repo_ref.owner, &repo_ref.repo).await
+        };
+        match result {
+            Ok(()) => {
+                // Tables report each line as it's done, other formats print every record at the end
+                if format.is_table() {
+                    print_star_result(format, std::slice::from_ref(&repo_ref), star)?;
+                }
+                done.push(repo_ref);
+            }
+            Err(e) => {
+                eprintln!("Failed to {} {}: {}", verb, repo_ref, e);
+                failed += 1;
+            }
+        }
+    }
+    if !format.is_table() {
+        print_star_result(format, &done, star)?;
+    }
+
+    let summary = format!(
+        "{} {} repositories, {} failed, {} invalid lines skipped",
+        done_verb,
+        done.len(),
+        failed,
+        collection.invalid_lines.len()
+    );
+    if graceful.is_cancelled() {
+        eprintln!("Interrupted: {}", summary.to_lowercase());
+        return Err(INTERRUPTED.into());
+    }
+    if format.is_table() {
+        println!("{}", summary);
+    } else {
+        eprintln!("{}", summary);
+    }
+    if failed > 0 {
+        return Err(format!("{} repositories could not be {}", failed, done_verb.to_lowercase()).into());
+    }
+    Ok(())
+}
+
+// Print repositories as a table, or in another format
+fn print_repos(repos: &[RepoDetails], format: OutputFormat) -> Result<(), Box<dyn Error>> {
+    let rows: Vec<Vec<String>> = repos
+        .iter()
+        .map(|repo| vec![
+            repo.id.to_string(),
+            repo.name.clone(),
+            repo.full_name.clone(),
+            repo.description.clone().unwrap_or_default(),
+            repo.html_url.clone(),
+        ])
+        .collect();
+
+    print!("{}", render_output(format, REPO_HEADERS, &rows, repos)?);
+    Ok(())
+}
+
+// Fetch many repositories concurrently, keeping the order they were listed in and retuning the
+// limit as responses come in. Once cancelled, requests in flight finish but repositories still
+// waiting are left out.
+async fn get_repos(
+    service: &StarsService,
+    repos: Vec<RepoRef>,
+    mut limit: ReadLimit,
+    graceful: &Graceful,
+) -> Vec<Result<RepoDetails, String>> {
+    let semaphore = limit.semaphore();
+    let mut tasks = JoinSet::new();
+
+    for (index, repo_ref) in repos.into_iter().enumerate() {
+        let (service, semaphore) = (service.clone(), semaphore.clone());
+        let token = graceful.token().clone();
+        tasks.spawn(async move {
+            let _permit = tokio::select! {
+                biased;
+                _ = token.cancelled() => return None,
+                permit = semaphore.acquire_owned() => permit,
+            };
+            let result = get_repo(&service, &repo_ref.owner, &repo_ref.repo)
+                .await
+                .map_err(|e| format!("{}: {}", repo_ref, e));
+            Some((index, result))
+        });
+    }
+
+    let mut results: Vec<(usize, Result<RepoDetails, String>)> = Vec::new();
+    while let Some(joined) = tasks.join_next().await {
+        limit.retune();
+        match joined {
+            Ok(Some(result)) => results.push(result),
+            Ok(None) => {}
+            Err(e) => results.push((usize::MAX, Err(e.to_string()))),
+        }
+    }
+    results.sort_by_key(|(index, _)| *index);
+    results.into_iter().map(|(_, result)| result).collect()
+}
+
+// Fetch every repository listed in a file and print them together
+async fn get_batch_command(service: &StarsService, shutdown: &Shutdown, matches: &ArgMatches) -> Result<(), Box<dyn Error>> {
+    let collection = parse_collection(&fs::read_to_string(matches.value_of("batch").unwrap())?);
+    for line in &collection.invalid_lines {
+        eprintln!("Skipping invalid line: {}", line);
+    }
+
+    let concurrency = Config::new()?.concurrency;
+    let limit = ReadLimit::new(concurrency.api_reads, concurrency.adaptive);
+    let graceful = shutdown.graceful();
+    let total = collection.repos.len();
+    let mut repos = Vec::new();
+    let mut failed = 0;
+    for result in get_repos(service, collection.repos, limit, &graceful).await {
+        match result {
+            Ok(repo) => repos.push(repo),
+            Err(e) => {
+                eprintln!("Failed to fetch {}", e);
+                failed += 1;
+            }
+        }
+    }
+
+    print_repos(&repos, output_format(matches)?)?;
+
+    if graceful.is_cancelled() {
+        eprintln!("Interrupted after fetching {} of {} repositories ({} failed)", repos.len() + failed, total, failed);
+        return Err(INTERRUPTED.into());
+    }
+    if failed > 0 {
+        return Err(format!("{} repositories could not be fetched", failed).into());
+    }
+    Ok(())
+}
+
+// Display help information
+fn show_help() {
+    println!("GitHub CLI Tool - Commands:");
+    println!("  get <owner> <repo>      - Fetch information about a repository");
+    println!("  get --batch FILE [--format json] - Fetch every repository listed in a file");
+    println!("  list [--filter F] [--language L1,L2] [--topic T]... [--sort S [--desc]] [--per-page N] [--max-pages N] - List all starred repositories, optionally filtered");
+    println!("  star <owner> <repo>     - Star a repository");
+    println!("  unstar <owner> <repo>   - Unstar a repository");
+    println!("  unstar --interactive    - Pick repositories to unstar from a list of my stars");
+    println!("  star|unstar --from-file FILE - Star or unstar every repository listed in a file, - for stdin");
+    println!("  detail <owner> <repo>   - Get detailed information about a repository");
+    println!("  badge [--repo R] [--gist [ID]] - Generate a star count badge (SVG or shields.io JSON)");
+    println!("  my-stargazers [--repo NAME] - List who recently starred my repositories");
+    println!("  fork <owner/repo> [--star] - Fork a repository, optionally starring the upstream");
+    println!("  fork-status <repo> | --all - Show how far forks are behind their upstream");
+    println!("  releases download <owner/repo> --asset A [--tag T] [--verify-signature] - Download and verify release assets");
+    println!("  tags <owner/repo> [--page N] - List tags with commit dates");
+    println!("  branches <owner/repo> [--page N] - List branches with commit dates");
+    println!("  changelog <owner/repo> --from TAG [--to TAG] [--pick] - Show release notes between two versions");
+    println!("  install <owner/repo> [--bin-dir DIR] - Install a release binary for this platform");
+    println!("  install --upgrade-all   - Upgrade every installed binary to its latest release");
+    println!("  installed list          - List binaries installed from releases");
+    println!("  uninstall <name>        - Remove an installed binary");
+    println!("  topics set <repo> <a,b> - Replace the topics of my repository");
+    println!("  traffic <repo> [--csv FILE] - Show views, clones and referrers for my repository");
+    println!("  ci <owner/repo>... [--file FILE] - Show CI status of default branches");
+    println!("  activity <owner/repo>   - Show weekly commit activity as a heatmap");
+    println!("  owner <login>           - Show an owner's profile and most-starred repositories");
+    println!("  similar <owner/repo> [--star] - Find similar repositories I haven't starred");
+    println!("  prune --archived [--yes] - Unstar starred repositories their owners archived");
+    println!("  prune --inactive-years N [--dry-run] - Unstar starred repositories nobody pushed to in N years");
+    println!("  search <query> [--language L] [--stars RANGE] [--sort S] - Search GitHub and star repositories picked from the results");
+    println!("  crates                  - Show crates published from my starred Rust repositories");
+    println!("  crates find <crate>     - Check whether I've starred a crate's source repository");
+    println!("  packages [--registry R] - Show crates.io, npm and PyPI packages from my starred repositories");
+    println!("  packages find <name> --registry R - Check whether I've starred a package's source repository");
+    println!("  collection pull <gist>  - Star every repository in a team collection");
+    println!("  collection propose <gist> <repo> - Propose a repository for a team collection");
+    println!("  pin [owner/repo]        - Pin a repository so list and --interactive show it first, or show pins");
+    println!("  unpin <owner/repo>      - Unpin a repository");
+    println!("  snooze [owner/repo --for 30d] - Hide a repository from listings for a while, or show snoozes");
+    println!("  unsnooze <owner/repo>   - Show a snoozed repository again");
+    println!("  archive [owner/repo]    - Hide a star from listings for good, or show archived stars");
+    println!("  archive --auto [--dry-run] - Archive stars older than auto_archive_after that I never used");
+    println!("  unarchive <owner/repo>  - Show an archived star again");
+    println!("  verify [--fix]          - Find pins, snoozes and archived stars that no longer match my stars on GitHub");
+    println!("  cache stats             - Show how many responses are kept for conditional requests and how often they're used");
+    println!("  cache clear             - Forget every kept response");
+    println!("  cache prune --older-than 30d - Forget responses fetched longer ago than a period");
+    println!("  whoami                  - Show the account and token in use, with the token's scopes");
+    println!("  rate-limit [--format F] - Show the core, search and GraphQL requests left and when they reset");
+    println!("  onthisday [--date YYYY-MM-DD] - List repositories I starred on this day in earlier years");
+    println!("  map [--by language|topic|owner] - Show my stars as a treemap, arrow keys to move, Enter to pick from a group");
+    println!("  export [--format graphviz|gexf] [--link owner,topic,language] [-o FILE] - Write my stars as a graph of shared owners, topics and languages");
+    println!("  export --format html [-o FILE] - Write my stars as one HTML page to search and filter them in a browser");
+    println!("  manpage [-o DIR]        - Write man pages for every command, into ./man by default");
+    println!("  completions <SHELL>     - Print a completion script for bash, zsh, fish, powershell or elvish");
+    println!("  store dump [--format csv|json] [-o DIR] - Write the local data as tables for DuckDB, pandas or a spreadsheet");
+    println!("  store rollback          - Put back the local data as it was before an upgrade migrated it");
+    println!("  stats --self [--reset]  - Show which commands I use, recorded only on this machine");
+    println!("  config get <KEY>        - Print a setting, e.g. github.api_url");
+    println!("  config set <KEY> <VALUE> - Change a setting, e.g. retry.max_attempts 5");
+    println!("  config list             - Print every setting, with secrets masked");
+    println!("  config path             - Print where the config file is");
+    println!("  config edit             - Open the config file in $VISUAL or $EDITOR and check it afterwards");
+    println!("  config export [-o FILE] - Export settings without secrets");
+    println!("  config import <FILE>    - Import settings exported on another machine");
+    println!("  config keyring          - Move the token from config.toml to the system keyring");
+    println!("  --interactive           - Browse starred repositories full screen, starring, cloning and opening them");
+    println!("  --format F              - Print results of list, get, detail, star and other listing commands");
+    println!("                            as table (default), json, csv, yaml or markdown");
+    println!("  --no-meta               - Print JSON results as a bare array, without the metadata envelope");
+    println!("  --dry-run               - With star, unstar, prune or releases download, print the API calls and");
+    println!("                            file writes they would make instead of making them");
+    println!("  --bug-report            - If the command fails, write a report without secrets to attach to an issue");
+    println!("  --wait-on-ratelimit     - When the rate limit runs out, count down until it resets and carry on");
+    println!("  --no-cache              - Fetch everything in full, without the responses kept for conditional requests");
+    println!("  -v, -vv                 - Report the API requests made when done, per endpoint with -vv");
+    println!("  --profile NAME          - Use the token, email and API URL of a [profiles.NAME] section of the config");
+    println!();
+    println!("Example usage:");
+    println!("  github-cli list");
+    println!("  github-cli star octocat hello-world");
+    println!("  github-cli get rust-lang/rust");
+    println!();
+    println!("Note: a token must be set in the config file or GITHUB_TOKEN, except for get, detail, ci, releases, tags, branches,");
+    println!("      changelog and the install commands");
+    println!("      which fall back to anonymous access with a lower rate limit");
+    println!("      With forge = \"gitlab\" or \"gitea\" in the config, only list, star and unstar are available");
+}
+
+// Items per page when a command isn't given --per-page: the config's, or the most GitHub allows
+// so long lists take as few requests as possible
+fn default_per_page() -> u32 {
+    Config::new().ok().and_then(|config| config.pagination.per_page).unwrap_or(MAX_PER_PAGE)
+}
+
+// Every page of a list
+fn all_pages() -> Pagination {
+    Pagination { per_page: Some(default_per_page()), max_pages: None }
+}
+
+// The --per-page and --max-pages options of a list command
+fn pagination_args(matches: &ArgMatches) -> Result<Pagination, Box<dyn Error>> {
+    let per_page = match matches.value_of("per-page") {
+        Some(per_page) => match per_page.parse() {
+            Ok(per_page @ 1..=MAX_PER_PAGE) => per_page,
+            _ => return Err(format!("--per-page must be between 1 and {}", MAX_PER_PAGE).into()),
+        },
+        None => default_per_page(),
+    };
+    let max_pages = match matches.value_of("max-pages") {
+        Some(max_pages) => match max_pages.parse() {
+            Ok(max_pages) if max_pages > 0 => Some(max_pages),
+            _ => return Err("--max-pages must be a positive number".into()),
+        },
+        None => None,
+    };
+
+    Ok(Pagination { per_page: Some(per_page), max_pages })
+}
+
+// Resolve the <owner> <repo> arguments, which may also be given as a single owner/repo
+fn repo_ref_args(matches: &ArgMatches) -> Result<RepoRef, Box<dyn Error>> {
+    let owner = matches.value_of("owner").unwrap();
+    match matches.value_of("repo") {
+        Some(repo) => RepoRef::new(owner, repo),
+        None => parse_repo_spec(owner),
+    }
+}
+
+// The command recorded in usage statistics: the subcommand and any nested subcommand, e.g.
+// `releases download`
+fn usage_command_name(app: &ArgMatches) -> String {
+    if app.is_present("interactive") {
+        return String::from("--interactive");
+    }
+
+    let mut names = Vec::new();
+    let mut matches = app;
+    while let Some((name, sub_m)) = matches.subcommand() {
+        names.push(name);
+        matches = sub_m;
+    }
+    names.join(" ")
+}
+
+// Count a run in the local usage statistics. They are a convenience, so failures are ignored.
+// Looking at the statistics isn't counted, so a reset leaves them empty.
+fn record_usage(app: &ArgMatches, duration: Duration, succeeded: bool) {
+    let command = usage_command_name(app);
+    if command.is_empty() || command == "stats" {
+        return;
+    }
+
+    if let Ok(store) = Store::open_default() {
+        if let Ok(mut usage) = Usage::load(&store) {
+            let requests = metrics::snapshot();
+            usage.record(&command, duration, succeeded);
+            usage.record_responses(requests.cache_hits, requests.cache_misses);
+            let _ = usage.save(&store);
+        }
+    }
+}
+
+// Seconds since the Unix epoch
+fn unix_now() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or_default()
+}
+
+// Seconds as a rough period, rounded up, e.g. 29d, 5h or 12m
+fn format_period(secs: u64) -> String {
+    match secs {
+        secs if secs >= 24 * 60 * 60 => format!("{}d", secs.div_ceil(24 * 60 * 60)),
+        secs if secs >= 60 * 60 => format!("{}h", secs.div_ceil(60 * 60)),
+        secs => format!("{}m", secs.div_ceil(60)),
+    }
+}
+
+// Milliseconds as a short duration, e.g. 850ms or 12.3s
+fn format_ms(ms: u64) -> String {
+    if ms < 1000 {
+        format!("{}ms", ms)
+    } else {
+        format!("{:.1}s", ms as f64 / 1000.0)
+    }
+}
+
+// Show or reset the usage statistics recorded on this machine
+fn stats_command(matches: &ArgMatches) -> Result<(), Box<dyn Error>> {
+    let store = Store::open_default()?;
+    if matches.is_present("reset") {
+        Usage::reset(&store)?;
+        println!("Forgot the recorded usage");
+        return Ok(());
+    }
+
+    // Usage is local data, so the envelope describing API requests doesn't apply
+    let format = output_format(matches)?.without_meta();
+    let usage = Usage::load(&store)?;
+    let commands = usage.by_runs();
+    if commands.is_empty() && format.is_table() {
+        println!("No usage recorded yet");
+        return Ok(());
+    }
+
+    let rows: Vec<Vec<String>> = commands
+        .iter()
+        .map(|(command, usage)| vec![
+            command.to_string(),
+            usage.runs.to_string(),
+            usage.failures.to_string(),
+            format_ms(usage.average_ms()),
+            format_ms(usage.longest_ms),
+            format_ms(usage.total_ms),
+        ])
+        .collect();
+    print!("{}", render_output(format, USAGE_HEADERS, &rows, &usage.commands)?);
+    if format.is_table() {
+        println!("Recorded locally in {} and never sent anywhere", store.root().display());
+    }
+
+    Ok(())
+}
+
+// Export or import settings, never touching secrets
+fn config_command(matches: &ArgMatches) -> Result<(), Box<dyn Error>> {
+    match matches.subcommand() {
+        Some(("get", sub_m)) => {
+            let key = sub_m.value_of("key").unwrap();
+            match Config::new()?.get(key)? {
+                Some(value) => println!("{}", value),
+                None => return Err(format!("{} is not set", key).into()),
+            }
+        }
+        Some(("set", sub_m)) => {
+            let (key, value) = (sub_m.value_of("key").unwrap(), sub_m.value_of("value").unwrap());
+            Config::stored()?.set(key, value)?.save()?;
+            println!("Set {} in {}", key, Config::path()?.display());
+        }
+        Some(("list", _)) => {
+            for (key, value) in Config::new()?.settings()? {
+                println!("{} = {}", key, value);
+            }
+        }
+        Some(("path", _)) => println!("{}", Config::path()?.display()),
+        Some(("edit", _)) => edit_config()?,
+        Some(("export", sub_m)) => {
+            let exported = Config::new()?.export()?;
+            match sub_m.value_of("output") {
+                Some(path) => {
+                    fs::write(path, exported)?;
+                    println!("Exported settings to {}", path);
+                }
+                None => print!("{}", exported),
+            }
+        }
+        Some(("import", sub_m)) => {
+            let path = sub_m.value_of("file").unwrap();
+            let contents = fs::read_to_string(path)?;
+            // The config file as saved, so GITHUB_TOKEN or a keyring token never ends up on disk
+            let current = if Config::path()?.exists() { Config::stored().ok() } else { None };
+            let config = Config::import(&contents, current.as_ref())?;
+            config.save()?;
+            println!("Imported settings from {} into {}", path, Config::path()?.display());
+            if config.github.token.is_empty() {
+                println!("Note: the config file holds no token, GITHUB_TOKEN or the keyring is used when set");
+            }
+        }
+        Some(("keyring", _)) => {
+            Config::new()?.move_token_to_keyring()?;
+            println!("Moved the token to the system keyring, {} no longer holds it", Config::path()?.display());
+        }
+        _ => show_help(),
+    }
+
+    Ok(())
+}
+
+// Render a star count badge, printing or saving it and optionally publishing it to a gist
+async fn badge_command(github: &GitHubClient, matches: &ArgMatches) -> Result<(), Box<dyn Error>> {
+    let (label, count) = match matches.value_of("repo") {
+        Some(spec) => {
+            let repo_ref = parse_repo_spec(spec)?;
+            ("stars", github.get_repo(&repo_ref.owner, &repo_ref.repo).await?.stars)
+        }
+        None => ("starred", github.starred_count().await?),
+    };
+    let label = matches.value_of("label").unwrap_or(label);
+    let message = format_count(count);
+
+    let (content, extension) = match matches.value_of("format") {
+        Some("json") => (render_shields_json(label, &message), "json"),
+        _ => (render_svg(label, &message), "svg"),
+    };
+
+    match matches.value_of("output") {
+        Some(path) => {
+            fs::write(path, &content)?;
+            println!("Wrote badge to {}", path);
+        }
+        None if !matches.is_present("gist") => print!("{}", content),
+        None => {}
+    }
+
+    if matches.is_present("gist") {
+        let filename = format!("stars-badge.{}", extension);
+        let gist = match matches.value_of("gist") {
+            Some(id) => github.update_gist(id, &filename, &content).await?,
+            None => github.create_gist("Star count badge by stars_fetcher", true, &filename, &content).await?,
+        };
+        println!("Published badge to {}", gist.html_url);
+    }
+
+    Ok(())
+}
+
+// List recent stargazers across my repositories, diffed against the logins seen on earlier runs
+async fn my_stargazers_command(github: &GitHubClient, matches: &ArgMatches) -> Result<(), Box<dyn Error>> {
+    let limit: usize = matches.value_of("limit").unwrap().parse().map_err(|_| "--limit must be a number")?;
+    let store = Store::open_default()?;
+    let login = github.cached_authenticated_user(&store).await?.login;
+
+    let repos = match matches.value_of("repo") {
+        Some(name) => vec![RepoRef::new(&login, name)?],
+        None => github
+            .list_owned_repos()
+            .await?
+            .into_iter()
+            .filter(|repo| repo.stars > 0)
+            .map(|repo| RepoRef::new(&repo.owner.login, &repo.name))
+            .collect::<Result<_, _>>()?,
+    };
+
+    let mut snapshot = StargazerSnapshot::load(&store)?;
+    let mut recent = Vec::new();
+    for repo in &repos {
+        let stargazers = github.recent_stargazers(&repo.owner, &repo.repo).await?;
+        let new = snapshot.record(&repo.full_name(), &stargazers);
+        recent.extend(stargazers.into_iter().map(|stargazer| {
+            let is_new = new.contains(&stargazer.user.login);
+            (repo.full_name(), stargazer, is_new)
+        }));
+    }
+    snapshot.save(&store)?;
+
+    let new_count = recent.iter().filter(|(_, _, is_new)| *is_new).count();
+    recent.sort_by(|(_, a, _), (_, b, _)| b.starred_at.cmp(&a.starred_at));
+    let rows: Vec<Vec<String>> = recent
+        .into_iter()
+        .take(limit)
+        .map(|(repo, stargazer, is_new)| vec![
+            repo,
+            stargazer.user.login,
+            stargazer.starred_at,
+            if is_new { String::from("new") } else { String::new() },
+        ])
+        .collect();
+    let format = output_format(matches)?;
+    print_rows(format, STARGAZER_HEADERS, &rows)?;
+    if format.is_table() {
+        println!("{} new stargazers since the last run", new_count);
+    }
+
+    Ok(())
+}
+
+// Resolve a repository argument where a bare name refers to one of my own repositories
+async fn own_repo_ref(github: &GitHubClient, store: &Store, spec: &str) -> Result<RepoRef, Box<dyn Error>> {
+    match parse_repo_spec(spec) {
+        Ok(repo_ref) => Ok(repo_ref),
+        Err(_) if !spec.contains('/') => RepoRef::new(&github.cached_authenticated_user(store).await?.login, spec),
+        Err(e) => Err(e),
+    }
+}
+
+// Check the asset's own detached signature or, when it has none, the signature on the checksum file
+// that verified it, over the very copy of that file the asset was hashed against. Anything short of a
+// good signature is an error so the asset isn't saved.
+async fn verify_release_signature(
+    github: &GitHubClient,
+    release: &Release,
+    asset: &ReleaseAsset,
+    bytes: &[u8],
+    checksums: Option<(&str, &[u8])>,
+) -> Result<(), Box<dyn Error>> {
+    let names: Vec<&str> = release.assets.iter().map(|asset| asset.name.as_str()).collect();
+    let (signed, signed_bytes) = match (signature_file_for(&asset.name, &names), checksums) {
+        (Some(_), _) => (asset, bytes),
+        (None, Some((checksum_file, contents))) if signature_file_for(checksum_file, &names).is_some() => {
+            (release.asset(checksum_file).ok_or("Checksum file disappeared from the release")?, contents)
+        }
+        _ => return Err(format!("{} publishes no signature for {}; the file was not saved", release.tag_name, asset.name).into()),
+    };
+
+    let signature_name = signature_file_for(&signed.name, &names).unwrap();
+    let signature = github.download_asset(release.asset(signature_name).unwrap()).await?;
+    match verify_signature(signed_bytes, &signature)? {
+        SignatureStatus::Good { key_id, signer, trust } => {
+            println!("Good signature on {} from {} (key {}), trust: {}", signed.name, signer, key_id, trust);
+            match trust {
+                Trust::Never => return Err(format!("Key {} is marked as never trusted; the file was not saved", key_id).into()),
+                Trust::Undefined | Trust::Marginal => eprintln!(
+                    "WARNING: key {} is not certified by a key you trust, so it may not really belong to {}",
+                    key_id, signer
+                ),
+                Trust::Full | Trust::Ultimate => {}
+            }
+            Ok(())
+        }
+        SignatureStatus::Bad { key_id, signer } => Err(format!(
+            "BAD signature on {} from {} (key {}); the file was not saved", signed.name, signer, key_id
+        ).into()),
+        SignatureStatus::MissingKey { key_id } => Err(format!(
+            "Can't check the signature on {}: key {} isn't in your keyring, import it with `gpg --recv-keys {}`",
+            signed.name, key_id, key_id
+        ).into()),
+        SignatureStatus::Unverifiable(reason) => Err(format!(
+            "Can't check the signature on {}: {}; the file was not saved", signed.name, reason
+        ).into()),
+    }
+}
+
+// The release with the given tag, or the latest one
+async fn fetch_release(github: &GitHubClient, repo_ref: &RepoRef, tag: Option<&str>) -> Result<Release, StarsError> {
+    match tag {
+        Some(tag) => github.release_by_tag(&repo_ref.owner, &repo_ref.repo, tag).await,
+        None => github.latest_release(&repo_ref.owner, &repo_ref.repo).await,
+    }
+}
+
+// Download a release asset, checking it against the published checksums and, when asked, its signature
+async fn download_verified(
+    github: &GitHubClient,
+    repo_ref: &RepoRef,
+    release: &Release,
+    asset: &ReleaseAsset,
+    require_signature: bool,
+) -> Result<Vec<u8>, Box<dyn Error>> {
+    let bytes = github.download_asset(asset).await?;
+    let mut verified_by = None;
+    match verify_download(github, release, asset, &bytes).await? {
+        Verification::Verified { checksum_file, contents } => {
+            println!("Verified SHA-256 of {} against {}", asset.name, checksum_file);
+            verified_by = Some((checksum_file, contents));
+        }
+        Verification::Mismatch { checksum_file, expected, actual } => {
+            return Err(format!(
+                "Checksum mismatch for {}: {} lists {} but the download hashes to {}; the file was not saved",
+                asset.name, checksum_file, expected, actual
+            ).into());
+        }
+        Verification::Unavailable => {
+            eprintln!(
+                "WARNING: {} {} publishes no checksum for {}; its integrity could NOT be verified",
+                repo_ref, release.tag_name, asset.name
+            );
+        }
+    }
+
+    if require_signature {
+        let checksums = verified_by.as_ref().map(|(name, contents)| (name.as_str(), contents.as_slice()));
+        verify_release_signature(github, release, asset, &bytes, checksums).await?;
+    }
+
+    Ok(bytes)
+}
+
+// Download release assets, verifying each against the checksums published with the release
+async fn releases_download_command(github: &GitHubClient, matches: &ArgMatches) -> Result<(), Box<dyn Error>> {
+    let repo_ref = parse_repo_spec(matches.value_of("repo").unwrap())?;
+    let tag = tag_option(github, &repo_ref, matches).await?;
+    let release = fetch_release(github, &repo_ref, tag.as_deref()).await?;
+
+    let pattern = matches.value_of("asset").unwrap();
+    let assets: Vec<&ReleaseAsset> = release
+        .assets
+        .iter()
+        .filter(|asset| !is_checksum_file(&asset.name) && !is_signature_file(&asset.name) && asset.name.contains(pattern))
+        .collect();
+    if assets.is_empty() {
+        let available: Vec<&str> = release.assets.iter().map(|asset| asset.name.as_str()).collect();
+        return Err(format!(
+            "No asset of {} {} matches {:?}; available: {}",
+            repo_ref, release.tag_name, pattern, available.join(", ")
+        ).into());
+    }
+
+    let output = Path::new(matches.value_of("output").unwrap());
+    if matches.is_present("dry-run") {
+        let names: Vec<&str> = release.assets.iter().map(|asset| asset.name.as_str()).collect();
+        for asset in assets {
+            let file_name = Path::new(&asset.name).file_name().ok_or("Asset has no file name")?;
+            print_dry_run(OutputFormat::Table, &format!("send GET {} to download {} ({} bytes)", asset.url, asset.name, asset.size));
+            // The first published checksum file is read, and the next ones only if it doesn't list the asset
+            if let Some(checksums) = checksum_files_for(&asset.name, &names).into_iter().find_map(|name| release.asset(name)) {
+                print_dry_run(OutputFormat::Table, &format!("send GET {} to download {} and check the SHA-256", checksums.url, checksums.name));
+            }
+            if matches.is_present("verify-signature") {
+                print_dry_run(OutputFormat::Table, "download the release's OpenPGP signature and check it with gpg --verify");
+            }
+            print_dry_run(OutputFormat::Table, &format!("write {}", output.join(file_name).display()));
+        }
+        return Ok(());
+    }
+    for asset in assets {
+        let bytes = download_verified(github, &repo_ref, &release, asset, matches.is_present("verify-signature")).await?;
+
+        // Asset names come from the release, so keep only the file name part
+        let file_name = Path::new(&asset.name).file_name().ok_or("Asset has no file name")?;
+        let path = output.join(file_name);
+        fs::write(&path, &bytes)?;
+        println!("Saved {}", path.display());
+    }
+
+    Ok(())
+}
+
+// Install the binary for this platform from a release into bin_dir, returning its name and receipt.
+// `bin` names the executable to take from archives holding several, defaulting to the repository name.
+async fn install_release(
+    github: &GitHubClient,
+    repo_ref: &RepoRef,
+    release: &Release,
+    bin: Option<&str>,
+    bin_dir: &Path,
+    require_signature: bool,
+) -> Result<(String, Receipt), Box<dyn Error>> {
+    let names: Vec<&str> = release.assets.iter().map(|asset| asset.name.as_str()).collect();
+    let asset_name = pick_asset(&names, env::consts::OS, env::consts::ARCH).ok_or_else(|| format!(
+        "{} {} has no asset for {}-{}; available: {}",
+        repo_ref, release.tag_name, env::consts::OS, env::consts::ARCH, names.join(", ")
+    ))?;
+    let asset = release.asset(asset_name).ok_or("Picked asset is missing from the release")?;
+    let kind = ArchiveKind::from_name(&asset.name).ok_or("Picked asset can't be installed")?;
+    let bytes = download_verified(github, repo_ref, release, asset, require_signature).await?;
+
+    let work_dir = tempfile::tempdir()?;
+    let file_name = Path::new(&asset.name).file_name().ok_or("Asset has no file name")?;
+    let archive = work_dir.path().join(file_name);
+    fs::write(&archive, &bytes)?;
+    let unpacked = work_dir.path().join("unpacked");
+    unpack(&archive, kind, &unpacked)?;
+
+    let name = bin.unwrap_or(&repo_ref.repo);
+    let (binary, installed_name) = match kind {
+        // A bare binary is named after its platform, so install it under the plain name
+        ArchiveKind::Binary => (unpacked.join(file_name), format!("{}{}", name, env::consts::EXE_SUFFIX)),
+        ArchiveKind::Tar | ArchiveKind::Zip => {
+            let binary = find_binary(&unpacked, name)?;
+            let installed_name = binary.file_name().ok_or("Binary has no file name")?.to_string_lossy().to_string();
+            (binary, installed_name)
+        }
+    };
+
+    // Copy next to the target and rename over it, so a running binary is replaced rather than overwritten
+    fs::create_dir_all(bin_dir)?;
+    let target = bin_dir.join(&installed_name);
+    let staged = bin_dir.join(format!(".{}.tmp", installed_name));
+    fs::copy(&binary, &staged)?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        fs::set_permissions(&staged, fs::Permissions::from_mode(0o755))?;
+    }
+    fs::rename(&staged, &target)?;
+
+    let receipt_name = Path::new(&installed_name).file_stem().unwrap_or_default().to_string_lossy().to_string();
+    Ok((receipt_name, Receipt {
+        repo: repo_ref.full_name(),
+        tag: release.tag_name.clone(),
+        asset: asset.name.clone(),
+        path: target,
+        sha256: sha256_hex(&bytes),
+    }))
+}
+
+// Install a binary from a repository's releases, or upgrade everything installed before
+async fn install_command(github: &GitHubClient, shutdown: &Shutdown, matches: &ArgMatches) -> Result<(), Box<dyn Error>> {
+    let store = Store::open_default()?;
+    let mut receipts = Receipts::load(&store)?;
+    let require_signature = matches.is_present("verify-signature");
+
+    if matches.is_present("upgrade-all") {
+        if receipts.installed.is_empty() {
+            println!("Nothing has been installed yet");
+        }
+
+        // Receipts are saved after every upgrade, so stopping between upgrades loses nothing
+        let graceful = shutdown.graceful();
+        let (mut checked, mut failed) = (0, 0);
+        for (name, receipt) in receipts.installed.clone() {
+            if graceful.is_cancelled() {
+                break;
+            }
+            checked += 1;
+            let upgrade = async {
+                let repo_ref = parse_repo_spec(&receipt.repo)?;
+                let latest = github.latest_release(&repo_ref.owner, &repo_ref.repo).await?;
+                if latest.tag_name == receipt.tag {
+                    println!("{} {} is up to date", name, receipt.tag);
+                    return Ok(None);
+                }
+
+                // Upgrades go wherever the binary was installed, even if bin_dir changed since
+                let bin_dir = receipt.path.parent().ok_or("Receipt has no install directory")?;
+                install_release(github, &repo_ref, &latest, Some(&name), bin_dir, require_signature).await.map(Some)
+            };
+
+            match upgrade.await {
+                Ok(Some((name, upgraded))) => {
+                    println!("Upgraded {} from {} to {}", name, receipt.tag, upgraded.tag);
+                    receipts.record(&name, upgraded);
+                    receipts.save(&store)?;
+                }
+                Ok(None) => {}
+                Err(e) => {
+                    eprintln!("Failed to upgrade {}: {}", name, e);
+                    failed += 1;
+                }
+            }
+        }
+
+        if graceful.is_cancelled() {
+            eprintln!("Interrupted after checking {} of {} installed binaries ({} failed)", checked, receipts.installed.len(), failed);
+            return Err(INTERRUPTED.into());
+        }
+        if failed > 0 {
+            return Err(format!("Failed to upgrade {} binaries", failed).into());
+        }
+        return Ok(());
+    }
+
+    let bin_dir = match matches.value_of("bin-dir") {
+        Some(bin_dir) => PathBuf::from(bin_dir),
+        None => Config::new()?.bin_dir()?,
+    };
+    let repo_ref = parse_repo_spec(matches.value_of("repo").unwrap())?;
+    let tag = tag_option(github, &repo_ref, matches).await?;
+    let release = fetch_release(github, &repo_ref, tag.as_deref()).await?;
+
+    let (name, receipt) = install_release(
+        github, &repo_ref, &release, matches.value_of("bin"), &bin_dir, require_signature,
+    ).await?;
+    println!("Installed {} {} from {} to {}", name, receipt.tag, repo_ref, receipt.path.display());
+    receipts.record(&name, receipt);
+    receipts.save(&store)?;
+
+    Ok(())
+}
+
+// List the binaries installed from releases
+fn installed_list_command(matches: &ArgMatches) -> Result<(), Box<dyn Error>> {
+    let format = output_format(matches)?;
+    let receipts = Receipts::load(&Store::open_default()?)?;
+    if receipts.installed.is_empty() && format.is_table() {
+        println!("Nothing has been installed yet");
+        return Ok(());
+    }
+
+    let rows: Vec<Vec<String>> = receipts
+        .installed
+        .iter()
+        .map(|(name, receipt)| vec![
+            name.clone(),
+            receipt.tag.clone(),
+            receipt.repo.clone(),
+            receipt.path.display().to_string(),
+        ])
+        .collect();
+
+    print_rows(format, INSTALLED_HEADERS, &rows)
+}
+
+// Remove an installed binary and its receipt
+fn uninstall_command(matches: &ArgMatches) -> Result<(), Box<dyn Error>> {
+    let name = matches.value_of("name").unwrap();
+    let store = Store::open_default()?;
+    let mut receipts = Receipts::load(&store)?;
+    let receipt = receipts
+        .remove(name)
+        .ok_or_else(|| format!("{} was not installed by stars_fetcher; see `installed list`", name))?;
+
+    match fs::remove_file(&receipt.path) {
+        Ok(()) => {}
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+            eprintln!("Note: {} was already gone, forgetting it", receipt.path.display());
+        }
+        Err(e) => return Err(format!("Failed to remove {}: {}", receipt.path.display(), e).into()),
+    }
+    receipts.save(&store)?;
+
+    println!("Uninstalled {} {} (from {}) at {}", name, receipt.tag, receipt.repo, receipt.path.display());
+
+    Ok(())
+}
+
+// List a page of tags or branches with the date of the commit each points at
+async fn refs_command(github: &GitHubClient, matches: &ArgMatches, kind: RefKind) -> Result<(), Box<dyn Error>> {
+    let repo_ref = parse_repo_spec(matches.value_of("repo").unwrap())?;
+    let page: u32 = matches.value_of("page").unwrap().parse().map_err(|_| "--page must be a number")?;
+    let per_page: u32 = matches.value_of("per-page").unwrap().parse().map_err(|_| "--per-page must be a number")?;
+
+    let format = output_format(matches)?;
+    let refs = github.list_refs(&repo_ref.owner, &repo_ref.repo, kind, page, per_page).await?;
+    if refs.is_empty() && format.is_table() {
+        println!("No {} on page {}", kind.plural(), page);
+        return Ok(());
+    }
+
+    let concurrency = Config::new()?.concurrency;
+    let limit = ReadLimit::new(concurrency.api_reads, concurrency.adaptive);
+    let shas = refs.iter().map(|git_ref| git_ref.commit.sha.clone()).collect();
+    let dates = commit_dates(github, &repo_ref, shas, limit).await?;
+    let rows: Vec<Vec<String>> = refs
+        .iter()
+        .map(|git_ref| {
+            vec![
+                git_ref.name.clone(),
+                git_ref.commit.sha.chars().take(7).collect(),
+                dates.get(&git_ref.commit.sha).cloned().flatten().unwrap_or_default(),
+            ]
+        })
+        .collect();
+    print_rows(format, REF_HEADERS, &rows)?;
+
+    if refs.len() as u32 == per_page.min(MAX_REFS_PER_PAGE) && format.is_table() {
+        println!("More {} may follow: --page {}", kind.plural(), page + 1);
+    }
+
+    Ok(())
+}
+
+// Dates of the commits in `shas`, each fetched once however many refs point to it, at most as many
+// at once as `limit` allows
+async fn commit_dates(
+    github: &GitHubClient,
+    repo_ref: &RepoRef,
+    shas: HashSet<String>,
+    mut limit: ReadLimit,
+) -> Result<HashMap<String, Option<String>>, Box<dyn Error>> {
+    let semaphore = limit.semaphore();
+    let mut tasks = JoinSet::new();
+    for sha in shas {
+        let (github, repo_ref, semaphore) = (github.clone(), repo_ref.clone(), semaphore.clone());
+        tasks.spawn(async move {
+            let _permit = semaphore.acquire_owned().await;
+            let date = github.commit_date(&repo_ref.owner, &repo_ref.repo, &sha).await;
+            (sha, date)
+        });
+    }
+
+    let mut dates = HashMap::new();
+    while let Some(joined) = tasks.join_next().await {
+        limit.retune();
+        let (sha, date) = joined?;
+        dates.insert(sha, date?);
+    }
+    Ok(dates)
+}
+
+// Let the user choose one of a repository's tags or branches, newest first as GitHub lists them
+async fn pick_ref(github: &GitHubClient, repo_ref: &RepoRef, kind: RefKind, prompt: &str) -> Result<String, Box<dyn Error>> {
+    let refs = github.list_refs(&repo_ref.owner, &repo_ref.repo, kind, 1, MAX_REFS_PER_PAGE).await?;
+    if refs.is_empty() {
+        return Err(format!("{} has no {} to pick from", repo_ref, kind.plural()).into());
+    }
+
+    let names: Vec<&str> = refs.iter().map(|git_ref| git_ref.name.as_str()).collect();
+    let selection = Select::with_theme(&ColorfulTheme::default())
+        .with_prompt(prompt)
+        .default(0)
+        .items(&names)
+        .interact_opt()?
+        .ok_or_else(|| format!("No {} selected", kind))?;
+
+    Ok(names[selection].to_string())
+}
+
+// The --tag option, or a tag picked from the repository when --pick is given
+async fn tag_option(github: &GitHubClient, repo_ref: &RepoRef, matches: &ArgMatches) -> Result<Option<String>, Box<dyn Error>> {
+    match matches.value_of("tag") {
+        Some(tag) => Ok(Some(tag.to_string())),
+        None if matches.is_present("pick") => Ok(Some(pick_ref(github, repo_ref, RefKind::Tag, "Release tag").await?)),
+        None => Ok(None),
+    }
+}
+
+// Show the release notes between two tags, falling back to the commits between them when either
+// tag has no release
+async fn changelog_command(github: &GitHubClient, matches: &ArgMatches) -> Result<(), Box<dyn Error>> {
+    let repo_ref = parse_repo_spec(matches.value_of("repo").unwrap())?;
+    let (owner, repo) = (repo_ref.owner.as_str(), repo_ref.repo.as_str());
+    let pick = matches.is_present("pick");
+    let from = match matches.value_of("from") {
+        Some(from) => from.to_string(),
+        None => pick_ref(github, &repo_ref, RefKind::Tag, "Upgrading from").await?,
+    };
+    let to = match matches.value_of("to") {
+        Some(to) => to.to_string(),
+        None if pick => pick_ref(github, &repo_ref, RefKind::Tag, "Upgrading to").await?,
+        None => github.latest_release(owner, repo).await?.tag_name,
+    };
+
+    let releases = releases_back_to(github, owner, repo, &from).await?;
+    if let Some(between) = releases_between(&releases, &from, &to) {
+        print!("{}", render_release_notes(between));
+        return Ok(());
+    }
+
+    eprintln!("Note: {} and {} aren't both releases, showing the commits between them instead", from, to);
+    let comparison = github.compare(owner, repo, &from, &to).await?;
+    print!("{}", render_commits(&from, &to, &comparison));
+
+    Ok(())
+}
+
+// Fork a repository into my account or an organization, optionally starring the upstream
+async fn fork_command(github: &GitHubClient, matches: &ArgMatches) -> Result<(), Box<dyn Error>> {
+    let repo_ref = parse_repo_spec(matches.value_of("repo").unwrap())?;
+    let fork = github.fork_repo(&repo_ref.owner, &repo_ref.repo, matches.value_of("org")).await?;
+
+    println!("Forked {} to {}", repo_ref, fork.full_name);
+    println!("Clone URL: {}", fork.clone_url);
+    println!("SSH URL: {}", fork.ssh_url);
+
+    if matches.is_present("star") {
+        github.star_repo(&repo_ref.owner, &repo_ref.repo).await?;
+        println!("Starred repository {}", repo_ref);
+    }
+
+    Ok(())
+}
+
+fn fork_status_row(status: &ForkStatus) -> Vec<String> {
+    vec![
+        status.fork.clone(),
+        status.upstream.clone(),
+        status.behind_by.to_string(),
+        status.ahead_by.to_string(),
+        status.summary().to_string(),
+    ]
+}
+
+// Show how far forks are behind and ahead of their upstream: one fork, or with --all every starred
+// fork, most behind first
+async fn fork_status_command(
+    service: &StarsService,
+    shutdown: &Shutdown,
+    matches: &ArgMatches,
+) -> Result<(), Box<dyn Error>> {
+    let github = service.client();
+    let format = output_format(matches)?;
+    if let Some(spec) = matches.value_of("repo") {
+        let fork = own_repo_ref(github, &Store::open_default()?, spec).await?;
+        let status = github.fork_status(&fork.owner, &fork.repo).await?;
+        return print_rows(format, FORK_STATUS_HEADERS, &[fork_status_row(&status)]);
+    }
+
+    let forks: Vec<RepoRef> = list_starred(service, &all_pages())
+        .await?
+        .iter()
+        .filter(|repo| repo.fork)
+        .filter_map(|repo| parse_repo_spec(&repo.full_name).ok())
+        .collect();
+    if forks.is_empty() && format.is_table() {
+        println!("None of my starred repositories are forks");
+        return Ok(());
+    }
+
+    let graceful = shutdown.graceful();
+    let mut statuses = Vec::new();
+    let mut failed = Vec::new();
+    for fork in &forks {
+        if graceful.is_cancelled() {
+            break;
+        }
+        match github.fork_status(&fork.owner, &fork.repo).await {
+            Ok(status) => statuses.push(status),
+            Err(e) => failed.push(vec![fork.full_name(), String::new(), String::new(), String::new(), format!("error: {}", e)]),
+        }
+    }
+    statuses.sort_by_key(|status| Reverse(status.behind_by));
+
+    let mut rows: Vec<Vec<String>> = statuses.iter().map(fork_status_row).collect();
+    rows.extend(failed);
+    print_rows(format, FORK_STATUS_HEADERS, &rows)?;
+
+    if graceful.is_cancelled() {
+        eprintln!("Interrupted after checking {} of {} forks", rows.len(), forks.len());
+        return Err(INTERRUPTED.into());
+    }
+    Ok(())
+}
+
+// Replace the topics of one of my repositories
+async fn topics_command(github: &GitHubClient, matches: &ArgMatches) -> Result<(), Box<dyn Error>> {
+    match matches.subcommand() {
+        Some(("set", sub_m)) => {
+            let store = Store::open_default()?;
+            let repo_ref = own_repo_ref(github, &store, sub_m.value_of("repo").unwrap()).await?;
+            let topics = parse_topics(sub_m.value_of("topics").unwrap_or_default())?;
+
+            let topics = github.set_topics(&repo_ref.owner, &repo_ref.repo, &topics).await?;
+            if topics.is_empty() {
+                println!("Removed all topics from {}", repo_ref);
+            } else {
+                println!("Set topics of {} to {}", repo_ref, topics.join(", "));
+            }
+        }
+        _ => show_help(),
+    }
+
+    Ok(())
+}
+
+// Show traffic for one of my repositories, recording it so history outlives GitHub's 14-day window
+async fn traffic_command(github: &GitHubClient, matches: &ArgMatches) -> Result<(), Box<dyn Error>> {
+    let spec = matches.value_of("repo").unwrap();
+    let days: usize = matches.value_of("days").unwrap().parse().map_err(|_| "--days must be a number")?;
+    let store = Store::open_default()?;
+
+    let repo_ref = own_repo_ref(github, &store, spec).await?;
+    let (owner, repo) = (repo_ref.owner.as_str(), repo_ref.repo.as_str());
+
+    let views = github.views(owner, repo).await?;
+    let clones = github.clones(owner, repo).await?;
+    let referrers = github.referrers(owner, repo).await?;
+
+    let mut history = TrafficHistory::load(&store, owner, repo)?;
+    history.merge(&views, &clones);
+    history.save(&store, owner, repo)?;
+
+    println!("Traffic for {} ({} days recorded)", repo_ref, history.days.len());
+    let rows: Vec<Vec<String>> = history
+        .days
+        .iter()
+        .rev()
+        .take(days)
+        .map(|(date, day)| vec![
+            date.clone(),
+            day.views.to_string(),
+            day.unique_views.to_string(),
+            day.clones.to_string(),
+            day.unique_clones.to_string(),
+        ])
+        .collect();
+    print_table(TRAFFIC_HEADERS, &rows);
+
+    if !referrers.is_empty() {
+        let rows: Vec<Vec<String>> = referrers
+            .iter()
+            .map(|referrer| vec![referrer.referrer.clone(), referrer.count.to_string(), referrer.uniques.to_string()])
+            .collect();
+        print_table(REFERRER_HEADERS, &rows);
+    }
+
+    if let Some(path) = matches.value_of("csv") {
+        fs::write(path, history.to_csv())?;
+        println!("Exported traffic history to {}", path);
+    }
+
+    Ok(())
+}
+
+// Summarize CI on the default branch of each repository, so red dependencies stand out
+async fn ci_command(github: &GitHubClient, matches: &ArgMatches) -> Result<(), Box<dyn Error>> {
+    let mut repos = Vec::new();
+    for spec in matches.values_of("repos").into_iter().flatten() {
+        repos.push(parse_repo_spec(spec)?);
+    }
+    if let Some(path) = matches.value_of("file") {
+        let collection = parse_collection(&fs::read_to_string(path)?);
+        for line in &collection.invalid_lines {
+            eprintln!("Skipping invalid line: {}", line);
+        }
+        repos.extend(collection.repos);
+    }
+
+    let mut rows = Vec::new();
+    for repo in &repos {
+        let (state, failing) = match github.ci_summary(&repo.owner, &repo.repo).await {
+            Ok(summary) => (summary.state.to_string(), summary.failing.join(", ")),
+            Err(e) => (format!("error: {}", e), String::new()),
+        };
+        rows.push(vec![repo.full_name(), state, failing]);
+    }
+
+    print_rows(output_format(matches)?, CI_HEADERS, &rows)
+}
+
+// A package registry, talking to {NAME}_API_URL instead of the public API when set, like GITHUB_API_URL
+fn registry(name: &str) -> Result<AnyRegistry, Box<dyn Error>> {
+    AnyRegistry::new(name, env::var(format!("{}_API_URL", name.to_uppercase())).ok())
+}
+
+// Show a package and whether I've starred the repository it's published from
+async fn find_package(github: &GitHubClient, registry: &AnyRegistry, name: &str) -> Result<(), Box<dyn Error>> {
+    let package = registry
+        .package(name)
+        .await?
+        .ok_or_else(|| format!("Package {} not found on {}", name, registry.name()))?;
+    match package.downloads {
+        Some(downloads) => println!("{} {} ({} downloads)", package.name, package.version, format_count(downloads)),
+        None => println!("{} {}", package.name, package.version),
+    }
+    println!("Link: {}", package.url);
+
+    match package.source_repo() {
+        Some(repo_ref) => {
+            let starred = github.is_starred(&repo_ref.owner, &repo_ref.repo).await?;
+            println!("Source: {} ({})", repo_ref, if starred { "starred" } else { "not starred" });
+        }
+        None => println!("Source: no GitHub repository listed"),
+    }
+
+    Ok(())
+}
+
+// Cross-reference starred repositories with the packages published from them, or the reverse for one
+// package, which is looked up on the first of the given registries
+async fn packages_command(
+    service: &StarsService,
+    matches: &ArgMatches,
+    registry_names: &[&str],
+) -> Result<(), Box<dyn Error>> {
+    let github = service.client();
+    if let Some(("find", sub_m)) = matches.subcommand() {
+        let registry = registry(registry_names[0])?;
+        return find_package(github, &registry, sub_m.value_of("package").unwrap()).await;
+    }
+
+    let starred = list_starred(service, &all_pages()).await?;
+    let mut rows = Vec::new();
+    for name in registry_names {
+        let registry = registry(name)?;
+        // Only repositories in the registry's languages are worth a lookup
+        let filter = language_filter(&registry.languages().join(","))?;
+
+        for repo in starred.iter().filter(|repo| filter.matches(repo)) {
+            let full_name = repo.full_name.as_str();
+            let repo_ref = parse_repo_spec(full_name)?;
+            match registry.packages_for_repo(&repo_ref).await {
+                Ok(packages) => rows.extend(packages.into_iter().map(|package| vec![
+                    full_name.to_string(),
+                    package.name,
+                    package.version,
+                    package.downloads.map(format_count).unwrap_or_else(|| String::from("-")),
+                    package.url,
+                    registry.name().to_string(),
+                ])),
+                Err(e) => eprintln!("Skipping {} on {}: {}", full_name, registry.name(), e),
+            }
+        }
+    }
+
+    let format = output_format(matches)?;
+    if rows.is_empty() && format.is_table() {
+        println!("No packages found for starred repositories");
+        return Ok(());
+    }
+
+    print_rows(format, PACKAGE_HEADERS, &rows)
+}
+
+// Show an owner's profile and their most-starred repositories, marking the ones I star
+async fn owner_command(github: &GitHubClient, matches: &ArgMatches) -> Result<(), Box<dyn Error>> {
+    let login = matches.value_of("login").unwrap();
+    let limit: u32 = matches.value_of("limit").unwrap().parse().map_err(|_| "--limit must be a number")?;
+
+    let user = github.get_user(login).await?;
+    match &user.name {
+        Some(name) => println!("{} ({})", user.owner.login, name),
+        None => println!("{}", user.owner.login),
+    }
+    println!("Type: {}", user.owner.kind);
+    if let Some(bio) = &user.bio {
+        println!("Bio: {}", bio);
+    }
+    if let Some(company) = &user.company {
+        println!("Company: {}", company);
+    }
+    println!("Followers: {}", user.followers);
+    println!("Public repositories: {}", user.public_repos);
+    if let Some(url) = &user.owner.html_url {
+        println!("URL: {}", url);
+    }
+    println!();
+
+    let repos = github.search_repos(&format!("user:{}", user.owner.login), limit).await?;
+    let mut rows = Vec::new();
+    for repo in &repos {
+        let starred = matches!(github.is_starred(&repo.owner.login, &repo.name).await, Ok(true));
+        rows.push(vec![
+            repo.full_name.clone(),
+            repo.stars.to_string(),
+            if starred { String::from("yes") } else { String::new() },
+            repo.description.clone().unwrap_or_default(),
+        ]);
+    }
+    print_table(OWNER_HEADERS, &rows);
+
+    Ok(())
+}
+
+// Who owns a repository, below its details
+fn print_owner(owner: &Owner) {
+    match &owner.html_url {
+        Some(url) => println!("Owner: {} ({}), {}", owner.login, owner.kind, url),
+        None => println!("Owner: {} ({})", owner.login, owner.kind),
+    }
+}
+
+// Show repositories sharing topics with the given one, skipping those I already star
+async fn similar_command(github: &GitHubClient, matches: &ArgMatches) -> Result<(), Box<dyn Error>> {
+    let repo_ref = parse_repo_spec(matches.value_of("repo").unwrap())?;
+    let limit: usize = matches.value_of("limit").unwrap().parse().map_err(|_| "--limit must be a number")?;
+
+    let topics = github.get_topics(&repo_ref.owner, &repo_ref.repo).await?;
+    let mut candidates = Vec::new();
+    for query in similar_queries(&repo_ref, &topics) {
+        candidates.extend(github.search_repos(&query, SIMILAR_SEARCH_SIZE).await?);
+    }
+
+    let mut similar = Vec::new();
+    for (candidate, shared) in rank_similar(&repo_ref, &topics, candidates) {
+        if similar.len() == limit {
+            break;
+        }
+        if let Ok(true) = github.is_starred(&candidate.owner.login, &candidate.name).await {
+            continue;
+        }
+        similar.push((candidate, shared));
+    }
+
+    if similar.is_empty() {
+        println!("No similar repositories found that aren't already starred");
+        return Ok(());
+    }
+
+    let rows: Vec<Vec<String>> = similar
+        .iter()
+        .map(|(repo, shared)| vec![
+            repo.full_name.clone(),
+            repo.stars.to_string(),
+            shared.to_string(),
+            repo.description.clone().unwrap_or_default(),
+        ])
+        .collect();
+    print_table(SIMILAR_HEADERS, &rows);
+
+    if matches.is_present("star") {
+        let candidates: Vec<SearchRepo> = similar.into_iter().map(|(repo, _)| repo).collect();
+        for selected in RepoSelector::select_multiple_repos(&candidates) {
+            github.star_repo(&selected.owner.login, &selected.name).await?;
+            println!("Starred repository {}", selected.full_name);
+        }
+    }
+
+    Ok(())
+}
+
+// Search GitHub for repositories and star the ones picked from the results
+async fn search_command(github: &GitHubClient, matches: &ArgMatches) -> Result<(), Box<dyn Error>> {
+    let terms: Vec<&str> = matches.values_of("query").map(Iterator::collect).unwrap_or_default();
+    let query = search_query(&terms.join(" "), matches.value_of("language"), matches.value_of("stars"));
+    if query.is_empty() {
+        return Err("Give search terms, --language or --stars".into());
+    }
+    let sort: SearchSort = matches.value_of("sort").unwrap().parse()?;
+    let limit = match matches.value_of("limit").unwrap().parse() {
+        Ok(limit @ 1..=MAX_PER_PAGE) => limit,
+        _ => return Err(format!("--limit must be between 1 and {}", MAX_PER_PAGE).into()),
+    };
+
+    let repos = github.search_repos_sorted(&query, sort, limit).await?;
+    let format = output_format(matches)?;
+    if repos.is_empty() && format.is_table() {
+        println!("No repositories found for {}", query);
+        return Ok(());
+    }
+
+    let rows: Vec<Vec<String>> = repos
+        .iter()
+        .map(|repo| vec![
+            repo.full_name.clone(),
+            repo.stars.to_string(),
+            repo.language.clone().unwrap_or_default(),
+            repo.description.clone().unwrap_or_default(),
+        ])
+        .collect();
+    print!("{}", render_output(format, SEARCH_HEADERS, &rows, &repos)?);
+
+    // Only offer to star when a person is reading the results
+    if format.is_table() && Term::stdout().is_term() {
+        for selected in RepoSelector::select_multiple_repos(&repos) {
+            github.star_repo(&selected.owner.login, &selected.name).await?;
+            println!("Starred repository {}", selected.full_name);
+        }
+    }
+
+    Ok(())
+}
+
+// Unstar starred repositories that were archived or went years without a push. A terminal lets me
+// pick which ones to unstar, and --dry-run only lists them.
+async fn prune_command(service: &StarsService, shutdown: &Shutdown, matches: &ArgMatches) -> Result<(), Box<dyn Error>> {
+    let archived = matches.is_present("archived");
+    let inactive_years = matches
+        .value_of("inactive-years")
+        .map(|years| years.parse::<u64>().ok().filter(|&years| years > 0)
+            .ok_or_else(|| format!("--inactive-years expects a number of years, got: {}", years)))
+        .transpose()?;
+    if !archived && inactive_years.is_none() {
+        return Err("Choose what to prune, e.g. prune --archived or prune --inactive-years 3".into());
+    }
+    // Pushed to before this, in seconds since the epoch, counts as inactive
+    let pushed_before = inactive_years
+        .map(|years| parse_period(&format!("{}y", years)))
+        .transpose()?
+        .map(|period| unix_now().saturating_sub(period.as_secs()));
+
+    let inactive = |repo: &StarredRepo| {
+        let pushed_at = repo.pushed_at.as_deref().and_then(|pushed_at| parse_timestamp(pushed_at).ok());
+        matches!((pushed_before, pushed_at), (Some(before), Some(pushed_at)) if pushed_at < before)
+    };
+    let prunable: Vec<StarredRepo> = list_starred(service, &all_pages())
+        .await?
+        .into_iter()
+        .filter(|repo| (archived && repo.archived) || inactive(repo))
+        .collect();
+
+    let mut criteria = Vec::new();
+    let mut command = String::from("prune");
+    if archived {
+        criteria.push(String::from("are archived"));
+        command.push_str(" --archived");
+    }
+    if let Some(years) = inactive_years {
+        criteria.push(format!("went {} year{} without a push", years, if years == 1 { "" } else { "s" }));
+        command.push_str(&format!(" --inactive-years {}", years));
+    }
+
+    let format = output_format(matches)?;
+    if prunable.is_empty() {
+        if format.is_table() {
+            println!("None of my starred repositories {}", criteria.join(" or "));
+        }
+        return Ok(());
+    }
+
+    let rows: Vec<Vec<String>> = prunable
+        .iter()
+        .map(|repo| vec![
+            repo.full_name.clone(),
+            repo.stars.to_string(),
+            repo.pushed_at.clone().unwrap_or_default(),
+            repo.description.clone().unwrap_or_default(),
+        ])
+        .collect();
+    print!("{}", render_output(format, PRUNE_HEADERS, &rows, &prunable)?);
+
+    if matches.is_present("dry-run") {
+        for repo in &prunable {
+            print_star_request(service, format, &repo.owner.login, &repo.name, false);
+        }
+        if format.is_table() {
+            println!("Dry run: {} repositories would be unstarred", prunable.len());
+        }
+        return Ok(());
+    }
+    let picked: Vec<&StarredRepo> = if matches.is_present("yes") {
+        prunable.iter().collect()
+    } else if format.is_table() && Term::stdout().is_term() {
+        let labels: Vec<String> = prunable
+            .iter()
+            .map(|repo| match &repo.pushed_at {
+                Some(pushed_at) => format!("{} (last push {})", repo.full_name, pushed_at.get(..10).unwrap_or(pushed_at)),
+                None => repo.full_name.clone(),
+            })
+            .collect();
+        MultiSelect::with_theme(&ColorfulTheme::default())
+            .with_prompt("Unstar which repositories? (space to toggle, enter to confirm)")
+            .items(&labels)
+            .defaults(&vec![true; labels.len()])
+            .interact_opt()?
+            .unwrap_or_default()
+            .into_iter()
+            .map(|index| &prunable[index])
+            .collect()
+    } else {
+        eprintln!("Note: run {} --yes to unstar them", command);
+        Vec::new()
+    };
+
+    unstar_repos(service, shutdown, &picked, format.is_table()).await
+}
+
+// Pick starred repositories from a list and unstar them one after another, or with `dry_run` print
+// the requests that would unstar them
+async fn unstar_interactive_command(service: &StarsService, shutdown: &Shutdown, dry_run: bool) -> Result<(), Box<dyn Error>> {
+    if !Term::stdout().is_term() {
+        return Err("unstar --interactive needs a terminal, name the repository to unstar instead".into());
+    }
+    let starred = list_starred(service, &all_pages()).await?;
+    if starred.is_empty() {
+        println!("No starred repositories to unstar");
+        return Ok(());
+    }
+
+    let picked = RepoSelector::select_multiple_repos(&starred);
+    if picked.is_empty() {
+        println!("No repositories picked, nothing unstarred");
+        return Ok(());
+    }
+    if dry_run {
+        for repo in picked {
+            print_star_request(service, OutputFormat::Table, &repo.owner.login, &repo.name, false);
+        }
+        return Ok(());
+    }
+    unstar_repos(service, shutdown, &picked, true).await
+}
+
+// Unstar repositories one after another, reporting each one unstarred when `report` is set and
+// each failure, then how many were unstarred. Stops between repositories on Ctrl-C, and fails at
+// the end if any couldn't be unstarred.
+async fn unstar_repos(service: &StarsService, shutdown: &Shutdown, repos: &[&StarredRepo], report: bool) -> Result<(), Box<dyn Error>> {
+    let graceful = shutdown.graceful();
+    let (mut unstarred, mut failed) = (0, 0);
+    for repo in repos {
+        if graceful.is_cancelled() {
+            break;
+        }
+        match service.stars().unstar(&repo.owner.login, &repo.name).await {
+            Ok(()) => {
+                if report {
+                    println!("Unstarred repository {}", repo.full_name);
+                }
+                unstarred += 1;
+            }
+            Err(e) => {
+                eprintln!("Failed to unstar {}: {}", repo.full_name, e);
+                failed += 1;
+            }
+        }
+    }
+    if repos.is_empty() {
+        return Ok(());
+    }
+
+    let summary = format!("Unstarred {} repositories, {} failed", unstarred, failed);
+    if graceful.is_cancelled() {
+        eprintln!("Interrupted: {}, {} remaining", summary.to_lowercase(), repos.len() - unstarred - failed);
+        return Err(INTERRUPTED.into());
+    }
+    if report {
+        println!("{}", summary);
+    } else {
+        eprintln!("{}", summary);
+    }
+    if failed > 0 {
+        return Err(format!("{} of {} repositories could not be unstarred", failed, repos.len()).into());
+    }
+    Ok(())
+}
+
+// Pull a team collection into my stars, or propose a repository for it
+async fn collection_command(github: &GitHubClient, shutdown: &Shutdown, matches: &ArgMatches) -> Result<(), Box<dyn Error>> {
+    match matches.subcommand() {
+        Some(("pull", sub_m)) => {
+            let gist = github.get_gist(gist_id(sub_m.value_of("gist").unwrap())).await?;
+            let file = match sub_m.value_of("file") {
+                Some(name) => gist.files.get(name).ok_or_else(|| format!("Gist has no file named {}", name))?,
+                None => gist.files.values().next().ok_or("Gist has no files")?,
+            };
+            let collection = parse_collection(&github.gist_file_content(file).await?);
+
+            for line in &collection.invalid_lines {
+                eprintln!("Skipping invalid line: {}", line);
+            }
+
+            // Pulling again after a partial failure only stars what is still missing
+            let starred_before = starred_among(github, &collection.repos).await;
+            let graceful = shutdown.graceful();
+            let (mut starred, mut already_starred, mut failed) = (0, 0, 0);
+            for repo in &collection.repos {
+                if graceful.is_cancelled() {
+                    break;
+                }
+                let is_starred = match &starred_before {
+                    Some(names) => names.contains(&repo.full_name().to_lowercase()),
+                    None => matches!(github.is_starred(&repo.owner, &repo.repo).await, Ok(true)),
+                };
+                if is_starred {
+                    already_starred += 1;
+                    continue;
+                }
+
+                match github.star_repo(&repo.owner, &repo.repo).await {
+                    Ok(()) => {
+                        println!("Starred repository {}", repo);
+                        starred += 1;
+                    }
+                    Err(e) => {
+                        eprintln!("Failed to star {}: {}", repo, e);
+                        failed += 1;
+                    }
+                }
+            }
+
+            if graceful.is_cancelled() {
+                let skipped = collection.repos.len() - starred - already_starred - failed;
+                println!("Interrupted: {} starred, {} already starred, {} failed, {} not processed", starred, already_starred, failed, skipped);
+                return Err(INTERRUPTED.into());
+            }
+            println!("Collection pulled: {} starred, {} already starred, {} failed", starred, already_starred, failed);
+            if starred == 0 && failed == 0 {
+                println!("Nothing changed, every repository in the collection was already starred");
+            }
+            if failed > 0 {
+                return Err(format!("{} repositories could not be starred", failed).into());
+            }
+        }
+        Some(("propose", sub_m)) => {
+            let gist_id = gist_id(sub_m.value_of("gist").unwrap());
+            let repo = parse_repo_spec(sub_m.value_of("repo").unwrap())?;
+            let comment = proposal_comment(&repo, sub_m.value_of("reason"));
+
+            github.create_gist_comment(gist_id, &comment).await?;
+            println!("Proposed {} for collection {}", repo, gist_id);
+        }
+        _ => show_help(),
+    }
+
+    Ok(())
+}
+
+// Lowercased names of my starred repositories, when listing them 100 a page takes fewer requests
+// than checking each of `repos`. Unchanged pages are revalidated against the response cache.
+// None leaves the checks to one request per repository.
+async fn starred_among(github: &GitHubClient, repos: &[RepoRef]) -> Option<HashSet<String>> {
+    let count = github.starred_count().await.ok()?;
+    if count.div_ceil(MAX_PER_PAGE as u64) >= repos.len() as u64 {
+        return None;
+    }
+
+    let pagination = all_pages();
+    let starred = github.list_starred(&pagination).await.ok()?;
+    Some(starred
+        .repos
+        .iter()
+        .map(|repo| repo.full_name.to_lowercase())
+        .collect())
+}
+
+// Check whether the token can star and unstar; probing is best effort, so failures
+// leave the decision to the actual request
+async fn probe_star_access(github: &GitHubClient) -> Availability {
+    match github.capabilities().await {
+        Ok(capabilities) => capabilities.write_stars(),
+        Err(_) => Availability::Available,
+    }
+}
+
+// Remember an interactive command for the history menu; history is a convenience, so failures only warn
+fn remember(command: HistoryCommand) {
+    let result = Store::open_default().and_then(|store| {
+        let mut history = CommandHistory::load(&store)?;
+        history.record(command);
+        history.save(&store)
+    });
+    if let Err(e) = result {
+        eprintln!("Warning: could not save command history: {}", e);
+    }
+}
+
+// Pinned repositories; pins only change the order of results, so a store that can't be read
+// just warns
+fn load_pins() -> Pins {
+    match Store::open_default().and_then(|store| Pins::load(&store)) {
+        Ok(pins) => pins,
+        Err(e) => {
+            eprintln!("Warning: could not read pinned repositories: {}", e);
+            Pins::default()
+        }
+    }
+}
+
+// Snoozed repositories; like pins, a store that can't be read just warns
+fn load_snoozes() -> Snoozes {
+    match Store::open_default().and_then(|store| Snoozes::load(&store)) {
+        Ok(snoozes) => snoozes,
+        Err(e) => {
+            eprintln!("Warning: could not read snoozed repositories: {}", e);
+            Snoozes::default()
+        }
+    }
+}
+
+// Snooze a repository for a period, end a snooze, or show the snoozes still running
+fn snooze_command(matches: &ArgMatches, snooze: bool) -> Result<(), Box<dyn Error>> {
+    let store = Store::open_default()?;
+    let mut snoozes = Snoozes::load(&store)?;
+    let now = unix_now();
+    snoozes.expire(now);
+
+    match matches.value_of("repo") {
+        Some(spec) => {
+            let full_name = parse_repo_spec(spec)?.full_name();
+            if snooze {
+                let period = parse_period(matches.value_of("for").unwrap())?;
+                snoozes.snooze(&full_name, now.saturating_add(period.as_secs()));
+                println!("Snoozed {} for {}", full_name, format_period(period.as_secs()));
+            } else if snoozes.wake(&full_name) {
+                println!("{} is no longer snoozed", full_name);
+            } else {
+                println!("{} is not snoozed", full_name);
+            }
+        }
+        None if snoozes.snoozes.is_empty() => println!("No snoozed repositories"),
+        None => {
+            for snooze in &snoozes.snoozes {
+                println!("{} (another {})", snooze.repo, format_period(snooze.until - now));
+            }
+        }
+    }
+
+    snoozes.save(&store)
+}
+
+// Archived stars; like pins, a store that can't be read just warns
+fn load_archive() -> LocalArchive {
+    match Store::open_default().and_then(|store| LocalArchive::load(&store)) {
+        Ok(archive) => archive,
+        Err(e) => {
+            eprintln!("Warning: could not read archived repositories: {}", e);
+            LocalArchive::default()
+        }
+    }
+}
+
+// Archive or unarchive a repository, or show the archive when archive is given no repository
+fn archive_command(matches: &ArgMatches, archive: bool) -> Result<(), Box<dyn Error>> {
+    let store = Store::open_default()?;
+    let mut archived = LocalArchive::load(&store)?;
+
+    let Some(spec) = matches.value_of("repo") else {
+        if archived.repos.is_empty() {
+            println!("No archived repositories");
+        }
+        for repo in &archived.repos {
+            println!("{}", repo.repo);
+        }
+        return Ok(());
+    };
+
+    let full_name = parse_repo_spec(spec)?.full_name();
+    match (archive, if archive { archived.archive(&full_name, unix_now()) } else { archived.unarchive(&full_name) }) {
+        (true, true) => println!("Archived {}", full_name),
+        (true, false) => println!("{} is already archived", full_name),
+        (false, true) => println!("Unarchived {}", full_name),
+        (false, false) => println!("{} is not archived", full_name),
+    }
+    archived.save(&store)
+}
+
+// Apply the age policy: archive stars older than `auto_archive_after` that were never pinned,
+// snoozed or acted on in interactive mode. The first run only reports what the policy would do.
+async fn auto_archive_command(github: &GitHubClient, matches: &ArgMatches) -> Result<(), Box<dyn Error>> {
+    let config = Config::new()?;
+    let period = config.archive.auto_archive_after
+        .ok_or("Set auto_archive_after in the [archive] section of the config file, e.g. auto_archive_after = \"2y\"")?;
+    let max_age = parse_period(&period)?.as_secs();
+
+    let store = Store::open_default()?;
+    let mut archive = LocalArchive::load(&store)?;
+    let (pins, snoozes) = (Pins::load(&store)?, Snoozes::load(&store)?);
+    let history = CommandHistory::load(&store)?;
+    let used = |full_name: &str| {
+        pins.is_pinned(full_name)
+            || snoozes.snoozes.iter().any(|snooze| snooze.repo.eq_ignore_ascii_case(full_name))
+            || history.commands.iter().any(|command| match command {
+                HistoryCommand::Detail { owner, repo }
+                | HistoryCommand::Star { owner, repo }
+                | HistoryCommand::Unstar { owner, repo } => format!("{}/{}", owner, repo).eq_ignore_ascii_case(full_name),
+                HistoryCommand::ListStarred => false,
+            })
+    };
+
+    let stars = github
+        .starred_dates()
+        .await?
+        .into_iter()
+        .map(|star| Ok((star.full_name, parse_timestamp(&star.starred_at)?)))
+        .collect::<Result<Vec<_>, Box<dyn Error>>>()?;
+    let now = unix_now();
+    let due = due_for_archive(&archive, &stars, max_age, now, used);
+
+    let dry_run = matches.is_present("dry-run") || !archive.policy_reviewed;
+    if due.is_empty() {
+        println!("No stars older than {} to archive", period);
+    } else {
+        println!("{} {} stars older than {}:", if dry_run { "Would archive" } else { "Archiving" }, due.len(), period);
+        for full_name in &due {
+            println!("  {}", full_name);
+        }
+    }
+
+    if dry_run {
+        if !archive.policy_reviewed {
+            println!("This is a dry run; run archive --auto again to archive them");
+            archive.policy_reviewed = true;
+            archive.save(&store)?;
+        }
+        return Ok(());
+    }
+
+    for full_name in due {
+        archive.archive(full_name, now);
+    }
+    archive.save(&store)
+}
+
+// Oldest responses shown by cache stats
+const CACHE_STATS_OLDEST: usize = 5;
+
+// Formats export writes: the graph formats, and a web page
+const EXPORT_FORMATS: &[&str] = &["graphviz", "gexf", "html"];
+
+// File formats store dump writes, also used as the file extensions
+const STORE_DUMP_FORMATS: &[&str] = &["csv", "json"];
+
+// Open the config file in the user's editor, creating it first if needed, and check what was saved
+fn edit_config() -> Result<(), Box<dyn Error>> {
+    let path = Config::path()?;
+    if !path.exists() {
+        Config::stored()?;
+    }
+
+    // The editor may come with arguments, e.g. `code --wait`
+    let editor = ["VISUAL", "EDITOR"]
+        .iter()
+        .find_map(|name| env::var(name).ok().filter(|editor| !editor.trim().is_empty()))
+        .unwrap_or_else(|| String::from(if cfg!(windows) { "notepad" } else { "vi" }));
+    let mut words = editor.split_whitespace();
+    let program = words.next().ok_or("No editor configured")?;
+    let status = std::process::Command::new(program)
+        .args(words)
+        .arg(&path)
+        .status()
+        .map_err(|error| format!("Unable to run {}: {}", program, error))?;
+    if !status.success() {
+        return Err(format!("{} exited with {}", program, status).into());
+    }
+
+    Config::from_toml_str(&fs::read_to_string(&path)?)
+        .map_err(|error| format!("{} is invalid, run config edit again to fix it: {}", path.display(), error))?;
+    println!("Saved {}", path.display());
+    Ok(())
+}
+
+// Show which account and token the tool is using
+async fn whoami_command(service: &StarsService, matches: &ArgMatches) -> Result<(), Box<dyn Error>> {
+    let account = service.client().account().await?;
+    let kind = match service.token_kind() {
+        TokenKind::Anonymous => "none",
+        TokenKind::Classic => "classic",
+        TokenKind::FineGrained => "fine-grained",
+        TokenKind::App => "GitHub App installation",
+    };
+    let scopes = match &account.scopes {
+        Some(scopes) if scopes.is_empty() => String::from("none"),
+        Some(scopes) => scopes.join(", "),
+        None => String::from("not listed for this kind of token"),
+    };
+    let plan = account.plan.as_ref().map(|plan| plan.name.clone()).unwrap_or_default();
+    let name = account.name.clone().unwrap_or_default();
+
+    let format = output_format(matches)?;
+    if !format.is_table() {
+        let rows = vec![vec![account.login, name, plan, kind.to_string(), scopes, service.api_url().to_string()]];
+        return print_rows(format, WHOAMI_HEADERS, &rows);
+    }
+
+    match account.name {
+        Some(name) => println!("Logged in to {} as {} ({})", service.api_url(), account.login, name),
+        None => println!("Logged in to {} as {}", service.api_url(), account.login),
+    }
+    if !plan.is_empty() {
+        println!("Plan: {}", plan);
+    }
+    println!("Token: {}, scopes: {}", kind, scopes);
+    if let Ok(profile) = env::var(PROFILE_ENV) {
+        println!("Profile: {}", profile);
+    }
+    Ok(())
+}
+
+// Remaining requests of the core, search and GraphQL limits and when they reset, in local time
+async fn rate_limit_command(github: &GitHubClient, matches: &ArgMatches) -> Result<(), Box<dyn Error>> {
+    let limits = github.rate_limits().await?;
+    let resources = [("core", Some(limits.core)), ("search", Some(limits.search)), ("graphql", limits.graphql)];
+    let resources: Vec<_> = resources
+        .into_iter()
+        .filter_map(|(name, limit)| Some((name, limit?)))
+        .collect();
+
+    let rows: Vec<Vec<String>> = resources
+        .iter()
+        .map(|(name, limit)| vec![
+            name.to_string(),
+            limit.remaining.to_string(),
+            limit.limit.to_string(),
+            local_time(limit.reset),
+        ])
+        .collect();
+    let records: Vec<serde_json::Value> = resources
+        .iter()
+        .map(|(name, limit)| serde_json::json!({
+            "resource": name,
+            "remaining": limit.remaining,
+            "limit": limit.limit,
+            "reset": limit.reset,
+            "resets_at": local_time(limit.reset),
+        }))
+        .collect();
+    print!("{}", render_output(output_format(matches)?, RATE_LIMIT_HEADERS, &rows, &records)?);
+    Ok(())
+}
+
+// Stars from this day in earlier years, to look back on and maybe unstar
+async fn on_this_day_command(github: &GitHubClient, matches: &ArgMatches) -> Result<(), Box<dyn Error>> {
+    let today = matches.value_of("date").map_or_else(|| date_of(unix_now()), String::from);
+    let found = on_this_day(&github.starred_dates().await?, &today)?;
+
+    let format = output_format(matches)?;
+    if format.is_table() && found.is_empty() {
+        println!("Nothing starred on {} in earlier years", &today[5..]);
+        return Ok(());
+    }
+    let rows: Vec<Vec<String>> = found
+        .into_iter()
+        .map(|star| vec![star.full_name, star.starred_at, star.years_ago.to_string()])
+        .collect();
+    print_rows(format, ON_THIS_DAY_HEADERS, &rows)
+}
+
+// Write my stars as a graph for Graphviz or Gephi, or as a web page to search them in a browser
+async fn export_graph_command(service: &StarsService, matches: &ArgMatches) -> Result<(), Box<dyn Error>> {
+    let links = match matches.values_of("link") {
+        Some(links) => links.map(str::parse).collect::<Result<Vec<GraphLink>, _>>()?,
+        None => vec![GraphLink::Owner, GraphLink::Topic, GraphLink::Language],
+    };
+    let pagination = all_pages();
+    let starred = list_starred(service, &pagination).await?;
+
+    // A page is a list of the repositories rather than a graph of what links them
+    if matches.value_of("format") == Some("html") {
+        let page = render_html(&starred)?;
+        match matches.value_of("output") {
+            Some(path) => {
+                fs::write(path, page)?;
+                println!("Wrote {} repositories to {}", starred.len(), path);
+            }
+            None => print!("{}", page),
+        }
+        return Ok(());
+    }
+
+    let graph = build_graph(&starred, &links);
+    let rendered = match matches.value_of("format") {
+        Some("gexf") => render_gexf(&graph),
+        _ => render_dot(&graph),
+    };
+    match matches.value_of("output") {
+        Some(path) => {
+            fs::write(path, rendered)?;
+            println!("Wrote {} nodes and {} edges to {}", graph.nodes.len(), graph.edges.len(), path);
+        }
+        None => print!("{}", rendered),
+    }
+    Ok(())
+}
+
+// Export the local store for analysis elsewhere
+fn store_command(matches: &ArgMatches) -> Result<(), Box<dyn Error>> {
+    match matches.subcommand() {
+        Some(("dump", sub_m)) => {
+            let extension = sub_m.value_of("format").unwrap();
+            let format = match extension {
+                "json" => OutputFormat::Json { meta: false },
+                _ => OutputFormat::Csv,
+            };
+            let dir = Path::new(sub_m.value_of("output").unwrap());
+            fs::create_dir_all(dir)?;
+
+            for table in dump::tables(&Store::open_default()?)? {
+                let path = dir.join(format!("{}.{}", table.name, extension));
+                fs::write(&path, render_rows(format, table.headers, &table.rows)?)?;
+                println!("Wrote {} rows to {}", table.rows.len(), path.display());
+            }
+        }
+        Some(("rollback", _)) => {
+            let store = Store::open_default()?;
+            let restored = store.rollback()?;
+            let responses = ResponseCache::open_default()?.rollback()?;
+            if restored.is_empty() && responses.is_empty() {
+                println!("No backups to restore in {}", store.root().display());
+            } else {
+                for name in &restored {
+                    println!("Restored {}", name);
+                }
+                if !responses.is_empty() {
+                    println!("Restored {} cached responses", responses.len());
+                }
+            }
+        }
+        _ => show_help(),
+    }
+
+    Ok(())
+}
+
+// Show, clear or prune the response cache
+fn cache_command(matches: &ArgMatches) -> Result<(), Box<dyn Error>> {
+    let cache = ResponseCache::open_default()?;
+    match matches.subcommand() {
+        Some(("stats", _)) => {
+            let entries = cache.entries()?;
+            let size = entries.iter().map(|entry| entry.size).sum();
+            println!("{} responses kept, {} in {}", entries.len(), format_size(size), cache.root().display());
+
+            let usage = Usage::load(&Store::open_default()?)?;
+            match usage.cache_hit_rate() {
+                Some(rate) => println!(
+                    "{} of {} responses served from the cache ({:.0}%)",
+                    usage.cache_hits,
+                    usage.cache_hits + usage.cache_misses,
+                    rate * 100.0
+                ),
+                None => println!("No responses recorded yet"),
+            }
+
+            if !entries.is_empty() {
+                println!("Oldest responses:");
+            }
+            let now = SystemTime::now();
+            for entry in entries.iter().take(CACHE_STATS_OLDEST) {
+                let age = now.duration_since(entry.modified).unwrap_or_default().as_secs();
+                let url = cache.url(entry).unwrap_or_else(|| entry.name.clone());
+                println!("  {:>4} ago  {}", format_period(age), url);
+            }
+        }
+        Some(("clear", _)) => println!("Removed {} responses", cache.clear()?),
+        Some(("prune", sub_m)) => {
+            let period = sub_m.value_of("older-than").unwrap();
+            let pruned = cache.prune(parse_period(period)?, SystemTime::now())?;
+            println!("Removed {} responses fetched more than {} ago", pruned, period);
+        }
+        _ => show_help(),
+    }
+
+    Ok(())
+}
+
+// Bytes as a rough size, e.g. 512 B, 3.4 KB or 12.0 MB
+fn format_size(bytes: u64) -> String {
+    match bytes {
+        bytes if bytes >= 1_000_000 => format!("{:.1} MB", bytes as f64 / 1_000_000.0),
+        bytes if bytes >= 1_000 => format!("{:.1} KB", bytes as f64 / 1_000.0),
+        bytes => format!("{} B", bytes),
+    }
 }
 
-async fn get_repo(client: &Client, owner: &str, repo: &str) -> Result<Repo, Box<dyn Error>> {
-    let url = format!("https://api.github.com/repos/{}/{}", owner, repo);
-    let response = client.get(url).send().await?.json::<Repo>().await?;
-    Ok(response)
+// Report local entries that no longer match my stars on GitHub, fixing them with --fix
+async fn verify_command(github: &GitHubClient, matches: &ArgMatches) -> Result<(), Box<dyn Error>> {
+    let store = Store::open_default()?;
+    let (mut pins, mut snoozes, mut archive) = (Pins::load(&store)?, Snoozes::load(&store)?, LocalArchive::load(&store)?);
+
+    let stars = github
+        .starred_dates()
+        .await?
+        .into_iter()
+        .map(|star| Ok((star.full_name, parse_timestamp(&star.starred_at)?)))
+        .collect::<Result<Vec<_>, Box<dyn Error>>>()?;
+    let discrepancies = find_discrepancies(&pins, &snoozes, &archive, &stars);
+
+    let checked = pins.repos.len() + snoozes.snoozes.len() + archive.repos.len();
+    if discrepancies.is_empty() {
+        println!("Local store matches GitHub: {} entries checked against {} stars", checked, stars.len());
+        return Ok(());
+    }
+
+    let fix = matches.is_present("fix");
+    for discrepancy in &discrepancies {
+        println!("{} ({}: {})", discrepancy, if fix { "fixed" } else { "fix" }, discrepancy.fix());
+    }
+    if !fix {
+        println!("{} of {} entries don't match GitHub; run verify --fix to reconcile them", discrepancies.len(), checked);
+        return Ok(());
+    }
+
+    fix_discrepancies(&discrepancies, &mut pins, &mut snoozes, &mut archive);
+    pins.save(&store)?;
+    snoozes.save(&store)?;
+    archive.save(&store)?;
+    println!("Fixed {} entries", discrepancies.len());
+    Ok(())
 }
 
-async fn list_repos(client: &Client) -> Result<Vec<Repo>, Box<dyn Error>> {
-    let url = "https://api.github.com/user/starred";
-    let response = client.get(url).send().await?.json::<Vec<Repo>>().await?;
-    Ok(response)
+// Pin or unpin a repository, returning the message to show
+fn set_pinned(full_name: &str, pinned: bool) -> Result<String, Box<dyn Error>> {
+    let store = Store::open_default()?;
+    let mut pins = Pins::load(&store)?;
+    let message = match (pinned, if pinned { pins.pin(full_name) } else { pins.unpin(full_name) }) {
+        (true, true) => format!("Pinned {}", full_name),
+        (true, false) => format!("{} is already pinned", full_name),
+        (false, true) => format!("Unpinned {}", full_name),
+        (false, false) => format!("{} is not pinned", full_name),
+    };
+    pins.save(&store)?;
+    Ok(message)
 }
 
-async fn star_repo(client: &Client, owner: &str, repo: &str) -> Result<(), Box<dyn Error>> {
-    let url = format!("https://api.github.com/user/starred/{}/{}", owner, repo);
-    client.put(url).send().await?;
-    Ok(())
-}
+// Pin or unpin a repository, or show the pins when pin is given no repository
+fn pin_command(matches: &ArgMatches, pinned: bool) -> Result<(), Box<dyn Error>> {
+    let Some(spec) = matches.value_of("repo") else {
+        let pins = Pins::load(&Store::open_default()?)?;
+        if pins.repos.is_empty() {
+            println!("No pinned repositories");
+        }
+        for repo in &pins.repos {
+            println!("{}", repo);
+        }
+        return Ok(());
+    };
 
-async fn unstar_repo(client: &Client, owner: &str, repo: &str) -> Result<(), Box<dyn Error>> {
-    let url = format!("https://api.github.com/user/starred/{}/{}", owner, repo);
-    client.delete(url).send().await?;
+    println!("{}", set_pinned(&parse_repo_spec(spec)?.full_name(), pinned)?);
     Ok(())
 }
 
-async fn get_repo_detail(client: &Client, owner: &str, repo: &str) -> Result<Repo, Box<dyn Error>> {
-    get_repo(client, owner, repo).await
+// Print the repository picked from the interactive list
+fn print_selected_repo(selected: &StarredRepo) {
+    println!("\nSelected repository:");
+    println!("Name: {}", selected.name);
+    println!("Full name: {}", selected.full_name);
+    println!("URL: {}", selected.html_url);
+    if let Some(desc) = &selected.description {
+        println!("Description: {}", desc);
+    }
 }
 
-// Convert Repo structs to Value for selector
-async fn convert_repos_to_values(repos: Vec<Repo>) -> Vec<Value> {
-    repos
-        .into_iter()
-        .map(|repo| serde_json::to_value(repo).unwrap_or_default())
-        .collect()
-}
+// Run an interactive command whose arguments are already known, then remember it
+async fn run_command(service: &StarsService, command: HistoryCommand) -> Result<(), Box<dyn Error>> {
+    match &command {
+        HistoryCommand::ListStarred => {
+            let mut repos = list_starred(service, &all_pages()).await?;
+            load_snoozes().hide_snoozed(&mut repos, unix_now(), |repo| &repo.full_name);
+            load_archive().hide_archived(&mut repos, |repo| &repo.full_name);
+            load_pins().sort_pinned_first(&mut repos, |repo| &repo.full_name);
+            println!("Found {} starred repositories", repos.len());
 
-// Display help information
-fn show_help() {
-    println!("GitHub CLI Tool - Commands:");
-    println!("  get <owner> <repo>      - Fetch information about a repository");
-    println!("  list                    - List all starred repositories");
-    println!("  star <owner> <repo>     - Star a repository");
-    println!("  unstar <owner> <repo>   - Unstar a repository");
-    println!("  detail <owner> <repo>   - Get detailed information about a repository");
-    println!("  --interactive           - Launch interactive mode with menu selection");
-    println!("");
-    println!("Example usage:");
-    println!("  github-cli list");
-    println!("  github-cli star octocat hello-world");
-    println!("");
-    println!("Note: GITHUB_TOKEN environment variable must be set");
+            if let Some(selected) = RepoSelector::select_repo(&repos) {
+                print_selected_repo(selected);
+            }
+        }
+        HistoryCommand::Detail { owner, repo } => {
+            let repo_details = get_repo(service, owner, repo).await?;
+            print_repos(&[repo_details], OutputFormat::Table)?;
+        }
+        HistoryCommand::Star { owner, repo } => {
+            service.stars().star(owner, repo).await?;
+            println!("Starred repository {}/{}", owner, repo);
+        }
+        HistoryCommand::Unstar { owner, repo } => {
+            service.stars().unstar(owner, repo).await?;
+            println!("Unstarred repository {}/{}", owner, repo);
+        }
+    }
+
+    remember(command);
+    Ok(())
 }
 
-// Interactive mode showing menu options
-async fn interactive_mode(client: &Client) -> Result<(), Box<dyn Error>> {
-    let items = vec![
-        "List starred repositories",
-        "Get repository details",
-        "Star a repository",
-        "Unstar a repository",
-        "Exit",
-    ];
+// Pick a previous command to run again
+fn pick_from_history() -> Result<Option<HistoryCommand>, Box<dyn Error>> {
+    let history = CommandHistory::load(&Store::open_default()?)?;
+    if history.commands.is_empty() {
+        println!("No commands in history yet");
+        return Ok(None);
+    }
 
-    let selection = Select::with_theme(&ColorfulTheme::default())
-        .with_prompt("Select action")
+    Ok(Select::with_theme(&ColorfulTheme::default())
+        .with_prompt("Run again")
         .default(0)
-        .items(&items)
-        .interact()?;
+        .items(&history.commands)
+        .interact_opt()?
+        .map(|index| history.commands[index].clone()))
+}
 
-    match selection {
-        0 => {
-            // List repositories
-            let repos = list_repos(client).await?;
-            println!("Found {} starred repositories", repos.len());
+// Draw the browser over the whole terminal
+fn draw_browser(term: &Term, browser: &mut Browser) -> std::io::Result<()> {
+    let (rows, columns) = term.size();
+    let frame = browser.render(usize::from(columns), usize::from(rows), rate_limit::latest().as_ref(), unix_now());
+
+    term.move_cursor_to(0, 0)?;
+    term.write_str(&frame.join("\n"))
+}
+
+// Open a URL in the default web browser
+fn open_url(url: &str) -> std::io::Result<()> {
+    let mut command = if cfg!(target_os = "macos") {
+        std::process::Command::new("open")
+    } else if cfg!(windows) {
+        let mut command = std::process::Command::new("cmd");
+        command.args(["/C", "start", ""]);
+        command
+    } else {
+        std::process::Command::new("xdg-open")
+    };
+    command
+        .arg(url)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()?;
+    Ok(())
+}
 
-            // Convert to Value objects for the selector
-            let repos_json = convert_repos_to_values(repos).await;
+// Carry out a browser action on the selected repository, returning the message for the status bar
+async fn browser_action(
+    term: &Term,
+    github: &GitHubClient,
+    star_access: &Availability,
+    browser: &mut Browser,
+    action: BrowserAction,
+) -> String {
+    let Some(repo) = browser.selected().cloned() else {
+        return String::from("No repository selected");
+    };
+    let full_name = repo.full_name();
 
-            if let Some(selected) = RepoSelector::select_repo(repos_json) {
-                println!("\nSelected repository:");
-                println!("Name: {}", selected["name"]);
-                println!("Full name: {}", selected["full_name"]);
-                println!("URL: {}", selected["html_url"]);
-                if let Some(desc) = selected["description"].as_str() {
-                    println!("Description: {}", desc);
+    match action {
+        BrowserAction::Star | BrowserAction::Unstar => {
+            if let Availability::Unavailable(message) = star_access {
+                return message.clone();
+            }
+            let star = action == BrowserAction::Star;
+            let result = if star {
+                github.star_repo(&repo.owner, &repo.name).await
+            } else {
+                github.unstar_repo(&repo.owner, &repo.name).await
+            };
+            match result {
+                Ok(()) => {
+                    if let Some(selected) = browser.selected_mut() {
+                        selected.starred = star;
+                    }
+                    let (owner, repo) = (repo.owner, repo.name);
+                    if star {
+                        remember(HistoryCommand::Star { owner, repo });
+                        format!("Starred {}", full_name)
+                    } else {
+                        remember(HistoryCommand::Unstar { owner, repo });
+                        format!("Unstarred {}", full_name)
+                    }
                 }
+                Err(e) => format!("Could not update {}: {}", full_name, e),
             }
         }
-        1 => {
-            // Get repository details (first list, then show details)
-            let repos = list_repos(client).await?;
-            let repos_json = convert_repos_to_values(repos).await;
-
-            if let Some(selected) = RepoSelector::select_repo(repos_json) {
-                let owner = selected["owner"]["login"].as_str().unwrap_or("unknown");
-                let repo_name = selected["name"].as_str().unwrap_or("unknown");
+        BrowserAction::Clone => {
+            let path = match env::current_dir() {
+                Ok(dir) => dir.join(format!("{}-{}", repo.owner, repo.name)),
+                Err(e) => return format!("Could not clone {}: {}", full_name, e),
+            };
+            // Cloning removes an existing directory, so never clone over one from here
+            if path.exists() {
+                return format!("{} already exists", path.display());
+            }
 
-                let repo_details = get_repo_detail(client, owner, repo_name).await?;
-                let mut table = Table::new();
-                table.add_row(row!["ID", "Name", "Full Name", "Description", "URL"]);
-                table.add_row(row![
-                    repo_details.id,
-                    repo_details.name,
-                    repo_details.full_name,
-                    repo_details.description.unwrap_or_default(),
-                    repo_details.html_url
-                ]);
-                table.printstd();
+            browser.set_status(format!("Cloning {}…", full_name));
+            let _ = draw_browser(term, browser);
+            match github.download_repo(&repo.owner, &repo.name, Some(&path)).await {
+                Ok(location) => format!("Cloned {} into {}", full_name, location),
+                Err(e) => format!("Could not clone {}: {}", full_name, e),
             }
         }
-        2 => {
-            // Star a repository - need manual input
-            println!("Enter repository owner:");
-            let mut owner = String::new();
-            std::io::stdin().read_line(&mut owner)?;
-            let owner = owner.trim();
+        BrowserAction::TogglePin => match set_pinned(&full_name, !repo.pinned) {
+            Ok(message) => {
+                if let Some(selected) = browser.selected_mut() {
+                    selected.pinned = !repo.pinned;
+                }
+                message
+            }
+            Err(e) => format!("Could not update pins: {}", e),
+        },
+        BrowserAction::Open => match open_url(&repo.html_url) {
+            Ok(()) => format!("Opened {}", repo.html_url),
+            Err(e) => format!("Could not open {}: {}", repo.html_url, e),
+        },
+        BrowserAction::History | BrowserAction::Quit => String::new(),
+    }
+}
 
-            println!("Enter repository name:");
-            let mut repo_name = String::new();
-            std::io::stdin().read_line(&mut repo_name)?;
-            let repo_name = repo_name.trim();
+// Leave the browser to pick a command from history, then come back once it has run
+async fn browser_history(term: &Term, service: &StarsService, star_access: &Availability) -> Result<(), Box<dyn Error>> {
+    leave_alternate_screen();
+    term.show_cursor()?;
 
-            star_repo(client, owner, repo_name).await?;
-            println!("Starred repository {}/{}", owner, repo_name);
+    // History can hold starring commands from before the token lost access
+    match (pick_from_history()?, star_access) {
+        (Some(HistoryCommand::Star { .. } | HistoryCommand::Unstar { .. }), Availability::Unavailable(message)) => {
+            println!("{}", message);
+        }
+        (Some(command), _) => {
+            if let Err(e) = run_command(service, command).await {
+                println!("Error: {}", e);
+            }
         }
-        3 => {
-            // Unstar a repository - select from currently starred
-            let repos = list_repos(client).await?;
-            let repos_json = convert_repos_to_values(repos).await;
+        (None, _) => {}
+    }
+
+    println!("Press any key to return to your stars");
+    term.read_key()?;
+    enter_alternate_screen()?;
+    term.hide_cursor()?;
+    Ok(())
+}
+
+// Interactive mode: browse starred repositories full screen and act on the selected one
+async fn interactive_mode(
+    service: &StarsService,
+    star_access: &Availability,
+) -> Result<(), Box<dyn Error>> {
+    let github = service.client();
+    let term = Term::stdout();
+    if !term.is_term() {
+        return Err("Interactive mode needs a terminal".into());
+    }
+
+    let mut starred = list_starred(service, &all_pages()).await?;
+    let pins = load_pins();
+    load_snoozes().hide_snoozed(&mut starred, unix_now(), |repo| &repo.full_name);
+    load_archive().hide_archived(&mut starred, |repo| &repo.full_name);
+    pins.sort_pinned_first(&mut starred, |repo| &repo.full_name);
+    let mut repos: Vec<BrowserRepo> = starred.into_iter().map(BrowserRepo::from).collect();
+    for repo in &mut repos {
+        repo.pinned = pins.is_pinned(&repo.full_name());
+    }
+    let mut browser = Browser::new(repos);
+    if let Availability::Unavailable(message) = star_access {
+        browser.set_status(message.clone());
+    }
 
-            if let Some(selected) = RepoSelector::select_repo(repos_json) {
-                let owner = selected["owner"]["login"].as_str().unwrap_or("unknown");
-                let repo_name = selected["name"].as_str().unwrap_or("unknown");
+    let _terminal = TerminalGuard::new();
+    enter_alternate_screen()?;
+    term.hide_cursor()?;
 
-                unstar_repo(client, owner, repo_name).await?;
-                println!("Unstarred repository {}/{}", owner, repo_name);
+    loop {
+        draw_browser(&term, &mut browser)?;
+        let page = Browser::list_height(usize::from(term.size().0));
+        match browser.handle_key(&term.read_key()?, page) {
+            Some(BrowserAction::Quit) => return Ok(()),
+            Some(BrowserAction::History) => browser_history(&term, service, star_access).await?,
+            Some(action) => {
+                let status = browser_action(&term, github, star_access, &mut browser, action).await;
+                browser.set_status(status);
             }
-        }
-        4 | _ => {
-            println!("Exiting");
-            return Ok(());
+            None => {}
         }
     }
+}
 
-    // Recursively call interactive mode to keep the menu going
-    Box::pin(interactive_mode(client)).await
+fn draw_map(term: &Term, map: &mut StarMap) -> std::io::Result<()> {
+    let (rows, columns) = term.size();
+    let frame = map.render(usize::from(columns), usize::from(rows));
+
+    term.move_cursor_to(0, 0)?;
+    term.write_str(&frame.join("\n"))
 }
 
-#[tokio::main]
-async fn main() -> Result<(), Box<dyn Error>> {
-    // If no arguments provided, show help
-    if std::env::args().len() <= 1 {
-        show_help();
-        return Ok(());
+// Show my stars as a treemap grouped by language or topic, and pick from a group's repositories
+async fn map_command(service: &StarsService, matches: &ArgMatches) -> Result<(), Box<dyn Error>> {
+    let term = Term::stdout();
+    if !term.is_term() {
+        return Err("The map needs a terminal".into());
     }
+    let by: MapGroup = matches.value_of("by").unwrap().parse()?;
+
+    let mut starred = list_starred(service, &all_pages()).await?;
+    load_snoozes().hide_snoozed(&mut starred, unix_now(), |repo| &repo.full_name);
+    load_archive().hide_archived(&mut starred, |repo| &repo.full_name);
+    let mut map = StarMap::new(by, clusters(&starred, by));
 
-    let github_token = match env::var("GITHUB_TOKEN") {
-        Ok(token) => token,
-        Err(_) => {
-            eprintln!("Error: GITHUB_TOKEN environment variable must be set");
-            return Ok(());
+    let _terminal = TerminalGuard::new();
+    enter_alternate_screen()?;
+    term.hide_cursor()?;
+
+    loop {
+        draw_map(&term, &mut map)?;
+        match map.handle_key(&term.read_key()?) {
+            Some(MapAction::Quit) => return Ok(()),
+            Some(MapAction::Open) => {
+                let Some(cluster) = map.selected() else {
+                    continue;
+                };
+                leave_alternate_screen();
+                term.show_cursor()?;
+                if let Some(selected) = RepoSelector::select_repo(&cluster.repos) {
+                    print_selected_repo(selected);
+                }
+                println!("Press any key to return to the map");
+                term.read_key()?;
+                enter_alternate_screen()?;
+                term.hide_cursor()?;
+            }
+            None => {}
         }
-    };
+    }
+}
 
-    let mut headers = header::HeaderMap::new();
-    headers.insert(header::USER_AGENT, header::HeaderValue::from_static("reqwest"));
-    headers.insert(header::AUTHORIZATION, header::HeaderValue::from_str(&format!("token {}", github_token))?);
+// Shells completions can be generated for
+const COMPLETION_SHELLS: &[&str] = &["bash", "zsh", "fish", "powershell", "elvish"];
 
-    let client = Client::builder()
-        .default_headers(headers)
-        .build()?;
+// Print the completion script for a shell, covering every subcommand and flag of build_app
+fn completions_command(matches: &ArgMatches) -> Result<(), Box<dyn Error>> {
+    let shell: Shell = matches.value_of("shell").unwrap().parse()?;
+    clap_complete::generate(shell, &mut build_app(), env!("CARGO_BIN_NAME"), &mut std::io::stdout());
+    Ok(())
+}
+
+// Write a man page for a command and each of its subcommands, named like git's: stars_fetcher-config-get.1
+fn write_manpages(app: &App<'static>, name: &str, dir: &Path) -> Result<usize, Box<dyn Error>> {
+    let mut page = Vec::new();
+    clap_mangen::Man::new(app.clone().name(name)).render(&mut page)?;
+    fs::write(dir.join(format!("{}.1", name)), page)?;
+
+    let mut written = 1;
+    // clap's generated help subcommands only print the other pages
+    for subcommand in app.get_subcommands().filter(|subcommand| subcommand.get_name() != "help") {
+        written += write_manpages(subcommand, &format!("{}-{}", name, subcommand.get_name()), dir)?;
+    }
+    Ok(written)
+}
+
+fn manpage_command(matches: &ArgMatches) -> Result<(), Box<dyn Error>> {
+    let dir = Path::new(matches.value_of("output").unwrap());
+    fs::create_dir_all(dir)?;
+    let mut app = build_app();
+    app.build();
+    let written = write_manpages(&app, env!("CARGO_BIN_NAME"), dir)?;
+    println!("Wrote {} man pages to {}", written, dir.display());
+    Ok(())
+}
 
-    let app = App::new("GitHub CLI")
+// The command line, also used to generate shell completions and man pages
+fn build_app() -> App<'static> {
+    App::new("GitHub CLI")
         .version("1.0")
         .author("Your Name <your.email@example.com>")
         .about("CLI tool to interact with GitHub")
         .subcommand(SubCommand::with_name("get")
             .about("Fetch a repository")
             .arg(Arg::with_name("owner")
-                .help("Owner of the repository")
-                .required(true)
+                .help("Owner of the repository, owner/repo or any GitHub URL inside it")
+                .required_unless_present("batch")
                 .index(1))
             .arg(Arg::with_name("repo")
                 .help("Name of the repository")
-                .required(true)
-                .index(2)))
+                .index(2))
+            .arg(Arg::with_name("batch")
+                .long("batch")
+                .takes_value(true)
+                .value_name("FILE")
+                .conflicts_with("owner")
+                .help("Fetch every repository listed in a file, one per line"))
+            .arg(format_arg()))
         .subcommand(SubCommand::with_name("list")
-            .about("List all starred repositories"))
+            .about("List all starred repositories")
+            .arg(format_arg())
+            .arg(Arg::with_name("filter")
+                .long("filter")
+                .takes_value(true)
+                .help("Name of a saved filter, or a filter such as \"language:rust stars:>500\""))
+            .arg(Arg::with_name("language")
+                .long("language")
+                .takes_value(true)
+                .value_name("LANGUAGES")
+                .help("Only repositories mainly written in one of these comma-separated languages"))
+            .arg(Arg::with_name("topic")
+                .long("topic")
+                .takes_value(true)
+                .multiple_occurrences(true)
+                .help("Only repositories with this topic, repeat to require several"))
+            .arg(Arg::with_name("sort")
+                .long("sort")
+                .takes_value(true)
+                .possible_values(LIST_SORTS)
+                .help("Order by stars, name, last update or when I starred them, instead of the API's order"))
+            .arg(Arg::with_name("desc")
+                .long("desc")
+                .requires("sort")
+                .help("Sort in descending order"))
+            .arg(Arg::with_name("per-page")
+                .long("per-page")
+                .takes_value(true)
+                .help("Repositories per request, 1 to 100 (100 unless pagination.per_page is set)"))
+            .arg(Arg::with_name("max-pages")
+                .long("max-pages")
+                .takes_value(true)
+                .help("Stop after this many pages instead of fetching every starred repository")))
         .subcommand(SubCommand::with_name("star")
-            .about("Star a repository")
+            .about("Star a repository, or every repository listed in a file")
+            .arg(format_arg())
             .arg(Arg::with_name("owner")
-                .help("Owner of the repository")
-                .required(true)
+                .help("Owner of the repository, owner/repo or any GitHub URL inside it")
+                .required_unless_present("from-file")
                 .index(1))
             .arg(Arg::with_name("repo")
                 .help("Name of the repository")
-                .required(true)
-                .index(2)))
+                .index(2))
+            .arg(from_file_arg())
+            .arg(dry_run_arg()))
         .subcommand(SubCommand::with_name("unstar")
-            .about("Unstar a repository")
+            .about("Unstar a repository, or pick several to unstar from my stars")
+            .arg(format_arg())
             .arg(Arg::with_name("owner")
-                .help("Owner of the repository")
-                .required(true)
+                .help("Owner of the repository, owner/repo or any GitHub URL inside it")
+                .required_unless_present_any(["interactive", "from-file"])
                 .index(1))
             .arg(Arg::with_name("repo")
                 .help("Name of the repository")
-                .required(true)
-                .index(2)))
+                .index(2))
+            .arg(Arg::with_name("interactive")
+                .long("interactive")
+                .conflicts_with_all(&["owner", "from-file"])
+                .help("Pick the repositories to unstar from a list of my stars"))
+            .arg(from_file_arg())
+            .arg(dry_run_arg()))
         .subcommand(SubCommand::with_name("detail")
             .about("Get repository details")
+            .arg(format_arg())
             .arg(Arg::with_name("owner")
-                .help("Owner of the repository")
+                .help("Owner of the repository, owner/repo or any GitHub URL inside it")
                 .required(true)
                 .index(1))
             .arg(Arg::with_name("repo")
                 .help("Name of the repository")
-                .required(true)
                 .index(2)))
+        .subcommand(SubCommand::with_name("badge")
+            .about("Generate a star count badge")
+            .arg(Arg::with_name("repo")
+                .long("repo")
+                .takes_value(true)
+                .value_name("OWNER/REPO")
+                .help("Show this repository's stars instead of how many repositories I starred"))
+            .arg(Arg::with_name("format")
+                .long("format")
+                .takes_value(true)
+                .possible_values(["svg", "json"])
+                .default_value("svg")
+                .help("SVG image or shields.io endpoint JSON"))
+            .arg(Arg::with_name("label")
+                .long("label")
+                .takes_value(true)
+                .help("Text on the left side of the badge"))
+            .arg(Arg::with_name("output")
+                .long("output")
+                .short('o')
+                .takes_value(true)
+                .help("Write to a file instead of stdout"))
+            .arg(Arg::with_name("gist")
+                .long("gist")
+                .takes_value(true)
+                .min_values(0)
+                .value_name("ID")
+                .help("Upload to a new public gist, or update the gist with this ID")))
+        .subcommand(SubCommand::with_name("ci")
+            .about("Show the CI status of repositories' default branches")
+            .arg(format_arg())
+            .arg(Arg::with_name("repos")
+                .help("Repositories, as owner/repo or URL")
+                .multiple(true)
+                .required_unless_present("file")
+                .index(1))
+            .arg(Arg::with_name("file")
+                .long("file")
+                .takes_value(true)
+                .help("Read repositories from a file, one per line")))
+        .subcommand(SubCommand::with_name("my-stargazers")
+            .about("List who recently starred my repositories, marking new stargazers since the last run")
+            .arg(format_arg())
+            .arg(Arg::with_name("repo")
+                .long("repo")
+                .takes_value(true)
+                .value_name("NAME")
+                .help("Only look at this repository of mine"))
+            .arg(Arg::with_name("limit")
+                .long("limit")
+                .takes_value(true)
+                .default_value("20")
+                .help("Maximum number of stargazers to show")))
+        .subcommand(SubCommand::with_name("fork")
+            .about("Fork a repository into my account or an organization")
+            .arg(Arg::with_name("repo")
+                .help("Repository to fork, as owner/repo or URL")
+                .required(true)
+                .index(1))
+            .arg(Arg::with_name("org")
+                .long("org")
+                .takes_value(true)
+                .help("Fork into this organization instead of my account"))
+            .arg(Arg::with_name("star")
+                .long("star")
+                .help("Also star the upstream repository")))
+        .subcommand(SubCommand::with_name("fork-status")
+            .about("Show how far forks are behind their upstream, using the compare API")
+            .arg(format_arg())
+            .arg(Arg::with_name("repo")
+                .help("Fork, as owner/repo, URL, or the name of my own fork")
+                .required_unless_present("all")
+                .index(1))
+            .arg(Arg::with_name("all")
+                .long("all")
+                .conflicts_with("repo")
+                .help("Check every fork among my starred repositories")))
+        .subcommand(SubCommand::with_name("releases")
+            .about("Work with a repository's releases")
+            .subcommand(SubCommand::with_name("download")
+                .about("Download release assets, verifying their published checksums")
+                .arg(Arg::with_name("repo")
+                    .help("Repository, as owner/repo or URL")
+                    .required(true)
+                    .index(1))
+                .arg(Arg::with_name("tag")
+                    .long("tag")
+                    .takes_value(true)
+                    .help("Release tag, defaults to the latest release"))
+                .arg(Arg::with_name("pick")
+                    .long("pick")
+                    .conflicts_with("tag")
+                    .help("Pick the release tag from a list"))
+                .arg(Arg::with_name("asset")
+                    .long("asset")
+                    .takes_value(true)
+                    .required(true)
+                    .help("Download the assets whose name contains this text"))
+                .arg(Arg::with_name("output")
+                    .short('o')
+                    .long("output")
+                    .takes_value(true)
+                    .default_value(".")
+                    .help("Directory to save the assets in"))
+                .arg(Arg::with_name("verify-signature")
+                    .long("verify-signature")
+                    .help("Require a good OpenPGP signature, checked with gpg against my keyring"))
+                .arg(dry_run_arg())))
+        .subcommand(SubCommand::with_name("tags")
+            .about("List a repository's tags with commit dates")
+            .arg(format_arg())
+            .arg(Arg::with_name("repo")
+                .help("Repository, as owner/repo or URL")
+                .required(true)
+                .index(1))
+            .arg(Arg::with_name("page")
+                .long("page")
+                .takes_value(true)
+                .default_value("1")
+                .help("Page of results to show"))
+            .arg(Arg::with_name("per-page")
+                .long("per-page")
+                .takes_value(true)
+                .default_value("30")
+                .help("Results per page, at most 100")))
+        .subcommand(SubCommand::with_name("branches")
+            .about("List a repository's branches with commit dates")
+            .arg(format_arg())
+            .arg(Arg::with_name("repo")
+                .help("Repository, as owner/repo or URL")
+                .required(true)
+                .index(1))
+            .arg(Arg::with_name("page")
+                .long("page")
+                .takes_value(true)
+                .default_value("1")
+                .help("Page of results to show"))
+            .arg(Arg::with_name("per-page")
+                .long("per-page")
+                .takes_value(true)
+                .default_value("30")
+                .help("Results per page, at most 100")))
+        .subcommand(SubCommand::with_name("changelog")
+            .about("Show release notes between two versions of a repository")
+            .arg(Arg::with_name("repo")
+                .help("Repository, as owner/repo or URL")
+                .required(true)
+                .index(1))
+            .arg(Arg::with_name("from")
+                .long("from")
+                .takes_value(true)
+                .required_unless_present("pick")
+                .help("Tag of the version upgraded from, whose own notes are left out"))
+            .arg(Arg::with_name("to")
+                .long("to")
+                .takes_value(true)
+                .help("Tag of the version upgraded to, defaults to the latest release"))
+            .arg(Arg::with_name("pick")
+                .long("pick")
+                .help("Pick the tags not given from a list")))
+        .subcommand(SubCommand::with_name("install")
+            .about("Install a binary from a repository's releases for this platform")
+            .arg(Arg::with_name("repo")
+                .help("Repository, as owner/repo or URL")
+                .required_unless_present("upgrade-all")
+                .index(1))
+            .arg(Arg::with_name("tag")
+                .long("tag")
+                .takes_value(true)
+                .conflicts_with("upgrade-all")
+                .help("Release tag, defaults to the latest release"))
+            .arg(Arg::with_name("pick")
+                .long("pick")
+                .conflicts_with_all(&["tag", "upgrade-all"])
+                .help("Pick the release tag from a list"))
+            .arg(Arg::with_name("bin")
+                .long("bin")
+                .takes_value(true)
+                .conflicts_with("upgrade-all")
+                .help("Executable to install when the asset holds several, defaults to the repository name"))
+            .arg(Arg::with_name("bin-dir")
+                .long("bin-dir")
+                .takes_value(true)
+                .conflicts_with("upgrade-all")
+                .help("Directory to install into, defaults to install.bin_dir in the config or ~/.local/bin"))
+            .arg(Arg::with_name("upgrade-all")
+                .long("upgrade-all")
+                .conflicts_with("repo")
+                .help("Upgrade everything installed to the latest release"))
+            .arg(Arg::with_name("verify-signature")
+                .long("verify-signature")
+                .help("Require a good OpenPGP signature, checked with gpg against my keyring")))
+        .subcommand(SubCommand::with_name("installed")
+            .about("Binaries installed from releases")
+            .subcommand(SubCommand::with_name("list")
+                .about("List installed binaries with their version and origin")
+                .arg(format_arg())))
+        .subcommand(SubCommand::with_name("uninstall")
+            .about("Remove a binary installed from a release")
+            .arg(Arg::with_name("name")
+                .help("Name of the installed binary")
+                .required(true)
+                .index(1)))
+        .subcommand(SubCommand::with_name("topics")
+            .about("Curate the topics of my repositories")
+            .subcommand(SubCommand::with_name("set")
+                .about("Replace a repository's topics")
+                .arg(Arg::with_name("repo")
+                    .help("Name of my repository, or owner/repo for one I have push access to")
+                    .required(true)
+                    .index(1))
+                .arg(Arg::with_name("topics")
+                    .help("Comma separated topics, empty to remove them all")
+                    .required(true)
+                    .index(2))))
+        .subcommand(SubCommand::with_name("traffic")
+            .about("Show views, clones and referrers for one of my repositories")
+            .arg(Arg::with_name("repo")
+                .help("Name of my repository, or owner/repo for one I have push access to")
+                .required(true)
+                .index(1))
+            .arg(Arg::with_name("days")
+                .long("days")
+                .takes_value(true)
+                .default_value("14")
+                .help("Number of days of history to show"))
+            .arg(Arg::with_name("csv")
+                .long("csv")
+                .takes_value(true)
+                .value_name("FILE")
+                .help("Export the whole recorded history as CSV")))
+        .subcommand(SubCommand::with_name("activity")
+            .about("Show a repository's weekly commit activity as a heatmap")
+            .arg(Arg::with_name("repo")
+                .help("Repository, as owner/repo or URL")
+                .required(true)
+                .index(1)))
+        .subcommand(SubCommand::with_name("owner")
+            .about("Show a user's or organization's profile and most-starred repositories")
+            .arg(Arg::with_name("login")
+                .help("Login of the user or organization")
+                .required(true)
+                .index(1))
+            .arg(Arg::with_name("limit")
+                .long("limit")
+                .takes_value(true)
+                .default_value("10")
+                .help("Maximum number of repositories to show")))
+        .subcommand(SubCommand::with_name("similar")
+            .about("Find repositories similar to a given one that I haven't starred")
+            .arg(Arg::with_name("repo")
+                .help("Repository to compare against, as owner/repo or URL")
+                .required(true)
+                .index(1))
+            .arg(Arg::with_name("limit")
+                .long("limit")
+                .takes_value(true)
+                .default_value("10")
+                .help("Maximum number of repositories to show"))
+            .arg(Arg::with_name("star")
+                .long("star")
+                .help("Pick alternates to star from the results")))
+        .subcommand(SubCommand::with_name("prune")
+            .about("Unstar starred repositories that are no longer maintained")
+            .arg(Arg::with_name("archived")
+                .long("archived")
+                .help("Repositories their owners archived on GitHub"))
+            .arg(Arg::with_name("inactive-years")
+                .long("inactive-years")
+                .takes_value(true)
+                .value_name("N")
+                .help("Repositories nobody pushed to in the last N years"))
+            .arg(Arg::with_name("yes")
+                .long("yes")
+                .short('y')
+                .help("Unstar them without asking"))
+            .arg(dry_run_arg()
+                .conflicts_with("yes"))
+            .arg(format_arg()))
+        .subcommand(SubCommand::with_name("search")
+            .about("Search GitHub for repositories and star the ones picked from the results")
+            .arg(Arg::with_name("query")
+                .help("Search terms, with any of GitHub's search qualifiers")
+                .multiple_values(true)
+                .index(1))
+            .arg(Arg::with_name("language")
+                .long("language")
+                .takes_value(true)
+                .help("Only repositories mainly written in this language"))
+            .arg(Arg::with_name("stars")
+                .long("stars")
+                .takes_value(true)
+                .value_name("RANGE")
+                .help("Only repositories with this many stars, e.g. >1000 or 100..500"))
+            .arg(Arg::with_name("sort")
+                .long("sort")
+                .takes_value(true)
+                .possible_values(SEARCH_SORTS)
+                .default_value("best-match")
+                .help("Order of the results"))
+            .arg(Arg::with_name("limit")
+                .long("limit")
+                .takes_value(true)
+                .default_value("20")
+                .help("Maximum number of repositories to show"))
+            .arg(format_arg()))
+        .subcommand(SubCommand::with_name("crates")
+            .about("Show crates.io packages published from my starred Rust repositories")
+            .arg(format_arg())
+            .subcommand(SubCommand::with_name("find")
+                .about("Check whether I've starred the source repository of a crate")
+                .arg(Arg::with_name("package")
+                    .help("Name of the crate")
+                    .required(true)
+                    .index(1))))
+        .subcommand(SubCommand::with_name("packages")
+            .about("Show packages published from my starred repositories on crates.io, npm and PyPI")
+            .arg(format_arg())
+            .arg(Arg::with_name("registry")
+                .long("registry")
+                .takes_value(true)
+                .multiple_occurrences(true)
+                .possible_values(REGISTRY_NAMES)
+                .help("Registry to check, defaults to all of them"))
+            .subcommand(SubCommand::with_name("find")
+                .about("Check whether I've starred the source repository of a package")
+                .arg(Arg::with_name("package")
+                    .help("Name of the package")
+                    .required(true)
+                    .index(1))
+                .arg(Arg::with_name("registry")
+                    .long("registry")
+                    .takes_value(true)
+                    .possible_values(REGISTRY_NAMES)
+                    .default_value("crates")
+                    .help("Registry the package is published to"))))
+        .subcommand(SubCommand::with_name("collection")
+            .about("Team-shared star collections kept in a gist")
+            .subcommand(SubCommand::with_name("pull")
+                .about("Star every repository in a collection")
+                .arg(Arg::with_name("gist")
+                    .help("Gist ID or URL holding the collection")
+                    .required(true)
+                    .index(1))
+                .arg(Arg::with_name("file")
+                    .long("file")
+                    .takes_value(true)
+                    .help("File in the gist to read, defaults to the first one")))
+            .subcommand(SubCommand::with_name("propose")
+                .about("Propose adding a repository to a collection")
+                .arg(Arg::with_name("gist")
+                    .help("Gist ID or URL holding the collection")
+                    .required(true)
+                    .index(1))
+                .arg(Arg::with_name("repo")
+                    .help("Repository to add, as owner/repo or URL")
+                    .required(true)
+                    .index(2))
+                .arg(Arg::with_name("reason")
+                    .long("reason")
+                    .takes_value(true)
+                    .help("Why the team should star it"))))
+        .subcommand(SubCommand::with_name("pin")
+            .about("Pin a repository so it is listed first, or show pinned repositories")
+            .arg(Arg::with_name("repo")
+                .help("Repository, as owner/repo or URL")
+                .index(1)))
+        .subcommand(SubCommand::with_name("unpin")
+            .about("Unpin a repository")
+            .arg(Arg::with_name("repo")
+                .help("Repository, as owner/repo or URL")
+                .required(true)
+                .index(1)))
+        .subcommand(SubCommand::with_name("snooze")
+            .about("Hide a repository from listings for a while, or show snoozed repositories")
+            .arg(Arg::with_name("repo")
+                .help("Repository, as owner/repo or URL")
+                .requires("for")
+                .index(1))
+            .arg(Arg::with_name("for")
+                .long("for")
+                .takes_value(true)
+                .value_name("PERIOD")
+                .requires("repo")
+                .help("How long to hide it, e.g. 30d, 12h or 2w")))
+        .subcommand(SubCommand::with_name("unsnooze")
+            .about("Show a snoozed repository again")
+            .arg(Arg::with_name("repo")
+                .help("Repository, as owner/repo or URL")
+                .required(true)
+                .index(1)))
+        .subcommand(SubCommand::with_name("archive")
+            .about("Hide a star from listings without unstarring it, or show archived stars")
+            .arg(Arg::with_name("repo")
+                .help("Repository, as owner/repo or URL")
+                .conflicts_with("auto")
+                .index(1))
+            .arg(Arg::with_name("auto")
+                .long("auto")
+                .help("Archive stars older than auto_archive_after in the config that I never used"))
+            .arg(Arg::with_name("dry-run")
+                .long("dry-run")
+                .requires("auto")
+                .help("Only show what --auto would archive")))
+        .subcommand(SubCommand::with_name("unarchive")
+            .about("Show an archived star again")
+            .arg(Arg::with_name("repo")
+                .help("Repository, as owner/repo or URL")
+                .required(true)
+                .index(1)))
+        .subcommand(SubCommand::with_name("cache")
+            .about("Manage the responses kept for conditional requests")
+            .subcommand(SubCommand::with_name("stats")
+                .about("Show the size of the cache, how often it was used and its oldest responses"))
+            .subcommand(SubCommand::with_name("clear")
+                .about("Forget every kept response"))
+            .subcommand(SubCommand::with_name("prune")
+                .about("Forget responses fetched longer ago than a period")
+                .arg(Arg::with_name("older-than")
+                    .long("older-than")
+                    .takes_value(true)
+                    .required(true)
+                    .value_name("PERIOD")
+                    .help("Period such as 12h, 30d or 1y"))))
+        .subcommand(SubCommand::with_name("completions")
+            .about("Print a completion script for a shell")
+            .arg(Arg::with_name("shell")
+                .help("Shell to complete in")
+                .possible_values(COMPLETION_SHELLS)
+                .required(true)
+                .index(1)))
+        .subcommand(SubCommand::with_name("manpage")
+            .about("Write roff man pages for the command and every subcommand")
+            .arg(Arg::with_name("output")
+                .short('o')
+                .long("output")
+                .takes_value(true)
+                .value_name("DIR")
+                .default_value("man")
+                .help("Directory to write the pages to")))
+        .subcommand(SubCommand::with_name("export")
+            .about("Write my stars as a graph linked by shared owners, topics and languages, or as a searchable web page")
+            .arg(Arg::with_name("format")
+                .long("format")
+                .takes_value(true)
+                .possible_values(EXPORT_FORMATS)
+                .default_value("graphviz")
+                .help("Graphviz DOT, GEXF for Gephi, or a self-contained HTML page"))
+            .arg(Arg::with_name("link")
+                .long("link")
+                .takes_value(true)
+                .multiple_occurrences(true)
+                .use_value_delimiter(true)
+                .possible_values(GRAPH_LINKS)
+                .help("What to link repositories through, all of owner, topic and language by default"))
+            .arg(Arg::with_name("output")
+                .short('o')
+                .long("output")
+                .takes_value(true)
+                .value_name("FILE")
+                .help("Write to a file instead of stdout")))
+        .subcommand(SubCommand::with_name("whoami")
+            .about("Show the account and token in use, with the token's scopes")
+            .arg(format_arg()))
+        .subcommand(SubCommand::with_name("rate-limit")
+            .about("Show the requests left of the core, search and GraphQL rate limits and when they reset")
+            .arg(format_arg()))
+        .subcommand(SubCommand::with_name("onthisday")
+            .about("List repositories I starred on this day in earlier years")
+            .arg(Arg::with_name("date")
+                .long("date")
+                .takes_value(true)
+                .value_name("YYYY-MM-DD")
+                .help("Look back from another day than today (UTC)"))
+            .arg(format_arg()))
+        .subcommand(SubCommand::with_name("map")
+            .about("Show my stars as a treemap sized by stargazers, and pick from a group")
+            .arg(Arg::with_name("by")
+                .long("by")
+                .takes_value(true)
+                .possible_values(MAP_GROUPS)
+                .default_value("language")
+                .help("Group repositories by language, topic or owner")))
+        .subcommand(SubCommand::with_name("store")
+            .about("Work with the local data kept between runs")
+            .subcommand(SubCommand::with_name("dump")
+                .about("Write pins, snoozes, archived stars, stargazers, history and traffic as one file per table")
+                .arg(Arg::with_name("format")
+                    .long("format")
+                    .takes_value(true)
+                    .possible_values(STORE_DUMP_FORMATS)
+                    .default_value("csv")
+                    .help("File format of the tables"))
+                .arg(Arg::with_name("output")
+                    .short('o')
+                    .long("output")
+                    .takes_value(true)
+                    .value_name("DIR")
+                    .default_value("stars_fetcher-dump")
+                    .help("Directory to write the tables to, created if missing")))
+            .subcommand(SubCommand::with_name("rollback")
+                .about("Put back local data as it was before an upgrade of stars_fetcher migrated it")))
+        .subcommand(SubCommand::with_name("verify")
+            .about("Compare pins, snoozes and archived stars with my stars on GitHub")
+            .arg(Arg::with_name("fix")
+                .long("fix")
+                .help("Remove entries for repositories no longer starred and unarchive ones starred again")))
+        .subcommand(SubCommand::with_name("stats")
+            .about("Show statistics")
+            .arg(format_arg())
+            .arg(Arg::with_name("self")
+                .long("self")
+                .required(true)
+                .help("Show how often I ran each command and how long it took, as recorded locally"))
+            .arg(Arg::with_name("reset")
+                .long("reset")
+                .requires("self")
+                .help("Forget the recorded usage")))
+        .subcommand(SubCommand::with_name("config")
+            .about("Manage settings and share them across machines")
+            .subcommand(SubCommand::with_name("get")
+                .about("Print a setting")
+                .arg(Arg::with_name("key")
+                    .help("Setting as a dotted key, e.g. github.api_url")
+                    .required(true)
+                    .index(1)))
+            .subcommand(SubCommand::with_name("set")
+                .about("Change a setting in the config file")
+                .arg(Arg::with_name("key")
+                    .help("Setting as a dotted key, e.g. retry.max_attempts")
+                    .required(true)
+                    .index(1))
+                .arg(Arg::with_name("value")
+                    .help("New value, read as TOML when the setting accepts it")
+                    .required(true)
+                    .index(2)))
+            .subcommand(SubCommand::with_name("list")
+                .about("Print every setting, with secrets masked"))
+            .subcommand(SubCommand::with_name("path")
+                .about("Print where the config file is"))
+            .subcommand(SubCommand::with_name("edit")
+                .about("Open the config file in $VISUAL or $EDITOR and check it afterwards"))
+            .subcommand(SubCommand::with_name("export")
+                .about("Export settings without secrets")
+                .arg(Arg::with_name("output")
+                    .long("output")
+                    .short('o')
+                    .takes_value(true)
+                    .help("Write to a file instead of stdout")))
+            .subcommand(SubCommand::with_name("import")
+                .about("Import settings, keeping this machine's secrets")
+                .arg(Arg::with_name("file")
+                    .help("File produced by config export")
+                    .required(true)
+                    .index(1)))
+            .subcommand(SubCommand::with_name("keyring")
+                .about("Move the token from config.toml to the system keyring")))
         .arg(Arg::with_name("interactive")
             .long("interactive")
-            .help("Start interactive mode"))
-        .get_matches();
+            .help("Browse starred repositories full screen"))
+        .arg(Arg::with_name("no-meta")
+            .long("no-meta")
+            .global(true)
+            .help("Print bare JSON records, without the metadata envelope"))
+        .arg(Arg::with_name("bug-report")
+            .long("bug-report")
+            .global(true)
+            .help("On failure, write a report without secrets to attach to an issue"))
+        .arg(Arg::with_name("wait-on-ratelimit")
+            .long("wait-on-ratelimit")
+            .global(true)
+            .help("Wait for an exhausted rate limit to reset and carry on, instead of failing"))
+        .arg(Arg::with_name("no-cache")
+            .long("no-cache")
+            .global(true)
+            .help("Fetch every response in full instead of revalidating the ones kept locally"))
+        .arg(Arg::with_name("verbose")
+            .short('v')
+            .long("verbose")
+            .global(true)
+            .multiple_occurrences(true)
+            .help("Report the API requests made when done: a summary, or each endpoint with -vv"))
+        .arg(Arg::with_name("profile")
+            .long("profile")
+            .global(true)
+            .takes_value(true)
+            .value_name("NAME")
+            .help("Use the token, email and API URL of [profiles.NAME] in the config"))
+}
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn Error>> {
+    metrics::start();
+    install_panic_hook();
+
+    // If no arguments provided, show help
+    if std::env::args().len() <= 1 {
+        show_help();
+        return Ok(());
+    }
+
+    let app = build_app().get_matches();
+
+    // Every Config::new of the run picks the profile up from the environment
+    if let Some(profile) = app.value_of("profile") {
+        env::set_var(PROFILE_ENV, profile);
+    }
+
+    let started = Instant::now();
+    let result = run(&app).await;
+    record_usage(&app, started.elapsed(), result.is_ok());
+    print_request_summary(app.occurrences_of("verbose"));
+
+    if let Err(error) = &result {
+        if app.is_present("bug-report") {
+            match write_bug_report(&error.to_string()) {
+                Ok(path) => eprintln!("Wrote a bug report to {}, check it before attaching it to an issue", path.display()),
+                Err(report_error) => eprintln!("Could not write a bug report: {}", report_error),
+            }
+        }
+
+        let (message, code) = describe_error(error.as_ref(), unix_now());
+        eprintln!("Error: {}", message);
+        std::process::exit(code);
+    }
+
+    Ok(())
+}
+
+const ENDPOINT_HEADERS: &[&str] = &["Host", "Endpoint", "Calls", "Time", "Bytes", "Cache Hits"];
+
+// On stderr, so output piped to other programs stays the same: the requests of the run with -v,
+// and what each endpoint cost with -vv
+fn print_request_summary(verbosity: u64) {
+    if verbosity == 0 {
+        return;
+    }
+
+    let requests = metrics::snapshot();
+    eprintln!(
+        "{} requests ({} from cache), {} pages in {:.1}s",
+        requests.cache_hits + requests.cache_misses,
+        requests.cache_hits,
+        requests.pages,
+        metrics::elapsed().as_secs_f64()
+    );
+    let endpoints = metrics::endpoints();
+    if verbosity < 2 || endpoints.is_empty() {
+        return;
+    }
+
+    let rows: Vec<Vec<String>> = endpoints
+        .into_iter()
+        .map(|endpoint| vec![
+            endpoint.host,
+            endpoint.endpoint,
+            endpoint.calls.to_string(),
+            format!("{}ms", endpoint.time.as_millis()),
+            format_size(endpoint.bytes),
+            endpoint.cache_hits.to_string(),
+        ])
+        .collect();
+    eprint!("{}", render_table(ENDPOINT_HEADERS, &rows));
+}
+
+// The message and exit code for an error that ended the run, with a hint for the kinds of
+// failure the user can do something about
+fn describe_error(error: &(dyn Error + 'static), now: u64) -> (String, i32) {
+    // Requests still sent through reqwest directly fail with its own error type
+    if let Some(error) = error.downcast_ref::<reqwest::Error>() {
+        let code = if error.is_decode() { 7 } else { 6 };
+        return (format!("Network error: {}", error), code);
+    }
+    let Some(error) = error.downcast_ref::<StarsError>() else {
+        return (error.to_string(), EXIT_FAILURE);
+    };
+
+    let hint = match error {
+        StarsError::Auth(_) => String::from("check that GITHUB_TOKEN is valid and has the scopes this command needs"),
+        StarsError::RateLimited { reset: Some(reset), .. } => {
+            format!(
+                "the limit resets in {}, or rerun with --wait-on-ratelimit to wait for it",
+                format_period(reset.saturating_sub(now).max(60))
+            )
+        }
+        StarsError::RateLimited { reset: None, .. } => {
+            String::from("wait a few minutes before trying again, or rerun with --wait-on-ratelimit")
+        }
+        StarsError::Network(_) => String::from("check your connection and api_url"),
+        _ => return (error.to_string(), error.exit_code()),
+    };
+    (format!("{} ({})", error, hint), error.exit_code())
+}
+
+// Write a bug report for a failed run to the current directory
+fn write_bug_report(error: &str) -> Result<PathBuf, Box<dyn Error>> {
+    let config = Config::new().ok();
+    let env_token = env::var("GITHUB_TOKEN").unwrap_or_default();
+    let config_token = config.as_ref().map(|config| config.github.token.clone()).unwrap_or_default();
+
+    let report = BugReport {
+        version: env!("CARGO_PKG_VERSION").to_string(),
+        command: env::args().skip(1).collect::<Vec<_>>().join(" "),
+        error: error.to_string(),
+        failed_request: last_failed_request(),
+        config: config.and_then(|config| config.export().ok()),
+    };
+
+    let path = env::current_dir()?.join(format!("stars_fetcher-bug-report-{}.md", unix_now()));
+    fs::write(&path, report.render(&[&env_token, &config_token]))?;
+    Ok(path)
+}
+
+// Run list, star or unstar against a forge other than GitHub
+async fn forge_command(config: &Config, app: &ArgMatches) -> Result<(), Box<dyn Error>> {
+    if config.github.token.is_empty() {
+        return Err(StarsError::Auth(String::from("A token must be set in the config file or GITHUB_TOKEN")).into());
+    }
+    let forge = AnyForge::new(config.github.forge, config.github.api_url.clone(), config.github.token.clone()).await;
+
+    match app.subcommand() {
+        Some(("list", sub_m)) => {
+            let repos: Vec<RepoDetails> = forge.list_stars().await?.into_iter().map(RepoDetails::from).collect();
+            print_repos(&repos, output_format(sub_m)?)?;
+        }
+        Some(("unstar", sub_m)) if sub_m.is_present("interactive") => {
+            return Err(format!("unstar --interactive is only available on GitHub, not on {}", forge.name()).into());
+        }
+        Some((command @ ("star" | "unstar"), sub_m)) if sub_m.is_present("from-file") || sub_m.is_present("dry-run") => {
+            let option = if sub_m.is_present("from-file") { "--from-file" } else { "--dry-run" };
+            return Err(format!("{} {} is only available on GitHub, not on {}", command, option, forge.name()).into());
+        }
+        Some((command @ ("star" | "unstar"), sub_m)) => {
+            let repo_ref = repo_ref_args(sub_m)?;
+            let (owner, repo) = (repo_ref.owner.as_str(), repo_ref.repo.as_str());
+            let starred = command == "star";
+            if starred {
+                forge.star(owner, repo).await?;
+            } else {
+                forge.unstar(owner, repo).await?;
+            }
+            print_star_result(output_format(sub_m)?, &[repo_ref], starred)?;
+        }
+        Some((command, _)) => {
+            return Err(format!("{} is only available on GitHub, not on {}", command, forge.name()).into());
+        }
+        None => show_help(),
+    }
+    Ok(())
+}
+
+// Run the command chosen on the command line
+async fn run(app: &ArgMatches) -> Result<(), Box<dyn Error>> {
+    // Config, stats, pin, snooze and archive commands work on local files only and don't need a
+    // token, except for archive --auto which reads when each repository was starred
+    match app.subcommand() {
+        Some(("config", sub_m)) => return config_command(sub_m),
+        Some(("stats", sub_m)) => return stats_command(sub_m),
+        Some(("pin", sub_m)) => return pin_command(sub_m, true),
+        Some(("unpin", sub_m)) => return pin_command(sub_m, false),
+        Some(("snooze", sub_m)) => return snooze_command(sub_m, true),
+        Some(("unsnooze", sub_m)) => return snooze_command(sub_m, false),
+        Some(("archive", sub_m)) if !sub_m.is_present("auto") => return archive_command(sub_m, true),
+        Some(("unarchive", sub_m)) => return archive_command(sub_m, false),
+        Some(("cache", sub_m)) => return cache_command(sub_m),
+        Some(("store", sub_m)) => return store_command(sub_m),
+        Some(("completions", sub_m)) => return completions_command(sub_m),
+        Some(("manpage", sub_m)) => return manpage_command(sub_m),
+        _ => {}
+    }
+
+    // Stars on GitLab or Gitea go through the forge, which only lists, stars and unstars
+    if let Some(config) = Config::new().ok().filter(|config| config.github.forge != ForgeKind::Github) {
+        return forge_command(&config, app).await;
+    }
+
+    // The token of the config file, profile or keyring; the config already falls back to
+    // GITHUB_TOKEN where it should
+    let github_token = Some(Config::new()?.github.token).filter(|token| !token.is_empty());
+
+    // Read-only commands fall back to anonymous access instead of failing
+    if github_token.is_none() {
+        let read_only = !app.is_present("interactive")
+            && app.subcommand_name().is_some_and(|name| ANONYMOUS_COMMANDS.contains(&name));
+        if !read_only {
+            return Err(StarsError::Auth(String::from("A token must be set in the config file or GITHUB_TOKEN")).into());
+        }
+        eprintln!("Warning: no token is set, using anonymous access limited to 60 requests per hour");
+    }
+
+    // The API URL comes from the config file or GITHUB_API_URL, so the CLI can run against a fixture server
+    let (api_url, backend, retry, accept_language) = Config::new()
+        .map(|config| (config.github.api_url, config.github.backend, RetryPolicy::from(&config.retry), config.github.accept_language))
+        .unwrap_or_else(|_| (String::from("https://api.github.com"), ListBackend::default(), RetryPolicy::default(), None));
+
+    let service = StarsService::new(api_url, github_token.unwrap_or_default())
+        .await
+        .with_backend(backend)
+        .with_retry(retry)
+        .with_accept_language(accept_language)
+        .with_wait_on_rate_limit(app.is_present("wait-on-ratelimit"));
+    let service = match ResponseCache::open_default() {
+        Ok(cache) if !app.is_present("no-cache") => service.with_cache(cache),
+        _ => service,
+    };
+    let github = service.client();
+    let shutdown = Shutdown::listen();
+
+    // Flag starring operations the token can't perform before doing any work
+    let star_access = if app.is_present("interactive") || matches!(app.subcommand_name(), Some("star" | "unstar" | "prune")) {
+        probe_star_access(github).await
+    } else {
+        Availability::Available
+    };
 
     // Check if --interactive flag is used
     if app.is_present("interactive") {
-        return interactive_mode(&client).await;
+        return interactive_mode(&service, &star_access).await;
+    }
+
+    match &star_access {
+        Availability::Unavailable(message) => return Err(message.clone().into()),
+        Availability::Unknown(hint) => eprintln!("Note: {}", hint),
+        Availability::Available => {}
     }
 
     match app.subcommand() {
+        Some(("get", sub_m)) if sub_m.is_present("batch") => {
+            get_batch_command(&service, &shutdown, sub_m).await?;
+        }
         Some(("get", sub_m)) => {
-            let owner = sub_m.value_of("owner").unwrap();
-            let repo = sub_m.value_of("repo").unwrap();
-            let repo = get_repo(&client, owner, repo).await?;
-            let mut table = Table::new();
-            table.add_row(row!["ID", "Name", "Full Name", "Description", "URL"]);
-            table.add_row(row![
-                repo.id,
-                repo.name,
-                repo.full_name,
-                repo.description.unwrap_or_default(),
-                repo.html_url
-            ]);
-            table.printstd();
+            let repo_ref = repo_ref_args(sub_m)?;
+            let (owner, repo) = (repo_ref.owner.as_str(), repo_ref.repo.as_str());
+            let repo = get_repo(&service, owner, repo).await?;
+            print_repos(&[repo], output_format(sub_m)?)?;
         }
-        Some(("list", _)) => {
-            let repos = list_repos(&client).await?;
-            let mut table = Table::new();
-            table.add_row(row!["ID", "Name", "Full Name", "Description", "URL"]);
-            for repo in repos {
-                table.add_row(row![
-                    repo.id,
-                    repo.name,
-                    repo.full_name,
-                    repo.description.unwrap_or_default(),
-                    repo.html_url
-                ]);
-            }
-            table.printstd();
+        Some(("list", sub_m)) => {
+            let pagination = pagination_args(sub_m)?;
+            let saved = match sub_m.value_of("filter") {
+                Some(filter) => Some(parse_filter(Config::new()?.filter(filter))?),
+                None => None,
+            };
+            let languages = sub_m.value_of("language").map(language_filter).transpose()?;
+            let topics = sub_m.values_of("topic").map(topic_filter).transpose()?;
+            let filter = [saved, languages, topics]
+                .into_iter()
+                .flatten()
+                .reduce(|left, right| Filter::And(Box::new(left), Box::new(right)));
+            let mut starred = list_starred(&service, &pagination).await?;
+            if let Some(filter) = filter {
+                starred.retain(|repo| filter.matches(repo));
+            }
+            if let Some(sort) = sub_m.value_of("sort") {
+                sort_repos(&mut starred, sort.parse()?, sub_m.is_present("desc"));
+            }
+            let mut repos: Vec<RepoDetails> = starred.into_iter().map(RepoDetails::from).collect();
+            let snoozed = load_snoozes().hide_snoozed(&mut repos, unix_now(), |repo| &repo.full_name);
+            if snoozed > 0 {
+                eprintln!("Note: {} snoozed repositories are hidden, run snooze to see them", snoozed);
+            }
+            let archived = load_archive().hide_archived(&mut repos, |repo| &repo.full_name);
+            if archived > 0 {
+                eprintln!("Note: {} archived repositories are hidden, run archive to see them", archived);
+            }
+            load_pins().sort_pinned_first(&mut repos, |repo| &repo.full_name);
+            print_repos(&repos, output_format(sub_m)?)?;
+        }
+        Some((command @ ("star" | "unstar"), sub_m)) if sub_m.is_present("from-file") => {
+            star_batch_command(&service, &shutdown, sub_m, command == "star").await?;
+        }
+        Some(("unstar", sub_m)) if sub_m.is_present("interactive") => {
+            unstar_interactive_command(&service, &shutdown, sub_m.is_present("dry-run")).await?;
+        }
+        Some((command @ ("star" | "unstar"), sub_m)) if sub_m.is_present("dry-run") => {
+            let repo_ref = repo_ref_args(sub_m)?;
+            print_star_request(&service, output_format(sub_m)?, &repo_ref.owner, &repo_ref.repo, command == "star");
         }
         Some(("star", sub_m)) => {
-            let owner = sub_m.value_of("owner").unwrap();
-            let repo = sub_m.value_of("repo").unwrap();
-            star_repo(&client, owner, repo).await?;
-            println!("Starred repository {}/{}", owner, repo);
+            let repo_ref = repo_ref_args(sub_m)?;
+            let (owner, repo) = (repo_ref.owner.as_str(), repo_ref.repo.as_str());
+            service.stars().star(owner, repo).await?;
+            print_star_result(output_format(sub_m)?, &[repo_ref], true)?;
         }
         Some(("unstar", sub_m)) => {
-            let owner = sub_m.value_of("owner").unwrap();
-            let repo = sub_m.value_of("repo").unwrap();
-            unstar_repo(&client, owner, repo).await?;
-            println!("Unstarred repository {}/{}", owner, repo);
+            let repo_ref = repo_ref_args(sub_m)?;
+            let (owner, repo) = (repo_ref.owner.as_str(), repo_ref.repo.as_str());
+            service.stars().unstar(owner, repo).await?;
+            print_star_result(output_format(sub_m)?, &[repo_ref], false)?;
+        }
+        Some(("archive", sub_m)) => {
+            auto_archive_command(github, sub_m).await?;
+        }
+        Some(("verify", sub_m)) => {
+            verify_command(github, sub_m).await?;
+        }
+        Some(("export", sub_m)) => {
+            export_graph_command(&service, sub_m).await?;
+        }
+        Some(("map", sub_m)) => {
+            map_command(&service, sub_m).await?;
+        }
+        Some(("whoami", sub_m)) => {
+            whoami_command(&service, sub_m).await?;
+        }
+        Some(("onthisday", sub_m)) => {
+            on_this_day_command(github, sub_m).await?;
+        }
+        Some(("rate-limit", sub_m)) => {
+            rate_limit_command(github, sub_m).await?;
+        }
+        Some(("ci", sub_m)) => {
+            ci_command(github, sub_m).await?;
+        }
+        Some(("my-stargazers", sub_m)) => {
+            my_stargazers_command(github, sub_m).await?;
+        }
+        Some(("fork", sub_m)) => {
+            fork_command(github, sub_m).await?;
+        }
+        Some(("fork-status", sub_m)) => {
+            fork_status_command(&service, &shutdown, sub_m).await?;
+        }
+        Some(("releases", sub_m)) => match sub_m.subcommand() {
+            Some(("download", download_m)) => releases_download_command(github, download_m).await?,
+            _ => show_help(),
+        },
+        Some(("tags", sub_m)) => {
+            refs_command(github, sub_m, RefKind::Tag).await?;
+        }
+        Some(("branches", sub_m)) => {
+            refs_command(github, sub_m, RefKind::Branch).await?;
+        }
+        Some(("changelog", sub_m)) => {
+            changelog_command(github, sub_m).await?;
+        }
+        Some(("install", sub_m)) => {
+            install_command(github, &shutdown, sub_m).await?;
+        }
+        Some(("installed", sub_m)) => match sub_m.subcommand() {
+            Some(("list", list_m)) => installed_list_command(list_m)?,
+            _ => show_help(),
+        },
+        Some(("uninstall", sub_m)) => {
+            uninstall_command(sub_m)?;
+        }
+        Some(("topics", sub_m)) => {
+            topics_command(github, sub_m).await?;
+        }
+        Some(("traffic", sub_m)) => {
+            traffic_command(github, sub_m).await?;
+        }
+        Some(("activity", sub_m)) => {
+            let repo_ref = parse_repo_spec(sub_m.value_of("repo").unwrap())?;
+            let activity = wait_for_commit_activity(
+                github, &repo_ref.owner, &repo_ref.repo, STATS_RETRIES, STATS_RETRY_DELAY,
+            ).await?;
+            print!("{}", render_heatmap(&activity));
+        }
+        Some(("owner", sub_m)) => {
+            owner_command(github, sub_m).await?;
+        }
+        Some(("similar", sub_m)) => {
+            similar_command(github, sub_m).await?;
+        }
+        Some(("search", sub_m)) => {
+            search_command(github, sub_m).await?;
+        }
+        Some(("prune", sub_m)) => {
+            prune_command(&service, &shutdown, sub_m).await?;
+        }
+        Some(("crates", sub_m)) => {
+            packages_command(&service, sub_m, &["crates"]).await?;
+        }
+        Some(("packages", sub_m)) => {
+            let names: Vec<&str> = match sub_m.subcommand() {
+                Some(("find", find_m)) => vec![find_m.value_of("registry").unwrap()],
+                _ => sub_m.values_of("registry").map_or_else(|| REGISTRY_NAMES.to_vec(), |names| names.collect()),
+            };
+            packages_command(&service, sub_m, &names).await?;
+        }
+        Some(("collection", sub_m)) => {
+            collection_command(github, &shutdown, sub_m).await?;
+        }
+        Some(("badge", sub_m)) => {
+            badge_command(github, sub_m).await?;
         }
         Some(("detail", sub_m)) => {
-            let owner = sub_m.value_of("owner").unwrap();
-            let repo = sub_m.value_of("repo").unwrap();
-            let repo = get_repo_detail(&client, owner, repo).await?;
-            let mut table = Table::new();
-            table.add_row(row!["ID", "Name", "Full Name", "Description", "URL"]);
-            table.add_row(row![
-                repo.id,
-                repo.name,
-                repo.full_name,
-                repo.description.unwrap_or_default(),
-                repo.html_url
-            ]);
-            table.printstd();
+            let repo_ref = repo_ref_args(sub_m)?;
+            let (owner, repo) = (repo_ref.owner.as_str(), repo_ref.repo.as_str());
+            let repo = get_repo(&service, owner, repo).await?;
+            let format = output_format(sub_m)?;
+            print_repos(std::slice::from_ref(&repo), format)?;
+            if format.is_table() {
+                print_owner(&repo.owner);
+                if !repo.topics.is_empty() {
+                    println!("Topics: {}", repo.topics.join(", "));
+                }
+                if repo.archived {
+                    println!("Archived: read-only, no longer maintained");
+                }
+            }
         }
         _ => {
             // No matching subcommand, show help
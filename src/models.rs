@@ -0,0 +1,243 @@
+//!
+//! Repository models shared by the library and the command line tool
+//! [`RepoSummary`] is a repository in brief, as star counts and lists of owned repositories need
+//! it. [`RepoDetails`] adds the description, web page and topics that `get`, `list` and `detail`
+//! print.
+//! Both read GitHub's repository JSON and can be built from a [`StarredRepo`] or a [`ForgeRepo`].
+//! Like the other API types they are `#[non_exhaustive]`: fields can be read from other crates,
+//! and more may be added without a breaking release.
+//!
+
+use serde::{Deserialize, Serialize};
+use crate::api::repos::Owner;
+use crate::api::stars::StarredRepo;
+use crate::forge::ForgeRepo;
+
+/// A repository's name, owner and star count
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[non_exhaustive]
+pub struct RepoSummary {
+    pub id: u64,
+    pub name: String,
+    pub full_name: String,
+    pub owner: Owner,
+    #[serde(rename = "stargazers_count")]
+    pub stars: u64,
+}
+
+/// A repository with its description, web page and topics
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[non_exhaustive]
+pub struct RepoDetails {
+    pub id: u64,
+    pub name: String,
+    pub full_name: String,
+    pub owner: Owner,
+    #[serde(rename = "stargazers_count")]
+    pub stars: u64,
+    pub description: Option<String>,
+    pub html_url: String,
+    /// Empty for repositories on forges without topics
+    #[serde(default)]
+    pub topics: Vec<String>,
+    /// Whether the owner archived it, leaving it read-only
+    #[serde(default)]
+    pub archived: bool,
+}
+
+impl From<RepoDetails> for RepoSummary {
+    fn from(repo: RepoDetails) -> Self {
+        RepoSummary { id: repo.id, name: repo.name, full_name: repo.full_name, owner: repo.owner, stars: repo.stars }
+    }
+}
+
+impl From<StarredRepo> for RepoSummary {
+    fn from(repo: StarredRepo) -> Self {
+        RepoSummary { id: repo.id, name: repo.name, full_name: repo.full_name, owner: repo.owner, stars: repo.stars }
+    }
+}
+
+impl From<StarredRepo> for RepoDetails {
+    fn from(repo: StarredRepo) -> Self {
+        RepoDetails {
+            id: repo.id,
+            name: repo.name,
+            full_name: repo.full_name,
+            owner: repo.owner,
+            stars: repo.stars,
+            description: repo.description,
+            html_url: repo.html_url,
+            topics: repo.topics,
+            archived: repo.archived,
+        }
+    }
+}
+
+impl From<ForgeRepo> for RepoDetails {
+    fn from(repo: ForgeRepo) -> Self {
+        // Forges only tell the owner apart by the first segment of the path
+        let login = repo.full_name.split('/').next().unwrap_or_default();
+        RepoDetails {
+            id: repo.id,
+            name: repo.name,
+            owner: Owner::from_login(login),
+            full_name: repo.full_name,
+            stars: repo.stars,
+            description: repo.description,
+            html_url: repo.html_url,
+            topics: Vec::new(),
+            archived: false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::repos::OwnerKind;
+    use serde_json::{json, Value};
+
+    // A repository as GitHub's REST API returns it, trimmed of URLs the models don't read
+    fn hello_world() -> Value {
+        json!({
+            "id": 1296269,
+            "node_id": "MDEwOlJlcG9zaXRvcnkxMjk2MjY5",
+            "name": "Hello-World",
+            "full_name": "octocat/Hello-World",
+            "private": false,
+            "owner": {
+                "login": "octocat",
+                "id": 1,
+                "type": "User",
+                "avatar_url": "https://github.com/images/error/octocat_happy.gif",
+                "html_url": "https://github.com/octocat"
+            },
+            "html_url": "https://github.com/octocat/Hello-World",
+            "description": "This your first repo!",
+            "fork": false,
+            "language": null,
+            "forks_count": 9,
+            "stargazers_count": 80,
+            "watchers_count": 80,
+            "topics": ["octocat", "api"],
+            "archived": false
+        })
+    }
+
+    fn octocat() -> Owner {
+        Owner {
+            login: "octocat".to_string(),
+            id: 1,
+            kind: OwnerKind::User,
+            avatar_url: Some("https://github.com/images/error/octocat_happy.gif".to_string()),
+            html_url: Some("https://github.com/octocat".to_string()),
+        }
+    }
+
+    fn details() -> RepoDetails {
+        RepoDetails {
+            id: 1296269,
+            name: "Hello-World".to_string(),
+            full_name: "octocat/Hello-World".to_string(),
+            owner: octocat(),
+            stars: 80,
+            description: Some("This your first repo!".to_string()),
+            html_url: "https://github.com/octocat/Hello-World".to_string(),
+            topics: vec!["octocat".to_string(), "api".to_string()],
+            archived: false,
+        }
+    }
+
+    #[test]
+    fn test_deserialize_repo() {
+        let summary: RepoSummary = serde_json::from_value(hello_world()).unwrap();
+        let full: RepoDetails = serde_json::from_value(hello_world()).unwrap();
+
+        assert_eq!(full, details());
+        assert_eq!(summary, RepoSummary::from(full));
+    }
+
+    #[test]
+    fn test_deserialize_repo_without_description() {
+        let mut repo = hello_world();
+        repo["description"] = Value::Null;
+        assert_eq!(serde_json::from_value::<RepoDetails>(repo.clone()).unwrap().description, None);
+
+        repo.as_object_mut().unwrap().remove("description");
+        assert_eq!(serde_json::from_value::<RepoDetails>(repo).unwrap().description, None);
+    }
+
+    #[test]
+    fn test_deserialize_repo_without_topics() {
+        let mut repo = hello_world();
+        repo.as_object_mut().unwrap().remove("topics");
+        assert!(serde_json::from_value::<RepoDetails>(repo).unwrap().topics.is_empty());
+    }
+
+    #[test]
+    fn test_deserialize_repo_missing_fields() {
+        for field in ["id", "name", "full_name", "owner", "stargazers_count"] {
+            let mut repo = hello_world();
+            repo.as_object_mut().unwrap().remove(field);
+            assert!(serde_json::from_value::<RepoSummary>(repo.clone()).is_err(), "summary without {}", field);
+            assert!(serde_json::from_value::<RepoDetails>(repo).is_err(), "details without {}", field);
+        }
+
+        let mut repo = hello_world();
+        repo.as_object_mut().unwrap().remove("html_url");
+        assert!(serde_json::from_value::<RepoSummary>(repo.clone()).is_ok());
+        assert!(serde_json::from_value::<RepoDetails>(repo).is_err());
+    }
+
+    #[test]
+    fn test_serialize_repo() {
+        let serialized = serde_json::to_value(details()).unwrap();
+        assert_eq!(serialized, json!({
+            "id": 1296269,
+            "name": "Hello-World",
+            "full_name": "octocat/Hello-World",
+            "owner": {
+                "login": "octocat",
+                "id": 1,
+                "type": "User",
+                "avatar_url": "https://github.com/images/error/octocat_happy.gif",
+                "html_url": "https://github.com/octocat"
+            },
+            "stargazers_count": 80,
+            "description": "This your first repo!",
+            "html_url": "https://github.com/octocat/Hello-World",
+            "topics": ["octocat", "api"],
+            "archived": false
+        }));
+
+        assert_eq!(serde_json::from_value::<RepoDetails>(serialized).unwrap(), details());
+        let summary = RepoSummary::from(details());
+        assert_eq!(serde_json::from_value::<RepoSummary>(serde_json::to_value(&summary).unwrap()).unwrap(), summary);
+    }
+
+    #[test]
+    fn test_from_starred_repo() {
+        let starred: StarredRepo = serde_json::from_value(hello_world()).unwrap();
+
+        assert_eq!(RepoDetails::from(starred.clone()), details());
+        assert_eq!(RepoSummary::from(starred), RepoSummary::from(details()));
+    }
+
+    #[test]
+    fn test_from_forge_repo() {
+        let repo = RepoDetails::from(ForgeRepo {
+            id: 7,
+            name: "runner".to_string(),
+            full_name: "forgejo/runner".to_string(),
+            description: None,
+            html_url: "https://codeberg.org/forgejo/runner".to_string(),
+            stars: 120,
+        });
+
+        assert_eq!(repo.owner, Owner::from_login("forgejo"));
+        assert_eq!(repo.full_name, "forgejo/runner");
+        assert_eq!(repo.stars, 120);
+        assert_eq!(repo.description, None);
+        assert!(repo.topics.is_empty());
+    }
+}
@@ -0,0 +1,165 @@
+//!
+//! crates.io lookups
+//! Crates are found by searching for the repository name and keeping those whose repository URL matches.
+//!
+
+use std::error::Error;
+use crate::registry::{http_client, published_from, Package, Registry};
+use crate::utils::parse::RepoRef;
+use reqwest::{Client, StatusCode};
+use serde::Deserialize;
+
+/// The public crates.io API
+pub const CRATES_IO_API_URL: &str = "https://crates.io/api/v1";
+
+// Search results checked per repository; the crate named after the repository is nearly always among them
+const SEARCH_SIZE: u32 = 10;
+
+#[derive(Debug, Deserialize)]
+struct CrateInfo {
+    name: String,
+    max_version: String,
+    downloads: u64,
+    repository: Option<String>,
+}
+
+impl From<CrateInfo> for Package {
+    fn from(krate: CrateInfo) -> Self {
+        Package {
+            url: format!("https://docs.rs/{}", krate.name),
+            name: krate.name,
+            version: krate.max_version,
+            downloads: Some(krate.downloads),
+            repository: krate.repository,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct CrateResponse {
+    #[serde(rename = "crate")]
+    krate: CrateInfo,
+}
+
+#[derive(Debug, Deserialize)]
+struct SearchResponse {
+    crates: Vec<CrateInfo>,
+}
+
+pub struct CratesIo {
+    client: Client,
+    api_url: String,
+}
+
+impl CratesIo {
+    pub fn new(api_url: String) -> Self {
+        Self { client: http_client(), api_url }
+    }
+}
+
+impl Registry for CratesIo {
+    fn name(&self) -> &'static str {
+        "crates"
+    }
+
+    fn languages(&self) -> &'static [&'static str] {
+        &["rust"]
+    }
+
+    async fn package(&self, name: &str) -> Result<Option<Package>, Box<dyn Error>> {
+        let url = format!("{}/crates/{}", self.api_url, name);
+        let response = self.client.get(url).send().await?;
+
+        match response.status() {
+            StatusCode::OK => Ok(Some(response.json::<CrateResponse>().await?.krate.into())),
+            StatusCode::NOT_FOUND => Ok(None),
+            status => Err(format!("Failed to look up crate {}: {}", name, status).into()),
+        }
+    }
+
+    async fn packages_for_repo(&self, repo: &RepoRef) -> Result<Vec<Package>, Box<dyn Error>> {
+        let url = format!("{}/crates", self.api_url);
+        let response = self
+            .client
+            .get(url)
+            .query(&[("q", repo.repo.as_str()), ("per_page", &SEARCH_SIZE.to_string())])
+            .send()
+            .await?;
+
+        if response.status() != StatusCode::OK {
+            return Err(format!("Failed to search crates for {}: {}", repo, response.status()).into());
+        }
+
+        let crates = response.json::<SearchResponse>().await?.crates;
+        Ok(published_from(crates.into_iter().map(Package::from).collect(), repo))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mockito::{Matcher, Server};
+    use serde_json::json;
+
+    #[tokio::test]
+    async fn test_crate_info() {
+        let mut server = Server::new_async().await;
+
+        server
+            .mock("GET", "/crates/tokio")
+            .match_header("user-agent", Matcher::Regex("^stars_fetcher/".to_string()))
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(json!({
+                "crate": {
+                    "name": "tokio",
+                    "max_version": "1.37.0",
+                    "downloads": 250000000,
+                    "repository": "https://github.com/tokio-rs/tokio"
+                }
+            }).to_string())
+            .create_async()
+            .await;
+        server
+            .mock("GET", "/crates/missing")
+            .with_status(404)
+            .create_async()
+            .await;
+
+        let crates_io = CratesIo::new(server.url());
+        let krate = crates_io.package("tokio").await.unwrap().unwrap();
+
+        assert_eq!(krate.version, "1.37.0");
+        assert_eq!(krate.downloads, Some(250000000));
+        assert_eq!(krate.source_repo(), Some(RepoRef::new("tokio-rs", "tokio").unwrap()));
+        assert_eq!(krate.url, "https://docs.rs/tokio");
+        assert!(crates_io.package("missing").await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_crates_for_repo_matches_repository_url() {
+        let mut server = Server::new_async().await;
+
+        server
+            .mock("GET", "/crates")
+            .match_query(Matcher::UrlEncoded("q".into(), "tokio".into()))
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(json!({
+                "crates": [
+                    { "name": "tokio", "max_version": "1.37.0", "downloads": 250000000, "repository": "https://github.com/tokio-rs/tokio" },
+                    { "name": "tokio-util", "max_version": "0.7.10", "downloads": 180000000, "repository": "https://github.com/Tokio-RS/tokio.git" },
+                    { "name": "tokio-fork", "max_version": "0.1.0", "downloads": 12, "repository": "https://github.com/someone/tokio" },
+                    { "name": "tokio-nothing", "max_version": "0.1.0", "downloads": 3, "repository": null }
+                ]
+            }).to_string())
+            .create_async()
+            .await;
+
+        let crates_io = CratesIo::new(server.url());
+        let crates = crates_io.packages_for_repo(&RepoRef::new("tokio-rs", "tokio").unwrap()).await.unwrap();
+
+        let names: Vec<&str> = crates.iter().map(|krate| krate.name.as_str()).collect();
+        assert_eq!(names, vec!["tokio", "tokio-util"]);
+    }
+}
@@ -0,0 +1,168 @@
+//!
+//! Package registries
+//! Starred repositories often publish packages; this module looks them up so stars can be
+//! connected to the packages built from them. Each registry implements [`Registry`], and
+//! packages are matched to repositories through the repository URL in their metadata.
+//!
+
+pub mod crates;
+pub mod npm;
+pub mod pypi;
+
+use std::error::Error;
+use crate::registry::crates::CratesIo;
+use crate::registry::npm::Npm;
+use crate::registry::pypi::PyPi;
+use crate::utils::parse::{parse_repo_spec, RepoRef};
+use reqwest::Client;
+
+// Registries reject or throttle requests without a User-Agent identifying the tool
+const USER_AGENT: &str = concat!("stars_fetcher/", env!("CARGO_PKG_VERSION"));
+
+/// Names accepted by `--registry`
+pub const REGISTRY_NAMES: &[&str] = &["crates", "npm", "pypi"];
+
+/// A published package and the repository it says it is built from
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Package {
+    pub name: String,
+    pub version: String,
+    /// Total downloads, for registries that report them
+    pub downloads: Option<u64>,
+    pub repository: Option<String>,
+    /// Documentation or package page
+    pub url: String,
+}
+
+impl Package {
+    /// The GitHub repository listed in the package metadata
+    pub fn source_repo(&self) -> Option<RepoRef> {
+        source_repo(self.repository.as_deref()?)
+    }
+}
+
+#[allow(async_fn_in_trait)]
+pub trait Registry {
+    /// Name used on the command line
+    fn name(&self) -> &'static str;
+    /// Repository languages, as reported by GitHub, whose projects publish to this registry
+    fn languages(&self) -> &'static [&'static str];
+    /// Look up a package by name, returning None when it doesn't exist
+    async fn package(&self, name: &str) -> Result<Option<Package>, Box<dyn Error>>;
+    /// Packages published from a repository
+    async fn packages_for_repo(&self, repo: &RepoRef) -> Result<Vec<Package>, Box<dyn Error>>;
+}
+
+/// Any of the supported registries, chosen by name at runtime
+pub enum AnyRegistry {
+    Crates(CratesIo),
+    Npm(Npm),
+    PyPi(PyPi),
+}
+
+impl AnyRegistry {
+    /// Create the registry called `name`, talking to `api_url` instead of the public API when given
+    pub fn new(name: &str, api_url: Option<String>) -> Result<Self, Box<dyn Error>> {
+        match name {
+            "crates" => Ok(Self::Crates(CratesIo::new(api_url.unwrap_or_else(|| crates::CRATES_IO_API_URL.to_string())))),
+            "npm" => Ok(Self::Npm(Npm::new(api_url.unwrap_or_else(|| npm::NPM_REGISTRY_URL.to_string())))),
+            "pypi" => Ok(Self::PyPi(PyPi::new(api_url.unwrap_or_else(|| pypi::PYPI_API_URL.to_string())))),
+            _ => Err(format!("Unknown registry {}, expected one of {}", name, REGISTRY_NAMES.join(", ")).into()),
+        }
+    }
+}
+
+impl Registry for AnyRegistry {
+    fn name(&self) -> &'static str {
+        match self {
+            Self::Crates(registry) => registry.name(),
+            Self::Npm(registry) => registry.name(),
+            Self::PyPi(registry) => registry.name(),
+        }
+    }
+
+    fn languages(&self) -> &'static [&'static str] {
+        match self {
+            Self::Crates(registry) => registry.languages(),
+            Self::Npm(registry) => registry.languages(),
+            Self::PyPi(registry) => registry.languages(),
+        }
+    }
+
+    async fn package(&self, name: &str) -> Result<Option<Package>, Box<dyn Error>> {
+        match self {
+            Self::Crates(registry) => registry.package(name).await,
+            Self::Npm(registry) => registry.package(name).await,
+            Self::PyPi(registry) => registry.package(name).await,
+        }
+    }
+
+    async fn packages_for_repo(&self, repo: &RepoRef) -> Result<Vec<Package>, Box<dyn Error>> {
+        match self {
+            Self::Crates(registry) => registry.packages_for_repo(repo).await,
+            Self::Npm(registry) => registry.packages_for_repo(repo).await,
+            Self::PyPi(registry) => registry.packages_for_repo(repo).await,
+        }
+    }
+}
+
+pub(crate) fn http_client() -> Client {
+    Client::builder()
+        .user_agent(USER_AGENT)
+        .build()
+        .expect("Failed to create HTTP client")
+}
+
+/// Resolve a repository URL from package metadata, which comes in many shapes such as
+/// `git+https://github.com/o/r.git`, `git://github.com/o/r` or npm's `github:o/r` shorthand.
+/// URLs pointing anywhere other than GitHub give None.
+pub fn source_repo(url: &str) -> Option<RepoRef> {
+    let url = url.trim();
+    let url = url.strip_prefix("git+").unwrap_or(url);
+    if let Some(shorthand) = url.strip_prefix("github:") {
+        return parse_repo_spec(shorthand).ok();
+    }
+
+    let rest = url.split_once("://").map_or(url, |(_, rest)| rest);
+    let rest = rest.strip_prefix("git@").unwrap_or(rest);
+    let rest = rest.strip_prefix("www.").unwrap_or(rest);
+    let path = rest
+        .strip_prefix("github.com/")
+        .or_else(|| rest.strip_prefix("github.com:"))?;
+    parse_repo_spec(&format!("github.com/{}", path)).ok()
+}
+
+/// Keep the packages whose metadata points back at `repo`
+pub(crate) fn published_from(packages: Vec<Package>, repo: &RepoRef) -> Vec<Package> {
+    let full_name = repo.full_name().to_lowercase();
+    packages
+        .into_iter()
+        .filter(|package| package.source_repo().is_some_and(|source| source.full_name().to_lowercase() == full_name))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_source_repo() {
+        let tokio = Some(RepoRef::new("tokio-rs", "tokio").unwrap());
+
+        assert_eq!(source_repo("https://github.com/tokio-rs/tokio"), tokio);
+        assert_eq!(source_repo("git+https://github.com/tokio-rs/tokio.git"), tokio);
+        assert_eq!(source_repo("git://github.com/tokio-rs/tokio.git"), tokio);
+        assert_eq!(source_repo("git+ssh://git@github.com/tokio-rs/tokio.git"), tokio);
+        assert_eq!(source_repo("git@github.com:tokio-rs/tokio.git"), tokio);
+        assert_eq!(source_repo("github:tokio-rs/tokio"), tokio);
+        assert_eq!(source_repo("https://github.com/tokio-rs/tokio/tree/master/tokio-util"), tokio);
+        assert_eq!(source_repo("https://gitlab.com/tokio-rs/tokio"), None);
+        assert_eq!(source_repo("https://tokio.rs"), None);
+    }
+
+    #[test]
+    fn test_unknown_registry() {
+        assert!(AnyRegistry::new("maven", None).is_err());
+        assert_eq!(AnyRegistry::new("pypi", None).unwrap().name(), "pypi");
+    }
+}
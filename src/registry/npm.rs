@@ -0,0 +1,194 @@
+//!
+//! npm lookups
+//! Packages are found through the registry search and kept when their repository URL matches.
+//!
+
+use std::error::Error;
+use crate::registry::{http_client, published_from, Package, Registry};
+use crate::utils::parse::RepoRef;
+use reqwest::{Client, StatusCode};
+use serde::Deserialize;
+use serde_json::Value;
+
+/// The public npm registry
+pub const NPM_REGISTRY_URL: &str = "https://registry.npmjs.org";
+
+// Search results checked per repository
+const SEARCH_SIZE: u32 = 10;
+
+#[derive(Debug, Deserialize)]
+struct PackageDocument {
+    name: String,
+    #[serde(rename = "dist-tags", default)]
+    dist_tags: DistTags,
+    #[serde(default)]
+    repository: Value,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct DistTags {
+    latest: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct SearchResponse {
+    objects: Vec<SearchObject>,
+}
+
+#[derive(Debug, Deserialize)]
+struct SearchObject {
+    package: SearchPackage,
+}
+
+#[derive(Debug, Deserialize)]
+struct SearchPackage {
+    name: String,
+    version: String,
+    #[serde(default)]
+    links: SearchLinks,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct SearchLinks {
+    repository: Option<String>,
+}
+
+fn package_url(name: &str) -> String {
+    format!("https://www.npmjs.com/package/{}", name)
+}
+
+// package.json allows the repository as a plain string or as `{ "type": "git", "url": ... }`
+fn repository_url(repository: &Value) -> Option<String> {
+    match repository {
+        Value::String(url) => Some(url.clone()),
+        Value::Object(fields) => fields.get("url")?.as_str().map(String::from),
+        _ => None,
+    }
+}
+
+pub struct Npm {
+    client: Client,
+    api_url: String,
+}
+
+impl Npm {
+    pub fn new(api_url: String) -> Self {
+        Self { client: http_client(), api_url }
+    }
+}
+
+impl Registry for Npm {
+    fn name(&self) -> &'static str {
+        "npm"
+    }
+
+    fn languages(&self) -> &'static [&'static str] {
+        &["javascript", "typescript"]
+    }
+
+    async fn package(&self, name: &str) -> Result<Option<Package>, Box<dyn Error>> {
+        // Scoped names like @scope/name keep the slash escaped in registry URLs
+        let url = format!("{}/{}", self.api_url, name.replace('/', "%2F"));
+        let response = self.client.get(url).send().await?;
+
+        let document = match response.status() {
+            StatusCode::OK => response.json::<PackageDocument>().await?,
+            StatusCode::NOT_FOUND => return Ok(None),
+            status => return Err(format!("Failed to look up npm package {}: {}", name, status).into()),
+        };
+
+        Ok(Some(Package {
+            url: package_url(&document.name),
+            version: document.dist_tags.latest.unwrap_or_default(),
+            downloads: None,
+            repository: repository_url(&document.repository),
+            name: document.name,
+        }))
+    }
+
+    async fn packages_for_repo(&self, repo: &RepoRef) -> Result<Vec<Package>, Box<dyn Error>> {
+        let url = format!("{}/-/v1/search", self.api_url);
+        let response = self
+            .client
+            .get(url)
+            .query(&[("text", repo.repo.as_str()), ("size", &SEARCH_SIZE.to_string())])
+            .send()
+            .await?;
+
+        if response.status() != StatusCode::OK {
+            return Err(format!("Failed to search npm for {}: {}", repo, response.status()).into());
+        }
+
+        let packages = response
+            .json::<SearchResponse>()
+            .await?
+            .objects
+            .into_iter()
+            .map(|object| Package {
+                url: package_url(&object.package.name),
+                name: object.package.name,
+                version: object.package.version,
+                downloads: None,
+                repository: object.package.links.repository,
+            })
+            .collect();
+        Ok(published_from(packages, repo))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mockito::{Matcher, Server};
+    use serde_json::json;
+
+    #[tokio::test]
+    async fn test_scoped_package() {
+        let mut server = Server::new_async().await;
+
+        server
+            .mock("GET", "/@vue%2Freactivity")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(json!({
+                "name": "@vue/reactivity",
+                "dist-tags": { "latest": "3.4.21" },
+                "repository": { "type": "git", "url": "git+https://github.com/vuejs/core.git" }
+            }).to_string())
+            .create_async()
+            .await;
+
+        let npm = Npm::new(server.url());
+        let package = npm.package("@vue/reactivity").await.unwrap().unwrap();
+
+        assert_eq!(package.version, "3.4.21");
+        assert_eq!(package.url, "https://www.npmjs.com/package/@vue/reactivity");
+        assert_eq!(package.source_repo(), Some(RepoRef::new("vuejs", "core").unwrap()));
+    }
+
+    #[tokio::test]
+    async fn test_packages_for_repo_matches_repository_url() {
+        let mut server = Server::new_async().await;
+
+        server
+            .mock("GET", "/-/v1/search")
+            .match_query(Matcher::UrlEncoded("text".into(), "vue".into()))
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(json!({
+                "objects": [
+                    { "package": { "name": "vue", "version": "2.7.16", "links": { "repository": "https://github.com/vuejs/vue" } } },
+                    { "package": { "name": "vue-router", "version": "4.3.0", "links": { "repository": "https://github.com/vuejs/router" } } },
+                    { "package": { "name": "vue-unlinked", "version": "0.0.1", "links": {} } }
+                ]
+            }).to_string())
+            .create_async()
+            .await;
+
+        let npm = Npm::new(server.url());
+        let packages = npm.packages_for_repo(&RepoRef::new("vuejs", "vue").unwrap()).await.unwrap();
+
+        let names: Vec<&str> = packages.iter().map(|package| package.name.as_str()).collect();
+        assert_eq!(names, vec!["vue"]);
+    }
+}
@@ -0,0 +1,131 @@
+//!
+//! PyPI lookups
+//! PyPI has no search API, so a repository's packages are found by trying its name as the package name.
+//!
+
+use std::collections::BTreeMap;
+use std::error::Error;
+use crate::registry::{http_client, published_from, source_repo, Package, Registry};
+use crate::utils::parse::RepoRef;
+use reqwest::{Client, StatusCode};
+use serde::Deserialize;
+
+/// The public PyPI JSON API
+pub const PYPI_API_URL: &str = "https://pypi.org/pypi";
+
+#[derive(Debug, Deserialize)]
+struct ProjectResponse {
+    info: ProjectInfo,
+}
+
+#[derive(Debug, Deserialize)]
+struct ProjectInfo {
+    name: String,
+    version: String,
+    home_page: Option<String>,
+    project_urls: Option<BTreeMap<String, String>>,
+}
+
+impl ProjectInfo {
+    // Projects list their repository under free-form labels, so take any URL pointing at GitHub
+    fn repository(&self) -> Option<String> {
+        self.project_urls
+            .iter()
+            .flat_map(|urls| urls.values())
+            .chain(self.home_page.as_ref())
+            .find(|url| source_repo(url).is_some())
+            .cloned()
+    }
+}
+
+pub struct PyPi {
+    client: Client,
+    api_url: String,
+}
+
+impl PyPi {
+    pub fn new(api_url: String) -> Self {
+        Self { client: http_client(), api_url }
+    }
+}
+
+impl Registry for PyPi {
+    fn name(&self) -> &'static str {
+        "pypi"
+    }
+
+    fn languages(&self) -> &'static [&'static str] {
+        &["python"]
+    }
+
+    async fn package(&self, name: &str) -> Result<Option<Package>, Box<dyn Error>> {
+        let url = format!("{}/{}/json", self.api_url, name);
+        let response = self.client.get(url).send().await?;
+
+        let info = match response.status() {
+            StatusCode::OK => response.json::<ProjectResponse>().await?.info,
+            StatusCode::NOT_FOUND => return Ok(None),
+            status => return Err(format!("Failed to look up PyPI project {}: {}", name, status).into()),
+        };
+
+        Ok(Some(Package {
+            url: format!("https://pypi.org/project/{}/", info.name),
+            repository: info.repository(),
+            downloads: None,
+            name: info.name,
+            version: info.version,
+        }))
+    }
+
+    async fn packages_for_repo(&self, repo: &RepoRef) -> Result<Vec<Package>, Box<dyn Error>> {
+        let packages = self.package(&repo.repo).await?.into_iter().collect();
+        Ok(published_from(packages, repo))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mockito::Server;
+    use serde_json::json;
+
+    #[tokio::test]
+    async fn test_packages_for_repo() {
+        let mut server = Server::new_async().await;
+
+        server
+            .mock("GET", "/requests/json")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(json!({
+                "info": {
+                    "name": "requests",
+                    "version": "2.31.0",
+                    "home_page": "https://requests.readthedocs.io",
+                    "project_urls": {
+                        "Documentation": "https://requests.readthedocs.io",
+                        "Source": "https://github.com/psf/requests"
+                    }
+                }
+            }).to_string())
+            .create_async()
+            .await;
+        server
+            .mock("GET", "/flask/json")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(json!({
+                "info": { "name": "flask", "version": "3.0.2", "home_page": null, "project_urls": null }
+            }).to_string())
+            .create_async()
+            .await;
+
+        let pypi = PyPi::new(server.url());
+        let packages = pypi.packages_for_repo(&RepoRef::new("psf", "requests").unwrap()).await.unwrap();
+
+        assert_eq!(packages.len(), 1);
+        assert_eq!(packages[0].version, "2.31.0");
+        assert_eq!(packages[0].url, "https://pypi.org/project/requests/");
+        assert!(pypi.packages_for_repo(&RepoRef::new("pallets", "flask").unwrap()).await.unwrap().is_empty());
+    }
+}
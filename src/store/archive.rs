@@ -0,0 +1,132 @@
+//!
+//! Locally archived stars
+//! Stars that are kept on GitHub but no longer wanted in everyday listings. Stars can be archived
+//! one at a time or by the age policy in the `[archive]` config, which picks stars older than
+//! `auto_archive_after` that were never used locally.
+//!
+
+use std::error::Error;
+use crate::store::Store;
+use serde::{Deserialize, Serialize};
+
+const ARCHIVE_ENTRY: &str = "archive";
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct ArchivedStar {
+    /// Full name as `owner/repo`
+    pub repo: String,
+    /// When the repository was archived, in seconds since the Unix epoch
+    pub archived_at: u64,
+}
+
+#[derive(Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+pub struct LocalArchive {
+    pub repos: Vec<ArchivedStar>,
+    /// Whether the age policy's dry-run report was shown; the policy only archives after that
+    #[serde(default)]
+    pub policy_reviewed: bool,
+}
+
+impl LocalArchive {
+    pub fn load(store: &Store) -> Result<Self, Box<dyn Error>> {
+        Ok(store.load(ARCHIVE_ENTRY)?.unwrap_or_default())
+    }
+
+    pub fn save(&self, store: &Store) -> Result<(), Box<dyn Error>> {
+        store.save(ARCHIVE_ENTRY, self)
+    }
+
+    /// Archive a repository, returning false if it was already archived
+    pub fn archive(&mut self, full_name: &str, now: u64) -> bool {
+        if self.is_archived(full_name) {
+            return false;
+        }
+        self.repos.push(ArchivedStar { repo: full_name.to_string(), archived_at: now });
+        true
+    }
+
+    /// Bring a repository back to listings, returning false if it wasn't archived
+    pub fn unarchive(&mut self, full_name: &str) -> bool {
+        let before = self.repos.len();
+        self.repos.retain(|archived| !archived.repo.eq_ignore_ascii_case(full_name));
+        self.repos.len() != before
+    }
+
+    /// Like GitHub names, this ignores case
+    pub fn is_archived(&self, full_name: &str) -> bool {
+        self.repos.iter().any(|archived| archived.repo.eq_ignore_ascii_case(full_name))
+    }
+
+    /// Remove archived items, returning how many were hidden
+    pub fn hide_archived<T>(&self, items: &mut Vec<T>, full_name: impl Fn(&T) -> &str) -> usize {
+        let before = items.len();
+        items.retain(|item| !self.is_archived(full_name(item)));
+        before - items.len()
+    }
+}
+
+/// Stars the age policy would archive at `now`: starred more than `max_age` seconds ago, not
+/// archived yet and not `used` locally. `stars` pairs full names with when they were starred.
+pub fn due_for_archive<'a>(
+    archive: &LocalArchive,
+    stars: &'a [(String, u64)],
+    max_age: u64,
+    now: u64,
+    used: impl Fn(&str) -> bool,
+) -> Vec<&'a str> {
+    stars
+        .iter()
+        .filter(|(_, starred_at)| now.saturating_sub(*starred_at) > max_age)
+        .map(|(full_name, _)| full_name.as_str())
+        .filter(|full_name| !archive.is_archived(full_name) && !used(full_name))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_archive_and_unarchive() {
+        let mut archive = LocalArchive::default();
+
+        assert!(archive.archive("vuejs/vue", 100));
+        assert!(!archive.archive("VueJS/Vue", 200));
+        assert_eq!(archive.repos, vec![ArchivedStar { repo: "vuejs/vue".to_string(), archived_at: 100 }]);
+
+        let mut repos = vec!["rust-lang/rust", "vuejs/VUE"];
+        assert_eq!(archive.hide_archived(&mut repos, |repo| *repo), 1);
+        assert_eq!(repos, vec!["rust-lang/rust"]);
+
+        assert!(archive.unarchive("vuejs/vue"));
+        assert!(!archive.unarchive("vuejs/vue"));
+    }
+
+    #[test]
+    fn test_due_for_archive() {
+        let mut archive = LocalArchive::default();
+        archive.archive("old/archived", 0);
+        let stars = vec![
+            ("new/star".to_string(), 900),
+            ("old/star".to_string(), 100),
+            ("old/pinned".to_string(), 100),
+            ("old/archived".to_string(), 100),
+        ];
+
+        let due = due_for_archive(&archive, &stars, 500, 1000, |repo| repo == "old/pinned");
+        assert_eq!(due, vec!["old/star"]);
+    }
+
+    #[test]
+    fn test_save_and_load() {
+        let dir = tempdir().unwrap();
+        let store = Store::open(dir.path());
+
+        let mut archive = LocalArchive { policy_reviewed: true, ..LocalArchive::default() };
+        archive.archive("tokio-rs/tokio", 1_700_000_000);
+        archive.save(&store).unwrap();
+
+        assert_eq!(LocalArchive::load(&store).unwrap(), archive);
+    }
+}
@@ -0,0 +1,142 @@
+//!
+//! The local store as tables
+//! Every entry is flattened into rows with fixed columns, one table per kind of entry, so the
+//! whole dataset can be written out as CSV or JSON and queried with DuckDB, pandas or a spreadsheet.
+//!
+
+use std::error::Error;
+use crate::store::archive::LocalArchive;
+use crate::store::history::{CommandHistory, HistoryCommand};
+use crate::store::pins::Pins;
+use crate::store::snoozes::Snoozes;
+use crate::store::stargazers::StargazerSnapshot;
+use crate::store::traffic::TrafficHistory;
+use crate::store::Store;
+
+/// One kind of entry as rows of cells
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Table {
+    /// File name of the table, without extension
+    pub name: &'static str,
+    pub headers: &'static [&'static str],
+    pub rows: Vec<Vec<String>>,
+}
+
+/// Every table of the store, including empty ones so a dump always has the same files
+pub fn tables(store: &Store) -> Result<Vec<Table>, Box<dyn Error>> {
+    let pins = Pins::load(store)?;
+    let snoozes = Snoozes::load(store)?;
+    let archive = LocalArchive::load(store)?;
+    let stargazers = StargazerSnapshot::load(store)?;
+    let history = CommandHistory::load(store)?;
+    let traffic = TrafficHistory::load_all(store)?;
+
+    Ok(vec![
+        Table {
+            name: "pins",
+            headers: &["repo", "position"],
+            rows: pins
+                .repos
+                .iter()
+                .enumerate()
+                .map(|(position, repo)| vec![repo.clone(), (position + 1).to_string()])
+                .collect(),
+        },
+        Table {
+            name: "snoozes",
+            headers: &["repo", "until"],
+            rows: snoozes
+                .snoozes
+                .iter()
+                .map(|snooze| vec![snooze.repo.clone(), snooze.until.to_string()])
+                .collect(),
+        },
+        Table {
+            name: "archive",
+            headers: &["repo", "archived_at"],
+            rows: archive
+                .repos
+                .iter()
+                .map(|archived| vec![archived.repo.clone(), archived.archived_at.to_string()])
+                .collect(),
+        },
+        Table {
+            name: "stargazers",
+            headers: &["repo", "login"],
+            rows: stargazers
+                .repos
+                .iter()
+                .flat_map(|(repo, logins)| logins.iter().map(move |login| vec![repo.clone(), login.clone()]))
+                .collect(),
+        },
+        Table {
+            name: "history",
+            headers: &["position", "command", "repo"],
+            rows: history
+                .commands
+                .iter()
+                .enumerate()
+                .map(|(position, command)| history_row(position + 1, command))
+                .collect(),
+        },
+        Table {
+            name: "traffic",
+            headers: &["repo", "date", "views", "unique_views", "clones", "unique_clones"],
+            rows: traffic
+                .iter()
+                .flat_map(|(repo, history)| {
+                    history.days.iter().map(move |(date, day)| vec![
+                        repo.clone(),
+                        date.clone(),
+                        day.views.to_string(),
+                        day.unique_views.to_string(),
+                        day.clones.to_string(),
+                        day.unique_clones.to_string(),
+                    ])
+                })
+                .collect(),
+        },
+    ])
+}
+
+// Position 1 is the most recent command
+fn history_row(position: usize, command: &HistoryCommand) -> Vec<String> {
+    let (name, repo) = match command {
+        HistoryCommand::ListStarred => ("list_starred", String::new()),
+        HistoryCommand::Detail { owner, repo } => ("detail", format!("{}/{}", owner, repo)),
+        HistoryCommand::Star { owner, repo } => ("star", format!("{}/{}", owner, repo)),
+        HistoryCommand::Unstar { owner, repo } => ("unstar", format!("{}/{}", owner, repo)),
+    };
+    vec![position.to_string(), name.to_string(), repo]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_tables() {
+        let dir = tempdir().unwrap();
+        let store = Store::open(dir.path());
+        let mut pins = Pins::default();
+        pins.pin("rust-lang/rust");
+        pins.save(&store).unwrap();
+        let mut history = CommandHistory::default();
+        history.record(HistoryCommand::ListStarred);
+        history.record(HistoryCommand::Star { owner: "tokio-rs".to_string(), repo: "tokio".to_string() });
+        history.save(&store).unwrap();
+
+        let tables = tables(&store).unwrap();
+        let table = |name: &str| tables.iter().find(|table| table.name == name).unwrap();
+
+        assert_eq!(
+            tables.iter().map(|table| table.name).collect::<Vec<_>>(),
+            ["pins", "snoozes", "archive", "stargazers", "history", "traffic"]
+        );
+        assert_eq!(table("pins").rows, vec![vec!["rust-lang/rust", "1"]]);
+        assert_eq!(table("history").rows, vec![vec!["1", "star", "tokio-rs/tokio"], vec!["2", "list_starred", ""]]);
+        assert!(table("snoozes").rows.is_empty());
+        assert!(tables.iter().all(|table| table.rows.iter().all(|row| row.len() == table.headers.len())));
+    }
+}
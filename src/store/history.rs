@@ -0,0 +1,99 @@
+//!
+//! Commands run in interactive mode
+//! The most recent commands are kept so they can be re-run from the history menu.
+//!
+
+use std::error::Error;
+use std::fmt::{self, Display};
+use crate::store::Store;
+use serde::{Deserialize, Serialize};
+
+const HISTORY_ENTRY: &str = "history";
+const MAX_HISTORY: usize = 20;
+
+/// A command with everything needed to run it again without prompting
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(tag = "command", rename_all = "snake_case")]
+pub enum HistoryCommand {
+    ListStarred,
+    Detail { owner: String, repo: String },
+    Star { owner: String, repo: String },
+    Unstar { owner: String, repo: String },
+}
+
+impl Display for HistoryCommand {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            HistoryCommand::ListStarred => write!(f, "List starred repositories"),
+            HistoryCommand::Detail { owner, repo } => write!(f, "Get details of {}/{}", owner, repo),
+            HistoryCommand::Star { owner, repo } => write!(f, "Star {}/{}", owner, repo),
+            HistoryCommand::Unstar { owner, repo } => write!(f, "Unstar {}/{}", owner, repo),
+        }
+    }
+}
+
+#[derive(Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+pub struct CommandHistory {
+    /// Most recent first, without duplicates
+    pub commands: Vec<HistoryCommand>,
+}
+
+impl CommandHistory {
+    pub fn load(store: &Store) -> Result<Self, Box<dyn Error>> {
+        Ok(store.load(HISTORY_ENTRY)?.unwrap_or_default())
+    }
+
+    pub fn save(&self, store: &Store) -> Result<(), Box<dyn Error>> {
+        store.save(HISTORY_ENTRY, self)
+    }
+
+    /// Move a command to the top of the history, dropping the oldest beyond the limit
+    pub fn record(&mut self, command: HistoryCommand) {
+        self.commands.retain(|existing| *existing != command);
+        self.commands.insert(0, command);
+        self.commands.truncate(MAX_HISTORY);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    fn detail(repo: &str) -> HistoryCommand {
+        HistoryCommand::Detail { owner: "octocat".to_string(), repo: repo.to_string() }
+    }
+
+    #[test]
+    fn test_record_moves_repeats_to_the_top() {
+        let mut history = CommandHistory::default();
+        history.record(detail("Hello-World"));
+        history.record(HistoryCommand::ListStarred);
+        history.record(detail("Hello-World"));
+
+        assert_eq!(history.commands, vec![detail("Hello-World"), HistoryCommand::ListStarred]);
+    }
+
+    #[test]
+    fn test_record_keeps_the_most_recent() {
+        let mut history = CommandHistory::default();
+        for i in 0..MAX_HISTORY + 5 {
+            history.record(detail(&format!("repo{}", i)));
+        }
+
+        assert_eq!(history.commands.len(), MAX_HISTORY);
+        assert_eq!(history.commands[0], detail(&format!("repo{}", MAX_HISTORY + 4)));
+    }
+
+    #[test]
+    fn test_load_and_save() {
+        let dir = tempdir().unwrap();
+        let store = Store::open(dir.path());
+        let mut history = CommandHistory::load(&store).unwrap();
+        history.record(HistoryCommand::Star { owner: "tokio-rs".to_string(), repo: "tokio".to_string() });
+        history.save(&store).unwrap();
+
+        assert_eq!(CommandHistory::load(&store).unwrap(), history);
+        assert_eq!(history.commands[0].to_string(), "Star tokio-rs/tokio");
+    }
+}
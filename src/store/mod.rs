@@ -0,0 +1,316 @@
+//!
+//! Local store for data kept between runs
+//! Each entry is a named JSON document under the data directory, e.g. `~/.local/share/stars_fetcher`.
+//! Documents carry the schema version they were written with. Older ones are upgraded when read,
+//! and copied to `backups/` before they are first rewritten, so `store rollback` can restore them.
+//!
+
+pub mod archive;
+pub mod dump;
+pub mod history;
+pub mod pins;
+pub mod receipts;
+pub mod snoozes;
+pub mod stargazers;
+pub mod traffic;
+pub mod usage;
+pub mod verify;
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::{json, Value};
+use std::error::Error;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+/// Layout version written into every document
+pub const SCHEMA_VERSION: u64 = 1;
+
+// Upgrades of a stored value, MIGRATIONS[n] turning version n into n + 1. Version 0 is the bare
+// value written before documents carried a version, which needs no change beyond the envelope.
+const MIGRATIONS: [fn(Value) -> Value; SCHEMA_VERSION as usize] = [|value| value];
+
+// Directory under the root holding documents as they were before a migration rewrote them
+const BACKUP_DIR: &str = "backups";
+
+/// An entry as found on disk
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EntryInfo {
+    pub name: String,
+    /// Size in bytes
+    pub size: u64,
+    /// When the entry was last written
+    pub modified: SystemTime,
+}
+
+pub struct Store {
+    root: PathBuf,
+}
+
+impl Store {
+    /// Open the store in the user's data directory
+    pub fn open_default() -> Result<Self, Box<dyn Error>> {
+        let root = dirs::data_dir()
+            .ok_or("Unable to find data directory")?
+            .join("stars_fetcher");
+
+        Ok(Self::open(root))
+    }
+
+    /// Open a store rooted at the given directory, which is created on first write
+    pub fn open(root: impl Into<PathBuf>) -> Self {
+        Self { root: root.into() }
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    fn path(&self, name: &str) -> PathBuf {
+        self.root.join(format!("{}.json", name))
+    }
+
+    fn backup_path(&self, name: &str, version: u64) -> PathBuf {
+        self.root.join(BACKUP_DIR).join(format!("{}.v{}.json", name, version))
+    }
+
+    // Read a document as its schema version and value, None when it was never written
+    fn read(&self, name: &str) -> Result<Option<(u64, Value)>, Box<dyn Error>> {
+        let path = self.path(name);
+        if !path.exists() {
+            return Ok(None);
+        }
+
+        let document: Value = serde_json::from_str(&fs::read_to_string(path)?)?;
+        let version = document.get("schema_version").and_then(Value::as_u64);
+        let (version, value) = match (version, document) {
+            (Some(version), Value::Object(mut fields)) if fields.len() == 2 && fields.contains_key("data") => {
+                (version, fields.remove("data").unwrap_or_default())
+            }
+            (_, document) => (0, document),
+        };
+        if version > SCHEMA_VERSION {
+            return Err(format!(
+                "Store entry {} has schema version {}, newer than the {} this version of stars_fetcher reads",
+                name, version, SCHEMA_VERSION
+            )
+            .into());
+        }
+
+        Ok(Some((version, value)))
+    }
+
+    /// Read an entry, returning None when it was never written
+    pub fn load<T: DeserializeOwned>(&self, name: &str) -> Result<Option<T>, Box<dyn Error>> {
+        let Some((version, value)) = self.read(name)? else {
+            return Ok(None);
+        };
+
+        let value = MIGRATIONS[version as usize..].iter().fold(value, |value, migrate| migrate(value));
+        Ok(Some(serde_json::from_value(value)?))
+    }
+
+    /// Write an entry, replacing any previous value. A document of an older schema version is
+    /// backed up first.
+    pub fn save<T: Serialize>(&self, name: &str, value: &T) -> Result<(), Box<dyn Error>> {
+        fs::create_dir_all(&self.root)?;
+
+        let path = self.path(name);
+        if let Some((version, _)) = self.read(name)?.filter(|(version, _)| *version < SCHEMA_VERSION) {
+            // Keep the first backup of each version, which holds the entry as that version left it
+            let backup_path = self.backup_path(name, version);
+            if !backup_path.exists() {
+                fs::create_dir_all(self.root.join(BACKUP_DIR))?;
+                fs::copy(&path, backup_path)?;
+            }
+        }
+
+        // Write to a temporary file first so an interrupted write never leaves a corrupt entry
+        let document = json!({ "schema_version": SCHEMA_VERSION, "data": value });
+        let tmp_path = path.with_extension("json.tmp");
+        fs::write(&tmp_path, serde_json::to_string_pretty(&document)?)?;
+        fs::rename(tmp_path, path)?;
+
+        Ok(())
+    }
+
+    /// Put back every entry backed up before a migration rewrote it, returning the names
+    /// restored. An entry backed up under several versions gets the oldest.
+    pub fn rollback(&self) -> Result<Vec<String>, Box<dyn Error>> {
+        let backup_dir = self.root.join(BACKUP_DIR);
+        if !backup_dir.exists() {
+            return Ok(Vec::new());
+        }
+
+        let mut backups = Vec::new();
+        for entry in fs::read_dir(&backup_dir)? {
+            let file_name = entry?.file_name();
+            let Some((name, version)) = file_name
+                .to_str()
+                .and_then(|name| name.strip_suffix(".json"))
+                .and_then(|name| name.rsplit_once(".v"))
+                .and_then(|(name, version)| Some((name.to_string(), version.parse::<u64>().ok()?)))
+            else {
+                continue;
+            };
+            backups.push((name, version));
+        }
+        backups.sort();
+        backups.dedup_by(|later, first| later.0 == first.0);
+
+        for (name, version) in &backups {
+            fs::rename(self.backup_path(name, *version), self.path(name))?;
+        }
+        fs::remove_dir_all(backup_dir)?;
+
+        Ok(backups.into_iter().map(|(name, _)| name).collect())
+    }
+
+    /// All entries, by name
+    pub fn entries(&self) -> Result<Vec<EntryInfo>, Box<dyn Error>> {
+        if !self.root.exists() {
+            return Ok(Vec::new());
+        }
+
+        let mut entries = Vec::new();
+        for entry in fs::read_dir(&self.root)? {
+            let entry = entry?;
+            let metadata = entry.metadata()?;
+            // Temporary files of interrupted writes end in .json.tmp and are skipped
+            let file_name = entry.file_name();
+            let Some(name) = file_name.to_str().and_then(|name| name.strip_suffix(".json")) else {
+                continue;
+            };
+            if metadata.is_file() {
+                entries.push(EntryInfo { name: name.to_string(), size: metadata.len(), modified: metadata.modified()? });
+            }
+        }
+        entries.sort_by(|a, b| a.name.cmp(&b.name));
+
+        Ok(entries)
+    }
+
+    /// Delete an entry if it exists
+    pub fn remove(&self, name: &str) -> Result<(), Box<dyn Error>> {
+        let path = self.path(name);
+        if path.exists() {
+            fs::remove_file(path)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde::Deserialize;
+    use tempfile::tempdir;
+
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct Entry {
+        name: String,
+        count: u32,
+    }
+
+    #[test]
+    fn test_save_and_load() {
+        let dir = tempdir().unwrap();
+        let store = Store::open(dir.path().join("store"));
+        let entry = Entry { name: "stars".to_string(), count: 3 };
+
+        store.save("entry", &entry).unwrap();
+        assert_eq!(store.load::<Entry>("entry").unwrap(), Some(entry));
+    }
+
+    #[test]
+    fn test_load_missing_entry() {
+        let dir = tempdir().unwrap();
+        let store = Store::open(dir.path());
+
+        assert_eq!(store.load::<Entry>("missing").unwrap(), None);
+    }
+
+    #[test]
+    fn test_entries() {
+        let dir = tempdir().unwrap();
+        let store = Store::open(dir.path().join("store"));
+        assert!(store.entries().unwrap().is_empty());
+
+        store.save("b", &Entry { name: "stars".to_string(), count: 1 }).unwrap();
+        store.save("a", &Entry { name: "stars".to_string(), count: 2 }).unwrap();
+        fs::write(dir.path().join("store").join("c.json.tmp"), "{").unwrap();
+
+        let entries = store.entries().unwrap();
+        assert_eq!(entries.iter().map(|entry| entry.name.as_str()).collect::<Vec<_>>(), ["a", "b"]);
+        assert_eq!(entries[0].size, fs::metadata(dir.path().join("store").join("a.json")).unwrap().len());
+    }
+
+    #[test]
+    fn test_save_writes_schema_version() {
+        let dir = tempdir().unwrap();
+        let store = Store::open(dir.path());
+
+        store.save("entry", &Entry { name: "stars".to_string(), count: 1 }).unwrap();
+
+        let document: Value = serde_json::from_str(&fs::read_to_string(dir.path().join("entry.json")).unwrap()).unwrap();
+        assert_eq!(document, json!({ "schema_version": SCHEMA_VERSION, "data": { "name": "stars", "count": 1 } }));
+    }
+
+    #[test]
+    fn test_load_unversioned_entry() {
+        let dir = tempdir().unwrap();
+        let store = Store::open(dir.path());
+        fs::write(dir.path().join("entry.json"), r#"{"name": "stars", "count": 2}"#).unwrap();
+
+        assert_eq!(store.load::<Entry>("entry").unwrap(), Some(Entry { name: "stars".to_string(), count: 2 }));
+        assert!(!dir.path().join(BACKUP_DIR).exists());
+    }
+
+    #[test]
+    fn test_load_rejects_newer_schema() {
+        let dir = tempdir().unwrap();
+        let store = Store::open(dir.path());
+        let document = json!({ "schema_version": SCHEMA_VERSION + 1, "data": { "name": "stars", "count": 2 } });
+        fs::write(dir.path().join("entry.json"), document.to_string()).unwrap();
+
+        let error = store.load::<Entry>("entry").unwrap_err();
+        assert!(error.to_string().contains("newer than"));
+        assert!(store.save("entry", &Entry { name: "stars".to_string(), count: 3 }).is_err());
+        assert_eq!(fs::read_to_string(dir.path().join("entry.json")).unwrap(), document.to_string());
+    }
+
+    #[test]
+    fn test_save_backs_up_older_schema_and_rolls_back() {
+        let dir = tempdir().unwrap();
+        let store = Store::open(dir.path());
+        let original = r#"{"name": "stars", "count": 2}"#;
+        fs::write(dir.path().join("entry.json"), original).unwrap();
+        fs::write(dir.path().join("other.json"), "[]").unwrap();
+
+        store.save("entry", &Entry { name: "stars".to_string(), count: 3 }).unwrap();
+        store.save("entry", &Entry { name: "stars".to_string(), count: 4 }).unwrap();
+        store.save("new", &Entry { name: "stars".to_string(), count: 1 }).unwrap();
+        assert_eq!(fs::read_to_string(dir.path().join(BACKUP_DIR).join("entry.v0.json")).unwrap(), original);
+        assert_eq!(fs::read_dir(dir.path().join(BACKUP_DIR)).unwrap().count(), 1);
+        assert_eq!(store.entries().unwrap().len(), 3);
+
+        assert_eq!(store.rollback().unwrap(), ["entry"]);
+        assert_eq!(fs::read_to_string(dir.path().join("entry.json")).unwrap(), original);
+        assert_eq!(fs::read_to_string(dir.path().join("other.json")).unwrap(), "[]");
+        assert!(!dir.path().join(BACKUP_DIR).exists());
+        assert!(store.rollback().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_remove() {
+        let dir = tempdir().unwrap();
+        let store = Store::open(dir.path());
+
+        store.save("entry", &Entry { name: "stars".to_string(), count: 1 }).unwrap();
+        store.remove("entry").unwrap();
+        store.remove("entry").unwrap();
+        assert_eq!(store.load::<Entry>("entry").unwrap(), None);
+    }
+}
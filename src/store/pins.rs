@@ -0,0 +1,97 @@
+//!
+//! Pinned repositories
+//! A handful of favorite repositories that are listed before all other stars, in the order they
+//! were pinned. Pins are kept locally and never change anything on GitHub.
+//!
+
+use std::error::Error;
+use crate::store::Store;
+use serde::{Deserialize, Serialize};
+
+const PINS_ENTRY: &str = "pins";
+
+#[derive(Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+pub struct Pins {
+    /// Full names as `owner/repo`, in the order they were pinned
+    pub repos: Vec<String>,
+}
+
+impl Pins {
+    pub fn load(store: &Store) -> Result<Self, Box<dyn Error>> {
+        Ok(store.load(PINS_ENTRY)?.unwrap_or_default())
+    }
+
+    pub fn save(&self, store: &Store) -> Result<(), Box<dyn Error>> {
+        store.save(PINS_ENTRY, self)
+    }
+
+    /// Pin a repository, returning false if it was already pinned
+    pub fn pin(&mut self, full_name: &str) -> bool {
+        if self.is_pinned(full_name) {
+            return false;
+        }
+        self.repos.push(full_name.to_string());
+        true
+    }
+
+    /// Unpin a repository, returning false if it wasn't pinned
+    pub fn unpin(&mut self, full_name: &str) -> bool {
+        let before = self.repos.len();
+        self.repos.retain(|pinned| !pinned.eq_ignore_ascii_case(full_name));
+        self.repos.len() != before
+    }
+
+    /// GitHub names are case-insensitive, and so are pins
+    pub fn is_pinned(&self, full_name: &str) -> bool {
+        self.rank(full_name).is_some()
+    }
+
+    // Position among the pins
+    fn rank(&self, full_name: &str) -> Option<usize> {
+        self.repos.iter().position(|pinned| pinned.eq_ignore_ascii_case(full_name))
+    }
+
+    /// Move pinned items to the front in pin order, keeping the order of everything else
+    pub fn sort_pinned_first<T>(&self, items: &mut [T], full_name: impl Fn(&T) -> &str) {
+        items.sort_by_key(|item| self.rank(full_name(item)).unwrap_or(usize::MAX));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_pin_and_unpin() {
+        let mut pins = Pins::default();
+
+        assert!(pins.pin("rust-lang/rust"));
+        assert!(!pins.pin("Rust-Lang/Rust"));
+        assert!(pins.is_pinned("RUST-LANG/rust"));
+        assert!(pins.unpin("rust-lang/RUST"));
+        assert!(!pins.unpin("rust-lang/rust"));
+        assert!(pins.repos.is_empty());
+    }
+
+    #[test]
+    fn test_sort_pinned_first() {
+        let pins = Pins { repos: vec!["vuejs/vue".to_string(), "octocat/Hello-World".to_string()] };
+        let mut repos = vec!["a/one", "octocat/hello-world", "b/two", "vuejs/vue", "c/three"];
+        pins.sort_pinned_first(&mut repos, |repo| *repo);
+
+        assert_eq!(repos, vec!["vuejs/vue", "octocat/hello-world", "a/one", "b/two", "c/three"]);
+    }
+
+    #[test]
+    fn test_save_and_load() {
+        let dir = tempdir().unwrap();
+        let store = Store::open(dir.path());
+
+        let mut pins = Pins::default();
+        pins.pin("tokio-rs/tokio");
+        pins.save(&store).unwrap();
+
+        assert_eq!(Pins::load(&store).unwrap(), pins);
+    }
+}
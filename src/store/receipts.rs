@@ -0,0 +1,89 @@
+//!
+//! Receipts for binaries installed from releases
+//! Each install records where the binary came from, so it can be upgraded later.
+//!
+
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::path::PathBuf;
+use crate::store::Store;
+use serde::{Deserialize, Serialize};
+
+const RECEIPTS_ENTRY: &str = "receipts";
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct Receipt {
+    /// Repository the release belongs to, as `owner/repo`
+    pub repo: String,
+    pub tag: String,
+    pub asset: String,
+    /// Where the binary was placed
+    pub path: PathBuf,
+    pub sha256: String,
+}
+
+#[derive(Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+pub struct Receipts {
+    /// Receipts keyed by binary name
+    pub installed: BTreeMap<String, Receipt>,
+}
+
+impl Receipts {
+    pub fn load(store: &Store) -> Result<Self, Box<dyn Error>> {
+        Ok(store.load(RECEIPTS_ENTRY)?.unwrap_or_default())
+    }
+
+    pub fn save(&self, store: &Store) -> Result<(), Box<dyn Error>> {
+        store.save(RECEIPTS_ENTRY, self)
+    }
+
+    /// Record an install, replacing the receipt of an earlier version
+    pub fn record(&mut self, name: &str, receipt: Receipt) {
+        self.installed.insert(name.to_string(), receipt);
+    }
+
+    /// Forget an install, returning its receipt
+    pub fn remove(&mut self, name: &str) -> Option<Receipt> {
+        self.installed.remove(name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    fn receipt(tag: &str) -> Receipt {
+        Receipt {
+            repo: "BurntSushi/ripgrep".to_string(),
+            tag: tag.to_string(),
+            asset: format!("ripgrep-{}-x86_64-unknown-linux-musl.tar.gz", tag),
+            path: PathBuf::from("/home/me/.local/bin/rg"),
+            sha256: "0".repeat(64),
+        }
+    }
+
+    #[test]
+    fn test_record_replaces_older_version() {
+        let dir = tempdir().unwrap();
+        let store = Store::open(dir.path());
+
+        let mut receipts = Receipts::default();
+        receipts.record("rg", receipt("14.0.0"));
+        receipts.record("rg", receipt("14.1.0"));
+        receipts.save(&store).unwrap();
+
+        let loaded = Receipts::load(&store).unwrap();
+        assert_eq!(loaded.installed.len(), 1);
+        assert_eq!(loaded.installed["rg"].tag, "14.1.0");
+    }
+
+    #[test]
+    fn test_remove() {
+        let mut receipts = Receipts::default();
+        receipts.record("rg", receipt("14.1.0"));
+
+        assert_eq!(receipts.remove("rg").map(|receipt| receipt.tag), Some("14.1.0".to_string()));
+        assert!(receipts.remove("rg").is_none());
+    }
+}
@@ -0,0 +1,111 @@
+//!
+//! Snoozed repositories
+//! Stars deliberately ignored for a while are hidden from listings until their snooze runs out.
+//! Snoozes are kept locally; the repositories stay starred on GitHub.
+//!
+
+use std::error::Error;
+use crate::store::Store;
+use serde::{Deserialize, Serialize};
+
+const SNOOZES_ENTRY: &str = "snoozes";
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct Snooze {
+    /// Full name as `owner/repo`
+    pub repo: String,
+    /// When the repository shows up again, in seconds since the Unix epoch
+    pub until: u64,
+}
+
+#[derive(Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+pub struct Snoozes {
+    pub snoozes: Vec<Snooze>,
+}
+
+impl Snoozes {
+    pub fn load(store: &Store) -> Result<Self, Box<dyn Error>> {
+        Ok(store.load(SNOOZES_ENTRY)?.unwrap_or_default())
+    }
+
+    pub fn save(&self, store: &Store) -> Result<(), Box<dyn Error>> {
+        store.save(SNOOZES_ENTRY, self)
+    }
+
+    /// Hide a repository until `until`, replacing an earlier snooze of it
+    pub fn snooze(&mut self, full_name: &str, until: u64) {
+        self.wake(full_name);
+        self.snoozes.push(Snooze { repo: full_name.to_string(), until });
+    }
+
+    /// End a repository's snooze early, returning false if it wasn't snoozed
+    pub fn wake(&mut self, full_name: &str) -> bool {
+        let before = self.snoozes.len();
+        self.snoozes.retain(|snooze| !snooze.repo.eq_ignore_ascii_case(full_name));
+        self.snoozes.len() != before
+    }
+
+    /// Forget snoozes that ran out by `now`
+    pub fn expire(&mut self, now: u64) {
+        self.snoozes.retain(|snooze| snooze.until > now);
+    }
+
+    /// Whether a repository is hidden at `now`; like GitHub names, this ignores case
+    pub fn is_snoozed(&self, full_name: &str, now: u64) -> bool {
+        self.snoozes
+            .iter()
+            .any(|snooze| snooze.until > now && snooze.repo.eq_ignore_ascii_case(full_name))
+    }
+
+    /// Remove the items snoozed at `now`, returning how many were hidden
+    pub fn hide_snoozed<T>(&self, items: &mut Vec<T>, now: u64, full_name: impl Fn(&T) -> &str) -> usize {
+        let before = items.len();
+        items.retain(|item| !self.is_snoozed(full_name(item), now));
+        before - items.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_snooze_and_wake() {
+        let mut snoozes = Snoozes::default();
+        snoozes.snooze("vuejs/vue", 100);
+        snoozes.snooze("VueJS/Vue", 200);
+
+        assert_eq!(snoozes.snoozes, vec![Snooze { repo: "VueJS/Vue".to_string(), until: 200 }]);
+        assert!(snoozes.is_snoozed("vuejs/vue", 150));
+        assert!(!snoozes.is_snoozed("vuejs/vue", 200));
+        assert!(snoozes.wake("vuejs/VUE"));
+        assert!(!snoozes.wake("vuejs/vue"));
+    }
+
+    #[test]
+    fn test_hide_snoozed_and_expire() {
+        let mut snoozes = Snoozes::default();
+        snoozes.snooze("vuejs/vue", 100);
+        snoozes.snooze("rust-lang/rust", 300);
+
+        let mut repos = vec!["octocat/Hello-World", "rust-lang/rust", "vuejs/vue"];
+        assert_eq!(snoozes.hide_snoozed(&mut repos, 200, |repo| *repo), 1);
+        assert_eq!(repos, vec!["octocat/Hello-World", "vuejs/vue"]);
+
+        snoozes.expire(200);
+        assert_eq!(snoozes.snoozes, vec![Snooze { repo: "rust-lang/rust".to_string(), until: 300 }]);
+    }
+
+    #[test]
+    fn test_save_and_load() {
+        let dir = tempdir().unwrap();
+        let store = Store::open(dir.path());
+
+        let mut snoozes = Snoozes::default();
+        snoozes.snooze("tokio-rs/tokio", 1_700_000_000);
+        snoozes.save(&store).unwrap();
+
+        assert_eq!(Snoozes::load(&store).unwrap(), snoozes);
+    }
+}
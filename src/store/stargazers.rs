@@ -0,0 +1,78 @@
+//!
+//! Stargazers seen on previous runs
+//! Every login that ever starred one of my repositories is remembered, so a run can tell which
+//! stargazers are new since the last one.
+//!
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::error::Error;
+use crate::api::stargazers::Stargazer;
+use crate::store::Store;
+use serde::{Deserialize, Serialize};
+
+const STARGAZERS_ENTRY: &str = "stargazers";
+
+#[derive(Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+pub struct StargazerSnapshot {
+    /// Logins seen per repository, keyed by `owner/repo`
+    pub repos: BTreeMap<String, BTreeSet<String>>,
+}
+
+impl StargazerSnapshot {
+    pub fn load(store: &Store) -> Result<Self, Box<dyn Error>> {
+        Ok(store.load(STARGAZERS_ENTRY)?.unwrap_or_default())
+    }
+
+    pub fn save(&self, store: &Store) -> Result<(), Box<dyn Error>> {
+        store.save(STARGAZERS_ENTRY, self)
+    }
+
+    /// Record the current stargazers of a repository, returning the logins not seen before.
+    /// The first run for a repository only records a baseline, so nothing is new.
+    pub fn record(&mut self, full_name: &str, stargazers: &[Stargazer]) -> BTreeSet<String> {
+        let first_run = !self.repos.contains_key(full_name);
+        let seen = self.repos.entry(full_name.to_string()).or_default();
+
+        let mut new = BTreeSet::new();
+        for stargazer in stargazers {
+            if seen.insert(stargazer.user.login.clone()) && !first_run {
+                new.insert(stargazer.user.login.clone());
+            }
+        }
+        new
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::stargazers::StargazerUser;
+    use tempfile::tempdir;
+
+    fn stargazer(login: &str) -> Stargazer {
+        Stargazer { starred_at: "2024-05-01T10:00:00Z".to_string(), user: StargazerUser { login: login.to_string() } }
+    }
+
+    #[test]
+    fn test_record() {
+        let mut snapshot = StargazerSnapshot::default();
+
+        assert!(snapshot.record("octocat/Hello-World", &[stargazer("hubot")]).is_empty());
+
+        let new = snapshot.record("octocat/Hello-World", &[stargazer("monalisa"), stargazer("hubot")]);
+        assert_eq!(new, BTreeSet::from(["monalisa".to_string()]));
+
+        assert!(snapshot.record("octocat/Hello-World", &[stargazer("monalisa")]).is_empty());
+    }
+
+    #[test]
+    fn test_load_and_save() {
+        let dir = tempdir().unwrap();
+        let store = Store::open(dir.path());
+        let mut snapshot = StargazerSnapshot::load(&store).unwrap();
+        snapshot.record("octocat/Hello-World", &[stargazer("hubot")]);
+        snapshot.save(&store).unwrap();
+
+        assert_eq!(StargazerSnapshot::load(&store).unwrap(), snapshot);
+    }
+}
@@ -0,0 +1,168 @@
+//!
+//! Traffic history kept beyond GitHub's 14-day window
+//! Each fetch is merged into a per-repository entry, one row per day.
+//!
+
+use std::collections::BTreeMap;
+use std::error::Error;
+use crate::api::traffic::DailyCount;
+use crate::store::Store;
+use serde::{Deserialize, Serialize};
+
+/// Views and clones on one day
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, Serialize)]
+pub struct TrafficDay {
+    pub views: u64,
+    pub unique_views: u64,
+    pub clones: u64,
+    pub unique_clones: u64,
+}
+
+#[derive(Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+pub struct TrafficHistory {
+    /// Days keyed by date, e.g. `2024-05-01`
+    pub days: BTreeMap<String, TrafficDay>,
+}
+
+const ENTRY_PREFIX: &str = "traffic_";
+
+// Between owner and repository in entry names, a character no forge allows in either
+const SEPARATOR: char = '+';
+
+fn entry_name(owner: &str, repo: &str) -> String {
+    format!("{}{}{}{}", ENTRY_PREFIX, owner, SEPARATOR, repo)
+}
+
+fn date(timestamp: &str) -> String {
+    timestamp.get(..10).unwrap_or(timestamp).to_string()
+}
+
+impl TrafficHistory {
+    pub fn load(store: &Store, owner: &str, repo: &str) -> Result<Self, Box<dyn Error>> {
+        Ok(store.load(&entry_name(owner, repo))?.unwrap_or_default())
+    }
+
+    pub fn save(&self, store: &Store, owner: &str, repo: &str) -> Result<(), Box<dyn Error>> {
+        store.save(&entry_name(owner, repo), self)
+    }
+
+    /// Every recorded history keyed by `owner/repo`
+    pub fn load_all(store: &Store) -> Result<BTreeMap<String, Self>, Box<dyn Error>> {
+        let mut histories = BTreeMap::new();
+        for entry in store.entries()? {
+            let Some((owner, repo)) = entry.name.strip_prefix(ENTRY_PREFIX).and_then(|name| name.split_once(SEPARATOR)) else {
+                continue;
+            };
+            if let Some(history) = store.load(&entry.name)? {
+                histories.insert(format!("{}/{}", owner, repo), history);
+            }
+        }
+
+        Ok(histories)
+    }
+
+    /// Merge a fetch into the history; the latest fetch wins because today's counts are still growing
+    pub fn merge(&mut self, views: &[DailyCount], clones: &[DailyCount]) {
+        for view in views {
+            let day = self.days.entry(date(&view.timestamp)).or_default();
+            day.views = view.count;
+            day.unique_views = view.uniques;
+        }
+        for clone in clones {
+            let day = self.days.entry(date(&clone.timestamp)).or_default();
+            day.clones = clone.count;
+            day.unique_clones = clone.uniques;
+        }
+    }
+
+    /// The whole history as CSV, oldest day first
+    pub fn to_csv(&self) -> String {
+        let mut csv = String::from("date,views,unique_views,clones,unique_clones\n");
+        for (date, day) in &self.days {
+            csv.push_str(&format!(
+                "{},{},{},{},{}\n",
+                date, day.views, day.unique_views, day.clones, day.unique_clones
+            ));
+        }
+        csv
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    fn count(timestamp: &str, count: u64, uniques: u64) -> DailyCount {
+        DailyCount { timestamp: timestamp.to_string(), count, uniques }
+    }
+
+    #[test]
+    fn test_merge_keeps_days_beyond_the_window() {
+        let mut history = TrafficHistory::default();
+        history.merge(&[count("2024-05-01T00:00:00Z", 10, 2), count("2024-05-02T00:00:00Z", 3, 1)], &[]);
+        history.merge(
+            &[count("2024-05-02T00:00:00Z", 7, 4), count("2024-05-16T00:00:00Z", 1, 1)],
+            &[count("2024-05-16T00:00:00Z", 2, 1)],
+        );
+
+        assert_eq!(history.days.len(), 3);
+        assert_eq!(history.days["2024-05-01"].views, 10);
+        assert_eq!(history.days["2024-05-02"], TrafficDay { views: 7, unique_views: 4, clones: 0, unique_clones: 0 });
+        assert_eq!(history.days["2024-05-16"].clones, 2);
+    }
+
+    #[test]
+    fn test_to_csv() {
+        let mut history = TrafficHistory::default();
+        history.merge(&[count("2024-05-02T00:00:00Z", 7, 4)], &[count("2024-05-01T00:00:00Z", 2, 1)]);
+
+        assert_eq!(
+            history.to_csv(),
+            "date,views,unique_views,clones,unique_clones\n2024-05-01,0,0,2,1\n2024-05-02,7,4,0,0\n"
+        );
+    }
+
+    #[test]
+    fn test_load_and_save() {
+        let dir = tempdir().unwrap();
+        let store = Store::open(dir.path());
+        let mut history = TrafficHistory::load(&store, "octocat", "Hello-World").unwrap();
+        history.merge(&[count("2024-05-01T00:00:00Z", 10, 2)], &[]);
+        history.save(&store, "octocat", "Hello-World").unwrap();
+
+        assert_eq!(TrafficHistory::load(&store, "octocat", "Hello-World").unwrap(), history);
+        assert!(dir.path().join("traffic_octocat+Hello-World.json").exists());
+    }
+
+    #[test]
+    fn test_names_with_underscores_stay_apart() {
+        let dir = tempdir().unwrap();
+        let store = Store::open(dir.path());
+        let mut first = TrafficHistory::default();
+        first.merge(&[count("2024-05-01T00:00:00Z", 10, 2)], &[]);
+        first.save(&store, "my_org", "repo").unwrap();
+        let mut second = TrafficHistory::default();
+        second.merge(&[count("2024-05-01T00:00:00Z", 3, 1)], &[]);
+        second.save(&store, "my", "org_repo").unwrap();
+
+        assert_eq!(TrafficHistory::load(&store, "my_org", "repo").unwrap(), first);
+        assert_eq!(TrafficHistory::load(&store, "my", "org_repo").unwrap(), second);
+        let histories = TrafficHistory::load_all(&store).unwrap();
+        assert_eq!(histories.keys().collect::<Vec<_>>(), ["my/org_repo", "my_org/repo"]);
+    }
+
+    #[test]
+    fn test_load_all() {
+        let dir = tempdir().unwrap();
+        let store = Store::open(dir.path());
+        let mut history = TrafficHistory::default();
+        history.merge(&[count("2024-05-01T00:00:00Z", 10, 2)], &[]);
+        history.save(&store, "octocat", "my_repo").unwrap();
+        store.save("pins", &serde_json::json!({ "repos": [] })).unwrap();
+
+        let histories = TrafficHistory::load_all(&store).unwrap();
+        assert_eq!(histories.keys().collect::<Vec<_>>(), ["octocat/my_repo"]);
+        assert_eq!(histories["octocat/my_repo"], history);
+    }
+}
@@ -0,0 +1,135 @@
+//!
+//! Local usage statistics
+//! How often each command runs and how long it takes, so users can see which features they use.
+//! The numbers stay in the local store and are never sent anywhere.
+//!
+
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::time::Duration;
+use crate::store::Store;
+use serde::{Deserialize, Serialize};
+
+const USAGE_ENTRY: &str = "usage";
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, Serialize)]
+pub struct CommandUsage {
+    pub runs: u64,
+    pub failures: u64,
+    /// Time spent over all runs, in milliseconds
+    pub total_ms: u64,
+    /// Longest run, in milliseconds
+    pub longest_ms: u64,
+}
+
+impl CommandUsage {
+    pub fn average_ms(&self) -> u64 {
+        self.total_ms.checked_div(self.runs).unwrap_or_default()
+    }
+}
+
+#[derive(Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+pub struct Usage {
+    /// Usage keyed by command, e.g. `list` or `releases download`
+    pub commands: BTreeMap<String, CommandUsage>,
+    /// API responses served from the response cache over all runs
+    #[serde(default)]
+    pub cache_hits: u64,
+    /// API responses fetched in full from GitHub over all runs
+    #[serde(default)]
+    pub cache_misses: u64,
+}
+
+impl Usage {
+    pub fn load(store: &Store) -> Result<Self, Box<dyn Error>> {
+        Ok(store.load(USAGE_ENTRY)?.unwrap_or_default())
+    }
+
+    pub fn save(&self, store: &Store) -> Result<(), Box<dyn Error>> {
+        store.save(USAGE_ENTRY, self)
+    }
+
+    /// Forget all recorded usage
+    pub fn reset(store: &Store) -> Result<(), Box<dyn Error>> {
+        store.remove(USAGE_ENTRY)
+    }
+
+    /// Count one run of a command
+    pub fn record(&mut self, command: &str, duration: Duration, succeeded: bool) {
+        let usage = self.commands.entry(command.to_string()).or_default();
+        let ms = u64::try_from(duration.as_millis()).unwrap_or(u64::MAX);
+
+        usage.runs += 1;
+        if !succeeded {
+            usage.failures += 1;
+        }
+        usage.total_ms = usage.total_ms.saturating_add(ms);
+        usage.longest_ms = usage.longest_ms.max(ms);
+    }
+
+    /// Count the API responses of one run
+    pub fn record_responses(&mut self, cache_hits: u64, cache_misses: u64) {
+        self.cache_hits = self.cache_hits.saturating_add(cache_hits);
+        self.cache_misses = self.cache_misses.saturating_add(cache_misses);
+    }
+
+    /// Share of API responses served from the response cache, None before any response
+    pub fn cache_hit_rate(&self) -> Option<f64> {
+        let total = self.cache_hits + self.cache_misses;
+        (total > 0).then(|| self.cache_hits as f64 / total as f64)
+    }
+
+    /// Commands with their usage, most used first
+    pub fn by_runs(&self) -> Vec<(&str, &CommandUsage)> {
+        let mut commands: Vec<(&str, &CommandUsage)> = self
+            .commands
+            .iter()
+            .map(|(command, usage)| (command.as_str(), usage))
+            .collect();
+        commands.sort_by(|(a_name, a), (b_name, b)| b.runs.cmp(&a.runs).then(a_name.cmp(b_name)));
+        commands
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_record() {
+        let mut usage = Usage::default();
+        usage.record("list", Duration::from_millis(300), true);
+        usage.record("list", Duration::from_millis(100), false);
+        usage.record("star", Duration::from_millis(50), true);
+
+        assert_eq!(usage.commands["list"], CommandUsage { runs: 2, failures: 1, total_ms: 400, longest_ms: 300 });
+        assert_eq!(usage.commands["list"].average_ms(), 200);
+        assert_eq!(usage.by_runs().iter().map(|(command, _)| *command).collect::<Vec<_>>(), vec!["list", "star"]);
+    }
+
+    #[test]
+    fn test_record_responses() {
+        let mut usage = Usage::default();
+        assert_eq!(usage.cache_hit_rate(), None);
+
+        usage.record_responses(1, 3);
+        usage.record_responses(2, 2);
+        assert_eq!((usage.cache_hits, usage.cache_misses), (3, 5));
+        assert_eq!(usage.cache_hit_rate(), Some(0.375));
+    }
+
+    #[test]
+    fn test_save_and_reset() {
+        let dir = tempdir().unwrap();
+        let store = Store::open(dir.path());
+
+        let mut usage = Usage::default();
+        usage.record("releases download", Duration::from_secs(2), true);
+        usage.save(&store).unwrap();
+        assert_eq!(Usage::load(&store).unwrap(), usage);
+
+        Usage::reset(&store).unwrap();
+        assert_eq!(Usage::load(&store).unwrap(), Usage::default());
+    }
+}
@@ -0,0 +1,169 @@
+//!
+//! Reconciling the local store with GitHub
+//! Pins, snoozes and archived stars name repositories that were starred when the entry was made.
+//! Unstarring elsewhere, or starring an archived repository again, leaves entries that no longer
+//! match GitHub; these are found against one listing of my stars and can be fixed in place.
+//!
+
+use std::collections::HashMap;
+use std::fmt;
+use crate::store::archive::LocalArchive;
+use crate::store::pins::Pins;
+use crate::store::snoozes::Snoozes;
+
+/// A local entry that doesn't match GitHub
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Discrepancy {
+    /// A pinned repository that is no longer starred
+    StalePin(String),
+    /// A snoozed repository that is no longer starred
+    StaleSnooze(String),
+    /// An archived repository that is no longer starred
+    StaleArchive(String),
+    /// An archived repository that was starred again on GitHub after it was archived
+    Restarred(String),
+}
+
+impl Discrepancy {
+    pub fn repo(&self) -> &str {
+        match self {
+            Discrepancy::StalePin(repo)
+            | Discrepancy::StaleSnooze(repo)
+            | Discrepancy::StaleArchive(repo)
+            | Discrepancy::Restarred(repo) => repo,
+        }
+    }
+
+    /// What fixing the discrepancy does
+    pub fn fix(&self) -> &'static str {
+        match self {
+            Discrepancy::StalePin(_) => "unpin",
+            Discrepancy::StaleSnooze(_) => "wake",
+            Discrepancy::StaleArchive(_) => "remove from the archive",
+            Discrepancy::Restarred(_) => "unarchive",
+        }
+    }
+}
+
+impl fmt::Display for Discrepancy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Discrepancy::StalePin(repo) => write!(f, "{} is pinned but no longer starred", repo),
+            Discrepancy::StaleSnooze(repo) => write!(f, "{} is snoozed but no longer starred", repo),
+            Discrepancy::StaleArchive(repo) => write!(f, "{} is archived but no longer starred", repo),
+            Discrepancy::Restarred(repo) => write!(f, "{} is archived but was starred again since", repo),
+        }
+    }
+}
+
+/// Entries of the local store that don't match `stars`, which pairs the full names of all my
+/// starred repositories with when they were starred. Like GitHub names, matching ignores case.
+pub fn find_discrepancies(pins: &Pins, snoozes: &Snoozes, archive: &LocalArchive, stars: &[(String, u64)]) -> Vec<Discrepancy> {
+    let starred_at: HashMap<String, u64> = stars
+        .iter()
+        .map(|(full_name, starred_at)| (full_name.to_lowercase(), *starred_at))
+        .collect();
+    let starred = |repo: &str| starred_at.get(&repo.to_lowercase()).copied();
+
+    let pins = pins
+        .repos
+        .iter()
+        .filter(|repo| starred(repo).is_none())
+        .map(|repo| Discrepancy::StalePin(repo.clone()));
+    let snoozes = snoozes
+        .snoozes
+        .iter()
+        .filter(|snooze| starred(&snooze.repo).is_none())
+        .map(|snooze| Discrepancy::StaleSnooze(snooze.repo.clone()));
+    let archived = archive.repos.iter().filter_map(|archived| match starred(&archived.repo) {
+        None => Some(Discrepancy::StaleArchive(archived.repo.clone())),
+        Some(at) if at > archived.archived_at => Some(Discrepancy::Restarred(archived.repo.clone())),
+        Some(_) => None,
+    });
+
+    pins.chain(snoozes).chain(archived).collect()
+}
+
+/// Fix `discrepancies` in the local stores
+pub fn fix_discrepancies(discrepancies: &[Discrepancy], pins: &mut Pins, snoozes: &mut Snoozes, archive: &mut LocalArchive) {
+    for discrepancy in discrepancies {
+        match discrepancy {
+            Discrepancy::StalePin(repo) => {
+                pins.unpin(repo);
+            }
+            Discrepancy::StaleSnooze(repo) => {
+                snoozes.wake(repo);
+            }
+            Discrepancy::StaleArchive(repo) | Discrepancy::Restarred(repo) => {
+                archive.unarchive(repo);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stores() -> (Pins, Snoozes, LocalArchive) {
+        let mut pins = Pins::default();
+        pins.pin("rust-lang/rust");
+        pins.pin("gone/pinned");
+
+        let mut snoozes = Snoozes::default();
+        snoozes.snooze("Tokio-RS/Tokio", 5000);
+        snoozes.snooze("gone/snoozed", 5000);
+
+        let mut archive = LocalArchive::default();
+        archive.archive("old/archived", 500);
+        archive.archive("old/restarred", 500);
+        archive.archive("gone/archived", 500);
+
+        (pins, snoozes, archive)
+    }
+
+    fn stars() -> Vec<(String, u64)> {
+        vec![
+            ("rust-lang/rust".to_string(), 100),
+            ("tokio-rs/tokio".to_string(), 100),
+            ("old/archived".to_string(), 100),
+            ("old/restarred".to_string(), 900),
+        ]
+    }
+
+    #[test]
+    fn test_find_discrepancies() {
+        let (pins, snoozes, archive) = stores();
+
+        assert_eq!(
+            find_discrepancies(&pins, &snoozes, &archive, &stars()),
+            vec![
+                Discrepancy::StalePin("gone/pinned".to_string()),
+                Discrepancy::StaleSnooze("gone/snoozed".to_string()),
+                Discrepancy::Restarred("old/restarred".to_string()),
+                Discrepancy::StaleArchive("gone/archived".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_fix_discrepancies() {
+        let (mut pins, mut snoozes, mut archive) = stores();
+        let discrepancies = find_discrepancies(&pins, &snoozes, &archive, &stars());
+
+        fix_discrepancies(&discrepancies, &mut pins, &mut snoozes, &mut archive);
+
+        assert_eq!(pins.repos, vec!["rust-lang/rust"]);
+        assert!(snoozes.is_snoozed("tokio-rs/tokio", 0) && !snoozes.is_snoozed("gone/snoozed", 0));
+        assert_eq!(archive.repos.iter().map(|archived| archived.repo.as_str()).collect::<Vec<_>>(), ["old/archived"]);
+        assert!(find_discrepancies(&pins, &snoozes, &archive, &stars()).is_empty());
+    }
+
+    #[test]
+    fn test_messages() {
+        let discrepancy = Discrepancy::Restarred("old/restarred".to_string());
+        assert_eq!(discrepancy.to_string(), "old/restarred is archived but was starred again since");
+        assert_eq!(discrepancy.fix(), "unarchive");
+        assert_eq!(discrepancy.repo(), "old/restarred");
+    }
+}
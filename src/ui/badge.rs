@@ -0,0 +1,138 @@
+use serde_json::json;
+
+const LABEL_COLOR: &str = "#555";
+const MESSAGE_COLOR: &str = "#007ec6";
+
+/// Format a count the way shields.io does, e.g. 2789 as 2.8k
+pub fn format_count(count: u64) -> String {
+    const UNITS: [(u64, &str); 4] = [(1, ""), (1_000, "k"), (1_000_000, "M"), (1_000_000_000, "G")];
+
+    // The largest unit the count reaches, or the next one up when rounding would show 1000 of it
+    let mut unit = UNITS.iter().rposition(|(size, _)| count >= *size).unwrap_or(0);
+    if unit > 0 && unit + 1 < UNITS.len() && (count as f64 / UNITS[unit].0 as f64).round() >= 1000.0 {
+        unit += 1;
+    }
+    if unit == 0 {
+        return count.to_string();
+    }
+
+    let (size, suffix) = UNITS[unit];
+    let value = count as f64 / size as f64;
+    if value < 10.0 {
+        let formatted = format!("{:.1}", value);
+        format!("{}{}", formatted.trim_end_matches(".0"), suffix)
+    } else {
+        format!("{}{}", value.round() as u64, suffix)
+    }
+}
+
+// Rough width of Verdana 11px text, which is what badge renderers assume
+fn text_width(text: &str) -> usize {
+    text.chars().count() * 7 + 10
+}
+
+fn escape_xml(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
+
+/// Render a flat badge as a standalone SVG image
+pub fn render_svg(label: &str, message: &str) -> String {
+    let label_width = text_width(label);
+    let message_width = text_width(message);
+    let width = label_width + message_width;
+    let label = escape_xml(label);
+    let message = escape_xml(message);
+
+    format!(
+        r##"<svg xmlns="http://www.w3.org/2000/svg" width="{width}" height="20" role="img" aria-label="{label}: {message}">
+  <title>{label}: {message}</title>
+  <linearGradient id="s" x2="0" y2="100%">
+    <stop offset="0" stop-color="#bbb" stop-opacity=".1"/>
+    <stop offset="1" stop-opacity=".1"/>
+  </linearGradient>
+  <clipPath id="r">
+    <rect width="{width}" height="20" rx="3" fill="#fff"/>
+  </clipPath>
+  <g clip-path="url(#r)">
+    <rect width="{label_width}" height="20" fill="{LABEL_COLOR}"/>
+    <rect x="{label_width}" width="{message_width}" height="20" fill="{MESSAGE_COLOR}"/>
+    <rect width="{width}" height="20" fill="url(#s)"/>
+  </g>
+  <g fill="#fff" text-anchor="middle" font-family="Verdana,Geneva,DejaVu Sans,sans-serif" font-size="11">
+    <text x="{label_x}" y="14">{label}</text>
+    <text x="{message_x}" y="14">{message}</text>
+  </g>
+</svg>
+"##,
+        label_x = label_width / 2,
+        message_x = label_width + message_width / 2,
+    )
+}
+
+/// Render a badge as a shields.io endpoint document
+pub fn render_shields_json(label: &str, message: &str) -> String {
+    let badge = json!({
+        "schemaVersion": 1,
+        "label": label,
+        "message": message,
+        "color": MESSAGE_COLOR.trim_start_matches('#'),
+    });
+
+    serde_json::to_string_pretty(&badge).unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_count() {
+        assert_eq!(format_count(0), "0");
+        assert_eq!(format_count(999), "999");
+        assert_eq!(format_count(1000), "1k");
+        assert_eq!(format_count(2789), "2.8k");
+        assert_eq!(format_count(97412), "97k");
+        assert_eq!(format_count(207019), "207k");
+        assert_eq!(format_count(1_260_000), "1.3M");
+        assert_eq!(format_count(9_960), "10k");
+        assert_eq!(format_count(u64::MAX), "18446744074G");
+    }
+
+    #[test]
+    fn test_format_count_promotes_rounded_thousands() {
+        assert_eq!(format_count(999_499), "999k");
+        assert_eq!(format_count(999_500), "1M");
+        assert_eq!(format_count(999_600), "1M");
+        assert_eq!(format_count(999_999), "1M");
+        assert_eq!(format_count(999_499_999), "999M");
+        assert_eq!(format_count(999_600_000), "1G");
+        assert_eq!(format_count(999_999_999), "1G");
+        assert_eq!(format_count(999_600_000_000), "1000G");
+    }
+
+    #[test]
+    fn test_render_svg() {
+        insta::assert_snapshot!(render_svg("starred", "1.2k"));
+    }
+
+    #[test]
+    fn test_render_svg_escapes_text() {
+        let svg = render_svg("<stars & co>", "\"42\"");
+        assert!(svg.contains("&lt;stars &amp; co&gt;"));
+        assert!(svg.contains("&quot;42&quot;"));
+        assert!(!svg.contains("<stars"));
+    }
+
+    #[test]
+    fn test_render_shields_json() {
+        let badge: serde_json::Value = serde_json::from_str(&render_shields_json("stars", "97k")).unwrap();
+        assert_eq!(badge["schemaVersion"], 1);
+        assert_eq!(badge["label"], "stars");
+        assert_eq!(badge["message"], "97k");
+        assert_eq!(badge["color"], "007ec6");
+    }
+}
@@ -0,0 +1,322 @@
+//!
+//! Full-screen browser for starred repositories
+//! A scrollable list on the left, the selected repository's details on the right and a status bar
+//! with the rate limit at the bottom. The browser only keeps state and draws frames; the caller
+//! reads keys and carries out the actions they map to.
+//!
+
+use console::{style, measure_text_width, truncate_str, Key};
+use crate::api::stars::StarredRepo;
+use crate::ui::terminal::fit;
+use crate::api::rate_limit::RateLimit;
+
+/// Keys shown in the status bar
+const KEY_HELP: &str = "↑↓ move  s star  u unstar  p pin  c clone  o open  h history  q quit";
+
+// Columns between the list and the detail pane
+const SEPARATOR: &str = " │ ";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BrowserRepo {
+    pub owner: String,
+    pub name: String,
+    pub description: Option<String>,
+    pub language: Option<String>,
+    pub stars: u64,
+    pub forks: u64,
+    pub topics: Vec<String>,
+    pub html_url: String,
+    /// Unstarred repositories stay listed so they can be starred again
+    pub starred: bool,
+    pub pinned: bool,
+}
+
+impl From<StarredRepo> for BrowserRepo {
+    fn from(repo: StarredRepo) -> Self {
+        BrowserRepo {
+            owner: repo.owner.login,
+            name: repo.name,
+            description: repo.description,
+            language: repo.language,
+            stars: repo.stars,
+            forks: repo.forks,
+            topics: repo.topics,
+            html_url: repo.html_url,
+            starred: true,
+            pinned: false,
+        }
+    }
+}
+
+impl BrowserRepo {
+
+    pub fn full_name(&self) -> String {
+        format!("{}/{}", self.owner, self.name)
+    }
+}
+
+/// What a key asks the caller to do with the selected repository
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BrowserAction {
+    Star,
+    Unstar,
+    /// Pin the repository, or unpin it if it is pinned
+    TogglePin,
+    Clone,
+    Open,
+    History,
+    Quit,
+}
+
+pub struct Browser {
+    repos: Vec<BrowserRepo>,
+    selected: usize,
+    /// Index of the first repository in view
+    offset: usize,
+    status: String,
+}
+
+impl Browser {
+    pub fn new(repos: Vec<BrowserRepo>) -> Self {
+        let status = format!("{} starred repositories", repos.len());
+        Browser { repos, selected: 0, offset: 0, status }
+    }
+
+    pub fn selected(&self) -> Option<&BrowserRepo> {
+        self.repos.get(self.selected)
+    }
+
+    pub fn selected_mut(&mut self) -> Option<&mut BrowserRepo> {
+        self.repos.get_mut(self.selected)
+    }
+
+    /// Replace the message at the start of the status bar
+    pub fn set_status(&mut self, status: impl Into<String>) {
+        self.status = status.into();
+    }
+
+    /// Rows of repositories a frame `height` lines tall has room for, below the title and above
+    /// the status bar
+    pub fn list_height(height: usize) -> usize {
+        height.saturating_sub(2)
+    }
+
+    /// Move the selection, or return the action a key stands for. `page` is how many repositories
+    /// Page Up and Page Down skip.
+    pub fn handle_key(&mut self, key: &Key, page: usize) -> Option<BrowserAction> {
+        let last = self.repos.len().saturating_sub(1);
+        match key {
+            Key::ArrowUp | Key::Char('k') => self.selected = self.selected.saturating_sub(1),
+            Key::ArrowDown | Key::Char('j') => self.selected = (self.selected + 1).min(last),
+            Key::PageUp => self.selected = self.selected.saturating_sub(page.max(1)),
+            Key::PageDown => self.selected = (self.selected + page.max(1)).min(last),
+            Key::Home | Key::Char('g') => self.selected = 0,
+            Key::End | Key::Char('G') => self.selected = last,
+            Key::Char('s') => return Some(BrowserAction::Star),
+            Key::Char('u') => return Some(BrowserAction::Unstar),
+            Key::Char('p') => return Some(BrowserAction::TogglePin),
+            Key::Char('c') => return Some(BrowserAction::Clone),
+            Key::Char('o') | Key::Enter => return Some(BrowserAction::Open),
+            Key::Char('h') => return Some(BrowserAction::History),
+            Key::Char('q') | Key::Escape | Key::CtrlC => return Some(BrowserAction::Quit),
+            _ => {}
+        }
+        None
+    }
+
+    /// Draw a frame `width` columns wide and `height` lines tall, one string per line padded to
+    /// the full width. `now` is the time in seconds since the Unix epoch, for the rate limit.
+    pub fn render(&mut self, width: usize, height: usize, rate_limit: Option<&RateLimit>, now: u64) -> Vec<String> {
+        let rows = Self::list_height(height);
+        if self.selected < self.offset {
+            self.offset = self.selected;
+        } else if rows > 0 && self.selected >= self.offset + rows {
+            self.offset = self.selected + 1 - rows;
+        }
+
+        let list_width = (width * 2 / 5).max(20).min(width);
+        let detail_width = width.saturating_sub(list_width + measure_text_width(SEPARATOR));
+        let details = self.selected().map(|repo| detail_lines(repo, detail_width)).unwrap_or_default();
+
+        let mut lines = Vec::with_capacity(height);
+        let title = format!("Starred repositories ({})", self.repos.len());
+        lines.push(style(fit(&title, width)).bold().to_string());
+
+        for row in 0..rows {
+            let index = self.offset + row;
+            let entry = match self.repos.get(index) {
+                Some(repo) => {
+                    let cursor = if index == self.selected { ">" } else { " " };
+                    let star = if repo.starred { "★" } else { "☆" };
+                    let entry = fit(&format!("{} {} {}", cursor, star, repo.full_name()), list_width);
+                    if index == self.selected {
+                        style(entry).reverse().to_string()
+                    } else {
+                        entry
+                    }
+                }
+                None if index == 0 => fit("  No starred repositories", list_width),
+                None => fit("", list_width),
+            };
+            let detail = details.get(row).map(String::as_str).unwrap_or("");
+            lines.push(format!("{}{}{}", entry, SEPARATOR, fit(detail, detail_width)));
+        }
+
+        let rate = rate_limit.map_or_else(|| String::from("rate limit unknown"), |rate_limit| rate_limit.summary(now));
+        let status = format!("{} | {} | {}", self.status, rate, KEY_HELP);
+        lines.push(style(fit(&status, width)).reverse().to_string());
+
+        lines.truncate(height);
+        lines
+    }
+}
+
+// The detail pane for a repository, each line at most `width` columns
+fn detail_lines(repo: &BrowserRepo, width: usize) -> Vec<String> {
+    let mut lines = vec![style(repo.full_name()).bold().to_string(), String::new()];
+    lines.extend(wrap(repo.description.as_deref().unwrap_or("No description"), width));
+    lines.push(String::new());
+    lines.push(format!("Language: {}", repo.language.as_deref().unwrap_or("unknown")));
+    lines.push(format!("Stars: {}", repo.stars));
+    lines.push(format!("Forks: {}", repo.forks));
+    if !repo.topics.is_empty() {
+        lines.extend(wrap(&format!("Topics: {}", repo.topics.join(", ")), width));
+    }
+    lines.push(format!("Starred: {}", if repo.starred { "yes" } else { "no" }));
+    if repo.pinned {
+        lines.push(String::from("Pinned: yes"));
+    }
+    lines.push(repo.html_url.clone());
+    lines
+}
+
+// Break text into lines of at most `width` columns at spaces, cutting words that don't fit at all.
+// Chinese and Japanese don't put spaces between words, so text with wide characters breaks between
+// any two of them instead.
+fn wrap(text: &str, width: usize) -> Vec<String> {
+    let mut lines = Vec::new();
+    let mut line = String::new();
+    for word in text.split_whitespace() {
+        let needed = measure_text_width(&line) + usize::from(!line.is_empty()) + measure_text_width(word);
+        if needed > width && word.chars().any(|c| measure_text_width(c.encode_utf8(&mut [0; 4])) > 1) {
+            let mut space = !line.is_empty();
+            for c in word.chars() {
+                let c = c.to_string();
+                if !line.is_empty() && measure_text_width(&line) + usize::from(space) + measure_text_width(&c) > width {
+                    lines.push(std::mem::take(&mut line));
+                    space = false;
+                }
+                if std::mem::take(&mut space) {
+                    line.push(' ');
+                }
+                line.push_str(&c);
+            }
+            continue;
+        }
+        if !line.is_empty() && needed > width {
+            lines.push(std::mem::take(&mut line));
+        }
+        if !line.is_empty() {
+            line.push(' ');
+        }
+        line.push_str(&truncate_str(word, width, "…"));
+    }
+    if !line.is_empty() {
+        lines.push(line);
+    }
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn repos() -> Vec<BrowserRepo> {
+        let starred = json!([
+            { "id": 1296269, "name": "Hello-World", "full_name": "octocat/Hello-World", "owner": { "login": "octocat" },
+              "description": "My first repository on GitHub!",
+              "language": "C", "stargazers_count": 80, "forks_count": 9, "topics": ["git", "example"],
+              "html_url": "https://github.com/octocat/Hello-World" },
+            { "id": 724712, "name": "rust", "full_name": "rust-lang/rust", "owner": { "login": "rust-lang" },
+              "description": null, "language": "Rust",
+              "stargazers_count": 90000, "forks_count": 12000, "html_url": "https://github.com/rust-lang/rust" },
+            { "id": 11730342, "name": "vue", "full_name": "vuejs/vue", "owner": { "login": "vuejs" },
+              "description": "Progressive JavaScript framework",
+              "html_url": "https://github.com/vuejs/vue" },
+        ]);
+        let starred: Vec<StarredRepo> = serde_json::from_value(starred).unwrap();
+        starred.into_iter().map(BrowserRepo::from).collect()
+    }
+
+    fn rate_limit() -> RateLimit {
+        RateLimit { limit: 5000, remaining: 4990, reset: 1_700_000_600 }
+    }
+
+    #[test]
+    fn test_from_starred_repo() {
+        let repos = repos();
+
+        assert_eq!(repos.len(), 3);
+        assert_eq!(repos[0].full_name(), "octocat/Hello-World");
+        assert_eq!(repos[0].topics, vec!["git", "example"]);
+        assert_eq!(repos[2].stars, 0);
+        assert!(serde_json::from_value::<StarredRepo>(json!({ "name": "no-owner" })).is_err());
+    }
+
+    #[test]
+    fn test_handle_key() {
+        let mut browser = Browser::new(repos());
+
+        assert_eq!(browser.handle_key(&Key::ArrowUp, 10), None);
+        assert_eq!(browser.selected().unwrap().name, "Hello-World");
+        browser.handle_key(&Key::PageDown, 10);
+        assert_eq!(browser.selected().unwrap().name, "vue");
+        browser.handle_key(&Key::Char('k'), 10);
+        assert_eq!(browser.selected().unwrap().name, "rust");
+        assert_eq!(browser.handle_key(&Key::Char('u'), 10), Some(BrowserAction::Unstar));
+        assert_eq!(browser.handle_key(&Key::Char('p'), 10), Some(BrowserAction::TogglePin));
+        assert_eq!(browser.handle_key(&Key::Escape, 10), Some(BrowserAction::Quit));
+    }
+
+    #[test]
+    fn test_render() {
+        console::set_colors_enabled(false);
+        let mut browser = Browser::new(repos());
+        browser.handle_key(&Key::ArrowDown, 10);
+        browser.selected_mut().unwrap().starred = false;
+        browser.selected_mut().unwrap().pinned = true;
+
+        insta::assert_snapshot!(browser.render(80, 14, Some(&rate_limit()), 1_700_000_000).join("\n"));
+    }
+
+    #[test]
+    fn test_render_scrolls_to_selection() {
+        console::set_colors_enabled(false);
+        let mut browser = Browser::new(repos());
+        browser.handle_key(&Key::End, 10);
+        let lines = browser.render(60, 4, None, 0);
+
+        assert_eq!(lines.len(), 4);
+        assert!(lines[1].contains("rust-lang/rust"));
+        assert!(lines[2].contains("> ★ vuejs/vue"));
+        assert!(lines[3].starts_with("3 starred repositories | rate limit unknown"));
+        assert!(lines.iter().all(|line| measure_text_width(line) == 60));
+    }
+
+    #[test]
+    fn test_render_empty() {
+        console::set_colors_enabled(false);
+        let lines = Browser::new(Vec::new()).render(60, 5, None, 0);
+
+        assert!(lines[1].starts_with("  No starred"));
+    }
+
+    #[test]
+    fn test_wrap() {
+        assert_eq!(wrap("a quick brown fox", 7), vec!["a quick", "brown", "fox"]);
+        assert_eq!(wrap("supercalifragilistic", 6), vec!["super…"]);
+        assert_eq!(wrap("获取 GitHub 上收藏的仓库列表", 10), vec!["获取", "GitHub 上", "收藏的仓库", "列表"]);
+        assert!(wrap("用于命令行的仓库收藏管理工具", 7).iter().all(|line| measure_text_width(line) <= 7));
+    }
+}
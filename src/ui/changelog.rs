@@ -0,0 +1,78 @@
+use crate::api::compare::Comparison;
+use crate::api::releases::Release;
+
+// Commit SHAs are shortened the way git does
+const SHORT_SHA: usize = 7;
+
+/// Render release notes as one section per release, in the order given
+pub fn render_release_notes(releases: &[Release]) -> String {
+    let mut notes = String::new();
+
+    for release in releases {
+        let heading = match &release.name {
+            Some(name) if !name.is_empty() && name != &release.tag_name => format!("{} ({})", release.tag_name, name),
+            _ => release.tag_name.clone(),
+        };
+        let date = release.published_at.as_deref().and_then(|date| date.get(..10)).unwrap_or("unpublished");
+        notes.push_str(&format!("## {} - {}\n\n", heading, date));
+
+        match release.body.as_deref().map(str::trim) {
+            Some(body) if !body.is_empty() => notes.push_str(&format!("{}\n\n", body.replace("\r\n", "\n"))),
+            _ => notes.push_str("No release notes.\n\n"),
+        }
+    }
+
+    notes
+}
+
+/// Render the commits between two refs, one line each with the first line of the message
+pub fn render_commits(base: &str, head: &str, comparison: &Comparison) -> String {
+    let mut log = format!("## {}...{}: {} commits\n\n", base, head, comparison.ahead_by);
+
+    for commit in &comparison.commits {
+        let sha = commit.sha.get(..SHORT_SHA).unwrap_or(&commit.sha);
+        let summary = commit.commit.message.lines().next().unwrap_or_default();
+        match &commit.commit.author {
+            Some(author) => log.push_str(&format!("- {} {} ({})\n", sha, summary, author.name)),
+            None => log.push_str(&format!("- {} {}\n", sha, summary)),
+        }
+    }
+
+    let missing = comparison.ahead_by.saturating_sub(comparison.commits.len() as u64);
+    if missing > 0 {
+        log.push_str(&format!("- ... and {} more\n", missing));
+    }
+
+    log
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_render_release_notes() {
+        let releases: Vec<Release> = serde_json::from_value(json!([
+            { "tag_name": "v1.4.0", "name": "v1.4.0", "published_at": "2024-03-01T10:00:00Z", "body": "### Added\r\n- `--json` output\r\n" },
+            { "tag_name": "v1.3.0", "name": "Spring cleaning", "published_at": "2024-02-01T10:00:00Z", "body": "" }
+        ])).unwrap();
+
+        insta::assert_snapshot!(render_release_notes(&releases));
+    }
+
+    #[test]
+    fn test_render_commits() {
+        let comparison: Comparison = serde_json::from_value(json!({
+            "status": "ahead",
+            "ahead_by": 3,
+            "behind_by": 0,
+            "commits": [
+                { "sha": "0123456789abcdef", "commit": { "message": "Fix panic on empty input\n\nCloses #12", "author": { "name": "Alice", "date": "2024-03-01T00:00:00Z" } } },
+                { "sha": "fedcba9876543210", "commit": { "message": "Release 1.4.0", "author": null } }
+            ]
+        })).unwrap();
+
+        insta::assert_snapshot!(render_commits("v1.3.0", "v1.4.0", &comparison));
+    }
+}
@@ -0,0 +1,247 @@
+//!
+//! Starred repositories as a graph
+//! Repositories are linked through the owners, topics and languages they share: each one shared by
+//! at least two repositories becomes a node with an edge to every repository that has it, so tools
+//! like Graphviz or Gephi lay the stars out in clusters. Written as Graphviz DOT or GEXF.
+//!
+
+use std::collections::BTreeMap;
+use std::str::FromStr;
+use crate::api::stars::StarredRepo;
+
+/// Graph formats accepted by `export --format`
+pub const GRAPH_FORMATS: &[&str] = &["graphviz", "gexf"];
+/// Names accepted by `export --link`
+pub const GRAPH_LINKS: &[&str] = &["owner", "topic", "language"];
+
+/// What repositories are linked through
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum GraphLink {
+    Owner,
+    Topic,
+    Language,
+}
+
+impl GraphLink {
+    fn name(&self) -> &'static str {
+        match self {
+            GraphLink::Owner => "owner",
+            GraphLink::Topic => "topic",
+            GraphLink::Language => "language",
+        }
+    }
+
+    // Values of this kind a repository has
+    fn values<'a>(&self, repo: &'a StarredRepo) -> Vec<&'a str> {
+        match self {
+            GraphLink::Owner => vec![repo.owner.login.as_str()],
+            GraphLink::Language => repo.language.as_deref().into_iter().collect(),
+            GraphLink::Topic => repo.topics.iter().map(String::as_str).collect(),
+        }
+    }
+}
+
+impl FromStr for GraphLink {
+    type Err = String;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        match name {
+            "owner" => Ok(GraphLink::Owner),
+            "topic" => Ok(GraphLink::Topic),
+            "language" => Ok(GraphLink::Language),
+            _ => Err(format!("Unknown link {:?}, expected one of {}", name, GRAPH_LINKS.join(", "))),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum NodeKind {
+    Repo,
+    Link(GraphLink),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Node {
+    /// Unique within the graph, e.g. `repo:rust-lang/rust` or `topic:cli`
+    pub id: String,
+    pub label: String,
+    pub kind: NodeKind,
+    /// Stargazers of a repository, or how many repositories share an owner, topic or language
+    pub weight: u64,
+}
+
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct StarGraph {
+    pub nodes: Vec<Node>,
+    /// Pairs of indexes into `nodes`, from a repository to what it shares
+    pub edges: Vec<(usize, usize)>,
+}
+
+/// Build the graph of `repos` linked by `links`, leaving out values only one repository has
+pub fn build_graph(repos: &[StarredRepo], links: &[GraphLink]) -> StarGraph {
+    let mut graph = StarGraph::default();
+    let mut shared: BTreeMap<(GraphLink, String), Vec<usize>> = BTreeMap::new();
+
+    for repo in repos {
+        let index = graph.nodes.len();
+        graph.nodes.push(Node {
+            id: format!("repo:{}", repo.full_name),
+            label: repo.full_name.clone(),
+            kind: NodeKind::Repo,
+            weight: repo.stars,
+        });
+
+        for link in links {
+            for value in link.values(repo) {
+                shared.entry((*link, value.to_string())).or_default().push(index);
+            }
+        }
+    }
+
+    for ((link, value), repos) in shared.into_iter().filter(|(_, repos)| repos.len() > 1) {
+        let index = graph.nodes.len();
+        graph.nodes.push(Node {
+            id: format!("{}:{}", link.name(), value),
+            label: value,
+            kind: NodeKind::Link(link),
+            weight: repos.len() as u64,
+        });
+        graph.edges.extend(repos.into_iter().map(|repo| (repo, index)));
+    }
+
+    graph
+}
+
+/// The graph in Graphviz DOT, with repositories as boxes and what they share as ellipses
+pub fn render_dot(graph: &StarGraph) -> String {
+    let mut dot = String::from("graph stars {\n  overlap=false;\n");
+
+    for node in &graph.nodes {
+        let shape = match node.kind {
+            NodeKind::Repo => "box",
+            NodeKind::Link(_) => "ellipse",
+        };
+        dot.push_str(&format!("  {} [label={}, shape={}];\n", dot_id(&node.id), dot_id(&node.label), shape));
+    }
+    for (from, to) in &graph.edges {
+        dot.push_str(&format!("  {} -- {};\n", dot_id(&graph.nodes[*from].id), dot_id(&graph.nodes[*to].id)));
+    }
+
+    dot.push_str("}\n");
+    dot
+}
+
+// A quoted DOT identifier
+fn dot_id(text: &str) -> String {
+    format!("\"{}\"", text.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+/// The graph in GEXF 1.3 for Gephi, with the kind of each node and its weight as attributes
+pub fn render_gexf(graph: &StarGraph) -> String {
+    let mut gexf = String::from(concat!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n",
+        "<gexf xmlns=\"http://gexf.net/1.3\" version=\"1.3\">\n",
+        "  <graph defaultedgetype=\"undirected\">\n",
+        "    <attributes class=\"node\">\n",
+        "      <attribute id=\"kind\" title=\"kind\" type=\"string\"/>\n",
+        "      <attribute id=\"weight\" title=\"weight\" type=\"long\"/>\n",
+        "    </attributes>\n",
+        "    <nodes>\n",
+    ));
+
+    for node in &graph.nodes {
+        let kind = match node.kind {
+            NodeKind::Repo => "repo",
+            NodeKind::Link(link) => link.name(),
+        };
+        gexf.push_str(&format!(
+            "      <node id=\"{}\" label=\"{}\"><attvalues><attvalue for=\"kind\" value=\"{}\"/><attvalue for=\"weight\" value=\"{}\"/></attvalues></node>\n",
+            xml_escape(&node.id),
+            xml_escape(&node.label),
+            kind,
+            node.weight
+        ));
+    }
+    gexf.push_str("    </nodes>\n    <edges>\n");
+    for (index, (from, to)) in graph.edges.iter().enumerate() {
+        gexf.push_str(&format!(
+            "      <edge id=\"{}\" source=\"{}\" target=\"{}\"/>\n",
+            index,
+            xml_escape(&graph.nodes[*from].id),
+            xml_escape(&graph.nodes[*to].id)
+        ));
+    }
+
+    gexf.push_str("    </edges>\n  </graph>\n</gexf>\n");
+    gexf
+}
+
+fn xml_escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn repos() -> Vec<StarredRepo> {
+        serde_json::from_value(json!([
+            { "id": 1, "name": "rust", "full_name": "rust-lang/rust", "owner": { "login": "rust-lang" }, "description": null,
+                "html_url": "https://github.com/rust-lang/rust", "language": "Rust", "topics": ["compiler"], "stargazers_count": 90000 },
+            { "id": 2, "name": "cargo", "full_name": "rust-lang/cargo", "owner": { "login": "rust-lang" }, "description": null,
+                "html_url": "https://github.com/rust-lang/cargo", "language": "Rust", "topics": ["package-manager"], "stargazers_count": 12000 },
+            { "id": 3, "name": "vue", "full_name": "vuejs/vue", "owner": { "login": "vuejs" }, "description": null,
+                "html_url": "https://github.com/vuejs/vue", "language": "TypeScript", "topics": [], "stargazers_count": 200000 },
+        ]))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_build_graph_links_shared_values_only() {
+        let graph = build_graph(&repos(), &[GraphLink::Owner, GraphLink::Topic, GraphLink::Language]);
+
+        let ids: Vec<&str> = graph.nodes.iter().map(|node| node.id.as_str()).collect();
+        assert_eq!(ids, ["repo:rust-lang/rust", "repo:rust-lang/cargo", "repo:vuejs/vue", "owner:rust-lang", "language:Rust"]);
+        assert_eq!(graph.edges, vec![(0, 3), (1, 3), (0, 4), (1, 4)]);
+        assert_eq!(graph.nodes[4].weight, 2);
+        assert_eq!(graph.nodes[2].weight, 200000);
+    }
+
+    #[test]
+    fn test_build_graph_with_chosen_links() {
+        let graph = build_graph(&repos(), &[GraphLink::Language]);
+
+        assert_eq!(graph.nodes.len(), 4);
+        assert_eq!(graph.nodes[3].kind, NodeKind::Link(GraphLink::Language));
+        assert!("colour".parse::<GraphLink>().is_err());
+    }
+
+    #[test]
+    fn test_render_dot() {
+        let graph = build_graph(&repos(), &[GraphLink::Owner]);
+        let dot = render_dot(&graph);
+
+        assert!(dot.starts_with("graph stars {\n"));
+        assert!(dot.contains("  \"repo:vuejs/vue\" [label=\"vuejs/vue\", shape=box];\n"));
+        assert!(dot.contains("  \"owner:rust-lang\" [label=\"rust-lang\", shape=ellipse];\n"));
+        assert!(dot.contains("  \"repo:rust-lang/cargo\" -- \"owner:rust-lang\";\n"));
+        assert_eq!(dot_id("a \"b\""), "\"a \\\"b\\\"\"");
+    }
+
+    #[test]
+    fn test_render_gexf() {
+        let mut repos = repos();
+        repos[0].topics = vec![String::from("c&c")];
+        repos[1].topics = vec![String::from("c&c")];
+        let gexf = render_gexf(&build_graph(&repos, &[GraphLink::Topic]));
+
+        assert!(gexf.contains("<node id=\"topic:c&amp;c\" label=\"c&amp;c\"><attvalues><attvalue for=\"kind\" value=\"topic\"/><attvalue for=\"weight\" value=\"2\"/></attvalues></node>"));
+        assert!(gexf.contains("<edge id=\"1\" source=\"repo:rust-lang/cargo\" target=\"topic:c&amp;c\"/>"));
+        assert!(gexf.ends_with("</gexf>\n"));
+    }
+}
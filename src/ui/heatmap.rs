@@ -0,0 +1,74 @@
+use crate::api::stats::WeekActivity;
+
+const DAYS: [&str; 7] = ["Sun", "Mon", "Tue", "Wed", "Thu", "Fri", "Sat"];
+const EMPTY: char = '·';
+const SHADES: [char; 4] = ['░', '▒', '▓', '█'];
+
+// Shade a day relative to the busiest day, so every repository uses the full range
+fn shade(count: u64, max: u64) -> char {
+    if count == 0 || max == 0 {
+        return EMPTY;
+    }
+
+    let level = (count * SHADES.len() as u64).div_ceil(max) as usize;
+    SHADES[level.clamp(1, SHADES.len()) - 1]
+}
+
+/// Render weekly commit activity as a heatmap with one row per weekday and one column per week
+pub fn render_heatmap(weeks: &[WeekActivity]) -> String {
+    let total: u64 = weeks.iter().map(|week| week.total).sum();
+    if total == 0 {
+        return String::from("No commit activity in the last year\n");
+    }
+
+    let max = weeks.iter().flat_map(|week| week.days).max().unwrap_or_default();
+    let mut heatmap = String::new();
+
+    for (day, label) in DAYS.iter().enumerate() {
+        let cells: String = weeks.iter().map(|week| shade(week.days[day], max)).collect();
+        heatmap.push_str(&format!("{} {}\n", label, cells));
+    }
+
+    let legend: Vec<String> = std::iter::once(EMPTY).chain(SHADES).map(String::from).collect();
+    heatmap.push_str(&format!("    Less {} More\n", legend.join(" ")));
+    heatmap.push_str(&format!("{} commits in the last {} weeks, busiest day had {}\n", total, weeks.len(), max));
+
+    heatmap
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn week(days: [u64; 7]) -> WeekActivity {
+        WeekActivity { week: 0, total: days.iter().sum(), days }
+    }
+
+    #[test]
+    fn test_shade() {
+        assert_eq!(shade(0, 40), '·');
+        assert_eq!(shade(1, 40), '░');
+        assert_eq!(shade(10, 40), '░');
+        assert_eq!(shade(11, 40), '▒');
+        assert_eq!(shade(30, 40), '▓');
+        assert_eq!(shade(40, 40), '█');
+    }
+
+    #[test]
+    fn test_render_heatmap() {
+        let weeks = vec![
+            week([0, 3, 26, 20, 39, 1, 0]),
+            week([0, 0, 0, 0, 0, 0, 0]),
+            week([2, 12, 8, 40, 15, 6, 1]),
+            week([0, 1, 0, 5, 0, 0, 0]),
+        ];
+
+        insta::assert_snapshot!(render_heatmap(&weeks));
+    }
+
+    #[test]
+    fn test_render_heatmap_no_activity() {
+        assert_eq!(render_heatmap(&[]), "No commit activity in the last year\n");
+        assert_eq!(render_heatmap(&[week([0; 7])]), "No commit activity in the last year\n");
+    }
+}
@@ -0,0 +1,192 @@
+//!
+//! Starred repositories as a web page
+//! One HTML file with everything inline: the repositories as JSON, a stylesheet and a short script
+//! that searches and filters them in the browser, so the file can be opened, shared or hosted
+//! without a server. The script only ever sets text, never markup, so descriptions show as written.
+//!
+
+use serde::Serialize;
+use crate::api::stars::StarredRepo;
+
+/// A repository as the page reads it
+#[derive(Serialize)]
+struct PageRepo<'a> {
+    full_name: &'a str,
+    html_url: &'a str,
+    description: Option<&'a str>,
+    language: Option<&'a str>,
+    stars: u64,
+    topics: &'a [String],
+    archived: bool,
+}
+
+impl<'a> From<&'a StarredRepo> for PageRepo<'a> {
+    fn from(repo: &'a StarredRepo) -> Self {
+        PageRepo {
+            full_name: &repo.full_name,
+            html_url: &repo.html_url,
+            description: repo.description.as_deref(),
+            language: repo.language.as_deref(),
+            stars: repo.stars,
+            topics: &repo.topics,
+            archived: repo.archived,
+        }
+    }
+}
+
+const STYLE: &str = "\
+body { font: 15px/1.5 system-ui, sans-serif; max-width: 960px; margin: 2em auto; padding: 0 1em; color: #1f2328; }
+header { display: flex; flex-wrap: wrap; gap: .5em; align-items: center; margin-bottom: 1em; }
+input, select { font: inherit; padding: .3em .5em; }
+input { flex: 1; min-width: 12em; }
+ul { list-style: none; padding: 0; }
+li { border-bottom: 1px solid #d0d7de; padding: .6em 0; }
+a { font-weight: 600; color: #0969da; text-decoration: none; }
+p { margin: .2em 0; }
+.meta, #count { color: #59636e; font-size: .9em; }
+.topic { background: #ddf4ff; color: #0969da; border-radius: 1em; padding: 0 .6em; margin-right: .3em; font-size: .85em; }
+";
+
+const SCRIPT: &str = r#"
+const repos = JSON.parse(document.getElementById("stars").textContent);
+const search = document.getElementById("search");
+const language = document.getElementById("language");
+const count = document.getElementById("count");
+const list = document.getElementById("repos");
+
+for (const repo of repos) {
+  repo.text = [repo.full_name, repo.description, repo.language, ...repo.topics].join(" ").toLowerCase();
+}
+for (const name of [...new Set(repos.map(repo => repo.language).filter(Boolean))].sort()) {
+  language.append(new Option(name, name));
+}
+
+function element(tag, text, className) {
+  const node = document.createElement(tag);
+  if (text) node.textContent = text;
+  if (className) node.className = className;
+  return node;
+}
+
+function item(repo) {
+  const li = element("li");
+  const link = element("a", repo.full_name);
+  if (/^https?:\/\//.test(repo.html_url)) link.href = repo.html_url;
+  li.append(link);
+  if (repo.description) li.append(element("p", repo.description));
+  const meta = [repo.stars.toLocaleString() + " stars", repo.language, repo.archived && "archived"];
+  const details = element("p", meta.filter(Boolean).join(" · "), "meta");
+  for (const topic of repo.topics) details.append(" ", element("span", topic, "topic"));
+  li.append(details);
+  return li;
+}
+
+function show() {
+  const terms = search.value.toLowerCase().split(/\s+/).filter(Boolean);
+  const shown = repos.filter(repo =>
+    (!language.value || repo.language === language.value) && terms.every(term => repo.text.includes(term)));
+  list.replaceChildren(...shown.map(item));
+  count.textContent = shown.length + " of " + repos.length + " repositories";
+}
+
+search.addEventListener("input", show);
+language.addEventListener("change", show);
+show();
+"#;
+
+/// A self-contained page listing `repos`, with a search box and a language filter
+pub fn render_html(repos: &[StarredRepo]) -> Result<String, serde_json::Error> {
+    let records: Vec<PageRepo> = repos.iter().map(PageRepo::from).collect();
+    // `<` can't end the script element holding the JSON, nor `&` start an entity in it
+    let json = serde_json::to_string(&records)?
+        .replace('<', "\\u003c")
+        .replace('>', "\\u003e")
+        .replace('&', "\\u0026");
+
+    Ok(format!(
+        concat!(
+            "<!DOCTYPE html>\n",
+            "<html lang=\"en\">\n",
+            "<head>\n",
+            "<meta charset=\"utf-8\">\n",
+            "<meta name=\"viewport\" content=\"width=device-width, initial-scale=1\">\n",
+            "<title>Starred repositories</title>\n",
+            "<style>\n{}</style>\n",
+            "</head>\n",
+            "<body>\n",
+            "<h1>Starred repositories</h1>\n",
+            "<header>\n",
+            "<input id=\"search\" type=\"search\" placeholder=\"Search names, descriptions and topics\" autofocus>\n",
+            "<select id=\"language\"><option value=\"\">All languages</option></select>\n",
+            "<span id=\"count\"></span>\n",
+            "</header>\n",
+            "<ul id=\"repos\"></ul>\n",
+            "<script type=\"application/json\" id=\"stars\">{}</script>\n",
+            "<script>{}</script>\n",
+            "</body>\n",
+            "</html>\n",
+        ),
+        STYLE, json, SCRIPT
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::{json, Value};
+
+    fn repos() -> Vec<StarredRepo> {
+        serde_json::from_value(json!([
+            { "id": 1296269, "name": "Hello-World", "full_name": "octocat/Hello-World", "owner": { "login": "octocat" },
+              "description": "</script><script>alert(\"stars\")</script> & <!-- more", "language": "C",
+              "stargazers_count": 80, "topics": ["git"], "html_url": "https://github.com/octocat/Hello-World" },
+            { "id": 724712, "name": "rust", "full_name": "rust-lang/rust", "owner": { "login": "rust-lang" },
+              "description": null, "language": "Rust", "stargazers_count": 90000, "archived": true,
+              "html_url": "https://github.com/rust-lang/rust" },
+        ]))
+        .unwrap()
+    }
+
+    // The JSON between the data script's tags
+    fn embedded_json(html: &str) -> Value {
+        let start = html.find("id=\"stars\">").unwrap() + "id=\"stars\">".len();
+        let end = start + html[start..].find("</script>").unwrap();
+        serde_json::from_str(&html[start..end]).unwrap()
+    }
+
+    #[test]
+    fn test_render_html() {
+        let html = render_html(&repos()).unwrap();
+
+        assert!(html.starts_with("<!DOCTYPE html>"));
+        assert_eq!(html.matches("<script").count(), 2);
+        assert_eq!(embedded_json(&html), json!([
+            {
+                "full_name": "octocat/Hello-World",
+                "html_url": "https://github.com/octocat/Hello-World",
+                "description": "</script><script>alert(\"stars\")</script> & <!-- more",
+                "language": "C",
+                "stars": 80,
+                "topics": ["git"],
+                "archived": false,
+            },
+            {
+                "full_name": "rust-lang/rust",
+                "html_url": "https://github.com/rust-lang/rust",
+                "description": null,
+                "language": "Rust",
+                "stars": 90000,
+                "topics": [],
+                "archived": true,
+            },
+        ]));
+    }
+
+    #[test]
+    fn test_render_html_without_repos() {
+        let html = render_html(&[]).unwrap();
+
+        assert_eq!(embedded_json(&html), json!([]));
+        assert!(!html.contains("http://") && !html.contains("src="), "the page loads nothing from elsewhere");
+    }
+}
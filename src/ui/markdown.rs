@@ -0,0 +1,100 @@
+//!
+//! Markdown tables for awesome-lists and static sites
+//! Cells come from GitHub as written by repository owners, so a description can hold a `|` that
+//! ends its cell early, brackets that turn into links, raw HTML or control characters. Every cell
+//! is cleaned with [`sanitize`] and then escaped, so it renders as the text it is.
+//!
+
+/// Text on one line without control characters: line breaks and tabs become spaces, other control
+/// characters, and the ones that reverse the direction of text, are dropped
+pub fn sanitize(text: &str) -> String {
+    text.chars()
+        .filter_map(|c| match c {
+            '\n' | '\r' | '\t' => Some(' '),
+            c if c.is_control() || is_bidi_control(c) => None,
+            c => Some(c),
+        })
+        .collect()
+}
+
+// Characters that make text after them display right to left or isolate it, and can make a cell
+// read differently from what it holds
+fn is_bidi_control(c: char) -> bool {
+    matches!(c, '\u{200E}' | '\u{200F}' | '\u{202A}'..='\u{202E}' | '\u{2066}'..='\u{2069}')
+}
+
+// Punctuation Markdown may read as syntax inside a table cell: emphasis, code, links, HTML tags,
+// entities and the cell's own pipes. Headings and lists only start lines, which cells never do.
+// CommonMark lets any of them be escaped with a backslash, and GitHub reads `\|` as a pipe.
+const MARKDOWN_SYNTAX: &[char] = &['\\', '`', '*', '_', '[', ']', '<', '>', '|', '~', '&'];
+
+/// Text that renders as itself inside a Markdown table cell: sanitized, with Markdown syntax and
+/// HTML escaped
+pub fn escape(text: &str) -> String {
+    let text = sanitize(text);
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.trim().chars() {
+        if MARKDOWN_SYNTAX.contains(&c) {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+/// A GitHub Flavored Markdown table with `headers` as its header row
+pub fn render_markdown(headers: &[&str], rows: &[Vec<String>]) -> String {
+    let line = |cells: Vec<String>| format!("| {} |\n", cells.join(" | "));
+
+    let mut markdown = line(headers.iter().map(|header| escape(header)).collect());
+    markdown.push_str(&line(headers.iter().map(|_| String::from("---")).collect()));
+    for row in rows {
+        markdown.push_str(&line(row.iter().map(|cell| escape(cell)).collect()));
+    }
+    markdown
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_escape_pipes() {
+        assert_eq!(escape("fast | small"), r"fast \| small");
+        assert_eq!(escape(r"a \| b"), r"a \\\| b");
+    }
+
+    #[test]
+    fn test_escape_brackets() {
+        assert_eq!(escape("[docs](https://example.com)"), r"\[docs\](https://example.com)");
+        assert_eq!(escape("![badge](x.svg)"), r"!\[badge\](x.svg)");
+        assert_eq!(escape("*not* _emphasis_ `code`"), r"\*not\* \_emphasis\_ \`code\`");
+    }
+
+    #[test]
+    fn test_escape_html() {
+        assert_eq!(escape("<script>alert(1)</script>"), r"\<script\>alert(1)\</script\>");
+        assert_eq!(escape("Tom &amp; Jerry"), r"Tom \&amp; Jerry");
+        assert_eq!(escape("~~struck~~ rust-lang/rust"), r"\~\~struck\~\~ rust-lang/rust");
+    }
+
+    #[test]
+    fn test_sanitize_control_characters() {
+        assert_eq!(sanitize("first line\nsecond\r\nthird\tcolumn"), "first line second  third column");
+        assert_eq!(sanitize("\u{1b}[31mred\u{1b}[0m\u{0}\u{7f}"), "[31mred[0m");
+        assert_eq!(sanitize("abc\u{202E}fed"), "abcfed");
+        // Joiners inside emoji and text in other scripts stay
+        assert_eq!(sanitize("👩‍💻 获取 café"), "👩‍💻 获取 café");
+        assert_eq!(escape(" padded\n"), "padded");
+    }
+
+    #[test]
+    fn test_render_markdown() {
+        let rows = vec![
+            vec!["octocat/Hello-World".to_string(), "My first repository | on GitHub\n<b>bold</b>".to_string()],
+            vec!["rust-lang/rust".to_string(), String::new()],
+        ];
+
+        insta::assert_snapshot!(render_markdown(&["Full Name", "Description"], &rows));
+    }
+}
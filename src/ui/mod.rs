@@ -1 +1,12 @@
-pub mod selector;
\ No newline at end of file
+pub mod selector;
+pub mod table;
+pub mod badge;
+pub mod heatmap;
+pub mod changelog;
+pub mod terminal;
+pub mod output;
+pub mod browser;
+pub mod graph;
+pub mod treemap;
+pub mod markdown;
+pub mod html;
\ No newline at end of file
@@ -0,0 +1,321 @@
+//!
+//! Output formats for command results
+//! Results are printed as a table by default, as JSON, CSV or YAML for scripts, or as a Markdown
+//! table for awesome-lists and static sites. Tables, CSV and Markdown are built from the same rows
+//! of cells; JSON and YAML serialize the records behind them. JSON
+//! wraps the records in an envelope with metadata about the run unless asked for the bare records.
+//!
+
+use std::error::Error;
+use std::fmt::Write as _;
+use std::str::FromStr;
+use serde::Serialize;
+use serde_json::{Map, Value};
+use crate::api::{metrics, rate_limit};
+use crate::ui::markdown::render_markdown;
+use crate::ui::table::render_table;
+
+/// Names accepted by `--format`
+pub const OUTPUT_FORMATS: &[&str] = &["table", "json", "csv", "yaml", "markdown"];
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum OutputFormat {
+    #[default]
+    Table,
+    /// JSON, inside a metadata envelope when `meta` is set
+    Json { meta: bool },
+    Csv,
+    Yaml,
+    /// A GitHub Flavored Markdown table, with cells escaped to render as plain text
+    Markdown,
+}
+
+impl OutputFormat {
+    /// Whether the output is meant for people rather than other programs
+    pub fn is_table(&self) -> bool {
+        *self == OutputFormat::Table
+    }
+
+    /// The same format, with JSON records left bare
+    pub fn without_meta(self) -> Self {
+        match self {
+            OutputFormat::Json { .. } => OutputFormat::Json { meta: false },
+            format => format,
+        }
+    }
+}
+
+/// What producing a result cost, reported alongside JSON records
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct OutputMeta {
+    /// Number of records
+    pub total: usize,
+    /// Pages of paginated lists fetched
+    pub pages: u64,
+    pub rate_limit_remaining: Option<u64>,
+    pub cache: CacheCounts,
+    pub duration_ms: u64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct CacheCounts {
+    pub hits: u64,
+    pub misses: u64,
+}
+
+impl OutputMeta {
+    /// Metadata for `total` records, from the requests made so far in this run
+    pub fn current(total: usize) -> Self {
+        let requests = metrics::snapshot();
+        OutputMeta {
+            total,
+            pages: requests.pages,
+            rate_limit_remaining: rate_limit::latest().map(|rate_limit| rate_limit.remaining),
+            cache: CacheCounts { hits: requests.cache_hits, misses: requests.cache_misses },
+            duration_ms: u64::try_from(metrics::elapsed().as_millis()).unwrap_or(u64::MAX),
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct Envelope<'a, T: Serialize + ?Sized> {
+    meta: &'a OutputMeta,
+    items: &'a T,
+}
+
+/// JSON for `records`, inside an envelope with `meta` when given
+pub fn render_json<T: Serialize + ?Sized>(records: &T, meta: Option<&OutputMeta>) -> Result<String, Box<dyn Error>> {
+    let json = match meta {
+        Some(meta) => serde_json::to_string_pretty(&Envelope { meta, items: records })?,
+        None => serde_json::to_string_pretty(records)?,
+    };
+    Ok(format!("{}\n", json))
+}
+
+impl FromStr for OutputFormat {
+    type Err = String;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        match name {
+            "table" => Ok(OutputFormat::Table),
+            "json" => Ok(OutputFormat::Json { meta: true }),
+            "csv" => Ok(OutputFormat::Csv),
+            "yaml" => Ok(OutputFormat::Yaml),
+            "markdown" => Ok(OutputFormat::Markdown),
+            _ => Err(format!("Unknown format {:?}, expected one of {}", name, OUTPUT_FORMATS.join(", "))),
+        }
+    }
+}
+
+/// Render `records` in `format`; tables, CSV and Markdown use `headers` and `rows`, which must describe the
+/// same records, and the JSON envelope counts the rows
+pub fn render_output<T: Serialize + ?Sized>(
+    format: OutputFormat,
+    headers: &[&str],
+    rows: &[Vec<String>],
+    records: &T,
+) -> Result<String, Box<dyn Error>> {
+    match format {
+        OutputFormat::Table => Ok(render_table(headers, rows)),
+        OutputFormat::Csv => Ok(render_csv(headers, rows)),
+        OutputFormat::Json { meta } => render_json(records, meta.then(|| OutputMeta::current(rows.len())).as_ref()),
+        OutputFormat::Yaml => Ok(render_yaml(&serde_json::to_value(records)?)),
+        OutputFormat::Markdown => Ok(render_markdown(headers, rows)),
+    }
+}
+
+/// Render rows without records of their own, keying JSON and YAML fields by the headers in
+/// snake case, e.g. `Full Name` becomes `full_name`
+pub fn render_rows(format: OutputFormat, headers: &[&str], rows: &[Vec<String>]) -> Result<String, Box<dyn Error>> {
+    let records: Vec<Map<String, Value>> = rows
+        .iter()
+        .map(|row| {
+            headers
+                .iter()
+                .zip(row)
+                .map(|(header, cell)| (header.to_lowercase().replace(' ', "_"), Value::String(cell.clone())))
+                .collect()
+        })
+        .collect();
+
+    render_output(format, headers, rows, &records)
+}
+
+/// CSV with a header line, quoting cells as RFC 4180 requires
+pub fn render_csv(headers: &[&str], rows: &[Vec<String>]) -> String {
+    let mut csv = String::new();
+    let headers: Vec<String> = headers.iter().map(|header| header.to_string()).collect();
+
+    for row in std::iter::once(&headers).chain(rows) {
+        let cells: Vec<String> = row.iter().map(|cell| csv_cell(cell)).collect();
+        csv.push_str(&cells.join(","));
+        csv.push_str("\r\n");
+    }
+
+    csv
+}
+
+fn csv_cell(cell: &str) -> String {
+    if cell.contains([',', '"', '\r', '\n']) {
+        format!("\"{}\"", cell.replace('"', "\"\""))
+    } else {
+        cell.to_string()
+    }
+}
+
+/// YAML for a JSON value. Strings are written as double-quoted JSON strings, which YAML reads the
+/// same way, so no value can be mistaken for a number, boolean or null.
+pub fn render_yaml(value: &Value) -> String {
+    let mut yaml = String::new();
+    match value {
+        Value::Array(items) if items.is_empty() => yaml.push_str("[]\n"),
+        Value::Object(fields) if fields.is_empty() => yaml.push_str("{}\n"),
+        Value::Array(_) | Value::Object(_) => write_yaml(&mut yaml, value, 0),
+        scalar => {
+            let _ = writeln!(yaml, "{}", scalar);
+        }
+    }
+    yaml
+}
+
+fn write_yaml(yaml: &mut String, value: &Value, indent: usize) {
+    let pad = "  ".repeat(indent);
+    match value {
+        Value::Array(items) => {
+            for item in items {
+                match item {
+                    Value::Object(fields) if !fields.is_empty() => {
+                        // The first field goes on the dash line, the rest line up under it
+                        let mut nested = String::new();
+                        write_yaml(&mut nested, item, indent + 1);
+                        let _ = write!(yaml, "{}- {}", pad, &nested[pad.len() + 2..]);
+                    }
+                    Value::Array(inner) if !inner.is_empty() => {
+                        let _ = writeln!(yaml, "{}-", pad);
+                        write_yaml(yaml, item, indent + 1);
+                    }
+                    _ => {
+                        let _ = writeln!(yaml, "{}- {}", pad, inline_yaml(item));
+                    }
+                }
+            }
+        }
+        Value::Object(fields) => {
+            for (key, field) in fields {
+                let key = yaml_key(key);
+                match field {
+                    Value::Array(items) if !items.is_empty() => {
+                        let _ = writeln!(yaml, "{}{}:", pad, key);
+                        write_yaml(yaml, field, indent + 1);
+                    }
+                    Value::Object(inner) if !inner.is_empty() => {
+                        let _ = writeln!(yaml, "{}{}:", pad, key);
+                        write_yaml(yaml, field, indent + 1);
+                    }
+                    _ => {
+                        let _ = writeln!(yaml, "{}{}: {}", pad, key, inline_yaml(field));
+                    }
+                }
+            }
+        }
+        scalar => {
+            let _ = writeln!(yaml, "{}{}", pad, inline_yaml(scalar));
+        }
+    }
+}
+
+// Words YAML readers may take for booleans or null
+const YAML_RESERVED: &[&str] = &["true", "false", "yes", "no", "on", "off", "y", "n", "null"];
+
+// Keys stay bare when they are plain words, and are quoted like strings otherwise
+fn yaml_key(key: &str) -> String {
+    let plain = key.starts_with(|c: char| c.is_ascii_alphabetic())
+        && key.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+        && !YAML_RESERVED.contains(&key.to_lowercase().as_str());
+    if plain {
+        key.to_string()
+    } else {
+        Value::String(key.to_string()).to_string()
+    }
+}
+
+// Scalars and empty collections, which fit on one line
+fn inline_yaml(value: &Value) -> String {
+    match value {
+        Value::Array(_) => String::from("[]"),
+        Value::Object(_) => String::from("{}"),
+        scalar => scalar.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    const HEADERS: &[&str] = &["Full Name", "Description"];
+
+    fn rows() -> Vec<Vec<String>> {
+        vec![
+            vec!["octocat/Hello-World".to_string(), "My first repository, on \"GitHub\"".to_string()],
+            vec!["rust-lang/rust".to_string(), String::new()],
+        ]
+    }
+
+    #[test]
+    fn test_parse_format() {
+        for name in OUTPUT_FORMATS {
+            assert!(name.parse::<OutputFormat>().is_ok());
+        }
+        assert!("xml".parse::<OutputFormat>().is_err());
+    }
+
+    #[test]
+    fn test_render_csv() {
+        insta::assert_snapshot!(render_csv(HEADERS, &rows()));
+    }
+
+    #[test]
+    fn test_render_rows_json() {
+        let json: Value = serde_json::from_str(&render_rows(OutputFormat::Json { meta: false }, HEADERS, &rows()).unwrap()).unwrap();
+
+        assert_eq!(json[0]["full_name"], "octocat/Hello-World");
+        assert_eq!(json[1]["description"], "");
+    }
+
+    #[test]
+    fn test_render_json_envelope() {
+        let meta = OutputMeta {
+            total: 2,
+            pages: 1,
+            rate_limit_remaining: Some(4998),
+            cache: CacheCounts { hits: 0, misses: 2 },
+            duration_ms: 120,
+        };
+        let json: Value = serde_json::from_str(&render_json(&json!([1, 2]), Some(&meta)).unwrap()).unwrap();
+
+        assert_eq!(json, json!({
+            "meta": { "total": 2, "pages": 1, "rate_limit_remaining": 4998, "cache": { "hits": 0, "misses": 2 }, "duration_ms": 120 },
+            "items": [1, 2],
+        }));
+        assert_eq!(render_json(&json!([1]), None).unwrap(), "[\n  1\n]\n");
+    }
+
+    #[test]
+    fn test_without_meta() {
+        assert_eq!("json".parse::<OutputFormat>().unwrap(), OutputFormat::Json { meta: true });
+        assert_eq!(OutputFormat::Json { meta: true }.without_meta(), OutputFormat::Json { meta: false });
+        assert_eq!(OutputFormat::Csv.without_meta(), OutputFormat::Csv);
+    }
+
+    #[test]
+    fn test_render_yaml() {
+        let value = json!([
+            { "id": 1296269, "full_name": "octocat/Hello-World", "description": null, "topics": ["git", "yes"], "owner": { "login": "octocat" } },
+            { "id": 724712, "full_name": "rust-lang/rust", "description": "1.0", "topics": [], "owner": {} },
+        ]);
+
+        insta::assert_snapshot!(render_yaml(&value));
+        assert_eq!(render_yaml(&json!([])), "[]\n");
+    }
+}
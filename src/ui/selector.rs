@@ -1,56 +1,67 @@
 use dialoguer::{theme::ColorfulTheme, Select, MultiSelect};
 use std::fmt::Display;
+use crate::api::search::SearchRepo;
+use crate::api::stars::StarredRepo;
+
+/// A repository the selector can list, whichever API or view it came from
+pub trait Selectable {
+    fn owner(&self) -> &str;
+    fn name(&self) -> &str;
+    fn description(&self) -> Option<&str>;
+}
 
-/// A simple wrapper for repository data to display in selector
-pub struct RepoDisplayItem {
-    pub id: u64,
-    pub name: String,
-    pub owner: String,
-    pub description: Option<String>,
-    pub html_url: String,
+impl Selectable for StarredRepo {
+    fn owner(&self) -> &str {
+        &self.owner.login
+    }
 
-    // Store the original repo to return it later
-    repo: serde_json::Value,
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn description(&self) -> Option<&str> {
+        self.description.as_deref()
+    }
 }
 
-impl RepoDisplayItem {
-    /// Create a new RepoDisplayItem from a repository JSON
-    pub fn from_repo(repo: serde_json::Value) -> Option<Self> {
-        let id = repo.get("id")?.as_u64()?;
-        let name = repo.get("name")?.as_str()?.to_string();
-        let owner = repo.get("owner")?.get("login")?.as_str()?.to_string();
-        let description = repo.get("description").and_then(|d| d.as_str()).map(|s| s.to_string());
-        let html_url = repo.get("html_url")?.as_str()?.to_string();
-
-        Some(Self {
-            id,
-            name,
-            owner,
-            description,
-            html_url,
-            repo,
-        })
-    }
-
-    /// Get the original repo data
-    pub fn into_repo(self) -> serde_json::Value {
-        self.repo
+impl Selectable for SearchRepo {
+    fn owner(&self) -> &str {
+        &self.owner.login
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn description(&self) -> Option<&str> {
+        self.description.as_deref()
     }
-    
-    /// Get the repository ID
-    pub fn repo(&self) -> serde_json::Value {
-        self.repo.clone()
+}
+
+/// A repository as the selector lists it, borrowed from the caller's list
+pub struct RepoDisplayItem<'a, T> {
+    repo: &'a T,
+}
+
+impl<'a, T: Selectable> RepoDisplayItem<'a, T> {
+    pub fn new(repo: &'a T) -> Self {
+        Self { repo }
+    }
+
+    /// Get the repository this item shows
+    pub fn repo(&self) -> &'a T {
+        self.repo
     }
 }
 
-impl Display for RepoDisplayItem {
+impl<T: Selectable> Display for RepoDisplayItem<'_, T> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(
             f,
             "{}/{}: {}",
-            self.owner,
-            self.name,
-            self.description.as_deref().unwrap_or("No description")
+            self.repo.owner(),
+            self.repo.name(),
+            self.repo.description().unwrap_or("No description")
         )
     }
 }
@@ -60,22 +71,14 @@ pub struct RepoSelector;
 
 impl RepoSelector {
     /// Display a list of repositories and allow the user to select one
-    pub fn select_repo(repos: Vec<serde_json::Value>) -> Option<serde_json::Value> {
+    pub fn select_repo<T: Selectable>(repos: &[T]) -> Option<&T> {
         if repos.is_empty() {
             println!("No repositories to display.");
             return None;
         }
 
         // Convert to display items
-        let display_items: Vec<RepoDisplayItem> = repos
-            .into_iter()
-            .filter_map(RepoDisplayItem::from_repo)
-            .collect();
-
-        if display_items.is_empty() {
-            println!("Failed to parse repository data.");
-            return None;
-        }
+        let display_items: Vec<RepoDisplayItem<T>> = repos.iter().map(RepoDisplayItem::new).collect();
 
         // Display selection dialog
         let selection = Select::with_theme(&ColorfulTheme::default())
@@ -85,27 +88,18 @@ impl RepoSelector {
             .interact_opt()
             .unwrap_or(None);
 
-        // Use non-consuming repo() method
         selection.map(|index| display_items[index].repo())
     }
 
     /// Display a list of repositories and allow the user to select multiple
-    pub fn select_multiple_repos(repos: Vec<serde_json::Value>) -> Vec<serde_json::Value> {
+    pub fn select_multiple_repos<T: Selectable>(repos: &[T]) -> Vec<&T> {
         if repos.is_empty() {
             println!("No repositories to display.");
             return Vec::new();
         }
 
         // Convert to display items
-        let display_items: Vec<RepoDisplayItem> = repos
-            .into_iter()
-            .filter_map(RepoDisplayItem::from_repo)
-            .collect();
-
-        if display_items.is_empty() {
-            println!("Failed to parse repository data.");
-            return Vec::new();
-        }
+        let display_items: Vec<RepoDisplayItem<T>> = repos.iter().map(RepoDisplayItem::new).collect();
 
         // Display multi-selection dialog
         let selection = MultiSelect::with_theme(&ColorfulTheme::default())
@@ -114,7 +108,6 @@ impl RepoSelector {
             .interact_opt()
             .unwrap_or(None);
 
-        // Use non-consuming repo() method
         match selection {
             Some(indices) => indices
                 .into_iter()
@@ -131,73 +124,83 @@ mod tests {
     use serde_json::json;
 
     // Create test repo data
-    fn create_test_repos() -> Vec<serde_json::Value> {
-        vec![
-            json!({
+    fn create_test_repos() -> Vec<StarredRepo> {
+        serde_json::from_value(json!([
+            {
                 "id": 1,
                 "name": "repo1",
+                "full_name": "user1/repo1",
                 "owner": {
                     "login": "user1"
                 },
                 "description": "Description for repo1",
                 "html_url": "https://github.com/user1/repo1"
-            }),
-            json!({
+            },
+            {
                 "id": 2,
                 "name": "repo2",
+                "full_name": "user2/repo2",
                 "owner": {
                     "login": "user2"
                 },
                 "description": "Description for repo2",
                 "html_url": "https://github.com/user2/repo2"
-            }),
-        ]
+            },
+        ]))
+        .unwrap()
     }
 
     #[test]
     #[ignore = "requires user interaction"]
     fn test_select_repo() {
         let repos = create_test_repos();
-        let _selected = RepoSelector::select_repo(repos);
+        let _selected = RepoSelector::select_repo(&repos);
     }
 
     #[test]
     #[ignore = "requires user interaction"]
     fn test_select_multiple_repos() {
         let repos = create_test_repos();
-        let _selected = RepoSelector::select_multiple_repos(repos);
+        let _selected = RepoSelector::select_multiple_repos(&repos);
     }
 
     #[test]
     fn test_empty_repos() {
-        let empty_repos: Vec<serde_json::Value> = vec![];
-        assert!(RepoSelector::select_repo(empty_repos.clone()).is_none());
-        assert!(RepoSelector::select_multiple_repos(empty_repos).is_empty());
+        let empty_repos: Vec<StarredRepo> = vec![];
+        assert!(RepoSelector::select_repo(&empty_repos).is_none());
+        assert!(RepoSelector::select_multiple_repos(&empty_repos).is_empty());
     }
 
     #[test]
     fn test_repo_display_item() {
-        let repo = json!({
-            "id": 1,
-            "name": "test-repo",
-            "owner": {
-                "login": "test-user"
-            },
-            "description": "Test description",
-            "html_url": "https://github.com/test-user/test-repo"
-        });
-
-        let item = RepoDisplayItem::from_repo(repo.clone()).unwrap();
-        assert_eq!(item.id, 1);
-        assert_eq!(item.name, "test-repo");
-        assert_eq!(item.owner, "test-user");
-        assert_eq!(item.description, Some("Test description".to_string()));
-        assert_eq!(item.html_url, "https://github.com/test-user/test-repo");
+        let mut repos = create_test_repos();
+        let item = RepoDisplayItem::new(&repos[0]);
 
         // Test display formatting
-        assert_eq!(format!("{}", item), "test-user/test-repo: Test description");
+        assert_eq!(format!("{}", item), "user1/repo1: Description for repo1");
+
+        // Test repo access
+        assert!(std::ptr::eq(item.repo(), &repos[0]));
 
-        // Test repo conversion
-        assert_eq!(item.into_repo(), repo);
+        repos[1].description = None;
+        assert_eq!(RepoDisplayItem::new(&repos[1]).to_string(), "user2/repo2: No description");
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_search_results_are_selectable() {
+        let found: SearchRepo = serde_json::from_value(json!({
+            "id": 3,
+            "name": "ripgrep",
+            "full_name": "BurntSushi/ripgrep",
+            "owner": {
+                "login": "BurntSushi"
+            },
+            "description": "Recursively search directories",
+            "html_url": "https://github.com/BurntSushi/ripgrep",
+            "stargazers_count": 50000
+        }))
+        .unwrap();
+
+        assert_eq!(RepoDisplayItem::new(&found).to_string(), "BurntSushi/ripgrep: Recursively search directories");
+    }
+}
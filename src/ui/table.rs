@@ -0,0 +1,68 @@
+use prettytable::{Cell, Row, Table};
+
+/// Render rows of cells as a bordered terminal table, with `headers` as the first row
+pub fn render_table(headers: &[&str], rows: &[Vec<String>]) -> String {
+    let mut table = Table::new();
+    table.add_row(Row::new(headers.iter().map(|h| Cell::new(h)).collect()));
+
+    for row in rows {
+        table.add_row(Row::new(row.iter().map(|c| Cell::new(c)).collect()));
+    }
+
+    table.to_string()
+}
+
+/// Print a table rendered by [`render_table`] to stdout
+pub fn print_table(headers: &[&str], rows: &[Vec<String>]) {
+    print!("{}", render_table(headers, rows));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HEADERS: &[&str] = &["ID", "Name", "Full Name", "Description", "URL"];
+
+    fn row(cells: &[&str]) -> Vec<String> {
+        cells.iter().map(|c| c.to_string()).collect()
+    }
+
+    #[test]
+    fn test_render_table() {
+        let rows = vec![
+            row(&["1296269", "Hello-World", "octocat/Hello-World", "My first repository on GitHub!", "https://github.com/octocat/Hello-World"]),
+            row(&["724712", "rust", "rust-lang/rust", "Empowering everyone to build reliable and efficient software.", "https://github.com/rust-lang/rust"]),
+        ];
+
+        insta::assert_snapshot!(render_table(HEADERS, &rows));
+    }
+
+    #[test]
+    fn test_render_table_unicode() {
+        let rows = vec![
+            row(&["1", "星标", "用户/星标", "获取 GitHub 仓库的 star 数量 🚀", "https://github.com/用户/星标"]),
+            row(&["2", "café", "zoë/café", "Ünïcödé — with combining é and emoji 👩‍💻", "https://github.com/zoe/cafe"]),
+        ];
+
+        insta::assert_snapshot!(render_table(HEADERS, &rows));
+    }
+
+    #[test]
+    fn test_render_table_empty_and_missing_values() {
+        let rows = vec![
+            row(&["3", "no-description", "octocat/no-description", "", "https://github.com/octocat/no-description"]),
+        ];
+
+        insta::assert_snapshot!(render_table(HEADERS, &rows));
+        insta::assert_snapshot!("no_rows", render_table(HEADERS, &[]));
+    }
+
+    #[test]
+    fn test_render_table_huge_numbers() {
+        let rows = vec![
+            row(&[&u64::MAX.to_string(), "huge", "octocat/huge", "Largest possible id", "https://github.com/octocat/huge"]),
+        ];
+
+        insta::assert_snapshot!(render_table(HEADERS, &rows));
+    }
+}
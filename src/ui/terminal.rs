@@ -0,0 +1,129 @@
+//!
+//! Keeping the terminal usable
+//! Prompts hide the cursor and switch the terminal to raw mode while they read keys, and full-screen
+//! views draw on the alternate screen. If the program panics or exits in between, the shell is left
+//! without a cursor or echo, so the terminal state saved when a `TerminalGuard` is created is put
+//! back on the way out, panics included.
+//!
+
+use std::io;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::OnceLock;
+use console::{pad_str, Alignment, Term};
+
+#[cfg(unix)]
+static SAVED_MODE: OnceLock<Option<libc::termios>> = OnceLock::new();
+#[cfg(not(unix))]
+static SAVED_MODE: OnceLock<Option<()>> = OnceLock::new();
+
+static ALTERNATE_SCREEN: AtomicBool = AtomicBool::new(false);
+
+/// Restores the terminal when dropped, wrapping code that shows prompts
+pub struct TerminalGuard {
+    _private: (),
+}
+
+impl TerminalGuard {
+    pub fn new() -> Self {
+        SAVED_MODE.get_or_init(saved_mode);
+        TerminalGuard { _private: () }
+    }
+}
+
+impl Default for TerminalGuard {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Drop for TerminalGuard {
+    fn drop(&mut self) {
+        restore_terminal();
+    }
+}
+
+#[cfg(unix)]
+fn saved_mode() -> Option<libc::termios> {
+    // SAFETY: tcgetattr only writes into the zeroed termios it is given
+    unsafe {
+        if libc::isatty(libc::STDIN_FILENO) != 1 {
+            return None;
+        }
+        let mut mode: libc::termios = std::mem::zeroed();
+        (libc::tcgetattr(libc::STDIN_FILENO, &mut mode) == 0).then_some(mode)
+    }
+}
+
+#[cfg(not(unix))]
+fn saved_mode() -> Option<()> {
+    None
+}
+
+/// Draw on the alternate screen, leaving the shell's scrollback as it was until it is left
+pub fn enter_alternate_screen() -> io::Result<()> {
+    Term::stdout().write_str("\x1b[?1049h")?;
+    ALTERNATE_SCREEN.store(true, Ordering::SeqCst);
+    Ok(())
+}
+
+/// Go back to the normal screen if the alternate screen is in use
+pub fn leave_alternate_screen() {
+    if ALTERNATE_SCREEN.swap(false, Ordering::SeqCst) {
+        let _ = Term::stdout().write_str("\x1b[?1049l");
+    }
+}
+
+/// Leave raw mode if a guard saved the terminal's mode, leave the alternate screen and show the
+/// cursor again
+pub fn restore_terminal() {
+    leave_alternate_screen();
+
+    #[cfg(unix)]
+    if let Some(Some(mode)) = SAVED_MODE.get() {
+        // SAFETY: mode is a termios read by tcgetattr for the same descriptor
+        unsafe {
+            libc::tcsetattr(libc::STDIN_FILENO, libc::TCSANOW, mode);
+        }
+    }
+
+    for term in [Term::stdout(), Term::stderr()] {
+        if term.is_term() {
+            let _ = term.show_cursor();
+        }
+    }
+}
+
+/// Restore the terminal before a panic message is printed, and start the message on a clean line
+/// rather than after a half-drawn prompt
+pub fn install_panic_hook() {
+    let default_hook = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        restore_terminal();
+        let term = Term::stderr();
+        if term.is_term() {
+            let _ = term.clear_line();
+        }
+        default_hook(info);
+    }));
+}
+
+/// Pad or cut text to exactly `width` columns, for lines of full-screen views
+pub(crate) fn fit(text: &str, width: usize) -> String {
+    pad_str(text, width, Alignment::Left, Some("…")).into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_guard_saves_mode_once() {
+        let guard = TerminalGuard::new();
+        let saved = SAVED_MODE.get().map(Option::is_some);
+        drop(guard);
+        drop(TerminalGuard::new());
+
+        assert!(saved.is_some());
+        assert_eq!(SAVED_MODE.get().map(Option::is_some), saved);
+    }
+}
@@ -0,0 +1,453 @@
+//!
+//! Starred repositories as a terminal treemap
+//! Stars are grouped by language or topic and each group gets a tile whose area follows the
+//! stargazers of its repositories, so the shape of a collection shows at a glance. Like the
+//! browser, the map only keeps state and draws frames; the caller reads keys and opens groups.
+//!
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::str::FromStr;
+use console::{style, Key};
+use crate::api::stars::StarredRepo;
+use crate::ui::terminal::fit;
+use crate::ui::badge::format_count;
+
+/// Names accepted by `map --by`
+pub const MAP_GROUPS: &[&str] = &["language", "topic", "owner"];
+
+/// Keys shown in the status bar
+const KEY_HELP: &str = "←↑↓→ move  enter open  q quit";
+
+// Groups beyond this many are merged into one tile, as smaller ones would be too thin to read
+const MAX_TILES: usize = 24;
+
+/// What repositories are grouped by
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MapGroup {
+    Language,
+    Topic,
+    /// The user or organization, which every repository has
+    Owner,
+}
+
+impl MapGroup {
+    // Groups a repository belongs to, or the group for repositories without any
+    fn names(&self, repo: &StarredRepo) -> Vec<String> {
+        let names: Vec<String> = match self {
+            MapGroup::Language => repo.language.clone().into_iter().collect(),
+            MapGroup::Topic => repo.topics.clone(),
+            MapGroup::Owner => vec![repo.owner.login.clone()],
+        };
+        if names.is_empty() {
+            let none = match self {
+                MapGroup::Language => "No language",
+                MapGroup::Topic => "No topics",
+                MapGroup::Owner => "No owner",
+            };
+            return vec![none.to_string()];
+        }
+        names
+    }
+}
+
+impl FromStr for MapGroup {
+    type Err = String;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        match name {
+            "language" => Ok(MapGroup::Language),
+            "topic" => Ok(MapGroup::Topic),
+            "owner" => Ok(MapGroup::Owner),
+            _ => Err(format!("Unknown grouping {:?}, expected one of {}", name, MAP_GROUPS.join(", "))),
+        }
+    }
+}
+
+/// Repositories sharing a language or topic
+#[derive(Debug, Clone, PartialEq)]
+pub struct Cluster {
+    pub name: String,
+    /// Most starred first
+    pub repos: Vec<StarredRepo>,
+    /// Stargazers of all repositories in the cluster
+    pub stars: u64,
+}
+
+/// Group `repos`, biggest cluster first. A repository with several topics is in each of them.
+pub fn clusters(repos: &[StarredRepo], by: MapGroup) -> Vec<Cluster> {
+    let mut groups: BTreeMap<String, Vec<StarredRepo>> = BTreeMap::new();
+    for repo in repos {
+        for name in by.names(repo) {
+            groups.entry(name).or_default().push(repo.clone());
+        }
+    }
+
+    let mut clusters: Vec<Cluster> = groups.into_iter().map(|(name, repos)| cluster(name, repos)).collect();
+    clusters.sort_by(|a, b| b.stars.cmp(&a.stars).then_with(|| b.repos.len().cmp(&a.repos.len())));
+
+    if clusters.len() > MAX_TILES {
+        let rest = clusters.split_off(MAX_TILES - 1);
+        let mut seen = BTreeSet::new();
+        let repos = rest
+            .iter()
+            .flat_map(|cluster| cluster.repos.iter())
+            .filter(|repo| seen.insert(repo.full_name.as_str()))
+            .cloned()
+            .collect();
+        clusters.push(cluster(format!("{} more", rest.len()), repos));
+    }
+    clusters
+}
+
+fn cluster(name: String, mut repos: Vec<StarredRepo>) -> Cluster {
+    repos.sort_by_key(|repo| std::cmp::Reverse(repo.stars));
+    let stars = repos.iter().map(|repo| repo.stars).sum();
+    Cluster { name, repos, stars }
+}
+
+/// Cells of the terminal, counted from the top left
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Rect {
+    pub x: usize,
+    pub y: usize,
+    pub width: usize,
+    pub height: usize,
+}
+
+impl Rect {
+    fn is_empty(&self) -> bool {
+        self.width == 0 || self.height == 0
+    }
+}
+
+/// Split `area` into one rectangle per weight, with areas in proportion to the weights. Each cut
+/// halves the remaining weight across the longer side, so tiles stay close to square.
+pub fn layout(weights: &[u64], area: Rect) -> Vec<Rect> {
+    let mut tiles = vec![Rect::default(); weights.len()];
+    let indexed: Vec<(usize, u64)> = weights.iter().map(|weight| (*weight).max(1)).enumerate().collect();
+    if !indexed.is_empty() {
+        split(&indexed, area, &mut tiles);
+    }
+    tiles
+}
+
+fn split(weights: &[(usize, u64)], area: Rect, tiles: &mut [Rect]) {
+    if let [(index, _)] = weights {
+        tiles[*index] = area;
+        return;
+    }
+
+    let total: u64 = weights.iter().map(|(_, weight)| weight).sum();
+    let mut before = 0;
+    let mut cut = 1;
+    for (position, (_, weight)) in weights.iter().enumerate().take(weights.len() - 1) {
+        before += weight;
+        cut = position + 1;
+        if before * 2 >= total {
+            break;
+        }
+    }
+    let share = |length: usize| ((length as u64 * before + total / 2) / total) as usize;
+
+    // Cells are about twice as tall as they are wide
+    let (first, second) = if area.width >= area.height * 2 {
+        let width = share(area.width);
+        (
+            Rect { width, ..area },
+            Rect { x: area.x + width, width: area.width - width, ..area },
+        )
+    } else {
+        let height = share(area.height);
+        (
+            Rect { height, ..area },
+            Rect { y: area.y + height, height: area.height - height, ..area },
+        )
+    };
+    split(&weights[..cut], first, tiles);
+    split(&weights[cut..], second, tiles);
+}
+
+/// What a key asks the caller to do
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MapAction {
+    /// Pick from the repositories of the selected cluster
+    Open,
+    Quit,
+}
+
+pub struct StarMap {
+    by: MapGroup,
+    clusters: Vec<Cluster>,
+    selected: usize,
+    /// Where each cluster was drawn in the last frame, for moving between neighbours
+    tiles: Vec<Rect>,
+}
+
+impl StarMap {
+    pub fn new(by: MapGroup, clusters: Vec<Cluster>) -> Self {
+        StarMap { by, clusters, selected: 0, tiles: Vec::new() }
+    }
+
+    pub fn selected(&self) -> Option<&Cluster> {
+        self.clusters.get(self.selected)
+    }
+
+    /// Move to the tile next to the selected one, or return the action a key stands for
+    pub fn handle_key(&mut self, key: &Key) -> Option<MapAction> {
+        let direction = match key {
+            Key::ArrowLeft | Key::Char('h') => Direction::Left,
+            Key::ArrowRight | Key::Char('l') => Direction::Right,
+            Key::ArrowUp | Key::Char('k') => Direction::Up,
+            Key::ArrowDown | Key::Char('j') => Direction::Down,
+            Key::Tab => {
+                self.selected = (self.selected + 1) % self.clusters.len().max(1);
+                return None;
+            }
+            Key::Enter | Key::Char('o') => return Some(MapAction::Open),
+            Key::Char('q') | Key::Escape | Key::CtrlC => return Some(MapAction::Quit),
+            _ => return None,
+        };
+
+        if let Some(next) = neighbour(&self.tiles, self.selected, direction) {
+            self.selected = next;
+        } else if self.tiles.is_empty() {
+            // Nothing drawn yet, so go by size
+            let last = self.clusters.len().saturating_sub(1);
+            self.selected = match direction {
+                Direction::Left | Direction::Up => self.selected.saturating_sub(1),
+                Direction::Right | Direction::Down => (self.selected + 1).min(last),
+            };
+        }
+        None
+    }
+
+    /// Lay the groups out as tiles filling `width` columns and `height` lines, with a title above
+    /// and the key help below, and the selected tile highlighted
+    pub fn render(&mut self, width: usize, height: usize) -> Vec<String> {
+        let rows = height.saturating_sub(2);
+        let weights: Vec<u64> = self.clusters.iter().map(|cluster| cluster.stars).collect();
+        self.tiles = layout(&weights, Rect { x: 0, y: 0, width, height: rows });
+
+        let mut canvas = vec![vec![' '; width]; rows];
+        for (cluster, tile) in self.clusters.iter().zip(&self.tiles) {
+            draw_tile(&mut canvas, cluster, *tile);
+        }
+
+        let by = match self.by {
+            MapGroup::Language => "language",
+            MapGroup::Topic => "topic",
+            MapGroup::Owner => "owner",
+        };
+        let repos: usize = self.clusters.iter().map(|cluster| cluster.repos.len()).sum();
+        let mut lines = Vec::with_capacity(height);
+        let title = format!("Stars by {} ({} groups)", by, self.clusters.len());
+        lines.push(style(fit(&title, width)).bold().to_string());
+
+        let selected = self.tiles.get(self.selected).copied().unwrap_or_default();
+        for (y, row) in canvas.iter().enumerate() {
+            let line: String = row.iter().collect();
+            if y < selected.y || y >= selected.y + selected.height || selected.is_empty() {
+                lines.push(line);
+                continue;
+            }
+            let before: String = row[..selected.x].iter().collect();
+            let tile: String = row[selected.x..selected.x + selected.width].iter().collect();
+            let after: String = row[selected.x + selected.width..].iter().collect();
+            lines.push(format!("{}{}{}", before, style(tile).reverse(), after));
+        }
+        if self.clusters.is_empty() && rows > 0 {
+            lines[1] = fit("  No starred repositories", width);
+        }
+
+        let status = match self.selected() {
+            Some(cluster) => format!("{}: {} repositories, {} stars", cluster.name, cluster.repos.len(), cluster.stars),
+            None => format!("{} repositories", repos),
+        };
+        lines.push(style(fit(&format!("{} | {}", status, KEY_HELP), width)).reverse().to_string());
+
+        lines.truncate(height);
+        lines
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Direction {
+    Left,
+    Right,
+    Up,
+    Down,
+}
+
+// The closest tile on the `direction` side of tile `from`, preferring ones lined up with it
+fn neighbour(tiles: &[Rect], from: usize, direction: Direction) -> Option<usize> {
+    let current = tiles.get(from)?;
+    // Doubled so centres stay whole cells
+    let centre = |tile: &Rect| (tile.x * 2 + tile.width, tile.y * 2 + tile.height);
+    let (cx, cy) = centre(current);
+
+    tiles
+        .iter()
+        .enumerate()
+        .filter(|(index, tile)| *index != from && !tile.is_empty())
+        .filter_map(|(index, tile)| {
+            let (x, y) = centre(tile);
+            let (gap, offset) = match direction {
+                Direction::Left => (current.x.checked_sub(tile.x + tile.width)?, cy.abs_diff(y)),
+                Direction::Right => (tile.x.checked_sub(current.x + current.width)?, cy.abs_diff(y)),
+                Direction::Up => (current.y.checked_sub(tile.y + tile.height)?, cx.abs_diff(x)),
+                Direction::Down => (tile.y.checked_sub(current.y + current.height)?, cx.abs_diff(x)),
+            };
+            Some(((gap, offset), index))
+        })
+        .min()
+        .map(|(_, index)| index)
+}
+
+// A box with the cluster's name, stars and size inside, or a shaded block if there is no room
+fn draw_tile(canvas: &mut [Vec<char>], cluster: &Cluster, tile: Rect) {
+    if tile.is_empty() {
+        return;
+    }
+    if tile.width < 2 || tile.height < 2 {
+        for row in &mut canvas[tile.y..tile.y + tile.height] {
+            row[tile.x..tile.x + tile.width].fill('▒');
+        }
+        return;
+    }
+
+    let (right, bottom) = (tile.x + tile.width - 1, tile.y + tile.height - 1);
+    canvas[tile.y][tile.x..=right].fill('─');
+    canvas[bottom][tile.x..=right].fill('─');
+    for row in &mut canvas[tile.y..=bottom] {
+        row[tile.x] = '│';
+        row[right] = '│';
+    }
+    canvas[tile.y][tile.x] = '┌';
+    canvas[tile.y][right] = '┐';
+    canvas[bottom][tile.x] = '└';
+    canvas[bottom][right] = '┘';
+
+    let labels = [
+        cluster.name.clone(),
+        format!("★ {}", format_count(cluster.stars)),
+        format!("{} repos", cluster.repos.len()),
+    ];
+    let inner = tile.width - 2;
+    for (line, label) in labels.iter().take(tile.height - 2).enumerate() {
+        let text: Vec<char> = label.chars().collect();
+        let text: Vec<char> = if text.len() > inner {
+            text[..inner.saturating_sub(1)].iter().copied().chain(std::iter::once('…')).take(inner).collect()
+        } else {
+            text
+        };
+        let row = &mut canvas[tile.y + 1 + line];
+        row[tile.x + 1..tile.x + 1 + text.len()].copy_from_slice(&text);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use console::measure_text_width;
+    use serde_json::json;
+
+    fn repo(full_name: &str, language: Option<&str>, topics: &[&str], stars: u64) -> StarredRepo {
+        let (owner, name) = full_name.split_once('/').unwrap();
+        serde_json::from_value(json!({
+            "id": stars, "name": name, "full_name": full_name, "owner": { "login": owner }, "description": null,
+            "html_url": format!("https://github.com/{}", full_name), "language": language, "topics": topics,
+            "stargazers_count": stars
+        }))
+        .unwrap()
+    }
+
+    fn repos() -> Vec<StarredRepo> {
+        vec![
+            repo("rust-lang/rust", Some("Rust"), &["compiler"], 90),
+            repo("rust-lang/cargo", Some("Rust"), &["cli"], 10),
+            repo("vuejs/vue", Some("TypeScript"), &[], 60),
+            repo("BurntSushi/ripgrep", Some("Rust"), &["cli"], 40),
+            repo("torvalds/linux", Some("C"), &[], 30),
+            repo("octocat/Spoon-Knife", None, &[], 0),
+        ]
+    }
+
+    #[test]
+    fn test_clusters() {
+        let by_language = clusters(&repos(), MapGroup::Language);
+        let names: Vec<&str> = by_language.iter().map(|cluster| cluster.name.as_str()).collect();
+        assert_eq!(names, ["Rust", "TypeScript", "C", "No language"]);
+        assert_eq!(by_language[0].stars, 140);
+        assert_eq!(by_language[0].repos[1].full_name, "BurntSushi/ripgrep");
+
+        let by_topic = clusters(&repos(), MapGroup::Topic);
+        let names: Vec<&str> = by_topic.iter().map(|cluster| cluster.name.as_str()).collect();
+        assert_eq!(names, ["No topics", "compiler", "cli"]);
+
+        let by_owner = clusters(&repos(), MapGroup::Owner);
+        assert_eq!(by_owner[0].name, "rust-lang");
+        assert_eq!(by_owner[0].repos.len(), 2);
+        assert!("license".parse::<MapGroup>().is_err());
+    }
+
+    #[test]
+    fn test_clusters_merges_small_groups() {
+        let repos: Vec<StarredRepo> = (0..30)
+            .map(|n| repo(&format!("o/r{}", n), Some(&format!("L{}", n)), &[], 100 - n))
+            .collect();
+        let clusters = clusters(&repos, MapGroup::Language);
+
+        assert_eq!(clusters.len(), MAX_TILES);
+        assert_eq!(clusters[MAX_TILES - 1].name, "7 more");
+        assert_eq!(clusters[MAX_TILES - 1].repos.len(), 7);
+    }
+
+    #[test]
+    fn test_layout_fills_the_area_in_proportion() {
+        let area = Rect { x: 0, y: 0, width: 40, height: 10 };
+        let tiles = layout(&[300, 100, 100], area);
+
+        assert_eq!(tiles[0], Rect { x: 0, y: 0, width: 24, height: 10 });
+        assert_eq!(tiles[1], Rect { x: 24, y: 0, width: 16, height: 5 });
+        assert_eq!(tiles[2], Rect { x: 24, y: 5, width: 16, height: 5 });
+        assert_eq!(tiles.iter().map(|tile| tile.width * tile.height).sum::<usize>(), 400);
+        assert!(layout(&[], area).is_empty());
+    }
+
+    #[test]
+    fn test_handle_key_moves_between_neighbours() {
+        let mut map = StarMap::new(MapGroup::Language, clusters(&repos(), MapGroup::Language));
+        map.render(40, 12);
+        let tiles = map.tiles.clone();
+
+        assert_eq!(map.handle_key(&Key::ArrowLeft), None);
+        assert_eq!(map.selected().unwrap().name, "Rust");
+        map.handle_key(&Key::ArrowRight);
+        let right = map.selected;
+        assert_eq!(tiles[right].x, tiles[0].x + tiles[0].width);
+        map.handle_key(&Key::ArrowLeft);
+        assert_eq!(map.selected, 0);
+        assert_eq!(map.handle_key(&Key::Enter), Some(MapAction::Open));
+        assert_eq!(map.handle_key(&Key::Char('q')), Some(MapAction::Quit));
+    }
+
+    #[test]
+    fn test_render() {
+        console::set_colors_enabled(false);
+        let mut map = StarMap::new(MapGroup::Language, clusters(&repos(), MapGroup::Language));
+        let lines = map.render(48, 12);
+
+        assert!(lines.iter().all(|line| measure_text_width(line) == 48));
+        insta::assert_snapshot!(lines.join("\n"));
+    }
+
+    #[test]
+    fn test_render_empty() {
+        console::set_colors_enabled(false);
+        let lines = StarMap::new(MapGroup::Topic, Vec::new()).render(40, 5);
+
+        assert_eq!(lines.len(), 5);
+        assert!(lines[1].starts_with("  No starred"));
+        assert!(lines[4].starts_with("0 repositories"));
+    }
+}
@@ -0,0 +1,114 @@
+//!
+//! Bug report bundles
+//! After a failure, `--bug-report` gathers what a maintainer needs into one Markdown file that can
+//! be attached to an issue: versions, the command and its error, the failed request and the config
+//! without secrets. Known secrets are also scrubbed from everything else in the report.
+//!
+
+use std::fmt::Write as _;
+use crate::api::client::{FailedRequest, API_VERSION};
+
+const REDACTED: &str = "[REDACTED]";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BugReport {
+    pub version: String,
+    /// Arguments the command was run with
+    pub command: String,
+    pub error: String,
+    pub failed_request: Option<FailedRequest>,
+    /// The exported config, which already leaves out secrets
+    pub config: Option<String>,
+}
+
+impl BugReport {
+    /// Render the report as Markdown, replacing every secret with a placeholder
+    pub fn render(&self, secrets: &[&str]) -> String {
+        let mut report = String::new();
+        let _ = writeln!(report, "# stars_fetcher bug report\n");
+        let _ = writeln!(report, "- Version: {}", self.version);
+        let _ = writeln!(report, "- Platform: {} {}", std::env::consts::OS, std::env::consts::ARCH);
+        let _ = writeln!(report, "- GitHub API version: {}", API_VERSION);
+        let _ = writeln!(report, "- Command: `{}`", self.command);
+
+        let _ = writeln!(report, "\n## Error\n\n```\n{}\n```", self.error);
+
+        let _ = writeln!(report, "\n## Failed request\n");
+        match &self.failed_request {
+            Some(request) => {
+                let _ = writeln!(report, "- Method: {}", request.method.as_deref().unwrap_or("unknown"));
+                let _ = writeln!(report, "- URL: {}", request.url);
+                let _ = writeln!(report, "- Status: {}", request.status);
+                let _ = writeln!(report, "- Request ID: {}", request.request_id.as_deref().unwrap_or("none"));
+            }
+            None => {
+                let _ = writeln!(report, "No request was answered with an error status.");
+            }
+        }
+
+        let _ = writeln!(report, "\n## Config (secrets removed)\n");
+        match &self.config {
+            Some(config) => {
+                let _ = writeln!(report, "```toml\n{}```", config);
+            }
+            None => {
+                let _ = writeln!(report, "No config could be read.");
+            }
+        }
+
+        redact(&report, secrets)
+    }
+}
+
+/// Replace every occurrence of the non-empty `secrets` in `text`
+pub fn redact(text: &str, secrets: &[&str]) -> String {
+    secrets
+        .iter()
+        .filter(|secret| !secret.is_empty())
+        .fold(text.to_string(), |text, secret| text.replace(secret, REDACTED))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn report() -> BugReport {
+        BugReport {
+            version: "0.1.0".to_string(),
+            command: "list --filter rusty".to_string(),
+            error: "GitHub API error (401 Unauthorized): Bad credentials for ghp_secret".to_string(),
+            failed_request: Some(FailedRequest {
+                method: Some("GET".to_string()),
+                url: "https://api.github.com/user/starred".to_string(),
+                status: 401,
+                request_id: Some("C0DE:1234".to_string()),
+            }),
+            config: Some("[github]\nemail = \"me@example.com\"\napi_url = \"https://api.github.com\"\n".to_string()),
+        }
+    }
+
+    #[test]
+    fn test_render() {
+        let rendered = report().render(&["ghp_secret"]);
+
+        assert!(rendered.contains("- Command: `list --filter rusty`"));
+        assert!(rendered.contains("- Status: 401"));
+        assert!(rendered.contains("- Request ID: C0DE:1234"));
+        assert!(rendered.contains("email = \"me@example.com\""));
+        assert!(rendered.contains("Bad credentials for [REDACTED]"));
+        assert!(!rendered.contains("ghp_secret"));
+    }
+
+    #[test]
+    fn test_render_without_request_or_config() {
+        let rendered = BugReport { failed_request: None, config: None, ..report() }.render(&[]);
+
+        assert!(rendered.contains("No request was answered with an error status."));
+        assert!(rendered.contains("No config could be read."));
+    }
+
+    #[test]
+    fn test_redact_ignores_empty_secrets() {
+        assert_eq!(redact("token abc", &["", "abc"]), "token [REDACTED]");
+    }
+}
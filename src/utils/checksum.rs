@@ -0,0 +1,145 @@
+//!
+//! Checksums of release assets
+//! Releases publish SHA-256 checksums either per asset (`<asset>.sha256`) or as one list covering
+//! every asset (`SHA256SUMS`, `checksums.txt`), in coreutils `<hex>  <name>` or BSD
+//! `SHA256 (<name>) = <hex>` format.
+//!
+
+use sha2::{Digest, Sha256};
+
+/// Outcome of checking a downloaded asset against its published checksum
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Verification {
+    /// The asset matches the checksum from the named file, whose contents are kept so a signature
+    /// over it is checked against the same bytes
+    Verified { checksum_file: String, contents: Vec<u8> },
+    Mismatch { checksum_file: String, expected: String, actual: String },
+    /// The release publishes no checksum for the asset
+    Unavailable,
+}
+
+/// Whether an asset is a checksum file rather than something to download for its own sake
+pub fn is_checksum_file(name: &str) -> bool {
+    let name = name.to_lowercase();
+    name.ends_with(".sha256")
+        || name.ends_with(".sha256sum")
+        || name.contains("sha256sums")
+        || name.ends_with("checksums.txt")
+}
+
+/// Checksum files among `names` that may cover `asset`, the asset's own file first
+pub fn checksum_files_for<'a>(asset: &str, names: &[&'a str]) -> Vec<&'a str> {
+    let own = [format!("{}.sha256", asset), format!("{}.sha256sum", asset)];
+    let (mut specific, shared): (Vec<&str>, Vec<&str>) = names
+        .iter()
+        .filter(|name| is_checksum_file(name))
+        .partition(|name| own.iter().any(|own| own.eq_ignore_ascii_case(name)));
+
+    // Another asset's own checksum file can't cover this one
+    specific.extend(shared.into_iter().filter(|name| {
+        let name = name.to_lowercase();
+        !name.ends_with(".sha256") && !name.ends_with(".sha256sum")
+    }));
+    specific
+}
+
+fn is_sha256(hex: &str) -> bool {
+    hex.len() == 64 && hex.chars().all(|c| c.is_ascii_hexdigit())
+}
+
+/// Find the checksum for `asset` in a checksum file. A file holding a single bare checksum is
+/// taken to be the asset's own.
+pub fn expected_checksum(text: &str, asset: &str) -> Option<String> {
+    let lines: Vec<&str> = text.lines().map(str::trim).filter(|line| !line.is_empty()).collect();
+
+    for line in &lines {
+        // BSD style: SHA256 (name) = hex
+        if let Some(rest) = line.strip_prefix("SHA256 (") {
+            if let Some((name, hex)) = rest.split_once(") = ") {
+                if name == asset && is_sha256(hex.trim()) {
+                    return Some(hex.trim().to_lowercase());
+                }
+            }
+            continue;
+        }
+
+        // coreutils style: hex  name, with `*` marking binary mode
+        let mut parts = line.split_whitespace();
+        let (Some(hex), name) = (parts.next(), parts.next()) else { continue };
+        if !is_sha256(hex) {
+            continue;
+        }
+        match name {
+            Some(name) => {
+                let name = name.trim_start_matches('*');
+                if name == asset || name.rsplit('/').next() == Some(asset) {
+                    return Some(hex.to_lowercase());
+                }
+            }
+            None if lines.len() == 1 => return Some(hex.to_lowercase()),
+            None => {}
+        }
+    }
+
+    None
+}
+
+pub fn sha256_hex(bytes: &[u8]) -> String {
+    format!("{:x}", Sha256::digest(bytes))
+}
+
+/// Compare a downloaded asset with the checksum found in `checksum_file`, which holds `contents`
+pub fn verify(bytes: &[u8], expected: &str, checksum_file: &str, contents: &[u8]) -> Verification {
+    let actual = sha256_hex(bytes);
+    if actual.eq_ignore_ascii_case(expected) {
+        Verification::Verified { checksum_file: checksum_file.to_string(), contents: contents.to_vec() }
+    } else {
+        Verification::Mismatch {
+            checksum_file: checksum_file.to_string(),
+            expected: expected.to_string(),
+            actual,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HELLO_SHA256: &str = "2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824";
+
+    #[test]
+    fn test_checksum_files_for() {
+        let names = [
+            "tool-linux.tar.gz", "tool-linux.tar.gz.sha256", "tool-macos.tar.gz.sha256", "SHA256SUMS", "tool_checksums.txt",
+        ];
+
+        assert_eq!(
+            checksum_files_for("tool-linux.tar.gz", &names),
+            vec!["tool-linux.tar.gz.sha256", "SHA256SUMS", "tool_checksums.txt"]
+        );
+        assert!(!is_checksum_file("tool-linux.tar.gz"));
+    }
+
+    #[test]
+    fn test_expected_checksum_formats() {
+        let coreutils = format!("{}  tool-macos.tar.gz\n{} *dist/tool-linux.tar.gz\n", "0".repeat(64), HELLO_SHA256);
+        let bsd = format!("SHA256 (tool-linux.tar.gz) = {}\n", HELLO_SHA256.to_uppercase());
+        let bare = format!("{}\n", HELLO_SHA256);
+
+        assert_eq!(expected_checksum(&coreutils, "tool-linux.tar.gz").as_deref(), Some(HELLO_SHA256));
+        assert_eq!(expected_checksum(&bsd, "tool-linux.tar.gz").as_deref(), Some(HELLO_SHA256));
+        assert_eq!(expected_checksum(&bare, "tool-linux.tar.gz").as_deref(), Some(HELLO_SHA256));
+        assert_eq!(expected_checksum(&coreutils, "tool-windows.zip"), None);
+        assert_eq!(expected_checksum("not a checksum", "tool-linux.tar.gz"), None);
+    }
+
+    #[test]
+    fn test_verify() {
+        assert_eq!(
+            verify(b"hello", HELLO_SHA256, "SHA256SUMS", b"sums"),
+            Verification::Verified { checksum_file: "SHA256SUMS".to_string(), contents: b"sums".to_vec() }
+        );
+        assert!(matches!(verify(b"tampered", HELLO_SHA256, "SHA256SUMS", b"sums"), Verification::Mismatch { .. }));
+    }
+}
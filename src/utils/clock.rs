@@ -0,0 +1,62 @@
+//!
+//! Times in the local time zone
+//! GitHub reports times in UTC. Times meant to be read, such as when a rate limit resets, are shown
+//! in the system's time zone as the C library sees it, and in UTC where it can't be looked up.
+//!
+
+use crate::utils::recall::date_of;
+
+/// `seconds` since the Unix epoch in the local time zone, such as `2024-05-01 14:00:00 +02:00`
+pub fn local_time(seconds: u64) -> String {
+    format_time(seconds, utc_offset(seconds))
+}
+
+/// `seconds` since the Unix epoch at `offset` seconds east of UTC
+pub fn format_time(seconds: u64, offset: i64) -> String {
+    let local = seconds.saturating_add_signed(offset);
+    let offset_minutes = offset.unsigned_abs() / 60;
+    format!(
+        "{} {:02}:{:02}:{:02} {}{:02}:{:02}",
+        date_of(local),
+        local / 3600 % 24,
+        local / 60 % 60,
+        local % 60,
+        if offset < 0 { '-' } else { '+' },
+        offset_minutes / 60,
+        offset_minutes % 60,
+    )
+}
+
+// Seconds east of UTC of the local time zone at `seconds` since the Unix epoch, following TZ
+#[cfg(unix)]
+#[allow(clippy::useless_conversion)] // tm_gmtoff is a c_long, 32 bits on some targets
+fn utc_offset(seconds: u64) -> i64 {
+    let Ok(time) = libc::time_t::try_from(seconds) else {
+        return 0;
+    };
+    // SAFETY: localtime_r only writes into the zeroed tm it is given
+    unsafe {
+        let mut tm: libc::tm = std::mem::zeroed();
+        if libc::localtime_r(&time, &mut tm).is_null() {
+            return 0;
+        }
+        i64::from(tm.tm_gmtoff)
+    }
+}
+
+#[cfg(not(unix))]
+fn utc_offset(_seconds: u64) -> i64 {
+    0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_time() {
+        assert_eq!(format_time(1_700_000_000, 0), "2023-11-14 22:13:20 +00:00");
+        assert_eq!(format_time(1_700_000_000, 2 * 3600), "2023-11-15 00:13:20 +02:00");
+        assert_eq!(format_time(1_700_000_000, -(5 * 3600 + 1800)), "2023-11-14 16:43:20 -05:30");
+    }
+}
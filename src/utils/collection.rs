@@ -0,0 +1,95 @@
+//!
+//! Team-shared star collections
+//! A collection is a plain text file, usually kept in a gist, listing one repository per line.
+//! Blank lines are ignored and `#` starts a comment.
+//!
+
+use crate::utils::parse::{parse_repo_spec, RepoRef};
+
+/// Repositories listed in a collection, plus the lines that couldn't be parsed
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct Collection {
+    pub repos: Vec<RepoRef>,
+    pub invalid_lines: Vec<String>,
+}
+
+/// Parse a collection file, keeping the first occurrence of each repository
+pub fn parse_collection(text: &str) -> Collection {
+    let mut collection = Collection::default();
+
+    for line in text.lines() {
+        let line = line.split('#').next().unwrap_or_default().trim();
+        if line.is_empty() {
+            continue;
+        }
+
+        match parse_repo_spec(line) {
+            Ok(repo) if !collection.repos.contains(&repo) => collection.repos.push(repo),
+            Ok(_) => {}
+            Err(_) => collection.invalid_lines.push(line.to_string()),
+        }
+    }
+
+    collection
+}
+
+/// Accept either a bare gist ID or a gist URL such as `https://gist.github.com/user/<id>`
+pub fn gist_id(spec: &str) -> &str {
+    spec.trim()
+        .trim_end_matches('/')
+        .rsplit('/')
+        .next()
+        .unwrap_or_default()
+}
+
+/// The comment proposing a repository for a collection, with the line to add
+pub fn proposal_comment(repo: &RepoRef, reason: Option<&str>) -> String {
+    let mut comment = format!(
+        "Proposing https://github.com/{} for this collection. Line to add:\n\n```\n{}\n```\n",
+        repo, repo
+    );
+    if let Some(reason) = reason {
+        comment.push_str(&format!("\n{}\n", reason));
+    }
+
+    comment
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_collection() {
+        let text = "\
+# Team favourites
+rust-lang/rust
+https://github.com/tokio-rs/tokio   # async runtime
+
+not a repo
+rust-lang/rust
+";
+        let collection = parse_collection(text);
+
+        assert_eq!(
+            collection.repos,
+            vec![RepoRef::new("rust-lang", "rust").unwrap(), RepoRef::new("tokio-rs", "tokio").unwrap()]
+        );
+        assert_eq!(collection.invalid_lines, vec!["not a repo".to_string()]);
+    }
+
+    #[test]
+    fn test_gist_id() {
+        assert_eq!(gist_id("aa5a315d61ae9438b18d"), "aa5a315d61ae9438b18d");
+        assert_eq!(gist_id("https://gist.github.com/octocat/aa5a315d61ae9438b18d/"), "aa5a315d61ae9438b18d");
+    }
+
+    #[test]
+    fn test_proposal_comment() {
+        let repo = RepoRef::new("tokio-rs", "tokio").unwrap();
+        let comment = proposal_comment(&repo, Some("We use it everywhere"));
+
+        assert!(comment.contains("```\ntokio-rs/tokio\n```"));
+        assert!(comment.ends_with("We use it everywhere\n"));
+    }
+}
@@ -0,0 +1,373 @@
+//!
+//! Filters for repository lists
+//! A filter combines terms with `AND`, `OR`, `NOT` and parentheses, e.g.
+//! `language:rust AND (topic:cli OR topic:tui) NOT archived`. Terms next to each other must
+//! both match, so `language:rust stars:>500` needs no `AND`. Terms are checked against
+//! starred repositories as the API lists them.
+//!
+
+use std::error::Error;
+use crate::api::stars::StarredRepo;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StarsRange {
+    Exactly(u64),
+    AtLeast(u64),
+    AtMost(u64),
+    Between(u64, u64),
+}
+
+impl StarsRange {
+    fn contains(&self, stars: u64) -> bool {
+        match *self {
+            StarsRange::Exactly(n) => stars == n,
+            StarsRange::AtLeast(n) => stars >= n,
+            StarsRange::AtMost(n) => stars <= n,
+            StarsRange::Between(low, high) => (low..=high).contains(&stars),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Term {
+    Language(String),
+    Topic(String),
+    Owner(String),
+    Stars(StarsRange),
+    Archived(bool),
+    Fork(bool),
+    /// Free text found in the name or description
+    Text(String),
+}
+
+impl Term {
+    fn matches(&self, repo: &StarredRepo) -> bool {
+        let text = |field: Option<&str>| field.unwrap_or_default().to_lowercase();
+
+        match self {
+            Term::Language(language) => text(repo.language.as_deref()) == *language,
+            Term::Topic(topic) => repo.topics.contains(topic),
+            Term::Owner(owner) => repo.owner.login.to_lowercase() == *owner,
+            Term::Stars(range) => range.contains(repo.stars),
+            Term::Archived(archived) => repo.archived == *archived,
+            Term::Fork(fork) => repo.fork == *fork,
+            Term::Text(word) => {
+                text(Some(&repo.full_name)).contains(word.as_str()) || text(repo.description.as_deref()).contains(word.as_str())
+            }
+        }
+    }
+}
+
+/// A parsed filter
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Filter {
+    Term(Term),
+    And(Box<Filter>, Box<Filter>),
+    Or(Box<Filter>, Box<Filter>),
+    Not(Box<Filter>),
+}
+
+impl Filter {
+    pub fn matches(&self, repo: &StarredRepo) -> bool {
+        match self {
+            Filter::Term(term) => term.matches(repo),
+            Filter::And(left, right) => left.matches(repo) && right.matches(repo),
+            Filter::Or(left, right) => left.matches(repo) || right.matches(repo),
+            Filter::Not(filter) => !filter.matches(repo),
+        }
+    }
+}
+
+fn parse_count(value: &str) -> Result<u64, Box<dyn Error>> {
+    value.parse().map_err(|_| format!("Invalid star count: {:?}", value).into())
+}
+
+fn parse_stars(value: &str) -> Result<StarsRange, Box<dyn Error>> {
+    if let Some(n) = value.strip_prefix(">=") {
+        Ok(StarsRange::AtLeast(parse_count(n)?))
+    } else if let Some(n) = value.strip_prefix('>') {
+        Ok(StarsRange::AtLeast(parse_count(n)?.saturating_add(1)))
+    } else if let Some(n) = value.strip_prefix("<=") {
+        Ok(StarsRange::AtMost(parse_count(n)?))
+    } else if let Some(n) = value.strip_prefix('<') {
+        Ok(StarsRange::AtMost(parse_count(n)?.checked_sub(1).ok_or("stars:<0 can never match")?))
+    } else if let Some((low, high)) = value.split_once("..") {
+        Ok(StarsRange::Between(parse_count(low)?, parse_count(high)?))
+    } else {
+        Ok(StarsRange::Exactly(parse_count(value)?))
+    }
+}
+
+fn parse_bool(key: &str, value: &str) -> Result<bool, Box<dyn Error>> {
+    match value {
+        "true" | "yes" => Ok(true),
+        "false" | "no" => Ok(false),
+        _ => Err(format!("Expected {}:true or {}:false, got {}:{}", key, key, key, value).into()),
+    }
+}
+
+fn parse_term(term: &str) -> Result<Term, Box<dyn Error>> {
+    let term = term.to_lowercase();
+    let Some((key, value)) = term.split_once(':') else {
+        return Ok(match term.as_str() {
+            "archived" => Term::Archived(true),
+            "fork" => Term::Fork(true),
+            _ => Term::Text(term),
+        });
+    };
+    if value.is_empty() {
+        return Err(format!("Missing value for {}:", key).into());
+    }
+
+    match key {
+        "language" | "lang" => Ok(Term::Language(value.to_string())),
+        "topic" => Ok(Term::Topic(value.to_string())),
+        "user" | "owner" | "org" => Ok(Term::Owner(value.to_string())),
+        "stars" => Ok(Term::Stars(parse_stars(value)?)),
+        "archived" => Ok(Term::Archived(parse_bool(key, value)?)),
+        "fork" => Ok(Term::Fork(parse_bool(key, value)?)),
+        _ => Err(format!("Unknown filter {:?}, expected language, topic, user, stars, archived or fork", key).into()),
+    }
+}
+
+fn tokenize(input: &str) -> Vec<&str> {
+    let mut tokens = Vec::new();
+    for word in input.split_whitespace() {
+        let mut rest = word;
+        while !rest.is_empty() {
+            let end = rest.find(['(', ')']).map(|i| if i == 0 { 1 } else { i }).unwrap_or(rest.len());
+            tokens.push(&rest[..end]);
+            rest = &rest[end..];
+        }
+    }
+    tokens
+}
+
+// Recursive descent over the grammar, loosest binding first:
+//   or    := and ("OR" and)*
+//   and   := unary ("AND"? unary)*
+//   unary := "NOT" unary | "(" or ")" | term
+// Nesting is limited so hostile input can't overflow the stack.
+struct Parser<'a> {
+    tokens: Vec<&'a str>,
+    position: usize,
+    depth: usize,
+}
+
+// Deep enough for any filter a person writes, shallow enough to never overflow the stack
+const MAX_DEPTH: usize = 32;
+
+impl<'a> Parser<'a> {
+    fn peek(&self) -> Option<&'a str> {
+        self.tokens.get(self.position).copied()
+    }
+
+    fn next(&mut self) -> Option<&'a str> {
+        let token = self.peek();
+        self.position += 1;
+        token
+    }
+
+    fn or(&mut self) -> Result<Filter, Box<dyn Error>> {
+        let mut filter = self.and()?;
+        while self.peek() == Some("OR") {
+            self.next();
+            filter = Filter::Or(Box::new(filter), Box::new(self.and()?));
+        }
+        Ok(filter)
+    }
+
+    fn and(&mut self) -> Result<Filter, Box<dyn Error>> {
+        let mut filter = self.unary()?;
+        loop {
+            match self.peek() {
+                Some("AND") => {
+                    self.next();
+                }
+                Some("OR") | Some(")") | None => return Ok(filter),
+                Some(_) => {}
+            }
+            filter = Filter::And(Box::new(filter), Box::new(self.unary()?));
+        }
+    }
+
+    fn unary(&mut self) -> Result<Filter, Box<dyn Error>> {
+        self.depth += 1;
+        if self.depth > MAX_DEPTH {
+            return Err("Filter is nested too deeply".into());
+        }
+
+        let filter = self.primary();
+        self.depth -= 1;
+        filter
+    }
+
+    fn primary(&mut self) -> Result<Filter, Box<dyn Error>> {
+        match self.next() {
+            Some("NOT") => Ok(Filter::Not(Box::new(self.unary()?))),
+            Some("(") => {
+                let filter = self.or()?;
+                match self.next() {
+                    Some(")") => Ok(filter),
+                    _ => Err("Missing closing parenthesis".into()),
+                }
+            }
+            Some(token @ (")" | "AND" | "OR")) => Err(format!("Unexpected {:?}", token).into()),
+            Some(token) => Ok(Filter::Term(parse_term(token)?)),
+            None => Err("Filter ends unexpectedly".into()),
+        }
+    }
+}
+
+/// Parse a filter such as `language:rust AND (topic:cli OR topic:tui) NOT archived`
+pub fn parse_filter(input: &str) -> Result<Filter, Box<dyn Error>> {
+    let mut parser = Parser { tokens: tokenize(input), position: 0, depth: 0 };
+    if parser.tokens.is_empty() {
+        return Err("Filter must not be empty".into());
+    }
+
+    let filter = parser.or()?;
+    match parser.peek() {
+        None => Ok(filter),
+        Some(token) => Err(format!("Unexpected {:?}", token).into()),
+    }
+}
+
+/// A filter matching repositories written mainly in any of a comma-separated list of languages
+pub fn language_filter(languages: &str) -> Result<Filter, Box<dyn Error>> {
+    languages
+        .split(',')
+        .map(str::trim)
+        .filter(|language| !language.is_empty())
+        .map(|language| Filter::Term(Term::Language(language.to_lowercase())))
+        .reduce(|left, right| Filter::Or(Box::new(left), Box::new(right)))
+        .ok_or_else(|| "--language needs at least one language".into())
+}
+
+/// A filter matching repositories tagged with every one of `topics`
+pub fn topic_filter<'a>(topics: impl IntoIterator<Item = &'a str>) -> Result<Filter, Box<dyn Error>> {
+    topics
+        .into_iter()
+        .map(str::trim)
+        .filter(|topic| !topic.is_empty())
+        .map(|topic| Filter::Term(Term::Topic(topic.to_lowercase())))
+        .reduce(|left, right| Filter::And(Box::new(left), Box::new(right)))
+        .ok_or_else(|| "--topic needs a topic".into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn tokio() -> StarredRepo {
+        serde_json::from_value(json!({
+            "id": 31423497,
+            "name": "tokio",
+            "full_name": "tokio-rs/tokio",
+            "html_url": "https://github.com/tokio-rs/tokio",
+            "owner": { "login": "tokio-rs" },
+            "description": "A runtime for writing reliable asynchronous applications with Rust.",
+            "language": "Rust",
+            "topics": ["async", "networking"],
+            "stargazers_count": 25000,
+            "archived": false,
+            "fork": false
+        }))
+        .unwrap()
+    }
+
+    fn matches(filter: &str) -> bool {
+        parse_filter(filter).unwrap().matches(&tokio())
+    }
+
+    fn term(term: Term) -> Box<Filter> {
+        Box::new(Filter::Term(term))
+    }
+
+    #[test]
+    fn test_parse_filter() {
+        assert_eq!(
+            parse_filter("language:Rust stars:>500 archived:false").unwrap(),
+            Filter::And(
+                Box::new(Filter::And(term(Term::Language("rust".into())), term(Term::Stars(StarsRange::AtLeast(501))))),
+                term(Term::Archived(false)),
+            )
+        );
+        assert_eq!(parse_filter("stars:10..100").unwrap(), *term(Term::Stars(StarsRange::Between(10, 100))));
+        assert_eq!(parse_filter("stars:<=10").unwrap(), *term(Term::Stars(StarsRange::AtMost(10))));
+    }
+
+    #[test]
+    fn test_parse_boolean_filter() {
+        assert_eq!(
+            parse_filter("language:rust AND (topic:cli OR topic:tui) NOT archived").unwrap(),
+            Filter::And(
+                Box::new(Filter::And(
+                    term(Term::Language("rust".into())),
+                    Box::new(Filter::Or(term(Term::Topic("cli".into())), term(Term::Topic("tui".into())))),
+                )),
+                Box::new(Filter::Not(term(Term::Archived(true)))),
+            )
+        );
+
+        // AND binds tighter than OR
+        assert_eq!(
+            parse_filter("topic:cli OR topic:tui fork").unwrap(),
+            Filter::Or(
+                term(Term::Topic("cli".into())),
+                Box::new(Filter::And(term(Term::Topic("tui".into())), term(Term::Fork(true)))),
+            )
+        );
+    }
+
+    #[test]
+    fn test_parse_filter_rejects_invalid() {
+        for input in [
+            "", "   ", "tag:tools", "stars:many", "stars:<0", "language:", "archived:maybe",
+            "(topic:cli", "topic:cli)", "AND topic:cli", "topic:cli OR", "NOT", "()",
+        ] {
+            assert!(parse_filter(input).is_err(), "{:?} should be rejected", input);
+        }
+
+        let nested = format!("{}fork{}", "(".repeat(1000), ")".repeat(1000));
+        assert!(parse_filter(&nested).is_err());
+    }
+
+    #[test]
+    fn test_matches() {
+        assert!(matches("language:rust stars:>20000 topic:async"));
+        assert!(matches("user:Tokio-RS runtime"));
+        assert!(matches("archived:false"));
+        assert!(!matches("archived"));
+        assert!(!matches("language:go"));
+        assert!(!matches("topic:cli"));
+        assert!(!matches("stars:<1000"));
+        assert!(!matches("rust fork"));
+    }
+
+    #[test]
+    fn test_matches_boolean_filter() {
+        assert!(matches("language:rust AND (topic:cli OR topic:async) NOT archived"));
+        assert!(matches("topic:cli OR topic:networking"));
+        assert!(matches("NOT (language:go OR fork)"));
+        assert!(!matches("language:rust AND (topic:cli OR topic:tui)"));
+        assert!(!matches("NOT language:rust"));
+    }
+
+    #[test]
+    fn test_language_filter() {
+        assert!(language_filter("Rust").unwrap().matches(&tokio()));
+        assert!(language_filter("go, rust").unwrap().matches(&tokio()));
+        assert!(!language_filter("go,python").unwrap().matches(&tokio()));
+        assert!(language_filter(" , ").is_err());
+    }
+
+    #[test]
+    fn test_topic_filter() {
+        assert!(topic_filter(["async"]).unwrap().matches(&tokio()));
+        assert!(topic_filter(["Async", "networking"]).unwrap().matches(&tokio()));
+        assert!(!topic_filter(["async", "cli"]).unwrap().matches(&tokio()));
+        assert!(topic_filter([" "]).is_err());
+    }
+}
@@ -0,0 +1,252 @@
+//!
+//! Installing binaries from release assets
+//! This module picks the asset built for the running platform, unpacks it with the system `tar`
+//! or `unzip`, and finds the executable inside.
+//!
+
+use std::error::Error;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use crate::utils::checksum::is_checksum_file;
+use crate::utils::signature::is_signature_file;
+
+/// How a release asset is packaged
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArchiveKind {
+    /// A tarball, compressed or not
+    Tar,
+    Zip,
+    /// The executable itself
+    Binary,
+}
+
+const TAR_SUFFIXES: &[&str] = &[".tar", ".tar.gz", ".tgz", ".tar.xz", ".txz", ".tar.bz2", ".tbz", ".tar.zst"];
+
+// Packages for system package managers, installers and metadata, none of which can be installed directly
+const UNSUPPORTED_SUFFIXES: &[&str] = &[
+    ".deb", ".rpm", ".apk", ".dmg", ".msi", ".pkg", ".7z", ".gz", ".xz", ".bz2", ".zst", ".txt", ".json",
+    ".sbom", ".pem", ".crt", ".md", ".jar", ".vsix", ".sh",
+];
+
+impl ArchiveKind {
+    /// Work out how an asset is packaged from its name, or None if it can't be installed
+    pub fn from_name(name: &str) -> Option<Self> {
+        let name = name.to_lowercase();
+        if is_checksum_file(&name) || is_signature_file(&name) {
+            None
+        } else if TAR_SUFFIXES.iter().any(|suffix| name.ends_with(suffix)) {
+            Some(ArchiveKind::Tar)
+        } else if name.ends_with(".zip") {
+            Some(ArchiveKind::Zip)
+        } else if UNSUPPORTED_SUFFIXES.iter().any(|suffix| name.ends_with(suffix)) {
+            None
+        } else {
+            Some(ArchiveKind::Binary)
+        }
+    }
+}
+
+// Spellings of each OS and architecture found in asset names, keyed by std::env::consts values
+fn os_aliases(os: &str) -> &'static [&'static str] {
+    match os {
+        "linux" => &["linux"],
+        "macos" => &["darwin", "macos", "apple", "osx"],
+        "windows" => &["windows", "win64", "win32", "win"],
+        _ => &[],
+    }
+}
+
+fn arch_aliases(arch: &str) -> &'static [&'static str] {
+    match arch {
+        "x86_64" => &["x86_64", "amd64", "x64"],
+        "aarch64" => &["aarch64", "arm64"],
+        "x86" => &["i686", "i386", "386"],
+        "arm" => &["armv7", "armhf", "arm"],
+        _ => &[],
+    }
+}
+
+const KNOWN_ARCHES: &[&str] = &["x86_64", "aarch64", "x86", "arm"];
+
+// Whether an alias occurs as its own word, so `win` doesn't match `darwin` and `arm` doesn't match `arm64`
+fn mentions(name: &str, alias: &str) -> bool {
+    name.match_indices(alias).any(|(start, _)| {
+        let before = name[..start].chars().next_back();
+        let after = name[start + alias.len()..].chars().next();
+        !before.is_some_and(|c| c.is_ascii_alphanumeric()) && !after.is_some_and(|c| c.is_ascii_alphanumeric())
+    })
+}
+
+/// Pick the asset built for `os` and `arch` (as in `std::env::consts`), preferring statically
+/// linked builds and archives native to the platform. Assets naming no architecture at all, such as
+/// universal macOS builds, are a fallback.
+pub fn pick_asset<'a>(names: &[&'a str], os: &str, arch: &str) -> Option<&'a str> {
+    let mut best: Option<(u32, &str)> = None;
+
+    for &name in names {
+        let Some(kind) = ArchiveKind::from_name(name) else { continue };
+        let lower = name.to_lowercase();
+        if !os_aliases(os).iter().any(|alias| mentions(&lower, alias)) {
+            continue;
+        }
+
+        let mut score = if arch_aliases(arch).iter().any(|alias| mentions(&lower, alias)) {
+            10
+        } else if KNOWN_ARCHES.iter().flat_map(|known| arch_aliases(known)).any(|alias| mentions(&lower, alias)) {
+            continue;
+        } else {
+            1
+        };
+        if os == "linux" && lower.contains("musl") {
+            score += 2;
+        }
+        score += match (kind, os) {
+            (ArchiveKind::Zip, "windows") | (ArchiveKind::Tar, "linux" | "macos") => 1,
+            _ => 0,
+        };
+
+        if best.is_none_or(|(best_score, _)| score > best_score) {
+            best = Some((score, name));
+        }
+    }
+
+    best.map(|(_, name)| name)
+}
+
+/// Unpack an archive into `dest` with the system tools, or copy a bare binary there
+pub fn unpack(archive: &Path, kind: ArchiveKind, dest: &Path) -> Result<(), Box<dyn Error>> {
+    fs::create_dir_all(dest)?;
+
+    let mut command = match kind {
+        ArchiveKind::Tar => {
+            let mut command = Command::new("tar");
+            command.arg("-xf").arg(archive).arg("-C").arg(dest);
+            command
+        }
+        ArchiveKind::Zip => {
+            let mut command = Command::new("unzip");
+            command.arg("-q").arg("-o").arg(archive).arg("-d").arg(dest);
+            command
+        }
+        ArchiveKind::Binary => {
+            let file_name = archive.file_name().ok_or("Asset has no file name")?;
+            fs::copy(archive, dest.join(file_name))?;
+            return Ok(());
+        }
+    };
+
+    let output = command.output().map_err(|e| format!("Failed to run {:?}: {}", command.get_program(), e))?;
+    if !output.status.success() {
+        return Err(format!("Failed to unpack {}: {}", archive.display(), String::from_utf8_lossy(&output.stderr)).into());
+    }
+
+    Ok(())
+}
+
+fn is_executable(path: &Path) -> bool {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        fs::metadata(path).is_ok_and(|metadata| metadata.permissions().mode() & 0o111 != 0)
+    }
+    #[cfg(not(unix))]
+    {
+        path.extension().is_some_and(|extension| extension.eq_ignore_ascii_case("exe"))
+    }
+}
+
+fn files(dir: &Path, found: &mut Vec<PathBuf>) -> Result<(), Box<dyn Error>> {
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            files(&path, found)?;
+        } else {
+            found.push(path);
+        }
+    }
+    Ok(())
+}
+
+/// Find the executable called `name` in an unpacked asset, or the only executable if none is
+pub fn find_binary(dir: &Path, name: &str) -> Result<PathBuf, Box<dyn Error>> {
+    let mut found = Vec::new();
+    files(dir, &mut found)?;
+    found.sort();
+    let executables: Vec<PathBuf> = found.into_iter().filter(|path| is_executable(path)).collect();
+
+    let stem = |path: &PathBuf| path.file_stem().map(|stem| stem.to_string_lossy().to_string());
+    if let Some(binary) = executables.iter().find(|path| stem(path).as_deref() == Some(name)) {
+        return Ok(binary.clone());
+    }
+
+    match executables.as_slice() {
+        [binary] => Ok(binary.clone()),
+        [] => Err("The asset contains no executable".into()),
+        _ => {
+            let names: Vec<String> = executables.iter().filter_map(stem).collect();
+            Err(format!("The asset contains several executables, choose one with --bin: {}", names.join(", ")).into())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    const RIPGREP_ASSETS: &[&str] = &[
+        "ripgrep-14.1.0-aarch64-apple-darwin.tar.gz",
+        "ripgrep-14.1.0-x86_64-apple-darwin.tar.gz",
+        "ripgrep-14.1.0-x86_64-apple-darwin.tar.gz.sha256",
+        "ripgrep-14.1.0-x86_64-pc-windows-msvc.zip",
+        "ripgrep-14.1.0-x86_64-unknown-linux-gnu.tar.gz",
+        "ripgrep-14.1.0-x86_64-unknown-linux-musl.tar.gz",
+        "ripgrep-14.1.0-arm-unknown-linux-gnueabihf.tar.gz",
+        "ripgrep_14.1.0-1_amd64.deb",
+    ];
+
+    #[test]
+    fn test_pick_asset() {
+        assert_eq!(pick_asset(RIPGREP_ASSETS, "linux", "x86_64"), Some("ripgrep-14.1.0-x86_64-unknown-linux-musl.tar.gz"));
+        assert_eq!(pick_asset(RIPGREP_ASSETS, "macos", "aarch64"), Some("ripgrep-14.1.0-aarch64-apple-darwin.tar.gz"));
+        assert_eq!(pick_asset(RIPGREP_ASSETS, "windows", "x86_64"), Some("ripgrep-14.1.0-x86_64-pc-windows-msvc.zip"));
+        assert_eq!(pick_asset(RIPGREP_ASSETS, "linux", "arm"), Some("ripgrep-14.1.0-arm-unknown-linux-gnueabihf.tar.gz"));
+        assert_eq!(pick_asset(RIPGREP_ASSETS, "linux", "aarch64"), None);
+    }
+
+    #[test]
+    fn test_pick_universal_asset() {
+        let names = ["tool-macos-universal.tar.gz", "tool-linux-amd64", "tool-linux-arm64"];
+
+        assert_eq!(pick_asset(&names, "macos", "aarch64"), Some("tool-macos-universal.tar.gz"));
+        assert_eq!(pick_asset(&names, "linux", "x86_64"), Some("tool-linux-amd64"));
+    }
+
+    #[test]
+    fn test_archive_kind() {
+        assert_eq!(ArchiveKind::from_name("tool.tar.xz"), Some(ArchiveKind::Tar));
+        assert_eq!(ArchiveKind::from_name("tool.ZIP"), Some(ArchiveKind::Zip));
+        assert_eq!(ArchiveKind::from_name("tool-linux-amd64"), Some(ArchiveKind::Binary));
+        assert_eq!(ArchiveKind::from_name("tool.exe"), Some(ArchiveKind::Binary));
+        assert_eq!(ArchiveKind::from_name("tool.deb"), None);
+        assert_eq!(ArchiveKind::from_name("tool.tar.gz.asc"), None);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_find_binary() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = tempdir().unwrap();
+        let nested = dir.path().join("ripgrep-14.1.0");
+        fs::create_dir_all(nested.join("complete")).unwrap();
+        for (file, mode) in [("rg", 0o755), ("README.md", 0o644), ("complete/rg.bash", 0o644), ("helper", 0o755)] {
+            fs::write(nested.join(file), "").unwrap();
+            fs::set_permissions(nested.join(file), fs::Permissions::from_mode(mode)).unwrap();
+        }
+
+        assert_eq!(find_binary(dir.path(), "rg").unwrap(), nested.join("rg"));
+        assert!(find_binary(dir.path(), "ripgrep").unwrap_err().to_string().ends_with("helper, rg"));
+    }
+}
@@ -0,0 +1,12 @@
+pub mod parse;
+pub mod collection;
+pub mod similar;
+pub mod filter;
+pub mod checksum;
+pub mod signature;
+pub mod install;
+pub mod shutdown;
+pub mod bug_report;
+pub mod recall;
+pub mod clock;
+pub mod sort;
\ No newline at end of file
@@ -0,0 +1,322 @@
+//!
+//! Parsing of user supplied repository references
+//! This module turns `owner/repo` strings and repository URLs into validated owner and name pairs,
+//! comma separated topic lists into validated topics, periods like `30d` into durations and
+//! GitHub's timestamps into seconds since the Unix epoch.
+//!
+
+use std::error::Error;
+use std::fmt::{self, Display};
+use std::str::FromStr;
+use std::time::Duration;
+
+/// A repository identified by its owner and name
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct RepoRef {
+    pub owner: String,
+    pub repo: String,
+}
+
+impl RepoRef {
+    /// Create a RepoRef, validating both parts against GitHub's naming rules
+    pub fn new(owner: &str, repo: &str) -> Result<Self, Box<dyn Error>> {
+        validate_owner(owner)?;
+        let repo = repo.strip_suffix(".git").unwrap_or(repo);
+        validate_repo(repo)?;
+
+        Ok(Self {
+            owner: owner.to_string(),
+            repo: repo.to_string(),
+        })
+    }
+
+    /// The `owner/repo` form used by the GitHub API
+    pub fn full_name(&self) -> String {
+        format!("{}/{}", self.owner, self.repo)
+    }
+}
+
+impl Display for RepoRef {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/{}", self.owner, self.repo)
+    }
+}
+
+impl FromStr for RepoRef {
+    type Err = Box<dyn Error>;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        parse_repo_spec(s)
+    }
+}
+
+// First path segments of GitHub URLs that are site pages rather than owners
+const RESERVED_OWNERS: &[&str] = &[
+    "apps", "collections", "explore", "features", "login", "marketplace", "notifications",
+    "organizations", "orgs", "search", "settings", "sponsors", "topics",
+];
+
+/// Parse `owner/repo`, an SSH clone URL or any URL inside a repository, e.g. an issue, pull
+/// request, file permalink or release page
+pub fn parse_repo_spec(input: &str) -> Result<RepoRef, Box<dyn Error>> {
+    let input = input.trim();
+    if input.is_empty() {
+        return Err("Repository must not be empty".into());
+    }
+
+    // URLs may point anywhere inside the repository, so only their first two segments matter
+    let (path, is_url) = if let Some(rest) = input.strip_prefix("git@") {
+        // git@github.com:owner/repo.git
+        let path = rest
+            .split_once(':')
+            .map(|(_, path)| path)
+            .ok_or_else(|| format!("Invalid SSH repository URL: {}", input))?;
+        (path, false)
+    } else if let Some(rest) = input
+        .strip_prefix("https://")
+        .or_else(|| input.strip_prefix("http://"))
+    {
+        let (host, path) = rest
+            .split_once('/')
+            .ok_or_else(|| format!("Repository URL has no path: {}", input))?;
+        if host.starts_with("gist.") {
+            return Err(format!("Gist URLs don't belong to a repository: {}", input).into());
+        }
+        // API URLs look like https://api.github.com/repos/owner/repo
+        let path = match host.starts_with("api.") {
+            true => path.strip_prefix("repos/").unwrap_or(path),
+            false => path,
+        };
+        (path, true)
+    } else if let Some(rest) = input
+        .strip_prefix("github.com/")
+        .or_else(|| input.strip_prefix("www.github.com/"))
+    {
+        (rest, true)
+    } else {
+        (input, false)
+    };
+
+    // Ignore query strings, fragments and a trailing slash
+    let path = path.split(['?', '#']).next().unwrap_or_default();
+    let path = path.trim_end_matches('/');
+
+    let mut parts = path.split('/');
+    match (parts.next(), parts.next(), parts.next()) {
+        (Some(owner), _, _) if is_url && RESERVED_OWNERS.contains(&owner) => {
+            Err(format!("URL doesn't point inside a repository: {}", input).into())
+        }
+        (Some(owner), Some(repo), None) => RepoRef::new(owner, repo),
+        (Some(owner), Some(repo), Some(_)) if is_url => RepoRef::new(owner, repo),
+        _ => Err(format!("Expected owner/repo or a repository URL, got: {}", input).into()),
+    }
+}
+
+/// Parse a comma separated list of topics, lowercasing them as GitHub does
+pub fn parse_topics(input: &str) -> Result<Vec<String>, Box<dyn Error>> {
+    let mut topics: Vec<String> = Vec::new();
+
+    for topic in input.split(',').map(|topic| topic.trim().to_lowercase()) {
+        if topic.is_empty() || topics.contains(&topic) {
+            continue;
+        }
+
+        let valid_chars = topic.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-');
+        if topic.len() > 50 || !valid_chars || topic.starts_with('-') {
+            return Err(format!("Invalid topic: {:?}", topic).into());
+        }
+        topics.push(topic);
+    }
+
+    if topics.len() > 20 {
+        return Err("A repository can have at most 20 topics".into());
+    }
+
+    Ok(topics)
+}
+
+/// Parse a period as a number followed by `m`, `h`, `d`, `w` or `y`, e.g. `30d` or `2y`. A year
+/// counts as 365 days.
+pub fn parse_period(input: &str) -> Result<Duration, Box<dyn Error>> {
+    let invalid = || format!("Expected a period such as 30d, 12h or 2y, got: {}", input);
+    let input = input.trim();
+    let unit = input.chars().last().ok_or_else(invalid)?;
+    let count: u64 = input[..input.len() - unit.len_utf8()].parse().map_err(|_| invalid())?;
+    let seconds = match unit {
+        'm' => 60,
+        'h' => 60 * 60,
+        'd' => 24 * 60 * 60,
+        'w' => 7 * 24 * 60 * 60,
+        'y' => 365 * 24 * 60 * 60,
+        _ => return Err(invalid().into()),
+    };
+
+    match count.checked_mul(seconds) {
+        Some(0) | None => Err(invalid().into()),
+        Some(total) => Ok(Duration::from_secs(total)),
+    }
+}
+
+/// Parse a UTC timestamp as GitHub writes them, e.g. `2024-05-01T12:00:00Z`, into seconds since
+/// the Unix epoch
+pub fn parse_timestamp(input: &str) -> Result<u64, Box<dyn Error>> {
+    let invalid = || format!("Expected a timestamp such as 2024-05-01T12:00:00Z, got: {}", input);
+    let field = |range: std::ops::Range<usize>| -> Result<u64, String> {
+        input.get(range).and_then(|digits| digits.parse().ok()).ok_or_else(invalid)
+    };
+    let separators = [(4, b'-'), (7, b'-'), (10, b'T'), (13, b':'), (16, b':'), (19, b'Z')];
+    if input.len() != 20 || separators.iter().any(|&(index, separator)| input.as_bytes()[index] != separator) {
+        return Err(invalid().into());
+    }
+
+    let (year, month, day) = (field(0..4)?, field(5..7)?, field(8..10)?);
+    let (hour, minute, second) = (field(11..13)?, field(14..16)?, field(17..19)?);
+    if year < 1970 || !(1..=12).contains(&month) || !(1..=31).contains(&day) || hour > 23 || minute > 59 || second > 60 {
+        return Err(invalid().into());
+    }
+
+    // Days since the epoch for a proleptic Gregorian date, counting years from March so the leap
+    // day comes last
+    let (year, month) = if month <= 2 { (year - 1, month + 9) } else { (year, month - 3) };
+    let era = year / 400;
+    let year_of_era = year % 400;
+    let day_of_year = (153 * month + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    let days = era * 146_097 + day_of_era - 719_468;
+
+    Ok(days * 86_400 + hour * 3600 + minute * 60 + second)
+}
+
+fn validate_owner(owner: &str) -> Result<(), Box<dyn Error>> {
+    let valid_chars = owner.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+
+    if owner.is_empty() || owner.len() > 39 || !valid_chars || owner.starts_with('-') || owner.ends_with('-') {
+        return Err(format!("Invalid repository owner: {:?}", owner).into());
+    }
+
+    Ok(())
+}
+
+fn validate_repo(repo: &str) -> Result<(), Box<dyn Error>> {
+    let valid_chars = repo.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'));
+
+    if repo.is_empty() || repo.len() > 100 || !valid_chars || repo == "." || repo == ".." {
+        return Err(format!("Invalid repository name: {:?}", repo).into());
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parsed(input: &str) -> (String, String) {
+        let repo = parse_repo_spec(input).unwrap();
+        (repo.owner, repo.repo)
+    }
+
+    #[test]
+    fn test_parse_owner_repo() {
+        assert_eq!(parsed("rust-lang/rust"), ("rust-lang".into(), "rust".into()));
+        assert_eq!(parsed("  tokio-rs/tokio \n"), ("tokio-rs".into(), "tokio".into()));
+        assert_eq!(parsed("serde-rs/serde.git"), ("serde-rs".into(), "serde".into()));
+        assert_eq!(parsed("user_emu/dotted.name"), ("user_emu".into(), "dotted.name".into()));
+    }
+
+    #[test]
+    fn test_parse_urls() {
+        assert_eq!(parsed("https://github.com/rust-lang/rust"), ("rust-lang".into(), "rust".into()));
+        assert_eq!(parsed("https://github.com/rust-lang/rust/"), ("rust-lang".into(), "rust".into()));
+        assert_eq!(parsed("http://www.github.com/rust-lang/rust.git"), ("rust-lang".into(), "rust".into()));
+        assert_eq!(parsed("https://ghe.example.com/team/tool?tab=readme"), ("team".into(), "tool".into()));
+        assert_eq!(parsed("github.com/rust-lang/rust#readme"), ("rust-lang".into(), "rust".into()));
+        assert_eq!(parsed("git@github.com:rust-lang/rust.git"), ("rust-lang".into(), "rust".into()));
+    }
+
+    #[test]
+    fn test_parse_urls_inside_a_repository() {
+        for url in [
+            "https://github.com/rust-lang/rust/issues/12345",
+            "https://github.com/rust-lang/rust/pull/98765/files#diff-1",
+            "https://github.com/rust-lang/rust/blob/3f5fd8dd41/src/lib.rs#L10-L20",
+            "https://github.com/rust-lang/rust/commit/3f5fd8dd41153bc5fdca9427e9e05be2c767ba23",
+            "https://github.com/rust-lang/rust/releases/tag/1.78.0",
+            "github.com/rust-lang/rust/tree/master/library",
+            "https://api.github.com/repos/rust-lang/rust/pulls/1",
+        ] {
+            assert_eq!(parsed(url), ("rust-lang".into(), "rust".into()), "{}", url);
+        }
+    }
+
+    #[test]
+    fn test_parse_rejects_invalid() {
+        for input in [
+            "",
+            "rust",
+            "/rust",
+            "rust-lang/",
+            "a/b/c",
+            "-owner/repo",
+            "owner-/repo",
+            "own er/repo",
+            "owner/..",
+            "owner/re po",
+            "https://github.com",
+            "https://github.com/rust-lang",
+            "git@github.com",
+            "ö/repo",
+            "https://github.com/orgs/rust-lang/people",
+            "https://github.com/topics/rust",
+            "https://gist.github.com/octocat/aa5a315d61ae9438b18d",
+            "git@github.com:rust-lang/rust/extra",
+        ] {
+            assert!(parse_repo_spec(input).is_err(), "{:?} should be rejected", input);
+        }
+
+        let long_owner = format!("{}/repo", "a".repeat(40));
+        assert!(parse_repo_spec(&long_owner).is_err());
+    }
+
+    #[test]
+    fn test_parse_topics() {
+        assert_eq!(parse_topics("rust, CLI,github-api,rust").unwrap(), vec!["rust", "cli", "github-api"]);
+        assert_eq!(parse_topics("").unwrap(), Vec::<String>::new());
+        assert!(parse_topics("has space").is_err());
+        assert!(parse_topics("-leading").is_err());
+        assert!(parse_topics(&"a".repeat(51)).is_err());
+
+        let too_many: Vec<String> = (0..21).map(|i| format!("topic{}", i)).collect();
+        assert!(parse_topics(&too_many.join(",")).is_err());
+    }
+
+    #[test]
+    fn test_parse_period() {
+        assert_eq!(parse_period("30d").unwrap(), Duration::from_secs(30 * 24 * 60 * 60));
+        assert_eq!(parse_period("12h").unwrap(), Duration::from_secs(12 * 60 * 60));
+        assert_eq!(parse_period("2w").unwrap(), Duration::from_secs(14 * 24 * 60 * 60));
+        assert_eq!(parse_period("90m").unwrap(), Duration::from_secs(90 * 60));
+        assert_eq!(parse_period("2y").unwrap(), Duration::from_secs(2 * 365 * 24 * 60 * 60));
+        for invalid in ["", "d", "30", "0d", "-1d", "1.5d", "30s", "99999999999999999w"] {
+            assert!(parse_period(invalid).is_err(), "{:?}", invalid);
+        }
+    }
+
+    #[test]
+    fn test_parse_timestamp() {
+        assert_eq!(parse_timestamp("1970-01-01T00:00:00Z").unwrap(), 0);
+        assert_eq!(parse_timestamp("2000-03-01T00:00:00Z").unwrap(), 951_868_800);
+        assert_eq!(parse_timestamp("2024-02-29T12:30:15Z").unwrap(), 1_709_209_815);
+        for invalid in ["", "2024-05-01", "2024-05-01T12:00:00+02:00", "2024-13-01T00:00:00Z", "1969-12-31T23:59:59Z"] {
+            assert!(parse_timestamp(invalid).is_err(), "{:?}", invalid);
+        }
+    }
+
+    #[test]
+    fn test_repo_ref_display_and_from_str() {
+        let repo: RepoRef = "octocat/Hello-World".parse().unwrap();
+        assert_eq!(repo.to_string(), "octocat/Hello-World");
+        assert_eq!(repo.full_name(), "octocat/Hello-World");
+        assert!(RepoRef::new("octocat", "bad/name").is_err());
+    }
+}
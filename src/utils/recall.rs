@@ -0,0 +1,103 @@
+//!
+//! Stars from this day in earlier years
+//! Repositories starred on the same month and day in a previous year, a reminder of what caught
+//! my eye back then and whether it is still worth keeping.
+//!
+
+use std::error::Error;
+use crate::api::stars::StarDate;
+use crate::utils::parse::parse_timestamp;
+
+/// A repository starred on this day some years ago
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Anniversary {
+    pub full_name: String,
+    /// Timestamp such as `2019-05-01T12:00:00Z`
+    pub starred_at: String,
+    pub years_ago: u64,
+}
+
+/// Stars from the month and day of `today`, a date such as `2024-05-01`, in earlier years, most
+/// recent first. Stars from 29 February show on 28 February when `today`'s year has no leap day.
+pub fn on_this_day(stars: &[StarDate], today: &str) -> Result<Vec<Anniversary>, Box<dyn Error>> {
+    parse_timestamp(&format!("{}T00:00:00Z", today)).map_err(|_| format!("Expected a date such as 2024-05-01, got: {}", today))?;
+    let year: u64 = today[..4].parse()?;
+    let month_day = &today[5..];
+
+    let mut anniversaries: Vec<Anniversary> = stars
+        .iter()
+        .filter_map(|star| {
+            let starred_year: u64 = star.starred_at.get(..4)?.parse().ok()?;
+            let starred_on = star.starred_at.get(5..10)?;
+            let same_day = starred_on == month_day || (starred_on == "02-29" && month_day == "02-28" && !is_leap(year));
+            (same_day && starred_year < year).then(|| Anniversary {
+                full_name: star.full_name.clone(),
+                starred_at: star.starred_at.clone(),
+                years_ago: year - starred_year,
+            })
+        })
+        .collect();
+    anniversaries.sort_by(|a, b| b.starred_at.cmp(&a.starred_at));
+    Ok(anniversaries)
+}
+
+/// The UTC date of `seconds` since the Unix epoch, such as `2024-05-01`
+pub fn date_of(seconds: u64) -> String {
+    // The inverse of the day count in parse_timestamp, with years starting in March
+    let days = seconds / 86_400 + 719_468;
+    let era = days / 146_097;
+    let day_of_era = days % 146_097;
+    let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month + 2) / 5 + 1;
+    let month = if month < 10 { month + 3 } else { month - 9 };
+    let year = era * 400 + year_of_era + u64::from(month <= 2);
+    format!("{:04}-{:02}-{:02}", year, month, day)
+}
+
+fn is_leap(year: u64) -> bool {
+    year.is_multiple_of(4) && (!year.is_multiple_of(100) || year.is_multiple_of(400))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn star(full_name: &str, starred_at: &str) -> StarDate {
+        StarDate { full_name: full_name.to_string(), starred_at: starred_at.to_string() }
+    }
+
+    #[test]
+    fn test_on_this_day() {
+        let stars = vec![
+            star("rust-lang/rust", "2024-05-01T12:00:00Z"),
+            star("tokio-rs/tokio", "2021-05-01T08:00:00Z"),
+            star("vuejs/vue", "2023-05-02T08:00:00Z"),
+            star("serde-rs/serde", "2022-05-01T23:59:59Z"),
+        ];
+        let found = on_this_day(&stars, "2024-05-01").unwrap();
+
+        let names: Vec<&str> = found.iter().map(|star| star.full_name.as_str()).collect();
+        assert_eq!(names, ["serde-rs/serde", "tokio-rs/tokio"]);
+        assert_eq!(found[1].years_ago, 3);
+        assert!(on_this_day(&stars, "2024-13-01").is_err());
+    }
+
+    #[test]
+    fn test_leap_day_stars_show_on_28_february() {
+        let stars = vec![star("rust-lang/rust", "2020-02-29T12:00:00Z")];
+
+        assert_eq!(on_this_day(&stars, "2023-02-28").unwrap().len(), 1);
+        assert!(on_this_day(&stars, "2024-02-28").unwrap().is_empty());
+        assert_eq!(on_this_day(&stars, "2024-02-29").unwrap()[0].years_ago, 4);
+    }
+
+    #[test]
+    fn test_date_of() {
+        assert_eq!(date_of(0), "1970-01-01");
+        assert_eq!(date_of(parse_timestamp("2024-02-29T23:59:59Z").unwrap()), "2024-02-29");
+        assert_eq!(date_of(parse_timestamp("2000-03-01T00:00:00Z").unwrap()), "2000-03-01");
+        assert_eq!(date_of(parse_timestamp("2023-12-31T12:00:00Z").unwrap()), "2023-12-31");
+    }
+}
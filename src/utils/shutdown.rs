@@ -0,0 +1,109 @@
+//!
+//! Ctrl-C handling
+//! Outside long operations Ctrl-C exits right away, as usual. Long operations hold a `Graceful`
+//! guard instead: the first Ctrl-C cancels its token so the operation can finish the requests in
+//! flight, save what it has and print a partial summary, and a second Ctrl-C exits immediately.
+//!
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use tokio_util::sync::CancellationToken;
+use crate::ui::terminal::restore_terminal;
+
+/// Exit status of a process stopped by Ctrl-C, as shells report it
+pub const INTERRUPTED_EXIT_CODE: i32 = 130;
+
+#[derive(Debug, Clone, Default)]
+pub struct Shutdown {
+    token: CancellationToken,
+    /// Number of live `Graceful` guards
+    graceful: Arc<AtomicUsize>,
+}
+
+impl Shutdown {
+    /// Handle Ctrl-C for the rest of the process; must be called inside the tokio runtime
+    pub fn listen() -> Self {
+        let shutdown = Shutdown::default();
+        let handler = shutdown.clone();
+        tokio::spawn(async move {
+            while tokio::signal::ctrl_c().await.is_ok() {
+                if handler.interrupt() {
+                    restore_terminal();
+                    std::process::exit(INTERRUPTED_EXIT_CODE);
+                }
+                eprintln!("\nInterrupted, finishing work in flight (press Ctrl-C again to quit now)");
+            }
+        });
+        shutdown
+    }
+
+    /// Let Ctrl-C cancel the guard's token rather than exit while the guard is alive
+    pub fn graceful(&self) -> Graceful {
+        self.graceful.fetch_add(1, Ordering::SeqCst);
+        Graceful { token: self.token.clone(), graceful: self.graceful.clone() }
+    }
+
+    // Handle one Ctrl-C, returning whether the process should exit now
+    fn interrupt(&self) -> bool {
+        if self.graceful.load(Ordering::SeqCst) == 0 || self.token.is_cancelled() {
+            return true;
+        }
+        self.token.cancel();
+        false
+    }
+}
+
+/// A long operation that stops early, rather than dying, on Ctrl-C
+#[derive(Debug)]
+pub struct Graceful {
+    token: CancellationToken,
+    graceful: Arc<AtomicUsize>,
+}
+
+impl Graceful {
+    pub fn token(&self) -> &CancellationToken {
+        &self.token
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.token.is_cancelled()
+    }
+}
+
+impl Drop for Graceful {
+    fn drop(&mut self) {
+        self.graceful.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_interrupt_exits_outside_graceful_operations() {
+        let shutdown = Shutdown::default();
+
+        assert!(shutdown.interrupt());
+    }
+
+    #[test]
+    fn test_interrupt_cancels_graceful_operation_once() {
+        let shutdown = Shutdown::default();
+        let graceful = shutdown.graceful();
+
+        assert!(!shutdown.interrupt());
+        assert!(graceful.is_cancelled());
+        assert!(graceful.token().is_cancelled());
+        // A second Ctrl-C doesn't wait any longer
+        assert!(shutdown.interrupt());
+    }
+
+    #[test]
+    fn test_dropped_guard_restores_exit() {
+        let shutdown = Shutdown::default();
+        drop(shutdown.graceful());
+
+        assert!(shutdown.interrupt());
+    }
+}
@@ -0,0 +1,194 @@
+//!
+//! Signatures of release assets
+//! Detached OpenPGP signatures (`<asset>.asc` or `<asset>.sig`) are checked with the system `gpg`
+//! against the user's keyring, reading its machine-readable `--status-fd` output.
+//!
+
+use std::error::Error;
+use std::fmt::{self, Display};
+use std::fs;
+use std::process::Command;
+
+/// How far the user's keyring trusts the key that made a signature
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Trust {
+    Ultimate,
+    Full,
+    Marginal,
+    /// The key is in the keyring but nobody trusted vouches for it
+    Undefined,
+    Never,
+}
+
+impl Display for Trust {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let trust = match self {
+            Trust::Ultimate => "ultimate",
+            Trust::Full => "full",
+            Trust::Marginal => "marginal",
+            Trust::Undefined => "undefined",
+            Trust::Never => "never",
+        };
+        write!(f, "{}", trust)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SignatureStatus {
+    Good { key_id: String, signer: String, trust: Trust },
+    Bad { key_id: String, signer: String },
+    /// The signing key isn't in the keyring, so the signature can't be checked
+    MissingKey { key_id: String },
+    /// gpg couldn't reach a verdict, e.g. because the key is expired or revoked
+    Unverifiable(String),
+}
+
+/// The detached signature among `names` covering `asset`, if the release publishes one
+pub fn signature_file_for<'a>(asset: &str, names: &[&'a str]) -> Option<&'a str> {
+    let candidates = [format!("{}.asc", asset), format!("{}.sig", asset)];
+    candidates
+        .iter()
+        .find_map(|candidate| names.iter().copied().find(|name| name == candidate))
+}
+
+/// Whether an asset is a detached signature rather than something to download for its own sake
+pub fn is_signature_file(name: &str) -> bool {
+    name.ends_with(".asc") || name.ends_with(".sig")
+}
+
+/// Interpret the `[GNUPG:]` status lines printed by `gpg --status-fd 1 --verify`. A file may carry
+/// several signatures, so any bad or uncheckable one fails the whole check whatever order the lines
+/// come in, and a good one only counts once gpg has also declared it valid.
+pub fn parse_gpg_status(status: &str) -> SignatureStatus {
+    let (mut good, mut valid, mut bad, mut unverifiable, mut missing_key) = (None, false, None, None, None);
+    let mut trust = Trust::Undefined;
+
+    for line in status.lines() {
+        let Some(line) = line.strip_prefix("[GNUPG:] ") else { continue };
+        let (keyword, rest) = line.split_once(' ').unwrap_or((line, ""));
+        let (key_id, signer) = rest.split_once(' ').unwrap_or((rest, ""));
+        let (key_id, signer) = (key_id.to_string(), signer.to_string());
+
+        match keyword {
+            "GOODSIG" => good = good.or(Some((key_id, signer))),
+            "VALIDSIG" => valid = true,
+            "BADSIG" => bad = bad.or(Some(SignatureStatus::Bad { key_id, signer })),
+            "NO_PUBKEY" => missing_key = missing_key.or(Some(SignatureStatus::MissingKey { key_id })),
+            "EXPKEYSIG" => unverifiable = unverifiable.or(Some(format!("signed with expired key {} ({})", key_id, signer))),
+            "REVKEYSIG" => unverifiable = unverifiable.or(Some(format!("signed with revoked key {} ({})", key_id, signer))),
+            "ERRSIG" => unverifiable = unverifiable.or(Some(format!("gpg could not check the signature by key {}", key_id))),
+            "TRUST_ULTIMATE" => trust = Trust::Ultimate,
+            "TRUST_FULLY" => trust = Trust::Full,
+            "TRUST_MARGINAL" => trust = Trust::Marginal,
+            "TRUST_UNDEFINED" => trust = Trust::Undefined,
+            "TRUST_NEVER" => trust = Trust::Never,
+            _ => {}
+        }
+    }
+
+    // A missing key also produces ERRSIG, but naming the key to import is more useful
+    if let Some(bad) = bad.or(missing_key) {
+        return bad;
+    }
+    match (unverifiable, good) {
+        (Some(reason), _) => SignatureStatus::Unverifiable(reason),
+        (None, Some((key_id, signer))) if valid => SignatureStatus::Good { key_id, signer, trust },
+        (None, Some(_)) => SignatureStatus::Unverifiable(String::from("gpg did not report the signature as valid")),
+        (None, None) => SignatureStatus::Unverifiable(String::from("gpg reported no signature")),
+    }
+}
+
+/// Check a detached signature over `data` with the system `gpg` and the user's keyring
+pub fn verify_signature(data: &[u8], signature: &[u8]) -> Result<SignatureStatus, Box<dyn Error>> {
+    if Command::new("gpg").arg("--version").output().is_err() {
+        return Err("gpg is not installed or not available in PATH".into());
+    }
+
+    let dir = tempfile::tempdir()?;
+    let (data_path, signature_path) = (dir.path().join("asset"), dir.path().join("asset.sig"));
+    fs::write(&data_path, data)?;
+    fs::write(&signature_path, signature)?;
+
+    // The status lines say why a signature is good or not; a good one also needs gpg to exit cleanly
+    let output = Command::new("gpg")
+        .args(["--batch", "--status-fd", "1", "--verify"])
+        .arg(&signature_path)
+        .arg(&data_path)
+        .output()?;
+
+    Ok(match parse_gpg_status(&String::from_utf8_lossy(&output.stdout)) {
+        SignatureStatus::Good { .. } if !output.status.success() => {
+            SignatureStatus::Unverifiable(format!("gpg reported a good signature but {}", output.status))
+        }
+        status => status,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_signature_file_for() {
+        let names = ["tool.tar.gz", "tool.tar.gz.sig", "tool.tar.gz.asc", "SHA256SUMS.asc"];
+
+        assert_eq!(signature_file_for("tool.tar.gz", &names), Some("tool.tar.gz.asc"));
+        assert_eq!(signature_file_for("SHA256SUMS", &names), Some("SHA256SUMS.asc"));
+        assert_eq!(signature_file_for("tool.zip", &names), None);
+    }
+
+    #[test]
+    fn test_parse_good_signature_with_trust() {
+        let status = "\
+[GNUPG:] NEWSIG
+[GNUPG:] KEY_CONSIDERED 0123456789ABCDEF0123456789ABCDEF01234567 0
+[GNUPG:] SIG_ID abc 2024-01-06 1704550000
+[GNUPG:] GOODSIG 89ABCDEF01234567 Andrew Gallant <jamslam@gmail.com>
+[GNUPG:] VALIDSIG 0123456789ABCDEF0123456789ABCDEF01234567 2024-01-06 1704550000 0 4 0 1 10 00 0123456789ABCDEF0123456789ABCDEF01234567
+[GNUPG:] TRUST_UNDEFINED 0 pgp
+";
+        assert_eq!(
+            parse_gpg_status(status),
+            SignatureStatus::Good {
+                key_id: "89ABCDEF01234567".to_string(),
+                signer: "Andrew Gallant <jamslam@gmail.com>".to_string(),
+                trust: Trust::Undefined,
+            }
+        );
+    }
+
+    #[test]
+    fn test_parse_failures() {
+        let bad = "[GNUPG:] BADSIG 89ABCDEF01234567 Someone <someone@example.com>\n";
+        let missing = "\
+[GNUPG:] ERRSIG 89ABCDEF01234567 1 10 00 1704550000 9 -
+[GNUPG:] NO_PUBKEY 89ABCDEF01234567
+";
+
+        assert!(matches!(parse_gpg_status(bad), SignatureStatus::Bad { .. }));
+        assert_eq!(parse_gpg_status(missing), SignatureStatus::MissingKey { key_id: "89ABCDEF01234567".to_string() });
+        assert!(matches!(parse_gpg_status(""), SignatureStatus::Unverifiable(_)));
+    }
+
+    #[test]
+    fn test_parse_mixed_signatures() {
+        let good = "\
+[GNUPG:] GOODSIG 89ABCDEF01234567 Andrew Gallant <jamslam@gmail.com>
+[GNUPG:] VALIDSIG 0123456789ABCDEF0123456789ABCDEF01234567 2024-01-06 1704550000 0 4 0 1 10 00 0123456789ABCDEF0123456789ABCDEF01234567
+";
+        let bad = "[GNUPG:] BADSIG 1111111111111111 Mallory <mallory@example.com>\n";
+        let expired = "[GNUPG:] EXPKEYSIG 2222222222222222 Old Key <old@example.com>\n";
+        let errsig = "[GNUPG:] ERRSIG 3333333333333333 1 10 00 1704550000 4 -\n";
+
+        // A good signature doesn't outweigh a bad one, whichever comes last
+        for failure in [bad, expired, errsig] {
+            assert!(!matches!(parse_gpg_status(&format!("{}{}", failure, good)), SignatureStatus::Good { .. }));
+            assert!(!matches!(parse_gpg_status(&format!("{}{}", good, failure)), SignatureStatus::Good { .. }));
+        }
+        assert!(matches!(parse_gpg_status(&format!("{}{}", bad, good)), SignatureStatus::Bad { .. }));
+
+        // Nor does GOODSIG count without VALIDSIG
+        let unvalidated = "[GNUPG:] GOODSIG 89ABCDEF01234567 Andrew Gallant <jamslam@gmail.com>\n";
+        assert!(matches!(parse_gpg_status(unvalidated), SignatureStatus::Unverifiable(_)));
+    }
+}
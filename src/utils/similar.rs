@@ -0,0 +1,102 @@
+//!
+//! Finding repositories similar to a given one
+//! Candidates come from searching each of the repository's topics and are ranked by how many
+//! topics they share with it, then by stars.
+//!
+
+use crate::api::search::SearchRepo;
+use crate::utils::parse::RepoRef;
+
+// Searching every topic of a heavily tagged repository would burn through the search rate limit
+const MAX_TOPICS: usize = 3;
+
+/// Search queries for repositories like this one, falling back to its name when it has no topics
+pub fn similar_queries(repo: &RepoRef, topics: &[String]) -> Vec<String> {
+    if topics.is_empty() {
+        return vec![format!("{} in:name,description", repo.repo)];
+    }
+
+    topics
+        .iter()
+        .take(MAX_TOPICS)
+        .map(|topic| format!("topic:{}", topic))
+        .collect()
+}
+
+/// Merge search results into a ranking, dropping the repository itself and duplicates
+pub fn rank_similar(repo: &RepoRef, topics: &[String], candidates: Vec<SearchRepo>) -> Vec<(SearchRepo, usize)> {
+    let mut ranked: Vec<(SearchRepo, usize)> = Vec::new();
+
+    for candidate in candidates {
+        if candidate.full_name.eq_ignore_ascii_case(&repo.full_name())
+            || ranked.iter().any(|(seen, _)| seen.id == candidate.id)
+        {
+            continue;
+        }
+
+        let shared = candidate.topics.iter().filter(|topic| topics.contains(topic)).count();
+        ranked.push((candidate, shared));
+    }
+
+    ranked.sort_by(|(a, a_shared), (b, b_shared)| b_shared.cmp(a_shared).then(b.stars.cmp(&a.stars)));
+    ranked
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::repos::Owner;
+
+    fn candidate(id: u64, full_name: &str, stars: u64, topics: &[&str]) -> SearchRepo {
+        let (owner, name) = full_name.split_once('/').unwrap();
+        SearchRepo {
+            id,
+            name: name.to_string(),
+            full_name: full_name.to_string(),
+            owner: Owner::from_login(owner),
+            description: None,
+            html_url: format!("https://github.com/{}", full_name),
+            stars,
+            language: None,
+            topics: topics.iter().map(|topic| topic.to_string()).collect(),
+        }
+    }
+
+    fn topics(names: &[&str]) -> Vec<String> {
+        names.iter().map(|name| name.to_string()).collect()
+    }
+
+    #[test]
+    fn test_similar_queries() {
+        let repo = RepoRef::new("tokio-rs", "tokio").unwrap();
+
+        assert_eq!(
+            similar_queries(&repo, &topics(&["async", "rust", "networking", "io"])),
+            vec!["topic:async", "topic:rust", "topic:networking"]
+        );
+        assert_eq!(similar_queries(&repo, &[]), vec!["tokio in:name,description"]);
+    }
+
+    #[test]
+    fn test_rank_similar() {
+        let repo = RepoRef::new("tokio-rs", "tokio").unwrap();
+        let candidates = vec![
+            candidate(1, "tokio-rs/tokio", 25000, &["async", "rust"]),
+            candidate(2, "smol-rs/smol", 3500, &["async", "rust"]),
+            candidate(3, "rust-lang/rust", 97000, &["rust"]),
+            candidate(2, "smol-rs/smol", 3500, &["async", "rust"]),
+            candidate(4, "async-rs/async-std", 3900, &["async", "rust"]),
+        ];
+
+        let ranked: Vec<(String, usize)> = rank_similar(&repo, &topics(&["async", "rust"]), candidates)
+            .into_iter()
+            .map(|(repo, shared)| (repo.full_name, shared))
+            .collect();
+
+        assert_eq!(ranked, vec![
+            ("async-rs/async-std".to_string(), 2),
+            ("smol-rs/smol".to_string(), 2),
+            ("rust-lang/rust".to_string(), 1),
+        ]);
+    }
+}
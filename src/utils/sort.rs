@@ -0,0 +1,132 @@
+//!
+//! Ordering starred repositories
+//! The API lists stars most recently starred first. Sorting happens on the fetched repositories,
+//! so it works the same with the REST and GraphQL backends and with cached responses.
+//!
+
+use std::str::FromStr;
+use crate::api::stars::StarredRepo;
+
+/// Names accepted by `list --sort`
+pub const LIST_SORTS: &[&str] = &["stars", "name", "updated", "starred_at"];
+
+/// Order of listed repositories, ascending unless reversed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RepoSort {
+    Stars,
+    /// Repository name, ignoring case
+    Name,
+    /// When the repository was last updated
+    Updated,
+    /// When I starred it
+    StarredAt,
+}
+
+impl FromStr for RepoSort {
+    type Err = String;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        match name {
+            "stars" => Ok(RepoSort::Stars),
+            "name" => Ok(RepoSort::Name),
+            "updated" => Ok(RepoSort::Updated),
+            "starred_at" | "starred-at" => Ok(RepoSort::StarredAt),
+            _ => Err(format!("Unknown sort {:?}, expected one of {}", name, LIST_SORTS.join(", "))),
+        }
+    }
+}
+
+/// Sort repositories listed most recently starred first, keeping that order between equal ones.
+/// Without star dates, as the REST API lists them, the listing order stands in for them.
+pub fn sort_repos(repos: &mut Vec<StarredRepo>, sort: RepoSort, descending: bool) {
+    let mut listed: Vec<(usize, StarredRepo)> = std::mem::take(repos).into_iter().enumerate().collect();
+    listed.sort_by(|(i, a), (j, b)| {
+        let order = match sort {
+            RepoSort::Stars => a.stars.cmp(&b.stars),
+            RepoSort::Name => a.name.to_lowercase()
+                .cmp(&b.name.to_lowercase())
+                .then_with(|| a.full_name.to_lowercase().cmp(&b.full_name.to_lowercase())),
+            RepoSort::Updated => a.updated_at.cmp(&b.updated_at),
+            // Later in the listing means starred earlier
+            RepoSort::StarredAt => a.starred_at.cmp(&b.starred_at).then(j.cmp(i)),
+        };
+        if descending { order.reverse() } else { order }
+    });
+    *repos = listed.into_iter().map(|(_, repo)| repo).collect();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn repo(full_name: &str, stars: u64, updated_at: &str, starred_at: Option<&str>) -> StarredRepo {
+        let (owner, name) = full_name.split_once('/').unwrap();
+        serde_json::from_value(json!({
+            "id": stars,
+            "name": name,
+            "full_name": full_name,
+            "owner": { "login": owner },
+            "description": null,
+            "html_url": format!("https://github.com/{}", full_name),
+            "stargazers_count": stars,
+            "updated_at": updated_at,
+            "starred_at": starred_at,
+        }))
+        .unwrap()
+    }
+
+    fn names(repos: &[StarredRepo]) -> Vec<&str> {
+        repos.iter().map(|repo| repo.full_name.as_str()).collect()
+    }
+
+    // Most recently starred first, as the REST API lists them
+    fn listed() -> Vec<StarredRepo> {
+        vec![
+            repo("vuejs/vue", 207000, "2024-05-14T06:50:31Z", None),
+            repo("rust-lang/rust", 96000, "2024-05-14T07:02:41Z", None),
+            repo("octocat/Hello-World", 2789, "2024-05-14T06:19:56Z", None),
+        ]
+    }
+
+    #[test]
+    fn test_parse_sort() {
+        assert_eq!("stars".parse::<RepoSort>().unwrap(), RepoSort::Stars);
+        assert_eq!("starred_at".parse::<RepoSort>().unwrap(), RepoSort::StarredAt);
+        assert!("forks".parse::<RepoSort>().is_err());
+    }
+
+    #[test]
+    fn test_sort_repos() {
+        let mut repos = listed();
+        sort_repos(&mut repos, RepoSort::Stars, false);
+        assert_eq!(names(&repos), ["octocat/Hello-World", "rust-lang/rust", "vuejs/vue"]);
+
+        sort_repos(&mut repos, RepoSort::Stars, true);
+        assert_eq!(names(&repos), ["vuejs/vue", "rust-lang/rust", "octocat/Hello-World"]);
+
+        sort_repos(&mut repos, RepoSort::Name, false);
+        assert_eq!(names(&repos), ["octocat/Hello-World", "rust-lang/rust", "vuejs/vue"]);
+
+        sort_repos(&mut repos, RepoSort::Updated, true);
+        assert_eq!(names(&repos), ["rust-lang/rust", "vuejs/vue", "octocat/Hello-World"]);
+    }
+
+    #[test]
+    fn test_sort_by_starred_at() {
+        let mut repos = listed();
+        sort_repos(&mut repos, RepoSort::StarredAt, false);
+        assert_eq!(names(&repos), ["octocat/Hello-World", "rust-lang/rust", "vuejs/vue"]);
+
+        let mut repos = listed();
+        sort_repos(&mut repos, RepoSort::StarredAt, true);
+        assert_eq!(names(&repos), ["vuejs/vue", "rust-lang/rust", "octocat/Hello-World"]);
+
+        let mut repos = vec![
+            repo("rust-lang/rust", 96000, "2024-05-14T07:02:41Z", Some("2023-01-02T00:00:00Z")),
+            repo("vuejs/vue", 207000, "2024-05-14T06:50:31Z", Some("2024-03-01T00:00:00Z")),
+        ];
+        sort_repos(&mut repos, RepoSort::StarredAt, true);
+        assert_eq!(names(&repos), ["vuejs/vue", "rust-lang/rust"]);
+    }
+}
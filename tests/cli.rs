@@ -0,0 +1,2892 @@
+//!
+//! End-to-end tests running the built binary against a mock GitHub API
+//! serving the shared fixtures in `fixtures/`.
+//!
+
+use assert_cmd::Command;
+use mockito::{Server, ServerGuard};
+use predicates::prelude::*;
+use serde_json::Value;
+use std::fs;
+use tempfile::TempDir;
+
+const REPOS_FIXTURE: &str = include_str!("../fixtures/repos.json");
+const STARRED_FIXTURE: &str = include_str!("../fixtures/starred.json");
+const USER_FIXTURE: &str = include_str!("../fixtures/user.json");
+
+fn fixture_repo(full_name: &str) -> Value {
+    let repos: Vec<Value> = serde_json::from_str(REPOS_FIXTURE).unwrap();
+    repos
+        .into_iter()
+        .find(|repo| repo["full_name"] == full_name)
+        .unwrap()
+}
+
+fn fixture_starred() -> Value {
+    let starred: Vec<String> = serde_json::from_str(STARRED_FIXTURE).unwrap();
+    starred.iter().map(|name| fixture_repo(name)).collect()
+}
+
+// Runs the CLI against the mock server, with the config and data directories isolated in `home`
+fn cli(server: &ServerGuard, home: &TempDir, token: &str) -> Command {
+    cli_at(&server.url(), home, token)
+}
+
+// Runs the CLI against the API at `api_url`, with the config and data directories isolated in `home`
+fn cli_at(api_url: &str, home: &TempDir, token: &str) -> Command {
+    let mut cmd = Command::cargo_bin("starts_fetcher").unwrap();
+    cmd.env("HOME", home.path())
+        .env("XDG_CONFIG_HOME", home.path().join(".config"))
+        .env("XDG_DATA_HOME", home.path().join(".local").join("share"))
+        .env("GITHUB_API_URL", api_url)
+        .env("CRATES_API_URL", api_url)
+        .env("NPM_API_URL", api_url)
+        .env("PYPI_API_URL", api_url)
+        .env("GITHUB_TOKEN", token);
+    cmd
+}
+
+#[test]
+fn test_no_arguments_shows_help() {
+    let mut cmd = Command::cargo_bin("starts_fetcher").unwrap();
+    cmd.assert()
+        .success()
+        .stdout(predicate::str::contains("Commands:"));
+}
+
+#[test]
+fn test_missing_token() {
+    let server = Server::new();
+    let home = TempDir::new().unwrap();
+
+    cli(&server, &home, "")
+        .env_remove("GITHUB_TOKEN")
+        .args(["list"])
+        .assert()
+        .code(3)
+        .stderr(predicate::str::contains("A token must be set in the config file or GITHUB_TOKEN"));
+}
+
+#[test]
+fn test_config_get_set_list() {
+    let server = Server::new();
+    let home = TempDir::new().unwrap();
+
+    cli(&server, &home, "env-token")
+        .args(["config", "set", "retry.max_attempts", "5"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Set retry.max_attempts in"));
+    cli(&server, &home, "env-token")
+        .args(["config", "get", "retry.max_attempts"])
+        .assert()
+        .success()
+        .stdout("5\n");
+    cli(&server, &home, "env-token")
+        .args(["config", "set", "retry.max_attempts", "0"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("retry.max_attempts must be at least 1"));
+    cli(&server, &home, "env-token")
+        .args(["config", "set", "github.colour", "blue"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Unknown setting github.colour"));
+    cli(&server, &home, "env-token")
+        .args(["config", "list"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("retry.max_attempts = 5"))
+        .stdout(predicate::str::contains("github.token = ********"))
+        .stdout(predicate::str::contains("env-token").not());
+
+    let path = home.path().join(".config").join("stars_fetcher").join("config.toml");
+    cli(&server, &home, "env-token")
+        .args(["config", "path"])
+        .assert()
+        .success()
+        .stdout(format!("{}\n", path.display()));
+    assert!(fs::read_to_string(&path).unwrap().contains("max_attempts = 5"));
+}
+
+#[cfg(unix)]
+#[test]
+fn test_config_edit_checks_the_file() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let server = Server::new();
+    let home = TempDir::new().unwrap();
+    let editor = home.path().join("editor.sh");
+    fs::write(&editor, "#!/bin/sh\nprintf '[retry]\\nmax_attempts = 0\\n' >> \"$1\"\n").unwrap();
+    fs::set_permissions(&editor, fs::Permissions::from_mode(0o755)).unwrap();
+
+    cli(&server, &home, "env-token")
+        .args(["config", "edit"])
+        .env_remove("VISUAL")
+        .env("EDITOR", "true")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Saved"));
+    cli(&server, &home, "env-token")
+        .args(["config", "edit"])
+        .env_remove("VISUAL")
+        .env("EDITOR", &editor)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("run config edit again"));
+}
+
+#[test]
+fn test_config_keyring_needs_a_token() {
+    let server = Server::new();
+    let home = TempDir::new().unwrap();
+
+    cli(&server, &home, "")
+        .env_remove("GITHUB_TOKEN")
+        .args(["config", "keyring"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("No token to move"));
+    let config = fs::read_to_string(home.path().join(".config").join("stars_fetcher").join("config.toml")).unwrap();
+    assert!(!config.contains("token_source"));
+}
+
+#[test]
+fn test_completions() {
+    let server = Server::new();
+    let home = TempDir::new().unwrap();
+
+    cli(&server, &home, "")
+        .env_remove("GITHUB_TOKEN")
+        .args(["completions", "bash"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("complete -F _starts_fetcher"))
+        .stdout(predicate::str::contains("--profile"))
+        .stdout(predicate::str::contains("export"));
+    cli(&server, &home, "").args(["completions", "tcsh"]).assert().failure();
+}
+
+#[test]
+fn test_manpage() {
+    let server = Server::new();
+    let home = TempDir::new().unwrap();
+    let output = home.path().join("man");
+
+    cli(&server, &home, "")
+        .env_remove("GITHUB_TOKEN")
+        .args(["manpage", "-o"])
+        .arg(&output)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("man pages to"));
+    let main = fs::read_to_string(output.join("starts_fetcher.1")).unwrap();
+    assert!(main.contains(".TH starts_fetcher 1"));
+    let config_get = fs::read_to_string(output.join("starts_fetcher-config-get.1")).unwrap();
+    assert!(config_get.contains("\\-\\-profile"));
+    assert!(!output.join("starts_fetcher-help.1").exists());
+}
+
+#[test]
+fn test_get_anonymous() {
+    let mut server = Server::new();
+    let home = TempDir::new().unwrap();
+    let mock = server
+        .mock("GET", "/repos/rust-lang/rust")
+        .match_header("authorization", mockito::Matcher::Missing)
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(fixture_repo("rust-lang/rust").to_string())
+        .create();
+
+    cli(&server, &home, "")
+        .env_remove("GITHUB_TOKEN")
+        .args(["get", "rust-lang/rust"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("rust-lang/rust"))
+        .stderr(predicate::str::contains("anonymous access"));
+
+    mock.assert();
+}
+
+#[test]
+fn test_list() {
+    let mut server = Server::new();
+    let home = TempDir::new().unwrap();
+    let mock = server
+        .mock("GET", "/user/starred?per_page=100")
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(fixture_starred().to_string())
+        .create();
+
+    cli(&server, &home, "fixture-token")
+        .arg("list")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("octocat/Hello-World"))
+        .stdout(predicate::str::contains("rust-lang/rust"));
+
+    mock.assert();
+}
+
+#[test]
+fn test_verbose_request_summary() {
+    let mut server = Server::new();
+    let home = TempDir::new().unwrap();
+    server
+        .mock("GET", "/user/starred?per_page=100")
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_header("etag", r#""starred-v1""#)
+        .with_body(fixture_starred().to_string())
+        .create();
+
+    cli(&server, &home, "fixture-token")
+        .args(["list", "-v"])
+        .assert()
+        .success()
+        .stderr(predicate::str::contains("1 requests (0 from cache), 1 pages in"))
+        .stderr(predicate::str::contains("Endpoint").not());
+    cli(&server, &home, "fixture-token")
+        .args(["list", "-vv"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("rust-lang/rust"))
+        .stderr(predicate::str::contains("Cache Hits"))
+        .stderr(predicate::str::is_match(r"GET /user/starred +\| 1 +\| \d+ms +\| [\d.]+ [KM]?B +\| 0 ").unwrap());
+    cli(&server, &home, "fixture-token")
+        .arg("list")
+        .assert()
+        .success()
+        .stderr(predicate::str::contains("requests").not());
+}
+
+#[test]
+fn test_list_page_size() {
+    let mut server = Server::new();
+    let home = TempDir::new().unwrap();
+    let configured = server
+        .mock("GET", "/user/starred?per_page=30")
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(fixture_starred().to_string())
+        .create();
+    let asked = server
+        .mock("GET", "/user/starred?per_page=5")
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(fixture_starred().to_string())
+        .create();
+
+    cli(&server, &home, "fixture-token").args(["config", "set", "pagination.per_page", "30"]).assert().success();
+    cli(&server, &home, "fixture-token").arg("list").assert().success();
+    cli(&server, &home, "fixture-token").args(["list", "--per-page", "5"]).assert().success();
+
+    configured.assert();
+    asked.assert();
+}
+
+#[test]
+fn test_list_revalidates_cached_responses() {
+    let mut server = Server::new();
+    let home = TempDir::new().unwrap();
+    let full = server
+        .mock("GET", "/user/starred?per_page=100")
+        .match_header("if-none-match", mockito::Matcher::Missing)
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_header("etag", r#""starred-v1""#)
+        .with_body(fixture_starred().to_string())
+        .expect(2)
+        .create();
+    let not_modified = server
+        .mock("GET", "/user/starred?per_page=100")
+        .match_header("if-none-match", r#""starred-v1""#)
+        .with_status(304)
+        .expect(1)
+        .create();
+
+    cli(&server, &home, "fixture-token").arg("list").assert().success();
+
+    let output = cli(&server, &home, "fixture-token")
+        .args(["list", "--format", "json"])
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+    let listing: Value = serde_json::from_slice(&output).unwrap();
+    assert_eq!(listing["items"][1]["full_name"], "rust-lang/rust");
+    assert_eq!(listing["meta"]["cache"]["hits"], 1);
+
+    cli(&server, &home, "fixture-token")
+        .args(["list", "--no-cache"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("rust-lang/rust"));
+
+    full.assert();
+    not_modified.assert();
+}
+
+#[test]
+fn test_cache_maintenance() {
+    let mut server = Server::new();
+    let home = TempDir::new().unwrap();
+    server
+        .mock("GET", "/user/starred?per_page=100")
+        .match_header("if-none-match", mockito::Matcher::Missing)
+        .with_status(200)
+        .with_header("etag", r#""starred-v1""#)
+        .with_body(fixture_starred().to_string())
+        .create();
+    server
+        .mock("GET", "/user/starred?per_page=100")
+        .match_header("if-none-match", r#""starred-v1""#)
+        .with_status(304)
+        .create();
+
+    for _ in 0..2 {
+        cli(&server, &home, "fixture-token").arg("list").assert().success();
+    }
+
+    cli(&server, &home, "fixture-token")
+        .args(["cache", "stats"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("1 responses kept"))
+        .stdout(predicate::str::contains("1 of 2 responses served from the cache (50%)"))
+        .stdout(predicate::str::contains(format!("{}/user/starred", server.url())));
+    cli(&server, &home, "fixture-token")
+        .args(["cache", "prune", "--older-than", "1d"])
+        .assert()
+        .success()
+        .stdout("Removed 0 responses fetched more than 1d ago\n");
+    cli(&server, &home, "fixture-token")
+        .args(["cache", "clear"])
+        .assert()
+        .success()
+        .stdout("Removed 1 responses\n");
+    cli(&server, &home, "fixture-token")
+        .args(["cache", "stats"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("0 responses kept, 0 B"));
+}
+
+#[test]
+fn test_store_dump() {
+    let server = Server::new();
+    let home = TempDir::new().unwrap();
+    let output = home.path().join("dump");
+    cli(&server, &home, "fixture-token").args(["pin", "rust-lang/rust"]).assert().success();
+    cli(&server, &home, "fixture-token").args(["snooze", "vuejs/vue", "--for", "7d"]).assert().success();
+
+    cli(&server, &home, "fixture-token")
+        .args(["store", "dump", "-o"])
+        .arg(&output)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Wrote 1 rows to"))
+        .stdout(predicate::str::contains("traffic.csv"));
+
+    assert_eq!(fs::read_to_string(output.join("pins.csv")).unwrap(), "repo,position\r\nrust-lang/rust,1\r\n");
+    assert!(fs::read_to_string(output.join("snoozes.csv")).unwrap().contains("vuejs/vue,"));
+    assert_eq!(fs::read_to_string(output.join("traffic.csv")).unwrap(), "repo,date,views,unique_views,clones,unique_clones\r\n");
+
+    cli(&server, &home, "fixture-token")
+        .args(["store", "dump", "--format", "json", "-o"])
+        .arg(&output)
+        .assert()
+        .success();
+    let pins: Value = serde_json::from_str(&fs::read_to_string(output.join("pins.json")).unwrap()).unwrap();
+    assert_eq!(pins, serde_json::json!([{ "repo": "rust-lang/rust", "position": "1" }]));
+}
+
+#[test]
+fn test_store_rollback() {
+    let server = Server::new();
+    let home = TempDir::new().unwrap();
+    let data = home.path().join(".local").join("share").join("stars_fetcher");
+    fs::create_dir_all(&data).unwrap();
+    fs::write(data.join("pins.json"), r#"{"repos": ["vuejs/vue"]}"#).unwrap();
+
+    cli(&server, &home, "fixture-token")
+        .args(["store", "rollback"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("No backups to restore"));
+
+    cli(&server, &home, "fixture-token").args(["pin", "rust-lang/rust"]).assert().success();
+    let pins: Value = serde_json::from_str(&fs::read_to_string(data.join("pins.json")).unwrap()).unwrap();
+    assert_eq!(pins["schema_version"], 1);
+    assert_eq!(pins["data"]["repos"], serde_json::json!(["vuejs/vue", "rust-lang/rust"]));
+
+    cli(&server, &home, "fixture-token")
+        .args(["store", "rollback"])
+        .assert()
+        .success()
+        .stdout("Restored pins\n");
+    assert_eq!(fs::read_to_string(data.join("pins.json")).unwrap(), r#"{"repos": ["vuejs/vue"]}"#);
+}
+
+#[test]
+fn test_export_graph() {
+    let mut server = Server::new();
+    let home = TempDir::new().unwrap();
+    let mut cargo = fixture_repo("rust-lang/rust");
+    cargo["full_name"] = "rust-lang/cargo".into();
+    cargo["topics"] = serde_json::json!(["package-manager"]);
+    let starred = serde_json::json!([fixture_repo("rust-lang/rust"), cargo, fixture_repo("vuejs/vue")]);
+    server
+        .mock("GET", "/user/starred?per_page=100")
+        .with_status(200)
+        .with_body(starred.to_string())
+        .expect(2)
+        .create();
+
+    cli(&server, &home, "fixture-token")
+        .args(["export", "--link", "language"])
+        .assert()
+        .success()
+        .stdout(predicate::str::starts_with("graph stars {"))
+        .stdout(predicate::str::contains("\"repo:rust-lang/cargo\" -- \"language:Rust\";"))
+        .stdout(predicate::str::contains("owner:rust-lang").not());
+
+    let output = home.path().join("stars.gexf");
+    cli(&server, &home, "fixture-token")
+        .args(["export", "--format", "gexf", "-o"])
+        .arg(&output)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Wrote 5 nodes and 4 edges"));
+    assert!(fs::read_to_string(&output).unwrap().contains("<node id=\"owner:rust-lang\" label=\"rust-lang\">"));
+}
+
+#[test]
+fn test_export_html() {
+    let mut server = Server::new();
+    let home = TempDir::new().unwrap();
+    server
+        .mock("GET", "/user/starred?per_page=100")
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(fixture_starred().to_string())
+        .create();
+
+    let output = home.path().join("stars.html");
+    cli(&server, &home, "fixture-token")
+        .args(["export", "--format", "html", "-o"])
+        .arg(&output)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Wrote 3 repositories to"));
+    let page = fs::read_to_string(&output).unwrap();
+    assert!(page.starts_with("<!DOCTYPE html>"));
+    assert!(page.contains(r#""full_name":"rust-lang/rust""#));
+    assert!(page.contains("<input id=\"search\""));
+}
+
+#[test]
+fn test_whoami() {
+    let mut server = Server::new();
+    let home = TempDir::new().unwrap();
+    server
+        .mock("GET", "/user")
+        .match_header("authorization", "Bearer fixture-token")
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_header("x-oauth-scopes", "public_repo, read:user")
+        .with_body(USER_FIXTURE)
+        .create();
+
+    cli(&server, &home, "fixture-token")
+        .arg("whoami")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(format!("Logged in to {} as octocat (The Octocat)", server.url())))
+        .stdout(predicate::str::contains("Plan: free"))
+        .stdout(predicate::str::contains("Token: classic, scopes: public_repo, read:user"));
+    cli(&server, &home, "fixture-token")
+        .args(["whoami", "--format", "json", "--no-meta"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(r#""login": "octocat""#))
+        .stdout(predicate::str::contains(r#""token": "classic""#));
+}
+
+#[test]
+fn test_rate_limit() {
+    let mut server = Server::new();
+    let home = TempDir::new().unwrap();
+    server
+        .mock("GET", "/rate_limit")
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(serde_json::json!({ "resources": {
+            "core": { "limit": 5000, "used": 1, "remaining": 4999, "reset": 1700000000 },
+            "search": { "limit": 30, "used": 12, "remaining": 18, "reset": 1700000060 },
+            "graphql": { "limit": 5000, "used": 0, "remaining": 5000, "reset": 1700003600 }
+        } }).to_string())
+        .create();
+
+    cli(&server, &home, "fixture-token")
+        .env("TZ", "UTC")
+        .arg("rate-limit")
+        .assert()
+        .success()
+        .stdout(predicate::str::is_match(r"core\s*\|\s*4999\s*\|\s*5000\s*\|\s*2023-11-14 22:13:20 \+00:00").unwrap())
+        .stdout(predicate::str::is_match(r"search\s*\|\s*18\s*\|\s*30").unwrap())
+        .stdout(predicate::str::contains("graphql"));
+    cli(&server, &home, "")
+        .env("TZ", "UTC")
+        .args(["rate-limit", "--format", "json", "--no-meta"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(r#""reset": 1700000060"#))
+        .stdout(predicate::str::contains(r#""resets_at": "2023-11-14 23:13:20 +00:00""#));
+}
+
+#[test]
+fn test_on_this_day() {
+    let mut server = Server::new();
+    let home = TempDir::new().unwrap();
+    server
+        .mock("GET", "/user/starred?per_page=100")
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(serde_json::json!([
+            { "starred_at": "2024-05-01T12:00:00Z", "repo": { "full_name": "rust-lang/rust" } },
+            { "starred_at": "2021-05-01T08:00:00Z", "repo": { "full_name": "vuejs/vue" } },
+            { "starred_at": "2020-06-01T08:00:00Z", "repo": { "full_name": "octocat/Hello-World" } },
+        ]).to_string())
+        .create();
+
+    cli(&server, &home, "fixture-token")
+        .args(["onthisday", "--date", "2024-05-01"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("vuejs/vue"))
+        .stdout(predicate::str::contains("rust-lang/rust").not())
+        .stdout(predicate::str::contains("octocat/Hello-World").not());
+    cli(&server, &home, "fixture-token")
+        .args(["onthisday", "--date", "2024-07-04"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Nothing starred on 07-04 in earlier years"));
+}
+
+#[test]
+fn test_map_needs_a_terminal() {
+    let server = Server::new();
+    let home = TempDir::new().unwrap();
+
+    cli(&server, &home, "fixture-token")
+        .arg("map")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("The map needs a terminal"));
+    cli(&server, &home, "fixture-token").args(["map", "--by", "owner"]).assert().failure();
+}
+
+#[test]
+fn test_list_formats() {
+    let mut server = Server::new();
+    let home = TempDir::new().unwrap();
+    server
+        .mock("GET", "/user/starred?per_page=100")
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(fixture_starred().to_string())
+        .create();
+
+    let output = cli(&server, &home, "fixture-token")
+        .args(["list", "--format", "json"])
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+    let listing: Value = serde_json::from_slice(&output).unwrap();
+    assert_eq!(listing["items"][1]["full_name"], "rust-lang/rust");
+    assert_eq!(listing["meta"]["total"], 3);
+    assert_eq!(listing["meta"]["pages"], 1);
+    assert_eq!(listing["meta"]["cache"]["misses"], 1);
+
+    let output = cli(&server, &home, "fixture-token")
+        .args(["list", "--format", "json", "--no-meta"])
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+    let repos: Value = serde_json::from_slice(&output).unwrap();
+    assert_eq!(repos[1]["full_name"], "rust-lang/rust");
+
+    cli(&server, &home, "fixture-token")
+        .args(["list", "--format", "csv"])
+        .assert()
+        .success()
+        .stdout(predicate::str::starts_with("ID,Name,Full Name,Description,URL\r\n"))
+        .stdout(predicate::str::contains(",rust,rust-lang/rust,"));
+
+    cli(&server, &home, "fixture-token")
+        .args(["list", "--format", "yaml"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("- archived: false"))
+        .stdout(predicate::str::contains("  description: \"My first repository on GitHub!\""))
+        .stdout(predicate::str::contains("  full_name: \"vuejs/vue\""));
+
+    cli(&server, &home, "fixture-token")
+        .args(["list", "--format", "markdown"])
+        .assert()
+        .success()
+        .stdout(predicate::str::starts_with("| ID | Name | Full Name | Description | URL |\n| --- | --- | --- | --- | --- |\n"))
+        .stdout(predicate::str::contains("| rust | rust-lang/rust |"));
+}
+
+#[test]
+fn test_stats_self() {
+    let mut server = Server::new();
+    let home = TempDir::new().unwrap();
+    server
+        .mock("GET", "/user/starred?per_page=100")
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(fixture_starred().to_string())
+        .create();
+
+    cli(&server, &home, "fixture-token").arg("list").assert().success();
+    cli(&server, &home, "fixture-token").arg("list").assert().success();
+    cli(&server, &home, "fixture-token").args(["installed", "list"]).assert().success();
+
+    let output = cli(&server, &home, "fixture-token")
+        .args(["stats", "--self", "--format", "json"])
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+    let usage: Value = serde_json::from_slice(&output).unwrap();
+    assert_eq!(usage["list"]["runs"], 2);
+    assert_eq!(usage["list"]["failures"], 0);
+    assert_eq!(usage["installed list"]["runs"], 1);
+
+    cli(&server, &home, "fixture-token")
+        .args(["stats", "--self", "--reset"])
+        .assert()
+        .success();
+    cli(&server, &home, "fixture-token")
+        .args(["stats", "--self"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("No usage recorded yet"));
+}
+
+#[test]
+fn test_pinned_repos_listed_first() {
+    let mut server = Server::new();
+    let home = TempDir::new().unwrap();
+    server
+        .mock("GET", "/user/starred?per_page=100")
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(fixture_starred().to_string())
+        .create();
+
+    cli(&server, &home, "fixture-token")
+        .args(["pin", "https://github.com/vuejs/vue"])
+        .assert()
+        .success()
+        .stdout("Pinned vuejs/vue\n");
+    cli(&server, &home, "fixture-token")
+        .args(["pin", "rust-lang/rust"])
+        .assert()
+        .success();
+    cli(&server, &home, "fixture-token")
+        .arg("pin")
+        .assert()
+        .success()
+        .stdout("vuejs/vue\nrust-lang/rust\n");
+
+    let output = cli(&server, &home, "fixture-token")
+        .args(["list", "--format", "json", "--no-meta"])
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+    let repos: Vec<Value> = serde_json::from_slice(&output).unwrap();
+    let names: Vec<&str> = repos.iter().map(|repo| repo["full_name"].as_str().unwrap()).collect();
+    assert_eq!(names, vec!["vuejs/vue", "rust-lang/rust", "octocat/Hello-World"]);
+
+    cli(&server, &home, "fixture-token")
+        .args(["unpin", "vuejs/vue"])
+        .assert()
+        .success()
+        .stdout("Unpinned vuejs/vue\n");
+    cli(&server, &home, "fixture-token")
+        .arg("pin")
+        .assert()
+        .success()
+        .stdout("rust-lang/rust\n");
+}
+
+#[test]
+fn test_snoozed_repos_hidden_from_list() {
+    let mut server = Server::new();
+    let home = TempDir::new().unwrap();
+    server
+        .mock("GET", "/user/starred?per_page=100")
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(fixture_starred().to_string())
+        .create();
+
+    cli(&server, &home, "fixture-token")
+        .args(["snooze", "vuejs/vue", "--for", "30d"])
+        .assert()
+        .success()
+        .stdout("Snoozed vuejs/vue for 30d\n");
+    cli(&server, &home, "fixture-token")
+        .arg("snooze")
+        .assert()
+        .success()
+        .stdout(predicate::str::starts_with("vuejs/vue (another 30d)"));
+    cli(&server, &home, "fixture-token")
+        .arg("list")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("rust-lang/rust"))
+        .stdout(predicate::str::contains("vuejs/vue").not())
+        .stderr(predicate::str::contains("1 snoozed repositories are hidden"));
+    cli(&server, &home, "fixture-token")
+        .args(["snooze", "vuejs/vue", "--for", "soon"])
+        .assert()
+        .failure();
+
+    cli(&server, &home, "fixture-token")
+        .args(["unsnooze", "vuejs/vue"])
+        .assert()
+        .success();
+    cli(&server, &home, "fixture-token")
+        .arg("list")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("vuejs/vue"));
+}
+
+#[test]
+fn test_verify_reconciles_local_entries() {
+    let mut server = Server::new();
+    let home = TempDir::new().unwrap();
+    server
+        .mock("GET", "/user/starred?per_page=100")
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(serde_json::json!([
+            { "starred_at": "2015-01-01T00:00:00Z", "repo": { "full_name": "vuejs/vue" } },
+            { "starred_at": "2099-01-01T00:00:00Z", "repo": { "full_name": "rust-lang/rust" } },
+        ]).to_string())
+        .create();
+
+    for args in [["pin", "octocat/Hello-World"], ["archive", "vuejs/vue"], ["archive", "rust-lang/rust"]] {
+        cli(&server, &home, "fixture-token").args(args).assert().success();
+    }
+
+    cli(&server, &home, "fixture-token")
+        .arg("verify")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("octocat/Hello-World is pinned but no longer starred (fix: unpin)"))
+        .stdout(predicate::str::contains("rust-lang/rust is archived but was starred again since (fix: unarchive)"))
+        .stdout(predicate::str::contains("2 of 3 entries don't match GitHub"));
+    cli(&server, &home, "fixture-token")
+        .args(["verify", "--fix"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Fixed 2 entries"));
+    cli(&server, &home, "fixture-token")
+        .arg("verify")
+        .assert()
+        .success()
+        .stdout("Local store matches GitHub: 1 entries checked against 2 stars\n");
+    cli(&server, &home, "fixture-token")
+        .arg("archive")
+        .assert()
+        .success()
+        .stdout("vuejs/vue\n");
+}
+
+#[test]
+fn test_auto_archive_reports_before_archiving() {
+    let mut server = Server::new();
+    let home = TempDir::new().unwrap();
+    server
+        .mock("GET", "/user/starred?per_page=100")
+        .match_header("accept", "application/vnd.github.star+json")
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(serde_json::json!([
+            { "starred_at": "2015-01-01T00:00:00Z", "repo": { "full_name": "vuejs/vue" } },
+            { "starred_at": "2015-01-01T00:00:00Z", "repo": { "full_name": "octocat/Hello-World" } },
+            { "starred_at": "2099-01-01T00:00:00Z", "repo": { "full_name": "rust-lang/rust" } },
+        ]).to_string())
+        .create();
+    server
+        .mock("GET", "/user/starred?per_page=100")
+        .match_header("accept", "application/vnd.github+json")
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(fixture_starred().to_string())
+        .create();
+    let config_dir = home.path().join(".config").join("stars_fetcher");
+    fs::create_dir_all(&config_dir).unwrap();
+    fs::write(
+        config_dir.join("config.toml"),
+        "[github]\nemail = \"\"\napi_url = \"https://api.github.com\"\n\n[archive]\nauto_archive_after = \"2y\"\n",
+    ).unwrap();
+
+    cli(&server, &home, "fixture-token")
+        .args(["pin", "octocat/Hello-World"])
+        .assert()
+        .success();
+    cli(&server, &home, "fixture-token")
+        .args(["archive", "--auto"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Would archive 1 stars older than 2y:\n  vuejs/vue\n"))
+        .stdout(predicate::str::contains("This is a dry run"));
+    cli(&server, &home, "fixture-token")
+        .arg("archive")
+        .assert()
+        .success()
+        .stdout("No archived repositories\n");
+
+    cli(&server, &home, "fixture-token")
+        .args(["archive", "--auto"])
+        .assert()
+        .success()
+        .stdout("Archiving 1 stars older than 2y:\n  vuejs/vue\n");
+    cli(&server, &home, "fixture-token")
+        .arg("list")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("vuejs/vue").not())
+        .stderr(predicate::str::contains("1 archived repositories are hidden"));
+
+    cli(&server, &home, "fixture-token")
+        .args(["unarchive", "vuejs/vue"])
+        .assert()
+        .success()
+        .stdout("Unarchived vuejs/vue\n");
+}
+
+#[test]
+fn test_bug_report_after_failure() {
+    let mut server = Server::new();
+    let home = TempDir::new().unwrap();
+    server
+        .mock("GET", "/user/starred?per_page=100")
+        .with_status(401)
+        .with_header("content-type", "application/json")
+        .with_header("x-github-request-id", "C0DE:1234")
+        .with_body(r#"{"message": "Bad credentials"}"#)
+        .create();
+
+    cli(&server, &home, "secret-fixture-token")
+        .current_dir(home.path())
+        .args(["list", "--bug-report"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Wrote a bug report to"));
+
+    let report = fs::read_dir(home.path())
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .find(|path| path.file_name().unwrap().to_string_lossy().starts_with("stars_fetcher-bug-report-"))
+        .expect("bug report written");
+    let report = fs::read_to_string(report).unwrap();
+    assert!(report.contains("Bad credentials"));
+    assert!(report.contains(&format!("- URL: {}/user/starred", server.url())));
+    assert!(report.contains("- Status: 401"));
+    assert!(report.contains("- Request ID: C0DE:1234"));
+    assert!(!report.contains("secret-fixture-token"));
+}
+
+#[test]
+fn test_list_through_graphql() {
+    let mut server = Server::new();
+    let home = TempDir::new().unwrap();
+    let node = |id: u64, owner: &str, name: &str, language: &str| serde_json::json!({
+        "starredAt": "2024-05-01T12:00:00Z",
+        "node": {
+            "databaseId": id, "name": name, "description": null, "url": format!("https://github.com/{}/{}", owner, name),
+            "stargazerCount": 1000, "forkCount": 10, "isArchived": false, "isFork": false,
+            "owner": { "login": owner }, "primaryLanguage": { "name": language },
+            "repositoryTopics": { "nodes": [] }
+        }
+    });
+    let rest = server.mock("GET", "/user/starred?per_page=100").expect(0).create();
+    server
+        .mock("POST", "/graphql")
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(serde_json::json!({ "data": { "viewer": { "starredRepositories": {
+            "pageInfo": { "hasNextPage": false, "endCursor": null },
+            "edges": [node(1, "rust-lang", "rust", "Rust"), node(2, "vuejs", "vue", "TypeScript")]
+        } } } }).to_string())
+        .create();
+    let config_dir = home.path().join(".config").join("stars_fetcher");
+    fs::create_dir_all(&config_dir).unwrap();
+    fs::write(
+        config_dir.join("config.toml"),
+        "[github]\nemail = \"\"\napi_url = \"https://api.github.com\"\nbackend = \"graphql\"\n",
+    ).unwrap();
+
+    cli(&server, &home, "fixture-token")
+        .args(["list", "--filter", "language:rust"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("rust-lang/rust"))
+        .stdout(predicate::str::contains("vuejs/vue").not());
+    rest.assert();
+}
+
+#[test]
+fn test_profile_uses_its_own_token() {
+    let mut server = Server::new();
+    let home = TempDir::new().unwrap();
+    let work = server
+        .mock("PUT", "/user/starred/octocat/hello-world")
+        .match_header("authorization", "Bearer work-token")
+        .with_status(204)
+        .create();
+    let config_dir = home.path().join(".config").join("stars_fetcher");
+    fs::create_dir_all(&config_dir).unwrap();
+    fs::write(
+        config_dir.join("config.toml"),
+        "[github]\nemail = \"\"\napi_url = \"https://api.github.com\"\n\n\
+         [profiles.work]\ntoken = \"work-token\"\napi_url = \"https://github.example.com/api/v3\"\n",
+    ).unwrap();
+
+    cli(&server, &home, "personal-token")
+        .args(["star", "octocat", "hello-world", "--profile", "work"])
+        .assert()
+        .success();
+    cli(&server, &home, "personal-token")
+        .args(["--profile", "home", "star", "octocat", "hello-world"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Unknown profile home"));
+    work.assert();
+}
+
+#[test]
+fn test_token_from_config_file() {
+    let mut server = Server::new();
+    let home = TempDir::new().unwrap();
+    let starred = server
+        .mock("GET", "/user/starred")
+        .match_query(mockito::Matcher::Any)
+        .match_header("authorization", "Bearer file_token")
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(serde_json::json!([fixture_repo("rust-lang/rust")]).to_string())
+        .create();
+    let config_dir = home.path().join(".config").join("stars_fetcher");
+    fs::create_dir_all(&config_dir).unwrap();
+    fs::write(
+        config_dir.join("config.toml"),
+        "[github]\ntoken = \"file_token\"\nemail = \"\"\napi_url = \"https://api.github.com\"\n",
+    ).unwrap();
+
+    cli(&server, &home, "")
+        .env_remove("GITHUB_TOKEN")
+        .arg("list")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("rust-lang/rust"));
+    starred.assert();
+}
+
+#[test]
+fn test_stars_on_codeberg() {
+    let mut server = Server::new();
+    let home = TempDir::new().unwrap();
+    server
+        .mock("GET", "/user/starred?page=1&limit=50")
+        .match_header("authorization", "token fixture-token")
+        .with_status(200)
+        .with_body(serde_json::json!([{
+            "id": 7, "name": "forgejo", "full_name": "forgejo/forgejo", "description": "Beyond coding",
+            "html_url": "https://codeberg.org/forgejo/forgejo", "stars_count": 2000
+        }]).to_string())
+        .create();
+    let star = server.mock("PUT", "/user/starred/forgejo/runner").with_status(204).create();
+    let config_dir = home.path().join(".config").join("stars_fetcher");
+    fs::create_dir_all(&config_dir).unwrap();
+    fs::write(
+        config_dir.join("config.toml"),
+        "[github]\nemail = \"\"\napi_url = \"https://codeberg.org/api/v1\"\nforge = \"codeberg\"\n",
+    ).unwrap();
+
+    cli(&server, &home, "fixture-token")
+        .arg("list")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("forgejo/forgejo"));
+    cli(&server, &home, "fixture-token")
+        .args(["star", "forgejo", "runner"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Starred repository forgejo/runner"));
+    cli(&server, &home, "fixture-token")
+        .args(["detail", "forgejo", "forgejo"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("detail is only available on GitHub, not on gitea"));
+    star.assert();
+}
+
+#[test]
+fn test_list_with_saved_filter() {
+    let mut server = Server::new();
+    let home = TempDir::new().unwrap();
+    server
+        .mock("GET", "/user/starred?per_page=100")
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(fixture_starred().to_string())
+        .create();
+    let config_dir = home.path().join(".config").join("stars_fetcher");
+    fs::create_dir_all(&config_dir).unwrap();
+    fs::write(
+        config_dir.join("config.toml"),
+        "[github]\nemail = \"\"\napi_url = \"https://api.github.com\"\n\n[filters]\nrusty = \"language:rust stars:>500\"\n",
+    ).unwrap();
+
+    cli(&server, &home, "fixture-token")
+        .args(["list", "--filter", "rusty"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("rust-lang/rust"))
+        .stdout(predicate::str::contains("octocat/Hello-World").not())
+        .stdout(predicate::str::contains("vuejs/vue").not());
+
+    cli(&server, &home, "fixture-token")
+        .args(["list", "--filter", "archived:true"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("vuejs/vue"))
+        .stdout(predicate::str::contains("rust-lang/rust").not());
+    cli(&server, &home, "fixture-token")
+        .args(["list", "--filter", "(topic:compiler OR topic:vue) NOT archived"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("rust-lang/rust"))
+        .stdout(predicate::str::contains("vuejs/vue").not());
+}
+
+#[test]
+fn test_list_by_language() {
+    let mut server = Server::new();
+    let home = TempDir::new().unwrap();
+    server
+        .mock("GET", "/user/starred?per_page=100")
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(fixture_starred().to_string())
+        .create();
+
+    cli(&server, &home, "fixture-token")
+        .args(["list", "--language", "rust"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("rust-lang/rust"))
+        .stdout(predicate::str::contains("octocat/Hello-World").not())
+        .stdout(predicate::str::contains("vuejs/vue").not());
+    cli(&server, &home, "fixture-token")
+        .args(["list", "--language", "Go,TypeScript"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("vuejs/vue"))
+        .stdout(predicate::str::contains("rust-lang/rust").not());
+    cli(&server, &home, "fixture-token")
+        .args(["list", "--language", "rust,typescript", "--filter", "archived:true"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("vuejs/vue"))
+        .stdout(predicate::str::contains("rust-lang/rust").not());
+}
+
+#[test]
+fn test_list_by_topic() {
+    let mut server = Server::new();
+    let home = TempDir::new().unwrap();
+    server
+        .mock("GET", "/user/starred?per_page=100")
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(fixture_starred().to_string())
+        .create();
+
+    cli(&server, &home, "fixture-token")
+        .args(["list", "--topic", "rust"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("rust-lang/rust"))
+        .stdout(predicate::str::contains("vuejs/vue").not());
+    cli(&server, &home, "fixture-token")
+        .args(["list", "--topic", "rust", "--topic", "vue"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("rust-lang/rust").not())
+        .stdout(predicate::str::contains("vuejs/vue").not());
+    cli(&server, &home, "fixture-token")
+        .args(["list", "--topic", "frontend", "--language", "typescript"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("vuejs/vue"))
+        .stdout(predicate::str::contains("rust-lang/rust").not());
+}
+
+#[test]
+fn test_list_sorted() {
+    let mut server = Server::new();
+    let home = TempDir::new().unwrap();
+    server
+        .mock("GET", "/user/starred?per_page=100")
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(fixture_starred().to_string())
+        .create();
+    let listed = |args: &[&str]| -> Vec<String> {
+        let output = cli(&server, &home, "fixture-token")
+            .args(["list", "--format", "json", "--no-meta"])
+            .args(args)
+            .assert()
+            .success()
+            .get_output()
+            .stdout
+            .clone();
+        let repos: Vec<Value> = serde_json::from_slice(&output).unwrap();
+        repos.iter().map(|repo| repo["full_name"].as_str().unwrap().to_string()).collect()
+    };
+
+    assert_eq!(listed(&["--sort", "stars", "--desc"]), ["vuejs/vue", "rust-lang/rust", "octocat/Hello-World"]);
+    assert_eq!(listed(&["--sort", "name"]), ["octocat/Hello-World", "rust-lang/rust", "vuejs/vue"]);
+    assert_eq!(listed(&["--sort", "updated", "--desc"]), ["rust-lang/rust", "vuejs/vue", "octocat/Hello-World"]);
+    assert_eq!(listed(&["--sort", "starred_at"]), ["vuejs/vue", "rust-lang/rust", "octocat/Hello-World"]);
+
+    cli(&server, &home, "fixture-token")
+        .args(["list", "--desc"])
+        .assert()
+        .failure();
+}
+
+#[test]
+fn test_list_with_invalid_filter() {
+    let server = Server::new();
+    let home = TempDir::new().unwrap();
+
+    cli(&server, &home, "fixture-token")
+        .args(["list", "--filter", "tag:tools"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Unknown filter"));
+}
+
+#[test]
+fn test_list_bad_token() {
+    let mut server = Server::new();
+    let home = TempDir::new().unwrap();
+    server
+        .mock("GET", "/user/starred?per_page=100")
+        .with_status(401)
+        .with_body(r#"{"message": "Bad credentials"}"#)
+        .create();
+
+    cli(&server, &home, "bad-token")
+        .arg("list")
+        .assert()
+        .code(3)
+        .stderr(predicate::str::contains("401"))
+        .stderr(predicate::str::contains("Bad credentials"));
+}
+
+#[test]
+fn test_get() {
+    let mut server = Server::new();
+    let home = TempDir::new().unwrap();
+    server
+        .mock("GET", "/repos/rust-lang/rust")
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(fixture_repo("rust-lang/rust").to_string())
+        .create();
+
+    cli(&server, &home, "fixture-token")
+        .args(["get", "rust-lang", "rust"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("rust-lang/rust"))
+        .stdout(predicate::str::contains("Empowering everyone"));
+}
+
+#[test]
+fn test_get_owner_repo_form() {
+    let mut server = Server::new();
+    let home = TempDir::new().unwrap();
+    let mock = server
+        .mock("GET", "/repos/rust-lang/rust")
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(fixture_repo("rust-lang/rust").to_string())
+        .create();
+
+    cli(&server, &home, "fixture-token")
+        .args(["get", "https://github.com/rust-lang/rust"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("rust-lang/rust"));
+
+    mock.assert();
+}
+
+#[test]
+fn test_get_invalid_repository() {
+    let server = Server::new();
+    let home = TempDir::new().unwrap();
+
+    cli(&server, &home, "fixture-token")
+        .args(["get", "not a repo"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Expected owner/repo"));
+}
+
+#[test]
+fn test_get_not_found() {
+    let mut server = Server::new();
+    let home = TempDir::new().unwrap();
+    server
+        .mock("GET", "/repos/octocat/missing")
+        .with_status(404)
+        .with_body(r#"{"message": "Not Found"}"#)
+        .create();
+
+    cli(&server, &home, "fixture-token")
+        .args(["get", "octocat", "missing"])
+        .assert()
+        .code(4)
+        .stderr(predicate::str::contains("Not Found"));
+}
+
+#[test]
+fn test_unsupported_api_version() {
+    let mut server = Server::new();
+    let home = TempDir::new().unwrap();
+    let mock = server
+        .mock("GET", "/repos/rust-lang/rust")
+        .match_header("x-github-api-version", "2022-11-28")
+        .with_status(400)
+        .with_body(r#"{"message": "Unsupported 'X-GitHub-Api-Version' header"}"#)
+        .create();
+
+    cli(&server, &home, "fixture-token")
+        .args(["get", "rust-lang/rust"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("does not support GitHub API version"));
+
+    mock.assert();
+}
+
+#[test]
+fn test_detail() {
+    let mut server = Server::new();
+    let home = TempDir::new().unwrap();
+    server
+        .mock("GET", "/repos/tokio-rs/tokio")
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(fixture_repo("tokio-rs/tokio").to_string())
+        .create();
+
+    cli(&server, &home, "fixture-token")
+        .args(["detail", "tokio-rs", "tokio"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("https://github.com/tokio-rs/tokio"))
+        .stdout(predicate::str::contains("Owner: tokio-rs (organization), https://github.com/tokio-rs"))
+        .stdout(predicate::str::contains("Topics: asynchronous, networking, rust"));
+    cli(&server, &home, "fixture-token")
+        .args(["detail", "tokio-rs", "tokio", "--format", "json", "--no-meta"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(r#""type": "Organization""#));
+}
+
+#[test]
+fn test_star() {
+    let mut server = Server::new();
+    let home = TempDir::new().unwrap();
+    server
+        .mock("GET", "/user")
+        .with_status(200)
+        .with_header("x-oauth-scopes", "public_repo, read:user")
+        .with_body(r#"{"login": "octocat"}"#)
+        .create();
+    let mock = server
+        .mock("PUT", "/user/starred/tokio-rs/tokio")
+        .with_status(204)
+        .create();
+
+    cli(&server, &home, "fixture-token")
+        .args(["star", "tokio-rs", "tokio"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Starred repository tokio-rs/tokio"));
+
+    mock.assert();
+}
+
+#[test]
+fn test_star_json() {
+    let mut server = Server::new();
+    let home = TempDir::new().unwrap();
+    server
+        .mock("GET", "/user")
+        .with_status(200)
+        .with_header("x-oauth-scopes", "public_repo, read:user")
+        .with_body(r#"{"login": "octocat"}"#)
+        .create();
+    server
+        .mock("PUT", "/user/starred/tokio-rs/tokio")
+        .with_status(204)
+        .create();
+
+    cli(&server, &home, "fixture-token")
+        .args(["star", "tokio-rs/tokio", "--format", "json"])
+        .assert()
+        .success()
+        .stdout(predicate::str::is_match(r#"(?s)\[\s*\{\s*"repository": "tokio-rs/tokio",\s*"starred": true\s*\}\s*\]"#).unwrap());
+}
+
+#[test]
+fn test_star_from_pull_request_url() {
+    let mut server = Server::new();
+    let home = TempDir::new().unwrap();
+    server
+        .mock("GET", "/user")
+        .with_status(200)
+        .with_header("x-oauth-scopes", "public_repo")
+        .with_body(r#"{"login": "octocat"}"#)
+        .create();
+    let mock = server
+        .mock("PUT", "/user/starred/tokio-rs/tokio")
+        .with_status(204)
+        .create();
+
+    cli(&server, &home, "fixture-token")
+        .args(["star", "https://github.com/tokio-rs/tokio/pull/6000/files"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Starred repository tokio-rs/tokio"));
+
+    mock.assert();
+}
+
+#[test]
+fn test_star_missing_scope() {
+    let mut server = Server::new();
+    let home = TempDir::new().unwrap();
+    server
+        .mock("GET", "/user")
+        .with_status(200)
+        .with_header("x-oauth-scopes", "read:user")
+        .with_body(r#"{"login": "octocat"}"#)
+        .create();
+    let star = server
+        .mock("PUT", "/user/starred/tokio-rs/tokio")
+        .expect(0)
+        .create();
+
+    cli(&server, &home, "fixture-token")
+        .args(["star", "tokio-rs/tokio"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("public_repo"));
+
+    star.assert();
+}
+
+#[test]
+fn test_star_rate_limited() {
+    let mut server = Server::new();
+    let home = TempDir::new().unwrap();
+    server
+        .mock("PUT", "/user/starred/tokio-rs/tokio")
+        .with_status(403)
+        .with_header("x-ratelimit-remaining", "0")
+        .with_body(r#"{"message": "API rate limit exceeded"}"#)
+        .create();
+
+    cli(&server, &home, "fixture-token")
+        .args(["star", "tokio-rs", "tokio"])
+        .assert()
+        .code(5)
+        .stdout(predicate::str::contains("Starred").not())
+        .stderr(predicate::str::contains("rate limit"));
+}
+
+#[test]
+fn test_star_waits_on_ratelimit() {
+    let mut server = Server::new();
+    let home = TempDir::new().unwrap();
+    let limited = server
+        .mock("PUT", "/user/starred/tokio-rs/tokio")
+        .with_status(403)
+        .with_header("retry-after", "1")
+        .with_body(r#"{"message": "You have exceeded a secondary rate limit"}"#)
+        .expect(1)
+        .create();
+    let star = server
+        .mock("PUT", "/user/starred/tokio-rs/tokio")
+        .with_status(204)
+        .expect(1)
+        .create();
+
+    cli(&server, &home, "fixture-token")
+        .args(["star", "tokio-rs", "tokio", "--wait-on-ratelimit"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Starred repository tokio-rs/tokio"))
+        .stderr(predicate::str::contains("waiting 1s"));
+
+    limited.assert();
+    star.assert();
+}
+
+#[test]
+fn test_star_from_file() {
+    let mut server = Server::new();
+    let home = TempDir::new().unwrap();
+    let starred = server
+        .mock("PUT", "/user/starred/rust-lang/rust")
+        .with_status(204)
+        .expect(2)
+        .create();
+    server
+        .mock("PUT", "/user/starred/octocat/missing")
+        .with_status(404)
+        .with_body(r#"{"message": "Not Found"}"#)
+        .create();
+    let unstarred = server
+        .mock("DELETE", "/user/starred/vuejs/vue")
+        .with_status(204)
+        .create();
+
+    let list = home.path().join("repos.txt");
+    fs::write(&list, "rust-lang/rust\nnot a repo\noctocat/missing # gone\n").unwrap();
+    cli(&server, &home, "fixture-token")
+        .args(["star", "--from-file", list.to_str().unwrap()])
+        .assert()
+        .failure()
+        .stdout(predicate::str::contains("Starred repository rust-lang/rust"))
+        .stdout(predicate::str::contains("Starred 1 repositories, 1 failed, 1 invalid lines skipped"))
+        .stderr(predicate::str::contains("Skipping invalid line: not a repo"))
+        .stderr(predicate::str::contains("Failed to star octocat/missing"));
+
+    let output = cli(&server, &home, "fixture-token")
+        .args(["star", "--from-file", list.to_str().unwrap(), "--format", "json", "--no-meta"])
+        .assert()
+        .failure()
+        .get_output()
+        .stdout
+        .clone();
+    let results: Value = serde_json::from_slice(&output).unwrap();
+    assert_eq!(results, serde_json::json!([{ "repository": "rust-lang/rust", "starred": true }]));
+    starred.assert();
+
+    cli(&server, &home, "fixture-token")
+        .args(["unstar", "--from-file", "-"])
+        .write_stdin("https://github.com/vuejs/vue\n")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Unstarred repository vuejs/vue"))
+        .stdout(predicate::str::contains("Unstarred 1 repositories, 0 failed"));
+    unstarred.assert();
+}
+
+#[test]
+fn test_star_dry_run() {
+    let mut server = Server::new();
+    let home = TempDir::new().unwrap();
+    let star = server
+        .mock("PUT", mockito::Matcher::Regex(r"^/user/starred/".to_string()))
+        .expect(0)
+        .create();
+    let unstar = server
+        .mock("DELETE", mockito::Matcher::Regex(r"^/user/starred/".to_string()))
+        .expect(0)
+        .create();
+
+    cli(&server, &home, "fixture-token")
+        .args(["star", "rust-lang/rust", "--dry-run"])
+        .assert()
+        .success()
+        .stdout(format!("Would send PUT {}/user/starred/rust-lang/rust\n", server.url()));
+    cli(&server, &home, "fixture-token")
+        .args(["unstar", "--from-file", "-", "--dry-run"])
+        .write_stdin("vuejs/vue\nnot a repo\n")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(format!("Would send DELETE {}/user/starred/vuejs/vue", server.url())))
+        .stdout(predicate::str::contains("Would unstar 1 repositories, 1 invalid lines skipped"));
+
+    star.assert();
+    unstar.assert();
+}
+
+#[test]
+fn test_unstar() {
+    let mut server = Server::new();
+    let home = TempDir::new().unwrap();
+    let mock = server
+        .mock("DELETE", "/user/starred/vuejs/vue")
+        .with_status(204)
+        .create();
+
+    cli(&server, &home, "fixture-token")
+        .args(["unstar", "vuejs", "vue"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Unstarred repository vuejs/vue"));
+
+    mock.assert();
+}
+
+#[test]
+fn test_unstar_not_found() {
+    let mut server = Server::new();
+    let home = TempDir::new().unwrap();
+    server
+        .mock("DELETE", "/user/starred/octocat/missing")
+        .with_status(404)
+        .with_body(r#"{"message": "Not Found"}"#)
+        .create();
+
+    cli(&server, &home, "fixture-token")
+        .args(["unstar", "octocat", "missing"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Not Found"));
+}
+
+#[test]
+fn test_unstar_interactive() {
+    let server = Server::new();
+    let home = TempDir::new().unwrap();
+
+    // Picking needs a terminal, which the test doesn't have
+    cli(&server, &home, "fixture-token")
+        .args(["unstar", "--interactive"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("unstar --interactive needs a terminal"));
+    cli(&server, &home, "fixture-token")
+        .args(["unstar", "--interactive", "vuejs/vue"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("cannot be used with"));
+    cli(&server, &home, "fixture-token")
+        .arg("unstar")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("<owner>"));
+}
+
+#[test]
+fn test_badge_for_my_stars() {
+    let mut server = Server::new();
+    let home = TempDir::new().unwrap();
+    let link = format!(r#"<{}/user/starred?per_page=1&page=1234>; rel="last""#, server.url());
+    server
+        .mock("GET", "/user/starred?per_page=1")
+        .with_status(200)
+        .with_header("link", &link)
+        .with_body("[{}]")
+        .create();
+
+    cli(&server, &home, "fixture-token")
+        .args(["badge"])
+        .assert()
+        .success()
+        .stdout(predicate::str::starts_with("<svg"))
+        .stdout(predicate::str::contains("starred: 1.2k"));
+}
+
+#[test]
+fn test_badge_for_repo_as_json() {
+    let mut server = Server::new();
+    let home = TempDir::new().unwrap();
+    server
+        .mock("GET", "/repos/rust-lang/rust")
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(fixture_repo("rust-lang/rust").to_string())
+        .create();
+
+    cli(&server, &home, "fixture-token")
+        .args(["badge", "--repo", "rust-lang/rust", "--format", "json"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(r#""message": "97k""#));
+}
+
+#[test]
+fn test_my_stargazers_marks_new_since_last_run() {
+    let mut server = Server::new();
+    let home = TempDir::new().unwrap();
+    server
+        .mock("GET", "/user")
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(USER_FIXTURE)
+        .create();
+    server
+        .mock("GET", "/user/repos?type=owner&per_page=100")
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(serde_json::json!([fixture_repo("octocat/Hello-World")]).to_string())
+        .create();
+    let first = server
+        .mock("GET", "/repos/octocat/Hello-World/stargazers?per_page=100&page=1")
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(r#"[{"starred_at": "2024-05-01T10:00:00Z", "user": {"login": "hubot"}}]"#)
+        .expect(1)
+        .create();
+
+    cli(&server, &home, "fixture-token")
+        .arg("my-stargazers")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("hubot"))
+        .stdout(predicate::str::contains("0 new stargazers since the last run"));
+    first.assert();
+    first.remove();
+
+    server
+        .mock("GET", "/repos/octocat/Hello-World/stargazers?per_page=100&page=1")
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(r#"[
+            {"starred_at": "2024-05-01T10:00:00Z", "user": {"login": "hubot"}},
+            {"starred_at": "2024-05-03T10:00:00Z", "user": {"login": "monalisa"}}
+        ]"#)
+        .create();
+
+    cli(&server, &home, "fixture-token")
+        .arg("my-stargazers")
+        .assert()
+        .success()
+        .stdout(predicate::str::is_match(r"monalisa\s*\|\s*2024-05-03T10:00:00Z\s*\|\s*new").unwrap())
+        .stdout(predicate::str::contains("1 new stargazers since the last run"));
+}
+
+#[test]
+fn test_fork_and_star() {
+    let mut server = Server::new();
+    let home = TempDir::new().unwrap();
+    let fork = server
+        .mock("POST", "/repos/rust-lang/rust/forks")
+        .with_status(202)
+        .with_header("content-type", "application/json")
+        .with_body(serde_json::json!({
+            "full_name": "octocat/rust",
+            "html_url": "https://github.com/octocat/rust",
+            "clone_url": "https://github.com/octocat/rust.git",
+            "ssh_url": "git@github.com:octocat/rust.git"
+        }).to_string())
+        .create();
+    let star = server
+        .mock("PUT", "/user/starred/rust-lang/rust")
+        .with_status(204)
+        .create();
+
+    cli(&server, &home, "fixture-token")
+        .args(["fork", "https://github.com/rust-lang/rust", "--star"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Forked rust-lang/rust to octocat/rust"))
+        .stdout(predicate::str::contains("Clone URL: https://github.com/octocat/rust.git"))
+        .stdout(predicate::str::contains("Starred repository rust-lang/rust"));
+
+    fork.assert();
+    star.assert();
+}
+
+#[test]
+fn test_topics_set_on_my_repo() {
+    let mut server = Server::new();
+    let home = TempDir::new().unwrap();
+    server
+        .mock("GET", "/user")
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(USER_FIXTURE)
+        .create();
+    let topics = server
+        .mock("PUT", "/repos/octocat/Hello-World/topics")
+        .match_body(mockito::Matcher::Json(serde_json::json!({ "names": ["github-api", "rust"] })))
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(r#"{"names": ["github-api", "rust"]}"#)
+        .create();
+
+    cli(&server, &home, "fixture-token")
+        .args(["topics", "set", "Hello-World", "GitHub-API, rust"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Set topics of octocat/Hello-World to github-api, rust"));
+
+    topics.assert();
+}
+
+#[test]
+fn test_topics_set_rejects_invalid_topic() {
+    let server = Server::new();
+    let home = TempDir::new().unwrap();
+
+    cli(&server, &home, "fixture-token")
+        .args(["topics", "set", "octocat/Hello-World", "not valid"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Invalid topic"));
+}
+
+#[test]
+fn test_traffic_records_history() {
+    let mut server = Server::new();
+    let home = TempDir::new().unwrap();
+    server
+        .mock("GET", "/user")
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(USER_FIXTURE)
+        .create();
+    server
+        .mock("GET", "/repos/octocat/Hello-World/traffic/views")
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(r#"{"count": 14, "uniques": 3, "views": [{"timestamp": "2024-05-01T00:00:00Z", "count": 14, "uniques": 3}]}"#)
+        .create();
+    server
+        .mock("GET", "/repos/octocat/Hello-World/traffic/clones")
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(r#"{"count": 2, "uniques": 1, "clones": [{"timestamp": "2024-05-02T00:00:00Z", "count": 2, "uniques": 1}]}"#)
+        .create();
+    server
+        .mock("GET", "/repos/octocat/Hello-World/traffic/popular/referrers")
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(r#"[{"referrer": "news.ycombinator.com", "count": 9, "uniques": 2}]"#)
+        .create();
+    let csv = home.path().join("traffic.csv");
+
+    cli(&server, &home, "fixture-token")
+        .args(["traffic", "Hello-World", "--csv"])
+        .arg(&csv)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Traffic for octocat/Hello-World (2 days recorded)"))
+        .stdout(predicate::str::contains("news.ycombinator.com"));
+
+    assert_eq!(
+        fs::read_to_string(&csv).unwrap(),
+        "date,views,unique_views,clones,unique_clones\n2024-05-01,14,3,0,0\n2024-05-02,0,0,2,1\n"
+    );
+}
+
+#[test]
+fn test_list_follows_pages() {
+    let mut server = Server::new();
+    let home = TempDir::new().unwrap();
+    let starred = fixture_starred();
+    let link = format!(r#"<{}/user/starred?per_page=2&page=2>; rel="next""#, server.url());
+    server
+        .mock("GET", "/user/starred?per_page=2")
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_header("link", &link)
+        .with_body(Value::Array(starred.as_array().unwrap()[..2].to_vec()).to_string())
+        .expect(2)
+        .create();
+    server
+        .mock("GET", "/user/starred?per_page=2&page=2")
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(Value::Array(starred.as_array().unwrap()[2..].to_vec()).to_string())
+        .create();
+
+    cli(&server, &home, "fixture-token")
+        .args(["list", "--per-page", "2"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("octocat/Hello-World"))
+        .stdout(predicate::str::contains("vuejs/vue"));
+
+    cli(&server, &home, "fixture-token")
+        .args(["list", "--per-page", "2", "--max-pages", "1"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("rust-lang/rust"))
+        .stdout(predicate::str::contains("vuejs/vue").not())
+        .stderr(predicate::str::contains("stopped after 1 pages"));
+}
+
+#[test]
+fn test_list_rejects_invalid_per_page() {
+    let server = Server::new();
+    let home = TempDir::new().unwrap();
+
+    cli(&server, &home, "fixture-token")
+        .args(["list", "--per-page", "500"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("--per-page must be between 1 and 100"));
+}
+
+#[test]
+fn test_fork_status_all() {
+    let mut server = Server::new();
+    let home = TempDir::new().unwrap();
+    let mut starred = fixture_starred();
+    starred[0]["fork"] = Value::Bool(true);
+    starred[2]["fork"] = Value::Bool(true);
+    server
+        .mock("GET", "/user/starred?per_page=100")
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(starred.to_string())
+        .create();
+    for (fork, upstream) in [("octocat/Hello-World", "github/Hello-World"), ("vuejs/vue", "evanyou/vue")] {
+        server
+            .mock("GET", format!("/repos/{}", fork).as_str())
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(serde_json::json!({
+                "full_name": fork,
+                "default_branch": "main",
+                "fork": true,
+                "parent": { "full_name": upstream, "default_branch": "main" }
+            }).to_string())
+            .create();
+    }
+    server
+        .mock("GET", "/repos/github/Hello-World/compare/main...octocat:main")
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(r#"{"status": "behind", "ahead_by": 0, "behind_by": 3, "commits": []}"#)
+        .create();
+    server
+        .mock("GET", "/repos/evanyou/vue/compare/main...vuejs:main")
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(r#"{"status": "diverged", "ahead_by": 2, "behind_by": 120, "commits": []}"#)
+        .create();
+
+    cli(&server, &home, "fixture-token")
+        .args(["fork-status", "--all"])
+        .assert()
+        .success()
+        .stdout(predicate::str::is_match(r"(?s)vuejs/vue\s*\|\s*evanyou/vue\s*\|\s*120\s*\|\s*2\s*\|\s*diverged.*octocat/Hello-World\s*\|\s*github/Hello-World\s*\|\s*3\s*\|\s*0\s*\|\s*behind").unwrap())
+        .stdout(predicate::str::contains("rust-lang/rust").not());
+}
+
+#[test]
+fn test_fork_status_not_a_fork() {
+    let mut server = Server::new();
+    let home = TempDir::new().unwrap();
+    server
+        .mock("GET", "/repos/rust-lang/rust")
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(fixture_repo("rust-lang/rust").to_string())
+        .create();
+
+    cli(&server, &home, "fixture-token")
+        .args(["fork-status", "rust-lang/rust"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("rust-lang/rust is not a fork"));
+}
+
+#[test]
+fn test_ci_batch() {
+    let mut server = Server::new();
+    let home = TempDir::new().unwrap();
+    server
+        .mock("GET", "/repos/rust-lang/rust/commits/HEAD/check-runs?per_page=100")
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(r#"{"check_runs": [{"name": "bors", "status": "completed", "conclusion": "failure"}]}"#)
+        .create();
+    server
+        .mock("GET", "/repos/tokio-rs/tokio/commits/HEAD/check-runs?per_page=100")
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(r#"{"check_runs": [{"name": "CI", "status": "completed", "conclusion": "success"}]}"#)
+        .create();
+    server
+        .mock("GET", mockito::Matcher::Regex(r"^/repos/.*/commits/HEAD/status$".to_string()))
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(r#"{"state": "pending", "statuses": []}"#)
+        .create();
+    let list = home.path().join("deps.txt");
+    fs::write(&list, "# dependencies\ntokio-rs/tokio\n").unwrap();
+
+    cli(&server, &home, "fixture-token")
+        .args(["ci", "rust-lang/rust", "--file"])
+        .arg(&list)
+        .assert()
+        .success()
+        .stdout(predicate::str::is_match(r"rust-lang/rust\s*\|\s*failing\s*\|\s*bors").unwrap())
+        .stdout(predicate::str::is_match(r"tokio-rs/tokio\s*\|\s*passing").unwrap());
+}
+
+#[test]
+fn test_activity() {
+    let mut server = Server::new();
+    let home = TempDir::new().unwrap();
+    server
+        .mock("GET", "/repos/rust-lang/rust/stats/commit_activity")
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(r#"[{"days": [0, 3, 26, 20, 39, 1, 0], "total": 89, "week": 1336280400}]"#)
+        .create();
+
+    cli(&server, &home, "fixture-token")
+        .args(["activity", "rust-lang/rust"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Thu █"))
+        .stdout(predicate::str::contains("89 commits in the last 1 weeks"));
+}
+
+#[test]
+fn test_owner() {
+    let mut server = Server::new();
+    let home = TempDir::new().unwrap();
+    server
+        .mock("GET", "/users/octocat")
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(USER_FIXTURE)
+        .create();
+    server
+        .mock("GET", "/search/repositories")
+        .match_query(mockito::Matcher::UrlEncoded("q".into(), "user:octocat".into()))
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(serde_json::json!({ "items": [fixture_repo("octocat/Hello-World")] }).to_string())
+        .create();
+    server
+        .mock("GET", "/user/starred/octocat/Hello-World")
+        .with_status(204)
+        .create();
+
+    cli(&server, &home, "fixture-token")
+        .args(["owner", "octocat"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("octocat (The Octocat)"))
+        .stdout(predicate::str::contains("Type: user"))
+        .stdout(predicate::str::contains("Company: @github"))
+        .stdout(predicate::str::contains("Followers: 9999"))
+        .stdout(predicate::str::is_match(r"octocat/Hello-World\s*\|\s*2789\s*\|\s*yes").unwrap());
+}
+
+#[test]
+fn test_crates() {
+    let mut server = Server::new();
+    let home = TempDir::new().unwrap();
+    server
+        .mock("GET", "/user/starred?per_page=100")
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(fixture_starred().to_string())
+        .create();
+    server
+        .mock("GET", "/crates")
+        .match_query(mockito::Matcher::UrlEncoded("q".into(), "rust".into()))
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(serde_json::json!({
+            "crates": [
+                { "name": "rustc-std-workspace-core", "max_version": "1.0.0", "downloads": 60000000, "repository": "https://github.com/rust-lang/rust" },
+                { "name": "rust-ini", "max_version": "0.21.0", "downloads": 9000000, "repository": "https://github.com/zonyitoo/rust-ini" }
+            ]
+        }).to_string())
+        .create();
+
+    cli(&server, &home, "fixture-token")
+        .arg("crates")
+        .assert()
+        .success()
+        .stdout(predicate::str::is_match(r"rust-lang/rust\s*\|\s*rustc-std-workspace-core\s*\|\s*1\.0\.0\s*\|\s*60M").unwrap())
+        .stdout(predicate::str::contains("https://docs.rs/rustc-std-workspace-core"))
+        .stdout(predicate::str::contains("rust-ini").not());
+}
+
+#[test]
+fn test_crates_find() {
+    let mut server = Server::new();
+    let home = TempDir::new().unwrap();
+    server
+        .mock("GET", "/crates/tokio")
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(r#"{"crate": {"name": "tokio", "max_version": "1.37.0", "downloads": 250000000, "repository": "https://github.com/tokio-rs/tokio"}}"#)
+        .create();
+    server
+        .mock("GET", "/user/starred/tokio-rs/tokio")
+        .with_status(404)
+        .create();
+    server
+        .mock("GET", "/crates/missing")
+        .with_status(404)
+        .create();
+
+    cli(&server, &home, "fixture-token")
+        .args(["crates", "find", "tokio"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("tokio 1.37.0 (250M downloads)"))
+        .stdout(predicate::str::contains("Source: tokio-rs/tokio (not starred)"));
+
+    cli(&server, &home, "fixture-token")
+        .args(["crates", "find", "missing"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("Package missing not found on crates"));
+}
+
+#[test]
+fn test_packages_across_registries() {
+    let mut server = Server::new();
+    let home = TempDir::new().unwrap();
+    server
+        .mock("GET", "/user/starred?per_page=100")
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(fixture_starred().to_string())
+        .create();
+    server
+        .mock("GET", "/crates")
+        .match_query(mockito::Matcher::UrlEncoded("q".into(), "rust".into()))
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(r#"{"crates": []}"#)
+        .create();
+    let npm = server
+        .mock("GET", "/-/v1/search")
+        .match_query(mockito::Matcher::UrlEncoded("text".into(), "vue".into()))
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(serde_json::json!({
+            "objects": [
+                { "package": { "name": "vue", "version": "2.7.16", "links": { "repository": "https://github.com/vuejs/vue" } } }
+            ]
+        }).to_string())
+        .create();
+
+    cli(&server, &home, "fixture-token")
+        .args(["packages", "--registry", "crates", "--registry", "npm"])
+        .assert()
+        .success()
+        .stdout(predicate::str::is_match(r"vuejs/vue\s*\|\s*vue\s*\|\s*2\.7\.16\s*\|\s*-\s*\|\s*https://www.npmjs.com/package/vue\s*\|\s*npm").unwrap());
+
+    npm.assert();
+}
+
+#[test]
+fn test_packages_find_on_pypi() {
+    let mut server = Server::new();
+    let home = TempDir::new().unwrap();
+    server
+        .mock("GET", "/requests/json")
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(r#"{"info": {"name": "requests", "version": "2.31.0", "home_page": null, "project_urls": {"Source": "https://github.com/psf/requests"}}}"#)
+        .create();
+    server
+        .mock("GET", "/user/starred/psf/requests")
+        .with_status(204)
+        .create();
+
+    cli(&server, &home, "fixture-token")
+        .args(["packages", "find", "requests", "--registry", "pypi"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("requests 2.31.0\n"))
+        .stdout(predicate::str::contains("Source: psf/requests (starred)"));
+}
+
+// A release of octocat/Hello-World with a Linux and a macOS build, checksummed in SHA256SUMS only for Linux
+fn mock_release(server: &mut ServerGuard) {
+    let asset = |name: &str, id: u32| serde_json::json!({
+        "name": name,
+        "size": 5,
+        "url": format!("{}/repos/octocat/Hello-World/releases/assets/{}", server.url(), id),
+        "browser_download_url": format!("https://github.com/octocat/Hello-World/releases/download/v1.0.0/{}", name)
+    });
+    let release = serde_json::json!({
+        "tag_name": "v1.0.0",
+        "name": "v1.0.0",
+        "assets": [asset("hello-linux", 1), asset("hello-macos", 2), asset("SHA256SUMS", 3)]
+    });
+    server
+        .mock("GET", "/repos/octocat/Hello-World/releases/latest")
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(release.to_string())
+        .create();
+    for (id, body) in [(1, "hello"), (2, "hello"), (3, "2cf24dba5fb0a30e26e83b2ac5b9e29e1b161e5c1fa7425e73043362938b9824  hello-linux\n")] {
+        server
+            .mock("GET", format!("/repos/octocat/Hello-World/releases/assets/{}", id).as_str())
+            .with_status(200)
+            .with_body(body)
+            .create();
+    }
+}
+
+#[test]
+fn test_releases_download_verifies_checksum() {
+    let mut server = Server::new();
+    let home = TempDir::new().unwrap();
+    mock_release(&mut server);
+
+    cli(&server, &home, "fixture-token")
+        .args(["releases", "download", "octocat/Hello-World", "--asset", "linux", "-o"])
+        .arg(home.path())
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Verified SHA-256 of hello-linux against SHA256SUMS"));
+
+    assert_eq!(fs::read_to_string(home.path().join("hello-linux")).unwrap(), "hello");
+}
+
+#[test]
+fn test_releases_download_dry_run() {
+    let mut server = Server::new();
+    let home = TempDir::new().unwrap();
+    mock_release(&mut server);
+    let asset = server
+        .mock("GET", "/repos/octocat/Hello-World/releases/assets/1")
+        .expect(0)
+        .create();
+
+    cli(&server, &home, "fixture-token")
+        .args(["releases", "download", "octocat/Hello-World", "--asset", "linux", "--dry-run", "-o"])
+        .arg(home.path())
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(format!(
+            "Would send GET {}/repos/octocat/Hello-World/releases/assets/1 to download hello-linux (5 bytes)",
+            server.url()
+        )))
+        .stdout(predicate::str::contains("/releases/assets/3 to download SHA256SUMS and check the SHA-256"))
+        .stdout(predicate::str::contains(format!("Would write {}", home.path().join("hello-linux").display())));
+
+    asset.assert();
+    assert!(!home.path().join("hello-linux").exists());
+}
+
+#[test]
+fn test_releases_download_warns_without_checksum() {
+    let mut server = Server::new();
+    let home = TempDir::new().unwrap();
+    mock_release(&mut server);
+
+    cli(&server, &home, "fixture-token")
+        .args(["releases", "download", "octocat/Hello-World", "--asset", "macos", "-o"])
+        .arg(home.path())
+        .assert()
+        .success()
+        .stderr(predicate::str::contains("WARNING: octocat/Hello-World v1.0.0 publishes no checksum for hello-macos"));
+
+    assert!(home.path().join("hello-macos").exists());
+}
+
+#[test]
+fn test_releases_download_requires_signature_when_asked() {
+    let mut server = Server::new();
+    let home = TempDir::new().unwrap();
+    mock_release(&mut server);
+
+    cli(&server, &home, "fixture-token")
+        .args(["releases", "download", "octocat/Hello-World", "--asset", "linux", "--verify-signature", "-o"])
+        .arg(home.path())
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("publishes no signature for hello-linux"));
+
+    assert!(!home.path().join("hello-linux").exists());
+}
+
+// A release of octocat/hello whose only asset is a tarball for this platform holding a `hello` script
+#[cfg(unix)]
+fn mock_installable_release(server: &mut ServerGuard, work: &TempDir) {
+    use std::os::unix::fs::PermissionsExt;
+
+    let staging = work.path().join("staging");
+    fs::create_dir_all(&staging).unwrap();
+    fs::write(staging.join("hello"), "#!/bin/sh\necho hello\n").unwrap();
+    fs::set_permissions(staging.join("hello"), fs::Permissions::from_mode(0o755)).unwrap();
+    fs::write(staging.join("README.md"), "hello").unwrap();
+
+    let asset_name = format!("hello-v1.0.0-{}-{}.tar.gz", std::env::consts::ARCH, std::env::consts::OS);
+    let tarball = work.path().join(&asset_name);
+    let status = std::process::Command::new("tar")
+        .arg("-czf").arg(&tarball).arg("-C").arg(&staging).args(["hello", "README.md"])
+        .status()
+        .unwrap();
+    assert!(status.success());
+
+    let release = serde_json::json!({
+        "tag_name": "v1.0.0",
+        "assets": [{
+            "name": asset_name,
+            "size": 1,
+            "url": format!("{}/repos/octocat/hello/releases/assets/1", server.url()),
+            "browser_download_url": "https://github.com/octocat/hello/releases/download/v1.0.0/hello.tar.gz"
+        }]
+    });
+    server
+        .mock("GET", "/repos/octocat/hello/releases/latest")
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(release.to_string())
+        .create();
+    server
+        .mock("GET", "/repos/octocat/hello/releases/assets/1")
+        .with_status(200)
+        .with_body(fs::read(&tarball).unwrap())
+        .create();
+}
+
+#[cfg(unix)]
+#[test]
+fn test_install_and_upgrade_all() {
+    let mut server = Server::new();
+    let home = TempDir::new().unwrap();
+    let work = TempDir::new().unwrap();
+    mock_installable_release(&mut server, &work);
+    let bin_dir = home.path().join("bin");
+
+    cli(&server, &home, "fixture-token")
+        .args(["install", "octocat/hello", "--bin-dir"])
+        .arg(&bin_dir)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Installed hello v1.0.0 from octocat/hello"))
+        .stderr(predicate::str::contains("publishes no checksum"));
+
+    let output = std::process::Command::new(bin_dir.join("hello")).output().unwrap();
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "hello\n");
+    assert!(!bin_dir.join("README.md").exists());
+
+    let receipts = fs::read_to_string(
+        home.path().join(".local").join("share").join("stars_fetcher").join("receipts.json"),
+    ).unwrap();
+    assert!(receipts.contains("octocat/hello"));
+
+    cli(&server, &home, "fixture-token")
+        .args(["install", "--upgrade-all"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("hello v1.0.0 is up to date"));
+}
+
+#[cfg(unix)]
+#[test]
+fn test_installed_list_and_uninstall() {
+    let mut server = Server::new();
+    let home = TempDir::new().unwrap();
+    let work = TempDir::new().unwrap();
+    mock_installable_release(&mut server, &work);
+    let bin_dir = home.path().join("bin");
+
+    cli(&server, &home, "fixture-token")
+        .args(["install", "octocat/hello", "--bin-dir"])
+        .arg(&bin_dir)
+        .assert()
+        .success();
+
+    cli(&server, &home, "")
+        .args(["installed", "list"])
+        .assert()
+        .success()
+        .stdout(predicate::str::is_match(r"hello\s*\|\s*v1\.0\.0\s*\|\s*octocat/hello").unwrap());
+
+    cli(&server, &home, "")
+        .args(["uninstall", "hello"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Uninstalled hello v1.0.0 (from octocat/hello)"));
+    assert!(!bin_dir.join("hello").exists());
+
+    cli(&server, &home, "")
+        .args(["uninstall", "hello"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("hello was not installed by stars_fetcher"));
+    cli(&server, &home, "")
+        .args(["installed", "list"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Nothing has been installed yet"));
+}
+
+#[test]
+fn test_changelog() {
+    let mut server = Server::new();
+    let home = TempDir::new().unwrap();
+    server
+        .mock("GET", "/repos/octocat/hello/releases")
+        .match_query(mockito::Matcher::UrlEncoded("page".into(), "1".into()))
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(serde_json::json!([
+            { "tag_name": "v1.4", "published_at": "2024-03-01T00:00:00Z", "body": "Added --json" },
+            { "tag_name": "v1.3", "published_at": "2024-02-01T00:00:00Z", "body": "Fixed a crash" },
+            { "tag_name": "v1.2", "published_at": "2024-01-01T00:00:00Z", "body": "Old news" }
+        ]).to_string())
+        .create();
+    server
+        .mock("GET", "/repos/octocat/hello/compare/v1.2...main")
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(r#"{"status": "ahead", "ahead_by": 1, "behind_by": 0, "commits": [{"sha": "abcdef0123", "commit": {"message": "Unreleased fix", "author": null}}]}"#)
+        .create();
+
+    cli(&server, &home, "fixture-token")
+        .args(["changelog", "octocat/hello", "--from", "v1.2", "--to", "v1.4"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("## v1.4 - 2024-03-01\n\nAdded --json"))
+        .stdout(predicate::str::contains("Fixed a crash"))
+        .stdout(predicate::str::contains("Old news").not());
+
+    cli(&server, &home, "fixture-token")
+        .args(["changelog", "octocat/hello", "--from", "v1.2", "--to", "main"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("- abcdef0 Unreleased fix"))
+        .stderr(predicate::str::contains("showing the commits between them"));
+}
+
+#[test]
+fn test_tags_and_branches() {
+    let mut server = Server::new();
+    let home = TempDir::new().unwrap();
+    server
+        .mock("GET", "/repos/octocat/Hello-World/tags")
+        .match_query(mockito::Matcher::UrlEncoded("per_page".into(), "1".into()))
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(r#"[{"name": "v1.0.0", "commit": {"sha": "7fd1a60b01f91b314f59955a4e4d4e80d8edf11d"}}]"#)
+        .create();
+    server
+        .mock("GET", "/repos/octocat/Hello-World/branches")
+        .match_query(mockito::Matcher::Any)
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(r#"[
+            {"name": "master", "commit": {"sha": "7fd1a60b01f91b314f59955a4e4d4e80d8edf11d"}},
+            {"name": "release", "commit": {"sha": "7fd1a60b01f91b314f59955a4e4d4e80d8edf11d"}}
+        ]"#)
+        .create();
+    // Refs pointing at the same commit share one request for its date
+    let commit = server
+        .mock("GET", "/repos/octocat/Hello-World/commits/7fd1a60b01f91b314f59955a4e4d4e80d8edf11d")
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(r#"{"commit": {"committer": {"date": "2012-03-06T23:06:50Z"}}}"#)
+        .expect(2)
+        .create();
+
+    cli(&server, &home, "fixture-token")
+        .args(["tags", "octocat/Hello-World", "--per-page", "1"])
+        .assert()
+        .success()
+        .stdout(predicate::str::is_match(r"v1\.0\.0\s*\|\s*7fd1a60\s*\|\s*2012-03-06T23:06:50Z").unwrap())
+        .stdout(predicate::str::contains("More tags may follow: --page 2"));
+
+    cli(&server, &home, "fixture-token")
+        .args(["branches", "octocat/Hello-World"])
+        .assert()
+        .success()
+        .stdout(predicate::str::is_match(r"master\s*\|\s*7fd1a60\s*\|\s*2012-03-06T23:06:50Z").unwrap())
+        .stdout(predicate::str::is_match(r"release\s*\|\s*7fd1a60\s*\|\s*2012-03-06T23:06:50Z").unwrap())
+        .stdout(predicate::str::contains("More branches").not());
+    commit.assert();
+}
+
+#[test]
+fn test_get_batch() {
+    let mut server = Server::new();
+    let home = TempDir::new().unwrap();
+    for full_name in ["rust-lang/rust", "tokio-rs/tokio", "octocat/Hello-World"] {
+        server
+            .mock("GET", format!("/repos/{}", full_name).as_str())
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(fixture_repo(full_name).to_string())
+            .create();
+    }
+    server
+        .mock("GET", "/repos/octocat/missing")
+        .with_status(404)
+        .with_body(r#"{"message": "Not Found"}"#)
+        .create();
+    let list = home.path().join("deps.txt");
+    fs::write(&list, "tokio-rs/tokio\nrust-lang/rust\noctocat/missing\nhttps://github.com/octocat/Hello-World\n").unwrap();
+
+    let output = cli(&server, &home, "fixture-token")
+        .args(["get", "--format", "json", "--batch"])
+        .arg(&list)
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("octocat/missing"))
+        .get_output()
+        .stdout
+        .clone();
+
+    let listing: Value = serde_json::from_slice(&output).unwrap();
+    let names: Vec<&str> = listing["items"].as_array().unwrap().iter().map(|repo| repo["full_name"].as_str().unwrap()).collect();
+    assert_eq!(names, vec!["tokio-rs/tokio", "rust-lang/rust", "octocat/Hello-World"]);
+}
+
+#[test]
+fn test_similar() {
+    let mut server = Server::new();
+    let home = TempDir::new().unwrap();
+    server
+        .mock("GET", "/repos/tokio-rs/tokio/topics")
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(r#"{"names": ["async"]}"#)
+        .create();
+    server
+        .mock("GET", "/search/repositories")
+        .match_query(mockito::Matcher::UrlEncoded("q".into(), "topic:async".into()))
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(serde_json::json!({
+            "items": [
+                {
+                    "id": 1, "name": "tokio", "full_name": "tokio-rs/tokio", "owner": { "login": "tokio-rs" },
+                    "description": null, "html_url": "https://github.com/tokio-rs/tokio",
+                    "stargazers_count": 25000, "topics": ["async"]
+                },
+                {
+                    "id": 2, "name": "smol", "full_name": "smol-rs/smol", "owner": { "login": "smol-rs" },
+                    "description": "A small and fast async runtime", "html_url": "https://github.com/smol-rs/smol",
+                    "stargazers_count": 3500, "topics": ["async"]
+                },
+                {
+                    "id": 3, "name": "async-std", "full_name": "async-rs/async-std", "owner": { "login": "async-rs" },
+                    "description": null, "html_url": "https://github.com/async-rs/async-std",
+                    "stargazers_count": 3900, "topics": ["async"]
+                }
+            ]
+        }).to_string())
+        .create();
+    server
+        .mock("GET", "/user/starred/async-rs/async-std")
+        .with_status(204)
+        .create();
+    server
+        .mock("GET", "/user/starred/smol-rs/smol")
+        .with_status(404)
+        .create();
+
+    cli(&server, &home, "fixture-token")
+        .args(["similar", "tokio-rs/tokio"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("smol-rs/smol"))
+        .stdout(predicate::str::contains("A small and fast async runtime"))
+        .stdout(predicate::str::contains("async-rs/async-std").not())
+        .stdout(predicate::str::contains("25000").not());
+}
+
+#[test]
+fn test_prune_archived() {
+    let mut server = Server::new();
+    let home = TempDir::new().unwrap();
+    server
+        .mock("GET", "/user/starred?per_page=100")
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(fixture_starred().to_string())
+        .create();
+    server
+        .mock("GET", "/repos/vuejs/vue")
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(fixture_repo("vuejs/vue").to_string())
+        .create();
+    let unstar = server
+        .mock("DELETE", "/user/starred/vuejs/vue")
+        .with_status(204)
+        .expect(1)
+        .create();
+
+    cli(&server, &home, "fixture-token")
+        .args(["prune", "--archived"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("vuejs/vue"))
+        .stdout(predicate::str::contains("rust-lang/rust").not())
+        .stderr(predicate::str::contains("run prune --archived --yes"));
+    cli(&server, &home, "fixture-token")
+        .args(["prune", "--archived", "--yes"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Unstarred repository vuejs/vue"));
+    unstar.assert();
+
+    cli(&server, &home, "fixture-token")
+        .args(["detail", "vuejs", "vue"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Archived: read-only"));
+    cli(&server, &home, "fixture-token")
+        .arg("prune")
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("prune --archived"));
+}
+
+#[test]
+fn test_prune_inactive() {
+    let mut server = Server::new();
+    let home = TempDir::new().unwrap();
+    server
+        .mock("GET", "/user/starred?per_page=100")
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(fixture_starred().to_string())
+        .create();
+    let unstar = server
+        .mock("DELETE", mockito::Matcher::Regex(r"^/user/starred/".to_string()))
+        .with_status(204)
+        .expect(3)
+        .create();
+
+    // Every fixture repository was last pushed to in 2024
+    cli(&server, &home, "fixture-token")
+        .args(["prune", "--inactive-years", "100"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("None of my starred repositories went 100 years without a push"));
+    cli(&server, &home, "fixture-token")
+        .args(["prune", "--inactive-years", "1", "--dry-run"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("rust-lang/rust"))
+        .stdout(predicate::str::contains("2024-05-14T06:58:10Z"))
+        .stdout(predicate::str::contains(format!("Would send DELETE {}/user/starred/vuejs/vue", server.url())))
+        .stdout(predicate::str::contains("Dry run: 3 repositories would be unstarred"))
+        .stdout(predicate::str::contains("Unstarred").not());
+    cli(&server, &home, "fixture-token")
+        .args(["prune", "--inactive-years", "1"])
+        .assert()
+        .success()
+        .stderr(predicate::str::contains("run prune --inactive-years 1 --yes"));
+    cli(&server, &home, "fixture-token")
+        .args(["prune", "--inactive-years", "1", "--yes"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Unstarred repository octocat/Hello-World"))
+        .stdout(predicate::str::contains("Unstarred repository rust-lang/rust"))
+        .stdout(predicate::str::contains("Unstarred 3 repositories, 0 failed"));
+    unstar.assert();
+
+    cli(&server, &home, "fixture-token")
+        .args(["prune", "--inactive-years", "soon"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("--inactive-years expects a number of years"));
+}
+
+#[test]
+fn test_search() {
+    let mut server = Server::new();
+    let home = TempDir::new().unwrap();
+    server
+        .mock("GET", "/search/repositories")
+        .match_query(mockito::Matcher::AllOf(vec![
+            mockito::Matcher::UrlEncoded("q".into(), "http client language:rust stars:>100".into()),
+            mockito::Matcher::UrlEncoded("sort".into(), "stars".into()),
+            mockito::Matcher::UrlEncoded("per_page".into(), "5".into()),
+        ]))
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(serde_json::json!({
+            "items": [
+                {
+                    "id": 1, "name": "reqwest", "full_name": "seanmonstar/reqwest", "owner": { "login": "seanmonstar" },
+                    "description": "An easy and powerful Rust HTTP Client", "html_url": "https://github.com/seanmonstar/reqwest",
+                    "stargazers_count": 9000, "language": "Rust", "topics": ["http"]
+                }
+            ]
+        }).to_string())
+        .create();
+
+    cli(&server, &home, "fixture-token")
+        .args(["search", "http", "client", "--language", "rust", "--stars", ">100", "--sort", "stars", "--limit", "5"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("seanmonstar/reqwest"))
+        .stdout(predicate::str::contains("9000"))
+        .stdout(predicate::str::contains("An easy and powerful Rust HTTP Client"))
+        .stdout(predicate::str::contains("Starred").not());
+
+    cli(&server, &home, "fixture-token")
+        .args(["search", "--limit", "0", "http"])
+        .assert()
+        .failure()
+        .stderr(predicate::str::contains("--limit must be between 1 and 100"));
+}
+
+#[test]
+fn test_collection_pull() {
+    let mut server = Server::new();
+    let home = TempDir::new().unwrap();
+    let gist = serde_json::json!({
+        "id": "team",
+        "html_url": "https://gist.github.com/team",
+        "files": {
+            "stars.txt": {
+                "raw_url": format!("{}/raw/stars.txt", server.url()),
+                "content": "rust-lang/rust\ntokio-rs/tokio\nnot a repo\n",
+                "truncated": false
+            }
+        }
+    });
+    server
+        .mock("GET", "/gists/team")
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(gist.to_string())
+        .create();
+    server
+        .mock("GET", "/user/starred/rust-lang/rust")
+        .with_status(204)
+        .create();
+    server
+        .mock("GET", "/user/starred/tokio-rs/tokio")
+        .with_status(404)
+        .create();
+    let star = server
+        .mock("PUT", "/user/starred/tokio-rs/tokio")
+        .with_status(204)
+        .create();
+
+    cli(&server, &home, "fixture-token")
+        .args(["collection", "pull", "https://gist.github.com/octocat/team"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Starred repository tokio-rs/tokio"))
+        .stdout(predicate::str::contains("1 starred, 1 already starred, 0 failed"))
+        .stderr(predicate::str::contains("Skipping invalid line: not a repo"));
+
+    star.assert();
+}
+
+#[test]
+fn test_collection_pull_checks_stars_in_one_listing() {
+    let mut server = Server::new();
+    let home = TempDir::new().unwrap();
+    let gist = serde_json::json!({
+        "id": "team",
+        "html_url": "https://gist.github.com/team",
+        "files": {
+            "stars.txt": {
+                "raw_url": format!("{}/raw/stars.txt", server.url()),
+                "content": "Rust-Lang/Rust\ntokio-rs/tokio\nserde-rs/serde\n",
+                "truncated": false
+            }
+        }
+    });
+    server
+        .mock("GET", "/gists/team")
+        .with_status(200)
+        .with_header("content-type", "application/json")
+        .with_body(gist.to_string())
+        .create();
+    server
+        .mock("GET", "/user/starred?per_page=1")
+        .with_status(200)
+        .with_body(r#"[{"full_name": "rust-lang/rust"}]"#)
+        .create();
+    server
+        .mock("GET", "/user/starred?per_page=100")
+        .with_status(200)
+        .with_body(serde_json::json!([fixture_repo("rust-lang/rust")]).to_string())
+        .expect(1)
+        .create();
+    let checks = server
+        .mock("GET", mockito::Matcher::Regex(r"^/user/starred/".to_string()))
+        .expect(0)
+        .create();
+    let already = server.mock("PUT", "/user/starred/Rust-Lang/Rust").expect(0).create();
+    let tokio = server.mock("PUT", "/user/starred/tokio-rs/tokio").with_status(204).expect(1).create();
+    let serde = server.mock("PUT", "/user/starred/serde-rs/serde").with_status(500).expect(3).create();
+
+    cli(&server, &home, "fixture-token")
+        .args(["collection", "pull", "team"])
+        .assert()
+        .failure()
+        .stdout(predicate::str::contains("Starred repository tokio-rs/tokio"))
+        .stdout(predicate::str::contains("1 starred, 1 already starred, 1 failed"))
+        .stderr(predicate::str::contains("Failed to star serde-rs/serde"));
+
+    for mock in [checks, already, tokio, serde] {
+        mock.assert();
+    }
+}
+
+#[test]
+fn test_collection_propose() {
+    let mut server = Server::new();
+    let home = TempDir::new().unwrap();
+    let comment = server
+        .mock("POST", "/gists/team/comments")
+        .match_body(mockito::Matcher::Regex("tokio-rs/tokio".to_string()))
+        .with_status(201)
+        .create();
+
+    cli(&server, &home, "fixture-token")
+        .args(["collection", "propose", "team", "tokio-rs/tokio"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Proposed tokio-rs/tokio"));
+
+    comment.assert();
+}
+
+#[test]
+fn test_config_export_and_import() {
+    let server = Server::new();
+    let home = TempDir::new().unwrap();
+    let exported = home.path().join("shared.toml");
+
+    cli(&server, &home, "secret-token")
+        .args(["config", "export", "-o"])
+        .arg(&exported)
+        .assert()
+        .success();
+
+    let contents = fs::read_to_string(&exported).unwrap();
+    assert!(!contents.contains("secret-token"));
+
+    let other_home = TempDir::new().unwrap();
+    cli(&server, &other_home, "other-token")
+        .args(["config", "import"])
+        .arg(&exported)
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Imported settings"))
+        .stdout(predicate::str::contains("the config file holds no token"));
+
+    let imported = fs::read_to_string(
+        other_home.path().join(".config").join("stars_fetcher").join("config.toml"),
+    ).unwrap();
+    assert!(!imported.contains("other-token"));
+}
+
+#[test]
+fn test_config_import_missing_file() {
+    let server = Server::new();
+    let home = TempDir::new().unwrap();
+
+    cli(&server, &home, "fixture-token")
+        .args(["config", "import", "does-not-exist.toml"])
+        .assert()
+        .failure();
+}
+
+// stars-fetcher-mock on a free port, stopped when dropped
+#[cfg(feature = "mock-server")]
+struct FixtureServer {
+    child: std::process::Child,
+    // Kept open so the server can go on printing
+    _stdout: std::io::BufReader<std::process::ChildStdout>,
+    url: String,
+}
+
+#[cfg(feature = "mock-server")]
+impl FixtureServer {
+    fn start() -> Self {
+        use std::io::{BufRead, BufReader};
+        use std::process::Stdio;
+
+        let mut child = std::process::Command::new(assert_cmd::cargo::cargo_bin("stars-fetcher-mock"))
+            .args(["--port", "0"])
+            .stdout(Stdio::piped())
+            .spawn()
+            .unwrap();
+        let mut stdout = BufReader::new(child.stdout.take().unwrap());
+        let mut line = String::new();
+        stdout.read_line(&mut line).unwrap();
+        let url = line.trim().rsplit(' ').next().unwrap().to_string();
+        FixtureServer { child, _stdout: stdout, url }
+    }
+}
+
+#[cfg(feature = "mock-server")]
+impl Drop for FixtureServer {
+    fn drop(&mut self) {
+        self.child.kill().ok();
+        self.child.wait().ok();
+    }
+}
+
+#[cfg(feature = "mock-server")]
+#[test]
+fn test_fixture_server_end_to_end() {
+    let server = FixtureServer::start();
+    let home = TempDir::new().unwrap();
+
+    cli_at(&server.url, &home, "fixture-token")
+        .args(["star", "tokio-rs/tokio"])
+        .assert()
+        .success();
+    cli_at(&server.url, &home, "fixture-token")
+        .args(["list", "--format", "json"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("tokio-rs/tokio"))
+        .stdout(predicate::str::contains("octocat/Hello-World"));
+    cli_at(&server.url, &home, "fixture-token")
+        .args(["releases", "download", "octocat/Hello-World", "--asset", "linux", "-o"])
+        .arg(home.path())
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("Verified SHA-256 of hello-linux against SHA256SUMS"));
+
+    assert_eq!(fs::read_to_string(home.path().join("hello-linux")).unwrap(), "hello-linux v1.1.0\n");
+}